@@ -61,7 +61,7 @@ struct Cli {
 
     // Active DEX platforms flag (comma-separated list of DEXes to use)
     #[arg(long = "active-dexes", value_name = "DEX_PLATFORMS",
-          help = "Comma-separated list of DEX platforms to use. Available options: orca, raydium, raydium-cpmm, raydium-clmm",
+          help = "Comma-separated list of DEX platforms to use. Available options: orca, raydium, raydium-cpmm, raydium-clmm, meteora-dlmm",
           value_delimiter = ',')]
     active_dexes: Option<Vec<String>>,
 
@@ -75,6 +75,10 @@ struct Cli {
     // Transaction simulation flag
     #[arg(long, help = "Simulate transactions instead of submitting them to the network")]
     simulate: bool,
+
+    // Mock mode flag (needs the mock feature)
+    #[arg(long, help = "Run on synthetic pools with mock RPC providers, without a network (needs the mock feature)")]
+    mock: bool,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -267,6 +271,7 @@ fn init_config() -> Result<ClientConfig> {
         blockchain: Some(blockchain.clone()),
         router: Some(router.clone()),
         simulate: cli.simulate,
+        mock: cli.mock,
     };
 
     Ok(ClientConfig {
@@ -347,8 +352,13 @@ fn init_metrics() -> SdkMeterProvider {
         .build()
         .expect("Failed to create metric exporter");
 
+    // Bridge the same meters into the Prometheus registry served by qtrade-runtime
+    let prometheus_reader = qtrade_runtime::metrics_server::prometheus_reader()
+        .expect("Failed to create Prometheus metric reader");
+
     SdkMeterProvider::builder()
         .with_periodic_exporter(exporter)// Defaults to 60 seconds
+        .with_reader(prometheus_reader)
         .with_resource(RESOURCE.clone())
         .build()
 }
@@ -32,5 +32,7 @@ pub const STATE_SEED: &[u8] = b"state";
 pub const REFERRAL_SEED: &[u8] = b"referral";
 
 /// Account space calculations for rent-exempt storage
-pub const PROGRAM_STATE_SIZE: usize = 8 + 32 + 2 + 1; // discriminator + owner + swap_multi_fee + bump
+pub const PROGRAM_STATE_SIZE: usize = 8 + 32 + 2 + 1 + 1; // discriminator + owner + swap_multi_fee + bump + paused
+/// Size of state accounts initialized before `paused` was added; `migrate_state` grows them
+pub const LEGACY_PROGRAM_STATE_SIZE: usize = 8 + 32 + 2 + 1; // discriminator + owner + swap_multi_fee + bump
 pub const REFERRAL_INFO_SIZE: usize = 8 + 2 + 32 + 1 + 1; // discriminator + referral_fee + beneficiary + registered + bump
@@ -2,6 +2,8 @@
 // This version reduces stack usage to avoid BPF stack overflow
 
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
 use anchor_spl::token::{self, Token, TokenAccount};
 
 mod constants;
@@ -20,6 +22,38 @@ pub mod qtrade_executor {
         state.owner = ctx.accounts.owner.key();
         state.swap_multi_fee = DEFAULT_SWAP_MULTI_FEE;
         state.bump = ctx.bumps.state;
+        state.paused = false;
+        Ok(())
+    }
+
+    /// Grows a state account initialized before `paused` existed to the current layout (owner-only)
+    /// `paused` is appended after `bump`, so the old fields keep their offsets and the
+    /// zeroed new byte leaves the program unpaused. Run once after upgrading the program.
+    pub fn migrate_state(ctx: Context<MigrateState>) -> Result<()> {
+        let state = ctx.accounts.state.to_account_info();
+        require!(state.data_len() == LEGACY_PROGRAM_STATE_SIZE, ErrorCode::StateAlreadyMigrated);
+
+        {
+            let data = state.try_borrow_data()?;
+            require!(data[..8] == *ProgramState::DISCRIMINATOR, ErrorCode::InvalidStateAccount);
+            let owner = Pubkey::try_from(&data[8..40]).map_err(|_| ErrorCode::InvalidStateAccount)?;
+            require_keys_eq!(owner, ctx.accounts.owner.key(), ErrorCode::Unauthorized);
+        }
+
+        // Keep the larger account rent exempt
+        let rent_exempt = Rent::get()?.minimum_balance(PROGRAM_STATE_SIZE);
+        let top_up = rent_exempt.saturating_sub(state.lamports());
+        if top_up > 0 {
+            let cpi_accounts = system_program::Transfer {
+                from: ctx.accounts.owner.to_account_info(),
+                to: state.clone(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+            system_program::transfer(cpi_ctx, top_up)?;
+        }
+
+        state.resize(PROGRAM_STATE_SIZE)?;
+        state.try_borrow_mut_data()?[LEGACY_PROGRAM_STATE_SIZE..].fill(0);
         Ok(())
     }
 
@@ -54,6 +88,19 @@ pub mod qtrade_executor {
         Ok(())
     }
 
+    /// Emergency pause switch (owner-only)
+    /// Halts `swap` and `swap_multi` until the owner unpauses the program
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        ctx.accounts.state.paused = paused;
+
+        emit!(PausedEvent {
+            owner: ctx.accounts.owner.key(),
+            paused,
+        });
+
+        Ok(())
+    }
+
     /// Complete swap function mapping OdosRouterV2.swap() to Solana DEX integration
     /// Equivalent to: OdosRouterV2.swap(SwapTokenInfo calldata tokenInfo, bytes calldata pathDefinition, address executor, uint32 referralCode)
     pub fn swap(
@@ -63,6 +110,8 @@ pub mod qtrade_executor {
         output_quote: u64,
         referral_code: u32,
     ) -> Result<()> {
+        require!(!ctx.accounts.state.paused, ErrorCode::ProgramPaused);
+
         // Validation equivalent to OdosRouterV2 require statements
         require!(input_amount > 0, ErrorCode::InvalidFundsTransfer);
         require!(output_min <= output_quote, ErrorCode::MinimumGreaterThanQuote);
@@ -73,8 +122,11 @@ pub mod qtrade_executor {
             // Validate referral info exists for fee-bearing codes
             require!(!ctx.remaining_accounts.is_empty(), ErrorCode::ReferralInfoMissing);
 
+            // Charge the fee registered for this code (equivalent to referralLookup[referralCode].referralFee)
+            let referral_fee = registered_referral_fee(ctx.program_id, &ctx.remaining_accounts[0], referral_code)?;
+
             // Calculate referral fee using basis points (simplified from OdosRouterV2's complex calculation)
-            utils::calculate_referral_fee(output_quote, 250, FEE_DENOMINATOR)? // Example: 2.5% fee
+            utils::calculate_referral_fee(output_quote, referral_fee, FEE_DENOMINATOR)?
         } else {
             0
         };
@@ -134,13 +186,34 @@ pub mod qtrade_executor {
         require!(amount > 0, ErrorCode::InvalidFundsTransfer);
         require!(recipient != Pubkey::default(), ErrorCode::NullBeneficiary);
 
-        // TODO: Implement actual token transfer from router accounts to recipient
-        // This would involve:
-        // 1. Finding the router's token account for the specified mint
-        // 2. Creating CPI call to transfer tokens using PDA authority
-        // 3. Validating sufficient balance exists
+        // Router token account must hold the requested mint
+        let router_token_account = &ctx.accounts.router_token_account;
+        require!(router_token_account.mint == token_mint, ErrorCode::InvalidTokenMint);
+        require!(
+            ctx.accounts.recipient_token_account.mint == token_mint,
+            ErrorCode::InvalidTokenMint
+        );
+        require!(
+            ctx.accounts.recipient_token_account.owner == recipient,
+            ErrorCode::InvalidRecipient
+        );
+
+        // Validate sufficient balance exists
+        require!(router_token_account.amount >= amount, ErrorCode::InsufficientBalance);
+
+        // Transfer tokens from the router account to the recipient using the state PDA as authority
+        let state_bump = ctx.accounts.state.bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[STATE_SEED, &[state_bump]]];
+        let cpi_accounts = anchor_spl::token::Transfer {
+            from: ctx.accounts.router_token_account.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: ctx.accounts.state.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token::transfer(cpi_ctx, amount)?;
 
-        // For now, emit an event to track the transfer request
+        // Emit an event to track the transfer
         emit!(TransferRouterFundsEvent {
             owner: ctx.accounts.owner.key(),
             token_mint,
@@ -157,6 +230,8 @@ pub mod qtrade_executor {
         ctx: Context<SwapMulti>,
         referral_code: u32,
     ) -> Result<()> {
+        require!(!ctx.accounts.state.paused, ErrorCode::ProgramPaused);
+
         // Apply swap multi fee (equivalent to OdosRouterV2 swapMultiFee logic)
         let multi_fee = ctx.accounts.state.swap_multi_fee;
 
@@ -181,12 +256,37 @@ pub mod qtrade_executor {
     }
 }
 
+/// Read the fee registered for a fee-bearing referral code from its ReferralInfo account
+/// The account must be the program-owned PDA for `referral_code` and hold a registered code;
+/// the fee is clamped to MAX_REFERRAL_FEE so a bad account can never overcharge the user
+fn registered_referral_fee(
+    program_id: &Pubkey,
+    referral_account: &AccountInfo,
+    referral_code: u32,
+) -> Result<u16> {
+    require_keys_eq!(*referral_account.owner, *program_id, ErrorCode::ReferralInfoMismatch);
+
+    let referral_info = ReferralInfo::try_deserialize(&mut referral_account.data.borrow().as_ref())?;
+
+    let expected = Pubkey::create_program_address(
+        &[REFERRAL_SEED, referral_code.to_le_bytes().as_ref(), &[referral_info.bump]],
+        program_id,
+    )
+    .map_err(|_| error!(ErrorCode::ReferralInfoMismatch))?;
+    require_keys_eq!(referral_account.key(), expected, ErrorCode::ReferralInfoMismatch);
+    require!(referral_info.registered, ErrorCode::ReferralNotRegistered);
+
+    Ok(referral_info.referral_fee.min(MAX_REFERRAL_FEE))
+}
+
 // Simplified account structures
 #[account]
 pub struct ProgramState {
     pub owner: Pubkey,
     pub swap_multi_fee: u16,
     pub bump: u8,
+    // After `bump` so state accounts created before it can be migrated in place
+    pub paused: bool,
 }
 
 #[account]
@@ -206,7 +306,7 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = owner,
-        space = 8 + 32 + 2 + 1, // Discriminator + pubkey + u16 + u8
+        space = PROGRAM_STATE_SIZE, // Discriminator + pubkey + u16 + u8 + bool
         seeds = [STATE_SEED],
         bump
     )]
@@ -215,6 +315,24 @@ pub struct Initialize<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    /// CHECK: still in the legacy layout, so it can't be deserialized as ProgramState;
+    /// its discriminator and owner are checked in `migrate_state`
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        owner = crate::ID
+    )]
+    pub state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 #[instruction(referral_code: u32)]
 pub struct RegisterReferralCode<'info> {
@@ -252,6 +370,19 @@ pub struct SetSwapMultiFee<'info> {
     pub owner: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = owner
+    )]
+    pub state: Box<Account<'info, ProgramState>>, // Box to move to heap
+
+    pub owner: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct Swap<'info> {
     #[account(mut)]
@@ -284,8 +415,16 @@ pub struct TransferRouterFunds<'info> {
 
     pub owner: Signer<'info>,
 
-    // TODO: Add token accounts for the specific token being transferred
-    // This would include router's token account and recipient's token account
+    /// Router's token account for the mint being recovered, owned by the state PDA
+    #[account(
+        mut,
+        constraint = router_token_account.owner == state.key() @ ErrorCode::InvalidTokenAccountOwner
+    )]
+    pub router_token_account: Box<Account<'info, TokenAccount>>, // Box to move to heap
+
+    /// Recipient's token account for the same mint
+    #[account(mut)]
+    pub recipient_token_account: Box<Account<'info, TokenAccount>>, // Box to move to heap
 
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
@@ -341,6 +480,13 @@ pub struct TransferRouterFundsEvent {
     pub recipient: Pubkey,
 }
 
+/// Emitted when the owner pauses or unpauses the program
+#[event]
+pub struct PausedEvent {
+    pub owner: Pubkey,
+    pub paused: bool,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Fee too high")]
@@ -361,4 +507,70 @@ pub enum ErrorCode {
     ReferralInfoMissing,
     #[msg("Arithmetic overflow")]
     ArithmeticOverflow,
+    #[msg("Insufficient balance")]
+    InsufficientBalance,
+    #[msg("Token account mint does not match")]
+    InvalidTokenMint,
+    #[msg("Token account not owned by router")]
+    InvalidTokenAccountOwner,
+    #[msg("Recipient token account owner mismatch")]
+    InvalidRecipient,
+    #[msg("Program is paused")]
+    ProgramPaused,
+    #[msg("Referral info account does not match referral code")]
+    ReferralInfoMismatch,
+    #[msg("Referral code not registered")]
+    ReferralNotRegistered,
+    #[msg("Program state already uses the current layout")]
+    StateAlreadyMigrated,
+    #[msg("Account is not the program state")]
+    InvalidStateAccount,
+    #[msg("Signer is not the program owner")]
+    Unauthorized,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy_state_data(owner: Pubkey, swap_multi_fee: u16, bump: u8) -> Vec<u8> {
+        let mut data = ProgramState::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(owner.as_ref());
+        data.extend_from_slice(&swap_multi_fee.to_le_bytes());
+        data.push(bump);
+        data
+    }
+
+    #[test]
+    fn test_migrated_legacy_state_keeps_its_fields() {
+        let owner = Pubkey::new_unique();
+        let mut data = legacy_state_data(owner, 7, 254);
+        assert_eq!(data.len(), LEGACY_PROGRAM_STATE_SIZE);
+
+        // What `migrate_state` leaves behind: the old bytes followed by a zeroed `paused`
+        data.resize(PROGRAM_STATE_SIZE, 0);
+        let state = ProgramState::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(state.owner, owner);
+        assert_eq!(state.swap_multi_fee, 7);
+        assert_eq!(state.bump, 254);
+        assert!(!state.paused);
+    }
+
+    #[test]
+    fn test_unmigrated_legacy_state_does_not_deserialize() {
+        let data = legacy_state_data(Pubkey::new_unique(), 7, 254);
+        assert!(ProgramState::try_deserialize(&mut data.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_state_size_matches_layout() {
+        let state = ProgramState { owner: Pubkey::new_unique(), swap_multi_fee: 5, bump: 1, paused: true };
+        let mut data = Vec::new();
+        state.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), PROGRAM_STATE_SIZE);
+
+        // The legacy fields keep their offsets, with `paused` appended
+        assert_eq!(data[..LEGACY_PROGRAM_STATE_SIZE], legacy_state_data(state.owner, 5, 1)[..]);
+        assert_eq!(data[LEGACY_PROGRAM_STATE_SIZE..], [1]);
+    }
 }
@@ -1,16 +1,336 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { QtradeExecutor } from "../target/types/qtrade_executor";
+import {
+  createAccount,
+  createMint,
+  getAccount,
+  mintTo,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import { assert } from "chai";
 
 describe("qtrade-executor", () => {
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.qtradeExecutor as Program<QtradeExecutor>;
+  const owner = (provider.wallet as anchor.Wallet).payer;
+
+  const [statePda] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("state")],
+    program.programId
+  );
 
   it("Is initialized!", async () => {
     // Add your test here.
     const tx = await program.methods.initialize().rpc();
     console.log("Your transaction signature", tx);
   });
+
+  it("refuses to migrate state that already has the current layout", async () => {
+    try {
+      await program.methods.migrateState().accounts({ owner: owner.publicKey }).rpc();
+      assert.fail("expected StateAlreadyMigrated");
+    } catch (err) {
+      assert.include(err.toString(), "StateAlreadyMigrated");
+    }
+
+    const state = await program.account.programState.fetch(statePda);
+    assert.isTrue(state.owner.equals(owner.publicKey));
+    assert.isFalse(state.paused);
+  });
+
+  describe("transfer_router_funds", () => {
+    const recipient = anchor.web3.Keypair.generate();
+    let mint: anchor.web3.PublicKey;
+    let routerTokenAccount: anchor.web3.PublicKey;
+    let recipientTokenAccount: anchor.web3.PublicKey;
+
+    before(async () => {
+      mint = await createMint(provider.connection, owner, owner.publicKey, null, 6);
+
+      // Router token account is owned by the state PDA
+      routerTokenAccount = await createAccount(
+        provider.connection,
+        owner,
+        mint,
+        statePda,
+        anchor.web3.Keypair.generate()
+      );
+      recipientTokenAccount = await createAccount(
+        provider.connection,
+        owner,
+        mint,
+        recipient.publicKey
+      );
+
+      await mintTo(provider.connection, owner, mint, routerTokenAccount, owner, 1_000_000);
+    });
+
+    it("transfers tokens from the router to the recipient", async () => {
+      await program.methods
+        .transferRouterFunds(mint, new anchor.BN(400_000), recipient.publicKey)
+        .accounts({
+          owner: owner.publicKey,
+          routerTokenAccount,
+          recipientTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const routerAccount = await getAccount(provider.connection, routerTokenAccount);
+      const recipientAccount = await getAccount(provider.connection, recipientTokenAccount);
+      assert.equal(routerAccount.amount.toString(), "600000");
+      assert.equal(recipientAccount.amount.toString(), "400000");
+    });
+
+    it("rejects transfers exceeding the router balance", async () => {
+      try {
+        await program.methods
+          .transferRouterFunds(mint, new anchor.BN(10_000_000), recipient.publicKey)
+          .accounts({
+            owner: owner.publicKey,
+            routerTokenAccount,
+            recipientTokenAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+        assert.fail("expected InsufficientBalance");
+      } catch (err) {
+        assert.include(err.toString(), "InsufficientBalance");
+      }
+    });
+
+    it("rejects a non-owner caller", async () => {
+      const attacker = anchor.web3.Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(
+        attacker.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(sig);
+
+      try {
+        await program.methods
+          .transferRouterFunds(mint, new anchor.BN(1), recipient.publicKey)
+          .accounts({
+            owner: attacker.publicKey,
+            routerTokenAccount,
+            recipientTokenAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([attacker])
+          .rpc();
+        assert.fail("expected has_one owner constraint to fail");
+      } catch (err) {
+        assert.include(err.toString(), "ConstraintHasOne");
+      }
+    });
+  });
+
+  describe("swap", () => {
+    // The relayer's route_through_executor relies on swap settling the output, which it
+    // doesn't yet, so the relayer refuses that setting until this test changes
+    it("keeps the input in the router without returning any output", async () => {
+      const inputMint = await createMint(provider.connection, owner, owner.publicKey, null, 6);
+      const outputMint = await createMint(provider.connection, owner, owner.publicKey, null, 6);
+      const userInputAccount = await createAccount(
+        provider.connection,
+        owner,
+        inputMint,
+        owner.publicKey
+      );
+      const userOutputAccount = await createAccount(
+        provider.connection,
+        owner,
+        outputMint,
+        owner.publicKey
+      );
+      const routerInputAccount = await createAccount(
+        provider.connection,
+        owner,
+        inputMint,
+        statePda,
+        anchor.web3.Keypair.generate()
+      );
+      await mintTo(provider.connection, owner, inputMint, userInputAccount, owner, 1_000);
+
+      await program.methods
+        .swap(new anchor.BN(100), new anchor.BN(90), new anchor.BN(100), 0)
+        .accounts({
+          user: owner.publicKey,
+          userInputAccount,
+          routerInputAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const userInput = await getAccount(provider.connection, userInputAccount);
+      const routerInput = await getAccount(provider.connection, routerInputAccount);
+      const userOutput = await getAccount(provider.connection, userOutputAccount);
+      assert.equal(userInput.amount.toString(), "900");
+      assert.equal(routerInput.amount.toString(), "100");
+      assert.equal(userOutput.amount.toString(), "0");
+    });
+  });
+
+  describe("emergency pause", () => {
+    const swapMulti = () =>
+      program.methods
+        .swapMulti(0)
+        .accounts({ user: owner.publicKey })
+        .rpc();
+
+    after(async () => {
+      await program.methods.setPaused(false).accounts({ owner: owner.publicKey }).rpc();
+    });
+
+    it("rejects swaps while paused", async () => {
+      await program.methods.setPaused(true).accounts({ owner: owner.publicKey }).rpc();
+
+      const state = await program.account.programState.fetch(statePda);
+      assert.isTrue(state.paused);
+
+      const mint = await createMint(provider.connection, owner, owner.publicKey, null, 6);
+      const userInputAccount = await createAccount(
+        provider.connection,
+        owner,
+        mint,
+        owner.publicKey
+      );
+      const routerInputAccount = await createAccount(
+        provider.connection,
+        owner,
+        mint,
+        statePda,
+        anchor.web3.Keypair.generate()
+      );
+      await mintTo(provider.connection, owner, mint, userInputAccount, owner, 1_000);
+
+      try {
+        await program.methods
+          .swap(new anchor.BN(100), new anchor.BN(90), new anchor.BN(100), 0)
+          .accounts({
+            user: owner.publicKey,
+            userInputAccount,
+            routerInputAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+        assert.fail("expected ProgramPaused");
+      } catch (err) {
+        assert.include(err.toString(), "ProgramPaused");
+      }
+
+      try {
+        await swapMulti();
+        assert.fail("expected ProgramPaused");
+      } catch (err) {
+        assert.include(err.toString(), "ProgramPaused");
+      }
+    });
+
+    it("allows swaps after unpausing", async () => {
+      await program.methods.setPaused(false).accounts({ owner: owner.publicKey }).rpc();
+
+      const state = await program.account.programState.fetch(statePda);
+      assert.isFalse(state.paused);
+
+      await swapMulti();
+    });
+
+    it("rejects a non-owner pausing the program", async () => {
+      const attacker = anchor.web3.Keypair.generate();
+      try {
+        await program.methods
+          .setPaused(true)
+          .accounts({ owner: attacker.publicKey })
+          .signers([attacker])
+          .rpc();
+        assert.fail("expected has_one owner constraint to fail");
+      } catch (err) {
+        assert.include(err.toString(), "ConstraintHasOne");
+      }
+    });
+  });
+
+  describe("referral fees", () => {
+    const REFERRAL_WITH_FEE_THRESHOLD = 2 ** 31;
+    const referralCode = REFERRAL_WITH_FEE_THRESHOLD + 1;
+    const otherReferralCode = REFERRAL_WITH_FEE_THRESHOLD + 2;
+    const beneficiary = anchor.web3.Keypair.generate().publicKey;
+    const eventParser = new anchor.EventParser(program.programId, program.coder);
+
+    let userInputAccount: anchor.web3.PublicKey;
+    let routerInputAccount: anchor.web3.PublicKey;
+
+    const referralPda = (code: number) => {
+      const codeBytes = Buffer.alloc(4);
+      codeBytes.writeUInt32LE(code);
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("referral"), codeBytes],
+        program.programId
+      )[0];
+    };
+
+    const swapWithReferral = (code: number, referralInfo: anchor.web3.PublicKey) =>
+      program.methods
+        .swap(new anchor.BN(100), new anchor.BN(9_000), new anchor.BN(10_000), code)
+        .accounts({
+          user: owner.publicKey,
+          userInputAccount,
+          routerInputAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts([{ pubkey: referralInfo, isSigner: false, isWritable: false }])
+        .rpc({ commitment: "confirmed" });
+
+    before(async () => {
+      await program.methods
+        .registerReferralCode(referralCode, 100, beneficiary)
+        .accounts({ payer: owner.publicKey })
+        .rpc();
+      await program.methods
+        .registerReferralCode(otherReferralCode, 150, beneficiary)
+        .accounts({ payer: owner.publicKey })
+        .rpc();
+
+      const mint = await createMint(provider.connection, owner, owner.publicKey, null, 6);
+      userInputAccount = await createAccount(provider.connection, owner, mint, owner.publicKey);
+      routerInputAccount = await createAccount(
+        provider.connection,
+        owner,
+        mint,
+        statePda,
+        anchor.web3.Keypair.generate()
+      );
+      await mintTo(provider.connection, owner, mint, userInputAccount, owner, 1_000);
+    });
+
+    it("deducts the registered referral fee", async () => {
+      const referralInfo = await program.account.referralInfo.fetch(referralPda(referralCode));
+      assert.equal(referralInfo.referralFee, 100);
+
+      const sig = await swapWithReferral(referralCode, referralPda(referralCode));
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const events = [...eventParser.parseLogs(tx.meta.logMessages)];
+      const swapEvent = events.find((e) => e.name === "swapEvent");
+
+      // 100 bps of the 10_000 quote
+      assert.equal(swapEvent.data.amountOut.toString(), "9900");
+    });
+
+    it("rejects a referral account for a different code", async () => {
+      try {
+        await swapWithReferral(referralCode, referralPda(otherReferralCode));
+        assert.fail("expected ReferralInfoMismatch");
+      } catch (err) {
+        assert.include(err.toString(), "ReferralInfoMismatch");
+      }
+    });
+  });
 });
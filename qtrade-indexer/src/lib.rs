@@ -8,6 +8,7 @@ use std::sync::Arc;
 use crate::streamer::MintCache;
 use crate::streamer::PoolCache;
 use crate::streamer::PoolConfigCache;
+use crate::streamer::PriceCache;
 
 pub mod parser;
 pub mod settings;
@@ -53,6 +54,9 @@ pub static POOL_CACHE: Lazy<Arc<PoolCache>> = Lazy::new(|| {
 pub static POOL_CONFIG_CACHE: Lazy<Arc<PoolConfigCache>> = Lazy::new(|| {
     Arc::new(PoolConfigCache::new())
 });
+pub static PRICE_CACHE: Lazy<Arc<PriceCache>> = Lazy::new(|| {
+    Arc::new(PriceCache::new())
+});
 
 
 
@@ -0,0 +1,256 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+// qtrade: spl_pod changed namespacing from 0.3.0 to 0.50
+use spl_pod::solana_pubkey::Pubkey;
+use qtrade_shared_types::PoolFee;
+
+// The DLMM program stores its accounts zero-copy (repr(C) without implicit padding),
+// so deserializing the fields in declaration order with borsh reads the same bytes.
+
+// https://github.com/MeteoraAg/dlmm-sdk/blob/main/programs/lb_clmm/src/constants.rs
+pub const MAX_BIN_PER_ARRAY: usize = 70;
+pub const NUM_REWARDS: usize = 2;
+/// Fee rates are fractions of 10^9
+pub const FEE_PRECISION: u64 = 1_000_000_000;
+/// Fees are capped at 10%
+pub const MAX_FEE_RATE: u64 = 100_000_000;
+
+// https://github.com/MeteoraAg/dlmm-sdk/blob/main/programs/lb_clmm/src/state/parameters.rs
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct StaticParameters {
+    /// Used for base fee calculation. base_fee_rate = base_factor * bin_step * 10 * 10^base_fee_power_factor
+    pub base_factor: u16,
+    /// Filter period determine high frequency trading time window.
+    pub filter_period: u16,
+    /// Decay period determine when the volatile fee start decay / decrease.
+    pub decay_period: u16,
+    /// Reduction factor controls the volatile fee rate decrement rate.
+    pub reduction_factor: u16,
+    /// Used to scale the variable fee component depending on the dynamic of the market
+    pub variable_fee_control: u32,
+    /// Maximum number of bin crossed can be accumulated. Used to cap volatile fee rate.
+    pub max_volatility_accumulator: u32,
+    /// Min bin id supported by the pool based on the configured bin step.
+    pub min_bin_id: i32,
+    /// Max bin id supported by the pool based on the configured bin step.
+    pub max_bin_id: i32,
+    /// Portion of swap fees retained by the protocol by controlling protocol_share parameter. protocol_swap_fee = protocol_share * total_swap_fee
+    pub protocol_share: u16,
+    /// Base fee power factor
+    pub base_fee_power_factor: u8,
+    /// Padding for bytemuck safe alignment
+    pub padding: [u8; 5],
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct VariableParameters {
+    /// Volatility accumulator measure the number of bin crossed since reference bin ID. Normally (without filter period taken into consideration), reference bin ID is the active bin of last swap.
+    /// It affects the variable fee rate
+    pub volatility_accumulator: u32,
+    /// Volatility reference is decayed volatility accumulator. It is always <= volatility_accumulator
+    pub volatility_reference: u32,
+    /// Active bin id of last swap.
+    pub index_reference: i32,
+    /// Padding for bytemuck safe alignment
+    pub padding: [u8; 4],
+    /// Last timestamp the variable parameters was updated
+    pub last_update_timestamp: i64,
+    /// Padding for bytemuck safe alignment
+    pub padding_1: [u8; 8],
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct ProtocolFee {
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct RewardInfo {
+    /// Reward token mint.
+    pub mint: Pubkey,
+    /// Reward vault token account.
+    pub vault: Pubkey,
+    /// Authority account that allows to fund rewards
+    pub funder: Pubkey,
+    /// TODO check whether we need to store it in pool
+    pub reward_duration: u64,
+    /// TODO check whether we need to store it in pool
+    pub reward_duration_end: u64,
+    /// TODO check whether we need to store it in pool
+    pub reward_rate: u128,
+    /// The last time reward states were updated.
+    pub last_update_time: u64,
+    /// Accumulated seconds where when farm distribute rewards, but the bin is empty. The reward will be accumulated for next reward time window.
+    pub cumulative_seconds_with_empty_liquidity_reward: u64,
+}
+
+// https://github.com/MeteoraAg/dlmm-sdk/blob/main/programs/lb_clmm/src/state/lb_pair/mod.rs
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct LbPair {
+    pub parameters: StaticParameters,
+    pub v_parameters: VariableParameters,
+    pub bump_seed: [u8; 1],
+    /// Bin step signer seed
+    pub bin_step_seed: [u8; 2],
+    /// Type of the pair
+    pub pair_type: u8,
+    /// Active bin id
+    pub active_id: i32,
+    /// Bin step. Represent the price increment / decrement.
+    pub bin_step: u16,
+    /// Status of the pair. Check PairStatus enum.
+    pub status: u8,
+    /// Require base factor seed
+    pub require_base_factor_seed: u8,
+    /// Base factor seed
+    pub base_factor_seed: [u8; 2],
+    /// Activation type
+    pub activation_type: u8,
+    /// Allow pool creator to enable/disable pool with restricted validation. Only applicable for customizable permissionless pair type.
+    pub creator_pool_on_off_control: u8,
+    /// Token X mint
+    pub token_x_mint: Pubkey,
+    /// Token Y mint
+    pub token_y_mint: Pubkey,
+    /// LB token X vault
+    pub reserve_x: Pubkey,
+    /// LB token Y vault
+    pub reserve_y: Pubkey,
+    /// Uncollected protocol fee
+    pub protocol_fee: ProtocolFee,
+    /// _padding_1, previous Fee owner, BE CAREFUL FOR TOMBSTONE WHEN REUSE !!
+    pub padding_1: [u8; 32],
+    /// Farming reward information
+    pub reward_infos: [RewardInfo; NUM_REWARDS],
+    /// Oracle pubkey
+    pub oracle: Pubkey,
+    /// Packed initialized bin array state
+    pub bin_array_bitmap: [u64; 16],
+    /// Last time the pool fee parameter was updated
+    pub last_updated_at: i64,
+    /// _padding_2, previous whitelisted_wallet, BE CAREFUL FOR TOMBSTONE WHEN REUSE !!
+    pub padding_2: [u8; 32],
+    /// Address allowed to swap when the current point is greater than or equal to the pre-activation point. The pre-activation point is calculated as `activation_point - pre_activation_duration`.
+    pub pre_activation_swap_address: Pubkey,
+    /// Base keypair. Only required for permission pair
+    pub base_key: Pubkey,
+    /// Time point to enable the pair. Only applicable for permission pair.
+    pub activation_point: u64,
+    /// Duration before activation activation_point. Used to calculate pre-activation time point for pre_activation_swap_address
+    pub pre_activation_duration: u64,
+    /// _padding 3 is reclaimed free space from swap_cap_deactivate_point and swap_cap_amount before, BE CAREFUL FOR TOMBSTONE WHEN REUSE !!
+    pub padding_3: [u8; 8],
+    /// _padding_4, previous lock_duration, BE CAREFUL FOR TOMBSTONE WHEN REUSE !!
+    pub padding_4: u64,
+    /// Pool creator
+    pub creator: Pubkey,
+    /// token_mint_x_program_flag
+    pub token_mint_x_program_flag: u8,
+    /// token_mint_y_program_flag
+    pub token_mint_y_program_flag: u8,
+    /// Reserved space for future use
+    pub reserved: [u8; 22],
+}
+
+impl LbPair {
+    pub const LEN: usize = 8 + 32 * 2 + 16 + 32 * 4 + 16 + 32 + RewardInfo::LEN * NUM_REWARDS + 32 + 8 * 16 + 8 + 32 + 32 * 2 + 8 * 4 + 32 + 1 * 2 + 22;
+
+    /// Base fee rate over [`FEE_PRECISION`]
+    pub fn base_fee_rate(&self) -> u128 {
+        u128::from(self.parameters.base_factor)
+            * u128::from(self.bin_step)
+            * 10
+            * 10u128.pow(self.parameters.base_fee_power_factor.into())
+    }
+
+    /// Variable (volatility) fee rate over [`FEE_PRECISION`], rounded up
+    pub fn variable_fee_rate(&self) -> u128 {
+        let variable_fee_control = u128::from(self.parameters.variable_fee_control);
+        if variable_fee_control == 0 {
+            return 0;
+        }
+        let square_vfa_bin = (u128::from(self.v_parameters.volatility_accumulator) * u128::from(self.bin_step)).pow(2);
+        (variable_fee_control * square_vfa_bin).div_ceil(100_000_000_000)
+    }
+
+    /// Total trade fee charged by the pair, capped at [`MAX_FEE_RATE`]
+    pub fn pool_fee(&self) -> PoolFee {
+        let total_fee_rate = self.base_fee_rate()
+            .saturating_add(self.variable_fee_rate())
+            .min(MAX_FEE_RATE as u128);
+        PoolFee::new(total_fee_rate as u64, FEE_PRECISION)
+    }
+}
+
+impl RewardInfo {
+    pub const LEN: usize = 3 * 32 + 8 + 8 + 16 + 8 + 8;
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct KeyedLbPair {
+    pub pubkey: Pubkey,
+    pub lb_pair: LbPair,
+}
+
+// https://github.com/MeteoraAg/dlmm-sdk/blob/main/programs/lb_clmm/src/state/bin.rs
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct Bin {
+    /// Amount of token X in the bin. This already excluded protocol fees.
+    pub amount_x: u64,
+    /// Amount of token Y in the bin. This already excluded protocol fees.
+    pub amount_y: u64,
+    /// Bin price
+    pub price: u128,
+    /// Liquidities of the bin. This is the same as LP mint supply. q-number
+    pub liquidity_supply: u128,
+    /// reward_a_per_token_stored
+    pub reward_per_token_stored: [u128; NUM_REWARDS],
+    /// Swap fee amount of token X per liquidity deposited.
+    pub fee_amount_x_per_token_stored: u128,
+    /// Swap fee amount of token Y per liquidity deposited.
+    pub fee_amount_y_per_token_stored: u128,
+    /// Total token X swap into the bin. Only used for tracking purpose.
+    pub amount_x_in: u128,
+    /// Total token Y swap into he bin. Only used for tracking purpose.
+    pub amount_y_in: u128,
+}
+
+impl Bin {
+    pub const LEN: usize = 8 + 8 + 16 + 16 + 16 * NUM_REWARDS + 16 * 4;
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct BinArray {
+    pub index: i64,
+    /// Version of binArray
+    pub version: u8,
+    pub padding: [u8; 7],
+    pub lb_pair: Pubkey,
+    pub bins: [Bin; MAX_BIN_PER_ARRAY],
+}
+
+impl BinArray {
+    pub const LEN: usize = 8 + 8 + 1 + 7 + 32 + Bin::LEN * MAX_BIN_PER_ARRAY;
+
+    /// Id of the first bin in the array
+    pub fn lower_bin_id(&self) -> i64 {
+        self.index * MAX_BIN_PER_ARRAY as i64
+    }
+
+    /// The bin with `bin_id`, if it falls in this array
+    pub fn bin(&self, bin_id: i32) -> Option<&Bin> {
+        let offset = i64::from(bin_id) - self.lower_bin_id();
+        usize::try_from(offset).ok().and_then(|offset| self.bins.get(offset))
+    }
+}
+
+/// Index of the bin array holding `bin_id` (arrays hold [`MAX_BIN_PER_ARRAY`] bins each)
+pub fn bin_id_to_bin_array_index(bin_id: i32) -> i64 {
+    i64::from(bin_id).div_euclid(MAX_BIN_PER_ARRAY as i64)
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct KeyedBinArray {
+    pub pubkey: Pubkey,
+    pub bin_array: BinArray,
+}
@@ -0,0 +1,107 @@
+use borsh::BorshDeserialize;
+// qtrade: spl_pod changed namespacing from 0.3.0 to 0.50
+use spl_pod::solana_program_error::ProgramError;
+use yellowstone_vixen_core::{ParseError, ParseResult, Parser, Prefilter, ProgramParser};
+use opentelemetry::global;
+use opentelemetry::trace::Tracer;
+
+// qtrade: from account_helpers.rs
+use spl_pod::solana_pubkey::Pubkey;
+
+use super::account_helpers::{BinArray, KeyedBinArray, KeyedLbPair, LbPair};
+use crate::parser::{helpers::ACC_DISCRIMINATOR_SIZE, meteora_dlmm::METEORA_DLMM_PROGRAM_ID};
+
+// For help in naming spans
+use crate::QTRADE_INDEXER_TRACER_NAME;
+const METEORA_DLMM_PROGRAM_STATE: &str = "meteora_dlmm::MeteoraDlmmProgramState";
+const METEORA_DLMM_ACCOUNT_PARSER: &str = "meteora_dlmm::AccountParser";
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum MeteoraDlmmProgramState {
+    LbPair(KeyedLbPair),
+    BinArray(KeyedBinArray),
+}
+
+impl MeteoraDlmmProgramState {
+    pub fn try_unpack(pubkey_bytes: [u8; 32], data_bytes: &[u8]) -> ParseResult<Self> {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::try_unpack", METEORA_DLMM_PROGRAM_STATE);
+
+        let result = tracer.in_span(span_name, move |_cx|  {
+            // qtrade
+            let pubkey = Pubkey::new_from_array(pubkey_bytes);
+
+            let data_len = data_bytes.len();
+            if data_len < ACC_DISCRIMINATOR_SIZE {
+                return Err(ParseError::from("Invalid Account data length".to_owned()));
+            }
+            let data_bytes = &data_bytes[ACC_DISCRIMINATOR_SIZE..];
+
+            match data_len {
+                LbPair::LEN => {
+                    let lb_pair = LbPair::try_from_slice(data_bytes)?;
+                    Ok(MeteoraDlmmProgramState::LbPair(KeyedLbPair {
+                        pubkey,
+                        lb_pair,
+                    }))
+                },
+                BinArray::LEN => {
+                    let bin_array = BinArray::try_from_slice(data_bytes)?;
+                    Ok(MeteoraDlmmProgramState::BinArray(KeyedBinArray {
+                        pubkey,
+                        bin_array,
+                    }))
+                },
+                _ => Err(ParseError::from("Invalid Account data length".to_owned())),
+            }
+        });
+
+        result
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct AccountParser;
+
+impl Parser for AccountParser {
+    type Input = yellowstone_vixen_core::AccountUpdate;
+    type Output = MeteoraDlmmProgramState;
+
+    fn id(&self) -> std::borrow::Cow<str> {
+        "meteora_dlmm::AccountParser".into()
+    }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .account_owners([METEORA_DLMM_PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    async fn parse(
+        &self,
+        acct: &yellowstone_vixen_core::AccountUpdate,
+    ) -> ParseResult<Self::Output> {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::parse", METEORA_DLMM_ACCOUNT_PARSER);
+
+        let result = tracer.in_span(span_name, |_cx| async move {
+            let inner = acct.account.as_ref().ok_or(ProgramError::InvalidArgument)?;
+
+            // qtrade
+            let pubkey_bytes: [u8; 32] = inner.pubkey.clone().try_into().map_err(|_| ProgramError::InvalidArgument)?;
+
+            MeteoraDlmmProgramState::try_unpack(pubkey_bytes, &inner.data)
+        }).await;
+
+        result
+    }
+}
+
+impl ProgramParser for AccountParser {
+    #[inline]
+    fn program_id(&self) -> yellowstone_vixen_core::Pubkey {
+        METEORA_DLMM_PROGRAM_ID.to_bytes().into()
+    }
+}
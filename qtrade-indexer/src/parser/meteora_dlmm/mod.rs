@@ -0,0 +1,11 @@
+// qtrade: spl_pod changed namespacing from 0.3.0 to 0.50
+use spl_pod::solana_pubkey::Pubkey;
+
+mod account_helpers;
+mod account_parser;
+
+pub const METEORA_DLMM_ADDRESS: &str = "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo";
+pub const METEORA_DLMM_PROGRAM_ID: Pubkey = Pubkey::from_str_const(METEORA_DLMM_ADDRESS);
+
+pub use account_helpers::*;
+pub use account_parser::*;
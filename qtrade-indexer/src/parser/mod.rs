@@ -15,7 +15,10 @@ pub use error::*;
 
 mod helpers;
 
+pub mod meteora_dlmm;
 pub mod orca;
+pub mod phoenix;
+pub mod pyth;
 pub mod raydium;
 pub mod raydium_clmm;
 pub mod raydium_cpmm;
@@ -0,0 +1,113 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+// qtrade: spl_pod changed namespacing from 0.3.0 to 0.50
+use spl_pod::solana_pubkey::Pubkey;
+use qtrade_shared_types::{OrderBookState, PhoenixMarketState, PoolFee};
+
+// Phoenix stores its market accounts zero-copy (repr(C) without implicit padding),
+// so deserializing the fields in declaration order with borsh reads the same bytes.
+
+/// Taker fees are in basis points
+pub const FEE_PRECISION: u64 = 10_000;
+
+// https://github.com/Ellipsis-Labs/phoenix-v1/blob/master/src/program/accounts.rs
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct MarketSizeParams {
+    pub bids_size: u64,
+    pub asks_size: u64,
+    pub num_seats: u64,
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct TokenParams {
+    /// Number of decimals for the token (e.g. 9 for SOL, 6 for USDC)
+    pub decimals: u32,
+    /// Bump used for generating the PDA for the market's token vault
+    pub vault_bump: u32,
+    /// Pubkey of the token mint
+    pub mint_key: Pubkey,
+    /// Pubkey of the token vault
+    pub vault_key: Pubkey,
+}
+
+/// Header at the start of every Phoenix market account, ahead of its order book
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct MarketHeader {
+    pub discriminant: u64,
+    pub status: u64,
+    pub market_size_params: MarketSizeParams,
+    pub base_params: TokenParams,
+    /// Base token atoms per base lot
+    pub base_lot_size: u64,
+    pub quote_params: TokenParams,
+    /// Quote token atoms per quote lot
+    pub quote_lot_size: u64,
+    /// Quote token atoms per base unit that one tick of price represents
+    pub tick_size_in_quote_atoms_per_base_unit: u64,
+    pub authority: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub market_sequence_number: u64,
+    pub successor: Pubkey,
+    pub raw_base_units_per_base_unit: u32,
+    pub padding1: u32,
+    pub padding2: [u64; 32],
+}
+
+impl MarketHeader {
+    pub const LEN: usize = 8 + 8 + 8 * 3 + TokenParams::LEN + 8 + TokenParams::LEN + 8 + 8 + 32 * 2 + 8 + 32 + 4 + 4 + 8 * 32;
+}
+
+impl TokenParams {
+    pub const LEN: usize = 4 + 4 + 32 * 2;
+}
+
+// https://github.com/Ellipsis-Labs/phoenix-v1/blob/master/src/state/markets/fifo.rs
+/// Fixed fields at the start of the FIFO market that follows the header
+///
+/// The resting orders and trader seats come after these, sized by the header's
+/// [`MarketSizeParams`].
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct FifoMarketParams {
+    pub padding: [u64; 32],
+    /// Base lots in one whole base unit
+    pub base_lots_per_base_unit: u64,
+    /// Quote lots per base unit that one tick of price represents
+    pub tick_size_in_quote_lots_per_base_unit: u64,
+    pub order_sequence_number: u64,
+    /// Taker fee in basis points
+    pub taker_fee_bps: u64,
+}
+
+impl FifoMarketParams {
+    pub const LEN: usize = 8 * 32 + 8 * 4;
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct KeyedMarket {
+    pub pubkey: Pubkey,
+    pub header: MarketHeader,
+    pub market: FifoMarketParams,
+}
+
+impl KeyedMarket {
+    /// Taker fee charged on every fill
+    pub fn pool_fee(&self) -> PoolFee {
+        PoolFee::new(self.market.taker_fee_bps, FEE_PRECISION)
+    }
+
+    /// Market state as the router quotes it
+    ///
+    /// Only the lot and tick sizes and the taker fee are filled in: the resting orders
+    /// aren't indexed yet, so the book is empty.
+    pub fn market_state(&self) -> PhoenixMarketState {
+        PhoenixMarketState {
+            order_book: OrderBookState {
+                base_lot_size: self.header.base_lot_size,
+                quote_lot_size: self.header.quote_lot_size,
+                tick_size_in_quote_lots_per_base_unit: self.market.tick_size_in_quote_lots_per_base_unit,
+                base_lots_per_base_unit: self.market.base_lots_per_base_unit,
+                ..OrderBookState::default()
+            },
+            taker_fee_bps: u16::try_from(self.market.taker_fee_bps).unwrap_or(u16::MAX),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use borsh::BorshDeserialize;
+// qtrade: spl_pod changed namespacing from 0.3.0 to 0.50
+use spl_pod::solana_program_error::ProgramError;
+use yellowstone_vixen_core::{ParseError, ParseResult, Parser, Prefilter, ProgramParser};
+use opentelemetry::global;
+use opentelemetry::trace::Tracer;
+
+// qtrade: from account_helpers.rs
+use spl_pod::solana_pubkey::Pubkey;
+
+use super::account_helpers::{FifoMarketParams, KeyedMarket, MarketHeader};
+use crate::parser::phoenix::PHOENIX_PROGRAM_ID;
+
+// For help in naming spans
+use crate::QTRADE_INDEXER_TRACER_NAME;
+const PHOENIX_PROGRAM_STATE: &str = "phoenix::PhoenixProgramState";
+const PHOENIX_ACCOUNT_PARSER: &str = "phoenix::AccountParser";
+
+#[derive(Debug)]
+pub enum PhoenixProgramState {
+    Market(KeyedMarket),
+}
+
+impl PhoenixProgramState {
+    pub fn try_unpack(pubkey_bytes: [u8; 32], data_bytes: &[u8]) -> ParseResult<Self> {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::try_unpack", PHOENIX_PROGRAM_STATE);
+
+        let result = tracer.in_span(span_name, move |_cx|  {
+            // qtrade
+            let pubkey = Pubkey::new_from_array(pubkey_bytes);
+
+            // Besides markets the program only owns trader seats, which are far smaller
+            // than a market header and the fixed part of its order book
+            if data_bytes.len() < MarketHeader::LEN + FifoMarketParams::LEN {
+                return Err(ParseError::Filtered);
+            }
+
+            // Markets vary in size with their order book, so read the fixed part and
+            // leave the resting orders behind it
+            let mut data_bytes = data_bytes;
+            let header = MarketHeader::deserialize(&mut data_bytes)?;
+            let market = FifoMarketParams::deserialize(&mut data_bytes)?;
+            if header.base_lot_size == 0 || header.quote_lot_size == 0 {
+                return Err(ParseError::from("Phoenix market has no lot sizes".to_owned()));
+            }
+
+            Ok(PhoenixProgramState::Market(KeyedMarket {
+                pubkey,
+                header,
+                market,
+            }))
+        });
+
+        result
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct AccountParser;
+
+impl Parser for AccountParser {
+    type Input = yellowstone_vixen_core::AccountUpdate;
+    type Output = PhoenixProgramState;
+
+    fn id(&self) -> std::borrow::Cow<str> {
+        "phoenix::AccountParser".into()
+    }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .account_owners([PHOENIX_PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    async fn parse(
+        &self,
+        acct: &yellowstone_vixen_core::AccountUpdate,
+    ) -> ParseResult<Self::Output> {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::parse", PHOENIX_ACCOUNT_PARSER);
+
+        let result = tracer.in_span(span_name, |_cx| async move {
+            let inner = acct.account.as_ref().ok_or(ProgramError::InvalidArgument)?;
+
+            // qtrade
+            let pubkey_bytes: [u8; 32] = inner.pubkey.clone().try_into().map_err(|_| ProgramError::InvalidArgument)?;
+
+            PhoenixProgramState::try_unpack(pubkey_bytes, &inner.data)
+        }).await;
+
+        result
+    }
+}
+
+impl ProgramParser for AccountParser {
+    #[inline]
+    fn program_id(&self) -> yellowstone_vixen_core::Pubkey {
+        PHOENIX_PROGRAM_ID.to_bytes().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use qtrade_shared_types::{OrderBookState, PhoenixMarketState, PoolFee};
+
+    use super::*;
+
+    /// Bytes of a SOL/USDC style market: 0.001 SOL base lots, 0.000001 USDC quote lots
+    /// and 0.001 USDC ticks, with a resting order book behind the fixed fields
+    fn market_account() -> Vec<u8> {
+        let mut data = vec![0u8; MarketHeader::LEN + FifoMarketParams::LEN + 4096];
+        let mut put_u64 = |offset: usize, value: u64| data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+
+        // Header: base lot size follows the base token params, quote lot and tick size the quote ones
+        put_u64(8 + 8 + 24 + 72, 1_000_000);
+        put_u64(8 + 8 + 24 + 72 + 8 + 72, 1);
+        put_u64(8 + 8 + 24 + 72 + 8 + 72 + 8, 1_000);
+
+        // Market: base lots per base unit, tick size in quote lots and taker fee after the padding
+        let market = MarketHeader::LEN + 8 * 32;
+        put_u64(market, 1_000);
+        put_u64(market + 8, 1_000);
+        put_u64(market + 24, 2);
+
+        data
+    }
+
+    #[test]
+    fn test_market_header_parsing() {
+        let pubkey = Pubkey::new_unique();
+        let PhoenixProgramState::Market(keyed_market) =
+            PhoenixProgramState::try_unpack(pubkey.to_bytes(), &market_account()).unwrap();
+
+        assert_eq!(keyed_market.pubkey, pubkey);
+        assert_eq!(keyed_market.header.tick_size_in_quote_atoms_per_base_unit, 1_000);
+        assert_eq!(keyed_market.pool_fee(), PoolFee::new(2, 10_000));
+        assert_eq!(keyed_market.market_state(), PhoenixMarketState {
+            order_book: OrderBookState {
+                base_lot_size: 1_000_000,
+                quote_lot_size: 1,
+                tick_size_in_quote_lots_per_base_unit: 1_000,
+                base_lots_per_base_unit: 1_000,
+                ..OrderBookState::default()
+            },
+            taker_fee_bps: 2,
+        });
+    }
+
+    #[test]
+    fn test_non_market_accounts_are_filtered() {
+        // Trader seats are too small to be markets
+        assert!(matches!(PhoenixProgramState::try_unpack([1; 32], &[0; 128]), Err(ParseError::Filtered)));
+
+        // And a market without lot sizes can't be traded
+        let uninitialized = vec![0u8; MarketHeader::LEN + FifoMarketParams::LEN];
+        assert!(matches!(PhoenixProgramState::try_unpack([1; 32], &uninitialized), Err(ParseError::Other(_))));
+    }
+}
@@ -0,0 +1,11 @@
+// qtrade: spl_pod changed namespacing from 0.3.0 to 0.50
+use spl_pod::solana_pubkey::Pubkey;
+
+mod account_helpers;
+mod account_parser;
+
+pub const PHOENIX_ADDRESS: &str = "PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY";
+pub const PHOENIX_PROGRAM_ID: Pubkey = Pubkey::from_str_const(PHOENIX_ADDRESS);
+
+pub use account_helpers::*;
+pub use account_parser::*;
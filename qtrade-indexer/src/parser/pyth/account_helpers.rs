@@ -0,0 +1,49 @@
+// qtrade: spl_pod changed namespacing from 0.3.0 to 0.50
+use spl_pod::solana_pubkey::Pubkey;
+use pyth_sdk_solana::state::{PriceStatus, SolanaPriceAccount};
+
+// Pyth price accounts are read zero-copy with pyth-sdk-solana; only the aggregate
+// price is kept, the per-publisher components aren't needed to value a trade.
+
+/// Aggregate price of one Pyth price feed
+///
+/// `price` and `confidence` are fixed-point with exponent `expo`: the price is
+/// `price * 10^expo`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PythPrice {
+    pub price: i64,
+    pub confidence: u64,
+    pub expo: i32,
+    /// Slot the aggregate price was published in
+    pub publish_slot: u64,
+    /// Whether the feed is trading; other statuses mean the price isn't current
+    pub trading: bool,
+}
+
+impl PythPrice {
+    pub fn from_price_account(price_account: &SolanaPriceAccount) -> Self {
+        Self {
+            price: price_account.agg.price,
+            confidence: price_account.agg.conf,
+            expo: price_account.expo,
+            publish_slot: price_account.agg.pub_slot,
+            trading: price_account.agg.status == PriceStatus::Trading,
+        }
+    }
+
+    /// Price as a float, with the exponent applied
+    pub fn price_f64(&self) -> f64 {
+        self.price as f64 * 10f64.powi(self.expo)
+    }
+
+    /// Confidence interval as a float, with the exponent applied
+    pub fn confidence_f64(&self) -> f64 {
+        self.confidence as f64 * 10f64.powi(self.expo)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyedPythPrice {
+    pub pubkey: Pubkey,
+    pub price: PythPrice,
+}
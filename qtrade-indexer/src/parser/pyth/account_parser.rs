@@ -0,0 +1,143 @@
+// qtrade: spl_pod changed namespacing from 0.3.0 to 0.50
+use spl_pod::solana_program_error::ProgramError;
+use yellowstone_vixen_core::{ParseError, ParseResult, Parser, Prefilter, ProgramParser};
+use opentelemetry::global;
+use opentelemetry::trace::Tracer;
+use pyth_sdk_solana::PythError;
+use pyth_sdk_solana::state::load_price_account;
+
+// qtrade: from account_helpers.rs
+use spl_pod::solana_pubkey::Pubkey;
+
+use super::account_helpers::{KeyedPythPrice, PythPrice};
+use crate::parser::pyth::PYTH_ORACLE_PROGRAM_ID;
+
+// For help in naming spans
+use crate::QTRADE_INDEXER_TRACER_NAME;
+const PYTH_PROGRAM_STATE: &str = "pyth::PythProgramState";
+const PYTH_ACCOUNT_PARSER: &str = "pyth::AccountParser";
+
+#[derive(Debug)]
+pub enum PythProgramState {
+    Price(KeyedPythPrice),
+}
+
+impl PythProgramState {
+    pub fn try_unpack(pubkey_bytes: [u8; 32], data_bytes: &[u8]) -> ParseResult<Self> {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::try_unpack", PYTH_PROGRAM_STATE);
+
+        let result = tracer.in_span(span_name, move |_cx|  {
+            // qtrade
+            let pubkey = Pubkey::new_from_array(pubkey_bytes);
+
+            match load_price_account::<32, ()>(data_bytes) {
+                Ok(price_account) => Ok(PythProgramState::Price(KeyedPythPrice {
+                    pubkey,
+                    price: PythPrice::from_price_account(price_account),
+                })),
+                // Mapping and product accounts say nothing about prices
+                Err(PythError::WrongAccountType) => Err(ParseError::Filtered),
+                Err(e) => Err(ParseError::from(e)),
+            }
+        });
+
+        result
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct AccountParser;
+
+impl Parser for AccountParser {
+    type Input = yellowstone_vixen_core::AccountUpdate;
+    type Output = PythProgramState;
+
+    fn id(&self) -> std::borrow::Cow<str> {
+        "pyth::AccountParser".into()
+    }
+
+    fn prefilter(&self) -> Prefilter {
+        Prefilter::builder()
+            .account_owners([PYTH_ORACLE_PROGRAM_ID])
+            .build()
+            .unwrap()
+    }
+
+    async fn parse(
+        &self,
+        acct: &yellowstone_vixen_core::AccountUpdate,
+    ) -> ParseResult<Self::Output> {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::parse", PYTH_ACCOUNT_PARSER);
+
+        let result = tracer.in_span(span_name, |_cx| async move {
+            let inner = acct.account.as_ref().ok_or(ProgramError::InvalidArgument)?;
+
+            // qtrade
+            let pubkey_bytes: [u8; 32] = inner.pubkey.clone().try_into().map_err(|_| ProgramError::InvalidArgument)?;
+
+            PythProgramState::try_unpack(pubkey_bytes, &inner.data)
+        }).await;
+
+        result
+    }
+}
+
+impl ProgramParser for AccountParser {
+    #[inline]
+    fn program_id(&self) -> yellowstone_vixen_core::Pubkey {
+        PYTH_ORACLE_PROGRAM_ID.to_bytes().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pyth_sdk_solana::state::{AccountType, PriceInfo, PriceStatus, SolanaPriceAccount, MAGIC, VERSION_2};
+
+    use super::*;
+
+    fn price_account(atype: AccountType) -> SolanaPriceAccount {
+        SolanaPriceAccount {
+            magic: MAGIC,
+            ver: VERSION_2,
+            atype: atype as u32,
+            expo: -8,
+            agg: PriceInfo {
+                price: 15_012_345_678,
+                conf: 7_500_000,
+                status: PriceStatus::Trading,
+                pub_slot: 301_234_567,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_price_account_parsing() {
+        let pubkey = Pubkey::new_unique();
+        let data = bytemuck::bytes_of(&price_account(AccountType::Price)).to_vec();
+
+        let PythProgramState::Price(keyed_price) = PythProgramState::try_unpack(pubkey.to_bytes(), &data).unwrap();
+        assert_eq!(keyed_price.pubkey, pubkey);
+        assert_eq!(keyed_price.price, PythPrice {
+            price: 15_012_345_678,
+            confidence: 7_500_000,
+            expo: -8,
+            publish_slot: 301_234_567,
+            trading: true,
+        });
+        assert!((keyed_price.price.price_f64() - 150.12345678).abs() < 1e-9);
+        assert!((keyed_price.price.confidence_f64() - 0.075).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_non_price_accounts_are_filtered() {
+        let data = bytemuck::bytes_of(&price_account(AccountType::Product)).to_vec();
+        assert!(matches!(PythProgramState::try_unpack([1; 32], &data), Err(ParseError::Filtered)));
+
+        // Too short to be a price account at all
+        assert!(matches!(PythProgramState::try_unpack([1; 32], &[0; 16]), Err(ParseError::Other(_))));
+    }
+}
@@ -0,0 +1,11 @@
+// qtrade: spl_pod changed namespacing from 0.3.0 to 0.50
+use spl_pod::solana_pubkey::Pubkey;
+
+mod account_helpers;
+mod account_parser;
+
+pub const PYTH_ORACLE_ADDRESS: &str = "FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH";
+pub const PYTH_ORACLE_PROGRAM_ID: Pubkey = Pubkey::from_str_const(PYTH_ORACLE_ADDRESS);
+
+pub use account_helpers::*;
+pub use account_parser::*;
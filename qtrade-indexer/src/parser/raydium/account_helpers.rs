@@ -1,6 +1,7 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 // qtrade: spl_pod changed namespacing from 0.3.0 to 0.50
 use spl_pod::solana_pubkey::Pubkey;
+use qtrade_shared_types::PoolFee;
 
 // qtrade: hack allows us to ignore all other account type updates when parsing
 #[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
@@ -143,4 +144,9 @@ pub struct KeyedAmmInfo {
 
 impl AmmInfo {
     pub const LEN: usize = 752;
+
+    /// Swap fee charged on each input, e.g. 25 / 10_000 for 0.25%
+    pub fn pool_fee(&self) -> PoolFee {
+        PoolFee::new(self.swap_fee_numerator, self.swap_fee_denominator)
+    }
 }
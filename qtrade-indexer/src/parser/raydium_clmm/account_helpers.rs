@@ -1,6 +1,7 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 // qtrade: spl_pod changed namespacing from 0.3.0 to 0.50
 use spl_pod::solana_pubkey::Pubkey;
+use qtrade_shared_types::PoolFee;
 
 pub const OPERATION_SIZE_USIZE: usize = 10;
 pub const WHITE_MINT_SIZE_USIZE: usize = 100;
@@ -32,6 +33,17 @@ pub struct AmmConfig {
 
 impl AmmConfig {
     pub const LEN: usize = 8 + 1 + 2 + 32 + 4 + 4 + 2 + 64;
+
+    /// Trade fee charged by every pool using this config
+    pub fn pool_fee(&self) -> PoolFee {
+        PoolFee::from_hundredths_bps(self.trade_fee_rate as u64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct KeyedAmmConfig {
+    pub pubkey: Pubkey,
+    pub amm_config: AmmConfig,
 }
 
 #[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
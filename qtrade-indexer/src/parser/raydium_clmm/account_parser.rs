@@ -10,7 +10,7 @@ use opentelemetry::trace::{Span, Tracer};
 use spl_pod::solana_pubkey::Pubkey;
 
 use super::account_helpers::{
-    AmmConfig, ObservationState, OperationState, PersonalPositionState, KeyedPoolState, PoolState,
+    AmmConfig, KeyedAmmConfig, ObservationState, OperationState, PersonalPositionState, KeyedPoolState, PoolState,
     ProtocolPositionState, TickArrayBitmapExtension, TickArrayState,
 };
 use crate::parser::{helpers::ACC_DISCRIMINATOR_SIZE, raydium_clmm::RADIUM_V3_PROGRAM_ID};
@@ -23,7 +23,7 @@ const RAYDIUM_CLMM_ACCOUNT_PARSER: &str = "raydium_clmm::AccountParser";
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum RaydiumProgramState {
-    AmmConfig(AmmConfig),
+    AmmConfig(KeyedAmmConfig),
     OperationState(OperationState),
     ObservationState(ObservationState),
     PersonalPositionState(PersonalPositionState),
@@ -48,9 +48,13 @@ impl RaydiumProgramState {
             let data_bytes = &data_bytes[ACC_DISCRIMINATOR_SIZE..];
 
             match data_len {
-                AmmConfig::LEN => Ok(RaydiumProgramState::AmmConfig(AmmConfig::try_from_slice(
-                    data_bytes,
-                )?)),
+                AmmConfig::LEN => {
+                    let amm_config = AmmConfig::try_from_slice(data_bytes)?;
+                    Ok(RaydiumProgramState::AmmConfig(KeyedAmmConfig {
+                        pubkey: pubkey,
+                        amm_config: amm_config,
+                    }))
+                },
                 OperationState::LEN => Ok(RaydiumProgramState::OperationState(
                     OperationState::try_from_slice(data_bytes)?,
                 )),
@@ -387,7 +391,7 @@ mod proto_parser {
         fn output_into_message(value: Self::Output) -> Self::Message {
             let state_oneof = match value {
                 RaydiumProgramState::AmmConfig(data) => Some(
-                    raydium_program_state_proto::StateOneof::AmmConfig(data.into_proto()),
+                    raydium_program_state_proto::StateOneof::AmmConfig(data.amm_config.into_proto()),
                 ),
                 RaydiumProgramState::OperationState(data) => Some(
                     raydium_program_state_proto::StateOneof::OperationState(data.into_proto()),
@@ -434,7 +438,8 @@ mod tests {
 
         let account = account_fixture!("A1BBtTYJd4i3xU8D6Tc2FzU6ZN4oXZWXKZnCxwbHXr8x", &parser);
 
-        if let RaydiumProgramState::AmmConfig(amm_config) = account {
+        if let RaydiumProgramState::AmmConfig(keyed_amm_config) = account {
+            let amm_config = keyed_amm_config.amm_config;
             assert_eq!(
                 amm_config.owner.to_string(),
                 "projjosVCPQH49d5em7VYS7fJZzaqKixqKtus7yk416".to_string()
@@ -1,6 +1,7 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 // qtrade: spl_pod changed namespacing from 0.3.0 to 0.50
 use spl_pod::solana_pubkey::Pubkey;
+use qtrade_shared_types::PoolFee;
 
 // https://github.com/raydium-io/raydium-cp-swap/blob/master/programs/cp-swap/src/states/config.rs
 #[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
@@ -29,6 +30,17 @@ pub struct AmmConfig {
 
 impl AmmConfig {
     pub const LEN: usize = 8 + 1 + 1 + 2 + 4 * 8 + 32 * 2 + 8 * 16;
+
+    /// Trade fee charged by every pool using this config
+    pub fn pool_fee(&self) -> PoolFee {
+        PoolFee::from_hundredths_bps(self.trade_fee_rate)
+    }
+}
+
+#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub struct KeyedAmmConfig {
+    pub pubkey: Pubkey,
+    pub amm_config: AmmConfig,
 }
 
 // https://github.com/raydium-io/raydium-cp-swap/blob/master/programs/cp-swap/src/states/oracle.rs
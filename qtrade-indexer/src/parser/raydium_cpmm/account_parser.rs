@@ -10,7 +10,7 @@ use opentelemetry::trace::Tracer;
 use spl_pod::solana_pubkey::Pubkey;
 
 use super::account_helpers::{
-    AmmConfig, ObservationState, KeyedPoolState, PoolState};
+    AmmConfig, KeyedAmmConfig, ObservationState, KeyedPoolState, PoolState};
 use crate::parser::{helpers::ACC_DISCRIMINATOR_SIZE, raydium_cpmm::RADIUM_CPMM_PROGRAM_ID};
 
 // For help in naming spans
@@ -21,7 +21,7 @@ const RAYDIUM_CPMM_ACCOUNT_PARSER: &str = "raydium_cpmm::AccountParser";
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum RaydiumProgramState {
-    AmmConfig(AmmConfig),
+    AmmConfig(KeyedAmmConfig),
     ObservationState(ObservationState),
     PoolState(KeyedPoolState),
 }
@@ -39,9 +39,13 @@ impl RaydiumProgramState {
             let data_bytes = &data_bytes[ACC_DISCRIMINATOR_SIZE..];
 
             match data_len {
-                AmmConfig::LEN => Ok(RaydiumProgramState::AmmConfig(AmmConfig::try_from_slice(
-                    data_bytes,
-                )?)),
+                AmmConfig::LEN => {
+                    let amm_config = AmmConfig::try_from_slice(data_bytes)?;
+                    Ok(RaydiumProgramState::AmmConfig(KeyedAmmConfig {
+                        pubkey: pubkey,
+                        amm_config: amm_config,
+                    }))
+                },
                 ObservationState::LEN => Ok(RaydiumProgramState::ObservationState(
                     ObservationState::try_from_slice(data_bytes)?,
                 )),
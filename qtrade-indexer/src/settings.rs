@@ -24,8 +24,37 @@ pub struct IndexerSettings {
     /// This file contains configuration for the yellowstone-vixen streamer,
     /// such as RPC endpoints and other stream-related settings.
     pub vixen_config_path: String,
+
+    /// Age in seconds after which pool cache entries are evicted
+    ///
+    /// Pools that stop receiving updates (paused or delisted) are dropped
+    /// from the cache once their last update is older than this.
+    pub pool_cache_ttl_secs: u64,
+
+    /// RPC endpoint used to backfill pool state on startup
+    ///
+    /// When set, the active DEXes' pools are loaded with `getProgramAccounts`
+    /// before the router's first solve. When unset, the caches fill from the
+    /// stream alone.
+    pub backfill_rpc_url: Option<String>,
+
+    /// Maximum number of entries kept in each of the pool, pool config and mint caches
+    ///
+    /// Past the limit, the least recently updated entries are evicted. When
+    /// unset, the caches grow with every pool and mint seen on the stream.
+    pub max_cache_entries: Option<usize>,
+
+    /// Whether to index oracle price feeds
+    ///
+    /// When set, Pyth price accounts are streamed into the price cache
+    /// alongside the DEX pools. Off by default.
+    #[serde(default)]
+    pub index_price_feeds: bool,
 }
 
+/// Default pool cache TTL in seconds
+pub const DEFAULT_POOL_CACHE_TTL_SECS: u64 = 600;
+
 impl IndexerSettings {
     /// Create a new IndexerSettings instance with default values
     pub fn new() -> Self {
@@ -35,8 +64,13 @@ impl IndexerSettings {
                 "raydium".to_string(),
                 "raydium-cpmm".to_string(),
                 "raydium-clmm".to_string(),
+                "meteora-dlmm".to_string(),
             ],
             vixen_config_path: "default_vixon_config.toml".to_string(),
+            pool_cache_ttl_secs: DEFAULT_POOL_CACHE_TTL_SECS,
+            backfill_rpc_url: None,
+            max_cache_entries: None,
+            index_price_feeds: false,
         }
     }
 
@@ -45,6 +79,10 @@ impl IndexerSettings {
         Self {
             active_dexes,
             vixen_config_path: "default_vixon_config.toml".to_string(),
+            pool_cache_ttl_secs: DEFAULT_POOL_CACHE_TTL_SECS,
+            backfill_rpc_url: None,
+            max_cache_entries: None,
+            index_price_feeds: false,
         }
     }
 
@@ -53,9 +91,37 @@ impl IndexerSettings {
         Self {
             active_dexes,
             vixen_config_path,
+            pool_cache_ttl_secs: DEFAULT_POOL_CACHE_TTL_SECS,
+            backfill_rpc_url: None,
+            max_cache_entries: None,
+            index_price_feeds: false,
         }
     }
 
+    /// Set the age after which pool cache entries are evicted
+    pub fn with_pool_cache_ttl_secs(mut self, pool_cache_ttl_secs: u64) -> Self {
+        self.pool_cache_ttl_secs = pool_cache_ttl_secs;
+        self
+    }
+
+    /// Backfill pool state from `rpc_url` on startup
+    pub fn with_backfill_rpc_url(mut self, rpc_url: String) -> Self {
+        self.backfill_rpc_url = Some(rpc_url);
+        self
+    }
+
+    /// Bound each indexer cache to `max_cache_entries` entries
+    pub fn with_max_cache_entries(mut self, max_cache_entries: usize) -> Self {
+        self.max_cache_entries = Some(max_cache_entries);
+        self
+    }
+
+    /// Stream oracle price feeds into the price cache
+    pub fn with_price_feeds(mut self) -> Self {
+        self.index_price_feeds = true;
+        self
+    }
+
     /// Check if a specific DEX platform is active
     pub fn is_dex_active(&self, dex_name: &str) -> bool {
         self.active_dexes.iter().any(|d| d.eq_ignore_ascii_case(dex_name))
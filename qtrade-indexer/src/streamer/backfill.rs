@@ -0,0 +1,333 @@
+//! Startup backfill of pool state.
+//!
+//! The streamer only learns about a pool when Geyser pushes an update for it, so on a
+//! cold start the caches stay empty until each pool next changes. `run_backfill` fetches
+//! the active DEXes' pool (and fee config) accounts with `getProgramAccounts`, filtered
+//! by account size, and feeds them through the same parsers and handlers as streamed
+//! updates. Once it finishes (or fails, or is cancelled) the pool cache is marked ready,
+//! which is what the router waits on before its first solve.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use once_cell::sync::Lazy;
+use opentelemetry::metrics::Counter;
+use orca_whirlpools_client::Whirlpool;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcProgramAccountsConfig;
+use solana_client::rpc_filter::RpcFilterType;
+use spl_pod::solana_pubkey::Pubkey;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+use yellowstone_vixen::{self as vixen};
+
+use crate::parser::meteora_dlmm::{LbPair, MeteoraDlmmProgramState, METEORA_DLMM_PROGRAM_ID};
+use crate::parser::orca::OrcaProgramState;
+use crate::parser::raydium::{AmmInfo, RaydiumProgramState, RADIUM_PROGRAM_ID};
+use crate::parser::raydium_clmm::{
+    AmmConfig as ClmmAmmConfig, PoolState as ClmmPoolState,
+    RaydiumProgramState as RaydiumClmmProgramState, RADIUM_V3_PROGRAM_ID};
+use crate::parser::raydium_cpmm::{
+    AmmConfig as CpmmAmmConfig, PoolState as CpmmPoolState,
+    RaydiumProgramState as RaydiumCpmmProgramState, RADIUM_CPMM_PROGRAM_ID};
+use crate::settings::IndexerSettings;
+use crate::streamer::handlers::meteora_dlmm_handler::MeteoraDlmmHandler;
+use crate::streamer::handlers::orca_handler::OrcaHandler;
+use crate::streamer::handlers::raydium_clmm_handler::RaydiumClmmHandler;
+use crate::streamer::handlers::raydium_cpmm_handler::RaydiumCpmmHandler;
+use crate::streamer::handlers::raydium_handler::RaydiumHandler;
+use crate::streamer::Cache;
+use crate::{POOL_CACHE, QTRADE_INDEXER_METER};
+
+lazy_static! {
+    static ref BACKFILLED_ACCOUNTS: Counter<u64> = QTRADE_INDEXER_METER
+        .u64_counter("qtrade.indexer.backfilled_accounts")
+        .with_description("Number of pool and config accounts loaded by the startup backfill")
+        .build();
+}
+
+// Whether the pool cache has been primed (or priming was skipped or abandoned)
+static POOL_CACHE_READY: Lazy<watch::Sender<bool>> = Lazy::new(|| watch::channel(false).0);
+
+/// Mark the pool cache ready for the router
+pub fn mark_pool_cache_ready() {
+    POOL_CACHE_READY.send_replace(true);
+}
+
+/// Whether the pool cache has been marked ready
+pub fn is_pool_cache_ready() -> bool {
+    *POOL_CACHE_READY.borrow()
+}
+
+/// Wait until the pool cache has been marked ready
+pub async fn wait_for_pool_cache_ready() {
+    let mut ready = POOL_CACHE_READY.subscribe();
+    // The sender lives in a static, so the channel never closes
+    let _ = ready.wait_for(|ready| *ready).await;
+}
+
+/// Source of a program's accounts, abstracted so the backfill can run without an RPC node
+#[async_trait]
+pub trait ProgramAccountsSource: Send + Sync {
+    /// All accounts owned by `program_id` whose data is exactly `data_size` bytes
+    async fn get_program_accounts(&self, program_id: &Pubkey, data_size: u64) -> Result<Vec<(Pubkey, Vec<u8>)>>;
+}
+
+/// Program accounts fetched from a Solana RPC node with `getProgramAccounts`
+pub struct RpcProgramAccounts {
+    client: RpcClient,
+}
+
+impl RpcProgramAccounts {
+    pub fn new(rpc_url: String) -> Self {
+        Self {
+            client: RpcClient::new(rpc_url),
+        }
+    }
+}
+
+#[async_trait]
+impl ProgramAccountsSource for RpcProgramAccounts {
+    async fn get_program_accounts(&self, program_id: &Pubkey, data_size: u64) -> Result<Vec<(Pubkey, Vec<u8>)>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::DataSize(data_size)]),
+            ..RpcProgramAccountsConfig::default()
+        };
+
+        let program_id = solana_sdk::pubkey::Pubkey::new_from_array(program_id.to_bytes());
+        let accounts = self.client
+            .get_program_accounts_with_config(&program_id, config)
+            .await
+            .map_err(|e| anyhow!("getProgramAccounts failed for {}: {}", program_id, e))?;
+
+        Ok(accounts
+            .into_iter()
+            .map(|(pubkey, account)| (Pubkey::new_from_array(pubkey.to_bytes()), account.data))
+            .collect())
+    }
+}
+
+/// DEXes whose pools can be backfilled, named as in `IndexerSettings::active_dexes`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BackfillDex {
+    Orca,
+    Raydium,
+    RaydiumClmm,
+    RaydiumCpmm,
+    MeteoraDlmm,
+}
+
+impl BackfillDex {
+    const ALL: [BackfillDex; 5] = [
+        BackfillDex::Orca,
+        BackfillDex::Raydium,
+        BackfillDex::RaydiumClmm,
+        BackfillDex::RaydiumCpmm,
+        BackfillDex::MeteoraDlmm,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            BackfillDex::Orca => "orca",
+            BackfillDex::Raydium => "raydium",
+            BackfillDex::RaydiumClmm => "raydium-clmm",
+            BackfillDex::RaydiumCpmm => "raydium-cpmm",
+            BackfillDex::MeteoraDlmm => "meteora-dlmm",
+        }
+    }
+
+    fn program_id(&self) -> Pubkey {
+        match self {
+            BackfillDex::Orca => Pubkey::new_from_array(orca_whirlpools_client::ID.to_bytes()),
+            BackfillDex::Raydium => RADIUM_PROGRAM_ID,
+            BackfillDex::RaydiumClmm => RADIUM_V3_PROGRAM_ID,
+            BackfillDex::RaydiumCpmm => RADIUM_CPMM_PROGRAM_ID,
+            BackfillDex::MeteoraDlmm => METEORA_DLMM_PROGRAM_ID,
+        }
+    }
+
+    /// Sizes of the accounts to fetch: the pools, plus the AMM configs holding their fees
+    fn account_sizes(&self) -> &'static [usize] {
+        match self {
+            BackfillDex::Orca => &[Whirlpool::LEN],
+            BackfillDex::Raydium => &[AmmInfo::LEN],
+            BackfillDex::RaydiumClmm => &[ClmmAmmConfig::LEN, ClmmPoolState::LEN],
+            BackfillDex::RaydiumCpmm => &[CpmmAmmConfig::LEN, CpmmPoolState::LEN],
+            BackfillDex::MeteoraDlmm => &[LbPair::LEN],
+        }
+    }
+
+    /// Parse an account and hand it to this DEX's streamer handler
+    async fn ingest(&self, pubkey: Pubkey, data: &[u8]) -> Result<()> {
+        let pubkey_bytes = pubkey.to_bytes();
+        let result = match self {
+            BackfillDex::Orca => {
+                let state = OrcaProgramState::try_unpack(pubkey_bytes, data)?;
+                vixen::Handler::handle(&OrcaHandler::new(), &state).await
+            }
+            BackfillDex::Raydium => {
+                let state = RaydiumProgramState::try_unpack(pubkey_bytes, data)?;
+                vixen::Handler::handle(&RaydiumHandler::new(), &state).await
+            }
+            BackfillDex::RaydiumClmm => {
+                let state = RaydiumClmmProgramState::try_unpack(pubkey_bytes, data)?;
+                vixen::Handler::handle(&RaydiumClmmHandler::new(), &state).await
+            }
+            BackfillDex::RaydiumCpmm => {
+                let state = RaydiumCpmmProgramState::try_unpack(pubkey_bytes, data)?;
+                vixen::Handler::handle(&RaydiumCpmmHandler::new(), &state).await
+            }
+            BackfillDex::MeteoraDlmm => {
+                let state = MeteoraDlmmProgramState::try_unpack(pubkey_bytes, data)?;
+                vixen::Handler::handle(&MeteoraDlmmHandler::new(), &state).await
+            }
+        };
+
+        result.map_err(|e| anyhow!("Failed to handle {} account {}: {:?}", self.name(), pubkey, e))
+    }
+}
+
+/// Prime the pool caches for the active DEXes, then mark the pool cache ready
+///
+/// Pools the stream has already delivered are left alone, since the streamed state is
+/// at least as fresh as the snapshot. Accounts that fail to parse are logged and
+/// skipped. Returns the number of accounts loaded.
+pub async fn run_backfill(
+    settings: &IndexerSettings,
+    source: &dyn ProgramAccountsSource,
+    cancellation_token: CancellationToken,
+) -> Result<usize> {
+    let result = tokio::select! {
+        _ = cancellation_token.cancelled() => {
+            info!("Cancellation token activated, abandoning pool backfill");
+            Ok(0)
+        }
+        result = backfill_active_dexes(settings, source) => result,
+    };
+
+    // The router must not wait forever on a failed or abandoned backfill
+    mark_pool_cache_ready();
+    result
+}
+
+async fn backfill_active_dexes(settings: &IndexerSettings, source: &dyn ProgramAccountsSource) -> Result<usize> {
+    let mut loaded = 0;
+
+    for dex in BackfillDex::ALL.into_iter().filter(|dex| settings.is_dex_active(dex.name())) {
+        let program_id = dex.program_id();
+
+        for &data_size in dex.account_sizes() {
+            let accounts = source.get_program_accounts(&program_id, data_size as u64).await?;
+            info!("Backfilling {} {} accounts of {} bytes", accounts.len(), dex.name(), data_size);
+
+            for (pubkey, data) in accounts {
+                if POOL_CACHE.read_cache(&pubkey).await.is_some() {
+                    continue;
+                }
+
+                match dex.ingest(pubkey, &data).await {
+                    Ok(()) => {
+                        BACKFILLED_ACCOUNTS.add(1, &[]);
+                        loaded += 1;
+                    }
+                    Err(e) => warn!("Skipping {} account {} during backfill: {:?}", dex.name(), pubkey, e),
+                }
+            }
+        }
+    }
+
+    info!("Pool backfill loaded {} accounts", loaded);
+    Ok(loaded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    /// Serves fixed accounts per program, honouring the data size filter
+    struct MockProgramAccounts {
+        accounts: HashMap<Pubkey, Vec<(Pubkey, Vec<u8>)>>,
+    }
+
+    #[async_trait]
+    impl ProgramAccountsSource for MockProgramAccounts {
+        async fn get_program_accounts(&self, program_id: &Pubkey, data_size: u64) -> Result<Vec<(Pubkey, Vec<u8>)>> {
+            Ok(self.accounts
+                .get(program_id)
+                .into_iter()
+                .flatten()
+                .filter(|(_, data)| data.len() as u64 == data_size)
+                .cloned()
+                .collect())
+        }
+    }
+
+    /// Never answers, like a node stuck on a large getProgramAccounts
+    struct HangingProgramAccounts;
+
+    #[async_trait]
+    impl ProgramAccountsSource for HangingProgramAccounts {
+        async fn get_program_accounts(&self, _program_id: &Pubkey, _data_size: u64) -> Result<Vec<(Pubkey, Vec<u8>)>> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backfill_populates_pool_cache() {
+        let whirlpools = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let amm = Pubkey::new_unique();
+        let inactive_pair = Pubkey::new_unique();
+
+        let source = MockProgramAccounts {
+            accounts: HashMap::from([
+                (
+                    BackfillDex::Orca.program_id(),
+                    vec![
+                        (whirlpools[0], vec![0u8; Whirlpool::LEN]),
+                        (whirlpools[1], vec![0u8; Whirlpool::LEN]),
+                        // Not a pool; left out by the size filter
+                        (Pubkey::new_unique(), vec![0u8; 16]),
+                    ],
+                ),
+                (BackfillDex::Raydium.program_id(), vec![(amm, vec![0u8; AmmInfo::LEN])]),
+                (BackfillDex::MeteoraDlmm.program_id(), vec![(inactive_pair, vec![0u8; LbPair::LEN])]),
+            ]),
+        };
+        let settings = IndexerSettings::new_with_dexes(vec!["orca".to_string(), "raydium".to_string()]);
+
+        let loaded = run_backfill(&settings, &source, CancellationToken::new()).await.unwrap();
+
+        assert_eq!(loaded, 3);
+        for pubkey in whirlpools.iter().chain([&amm]) {
+            assert!(POOL_CACHE.read_cache(pubkey).await.is_some(), "{} not backfilled", pubkey);
+        }
+        assert!(POOL_CACHE.read_cache(&inactive_pair).await.is_none());
+        assert!(is_pool_cache_ready());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_backfill_still_marks_ready() {
+        let token = CancellationToken::new();
+        let cancel = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            cancel.cancel();
+        });
+
+        let loaded = tokio::time::timeout(
+            Duration::from_secs(5),
+            run_backfill(&IndexerSettings::new(), &HangingProgramAccounts, token),
+        )
+        .await
+        .expect("backfill should stop when cancelled")
+        .unwrap();
+
+        assert_eq!(loaded, 0);
+        tokio::time::timeout(Duration::from_secs(1), wait_for_pool_cache_ready())
+            .await
+            .expect("pool cache should be ready");
+    }
+}
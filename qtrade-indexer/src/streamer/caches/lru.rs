@@ -0,0 +1,96 @@
+use std::collections::{BTreeMap, HashMap};
+use spl_pod::solana_pubkey::Pubkey;
+
+/// Update order of a cache's keys, used to bound the cache by entry count
+///
+/// Each update stamps the key with an increasing tick. When the cache holds more than
+/// `max_entries` keys, the ones with the oldest ticks (least recently updated) are
+/// handed back to the cache to evict. Reads don't count as use, so they can stay
+/// behind the cache's read lock.
+pub(crate) struct LruIndex {
+    max_entries: Option<usize>,
+    next_tick: u64,
+    ticks: HashMap<Pubkey, u64>,
+    order: BTreeMap<u64, Pubkey>,
+}
+
+impl LruIndex {
+    /// An index with no entry limit
+    pub(crate) fn new() -> Self {
+        Self {
+            max_entries: None,
+            next_tick: 0,
+            ticks: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    /// Change the entry limit (`None` for unbounded), returning the keys now over it
+    pub(crate) fn set_max_entries(&mut self, max_entries: Option<usize>) -> Vec<Pubkey> {
+        self.max_entries = max_entries;
+        self.evict_over_capacity()
+    }
+
+    /// Record an update of `key`, returning the keys evicted to make room for it
+    pub(crate) fn touch(&mut self, key: Pubkey) -> Vec<Pubkey> {
+        let tick = self.next_tick;
+        self.next_tick += 1;
+
+        if let Some(previous) = self.ticks.insert(key, tick) {
+            self.order.remove(&previous);
+        }
+        self.order.insert(tick, key);
+
+        self.evict_over_capacity()
+    }
+
+    /// Forget `key`, e.g. after it was removed from the cache
+    pub(crate) fn remove(&mut self, key: &Pubkey) {
+        if let Some(tick) = self.ticks.remove(key) {
+            self.order.remove(&tick);
+        }
+    }
+
+    fn evict_over_capacity(&mut self) -> Vec<Pubkey> {
+        let Some(max_entries) = self.max_entries else {
+            return Vec::new();
+        };
+
+        let mut evicted = Vec::new();
+        while self.ticks.len() > max_entries {
+            let Some((_, key)) = self.order.pop_first() else {
+                break;
+            };
+            self.ticks.remove(&key);
+            evicted.push(key);
+        }
+
+        evicted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_least_recently_updated_evicted_first() {
+        let mut lru = LruIndex::new();
+        let keys: Vec<Pubkey> = (1..=3).map(|i| Pubkey::new_from_array([i; 32])).collect();
+
+        for key in &keys {
+            assert!(lru.touch(*key).is_empty());
+        }
+
+        // Updating the first key again makes the second the oldest
+        assert!(lru.touch(keys[0]).is_empty());
+        assert_eq!(lru.set_max_entries(Some(2)), vec![keys[1]]);
+
+        let newest = Pubkey::new_from_array([4; 32]);
+        assert_eq!(lru.touch(newest), vec![keys[2]]);
+
+        // Removed keys no longer count against the limit
+        lru.remove(&keys[0]);
+        assert!(lru.touch(keys[1]).is_empty());
+    }
+}
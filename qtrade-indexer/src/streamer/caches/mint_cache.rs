@@ -7,8 +7,10 @@ use spl_pod::solana_pubkey::Pubkey;
 use spl_token::state::Mint;
 use opentelemetry::global;
 use opentelemetry::trace::Tracer;
+use qtrade_shared_types::MintDecimals;
 
 use crate::streamer::Cache;
+use crate::streamer::caches::lru::LruIndex;
 
 // For help in naming spans
 use crate::QTRADE_INDEXER_TRACER_NAME;
@@ -45,6 +47,8 @@ pub struct MintCache {
 
 struct MintCacheInner {
     data: DashMap<Pubkey, TokenProgramState>,
+    // Update order of the keys in `data`, bounding how many are kept
+    lru: LruIndex,
 }
 
 impl MintCache {
@@ -53,9 +57,24 @@ impl MintCache {
         Self {
             inner: Arc::new(RwLock::new(MintCacheInner {
                 data: DashMap::new(),
+                lru: LruIndex::new(),
             }))
         }
     }
+
+    /// Bound the cache to `max_entries` entries (`None` for unbounded)
+    ///
+    /// Past the limit, the least recently updated entries are evicted. Returns the
+    /// number of entries evicted to fit the new limit.
+    pub async fn set_max_entries(&self, max_entries: Option<usize>) -> usize {
+        let mut cache_write = self.inner.write().await;
+        let evicted = cache_write.lru.set_max_entries(max_entries);
+        for key in &evicted {
+            cache_write.data.remove(key);
+        }
+
+        evicted.len()
+    }
 }
 
 impl Cache<Pubkey, TokenProgramState> for MintCache {
@@ -167,8 +186,13 @@ impl Cache<Pubkey, TokenProgramState> for MintCache {
             // 1. Make sure not to hold RwLockWriteGuard across await points
             // 2. Make sure not to hold any reference to dashmap
             let cache_result = {
-                let cache_write = self.inner.write().await;
-                cache_write.data.insert(key, value)
+                let mut cache_write = self.inner.write().await;
+                let previous = cache_write.data.insert(key, value);
+                let evicted = cache_write.lru.touch(key);
+                for evicted_key in &evicted {
+                    cache_write.data.remove(evicted_key);
+                }
+                previous
             };
 
             cache_result
@@ -186,7 +210,8 @@ impl Cache<Pubkey, TokenProgramState> for MintCache {
             // 1. Make sure not to hold RwLockWriteGuard across await points
             // 2. Make sure not to hold any reference to dashmap
             let cache_result = {
-                let cache_write = self.inner.write().await;
+                let mut cache_write = self.inner.write().await;
+                cache_write.lru.remove(&key);
                 cache_write.data.remove(&key)
             };
 
@@ -196,3 +221,15 @@ impl Cache<Pubkey, TokenProgramState> for MintCache {
         result
     }
 }
+
+/// Implementation of the MintDecimals trait from qtrade-shared-types for our MintCache struct
+/// This allows the relayer to scale swap amounts by each mint's decimals
+#[async_trait::async_trait]
+impl MintDecimals for MintCache {
+    async fn get_decimals(&self, mint: &Pubkey) -> Option<u8> {
+        match <Self as Cache<Pubkey, TokenProgramState>>::read_cache(self, mint).await {
+            Some(TokenProgramState::Mint(mint_state)) => Some(mint_state.decimals),
+            None => None,
+        }
+    }
+}
@@ -1,3 +1,4 @@
+mod lru;
 pub mod mint_cache;
 pub mod oracle_cache;
 pub mod pool_cache;
@@ -0,0 +1,191 @@
+use dashmap::DashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+// qtrade: from raydium_clmm, account_helper.rs
+use spl_pod::solana_pubkey::Pubkey;
+use opentelemetry::global;
+use opentelemetry::trace::Tracer;
+
+use crate::parser::pyth::KeyedPythPrice;
+use crate::streamer::Cache;
+
+// For help in naming spans
+use crate::QTRADE_INDEXER_TRACER_NAME;
+const PRICE_CACHE: &str = "streamer::caches::PriceCache";
+
+// Notes:
+// 1. If you add more enum variants (e.g. Switchboard feeds), make sure the enum fields all #[derive Clone]
+// 2. Every variant must say what its price, confidence and publish slot are, see the accessors below
+#[derive(Debug, Clone)]
+pub enum PriceCacheState {
+    PythPriceState(KeyedPythPrice),
+}
+
+impl PriceCacheState {
+    /// Price of the feed, with its exponent applied
+    pub fn price(&self) -> f64 {
+        match self {
+            PriceCacheState::PythPriceState(keyed_price) => keyed_price.price.price_f64(),
+        }
+    }
+
+    /// Confidence interval around the price, with its exponent applied
+    pub fn confidence(&self) -> f64 {
+        match self {
+            PriceCacheState::PythPriceState(keyed_price) => keyed_price.price.confidence_f64(),
+        }
+    }
+
+    /// Slot the price was published in
+    pub fn publish_slot(&self) -> u64 {
+        match self {
+            PriceCacheState::PythPriceState(keyed_price) => keyed_price.price.publish_slot,
+        }
+    }
+
+    /// Whether the oracle considers the price current
+    pub fn is_trading(&self) -> bool {
+        match self {
+            PriceCacheState::PythPriceState(keyed_price) => keyed_price.price.trading,
+        }
+    }
+}
+
+// Reference:
+// https://draft.ryhl.io/blog/shared-mutable-state/
+//
+// Keyed by the price feed's account. There are only a few hundred feeds, so unlike the
+// pool and mint caches this one isn't bounded.
+#[derive(Clone)]
+pub struct PriceCache {
+    inner: Arc<RwLock<PriceCacheInner>>
+}
+
+struct PriceCacheInner {
+    data: DashMap<Pubkey, PriceCacheState>,
+}
+
+impl PriceCache {
+    // Keep the constructor, but not as part of the Cache trait
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(PriceCacheInner {
+                data: DashMap::new(),
+            }))
+        }
+    }
+
+    /// Store `value` unless the cache already holds a price published in a later slot
+    ///
+    /// Returns whether the cache was updated.
+    pub async fn update_if_newer(&self, key: Pubkey, value: PriceCacheState) -> bool {
+        let is_newer = match self.read_cache(&key).await {
+            Some(cached) => value.publish_slot() >= cached.publish_slot(),
+            None => true,
+        };
+        if is_newer {
+            self.update_cache(key, value).await;
+        }
+
+        is_newer
+    }
+}
+
+impl Cache<Pubkey, PriceCacheState> for PriceCache {
+
+    async fn get_all_entries(&self) -> Vec<(Pubkey, PriceCacheState)> {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::get_all_entries", PRICE_CACHE);
+
+        let result = tracer.in_span(span_name, |_cx| async move {
+            // We add a block here to:
+            // 1. Make sure not to hold RwLockReadGuard across await points
+            // 2. Make sure not to hold any reference to dashmap
+            let cache_result = {
+                let cache_read = self.inner.read().await;
+                cache_read.data.iter().map(|entry| (*entry.key(), entry.value().clone())).collect()
+            };
+
+            cache_result
+        }).await;
+
+        result
+    }
+
+    async fn get_all_entries_as_slice(&self) -> Box<[(Pubkey, PriceCacheState)]> {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::get_all_entries_as_slice", PRICE_CACHE);
+
+        let result = tracer.in_span(span_name, |_cx| async move {
+            // We add a block here to:
+            // 1. Make sure not to hold RwLockReadGuard across await points
+            // 2. Make sure not to hold any reference to dashmap
+            let cache_result = {
+                let cache_read = self.inner.read().await;
+                let cache_result: Vec<(Pubkey, PriceCacheState)> = cache_read.data.iter().map(|entry| (*entry.key(), entry.value().clone())).collect();
+                cache_result.into_boxed_slice()
+            };
+
+            cache_result
+        }).await;
+
+        result
+    }
+
+    async fn read_cache(&self, key: &Pubkey) -> Option<PriceCacheState> {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::read_cache", PRICE_CACHE);
+
+        let result = tracer.in_span(span_name, |_cx| async move {
+            // We add a block here to:
+            // 1. Make sure not to hold RwLockReadGuard across await points
+            // 2. Make sure not to hold any reference to dashmap
+            let cache_result = {
+                let cache_read = self.inner.read().await;
+                cache_read.data.get(key).map(|cache_entry| cache_entry.value().clone())
+            };
+
+            cache_result
+        }).await;
+
+        result
+    }
+
+    async fn update_cache(&self, key: Pubkey, value: PriceCacheState) -> Option<PriceCacheState> {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::update_cache", PRICE_CACHE);
+
+        let result = tracer.in_span(span_name, |_cx| async move {
+            // We add a block here to:
+            // 1. Make sure not to hold RwLockWriteGuard across await points
+            // 2. Make sure not to hold any reference to dashmap
+            let cache_result = {
+                let cache_write = self.inner.write().await;
+                cache_write.data.insert(key, value)
+            };
+
+            cache_result
+        }).await;
+
+        result
+    }
+
+    async fn remove_cache(&self, key: Pubkey) -> Option<(Pubkey, PriceCacheState)> {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::remove_cache", PRICE_CACHE);
+
+        let result = tracer.in_span(span_name, |_cx| async move {
+            // We add a block here to:
+            // 1. Make sure not to hold RwLockWriteGuard across await points
+            // 2. Make sure not to hold any reference to dashmap
+            let cache_result = {
+                let cache_write = self.inner.write().await;
+                cache_write.data.remove(&key)
+            };
+
+            cache_result
+        }).await;
+
+        result
+    }
+}
@@ -1,21 +1,32 @@
 use dashmap::DashMap;
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
 // qtrade: from raydium_clmm, account_helper.rs
 use spl_pod::solana_pubkey::Pubkey;
 use opentelemetry::global;
 use opentelemetry::trace::Tracer;
-use std::any::Any;
-use tracing::info;
-use qtrade_shared_types::{PoolCache as SharedPoolCache, PoolEntry};
-
+use tracing::{debug, info};
+use qtrade_shared_types::{
+    BinLiquidity, ConcentratedLiquidityState, DlmmState, IndexedPool, PoolCache as SharedPoolCache, PoolEntry, PoolFee,
+    PoolState, ReservesState, HEALTH_STATUS};
+
+use crate::parser::meteora_dlmm::{
+    BinArray as MeteoraDlmmBinArray,
+    KeyedBinArray as MeteoraDlmmKeyedBinArray,
+    KeyedLbPair as MeteoraDlmmKeyedLbPair};
 use crate::parser::orca::{
     KeyedWhirlpool as OrcaKeyedWhirlpool,
     KeyedWhirlpoolsConfig as OrcaKeyedWhirlpoolsConfig};
+use crate::parser::phoenix::KeyedMarket as PhoenixKeyedMarket;
 use crate::parser::raydium::KeyedAmmInfo as RaydiumKeyedAmmInfo;
-use crate::parser::raydium_cpmm::KeyedPoolState as RaydiumClmmKeyedPoolState;
+use crate::parser::raydium_clmm::KeyedPoolState as RaydiumClmmKeyedPoolState;
 use crate::parser::raydium_cpmm::KeyedPoolState as RaydiumCpmmKeyedPoolState;
-use crate::streamer::Cache;
+use crate::streamer::{Cache, PoolConfigCacheState};
+use crate::streamer::caches::lru::LruIndex;
 
 // For help in naming spans
 use crate::QTRADE_INDEXER_TRACER_NAME;
@@ -43,6 +54,80 @@ pub enum PoolCacheState {
     RaydiumPoolState(RaydiumKeyedAmmInfo),
     RaydiumClmmPoolState(RaydiumClmmKeyedPoolState),
     RaydiumCpmmPoolState(RaydiumCpmmKeyedPoolState),
+    MeteoraDlmmPoolState(MeteoraDlmmKeyedLbPair),
+    PhoenixPoolState(PhoenixKeyedMarket),
+}
+
+impl PoolCacheState {
+    /// Address of the config account holding this pool's trade fee, if it lives outside the pool
+    pub fn fee_config(&self) -> Option<Pubkey> {
+        match self {
+            PoolCacheState::RaydiumClmmPoolState(pool) => Some(pool.pool_state.amm_config),
+            PoolCacheState::RaydiumCpmmPoolState(pool) => Some(pool.pool_state.amm_config),
+            PoolCacheState::OrcaPoolState(_)
+            | PoolCacheState::RaydiumPoolState(_)
+            | PoolCacheState::MeteoraDlmmPoolState(_)
+            | PoolCacheState::PhoenixPoolState(_) => None,
+        }
+    }
+
+    /// Trade fee parsed from the pool's accounts
+    ///
+    /// Orca whirlpools, Raydium AMM v4 pools, Meteora DLMM pairs and Phoenix markets store
+    /// their fee in the pool itself.
+    /// Raydium CPMM and CLMM pools read it from their AMM config, so `config` must be
+    /// the cached state of [`Self::fee_config`]; without it the fee is unknown.
+    pub fn pool_fee(&self, config: Option<&PoolConfigCacheState>) -> Option<PoolFee> {
+        match self {
+            PoolCacheState::OrcaPoolState(pool) => {
+                Some(PoolFee::from_hundredths_bps(pool.whirlpool.fee_rate as u64))
+            }
+            PoolCacheState::RaydiumPoolState(pool) => Some(pool.amm_info.pool_fee()),
+            PoolCacheState::RaydiumClmmPoolState(_) => match config {
+                Some(PoolConfigCacheState::RaydiumClmmAmmConfigState(config)) => Some(config.amm_config.pool_fee()),
+                _ => None,
+            },
+            PoolCacheState::RaydiumCpmmPoolState(_) => match config {
+                Some(PoolConfigCacheState::RaydiumCpmmAmmConfigState(config)) => Some(config.amm_config.pool_fee()),
+                _ => None,
+            },
+            PoolCacheState::MeteoraDlmmPoolState(pool) => Some(pool.lb_pair.pool_fee()),
+            PoolCacheState::PhoenixPoolState(market) => Some(market.pool_fee()),
+        }
+    }
+
+    /// Pool state as the router quotes it
+    ///
+    /// Only what the pool account itself holds is filled in: Raydium AMM and CPMM reserves
+    /// sit in vault token accounts, which aren't indexed yet, DLMM bins are added from the
+    /// cached bin arrays by [`PoolCache::dlmm_bins`], Phoenix books are left empty, and fees
+    /// kept in another account come with [`Self::pool_fee`] instead.
+    pub fn pool_state(&self) -> PoolState {
+        match self {
+            PoolCacheState::OrcaPoolState(pool) => PoolState::Orca(ConcentratedLiquidityState {
+                sqrt_price: pool.whirlpool.sqrt_price,
+                tick_current_index: pool.whirlpool.tick_current_index,
+                liquidity: pool.whirlpool.liquidity,
+                fee_rate: pool.whirlpool.fee_rate,
+                tick_spacing: pool.whirlpool.tick_spacing,
+            }),
+            PoolCacheState::RaydiumPoolState(_) => PoolState::Raydium(ReservesState::default()),
+            PoolCacheState::RaydiumClmmPoolState(pool) => PoolState::RaydiumClmm(ConcentratedLiquidityState {
+                sqrt_price: pool.pool_state.sqrt_price_x64,
+                tick_current_index: pool.pool_state.tick_current,
+                liquidity: pool.pool_state.liquidity,
+                fee_rate: 0,
+                tick_spacing: pool.pool_state.tick_spacing,
+            }),
+            PoolCacheState::RaydiumCpmmPoolState(_) => PoolState::RaydiumCpmm(ReservesState::default()),
+            PoolCacheState::MeteoraDlmmPoolState(pool) => PoolState::MeteoraDlmm(DlmmState {
+                active_id: pool.lb_pair.active_id,
+                bin_step: pool.lb_pair.bin_step,
+                ..DlmmState::default()
+            }),
+            PoolCacheState::PhoenixPoolState(market) => PoolState::Phoenix(market.market_state()),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -60,6 +145,21 @@ pub struct PoolCache {
 
 struct PoolCacheInner {
     data: DashMap<Pubkey, PoolCacheState>,
+    // When each entry in `data` was last written by the streamer
+    last_updated: DashMap<Pubkey, Instant>,
+    // Update order of the keys in `data`, bounding how many are kept
+    lru: LruIndex,
+    // Latest bin arrays of each DLMM pair, by pair and then by bin array index
+    bin_arrays: DashMap<Pubkey, BTreeMap<i64, MeteoraDlmmBinArray>>,
+}
+
+impl PoolCacheInner {
+    /// Drop the entry for `key`, along with the bin arrays of a DLMM pair
+    fn remove(&self, key: &Pubkey) -> Option<PoolCacheState> {
+        self.last_updated.remove(key);
+        self.bin_arrays.remove(key);
+        self.data.remove(key).map(|(_, state)| state)
+    }
 }
 
 impl PoolCache {
@@ -68,9 +168,173 @@ impl PoolCache {
         Self {
             inner: Arc::new(RwLock::new(PoolCacheInner {
                 data: DashMap::new(),
+                last_updated: DashMap::new(),
+                lru: LruIndex::new(),
+                bin_arrays: DashMap::new(),
             }))
         }
     }
+
+    /// Bound the cache to `max_entries` entries (`None` for unbounded)
+    ///
+    /// Past the limit, the least recently updated entries are evicted. Returns the
+    /// number of entries evicted to fit the new limit.
+    pub async fn set_max_entries(&self, max_entries: Option<usize>) -> usize {
+        let mut cache_write = self.inner.write().await;
+        let evicted = cache_write.lru.set_max_entries(max_entries);
+        for key in &evicted {
+            cache_write.remove(key);
+        }
+
+        evicted.len()
+    }
+
+    /// When the entry for `key` was last updated, if it is cached
+    pub async fn last_updated(&self, key: &Pubkey) -> Option<Instant> {
+        let cache_read = self.inner.read().await;
+        cache_read.last_updated.get(key).map(|entry| *entry.value())
+    }
+
+    /// Get all entries updated within `max_age`
+    ///
+    /// Pools that haven't ticked recently may be paused or delisted, so the router
+    /// should only optimize over fresh entries.
+    pub async fn get_fresh_entries(&self, max_age: Duration) -> Vec<(Pubkey, PoolCacheState)> {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::get_fresh_entries", POOL_CACHE);
+
+        let result = tracer.in_span(span_name, |_cx| async move {
+            // We add a block here to:
+            // 1. Make sure not to hold RwLockReadGuard across await points
+            // 2. Make sure not to hold any reference to dashmap
+            let cache_result = {
+                let cache_read = self.inner.read().await;
+                cache_read.data
+                    .iter()
+                    .filter(|entry| {
+                        cache_read.last_updated
+                            .get(entry.key())
+                            .is_some_and(|updated| updated.elapsed() <= max_age)
+                    })
+                    .map(|entry| (*entry.key(), entry.value().clone()))
+                    .collect()
+            };
+
+            cache_result
+        }).await;
+
+        result
+    }
+
+    /// Remove all entries that haven't been updated within `ttl`
+    ///
+    /// Returns the number of evicted entries.
+    pub async fn evict_stale(&self, ttl: Duration) -> usize {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::evict_stale", POOL_CACHE);
+
+        let result = tracer.in_span(span_name, |_cx| async move {
+            // We add a block here to:
+            // 1. Make sure not to hold RwLockWriteGuard across await points
+            // 2. Make sure not to hold any reference to dashmap
+            let evicted = {
+                let mut cache_write = self.inner.write().await;
+                let stale: Vec<Pubkey> = cache_write.last_updated
+                    .iter()
+                    .filter(|entry| entry.value().elapsed() > ttl)
+                    .map(|entry| *entry.key())
+                    .collect();
+
+                for key in &stale {
+                    cache_write.lru.remove(key);
+                    cache_write.remove(key);
+                }
+
+                stale.len()
+            };
+
+            if evicted > 0 {
+                info!("Evicted {} stale pool entries older than {:?}", evicted, ttl);
+            }
+
+            evicted
+        }).await;
+
+        result
+    }
+
+    /// Cache a DLMM bin array, replacing the previous version of the same array
+    ///
+    /// Bin arrays are kept with their pair and dropped when the pair is evicted.
+    pub async fn update_bin_array(&self, keyed_bin_array: &MeteoraDlmmKeyedBinArray) {
+        let cache_write = self.inner.write().await;
+        let bin_array = keyed_bin_array.bin_array;
+        cache_write.bin_arrays
+            .entry(bin_array.lb_pair)
+            .or_default()
+            .insert(bin_array.index, bin_array);
+    }
+
+    /// Bins holding liquidity in the cached bin arrays of DLMM pair `lb_pair`, by bin id
+    pub async fn dlmm_bins(&self, lb_pair: &Pubkey) -> Vec<BinLiquidity> {
+        let cache_read = self.inner.read().await;
+        let Some(bin_arrays) = cache_read.bin_arrays.get(lb_pair) else {
+            return Vec::new();
+        };
+
+        bin_arrays
+            .values()
+            .flat_map(|bin_array| {
+                let lower_bin_id = bin_array.lower_bin_id();
+                bin_array.bins.iter().enumerate().filter_map(move |(offset, bin)| {
+                    let bin_id = i32::try_from(lower_bin_id + offset as i64).ok()?;
+                    (bin.amount_x > 0 || bin.amount_y > 0).then_some(BinLiquidity {
+                        bin_id,
+                        amount_x: bin.amount_x,
+                        amount_y: bin.amount_y,
+                    })
+                })
+            })
+            .collect()
+    }
+
+    /// Convert each cache entry to an [`IndexedPool`], as required by the router
+    async fn to_pool_entries(&self, entries: Vec<(Pubkey, PoolCacheState)>) -> Vec<PoolEntry> {
+        let mut pool_entries = Vec::with_capacity(entries.len());
+
+        for (key, state) in entries {
+            let config = match state.fee_config() {
+                Some(config_key) => crate::POOL_CONFIG_CACHE.read_cache(&config_key).await,
+                None => None,
+            };
+
+            let mut indexed = to_indexed_pool(state, config.as_ref());
+            if let PoolState::MeteoraDlmm(pair) = &mut indexed.state {
+                pair.bins = self.dlmm_bins(&key).await;
+            }
+            pool_entries.push((key, indexed));
+        }
+
+        pool_entries
+    }
+
+    /// Periodically evict entries older than `ttl` until the cancellation token fires
+    pub async fn run_eviction(&self, ttl: Duration, cancellation_token: CancellationToken) -> anyhow::Result<()> {
+        // Check often enough that no entry outlives the TTL by more than half of it
+        let interval = (ttl / 2).max(Duration::from_secs(1));
+
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => {
+                    info!("Cancellation token activated, stopping pool cache eviction");
+                    return Ok(());
+                }
+                _ = sleep(interval) => {}
+            }
+
+            self.evict_stale(ttl).await;
+        }
+    }
 }
 
 impl Cache<Pubkey, PoolCacheState> for PoolCache {
@@ -182,10 +446,19 @@ impl Cache<Pubkey, PoolCacheState> for PoolCache {
             // 1. Make sure not to hold RwLockWriteGuard across await points
             // 2. Make sure not to hold any reference to dashmap
             let cache_result = {
-                let cache_write = self.inner.write().await;
-                cache_write.data.insert(key, value)
+                let mut cache_write = self.inner.write().await;
+                cache_write.last_updated.insert(key, Instant::now());
+                let previous = cache_write.data.insert(key, value);
+                let evicted = cache_write.lru.touch(key);
+                for evicted_key in &evicted {
+                    cache_write.remove(evicted_key);
+                }
+                previous
             };
 
+            // Every pool update is proof the stream is live
+            HEALTH_STATUS.record_account_update();
+
             cache_result
         }).await;
 
@@ -201,8 +474,9 @@ impl Cache<Pubkey, PoolCacheState> for PoolCache {
             // 1. Make sure not to hold RwLockWriteGuard across await points
             // 2. Make sure not to hold any reference to dashmap
             let cache_result = {
-                let cache_write = self.inner.write().await;
-                cache_write.data.remove(&key)
+                let mut cache_write = self.inner.write().await;
+                cache_write.lru.remove(&key);
+                cache_write.remove(&key).map(|state| (key, state))
             };
 
             cache_result
@@ -212,6 +486,19 @@ impl Cache<Pubkey, PoolCacheState> for PoolCache {
     }
 }
 
+/// Tag a cache entry with its trade fee, looking up the fee config when the pool has one
+fn to_indexed_pool(state: PoolCacheState, config: Option<&PoolConfigCacheState>) -> IndexedPool {
+    let fee = state.pool_fee(config);
+    if fee.is_none() {
+        debug!("Fee config {:?} not indexed yet, pool fee unknown", state.fee_config());
+    }
+
+    IndexedPool {
+        fee,
+        state: state.pool_state(),
+    }
+}
+
 /// Implementation of the PoolCache trait from qtrade-shared-types for our PoolCache struct
 /// This allows our local PoolCache to be used with the router component
 #[async_trait::async_trait]
@@ -225,16 +512,284 @@ impl SharedPoolCache for PoolCache {
         let entries = <Self as crate::streamer::Cache<Pubkey, PoolCacheState>>::get_all_entries(self).await;
 
         // Map our cache entries to the format expected by qtrade_router
-        let result = entries
-            .into_iter()
-            .map(|(key, state)| {
-                // Box the state as dyn Any + Send + Sync as required by the router
-                let boxed_state: Box<dyn Any + Send + Sync> = Box::new(state);
-                (key, boxed_state)
-            })
-            .collect::<Vec<PoolEntry>>();
+        let result = self.to_pool_entries(entries).await;
 
         info!("Retrieved {} pool entries for router", result.len());
         result
     }
+
+    async fn get_fresh_entries(&self, max_age: Duration) -> Vec<PoolEntry> {
+        info!("Getting pool entries updated within {:?} for router", max_age);
+
+        let entries = PoolCache::get_fresh_entries(self, max_age).await;
+        let result = self.to_pool_entries(entries).await;
+
+        info!("Retrieved {} fresh pool entries for router", result.len());
+        result
+    }
+
+    async fn wait_until_ready(&self) {
+        crate::streamer::backfill::wait_for_pool_cache_ready().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshDeserialize;
+    use orca_whirlpools_client::Whirlpool;
+    use crate::parser::meteora_dlmm::{Bin, LbPair, MAX_BIN_PER_ARRAY};
+    use crate::parser::raydium::AmmInfo;
+    use crate::parser::raydium_clmm::{
+        AmmConfig as ClmmAmmConfig, KeyedAmmConfig as ClmmKeyedAmmConfig, PoolState as ClmmPoolState};
+    use crate::parser::raydium_cpmm::{
+        AmmConfig as CpmmAmmConfig, KeyedAmmConfig as CpmmKeyedAmmConfig, PoolState as CpmmPoolState};
+
+    /// Deserialize an all-zero account, for state whose fee lives in another account
+    fn zeroed<T: BorshDeserialize>() -> T {
+        T::deserialize(&mut &vec![0u8; 8192][..]).unwrap()
+    }
+
+    fn orca_state(pubkey: Pubkey) -> PoolCacheState {
+        PoolCacheState::OrcaPoolState(OrcaKeyedWhirlpool {
+            pubkey,
+            whirlpool: Whirlpool::from_bytes(&[0u8; Whirlpool::LEN]).unwrap(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_stale_entries_excluded_from_fresh_entries() {
+        let cache = PoolCache::new();
+        let stale_key = Pubkey::new_from_array([1; 32]);
+        let fresh_key = Pubkey::new_from_array([2; 32]);
+
+        cache.update_cache(stale_key, orca_state(stale_key)).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        cache.update_cache(fresh_key, orca_state(fresh_key)).await;
+
+        let fresh = cache.get_fresh_entries(Duration::from_millis(25)).await;
+        assert_eq!(fresh.len(), 1);
+        assert_eq!(fresh[0].0, fresh_key);
+
+        // Everything is still cached, just filtered out
+        assert_eq!(cache.get_all_entries().await.len(), 2);
+        assert_eq!(cache.get_fresh_entries(Duration::from_secs(60)).await.len(), 2);
+
+        // Updating the stale pool makes it fresh again
+        cache.update_cache(stale_key, orca_state(stale_key)).await;
+        assert_eq!(cache.get_fresh_entries(Duration::from_millis(25)).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_evict_stale_removes_old_entries() {
+        let cache = PoolCache::new();
+        let stale_key = Pubkey::new_from_array([1; 32]);
+        let fresh_key = Pubkey::new_from_array([2; 32]);
+
+        cache.update_cache(stale_key, orca_state(stale_key)).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        cache.update_cache(fresh_key, orca_state(fresh_key)).await;
+
+        assert_eq!(cache.evict_stale(Duration::from_millis(25)).await, 1);
+        assert!(cache.read_cache(&stale_key).await.is_none());
+        assert!(cache.last_updated(&stale_key).await.is_none());
+        assert!(cache.read_cache(&fresh_key).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_oldest_entries_evicted_beyond_capacity() {
+        let cache = PoolCache::new();
+        cache.set_max_entries(Some(2)).await;
+        let keys: Vec<Pubkey> = (1..=3).map(|i| Pubkey::new_from_array([i; 32])).collect();
+
+        cache.update_cache(keys[0], orca_state(keys[0])).await;
+        cache.update_cache(keys[1], orca_state(keys[1])).await;
+        // Re-updating the first pool makes the second the least recently updated
+        cache.update_cache(keys[0], orca_state(keys[0])).await;
+        cache.update_cache(keys[2], orca_state(keys[2])).await;
+
+        assert_eq!(cache.get_all_entries().await.len(), 2);
+        assert!(cache.read_cache(&keys[1]).await.is_none());
+        assert!(cache.last_updated(&keys[1]).await.is_none());
+        assert!(cache.read_cache(&keys[0]).await.is_some());
+        assert!(cache.read_cache(&keys[2]).await.is_some());
+
+        // Removed entries free up room, and lowering the limit evicts the oldest
+        cache.remove_cache(keys[0]).await;
+        cache.update_cache(keys[1], orca_state(keys[1])).await;
+        assert_eq!(cache.get_all_entries().await.len(), 2);
+        assert_eq!(cache.set_max_entries(Some(1)).await, 1);
+        assert!(cache.read_cache(&keys[2]).await.is_none());
+        assert!(cache.read_cache(&keys[1]).await.is_some());
+    }
+
+    #[test]
+    fn test_orca_fee_matches_account_bytes() {
+        // Whirlpool: discriminator (8), whirlpools_config (32), whirlpool_bump (1),
+        // tick_spacing (2), tick_spacing_seed (2), then fee_rate as a little-endian u16
+        let mut data = [0u8; Whirlpool::LEN];
+        data[45..47].copy_from_slice(&3000u16.to_le_bytes());
+
+        let state = PoolCacheState::OrcaPoolState(OrcaKeyedWhirlpool {
+            pubkey: Pubkey::default(),
+            whirlpool: Whirlpool::from_bytes(&data).unwrap(),
+        });
+
+        assert_eq!(state.fee_config(), None);
+        assert_eq!(state.pool_fee(None), Some(PoolFee::new(3000, 1_000_000)));
+    }
+
+    #[test]
+    fn test_orca_pool_state_matches_account_bytes() {
+        // Whirlpool: tick_spacing at 41, fee_rate at 45, protocol_fee_rate (2), then
+        // liquidity and sqrt_price as little-endian u128s and tick_current_index as an i32
+        let mut data = [0u8; Whirlpool::LEN];
+        data[41..43].copy_from_slice(&64u16.to_le_bytes());
+        data[45..47].copy_from_slice(&3000u16.to_le_bytes());
+        data[49..65].copy_from_slice(&5_000_000u128.to_le_bytes());
+        data[65..81].copy_from_slice(&(1u128 << 64).to_le_bytes());
+        data[81..85].copy_from_slice(&(-128i32).to_le_bytes());
+
+        let state = PoolCacheState::OrcaPoolState(OrcaKeyedWhirlpool {
+            pubkey: Pubkey::default(),
+            whirlpool: Whirlpool::from_bytes(&data).unwrap(),
+        });
+
+        assert_eq!(state.pool_state(), PoolState::Orca(ConcentratedLiquidityState {
+            sqrt_price: 1u128 << 64,
+            tick_current_index: -128,
+            liquidity: 5_000_000,
+            fee_rate: 3000,
+            tick_spacing: 64,
+        }));
+    }
+
+    #[test]
+    fn test_raydium_amm_fee_matches_account_bytes() {
+        // AmmInfo: 16 u64 header fields, then the min_separate, trade_fee and pnl
+        // numerator/denominator pairs, then swap_fee_numerator and swap_fee_denominator
+        let mut data = [0u8; AmmInfo::LEN];
+        data[176..184].copy_from_slice(&25u64.to_le_bytes());
+        data[184..192].copy_from_slice(&10_000u64.to_le_bytes());
+
+        let state = PoolCacheState::RaydiumPoolState(RaydiumKeyedAmmInfo {
+            pubkey: Pubkey::default(),
+            amm_info: AmmInfo::try_from_slice(&data).unwrap(),
+        });
+
+        assert_eq!(state.fee_config(), None);
+        assert_eq!(state.pool_fee(None), Some(PoolFee::new(25, 10_000)));
+    }
+
+    #[test]
+    fn test_raydium_cpmm_fee_matches_config_bytes() {
+        // AmmConfig (after the discriminator): bump (1), disable_create_pool (1),
+        // index (2), then trade_fee_rate as a little-endian u64
+        let mut data = [0u8; CpmmAmmConfig::LEN - 8];
+        data[4..12].copy_from_slice(&2500u64.to_le_bytes());
+
+        let config_key = Pubkey::new_from_array([7; 32]);
+        let config = PoolConfigCacheState::RaydiumCpmmAmmConfigState(CpmmKeyedAmmConfig {
+            pubkey: config_key,
+            amm_config: CpmmAmmConfig::try_from_slice(&data).unwrap(),
+        });
+
+        let mut pool_state: CpmmPoolState = zeroed();
+        pool_state.amm_config = config_key;
+        let state = PoolCacheState::RaydiumCpmmPoolState(RaydiumCpmmKeyedPoolState {
+            pubkey: Pubkey::default(),
+            pool_state,
+        });
+
+        assert_eq!(state.fee_config(), Some(config_key));
+        assert_eq!(state.pool_fee(Some(&config)), Some(PoolFee::new(2500, 1_000_000)));
+        // The fee stays unknown until the config has been indexed
+        assert_eq!(state.pool_fee(None), None);
+    }
+
+    #[test]
+    fn test_raydium_clmm_fee_matches_config_bytes() {
+        // AmmConfig (after the discriminator): bump (1), index (2), owner (32),
+        // protocol_fee_rate (4), then trade_fee_rate as a little-endian u32
+        let mut data = [0u8; ClmmAmmConfig::LEN - 8];
+        data[39..43].copy_from_slice(&100u32.to_le_bytes());
+
+        let config_key = Pubkey::new_from_array([9; 32]);
+        let config = PoolConfigCacheState::RaydiumClmmAmmConfigState(ClmmKeyedAmmConfig {
+            pubkey: config_key,
+            amm_config: ClmmAmmConfig::try_from_slice(&data).unwrap(),
+        });
+
+        let mut pool_state: ClmmPoolState = zeroed();
+        pool_state.amm_config = config_key;
+        let state = PoolCacheState::RaydiumClmmPoolState(RaydiumClmmKeyedPoolState {
+            pubkey: Pubkey::default(),
+            pool_state,
+        });
+
+        assert_eq!(state.fee_config(), Some(config_key));
+        assert_eq!(state.pool_fee(Some(&config)), Some(PoolFee::new(100, 1_000_000)));
+        assert_eq!(config.pool_fee(), Some(PoolFee::from_hundredths_bps(100)));
+    }
+
+    #[tokio::test]
+    async fn test_pool_entries_carry_pool_fee() {
+        let key = Pubkey::new_from_array([3; 32]);
+        let entries = PoolCache::new().to_pool_entries(vec![(key, orca_state(key))]).await;
+
+        let indexed = &entries[0].1;
+        assert_eq!(indexed.fee, Some(PoolFee::from_hundredths_bps(0)));
+        assert!(matches!(indexed.state, PoolState::Orca(_)));
+    }
+
+    fn dlmm_bin_array(lb_pair: Pubkey, index: i64, liquidity: &[(usize, u64, u64)]) -> MeteoraDlmmKeyedBinArray {
+        let mut bins = [zeroed::<Bin>(); MAX_BIN_PER_ARRAY];
+        for &(offset, amount_x, amount_y) in liquidity {
+            bins[offset].amount_x = amount_x;
+            bins[offset].amount_y = amount_y;
+        }
+
+        MeteoraDlmmKeyedBinArray {
+            pubkey: Pubkey::new_unique(),
+            bin_array: MeteoraDlmmBinArray { index, version: 1, padding: [0; 7], lb_pair, bins },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dlmm_entries_carry_the_bins_of_their_bin_arrays() {
+        let cache = PoolCache::new();
+        let pair_key = Pubkey::new_unique();
+        let mut lb_pair = zeroed::<LbPair>();
+        lb_pair.active_id = 75;
+        lb_pair.bin_step = 10;
+        cache.update_cache(pair_key, PoolCacheState::MeteoraDlmmPoolState(MeteoraDlmmKeyedLbPair {
+            pubkey: pair_key,
+            lb_pair,
+        })).await;
+
+        // Arrays arrive in any order; bins come back by id, skipping empty ones
+        cache.update_bin_array(&dlmm_bin_array(pair_key, 1, &[(5, 500, 0), (6, 0, 700)])).await;
+        cache.update_bin_array(&dlmm_bin_array(pair_key, -1, &[(69, 100, 0)])).await;
+        cache.update_bin_array(&dlmm_bin_array(Pubkey::new_unique(), 1, &[(5, 1, 1)])).await;
+
+        let entries = SharedPoolCache::get_all_entries_as_slice(&cache).await;
+        let bins = |amounts: &[(i32, u64, u64)]| -> Vec<BinLiquidity> {
+            amounts.iter().map(|&(bin_id, amount_x, amount_y)| BinLiquidity { bin_id, amount_x, amount_y }).collect()
+        };
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].1.state, PoolState::MeteoraDlmm(DlmmState {
+            active_id: 75,
+            bin_step: 10,
+            fee_rate: 0,
+            bins: bins(&[(-1, 100, 0), (75, 500, 0), (76, 0, 700)]),
+        }));
+
+        // A newer version of an array replaces the old one
+        cache.update_bin_array(&dlmm_bin_array(pair_key, 1, &[(5, 400, 0)])).await;
+        assert_eq!(cache.dlmm_bins(&pair_key).await, bins(&[(-1, 100, 0), (75, 400, 0)]));
+
+        // And the arrays go with their pair
+        cache.remove_cache(pair_key).await;
+        assert!(cache.dlmm_bins(&pair_key).await.is_empty());
+    }
 }
@@ -5,9 +5,13 @@ use tokio::sync::RwLock;
 use spl_pod::solana_pubkey::Pubkey;
 use opentelemetry::global;
 use opentelemetry::trace::Tracer;
+use qtrade_shared_types::PoolFee;
 
 use crate::parser::orca::KeyedWhirlpoolsConfig as OrcaKeyedWhirlpoolsConfig;
+use crate::parser::raydium_clmm::KeyedAmmConfig as RaydiumClmmKeyedAmmConfig;
+use crate::parser::raydium_cpmm::KeyedAmmConfig as RaydiumCpmmKeyedAmmConfig;
 use crate::streamer::Cache;
+use crate::streamer::caches::lru::LruIndex;
 
 // For help in naming spans
 use crate::QTRADE_INDEXER_TRACER_NAME;
@@ -32,6 +36,21 @@ const POOL_CONFIG_CACHE: &str = "streamer::caches::PoolConfigCache";
 #[derive(Debug, Clone)]
 pub enum PoolConfigCacheState {
     OrcaPoolConfigState(OrcaKeyedWhirlpoolsConfig),
+    RaydiumClmmAmmConfigState(RaydiumClmmKeyedAmmConfig),
+    RaydiumCpmmAmmConfigState(RaydiumCpmmKeyedAmmConfig),
+}
+
+impl PoolConfigCacheState {
+    /// Trade fee set by this config, if it is one pools read their fee from
+    ///
+    /// Orca whirlpools carry their own fee rate, so their config has none.
+    pub fn pool_fee(&self) -> Option<PoolFee> {
+        match self {
+            PoolConfigCacheState::OrcaPoolConfigState(_) => None,
+            PoolConfigCacheState::RaydiumClmmAmmConfigState(config) => Some(config.amm_config.pool_fee()),
+            PoolConfigCacheState::RaydiumCpmmAmmConfigState(config) => Some(config.amm_config.pool_fee()),
+        }
+    }
 }
 
 // Reference:
@@ -43,6 +62,8 @@ pub struct PoolConfigCache {
 
 struct PoolConfigCacheInner {
     data: DashMap<Pubkey, PoolConfigCacheState>,
+    // Update order of the keys in `data`, bounding how many are kept
+    lru: LruIndex,
 }
 
 impl PoolConfigCache {
@@ -51,9 +72,24 @@ impl PoolConfigCache {
         Self {
             inner: Arc::new(RwLock::new(PoolConfigCacheInner {
                 data: DashMap::new(),
+                lru: LruIndex::new(),
             }))
         }
     }
+
+    /// Bound the cache to `max_entries` entries (`None` for unbounded)
+    ///
+    /// Past the limit, the least recently updated entries are evicted. Returns the
+    /// number of entries evicted to fit the new limit.
+    pub async fn set_max_entries(&self, max_entries: Option<usize>) -> usize {
+        let mut cache_write = self.inner.write().await;
+        let evicted = cache_write.lru.set_max_entries(max_entries);
+        for key in &evicted {
+            cache_write.data.remove(key);
+        }
+
+        evicted.len()
+    }
 }
 
 impl Cache<Pubkey, PoolConfigCacheState> for PoolConfigCache {
@@ -165,8 +201,13 @@ impl Cache<Pubkey, PoolConfigCacheState> for PoolConfigCache {
             // 1. Make sure not to hold RwLockWriteGuard across await points
             // 2. Make sure not to hold any reference to dashmap
             let cache_result = {
-                let cache_write = self.inner.write().await;
-                cache_write.data.insert(key, value)
+                let mut cache_write = self.inner.write().await;
+                let previous = cache_write.data.insert(key, value);
+                let evicted = cache_write.lru.touch(key);
+                for evicted_key in &evicted {
+                    cache_write.data.remove(evicted_key);
+                }
+                previous
             };
 
             cache_result
@@ -184,7 +225,8 @@ impl Cache<Pubkey, PoolConfigCacheState> for PoolConfigCache {
             // 1. Make sure not to hold RwLockWriteGuard across await points
             // 2. Make sure not to hold any reference to dashmap
             let cache_result = {
-                let cache_write = self.inner.write().await;
+                let mut cache_write = self.inner.write().await;
+                cache_write.lru.remove(&key);
                 cache_write.data.remove(&key)
             };
 
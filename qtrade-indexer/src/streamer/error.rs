@@ -0,0 +1,95 @@
+//! Classification of the ways the Geyser stream can fail.
+//!
+//! A dropped connection or a server error is worth reconnecting for, while a config the
+//! stream can't start with fails the same way on every attempt. `run_with_reconnect`
+//! retries the former and stops on the latter, so the runtime only ever sees fatal errors.
+//!
+//! Vixen doesn't expose why its Yellowstone client couldn't be built, so the config is
+//! checked with [`validate_yellowstone_config`] before the runtime is, and every error
+//! the runtime returns is transient.
+
+use std::error::Error as StdError;
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_vixen as vixen;
+use yellowstone_vixen::config::YellowstoneConfig;
+
+/// Why the Geyser stream stopped
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum StreamerError {
+    /// The stream can't run as configured; retrying won't help
+    #[error("Streamer configuration error: {0}")]
+    Config(String),
+    /// The stream dropped or the server returned an error; reconnecting may help
+    #[error("Geyser stream error: {0}")]
+    Transient(String),
+}
+
+impl StreamerError {
+    /// Whether the streamer should stop instead of reconnecting
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, StreamerError::Config(_))
+    }
+}
+
+/// An error with its sources, e.g. "Yellowstone gRPC error: Yellowstone client error: ..."
+fn with_sources(error: &dyn StdError) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(inner) = source {
+        message.push_str(": ");
+        message.push_str(&inner.to_string());
+        source = inner.source();
+    }
+    message
+}
+
+/// Check that a Yellowstone client can be built from `config`, before the runtime tries
+///
+/// Runs the builder steps vixen runs on every connect, so an invalid endpoint or token is
+/// a [`StreamerError::Config`] rather than a failure that is retried forever.
+pub fn validate_yellowstone_config(config: &YellowstoneConfig) -> Result<(), StreamerError> {
+    GeyserGrpcClient::build_from_shared(config.endpoint.clone())
+        .and_then(|builder| builder.x_token(config.x_token.clone()))
+        .map(|_| ())
+        .map_err(|e| StreamerError::Config(format!("Invalid Yellowstone config: {}", with_sources(&e))))
+}
+
+impl From<vixen::Error> for StreamerError {
+    fn from(error: vixen::Error) -> Self {
+        StreamerError::Transient(with_sources(&error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vixen_stream_errors_are_transient() {
+        let hangup = StreamerError::from(vixen::Error::ServerHangup);
+        assert!(!hangup.is_fatal());
+
+        let io = StreamerError::from(vixen::Error::Io(std::io::Error::other("connection reset")));
+        assert_eq!(io, StreamerError::Transient("I/O error: connection reset".to_string()));
+
+        assert!(StreamerError::Config("missing vixen config".to_string()).is_fatal());
+    }
+
+    #[test]
+    fn test_invalid_yellowstone_config_is_a_config_error() {
+        let config = |endpoint: &str, x_token: Option<&str>| YellowstoneConfig {
+            endpoint: endpoint.to_string(),
+            x_token: x_token.map(str::to_string),
+            timeout: 60,
+        };
+
+        assert_eq!(validate_yellowstone_config(&config("http://127.0.0.1:10000", Some("token"))), Ok(()));
+
+        let endpoint = validate_yellowstone_config(&config("not a url", None)).unwrap_err();
+        assert!(matches!(&endpoint, StreamerError::Config(message) if message.starts_with("Invalid Yellowstone config")));
+        assert!(endpoint.is_fatal());
+
+        let token = validate_yellowstone_config(&config("http://127.0.0.1:10000", Some("bad\ntoken"))).unwrap_err();
+        assert!(token.is_fatal());
+    }
+}
@@ -0,0 +1,87 @@
+use std::any::Any;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use opentelemetry::global;
+use opentelemetry::metrics::ObservableCounter;
+use opentelemetry::trace::Tracer;
+use tracing::{debug, warn};
+use yellowstone_vixen::{self as vixen};
+
+use crate::POOL_CACHE;
+use crate::parser::meteora_dlmm::MeteoraDlmmProgramState;
+use crate::streamer::Cache;
+use crate::streamer::PoolCacheState;
+
+// For help in naming spans
+use crate::QTRADE_INDEXER_TRACER_NAME;
+use crate::QTRADE_INDEXER_METER;
+const METEORA_DLMM_HANDLER: &str = "streamer::handlers::MeteoraDlmmHandler";
+
+#[derive(Debug)]
+pub struct MeteoraDlmmHandler {
+    cache_hits: Arc<AtomicU64>,
+    cache_hits_instrument: ObservableCounter<u64>
+}
+
+impl MeteoraDlmmHandler {
+    pub fn new() -> Self {
+        let cache_hits = Arc::new(AtomicU64::new(0));
+        let cache_hits_clone = Arc::clone(&cache_hits);
+
+        let cache_hits_instrument = QTRADE_INDEXER_METER
+            .u64_observable_counter("meteora_dlmm_cache_hits")
+            .with_description("Records cache hits for Meteora DLMM pool events")
+            .with_unit("hits/minute")
+            .with_callback(move |observer| {
+                // Load the current value of cache_hits
+                let hits = cache_hits_clone.load(Ordering::Relaxed);
+                // Observe the current value
+                observer.observe(hits, &[]);
+                // Reset cache_hits to 0
+                cache_hits_clone.store(0, Ordering::Relaxed);
+            })
+            .build();
+
+        MeteoraDlmmHandler {
+            cache_hits,
+            cache_hits_instrument,
+        }
+    }
+}
+
+impl<V: std::fmt::Debug + Sync + Any> vixen::Handler<V> for MeteoraDlmmHandler {
+    async fn handle(&self, value: &V) -> vixen::HandlerResult<()> {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::handle", METEORA_DLMM_HANDLER);
+
+        let result = tracer.in_span(span_name, |_cx| async move {
+            debug!(?value);
+
+            if let Some(meteora_program_state) = (value as &dyn Any).downcast_ref::<MeteoraDlmmProgramState>() {
+                match meteora_program_state {
+                    MeteoraDlmmProgramState::LbPair(keyed_lb_pair) => {
+                        // The pair carries its own fee parameters, so it can be quoted without a config
+                        let pool_cache_state = PoolCacheState::MeteoraDlmmPoolState(keyed_lb_pair.clone());
+                        POOL_CACHE.update_cache(keyed_lb_pair.pubkey, pool_cache_state).await;
+
+                        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+                    }
+                    MeteoraDlmmProgramState::BinArray(keyed_bin_array) => {
+                        debug!("Processing BinArray {} (index {})",
+                            keyed_bin_array.pubkey, keyed_bin_array.bin_array.index);
+                        // Kept with its pair, so the router sees the pair's liquidity
+                        POOL_CACHE.update_bin_array(keyed_bin_array).await;
+
+                        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            } else {
+                warn!("Value is not a MeteoraDlmmProgramState");
+            }
+
+            Ok(())
+        }).await;
+
+        result
+    }
+}
@@ -1,4 +1,7 @@
+pub mod meteora_dlmm_handler;
 pub mod orca_handler;
+pub mod phoenix_handler;
+pub mod pyth_handler;
 pub mod raydium_clmm_handler;
 pub mod raydium_cpmm_handler;
 pub mod raydium_handler;
@@ -0,0 +1,79 @@
+use std::any::Any;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use opentelemetry::global;
+use opentelemetry::metrics::ObservableCounter;
+use opentelemetry::trace::Tracer;
+use tracing::{debug, warn};
+use yellowstone_vixen::{self as vixen};
+
+use crate::POOL_CACHE;
+use crate::parser::phoenix::PhoenixProgramState;
+use crate::streamer::Cache;
+use crate::streamer::PoolCacheState;
+
+// For help in naming spans
+use crate::QTRADE_INDEXER_TRACER_NAME;
+use crate::QTRADE_INDEXER_METER;
+const PHOENIX_HANDLER: &str = "streamer::handlers::PhoenixHandler";
+
+#[derive(Debug)]
+pub struct PhoenixHandler {
+    cache_hits: Arc<AtomicU64>,
+    cache_hits_instrument: ObservableCounter<u64>
+}
+
+impl PhoenixHandler {
+    pub fn new() -> Self {
+        let cache_hits = Arc::new(AtomicU64::new(0));
+        let cache_hits_clone = Arc::clone(&cache_hits);
+
+        let cache_hits_instrument = QTRADE_INDEXER_METER
+            .u64_observable_counter("phoenix_cache_hits")
+            .with_description("Records cache hits for Phoenix market events")
+            .with_unit("hits/minute")
+            .with_callback(move |observer| {
+                // Load the current value of cache_hits
+                let hits = cache_hits_clone.load(Ordering::Relaxed);
+                // Observe the current value
+                observer.observe(hits, &[]);
+                // Reset cache_hits to 0
+                cache_hits_clone.store(0, Ordering::Relaxed);
+            })
+            .build();
+
+        PhoenixHandler {
+            cache_hits,
+            cache_hits_instrument,
+        }
+    }
+}
+
+impl<V: std::fmt::Debug + Sync + Any> vixen::Handler<V> for PhoenixHandler {
+    async fn handle(&self, value: &V) -> vixen::HandlerResult<()> {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::handle", PHOENIX_HANDLER);
+
+        let result = tracer.in_span(span_name, |_cx| async move {
+            debug!(?value);
+
+            if let Some(phoenix_program_state) = (value as &dyn Any).downcast_ref::<PhoenixProgramState>() {
+                match phoenix_program_state {
+                    PhoenixProgramState::Market(keyed_market) => {
+                        // The market header carries its lot sizes and taker fee, so it can be quoted without a config
+                        let pool_cache_state = PoolCacheState::PhoenixPoolState(*keyed_market);
+                        POOL_CACHE.update_cache(keyed_market.pubkey, pool_cache_state).await;
+
+                        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            } else {
+                warn!("Value is not a PhoenixProgramState");
+            }
+
+            Ok(())
+        }).await;
+
+        result
+    }
+}
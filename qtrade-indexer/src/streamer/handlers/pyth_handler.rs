@@ -0,0 +1,78 @@
+use std::any::Any;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use opentelemetry::global;
+use opentelemetry::metrics::ObservableCounter;
+use opentelemetry::trace::Tracer;
+use tracing::{debug, warn};
+use yellowstone_vixen::{self as vixen};
+
+use crate::PRICE_CACHE;
+use crate::parser::pyth::PythProgramState;
+use crate::streamer::PriceCacheState;
+
+// For help in naming spans
+use crate::QTRADE_INDEXER_TRACER_NAME;
+use crate::QTRADE_INDEXER_METER;
+const PYTH_HANDLER: &str = "streamer::handlers::PythHandler";
+
+#[derive(Debug)]
+pub struct PythHandler {
+    cache_hits: Arc<AtomicU64>,
+    cache_hits_instrument: ObservableCounter<u64>
+}
+
+impl PythHandler {
+    pub fn new() -> Self {
+        let cache_hits = Arc::new(AtomicU64::new(0));
+        let cache_hits_clone = Arc::clone(&cache_hits);
+
+        let cache_hits_instrument = QTRADE_INDEXER_METER
+            .u64_observable_counter("pyth_price_cache_hits")
+            .with_description("Records cache hits for Pyth price feed events")
+            .with_unit("hits/minute")
+            .with_callback(move |observer| {
+                // Load the current value of cache_hits
+                let hits = cache_hits_clone.load(Ordering::Relaxed);
+                // Observe the current value
+                observer.observe(hits, &[]);
+                // Reset cache_hits to 0
+                cache_hits_clone.store(0, Ordering::Relaxed);
+            })
+            .build();
+
+        PythHandler {
+            cache_hits,
+            cache_hits_instrument,
+        }
+    }
+}
+
+impl<V: std::fmt::Debug + Sync + Any> vixen::Handler<V> for PythHandler {
+    async fn handle(&self, value: &V) -> vixen::HandlerResult<()> {
+        let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
+        let span_name = format!("{}::handle", PYTH_HANDLER);
+
+        let result = tracer.in_span(span_name, |_cx| async move {
+            debug!(?value);
+
+            if let Some(pyth_program_state) = (value as &dyn Any).downcast_ref::<PythProgramState>() {
+                match pyth_program_state {
+                    PythProgramState::Price(keyed_price) => {
+                        // Updates can arrive out of order; never replace a price with an older one
+                        let price_cache_state = PriceCacheState::PythPriceState(keyed_price.clone());
+                        if PRICE_CACHE.update_if_newer(keyed_price.pubkey, price_cache_state).await {
+                            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                }
+            } else {
+                warn!("Value is not a PythProgramState");
+            }
+
+            Ok(())
+        }).await;
+
+        result
+    }
+}
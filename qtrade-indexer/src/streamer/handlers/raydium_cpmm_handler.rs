@@ -8,6 +8,9 @@ use tracing::{debug, warn};
 use yellowstone_vixen::{self as vixen};
 
 use crate::parser::raydium_cpmm::RaydiumProgramState as RaydiumCpmmProgramState;
+use crate::POOL_CONFIG_CACHE;
+use crate::streamer::Cache;
+use crate::streamer::PoolConfigCacheState;
 
 // For help in naming spans
 use crate::QTRADE_INDEXER_TRACER_NAME;
@@ -56,8 +59,12 @@ impl<V: std::fmt::Debug + Sync + Any> vixen::Handler<V> for RaydiumCpmmHandler {
 
             if let Some(raydium_program_state) = (value as &dyn Any).downcast_ref::<RaydiumCpmmProgramState>() {
                 match raydium_program_state {
-                    RaydiumCpmmProgramState::AmmConfig(amm_config) => {
-                        debug!("Processing AmmConfig: {:?}", amm_config);
+                    RaydiumCpmmProgramState::AmmConfig(keyed_amm_config) => {
+                        debug!("Processing AmmConfig: {:?}", keyed_amm_config);
+
+                        // Pools look up their trade fee by the config's address
+                        let pool_config_cache_state = PoolConfigCacheState::RaydiumCpmmAmmConfigState(keyed_amm_config.clone());
+                        POOL_CONFIG_CACHE.update_cache(keyed_amm_config.pubkey, pool_config_cache_state).await;
                     }
                     RaydiumCpmmProgramState::ObservationState(observation_state) => {
                         debug!("Processing ObservationState: {:?}", observation_state);
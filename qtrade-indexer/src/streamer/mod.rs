@@ -12,24 +12,39 @@ use anyhow::Result;
 use opentelemetry::global;
 use opentelemetry::trace::Tracer;
 use std::fs;
-use tracing::info;
+use std::time::Duration;
+use tokio::try_join;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
 use yellowstone_vixen::{self as vixen, Pipeline};
 use yellowstone_vixen::config::{NullConfig, VixenConfig };
 
+use crate::parser::meteora_dlmm::AccountParser as MeteoraDlmmAccParser;
 use crate::parser::orca::AccountParser as OrcaAccParser;
+use crate::parser::phoenix::AccountParser as PhoenixAccParser;
+use crate::parser::pyth::AccountParser as PythAccParser;
 use crate::parser::raydium::AccountParser as RaydiumAccParser;
 use crate::parser::raydium_clmm::AccountParser as RaydiumClmmAccParser;
 use crate::parser::raydium_cpmm::AccountParser as RaydiumCpmmAccParser;
 
+use crate::streamer::handlers::meteora_dlmm_handler::MeteoraDlmmHandler;
 use crate::streamer::handlers::orca_handler::OrcaHandler;
+use crate::streamer::handlers::phoenix_handler::PhoenixHandler;
+use crate::streamer::handlers::pyth_handler::PythHandler;
 use crate::streamer::handlers::raydium_handler::RaydiumHandler;
 use crate::streamer::handlers::raydium_clmm_handler::RaydiumClmmHandler;
 use crate::streamer::handlers::raydium_cpmm_handler::RaydiumCpmmHandler;
 
+pub mod backfill;
 mod caches;
+pub mod error;
 mod handlers;
+pub mod reconnect;
+
+pub use error::StreamerError;
 
 pub use caches::mint_cache::*;
+pub use caches::oracle_cache::*;
 pub use caches::pool_cache::*;
 pub use caches::pool_config_cache::*;
 
@@ -70,17 +85,23 @@ where
 /// and processes the streamed data to update the accounts database (acctsdb) cache
 /// and the pool reserves cache.
 ///
+/// If the Geyser connection drops, the runtime is rebuilt and the stream reconnected
+/// with capped exponential backoff until the cancellation token fires. A configuration
+/// error stops the streamer instead, returning a [`StreamerError::Config`].
+///
 /// # Arguments
 ///
 /// * `settings` - Optional indexer settings that control which DEX platforms to index
 ///   and the path to the vixen configuration file
-pub async fn run_streamer(settings: Option<crate::settings::IndexerSettings>) -> Result<()> {
+/// * `cancellation_token` - Token used to stop the streamer (and any pending reconnect)
+pub async fn run_streamer(
+    settings: Option<crate::settings::IndexerSettings>,
+    cancellation_token: CancellationToken,
+) -> Result<()> {
     let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
     let span_name = format!("{}::run_streamer", STREAMER);
 
     let result = tracer.in_span(span_name, |_cx| async move {
-        info!("Connecting to Geyser stream...");
-
         // Use provided settings or create default settings
         let settings = settings.unwrap_or_default();
 
@@ -88,88 +109,152 @@ pub async fn run_streamer(settings: Option<crate::settings::IndexerSettings>) ->
         info!("Active DEX platforms for indexing: {:?}", settings.active_dexes);
         info!("Using vixen config from: {}", settings.vixen_config_path);
 
-        // TODO: Confirm with bare metal geyser if this is still valid
-        // Note: Cannot setup multiple filters as the current geyser
-        //       we connect to is limited to 1 filter per connection
-        //
-
-        let config = read_and_parse_config(&settings.vixen_config_path)?;
-
-        // Build the runtime based on active DEX settings
-        let mut builder = vixen::Runtime::builder();
-
-        // Only add parsers for active DEXes
-        if settings.is_dex_active("orca") {
-            info!("Adding Orca parser to streamer");
-            builder = builder.account(Pipeline::new(OrcaAccParser, [OrcaHandler::new()]));
+        // Keep long-running indexers from growing the caches without bound
+        if let Some(max_entries) = settings.max_cache_entries {
+            info!("Bounding indexer caches to {} entries each", max_entries);
         }
+        crate::POOL_CACHE.set_max_entries(settings.max_cache_entries).await;
+        crate::POOL_CONFIG_CACHE.set_max_entries(settings.max_cache_entries).await;
+        crate::MINT_CACHE.set_max_entries(settings.max_cache_entries).await;
 
-        if settings.is_dex_active("raydium") {
-            info!("Adding Raydium parser to streamer");
-            builder = builder.account(Pipeline::new(RaydiumAccParser, [RaydiumHandler::new()]));
-        }
+        // Evict pools that stop ticking while the stream runs
+        let pool_cache_ttl = Duration::from_secs(settings.pool_cache_ttl_secs);
+        let eviction_future = crate::POOL_CACHE.run_eviction(pool_cache_ttl, cancellation_token.clone());
 
-        if settings.is_dex_active("raydium-clmm") {
-            info!("Adding Raydium CLMM parser to streamer");
-            builder = builder.account(Pipeline::new(RaydiumClmmAccParser, [RaydiumClmmHandler::new()]));
-        }
+        // Prime the pool cache alongside the stream so the router doesn't start from nothing
+        let backfill_future = run_startup_backfill(&settings, cancellation_token.clone());
 
-        if settings.is_dex_active("raydium-cpmm") {
-            info!("Adding Raydium CPMM parser to streamer");
-            builder = builder.account(Pipeline::new(RaydiumCpmmAccParser, [RaydiumCpmmHandler::new()]));
-        }
+        let stream_future = reconnect::run_with_reconnect(
+            || connect_and_stream(settings.clone()),
+            reconnect::ReconnectBackoff::default(),
+            cancellation_token,
+        );
 
-        // Build and run the runtime with the configured parsers
-        let result = builder
-            .build(config)
-            .try_run_async()
-            .await
-            .map_err(|e| {
-                // TODO: Populate anyhow error from yellowstone error
-                anyhow::anyhow!("Yellowstone error: {}", e)
-            });
-
-        result
-
-        /* See TODO note above
-        let config = read_and_parse_config(&settings.vixen_config_path)?;
-        let orca_acc_parser = vixen::Runtime::builder()
-            .account(Pipeline::new(OrcaAccParser, [OrcaHandler::new()]))
-            .build(config)
-            .try_run_async();
-
-        let config = read_and_parse_config(&settings.vixen_config_path)?;
-        let raydium_acc_parser = vixen::Runtime::builder()
-            .account(Pipeline::new(RaydiumAccParser, [RaydiumHandler]))
-            .build(config)
-            .try_run_async();
-
-        let config = read_and_parse_config(&settings.vixen_config_path)?;
-        let raydium_clmm_acc_parser = vixen::Runtime::builder()
-            .account(Pipeline::new(RaydiumClmmAccParser, [RaydiumClmmHandler]))
-            .build(config)
-            .try_run_async();
-
-        let config = read_and_parse_config(&settings.vixen_config_path)?;
-        let raydium_cpmm_acc_parser = vixen::Runtime::builder()
-            .account(Pipeline::new(RaydiumCpmmAccParser, [RaydiumCpmmHandler]))
-            .build(config)
-            .try_run_async();
-
-        try_join!(
-            orca_acc_parser,
-            raydium_acc_parser,
-            raydium_clmm_acc_parser,
-            raydium_cpmm_acc_parser
-        )?;
+        try_join!(stream_future, eviction_future, backfill_future)?;
 
         Ok(())
-        */
     }).await;
 
     result
 }
 
+/// Backfill pool state if a backfill RPC endpoint is configured, marking the pool cache ready either way
+///
+/// A failed backfill is logged rather than returned, so it never takes the stream down.
+async fn run_startup_backfill(
+    settings: &crate::settings::IndexerSettings,
+    cancellation_token: CancellationToken,
+) -> Result<()> {
+    let Some(rpc_url) = settings.backfill_rpc_url.clone() else {
+        info!("No backfill RPC configured, pool cache fills from the stream");
+        backfill::mark_pool_cache_ready();
+        return Ok(());
+    };
+
+    info!("Backfilling pool state from {}", rpc_url);
+    let source = backfill::RpcProgramAccounts::new(rpc_url);
+    if let Err(e) = backfill::run_backfill(settings, &source, cancellation_token).await {
+        warn!("Pool backfill failed, pool cache fills from the stream: {:?}", e);
+    }
+
+    Ok(())
+}
+
+/// Build the vixen runtime for the active DEXes and run it until the stream ends
+async fn connect_and_stream(settings: crate::settings::IndexerSettings) -> Result<(), StreamerError> {
+    info!("Connecting to Geyser stream...");
+
+    // TODO: Confirm with bare metal geyser if this is still valid
+    // Note: Cannot setup multiple filters as the current geyser
+    //       we connect to is limited to 1 filter per connection
+    //
+
+    let config = read_and_parse_config(&settings.vixen_config_path)
+        .map_err(|e| StreamerError::Config(e.to_string()))?;
+    error::validate_yellowstone_config(&config.yellowstone)?;
+
+    // Build the runtime based on active DEX settings
+    let mut builder = vixen::Runtime::builder();
+
+    // Only add parsers for active DEXes
+    if settings.is_dex_active("orca") {
+        info!("Adding Orca parser to streamer");
+        builder = builder.account(Pipeline::new(OrcaAccParser, [OrcaHandler::new()]));
+    }
+
+    if settings.is_dex_active("raydium") {
+        info!("Adding Raydium parser to streamer");
+        builder = builder.account(Pipeline::new(RaydiumAccParser, [RaydiumHandler::new()]));
+    }
+
+    if settings.is_dex_active("raydium-clmm") {
+        info!("Adding Raydium CLMM parser to streamer");
+        builder = builder.account(Pipeline::new(RaydiumClmmAccParser, [RaydiumClmmHandler::new()]));
+    }
+
+    if settings.is_dex_active("raydium-cpmm") {
+        info!("Adding Raydium CPMM parser to streamer");
+        builder = builder.account(Pipeline::new(RaydiumCpmmAccParser, [RaydiumCpmmHandler::new()]));
+    }
+
+    if settings.is_dex_active("meteora-dlmm") {
+        info!("Adding Meteora DLMM parser to streamer");
+        builder = builder.account(Pipeline::new(MeteoraDlmmAccParser, [MeteoraDlmmHandler::new()]));
+    }
+
+    if settings.is_dex_active("phoenix") {
+        info!("Adding Phoenix parser to streamer");
+        builder = builder.account(Pipeline::new(PhoenixAccParser, [PhoenixHandler::new()]));
+    }
+
+    if settings.index_price_feeds {
+        info!("Adding Pyth price feed parser to streamer");
+        builder = builder.account(Pipeline::new(PythAccParser, [PythHandler::new()]));
+    }
+
+    // Build and run the runtime with the configured parsers
+    builder
+        .build(config)
+        .try_run_async()
+        .await
+        .map_err(StreamerError::from)
+
+    /* See TODO note above
+    let config = read_and_parse_config(&settings.vixen_config_path)?;
+    let orca_acc_parser = vixen::Runtime::builder()
+        .account(Pipeline::new(OrcaAccParser, [OrcaHandler::new()]))
+        .build(config)
+        .try_run_async();
+
+    let config = read_and_parse_config(&settings.vixen_config_path)?;
+    let raydium_acc_parser = vixen::Runtime::builder()
+        .account(Pipeline::new(RaydiumAccParser, [RaydiumHandler]))
+        .build(config)
+        .try_run_async();
+
+    let config = read_and_parse_config(&settings.vixen_config_path)?;
+    let raydium_clmm_acc_parser = vixen::Runtime::builder()
+        .account(Pipeline::new(RaydiumClmmAccParser, [RaydiumClmmHandler]))
+        .build(config)
+        .try_run_async();
+
+    let config = read_and_parse_config(&settings.vixen_config_path)?;
+    let raydium_cpmm_acc_parser = vixen::Runtime::builder()
+        .account(Pipeline::new(RaydiumCpmmAccParser, [RaydiumCpmmHandler]))
+        .build(config)
+        .try_run_async();
+
+    try_join!(
+        orca_acc_parser,
+        raydium_acc_parser,
+        raydium_clmm_acc_parser,
+        raydium_cpmm_acc_parser
+    )?;
+
+    Ok(())
+    */
+}
+
 fn read_and_parse_config(path: &str) -> Result<VixenConfig<NullConfig>> {
     let tracer = global::tracer(QTRADE_INDEXER_TRACER_NAME);
     let span_name = format!("{}::read_and_parse_config", STREAMER);
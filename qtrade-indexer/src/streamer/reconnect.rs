@@ -0,0 +1,230 @@
+//! Reconnect loop for the Geyser stream.
+//!
+//! The vixen runtime's `try_run_async` returns as soon as the Geyser connection drops.
+//! `run_with_reconnect` re-runs the stream with capped exponential backoff so a dropped
+//! connection doesn't take the indexer (and arbitrage with it) down. Configuration errors
+//! are the exception: they'd fail every attempt, so they stop the loop.
+
+use anyhow::Result;
+use lazy_static::lazy_static;
+use opentelemetry::metrics::Counter;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+use crate::streamer::StreamerError;
+use crate::QTRADE_INDEXER_METER;
+
+/// Delay before the first reconnect attempt
+pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Upper bound on the delay between reconnect attempts
+pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+lazy_static! {
+    static ref STREAMER_RECONNECTS: Counter<u64> = QTRADE_INDEXER_METER
+        .u64_counter("qtrade.indexer.streamer_reconnects")
+        .with_description("Number of Geyser stream reconnect attempts")
+        .build();
+}
+
+static TOTAL_RECONNECTS: AtomicU64 = AtomicU64::new(0);
+
+/// Total reconnect attempts since startup
+pub fn total_reconnects() -> u64 {
+    TOTAL_RECONNECTS.load(Ordering::Relaxed)
+}
+
+fn record_reconnect() {
+    TOTAL_RECONNECTS.fetch_add(1, Ordering::Relaxed);
+    STREAMER_RECONNECTS.add(1, &[]);
+}
+
+/// Capped exponential backoff between reconnect attempts
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectBackoff {
+    pub initial: Duration,
+    pub max: Duration,
+}
+
+impl ReconnectBackoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self { initial, max }
+    }
+
+    /// Delay before reconnect attempt `attempt` (1-based): initial * 2^(attempt - 1), capped at max
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        self.initial
+            .checked_mul(1u32 << exponent)
+            .unwrap_or(self.max)
+            .min(self.max)
+    }
+}
+
+impl Default for ReconnectBackoff {
+    fn default() -> Self {
+        Self::new(DEFAULT_INITIAL_BACKOFF, DEFAULT_MAX_BACKOFF)
+    }
+}
+
+/// Run `run_once` until the cancellation token fires, reconnecting whenever it returns
+///
+/// Each time the stream ends (with a transient error or otherwise) a reconnect is logged
+/// and counted, then retried after the backoff delay. A connection that stayed up for
+/// longer than the maximum backoff resets the backoff to its initial delay.
+///
+/// A fatal [`StreamerError`] is returned as is (downcast it from the `anyhow::Error`).
+pub async fn run_with_reconnect<F, Fut>(
+    mut run_once: F,
+    backoff: ReconnectBackoff,
+    cancellation_token: CancellationToken,
+) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), StreamerError>>,
+{
+    let mut attempt: u32 = 0;
+
+    loop {
+        if cancellation_token.is_cancelled() {
+            info!("Cancellation token activated, stopping Geyser stream");
+            return Ok(());
+        }
+
+        let started = Instant::now();
+        tokio::select! {
+            _ = cancellation_token.cancelled() => {
+                info!("Cancellation token activated, stopping Geyser stream");
+                return Ok(());
+            }
+            result = run_once() => match result {
+                Ok(()) => warn!("Geyser stream ended"),
+                Err(e) if e.is_fatal() => {
+                    error!("Geyser stream can't run, not reconnecting: {}", e);
+                    qtrade_shared_types::HEALTH_STATUS.set_streamer_connected(false);
+                    return Err(e.into());
+                }
+                Err(e) => warn!("Geyser stream failed: {}", e),
+            }
+        }
+
+        qtrade_shared_types::HEALTH_STATUS.set_streamer_connected(false);
+
+        if started.elapsed() > backoff.max {
+            attempt = 0;
+        }
+        attempt = attempt.saturating_add(1);
+        record_reconnect();
+
+        let delay = backoff.delay(attempt);
+        info!("Reconnecting to Geyser stream (attempt {}) in {:?}", attempt, delay);
+
+        tokio::select! {
+            _ = cancellation_token.cancelled() => {
+                info!("Cancellation token activated, stopping Geyser stream");
+                return Ok(());
+            }
+            _ = sleep(delay) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        let backoff = ReconnectBackoff::new(Duration::from_secs(1), Duration::from_secs(10));
+        assert_eq!(backoff.delay(1), Duration::from_secs(1));
+        assert_eq!(backoff.delay(2), Duration::from_secs(2));
+        assert_eq!(backoff.delay(4), Duration::from_secs(8));
+        assert_eq!(backoff.delay(5), Duration::from_secs(10));
+        assert_eq!(backoff.delay(100), Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_after_run_error() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let token = CancellationToken::new();
+        let reconnects_before = total_reconnects();
+
+        let run_once = {
+            let attempts = Arc::clone(&attempts);
+            let token = token.clone();
+            move || {
+                let attempts = Arc::clone(&attempts);
+                let token = token.clone();
+                async move {
+                    // Fail twice, then stop the loop from inside the third run
+                    if attempts.fetch_add(1, Ordering::SeqCst) >= 2 {
+                        token.cancel();
+                    }
+                    Err(StreamerError::Transient("simulated Geyser disconnect".to_string()))
+                }
+            }
+        };
+
+        let backoff = ReconnectBackoff::new(Duration::from_millis(1), Duration::from_millis(5));
+        run_with_reconnect(run_once, backoff, token.clone()).await.unwrap();
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        // The third failure may race with the cancellation, so only the first two are guaranteed
+        assert!(total_reconnects() >= reconnects_before + 2);
+    }
+
+    #[tokio::test]
+    async fn test_transient_error_does_not_abort_join() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let token = CancellationToken::new();
+
+        let run_once = {
+            let attempts = Arc::clone(&attempts);
+            move || {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async { Err(StreamerError::Transient("simulated Geyser disconnect".to_string())) }
+            }
+        };
+        let backoff = ReconnectBackoff::new(Duration::from_millis(1), Duration::from_millis(5));
+
+        // A sibling task in the same join, as the runtime runs the indexer next to the router
+        let sibling = {
+            let attempts = Arc::clone(&attempts);
+            let token = token.clone();
+            async move {
+                while attempts.load(Ordering::SeqCst) < 3 {
+                    sleep(Duration::from_millis(1)).await;
+                }
+                token.cancel();
+                Ok::<_, anyhow::Error>("sibling finished")
+            }
+        };
+
+        let (_, sibling_result) = tokio::try_join!(run_with_reconnect(run_once, backoff, token.clone()), sibling).unwrap();
+        assert_eq!(sibling_result, "sibling finished");
+        assert!(attempts.load(Ordering::SeqCst) >= 3);
+    }
+
+    #[tokio::test]
+    async fn test_config_error_stops_without_reconnecting() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let run_once = {
+            let attempts = Arc::clone(&attempts);
+            move || {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async { Err(StreamerError::Config("Error reading config file".to_string())) }
+            }
+        };
+
+        let backoff = ReconnectBackoff::new(Duration::from_millis(1), Duration::from_millis(5));
+        let error = run_with_reconnect(run_once, backoff, CancellationToken::new()).await.unwrap_err();
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert!(error.downcast_ref::<StreamerError>().is_some_and(StreamerError::is_fatal));
+    }
+}
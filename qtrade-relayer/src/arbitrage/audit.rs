@@ -0,0 +1,215 @@
+//! Module for auditing the swaps of an arbitrage before it is submitted
+//!
+//! Each swap leg is logged with its decoded parameters: DEX, pool, mints, amounts and
+//! direction. With `swap_audit_path` set, the same records are also appended to that file
+//! as JSON lines for post-mortems. Only public pool and mint addresses are recorded,
+//! never keypairs or the accounts of the explorer key signing the swaps.
+
+use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::arbitrage::prepare::ArbitrageSwapParams;
+use crate::settings::RelayerSettings;
+
+/// Decoded parameters of one swap instruction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwapAuditRecord {
+    /// Correlation id of the arbitrage the swap belongs to
+    pub correlation_id: Uuid,
+    pub pool_index: usize,
+    pub dex_type: String,
+    pub pool: String,
+    pub input_mint: String,
+    pub output_mint: String,
+    pub amount_in: u64,
+    pub min_amount_out: u64,
+    /// `a_to_b` or `b_to_a`
+    pub direction: String,
+    pub exact_input: bool,
+}
+
+impl SwapAuditRecord {
+    /// Record of the swap built from `params`
+    ///
+    /// Swaps are always built from token A to token B with an exact input amount.
+    pub fn from_params(correlation_id: Uuid, params: &ArbitrageSwapParams) -> Self {
+        Self {
+            correlation_id,
+            pool_index: params.pool_index,
+            dex_type: params.dex_type.as_str().to_string(),
+            pool: params.pool_pubkey.to_string(),
+            input_mint: params.token_a_mint.to_string(),
+            output_mint: params.token_b_mint.to_string(),
+            amount_in: params.amount_in,
+            min_amount_out: params.min_amount_out,
+            direction: "a_to_b".to_string(),
+            exact_input: true,
+        }
+    }
+}
+
+/// Appends swap audit records to a JSON lines file
+#[derive(Debug)]
+pub struct SwapAuditLog {
+    file: Mutex<File>,
+    path: PathBuf,
+}
+
+impl SwapAuditLog {
+    /// Open the audit log at `path` for appending, creating it if needed
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open swap audit log {}", path.display()))?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+            path,
+        })
+    }
+
+    /// Append `record` as a single JSON line
+    pub fn record(&self, record: &SwapAuditRecord) -> Result<()> {
+        let mut line = serde_json::to_string(record).context("Failed to serialize swap audit record")?;
+        line.push('\n');
+
+        let mut file = self.file.lock().unwrap();
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("Failed to write swap audit log {}", self.path.display()))?;
+        file.flush()
+            .with_context(|| format!("Failed to flush swap audit log {}", self.path.display()))?;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+// Process-wide audit log, set up from the relayer settings in run_relayer
+static SWAP_AUDIT_LOG: OnceCell<Option<SwapAuditLog>> = OnceCell::new();
+
+/// Initialize the process-wide swap audit log from the relayer settings
+///
+/// The audit file is off unless `swap_audit_path` is configured. Only the first call has
+/// any effect.
+pub fn init_swap_audit_log(settings: &RelayerSettings) -> Result<()> {
+    SWAP_AUDIT_LOG.get_or_try_init(|| match settings.get_swap_audit_path() {
+        Some(path) => {
+            let audit_log = SwapAuditLog::open(path)?;
+            info!("Auditing swaps to {}", path);
+            Ok::<_, anyhow::Error>(Some(audit_log))
+        }
+        None => Ok(None),
+    })?;
+    Ok(())
+}
+
+/// Log every swap of an arbitrage, and append it to the audit file if one is configured
+///
+/// Failures writing the file are logged rather than returned, so auditing never blocks
+/// execution.
+pub fn audit_swaps(correlation_id: Uuid, swap_params_list: &[ArbitrageSwapParams]) -> Vec<SwapAuditRecord> {
+    let records: Vec<SwapAuditRecord> = swap_params_list
+        .iter()
+        .map(|params| SwapAuditRecord::from_params(correlation_id, params))
+        .collect();
+
+    for record in &records {
+        info!(
+            correlation_id = %record.correlation_id,
+            pool_index = record.pool_index,
+            dex_type = %record.dex_type,
+            pool = %record.pool,
+            input_mint = %record.input_mint,
+            output_mint = %record.output_mint,
+            amount_in = record.amount_in,
+            min_amount_out = record.min_amount_out,
+            direction = %record.direction,
+            exact_input = record.exact_input,
+            "Swap instruction built"
+        );
+
+        if let Some(Some(audit_log)) = SWAP_AUDIT_LOG.get() {
+            if let Err(e) = audit_log.record(record) {
+                warn!("Failed to audit swap for pool {}: {:?}", record.pool_index, e);
+            }
+        }
+    }
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::test_swap_params;
+    use crate::dex::DexType;
+
+    fn swap_params(pool_index: usize, dex_type: DexType) -> ArbitrageSwapParams {
+        ArbitrageSwapParams {
+            pool_index,
+            dex_type,
+            token_b_program: spl_token_2022::id(),
+            token_a_decimals: 9,
+            amount_in: 2_500_000,
+            min_amount_out: 1_250,
+            ..test_swap_params()
+        }
+    }
+
+    #[test]
+    fn test_audit_record_matches_swap_params() {
+        let correlation_id = Uuid::new_v4();
+        let swap_params_list = vec![swap_params(0, DexType::Orca), swap_params(3, DexType::RaydiumCpmm)];
+
+        let records = audit_swaps(correlation_id, &swap_params_list);
+
+        assert_eq!(records.len(), swap_params_list.len());
+        for (record, params) in records.iter().zip(&swap_params_list) {
+            assert_eq!(record.correlation_id, correlation_id);
+            assert_eq!(record.pool_index, params.pool_index);
+            assert_eq!(record.dex_type, params.dex_type.as_str());
+            assert_eq!(record.pool, params.pool_pubkey.to_string());
+            assert_eq!(record.input_mint, params.token_a_mint.to_string());
+            assert_eq!(record.output_mint, params.token_b_mint.to_string());
+            assert_eq!(record.amount_in, params.amount_in);
+            assert_eq!(record.min_amount_out, params.min_amount_out);
+            assert_eq!(record.direction, "a_to_b");
+            assert!(record.exact_input);
+        }
+        assert_eq!(records[1].dex_type, "raydium_cpmm");
+    }
+
+    #[test]
+    fn test_audit_log_appends_json_lines_without_wallets() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("swaps.jsonl");
+        let audit_log = SwapAuditLog::open(&path).unwrap();
+        let params = swap_params(1, DexType::Phoenix);
+
+        let record = SwapAuditRecord::from_params(Uuid::new_v4(), &params);
+        audit_log.record(&record).unwrap();
+        audit_log.record(&record).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let parsed: SwapAuditRecord = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(parsed, record);
+
+        // The explorer's token accounts stay out of the audit trail
+        assert!(!contents.contains(&params.token_a_wallet.to_string()));
+        assert!(!contents.contains(&params.token_b_wallet.to_string()));
+    }
+}
@@ -0,0 +1,302 @@
+//! Module for submitting arbitrage transactions to Jito as bundles and tracking them
+//!
+//! With `jito_bundles` set, the Jito transaction is sent with `sendBundle` instead of
+//! `sendTransaction`. The block engine answers with a bundle id rather than a signature,
+//! so the id is remembered against the transaction's signature until the submission
+//! returns and takes it back with [`take_submitted_bundles`]. While the submission is
+//! being confirmed, `getBundleStatuses` is polled alongside the signature statuses.
+//! Whichever settles first decides the outcome.
+
+use anyhow::{anyhow, bail, Context, Result};
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::TransactionError;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Instant;
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+use crate::arbitrage::confirm::{ConfirmationConfig, ConfirmationOutcome, SignatureStatus};
+use crate::arbitrage::submit::{jito_block_engines, submission_signature, RpcSubmissionResult};
+use crate::rpc::jito::JitoJsonRpcSDK;
+use crate::rpc::RpcProvider;
+use crate::settings::RelayerSettings;
+
+/// Extract the bundle id from a Jito `sendBundle` response
+pub fn bundle_id_from_response(response: &Value) -> Result<String> {
+    if let Some(error) = response.get("error") {
+        bail!("Jito rejected bundle: {}", error);
+    }
+
+    response
+        .get("result")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("Jito accepted bundle without returning a bundle id: {}", response))
+}
+
+/// Status of `bundle_id` at `commitment` from a `getBundleStatuses` response
+///
+/// Bundles missing from the response, or listed as `null`, haven't landed yet.
+pub fn bundle_status(response: &Value, bundle_id: &str, commitment: CommitmentConfig) -> Result<SignatureStatus> {
+    if let Some(error) = response.get("error") {
+        bail!("Failed to fetch status of bundle {}: {}", bundle_id, error);
+    }
+
+    let entries = response
+        .pointer("/result/value")
+        .and_then(Value::as_array)
+        .ok_or_else(|| anyhow!("Malformed getBundleStatuses response: {}", response))?;
+    let Some(entry) = entries
+        .iter()
+        .find(|entry| entry.get("bundle_id").and_then(Value::as_str) == Some(bundle_id))
+    else {
+        return Ok(SignatureStatus::Pending);
+    };
+
+    let reached = entry
+        .get("confirmation_status")
+        .and_then(Value::as_str)
+        .and_then(commitment_rank)
+        .is_some_and(|rank| rank >= commitment_rank_of(commitment.commitment));
+    if !reached {
+        return Ok(SignatureStatus::Pending);
+    }
+
+    match entry.get("err") {
+        Some(err) if err.get("Err").is_some_and(|e| !e.is_null()) => {
+            let error: TransactionError = serde_json::from_value(err["Err"].clone())
+                .with_context(|| format!("Unrecognized error for bundle {}: {}", bundle_id, err))?;
+            Ok(SignatureStatus::Failed(error))
+        },
+        _ => Ok(SignatureStatus::Confirmed),
+    }
+}
+
+fn commitment_rank(confirmation_status: &str) -> Option<u8> {
+    match confirmation_status {
+        "processed" => Some(0),
+        "confirmed" => Some(1),
+        "finalized" => Some(2),
+        _ => None,
+    }
+}
+
+fn commitment_rank_of(level: CommitmentLevel) -> u8 {
+    match level {
+        CommitmentLevel::Processed => 0,
+        CommitmentLevel::Confirmed => 1,
+        CommitmentLevel::Finalized => 2,
+    }
+}
+
+// Bundle ids by the signature of the transaction they carry, until the submission takes them
+static PENDING_BUNDLES: Lazy<Mutex<HashMap<Signature, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Remember that the transaction with `signature` was sent in bundle `bundle_id`
+pub fn record_pending_bundle(signature: Signature, bundle_id: String) {
+    PENDING_BUNDLES.lock().unwrap().insert(signature, bundle_id);
+}
+
+/// Take the bundles sent for the successful ones of `results`, as (signature, bundle id) pairs
+///
+/// Call as soon as the submission returns, however its results are then used, so that no
+/// bundle id outlives the submission that sent it.
+pub fn take_submitted_bundles(results: &[RpcSubmissionResult]) -> Vec<(Signature, String)> {
+    let mut pending = PENDING_BUNDLES.lock().unwrap();
+    results
+        .iter()
+        .filter_map(submission_signature)
+        .filter_map(|signature| pending.remove(&signature).map(|bundle_id| (signature, bundle_id)))
+        .collect()
+}
+
+/// Turn a Jito `sendBundle` response into a submission result
+///
+/// The result carries the signature of the bundled transaction, so it is confirmed like
+/// any other submission, and the bundle id is recorded for [`confirm_with_bundles`].
+pub fn jito_bundle_submission_result(response: Result<Value>, signature: Signature) -> RpcSubmissionResult {
+    match response.and_then(|response| bundle_id_from_response(&response)) {
+        Ok(bundle_id) => {
+            info!("Bundle {} submitted successfully via Jito: {}", bundle_id, signature);
+            record_pending_bundle(signature, bundle_id);
+            ("Jito".to_string(), true, signature.to_string())
+        },
+        Err(e) => {
+            warn!("Failed to submit bundle via Jito: {}", e);
+            ("Jito".to_string(), false, e.to_string())
+        }
+    }
+}
+
+/// Poll `getBundleStatuses` until one of the bundles lands or fails, or the timeout passes
+///
+/// Errors fetching statuses are logged and retried on the next poll.
+pub async fn monitor_bundles(
+    jito_sdk: &JitoJsonRpcSDK,
+    bundles: &[(Signature, String)],
+    config: &ConfirmationConfig,
+) -> ConfirmationOutcome {
+    let deadline = Instant::now() + config.timeout;
+    let bundle_ids: Vec<String> = bundles.iter().map(|(_, bundle_id)| bundle_id.clone()).collect();
+
+    loop {
+        match jito_sdk.get_bundle_statuses(bundle_ids.clone()).await {
+            Ok(response) => {
+                for (signature, bundle_id) in bundles {
+                    match bundle_status(&response, bundle_id, config.commitment) {
+                        Ok(SignatureStatus::Confirmed) => {
+                            info!("Bundle {} landed ({:?})", bundle_id, config.commitment.commitment);
+                            return ConfirmationOutcome::Confirmed(*signature);
+                        },
+                        Ok(SignatureStatus::Failed(e)) => {
+                            warn!("Bundle {} failed on-chain: {}", bundle_id, e);
+                            return ConfirmationOutcome::Failed(*signature, e);
+                        },
+                        Ok(SignatureStatus::Pending) => {},
+                        Err(e) => warn!("{}", e),
+                    }
+                }
+            },
+            Err(e) => warn!("{}", e),
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            warn!("No status for {} bundles after {:?}", bundles.len(), config.timeout);
+            return ConfirmationOutcome::TimedOut;
+        }
+        sleep(config.poll_interval.min(deadline - now)).await;
+    }
+}
+
+/// Wait for a submission that includes bundles, on signatures and bundle statuses at once
+///
+/// The first to settle decides the outcome. Bundle monitoring giving up doesn't end the
+/// wait, which is left to `signature_confirmation`.
+pub async fn confirm_with_bundles<F>(
+    signature_confirmation: F,
+    jito_sdk: &JitoJsonRpcSDK,
+    bundles: &[(Signature, String)],
+    config: &ConfirmationConfig,
+) -> ConfirmationOutcome
+where
+    F: Future<Output = ConfirmationOutcome>,
+{
+    tokio::pin!(signature_confirmation);
+    tokio::select! {
+        outcome = &mut signature_confirmation => outcome,
+        outcome = monitor_bundles(jito_sdk, bundles, config) => match outcome {
+            ConfirmationOutcome::TimedOut => signature_confirmation.await,
+            settled => settled,
+        },
+    }
+}
+
+/// Wait for a submission on `signature_confirmation`, and on the statuses of `bundles`
+/// when it sent any
+pub async fn confirm_submission<F>(
+    settings: &RelayerSettings,
+    signature_confirmation: F,
+    bundles: &[(Signature, String)],
+    config: &ConfirmationConfig,
+) -> ConfirmationOutcome
+where
+    F: Future<Output = ConfirmationOutcome>,
+{
+    if bundles.is_empty() {
+        return signature_confirmation.await;
+    }
+
+    let jito_sdk = JitoJsonRpcSDK::new(jito_block_engines(settings).fastest(), None)
+        .with_request_timeout(settings.get_request_timeout(RpcProvider::Jito));
+    confirm_with_bundles(signature_confirmation, &jito_sdk, bundles, config).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_sdk::instruction::InstructionError;
+
+    const BUNDLE_ID: &str = "892b79ed49138bfb3aa5441f0df6e06ef34f9ee8f3976c15b323605bae0cf51d";
+
+    fn statuses_response(confirmation_status: &str, err: Value) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "result": {
+                "context": { "slot": 242806119 },
+                "value": [
+                    null,
+                    {
+                        "bundle_id": BUNDLE_ID,
+                        "transactions": [
+                            "3bC2M9fiACSjkTXZDgeNAuQ4ScTsdKGwR42ytFdhUvikqTmBheUxfsR1fDVsM5ADCMMspuwGkdm1uKbU246x5aE3"
+                        ],
+                        "slot": 242804011,
+                        "confirmation_status": confirmation_status,
+                        "err": err
+                    }
+                ]
+            },
+            "id": 1
+        })
+    }
+
+    #[test]
+    fn test_parse_send_bundle_response() {
+        let accepted = json!({ "jsonrpc": "2.0", "result": BUNDLE_ID, "id": 1 });
+        assert_eq!(bundle_id_from_response(&accepted).unwrap(), BUNDLE_ID);
+
+        let rejected = json!({
+            "jsonrpc": "2.0",
+            "error": { "code": -32602, "message": "bundle contains an expired blockhash" },
+            "id": 1
+        });
+        assert!(bundle_id_from_response(&rejected).is_err());
+        assert!(bundle_id_from_response(&json!({ "jsonrpc": "2.0", "result": null, "id": 1 })).is_err());
+    }
+
+    #[test]
+    fn test_parse_bundle_statuses_response() {
+        let landed = statuses_response("finalized", json!({ "Ok": null }));
+        assert_eq!(bundle_status(&landed, BUNDLE_ID, CommitmentConfig::confirmed()).unwrap(), SignatureStatus::Confirmed);
+        // Unknown bundles haven't landed
+        assert_eq!(bundle_status(&landed, "unknown", CommitmentConfig::confirmed()).unwrap(), SignatureStatus::Pending);
+
+        // Seen, but not yet at the requested commitment
+        let processed = statuses_response("processed", json!({ "Ok": null }));
+        assert_eq!(bundle_status(&processed, BUNDLE_ID, CommitmentConfig::confirmed()).unwrap(), SignatureStatus::Pending);
+
+        let failed = statuses_response("confirmed", json!({ "Err": { "InstructionError": [2, { "Custom": 6001 }] } }));
+        assert_eq!(
+            bundle_status(&failed, BUNDLE_ID, CommitmentConfig::confirmed()).unwrap(),
+            SignatureStatus::Failed(TransactionError::InstructionError(2, InstructionError::Custom(6001)))
+        );
+
+        let empty = json!({ "jsonrpc": "2.0", "result": { "context": { "slot": 1 }, "value": [] }, "id": 1 });
+        assert_eq!(bundle_status(&empty, BUNDLE_ID, CommitmentConfig::confirmed()).unwrap(), SignatureStatus::Pending);
+        assert!(bundle_status(&json!({ "jsonrpc": "2.0", "result": null, "id": 1 }), BUNDLE_ID, CommitmentConfig::confirmed()).is_err());
+    }
+
+    #[test]
+    fn test_bundle_submission_is_recorded_until_taken() {
+        let signature = Signature::new_unique();
+        let response = json!({ "jsonrpc": "2.0", "result": BUNDLE_ID, "id": 1 });
+
+        let result = jito_bundle_submission_result(Ok(response), signature);
+        assert_eq!(result, ("Jito".to_string(), true, signature.to_string()));
+
+        let other = ("Solana RPC".to_string(), true, Signature::new_unique().to_string());
+        let failed = ("Helius".to_string(), false, "rate limited".to_string());
+        assert_eq!(
+            take_submitted_bundles(&[other, failed, result.clone()]),
+            vec![(signature, BUNDLE_ID.to_string())]
+        );
+        assert!(take_submitted_bundles(&[result]).is_empty());
+    }
+}
@@ -0,0 +1,214 @@
+//! Module for pausing all submissions after repeated systemic failures
+//!
+//! A run of failed execution cycles usually means something outside the opportunity is
+//! wrong (a bad config, drained wallets, an RPC outage), and retrying just burns keys
+//! and fees. After `failure_threshold` consecutive failures the breaker opens and
+//! submissions pause for `cool_down`. It then half-opens: the next cycle is let through
+//! as a probe, closing the breaker if it succeeds and re-opening it if it fails.
+
+use once_cell::sync::OnceCell;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+use crate::metrics::arbitrage::record_arbitrage_circuit_breaker_opened;
+use crate::settings::RelayerSettings;
+
+/// Consecutive failed cycles that open the breaker
+pub const DEFAULT_CIRCUIT_BREAKER_THRESHOLD: u32 = 5;
+
+/// How long submissions stay paused once the breaker opens
+pub const DEFAULT_CIRCUIT_BREAKER_COOL_DOWN: Duration = Duration::from_secs(60);
+
+/// State of the circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Submissions flow normally
+    Closed,
+    /// Submissions are paused until the cool-down ends
+    Open,
+    /// The cool-down has ended and the next cycle probes for recovery
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+/// Tracks consecutive failed cycles and pauses submissions when they pile up
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cool_down: Duration,
+    inner: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    /// Create a closed breaker; a `failure_threshold` of 0 disables it
+    pub fn new(failure_threshold: u32, cool_down: Duration) -> Self {
+        Self {
+            failure_threshold,
+            cool_down,
+            inner: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+            }),
+        }
+    }
+
+    /// Current state, moving from open to half-open once the cool-down has passed
+    pub fn state(&self) -> CircuitState {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state == CircuitState::Open
+            && inner.opened_at.is_some_and(|opened_at| opened_at.elapsed() >= self.cool_down)
+        {
+            info!("Circuit breaker cool-down of {:?} over, probing for recovery", self.cool_down);
+            inner.state = CircuitState::HalfOpen;
+        }
+        inner.state
+    }
+
+    /// Whether a submission cycle may run now
+    pub fn allows_submission(&self) -> bool {
+        self.state() != CircuitState::Open
+    }
+
+    /// Number of failed cycles since the last success
+    pub fn consecutive_failures(&self) -> u32 {
+        self.inner.lock().unwrap().consecutive_failures
+    }
+
+    /// Record a cycle whose transaction was submitted, closing the breaker
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state != CircuitState::Closed {
+            info!("Circuit breaker closed, resuming submissions");
+        }
+        inner.state = CircuitState::Closed;
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+    }
+
+    /// Record a failed cycle, opening the breaker at the threshold or after a failed probe
+    pub fn record_failure(&self) {
+        if self.failure_threshold == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+
+        let should_open = match inner.state {
+            CircuitState::Closed => inner.consecutive_failures >= self.failure_threshold,
+            CircuitState::HalfOpen => true,
+            CircuitState::Open => false,
+        };
+        if should_open {
+            error!(
+                "Circuit breaker opened after {} consecutive failures, pausing submissions for {:?}",
+                inner.consecutive_failures, self.cool_down
+            );
+            inner.state = CircuitState::Open;
+            inner.opened_at = Some(Instant::now());
+            record_arbitrage_circuit_breaker_opened();
+        } else if inner.state == CircuitState::Closed {
+            warn!("{} consecutive failed cycles (breaker opens at {})",
+                inner.consecutive_failures, self.failure_threshold);
+        }
+    }
+}
+
+// Process-wide breaker, set up from the relayer settings in run_relayer
+static CIRCUIT_BREAKER: OnceCell<CircuitBreaker> = OnceCell::new();
+
+/// Initialize the process-wide circuit breaker from the relayer settings
+///
+/// Only the first call has any effect.
+pub fn init_circuit_breaker(settings: &RelayerSettings) -> &'static CircuitBreaker {
+    CIRCUIT_BREAKER.get_or_init(|| CircuitBreaker::new(
+        settings.get_circuit_breaker_threshold(),
+        settings.get_circuit_breaker_cool_down(),
+    ))
+}
+
+/// The process-wide circuit breaker (with default settings if never initialized)
+pub fn circuit_breaker() -> &'static CircuitBreaker {
+    CIRCUIT_BREAKER.get_or_init(|| CircuitBreaker::new(
+        DEFAULT_CIRCUIT_BREAKER_THRESHOLD,
+        DEFAULT_CIRCUIT_BREAKER_COOL_DOWN,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::arbitrage::get_total_circuit_breaker_opens;
+    use std::thread::sleep;
+
+    const COOL_DOWN: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn test_opens_after_threshold_and_closes_after_successful_probe() {
+        let breaker = CircuitBreaker::new(3, COOL_DOWN);
+        let opens_before = get_total_circuit_breaker_opens();
+
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.allows_submission());
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.allows_submission());
+        assert!(get_total_circuit_breaker_opens() > opens_before);
+
+        // After the cool-down one probe is let through, and its success closes the breaker
+        sleep(COOL_DOWN);
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.allows_submission());
+
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn test_failed_probe_reopens() {
+        let breaker = CircuitBreaker::new(2, COOL_DOWN);
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        sleep(COOL_DOWN);
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        // A single failed probe re-opens it for another full cool-down
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.allows_submission());
+    }
+
+    #[test]
+    fn test_success_resets_failure_count() {
+        let breaker = CircuitBreaker::new(3, COOL_DOWN);
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_zero_threshold_disables_breaker() {
+        let breaker = CircuitBreaker::new(0, COOL_DOWN);
+        for _ in 0..100 {
+            breaker.record_failure();
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
@@ -0,0 +1,157 @@
+//! Module for sizing the compute budget of arbitrage transactions
+//!
+//! Without ComputeBudget instructions a transaction gets the default compute limit and
+//! pays no priority fee. Once a pre-flight simulation reports the units the transaction
+//! actually consumes, the unit limit is set to that plus a safety margin, and the unit
+//! price is capped so the total priority fee stays under a fraction of the profit.
+
+use solana_sdk::compute_budget::{self, ComputeBudgetInstruction};
+use solana_sdk::instruction::Instruction;
+use tracing::warn;
+
+use crate::arbitrage::prepare::{MAX_COMPUTE_UNITS, PRIORITY_FEE_MICRO_LAMPORTS_PER_CU};
+use crate::utils::checked_u64_amount;
+
+/// Extra compute units requested on top of the simulated usage, as a fraction (10%)
+pub const DEFAULT_COMPUTE_UNIT_MARGIN: f64 = 0.1;
+
+/// Largest share of the estimated profit spent on priority fees (10%)
+pub const DEFAULT_MAX_PRIORITY_FEE_PROFIT_FRACTION: f64 = 0.1;
+
+/// Micro-lamports per lamport, the unit of the compute unit price
+const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
+
+/// Compute unit limit and price for a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComputeBudget {
+    /// Compute units requested
+    pub unit_limit: u32,
+    /// Priority fee per compute unit, in micro-lamports
+    pub unit_price_micro_lamports: u64,
+}
+
+impl ComputeBudget {
+    /// Size the budget from the compute units consumed in simulation
+    ///
+    /// The limit is `units_consumed * (1 + margin)`, rounded up and capped at
+    /// [`MAX_COMPUTE_UNITS`]. The price is the standard priority fee, lowered when needed
+    /// so that `limit * price` stays within `max_profit_fraction` of `estimated_profit_lamports`.
+    pub fn from_simulation(
+        units_consumed: u64,
+        margin: f64,
+        estimated_profit_lamports: u64,
+        max_profit_fraction: f64,
+    ) -> Self {
+        let margin_units = (units_consumed as f64 * margin.max(0.0)).ceil() as u64;
+        let unit_limit = units_consumed
+            .saturating_add(margin_units)
+            .clamp(1, MAX_COMPUTE_UNITS);
+
+        let max_priority_fee_lamports =
+            (estimated_profit_lamports as f64 * max_profit_fraction.clamp(0.0, 1.0)).floor() as u128;
+        let max_unit_price =
+            max_priority_fee_lamports * MICRO_LAMPORTS_PER_LAMPORT as u128 / unit_limit as u128;
+        let unit_price_micro_lamports = (PRIORITY_FEE_MICRO_LAMPORTS_PER_CU as u128).min(max_unit_price) as u64;
+
+        Self {
+            unit_limit: unit_limit as u32,
+            unit_price_micro_lamports,
+        }
+    }
+
+    /// Priority fee paid if every requested unit is used, in lamports
+    pub fn priority_fee_lamports(&self) -> u64 {
+        (self.unit_limit as u128 * self.unit_price_micro_lamports as u128)
+            .div_ceil(MICRO_LAMPORTS_PER_LAMPORT as u128) as u64
+    }
+
+    /// ComputeBudget instructions setting this limit and price
+    pub fn instructions(&self) -> Vec<Instruction> {
+        vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(self.unit_limit),
+            ComputeBudgetInstruction::set_compute_unit_price(self.unit_price_micro_lamports),
+        ]
+    }
+
+    /// `instructions` with this budget prepended, replacing any ComputeBudget instructions
+    pub fn apply(&self, instructions: &[Instruction]) -> Vec<Instruction> {
+        let mut budgeted = self.instructions();
+        budgeted.extend(
+            instructions
+                .iter()
+                .filter(|instruction| !compute_budget::check_id(&instruction.program_id))
+                .cloned(),
+        );
+        budgeted
+    }
+}
+
+/// Convert a value in the router's numeraire into lamports at `sol_price` per SOL
+///
+/// Negative, non-finite or unpriced values convert to zero, as do values worth more
+/// lamports than a `u64` holds, so an anomalous profit never funds a priority fee.
+pub fn value_to_lamports(value: f64, sol_price: f64) -> u64 {
+    if !value.is_finite() || !sol_price.is_finite() || value <= 0.0 || sol_price <= 0.0 {
+        return 0;
+    }
+    match checked_u64_amount(value / sol_price * 1_000_000_000.0) {
+        Ok(lamports) => lamports,
+        Err(e) => {
+            warn!("Ignoring value {} at SOL price {}: {:#}", value, sol_price, e);
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn test_budget_from_simulated_units() {
+        // 0.001 SOL of profit allows 100_000 lamports of priority fees, more than the
+        // standard 100_000 micro-lamports per CU costs on 120_000 units
+        let budget = ComputeBudget::from_simulation(100_000, 0.2, 1_000_000, 0.1);
+        assert_eq!(budget.unit_limit, 120_000);
+        assert_eq!(budget.unit_price_micro_lamports, PRIORITY_FEE_MICRO_LAMPORTS_PER_CU);
+        assert_eq!(budget.priority_fee_lamports(), 12_000);
+
+        // 10_000 lamports of profit caps the fee at 1_000 lamports:
+        // 1_000 * 1_000_000 / 120_000 = 8_333 micro-lamports per CU
+        let budget = ComputeBudget::from_simulation(100_000, 0.2, 10_000, 0.1);
+        assert_eq!(budget.unit_limit, 120_000);
+        assert_eq!(budget.unit_price_micro_lamports, 8_333);
+        assert!(budget.priority_fee_lamports() <= 1_000);
+
+        // The margin never takes the limit past the maximum compute budget
+        let budget = ComputeBudget::from_simulation(1_300_000, 0.2, 1_000_000, 0.1);
+        assert_eq!(budget.unit_limit as u64, MAX_COMPUTE_UNITS);
+
+        // No profit, no priority fee
+        assert_eq!(ComputeBudget::from_simulation(100_000, 0.2, 0, 0.1).unit_price_micro_lamports, 0);
+    }
+
+    #[test]
+    fn test_apply_replaces_existing_budget() {
+        let swap = Instruction::new_with_bytes(Pubkey::new_unique(), &[1, 2, 3], vec![]);
+        let stale = ComputeBudgetInstruction::set_compute_unit_limit(200_000);
+
+        let budget = ComputeBudget::from_simulation(50_000, 0.1, 1_000_000, 0.1);
+        let instructions = budget.apply(&[stale, swap.clone()]);
+
+        assert_eq!(instructions.len(), 3);
+        assert_eq!(instructions[0], ComputeBudgetInstruction::set_compute_unit_limit(55_000));
+        assert_eq!(instructions[1], ComputeBudgetInstruction::set_compute_unit_price(PRIORITY_FEE_MICRO_LAMPORTS_PER_CU));
+        assert_eq!(instructions[2], swap);
+    }
+
+    #[test]
+    fn test_value_to_lamports() {
+        // 0.15 at 150 per SOL is 0.001 SOL
+        assert_eq!(value_to_lamports(0.15, 150.0), 1_000_000);
+        assert_eq!(value_to_lamports(-1.0, 150.0), 0);
+        assert_eq!(value_to_lamports(1.0, 0.0), 0);
+        assert_eq!(value_to_lamports(1e30, 1e-6), 0);
+    }
+}
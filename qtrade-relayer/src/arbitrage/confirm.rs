@@ -0,0 +1,585 @@
+//! Module for monitoring submitted arbitrage transactions until they confirm
+//!
+//! Signatures are either polled with `getSignatureStatuses` or, with the `websocket`
+//! confirmation method, subscribed to with `signatureSubscribe` so the node reports them
+//! as soon as they reach the commitment. A subscription that can't be set up, or whose
+//! socket closes, falls back to polling.
+
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSignatureSubscribeConfig;
+use solana_client::rpc_response::RpcSignatureResult;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::TransactionError;
+use solana_transaction_status::TransactionStatus;
+use std::time::{Duration, Instant};
+use tokio::time::{sleep, timeout_at};
+use tracing::{info, warn};
+
+use crate::settings::{ConfirmationMethod, RelayerSettings};
+
+/// How long to wait for a submitted transaction to confirm
+pub const DEFAULT_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often signature statuses are polled while waiting
+pub const DEFAULT_CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Most signatures `getSignatureStatuses` accepts in one request
+pub const MAX_SIGNATURE_STATUS_BATCH: usize = 256;
+
+/// Status of a transaction signature at the requested commitment
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignatureStatus {
+    /// Not yet seen at the requested commitment
+    Pending,
+    /// Reached the requested commitment and executed successfully
+    Confirmed,
+    /// Reached the requested commitment but failed on-chain
+    Failed(TransactionError),
+}
+
+impl SignatureStatus {
+    /// Status at `commitment` from a `getSignatureStatuses` entry, where `None` means unknown
+    pub fn from_transaction_status(status: Option<&TransactionStatus>, commitment: CommitmentConfig) -> Self {
+        match status {
+            Some(status) if status.satisfies_commitment(commitment) => match &status.err {
+                Some(e) => SignatureStatus::Failed(e.clone()),
+                None => SignatureStatus::Confirmed,
+            },
+            _ => SignatureStatus::Pending,
+        }
+    }
+}
+
+/// Source of transaction signature statuses
+pub trait SignatureStatusSource: Sync {
+    /// Fetch the status of `signature` at `commitment`
+    fn get_status(&self, signature: &Signature, commitment: CommitmentConfig) -> Result<SignatureStatus>;
+
+    /// Fetch the statuses of `signatures` at `commitment`, in the same order
+    ///
+    /// Sources that can look up many signatures in one request should override this;
+    /// by default each signature is fetched on its own.
+    fn get_statuses(&self, signatures: &[Signature], commitment: CommitmentConfig) -> Result<Vec<SignatureStatus>> {
+        signatures
+            .iter()
+            .map(|signature| self.get_status(signature, commitment))
+            .collect()
+    }
+}
+
+/// Fetch statuses in batches of up to [`MAX_SIGNATURE_STATUS_BATCH`] signatures
+///
+/// `fetch_batch` makes one `getSignatureStatuses` request, returning an entry per
+/// signature (`None` for signatures the node doesn't know).
+pub fn batched_statuses<F>(
+    signatures: &[Signature],
+    commitment: CommitmentConfig,
+    mut fetch_batch: F,
+) -> Result<Vec<SignatureStatus>>
+where
+    F: FnMut(&[Signature]) -> Result<Vec<Option<TransactionStatus>>>,
+{
+    let mut statuses = Vec::with_capacity(signatures.len());
+
+    for batch in signatures.chunks(MAX_SIGNATURE_STATUS_BATCH) {
+        let entries = fetch_batch(batch)?;
+        if entries.len() != batch.len() {
+            return Err(anyhow!(
+                "Requested statuses of {} signatures but received {}",
+                batch.len(),
+                entries.len()
+            ));
+        }
+
+        statuses.extend(
+            entries
+                .iter()
+                .map(|entry| SignatureStatus::from_transaction_status(entry.as_ref(), commitment)),
+        );
+    }
+
+    Ok(statuses)
+}
+
+/// Reads signature statuses over RPC
+pub struct RpcSignatureStatusSource<'a> {
+    rpc_client: &'a RpcClient,
+}
+
+impl<'a> RpcSignatureStatusSource<'a> {
+    pub fn new(rpc_client: &'a RpcClient) -> Self {
+        Self { rpc_client }
+    }
+}
+
+impl SignatureStatusSource for RpcSignatureStatusSource<'_> {
+    fn get_status(&self, signature: &Signature, commitment: CommitmentConfig) -> Result<SignatureStatus> {
+        let status = self.rpc_client
+            .get_signature_status_with_commitment(signature, commitment)
+            .map_err(|e| anyhow!("Failed to fetch status of {}: {}", signature, e))?;
+
+        Ok(match status {
+            None => SignatureStatus::Pending,
+            Some(Ok(())) => SignatureStatus::Confirmed,
+            Some(Err(e)) => SignatureStatus::Failed(e),
+        })
+    }
+
+    fn get_statuses(&self, signatures: &[Signature], commitment: CommitmentConfig) -> Result<Vec<SignatureStatus>> {
+        batched_statuses(signatures, commitment, |batch| {
+            self.rpc_client
+                .get_signature_statuses(batch)
+                .map(|response| response.value)
+                .map_err(|e| anyhow!("Failed to fetch statuses of {} signatures: {}", batch.len(), e))
+        })
+    }
+}
+
+/// Timing and commitment used while waiting for confirmation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfirmationConfig {
+    /// Give up once this much time has passed since monitoring started
+    pub timeout: Duration,
+    /// Delay between status polls
+    pub poll_interval: Duration,
+    /// Commitment a transaction must reach to count as confirmed
+    pub commitment: CommitmentConfig,
+}
+
+impl ConfirmationConfig {
+    pub fn from_settings(settings: &RelayerSettings) -> Self {
+        Self {
+            timeout: settings.get_confirmation_timeout(),
+            poll_interval: settings.get_confirmation_poll_interval(),
+            commitment: settings.get_confirmation_commitment(),
+        }
+    }
+}
+
+impl Default for ConfirmationConfig {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_CONFIRMATION_TIMEOUT,
+            poll_interval: DEFAULT_CONFIRMATION_POLL_INTERVAL,
+            commitment: CommitmentConfig::confirmed(),
+        }
+    }
+}
+
+/// How monitoring a submission ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfirmationOutcome {
+    /// One of the signatures confirmed
+    Confirmed(Signature),
+    /// One of the signatures failed on-chain
+    Failed(Signature, TransactionError),
+    /// None of the signatures reached the commitment before the timeout
+    TimedOut,
+}
+
+/// Poll the signatures of a submission until one confirms or fails, or the timeout passes
+///
+/// Providers may land the same transaction under different signatures (e.g. with different
+/// tips), so the first signature to reach the commitment decides the outcome. All
+/// signatures are checked together on each poll. Errors fetching statuses are logged
+/// and retried on the next poll.
+pub async fn monitor_confirmation(
+    status_source: &dyn SignatureStatusSource,
+    signatures: &[Signature],
+    config: &ConfirmationConfig,
+) -> ConfirmationOutcome {
+    let deadline = Instant::now() + config.timeout;
+
+    loop {
+        match status_source.get_statuses(signatures, config.commitment) {
+            Ok(statuses) => {
+                if let Some(outcome) = settled_outcome(signatures.iter().copied().zip(statuses), config.commitment) {
+                    return outcome;
+                }
+            },
+            Err(e) => warn!("{}", e),
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            warn!("No confirmation for {} signatures after {:?}", signatures.len(), config.timeout);
+            return ConfirmationOutcome::TimedOut;
+        }
+        sleep(config.poll_interval.min(deadline - now)).await;
+    }
+}
+
+/// Statuses pushed by the node as subscribed signatures reach the commitment
+pub type SignatureNotifications<'a> = BoxStream<'a, (Signature, SignatureStatus)>;
+
+/// Source of signature notifications, e.g. a `signatureSubscribe` WebSocket
+#[async_trait]
+pub trait SignatureSubscriber: Sync {
+    /// Subscribe to `signatures` at `commitment`, merging their notifications
+    async fn subscribe<'a>(
+        &'a self,
+        signatures: &'a [Signature],
+        commitment: CommitmentConfig,
+    ) -> Result<SignatureNotifications<'a>>;
+}
+
+/// Subscribes to signatures over the Solana WebSocket API
+pub struct PubsubSignatureSubscriber {
+    client: PubsubClient,
+}
+
+impl PubsubSignatureSubscriber {
+    /// Connect to the node's WebSocket endpoint
+    pub async fn connect(ws_url: &str) -> Result<Self> {
+        let client = PubsubClient::new(ws_url)
+            .await
+            .map_err(|e| anyhow!("Failed to connect to {}: {}", ws_url, e))?;
+        Ok(Self { client })
+    }
+}
+
+#[async_trait]
+impl SignatureSubscriber for PubsubSignatureSubscriber {
+    async fn subscribe<'a>(
+        &'a self,
+        signatures: &'a [Signature],
+        commitment: CommitmentConfig,
+    ) -> Result<SignatureNotifications<'a>> {
+        let mut subscriptions = Vec::with_capacity(signatures.len());
+        for signature in signatures {
+            // The node drops a signature subscription itself once it has notified it
+            let (notifications, _unsubscribe) = self.client
+                .signature_subscribe(signature, Some(RpcSignatureSubscribeConfig {
+                    commitment: Some(commitment),
+                    enable_received_notification: Some(false),
+                }))
+                .await
+                .map_err(|e| anyhow!("Failed to subscribe to {}: {}", signature, e))?;
+
+            let signature = *signature;
+            subscriptions.push(notifications.map(move |response| {
+                let status = match response.value {
+                    RpcSignatureResult::ProcessedSignature(processed) => match processed.err {
+                        Some(e) => SignatureStatus::Failed(e),
+                        None => SignatureStatus::Confirmed,
+                    },
+                    RpcSignatureResult::ReceivedSignature(_) => SignatureStatus::Pending,
+                };
+                (signature, status)
+            }));
+        }
+
+        Ok(stream::select_all(subscriptions).boxed())
+    }
+}
+
+/// Wait for the signatures of a submission with a subscription, polling if it fails
+///
+/// The statuses are polled once after subscribing, in case the transaction confirmed
+/// before the subscription started. If the subscription can't be set up or its stream
+/// ends, polling takes over for the rest of the timeout.
+pub async fn monitor_confirmation_with_subscriber(
+    subscriber: &dyn SignatureSubscriber,
+    status_source: &dyn SignatureStatusSource,
+    signatures: &[Signature],
+    config: &ConfirmationConfig,
+) -> ConfirmationOutcome {
+    let deadline = Instant::now() + config.timeout;
+
+    let mut notifications = match subscriber.subscribe(signatures, config.commitment).await {
+        Ok(notifications) => notifications,
+        Err(e) => {
+            warn!("Signature subscription unavailable, polling instead: {}", e);
+            return monitor_confirmation(status_source, signatures, &remaining(config, deadline)).await;
+        },
+    };
+
+    match status_source.get_statuses(signatures, config.commitment) {
+        Ok(statuses) => {
+            if let Some(outcome) = settled_outcome(signatures.iter().copied().zip(statuses), config.commitment) {
+                return outcome;
+            }
+        },
+        Err(e) => warn!("{}", e),
+    }
+
+    loop {
+        match timeout_at(deadline.into(), notifications.next()).await {
+            Ok(Some(notification)) => {
+                if let Some(outcome) = settled_outcome([notification], config.commitment) {
+                    return outcome;
+                }
+            },
+            Ok(None) => {
+                warn!("Signature subscription closed, polling for the rest of the timeout");
+                return monitor_confirmation(status_source, signatures, &remaining(config, deadline)).await;
+            },
+            Err(_) => {
+                warn!("No confirmation for {} signatures after {:?}", signatures.len(), config.timeout);
+                return ConfirmationOutcome::TimedOut;
+            },
+        }
+    }
+}
+
+/// Wait for the signatures of a submission with the configured confirmation method
+pub async fn confirm_signatures(
+    settings: &RelayerSettings,
+    status_source: &dyn SignatureStatusSource,
+    signatures: &[Signature],
+    config: &ConfirmationConfig,
+) -> ConfirmationOutcome {
+    match settings.get_confirmation_method() {
+        ConfirmationMethod::Polling => monitor_confirmation(status_source, signatures, config).await,
+        ConfirmationMethod::WebSocket => {
+            let ws_url = settings.get_solana_ws_url();
+            match PubsubSignatureSubscriber::connect(&ws_url).await {
+                Ok(subscriber) => monitor_confirmation_with_subscriber(&subscriber, status_source, signatures, config).await,
+                Err(e) => {
+                    warn!("Signature subscription unavailable, polling instead: {}", e);
+                    monitor_confirmation(status_source, signatures, config).await
+                },
+            }
+        },
+    }
+}
+
+/// The outcome decided by the first settled status, if any
+fn settled_outcome(
+    statuses: impl IntoIterator<Item = (Signature, SignatureStatus)>,
+    commitment: CommitmentConfig,
+) -> Option<ConfirmationOutcome> {
+    statuses.into_iter().find_map(|(signature, status)| match status {
+        SignatureStatus::Confirmed => {
+            info!("Transaction {} confirmed ({:?})", signature, commitment.commitment);
+            Some(ConfirmationOutcome::Confirmed(signature))
+        },
+        SignatureStatus::Failed(e) => {
+            warn!("Transaction {} failed on-chain: {}", signature, e);
+            Some(ConfirmationOutcome::Failed(signature, e))
+        },
+        SignatureStatus::Pending => None,
+    })
+}
+
+/// The config with its timeout cut to what is left before `deadline`
+fn remaining(config: &ConfirmationConfig, deadline: Instant) -> ConfirmationConfig {
+    ConfirmationConfig {
+        timeout: deadline.saturating_duration_since(Instant::now()),
+        ..*config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::InstructionError;
+    use solana_transaction_status::TransactionConfirmationStatus;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Never sees the transaction, counting how often it was asked
+    struct NeverConfirms(AtomicUsize);
+
+    impl SignatureStatusSource for NeverConfirms {
+        fn get_status(&self, _signature: &Signature, _commitment: CommitmentConfig) -> Result<SignatureStatus> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(SignatureStatus::Pending)
+        }
+    }
+
+    /// Confirms on the given poll
+    struct ConfirmsOnPoll(usize, AtomicUsize);
+
+    impl SignatureStatusSource for ConfirmsOnPoll {
+        fn get_status(&self, _signature: &Signature, commitment: CommitmentConfig) -> Result<SignatureStatus> {
+            assert_eq!(commitment, CommitmentConfig::finalized());
+            let poll = self.1.fetch_add(1, Ordering::SeqCst) + 1;
+            if poll >= self.0 {
+                Ok(SignatureStatus::Confirmed)
+            } else {
+                Ok(SignatureStatus::Pending)
+            }
+        }
+    }
+
+    fn transaction_status(
+        err: Option<TransactionError>,
+        confirmation_status: TransactionConfirmationStatus,
+    ) -> TransactionStatus {
+        TransactionStatus {
+            slot: 1,
+            confirmations: None,
+            status: err.clone().map_or(Ok(()), Err),
+            err,
+            confirmation_status: Some(confirmation_status),
+        }
+    }
+
+    /// Answers each batch from a fixed map, counting the requests made
+    struct BatchStatuses(HashMap<Signature, TransactionStatus>, AtomicUsize);
+
+    impl SignatureStatusSource for BatchStatuses {
+        fn get_status(&self, _signature: &Signature, _commitment: CommitmentConfig) -> Result<SignatureStatus> {
+            panic!("statuses should be fetched in batches");
+        }
+
+        fn get_statuses(&self, signatures: &[Signature], commitment: CommitmentConfig) -> Result<Vec<SignatureStatus>> {
+            batched_statuses(signatures, commitment, |batch| {
+                self.1.fetch_add(1, Ordering::SeqCst);
+                Ok(batch.iter().map(|signature| self.0.get(signature).cloned()).collect())
+            })
+        }
+    }
+
+    #[test]
+    fn test_batched_statuses_map_signatures_to_statuses() {
+        let signatures: Vec<Signature> = (0..300).map(|_| Signature::new_unique()).collect();
+        let failure = TransactionError::InstructionError(0, InstructionError::Custom(6001));
+        let mut known = HashMap::new();
+        known.insert(signatures[0], transaction_status(None, TransactionConfirmationStatus::Finalized));
+        known.insert(signatures[1], transaction_status(None, TransactionConfirmationStatus::Processed));
+        known.insert(signatures[299], transaction_status(Some(failure.clone()), TransactionConfirmationStatus::Confirmed));
+        let source = BatchStatuses(known, AtomicUsize::new(0));
+
+        let statuses = source.get_statuses(&signatures, CommitmentConfig::confirmed()).unwrap();
+
+        // 300 signatures take two requests of at most 256
+        assert_eq!(source.1.load(Ordering::SeqCst), 2);
+        assert_eq!(statuses.len(), signatures.len());
+        assert_eq!(statuses[0], SignatureStatus::Confirmed);
+        // Seen, but not yet at the requested commitment
+        assert_eq!(statuses[1], SignatureStatus::Pending);
+        // Null entries are signatures the node hasn't seen
+        assert_eq!(statuses[2], SignatureStatus::Pending);
+        assert_eq!(statuses[299], SignatureStatus::Failed(failure));
+    }
+
+    #[tokio::test]
+    async fn test_monitor_checks_all_signatures_in_one_batch() {
+        let signatures: Vec<Signature> = (0..3).map(|_| Signature::new_unique()).collect();
+        let mut known = HashMap::new();
+        known.insert(signatures[2], transaction_status(None, TransactionConfirmationStatus::Confirmed));
+        let source = BatchStatuses(known, AtomicUsize::new(0));
+
+        let outcome = monitor_confirmation(&source, &signatures, &ConfirmationConfig::default()).await;
+
+        assert_eq!(outcome, ConfirmationOutcome::Confirmed(signatures[2]));
+        assert_eq!(source.1.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_times_out_at_configured_bound() {
+        let source = NeverConfirms(AtomicUsize::new(0));
+        let config = ConfirmationConfig {
+            timeout: Duration::from_millis(200),
+            poll_interval: Duration::from_millis(50),
+            commitment: CommitmentConfig::confirmed(),
+        };
+
+        let started = Instant::now();
+        let outcome = monitor_confirmation(&source, &[Signature::default()], &config).await;
+        let elapsed = started.elapsed();
+
+        assert_eq!(outcome, ConfirmationOutcome::TimedOut);
+        assert!(elapsed >= config.timeout, "Gave up early after {:?}", elapsed);
+        assert!(elapsed < config.timeout + Duration::from_millis(150), "Gave up late after {:?}", elapsed);
+        // One poll up front, then one per interval until the deadline
+        let polls = source.0.load(Ordering::SeqCst);
+        assert!((4..=6).contains(&polls), "Polled {} times", polls);
+    }
+
+    #[tokio::test]
+    async fn test_poll_interval_longer_than_timeout_still_honours_timeout() {
+        let source = NeverConfirms(AtomicUsize::new(0));
+        let config = ConfirmationConfig {
+            timeout: Duration::from_millis(100),
+            poll_interval: Duration::from_secs(10),
+            commitment: CommitmentConfig::confirmed(),
+        };
+
+        let started = Instant::now();
+        let outcome = monitor_confirmation(&source, &[Signature::default()], &config).await;
+
+        assert_eq!(outcome, ConfirmationOutcome::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_confirms_at_configured_commitment() {
+        let source = ConfirmsOnPoll(3, AtomicUsize::new(0));
+        let config = ConfirmationConfig {
+            timeout: Duration::from_secs(5),
+            poll_interval: Duration::from_millis(10),
+            commitment: CommitmentConfig::finalized(),
+        };
+
+        let signature = Signature::from([7; 64]);
+        let outcome = monitor_confirmation(&source, &[signature], &config).await;
+        assert_eq!(outcome, ConfirmationOutcome::Confirmed(signature));
+    }
+
+    /// Mock WebSocket pushing whatever is sent on its channel, or refusing to subscribe
+    struct ChannelSubscriber(std::sync::Mutex<Option<futures::channel::mpsc::UnboundedReceiver<(Signature, SignatureStatus)>>>);
+
+    #[async_trait]
+    impl SignatureSubscriber for ChannelSubscriber {
+        async fn subscribe<'a>(
+            &'a self,
+            _signatures: &'a [Signature],
+            _commitment: CommitmentConfig,
+        ) -> Result<SignatureNotifications<'a>> {
+            let receiver = self.0.lock().unwrap().take().ok_or_else(|| anyhow!("socket unavailable"))?;
+            Ok(receiver.boxed())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscription_resolves_on_confirmation_notification() {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        let subscriber = ChannelSubscriber(std::sync::Mutex::new(Some(receiver)));
+        let source = NeverConfirms(AtomicUsize::new(0));
+        let signatures = [Signature::new_unique(), Signature::new_unique()];
+        let config = ConfirmationConfig {
+            timeout: Duration::from_secs(5),
+            // Long enough that only the notification can resolve the monitor in time
+            poll_interval: Duration::from_secs(10),
+            commitment: CommitmentConfig::confirmed(),
+        };
+
+        let landed = signatures[1];
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(20)).await;
+            sender.unbounded_send((landed, SignatureStatus::Pending)).unwrap();
+            sender.unbounded_send((landed, SignatureStatus::Confirmed)).unwrap();
+        });
+
+        let started = Instant::now();
+        let outcome = monitor_confirmation_with_subscriber(&subscriber, &source, &signatures, &config).await;
+
+        assert_eq!(outcome, ConfirmationOutcome::Confirmed(landed));
+        assert!(started.elapsed() < Duration::from_secs(1));
+        // Polled once after subscribing, never again
+        assert_eq!(source.0.load(Ordering::SeqCst), signatures.len());
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_subscription_falls_back_to_polling() {
+        let subscriber = ChannelSubscriber(std::sync::Mutex::new(None));
+        let source = ConfirmsOnPoll(2, AtomicUsize::new(0));
+        let config = ConfirmationConfig {
+            timeout: Duration::from_secs(5),
+            poll_interval: Duration::from_millis(10),
+            commitment: CommitmentConfig::finalized(),
+        };
+
+        let signature = Signature::new_unique();
+        let outcome = monitor_confirmation_with_subscriber(&subscriber, &source, &[signature], &config).await;
+        assert_eq!(outcome, ConfirmationOutcome::Confirmed(signature));
+    }
+}
@@ -0,0 +1,180 @@
+//! Module for cooling down pools whose arbitrage just failed
+//!
+//! A pool that keeps showing up as profitable, e.g. because the router quotes it from
+//! stale reserves, would otherwise be submitted to every cycle and fail every time,
+//! paying fees for nothing. Once an execution through a pool fails, opportunities
+//! trading that pool are skipped for `window`. A confirmed success clears the cooldown
+//! of every pool it traded.
+
+use once_cell::sync::OnceCell;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+use crate::arbitrage::confirm::ConfirmationOutcome;
+use crate::arbitrage::outcome::ExecutionOutcome;
+use crate::arbitrage::submit::is_transient_failure;
+use crate::metrics::arbitrage::record_arbitrage_pool_cooldown_skipped;
+use crate::settings::RelayerSettings;
+
+/// How long a pool is skipped after an execution through it fails
+pub const DEFAULT_POOL_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Tracks when each pool last failed and skips it until its cooldown ends
+#[derive(Debug)]
+pub struct PoolCooldown {
+    window: Duration,
+    failed_at: Mutex<HashMap<Pubkey, Instant>>,
+}
+
+impl PoolCooldown {
+    /// Create a cooldown of `window` per pool; a zero window disables it
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            failed_at: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether the pool failed within the window
+    pub fn is_cooling_down(&self, pool: &Pubkey) -> bool {
+        let mut failed_at = self.failed_at.lock().unwrap();
+        match failed_at.get(pool) {
+            Some(at) if at.elapsed() < self.window => true,
+            Some(_) => {
+                // Expired entries are dropped as they're found
+                failed_at.remove(pool);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// The first of the pools still cooling down, counting the skip it causes
+    pub fn check_pools<'a>(&self, pools: impl IntoIterator<Item = &'a Pubkey>) -> Option<Pubkey> {
+        let cooling = pools.into_iter().find(|pool| self.is_cooling_down(pool)).copied();
+        if let Some(pool) = cooling {
+            info!("Pool {} failed within the last {:?}, skipping execution", pool, self.window);
+            record_arbitrage_pool_cooldown_skipped();
+        }
+        cooling
+    }
+
+    /// Start the cooldown of every pool of a failed execution
+    pub fn record_failure<'a>(&self, pools: impl IntoIterator<Item = &'a Pubkey>) {
+        if self.window.is_zero() {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut failed_at = self.failed_at.lock().unwrap();
+        for pool in pools {
+            debug!("Cooling down pool {} for {:?}", pool, self.window);
+            failed_at.insert(*pool, now);
+        }
+    }
+
+    /// Clear the cooldown of every pool of a confirmed execution
+    pub fn record_success<'a>(&self, pools: impl IntoIterator<Item = &'a Pubkey>) {
+        let mut failed_at = self.failed_at.lock().unwrap();
+        for pool in pools {
+            failed_at.remove(pool);
+        }
+    }
+
+    /// Update the pools of an execution from how it ended
+    ///
+    /// Rejection by every provider and an on-chain failure start the cooldown, a confirmed
+    /// transaction clears it. Skips, simulations, unconfirmed submissions and failures where
+    /// every provider timed out leave it as is.
+    pub fn record_outcome<'a>(&self, pools: impl IntoIterator<Item = &'a Pubkey>, outcome: &ExecutionOutcome) {
+        match outcome {
+            // Slow providers say nothing about the pools
+            ExecutionOutcome::Failed { results } if !results.is_empty() && results.iter().all(is_transient_failure) => {}
+            ExecutionOutcome::Failed { .. }
+            | ExecutionOutcome::Submitted { confirmation: Some(ConfirmationOutcome::Failed(_, _)), .. } => {
+                self.record_failure(pools)
+            }
+            ExecutionOutcome::Submitted { confirmation: Some(ConfirmationOutcome::Confirmed(_)), .. } => {
+                self.record_success(pools)
+            }
+            _ => {}
+        }
+    }
+}
+
+// Process-wide cooldown, set up from the relayer settings in run_relayer
+static POOL_COOLDOWN: OnceCell<PoolCooldown> = OnceCell::new();
+
+/// Initialize the process-wide pool cooldown from the relayer settings
+///
+/// Only the first call has any effect.
+pub fn init_pool_cooldown(settings: &RelayerSettings) -> &'static PoolCooldown {
+    POOL_COOLDOWN.get_or_init(|| PoolCooldown::new(settings.get_pool_cooldown()))
+}
+
+/// The process-wide pool cooldown (with the default window if never initialized)
+pub fn pool_cooldown() -> &'static PoolCooldown {
+    POOL_COOLDOWN.get_or_init(|| PoolCooldown::new(DEFAULT_POOL_COOLDOWN))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::arbitrage::get_total_pool_cooldown_skips;
+    use solana_sdk::signature::Signature;
+    use solana_sdk::transaction::TransactionError;
+    use std::thread::sleep;
+
+    const WINDOW: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn test_pool_in_cooldown_is_skipped() {
+        let cooldown = PoolCooldown::new(WINDOW);
+        let failed_pool = Pubkey::new_unique();
+        let other_pool = Pubkey::new_unique();
+        let skips_before = get_total_pool_cooldown_skips();
+
+        cooldown.record_outcome([&failed_pool], &ExecutionOutcome::Failed { results: vec![] });
+        assert_eq!(cooldown.check_pools([&other_pool, &failed_pool]), Some(failed_pool));
+        assert!(get_total_pool_cooldown_skips() > skips_before);
+        assert_eq!(cooldown.check_pools([&other_pool]), None);
+
+        // The pool is traded again once the window has passed
+        sleep(WINDOW);
+        assert_eq!(cooldown.check_pools([&failed_pool]), None);
+    }
+
+    #[test]
+    fn test_confirmed_success_clears_cooldown() {
+        let cooldown = PoolCooldown::new(Duration::from_secs(60));
+        let pool = Pubkey::new_unique();
+        let signature = Signature::new_unique();
+
+        cooldown.record_outcome([&pool], &ExecutionOutcome::Submitted {
+            signatures: vec![signature],
+            confirmation: Some(ConfirmationOutcome::Failed(signature, TransactionError::AccountInUse)),
+        });
+        assert!(cooldown.is_cooling_down(&pool));
+
+        // A submission still waiting on confirmation changes nothing
+        cooldown.record_outcome([&pool], &ExecutionOutcome::Submitted { signatures: vec![signature], confirmation: None });
+        assert!(cooldown.is_cooling_down(&pool));
+
+        cooldown.record_outcome([&pool], &ExecutionOutcome::Submitted {
+            signatures: vec![signature],
+            confirmation: Some(ConfirmationOutcome::Confirmed(signature)),
+        });
+        assert!(!cooldown.is_cooling_down(&pool));
+    }
+
+    #[test]
+    fn test_zero_window_disables_cooldown() {
+        let cooldown = PoolCooldown::new(Duration::ZERO);
+        let pool = Pubkey::new_unique();
+        cooldown.record_failure([&pool]);
+        assert!(!cooldown.is_cooling_down(&pool));
+    }
+}
@@ -0,0 +1,302 @@
+//! Module for skipping arbitrage opportunities that were already submitted
+//!
+//! If the relayer crashes after submitting an opportunity but before it confirms, the
+//! same opportunity can be queued again after a restart and landed twice. Submissions
+//! are recorded under a deterministic hash of the opportunity, optionally persisted to
+//! a JSON file, and checked before anything is submitted.
+
+use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
+use qtrade_shared_types::ArbitrageResult;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+use crate::settings::RelayerSettings;
+
+/// How long a submission is remembered if it is never confirmed
+pub const DEFAULT_SUBMISSION_TTL: Duration = Duration::from_secs(600);
+
+/// Deterministic key identifying an arbitrage opportunity
+///
+/// Hex-encoded SHA-256 of the trade amounts and pool mappings. The status and
+/// execution order don't change what gets traded, so they are left out.
+pub fn opportunity_key(result: &ArbitrageResult) -> String {
+    let mut hasher = Sha256::new();
+
+    hasher.update(b"deltas");
+    hash_rows(&mut hasher, &result.deltas);
+    hasher.update(b"lambdas");
+    hash_rows(&mut hasher, &result.lambdas);
+    hasher.update(b"a_matrices");
+    hasher.update((result.a_matrices.len() as u64).to_le_bytes());
+    for matrix in &result.a_matrices {
+        hash_rows(&mut hasher, matrix);
+    }
+
+    hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// Length-prefix every row so [[1, 2], [3]] and [[1], [2, 3]] hash differently
+fn hash_rows(hasher: &mut Sha256, rows: &[Vec<f64>]) {
+    hasher.update((rows.len() as u64).to_le_bytes());
+    for row in rows {
+        hasher.update((row.len() as u64).to_le_bytes());
+        for value in row {
+            hasher.update(value.to_bits().to_le_bytes());
+        }
+    }
+}
+
+/// Current time in seconds since the Unix epoch (0 if the clock is before the epoch)
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// A submitted opportunity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubmissionRecord {
+    /// Signatures returned by the providers that accepted the transaction
+    /// (empty while the submission is still in flight)
+    pub signatures: Vec<String>,
+    /// When the opportunity was submitted, in seconds since the Unix epoch
+    pub submitted_at: u64,
+}
+
+/// Idempotency store of submitted opportunities, keyed by [`opportunity_key`]
+///
+/// Records are dropped once their transaction confirms (see [`SubmissionStore::remove`])
+/// or after the TTL. With a backing file every change is written through, so records
+/// survive a restart.
+#[derive(Debug)]
+pub struct SubmissionStore {
+    records: Mutex<HashMap<String, SubmissionRecord>>,
+    path: Option<PathBuf>,
+    ttl: Duration,
+}
+
+impl SubmissionStore {
+    /// Create a store that lives only in memory
+    pub fn in_memory(ttl: Duration) -> Self {
+        Self {
+            records: Mutex::new(HashMap::new()),
+            path: None,
+            ttl,
+        }
+    }
+
+    /// Open a store backed by the JSON file at `path`, loading any records already there
+    ///
+    /// A missing file starts an empty store; an unreadable one is an error, since
+    /// ignoring it could resubmit everything it recorded.
+    pub fn open(path: impl Into<PathBuf>, ttl: Duration) -> Result<Self> {
+        let path = path.into();
+        let records = if path.exists() {
+            let contents = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read submission store {}", path.display()))?;
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse submission store {}", path.display()))?
+        } else {
+            HashMap::new()
+        };
+
+        let store = Self {
+            records: Mutex::new(records),
+            path: Some(path),
+            ttl,
+        };
+        store.evict_expired()?;
+        Ok(store)
+    }
+
+    /// Whether the opportunity with `key` was submitted within the TTL
+    pub fn is_submitted(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// The unexpired record for `key`, if any
+    pub fn get(&self, key: &str) -> Option<SubmissionRecord> {
+        let records = self.records.lock().unwrap();
+        records
+            .get(key)
+            .filter(|record| !self.is_expired(record, now_secs()))
+            .cloned()
+    }
+
+    /// Record that the opportunity with `key` was submitted, with the signatures returned so far
+    ///
+    /// Recording an already known key replaces its signatures but keeps the original
+    /// submission time, so the TTL runs from the first submission.
+    pub fn record_submission(&self, key: &str, signatures: Vec<String>) -> Result<()> {
+        let mut records = self.records.lock().unwrap();
+        let submitted_at = records
+            .get(key)
+            .map(|record| record.submitted_at)
+            .unwrap_or_else(now_secs);
+        records.insert(key.to_string(), SubmissionRecord { signatures, submitted_at });
+        self.persist(&records)
+    }
+
+    /// Forget the opportunity with `key`, once its transaction has confirmed or every
+    /// provider rejected it
+    pub fn remove(&self, key: &str) -> Result<Option<SubmissionRecord>> {
+        let mut records = self.records.lock().unwrap();
+        let removed = records.remove(key);
+        if removed.is_some() {
+            self.persist(&records)?;
+        }
+        Ok(removed)
+    }
+
+    /// Drop every record older than the TTL, returning how many were dropped
+    pub fn evict_expired(&self) -> Result<usize> {
+        self.evict_expired_at(now_secs())
+    }
+
+    fn evict_expired_at(&self, now: u64) -> Result<usize> {
+        let mut records = self.records.lock().unwrap();
+        let before = records.len();
+        records.retain(|_, record| !self.is_expired(record, now));
+
+        let evicted = before - records.len();
+        if evicted > 0 {
+            info!("Evicted {} expired submission records", evicted);
+            self.persist(&records)?;
+        }
+        Ok(evicted)
+    }
+
+    /// Number of records held, including any not yet evicted
+    pub fn len(&self) -> usize {
+        self.records.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn is_expired(&self, record: &SubmissionRecord, now: u64) -> bool {
+        now.saturating_sub(record.submitted_at) >= self.ttl.as_secs()
+    }
+
+    // Write to a temporary file and rename it over the store, so a crash mid-write
+    // never leaves a truncated file behind
+    fn persist(&self, records: &HashMap<String, SubmissionRecord>) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let contents = serde_json::to_string(records).context("Failed to serialize submission store")?;
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, contents)
+            .with_context(|| format!("Failed to write submission store {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, path)
+            .with_context(|| format!("Failed to replace submission store {}", path.display()))?;
+        Ok(())
+    }
+}
+
+// Process-wide store, set up from the relayer settings in run_relayer
+static SUBMISSION_STORE: OnceCell<SubmissionStore> = OnceCell::new();
+
+/// Initialize the process-wide submission store from the relayer settings
+///
+/// Uses the file at `submission_store_path` when one is configured, memory otherwise.
+/// Only the first call has any effect.
+pub fn init_submission_store(settings: &RelayerSettings) -> Result<&'static SubmissionStore> {
+    SUBMISSION_STORE.get_or_try_init(|| match settings.get_submission_store_path() {
+        Some(path) => {
+            let store = SubmissionStore::open(path, settings.get_submission_ttl())?;
+            info!("Loaded {} submission records from {}", store.len(), path);
+            Ok(store)
+        }
+        None => {
+            warn!("No submission store path configured, submissions won't be deduplicated across restarts");
+            Ok(SubmissionStore::in_memory(settings.get_submission_ttl()))
+        }
+    })
+}
+
+/// The process-wide submission store (in memory with the default TTL if never initialized)
+pub fn submission_store() -> &'static SubmissionStore {
+    SUBMISSION_STORE.get_or_init(|| SubmissionStore::in_memory(DEFAULT_SUBMISSION_TTL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arbitrage_result(delta: f64) -> ArbitrageResult {
+        ArbitrageResult {
+            deltas: vec![vec![delta, 0.0]],
+            lambdas: vec![vec![0.0, 1.5]],
+            a_matrices: vec![vec![vec![1.0, 0.0], vec![0.0, 1.0]]],
+            status: "optimal".to_string(),
+            execution_order: vec![],
+            market_values: vec![],
+            correlation_id: None,
+            emitted_at: None,
+            enqueued_at: None,
+            pool_fees: vec![],
+            pool_states: vec![],
+            version: ArbitrageResult::VERSION,
+        }
+    }
+
+    #[test]
+    fn test_opportunity_key_is_deterministic() {
+        let key = opportunity_key(&arbitrage_result(1.0));
+        assert_eq!(key.len(), 64);
+
+        // The status doesn't change what gets traded
+        let mut solved_again = arbitrage_result(1.0);
+        solved_again.status = "optimal_inaccurate".to_string();
+        assert_eq!(opportunity_key(&solved_again), key);
+
+        assert_ne!(opportunity_key(&arbitrage_result(2.0)), key);
+    }
+
+    #[test]
+    fn test_duplicate_skipped_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("submissions.json");
+        let key = opportunity_key(&arbitrage_result(1.0));
+
+        {
+            let store = SubmissionStore::open(&path, DEFAULT_SUBMISSION_TTL).unwrap();
+            assert!(!store.is_submitted(&key));
+            store.record_submission(&key, vec!["signature".to_string()]).unwrap();
+        }
+
+        // A fresh store over the same file sees the earlier submission
+        let restarted = SubmissionStore::open(&path, DEFAULT_SUBMISSION_TTL).unwrap();
+        assert!(restarted.is_submitted(&key));
+        assert_eq!(restarted.get(&key).unwrap().signatures, vec!["signature".to_string()]);
+        assert!(!restarted.is_submitted(&opportunity_key(&arbitrage_result(2.0))));
+
+        // Once confirmed the record is gone, on disk too
+        restarted.remove(&key).unwrap();
+        let restarted_again = SubmissionStore::open(&path, DEFAULT_SUBMISSION_TTL).unwrap();
+        assert!(!restarted_again.is_submitted(&key));
+    }
+
+    #[test]
+    fn test_expired_records_are_evicted() {
+        let store = SubmissionStore::in_memory(Duration::from_secs(60));
+        store.record_submission("stale", vec![]).unwrap();
+
+        assert_eq!(store.evict_expired_at(now_secs() + 30).unwrap(), 0);
+        assert!(store.is_submitted("stale"));
+
+        assert_eq!(store.evict_expired_at(now_secs() + 60).unwrap(), 1);
+        assert!(store.is_empty());
+    }
+}
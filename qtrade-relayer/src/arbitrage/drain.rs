@@ -0,0 +1,185 @@
+//! Module for draining the arbitrage queue on shutdown
+//!
+//! Without draining, results still queued when the relayer is cancelled are lost. With
+//! `drain_on_shutdown` set, queued results keep being executed until the queue is empty
+//! or the drain budget runs out. Whatever is left is written to the queue spool, when
+//! one is configured, and put back in the queue on the next start.
+
+use anyhow::{Context, Result};
+use qtrade_shared_types::ArbitrageResult;
+use std::collections::VecDeque;
+use std::fs;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::{timeout_at, Instant};
+use tracing::{error, info, warn};
+
+/// How long queued results keep being executed after cancellation
+pub const DEFAULT_DRAIN_BUDGET: Duration = Duration::from_secs(10);
+
+/// What became of the queued results when the relayer shut down
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrainReport {
+    /// Results executed before the budget ran out
+    pub processed: usize,
+    /// Results whose execution the budget cut short (at most one)
+    pub interrupted: usize,
+    /// Results written to the spool for the next start
+    pub persisted: usize,
+    /// Results dropped because there was no spool to write them to (or writing failed)
+    pub abandoned: usize,
+}
+
+/// Execute queued results one at a time until the queue is empty or `budget` is spent,
+/// then spool whatever is left
+///
+/// A result still executing when the budget runs out is cut short and not spooled, since
+/// it may already have been submitted.
+pub async fn drain_queue<F, Fut>(
+    queue: &Mutex<VecDeque<ArbitrageResult>>,
+    budget: Duration,
+    spool_path: Option<&Path>,
+    mut execute: F,
+) -> DrainReport
+where
+    F: FnMut(ArbitrageResult) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let deadline = Instant::now() + budget;
+    let mut report = DrainReport::default();
+
+    while Instant::now() < deadline {
+        let Some(result) = queue.lock().unwrap().pop_front() else {
+            break;
+        };
+        if timeout_at(deadline, execute(result)).await.is_err() {
+            warn!("Drain budget of {:?} ran out mid-execution", budget);
+            report.interrupted += 1;
+            break;
+        }
+        report.processed += 1;
+    }
+
+    let remaining: Vec<ArbitrageResult> = queue.lock().unwrap().drain(..).collect();
+    if remaining.is_empty() {
+        return report;
+    }
+
+    match spool_path {
+        Some(path) => match save_queue_spool(path, &remaining) {
+            Ok(()) => {
+                info!("Spooled {} queued arbitrage results to {}", remaining.len(), path.display());
+                report.persisted = remaining.len();
+            }
+            Err(e) => {
+                error!("{} queued arbitrage results lost on shutdown: {:?}", remaining.len(), e);
+                report.abandoned = remaining.len();
+            }
+        },
+        None => {
+            warn!("{} queued arbitrage results lost on shutdown, no queue spool configured", remaining.len());
+            report.abandoned = remaining.len();
+        }
+    }
+
+    report
+}
+
+// Write to a temporary file and rename it over the spool, so a crash mid-write
+// leaves the previous spool intact
+fn save_queue_spool(path: &Path, results: &[ArbitrageResult]) -> Result<()> {
+    let contents = serde_json::to_string(results).context("Failed to serialize queued arbitrage results")?;
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, contents)
+        .with_context(|| format!("Failed to write queue spool {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to replace queue spool {}", path.display()))?;
+    Ok(())
+}
+
+/// Take the results spooled by the last shutdown, removing the spool
+///
+/// A missing spool means nothing was left over.
+pub fn take_queue_spool(path: &Path) -> Result<Vec<ArbitrageResult>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read queue spool {}", path.display()))?;
+    let results: Vec<ArbitrageResult> = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse queue spool {}", path.display()))?;
+    fs::remove_file(path)
+        .with_context(|| format!("Failed to remove queue spool {}", path.display()))?;
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queued_result(status: &str) -> ArbitrageResult {
+        ArbitrageResult {
+            status: status.to_string(),
+            deltas: vec![vec![1.0, 0.0]],
+            lambdas: vec![vec![0.0, 1.1]],
+            a_matrices: vec![],
+            execution_order: vec![],
+            market_values: vec![],
+            correlation_id: None,
+            emitted_at: None,
+            enqueued_at: None,
+            pool_fees: vec![],
+            pool_states: vec![],
+            version: ArbitrageResult::VERSION,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drain_processes_then_persists_the_rest() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = dir.path().join("queue.json");
+        let queue = Mutex::new(VecDeque::from(vec![queued_result("first"), queued_result("second"), queued_result("third")]));
+        let mut executed = Vec::new();
+
+        // The first result executes at once, the second outlasts the budget
+        let report = drain_queue(&queue, Duration::from_millis(100), Some(&spool), |result| {
+            let slow = result.status == "second";
+            executed.push(result.status);
+            async move {
+                if slow {
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(report, DrainReport { processed: 1, interrupted: 1, persisted: 1, abandoned: 0 });
+        assert_eq!(executed, vec!["first", "second"]);
+        assert!(queue.lock().unwrap().is_empty());
+
+        // The spooled result comes back on the next start, once
+        let reloaded = take_queue_spool(&spool).unwrap();
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded[0].status, "third");
+        assert!(take_queue_spool(&spool).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_zero_budget_persists_everything() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = dir.path().join("queue.json");
+        let queue = Mutex::new(VecDeque::from(vec![queued_result("first"), queued_result("second")]));
+
+        let report = drain_queue(&queue, Duration::ZERO, Some(&spool), |_| async {
+            panic!("nothing should execute without a budget");
+        })
+        .await;
+
+        assert_eq!(report.persisted, 2);
+        assert_eq!(take_queue_spool(&spool).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,205 @@
+//! Module for routing arbitrage swaps through the qtrade executor program
+//!
+//! By default each leg is a direct CPI into its DEX. With `route_through_executor` set,
+//! each leg becomes an executor `swap` instead: the executor moves the input from the
+//! explorer's account into its router account, enforces the minimum output and charges
+//! the referral fee, and the DEX instruction's program and accounts ride along as
+//! remaining accounts. `swap_multi` takes no per-leg amounts, so every leg gets its own
+//! `swap` rather than one `swap_multi` for the whole route.
+
+use anyhow::{Result, anyhow};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+use tracing::info;
+
+use crate::arbitrage::prepare::{ArbitrageSwapParams, create_swap_instructions};
+
+/// Mainnet qtrade executor program ID
+pub const EXECUTOR_PROGRAM_ID: Pubkey = pubkey!("E4uFtpkcE9vPXfULJaCZrJvoiSW9rJ1oqnhmHJMsEErj");
+
+/// Associated token account program, which owns the executor's router token accounts
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Anchor discriminator of the executor's `swap` instruction
+const SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+// Seeds the executor derives its PDAs from
+const STATE_SEED: &[u8] = b"state";
+const REFERRAL_SEED: &[u8] = b"referral";
+
+/// Referral codes above this charge a registered fee and need their referral info account
+pub const REFERRAL_WITH_FEE_THRESHOLD: u32 = 1u32 << 31;
+
+/// Referral code used when none is configured (no fee)
+pub const DEFAULT_EXECUTOR_REFERRAL_CODE: u32 = 0;
+
+/// Get the executor program ID
+pub fn program_id() -> Pubkey {
+    EXECUTOR_PROGRAM_ID
+}
+
+/// The executor's global state PDA
+pub fn state_address() -> Pubkey {
+    Pubkey::find_program_address(&[STATE_SEED], &program_id()).0
+}
+
+/// The referral info PDA registered for a referral code
+pub fn referral_info_address(referral_code: u32) -> Pubkey {
+    Pubkey::find_program_address(&[REFERRAL_SEED, &referral_code.to_le_bytes()], &program_id()).0
+}
+
+/// The executor's token account receiving a leg's input: the state PDA's associated
+/// token account for the input mint
+pub fn router_input_address(input_mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[state_address().as_ref(), token_program.as_ref(), input_mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Wrap one DEX swap instruction in an executor `swap`
+///
+/// The quote passed to the executor is the leg's minimum output, so the executor's slippage
+/// check matches the one the DEX instruction already carries.
+pub fn create_executor_swap_instruction(
+    params: &ArbitrageSwapParams,
+    user: &Pubkey,
+    referral_code: u32,
+    dex_instruction: &Instruction,
+) -> Result<Instruction> {
+    // The executor only takes legacy SPL Token accounts
+    if params.token_a_program != spl_token::id() {
+        return Err(anyhow!(
+            "Executor can't route pool {}: input mint {} isn't an SPL Token mint",
+            params.pool_index,
+            params.token_a_mint
+        ));
+    }
+    if params.min_amount_out == 0 {
+        return Err(anyhow!(
+            "Executor can't route pool {} without a minimum output",
+            params.pool_index
+        ));
+    }
+
+    let mut data = Vec::with_capacity(8 + 8 + 8 + 8 + 4);
+    data.extend_from_slice(&SWAP_DISCRIMINATOR);
+    data.extend_from_slice(&params.amount_in.to_le_bytes());
+    data.extend_from_slice(&params.min_amount_out.to_le_bytes());
+    data.extend_from_slice(&params.min_amount_out.to_le_bytes());
+    data.extend_from_slice(&referral_code.to_le_bytes());
+
+    let mut accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(state_address(), false),
+        AccountMeta::new(params.token_a_wallet, false),
+        AccountMeta::new(router_input_address(&params.token_a_mint, &params.token_a_program), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    // The executor reads the referral info from the first remaining account
+    if referral_code > REFERRAL_WITH_FEE_THRESHOLD {
+        accounts.push(AccountMeta::new_readonly(referral_info_address(referral_code), false));
+    }
+
+    // The DEX leg the executor swaps through
+    accounts.push(AccountMeta::new_readonly(dex_instruction.program_id, false));
+    accounts.extend(dex_instruction.accounts.iter().map(|meta| AccountMeta {
+        pubkey: meta.pubkey,
+        // Only the user can sign the outer instruction
+        is_signer: meta.is_signer && meta.pubkey == *user,
+        is_writable: meta.is_writable,
+    }));
+
+    Ok(Instruction {
+        program_id: program_id(),
+        accounts,
+        data,
+    })
+}
+
+/// Create executor `swap` instructions for each swap parameter, in the same order as
+/// `create_swap_instructions`
+pub fn create_executor_swap_instructions(
+    swap_params_list: &[ArbitrageSwapParams],
+    explorer_pubkey: &Pubkey,
+    referral_code: u32,
+) -> Result<Vec<Instruction>> {
+    info!("Routing {} swaps through the executor program", swap_params_list.len());
+    let dex_instructions = create_swap_instructions(swap_params_list, explorer_pubkey)?;
+
+    swap_params_list
+        .iter()
+        .zip(dex_instructions.iter())
+        .map(|(params, dex_instruction)| {
+            create_executor_swap_instruction(params, explorer_pubkey, referral_code, dex_instruction)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::test_swap_params;
+
+    fn swap_params() -> ArbitrageSwapParams {
+        ArbitrageSwapParams {
+            token_a_decimals: 9,
+            amount_in: 1_000_000,
+            min_amount_out: 950,
+            ..test_swap_params()
+        }
+    }
+
+    #[test]
+    fn test_executor_swap_instruction_from_swap_params() {
+        let params = swap_params();
+        let explorer = Pubkey::new_unique();
+        let referral_code = REFERRAL_WITH_FEE_THRESHOLD + 7;
+
+        let instructions = create_executor_swap_instructions(&[params.clone()], &explorer, referral_code).unwrap();
+        assert_eq!(instructions.len(), 1);
+        let instruction = &instructions[0];
+        assert_eq!(instruction.program_id, program_id());
+
+        // Discriminator, input amount, minimum output, quote and referral code
+        assert_eq!(&instruction.data[..8], &SWAP_DISCRIMINATOR);
+        assert_eq!(&instruction.data[8..16], &1_000_000u64.to_le_bytes());
+        assert_eq!(&instruction.data[16..24], &950u64.to_le_bytes());
+        assert_eq!(&instruction.data[24..32], &950u64.to_le_bytes());
+        assert_eq!(&instruction.data[32..36], &referral_code.to_le_bytes());
+
+        let accounts = &instruction.accounts;
+        assert_eq!(accounts[0], AccountMeta::new(explorer, true));
+        assert_eq!(accounts[1], AccountMeta::new_readonly(state_address(), false));
+        assert_eq!(accounts[2], AccountMeta::new(params.token_a_wallet, false));
+        assert_eq!(accounts[3].pubkey, router_input_address(&params.token_a_mint, &spl_token::id()));
+        assert_eq!(accounts[4].pubkey, spl_token::id());
+        assert_eq!(accounts[5].pubkey, referral_info_address(referral_code));
+        assert_eq!(accounts[6].pubkey, crate::dex::orca::OrcaSwap::program_id());
+        assert!(accounts[7..].iter().all(|meta| !meta.is_signer || meta.pubkey == explorer));
+    }
+
+    #[test]
+    fn test_fee_free_referral_code_needs_no_referral_account() {
+        let params = swap_params();
+        let explorer = Pubkey::new_unique();
+        let dex_instruction = Instruction {
+            program_id: Pubkey::new_unique(),
+            accounts: vec![],
+            data: vec![],
+        };
+
+        let instruction = create_executor_swap_instruction(&params, &explorer, 42, &dex_instruction).unwrap();
+        assert_eq!(instruction.accounts.len(), 6);
+        assert_eq!(instruction.accounts[5].pubkey, dex_instruction.program_id);
+
+        let token_2022_params = ArbitrageSwapParams {
+            token_a_program: spl_token_2022::id(),
+            ..params
+        };
+        assert!(create_executor_swap_instruction(&token_2022_params, &explorer, 42, &dex_instruction).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Module for bounding how many arbitrage executions run at once
+//!
+//! Every execution holds an explorer key until it confirms and sends to every active
+//! provider, so a burst of opportunities executed together can drain the key pool and
+//! flood the providers. An execution first takes a permit from the [`InFlightLimiter`];
+//! when none is left, the opportunity stays queued for a later cycle.
+
+use once_cell::sync::OnceCell;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::debug;
+
+use crate::metrics::arbitrage::{
+    record_arbitrage_in_flight_finished,
+    record_arbitrage_in_flight_rejected,
+    record_arbitrage_in_flight_started,
+};
+use crate::settings::RelayerSettings;
+
+/// Executions allowed in flight at once
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 4;
+
+/// Permit for one execution, given back when dropped
+#[derive(Debug)]
+pub struct InFlightPermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Drop for InFlightPermit {
+    fn drop(&mut self) {
+        record_arbitrage_in_flight_finished();
+    }
+}
+
+/// Semaphore capping the number of executions in flight
+#[derive(Debug)]
+pub struct InFlightLimiter {
+    semaphore: Arc<Semaphore>,
+    max_in_flight: usize,
+}
+
+impl InFlightLimiter {
+    /// Limiter allowing `max_in_flight` executions at once (at least 1)
+    pub fn new(max_in_flight: usize) -> Self {
+        let max_in_flight = max_in_flight.max(1);
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_in_flight)),
+            max_in_flight,
+        }
+    }
+
+    /// A permit to start an execution, or `None` if the limit is reached
+    ///
+    /// Callers only ask when an opportunity is waiting, so every `None` is counted as a
+    /// rejection.
+    pub fn try_acquire(&self) -> Option<InFlightPermit> {
+        match Arc::clone(&self.semaphore).try_acquire_owned() {
+            Ok(permit) => {
+                record_arbitrage_in_flight_started();
+                Some(InFlightPermit { _permit: permit })
+            }
+            Err(_) => {
+                debug!("{} arbitrage executions already in flight, deferring the next one", self.max_in_flight);
+                record_arbitrage_in_flight_rejected();
+                None
+            }
+        }
+    }
+
+    /// Executions currently holding a permit
+    pub fn in_flight(&self) -> usize {
+        self.max_in_flight - self.semaphore.available_permits()
+    }
+
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight
+    }
+}
+
+// Process-wide limiter, set up from the relayer settings in run_relayer
+static IN_FLIGHT_LIMITER: OnceCell<InFlightLimiter> = OnceCell::new();
+
+/// Initialize the process-wide in-flight limiter from the relayer settings
+///
+/// Only the first call has any effect.
+pub fn init_in_flight_limiter(settings: &RelayerSettings) -> &'static InFlightLimiter {
+    IN_FLIGHT_LIMITER.get_or_init(|| InFlightLimiter::new(settings.get_max_in_flight()))
+}
+
+/// The process-wide in-flight limiter (with the default limit if never initialized)
+pub fn in_flight_limiter() -> &'static InFlightLimiter {
+    IN_FLIGHT_LIMITER.get_or_init(|| InFlightLimiter::new(DEFAULT_MAX_IN_FLIGHT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::arbitrage::get_total_in_flight_rejected;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_permits_are_capped_and_returned() {
+        let limiter = InFlightLimiter::new(2);
+        let rejected_before = get_total_in_flight_rejected();
+
+        let first = limiter.try_acquire().unwrap();
+        let _second = limiter.try_acquire().unwrap();
+        assert_eq!(limiter.in_flight(), 2);
+        assert!(limiter.try_acquire().is_none());
+        assert!(get_total_in_flight_rejected() > rejected_before);
+
+        // A finished execution frees its permit for the next one
+        drop(first);
+        assert_eq!(limiter.in_flight(), 1);
+        assert!(limiter.try_acquire().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_executions_never_exceed_the_limit() {
+        let limiter = InFlightLimiter::new(3);
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let completed = AtomicUsize::new(0);
+
+        // Ten opportunities arrive at once; each round starts what the limiter allows
+        while completed.load(Ordering::SeqCst) < 10 {
+            let remaining = 10 - completed.load(Ordering::SeqCst);
+            let executions: Vec<_> = (0..remaining)
+                .map_while(|_| limiter.try_acquire())
+                .map(|permit| async {
+                    let _permit = permit;
+                    let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now_running, Ordering::SeqCst);
+                    tokio::task::yield_now().await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    completed.fetch_add(1, Ordering::SeqCst);
+                })
+                .collect();
+            futures::future::join_all(executions).await;
+        }
+
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert_eq!(limiter.in_flight(), 0);
+    }
+}
@@ -0,0 +1,150 @@
+//! Module for measuring how long an opportunity takes from solve to landing
+//!
+//! The router timestamps each result as it sends it and the relayer as it queues it. Once
+//! a transaction of the opportunity confirms, the time from emission to confirmation is
+//! recorded, along with its solve-to-enqueue, enqueue-to-submit and submit-to-confirm stages.
+
+use qtrade_shared_types::ArbitrageResult;
+use std::time::{Duration, SystemTime};
+use tracing::info;
+
+use crate::arbitrage::confirm::ConfirmationOutcome;
+use crate::arbitrage::outcome::ExecutionOutcome;
+use crate::metrics::arbitrage::{record_opportunity_latency, record_opportunity_stage_latency};
+
+/// Latency of a confirmed opportunity and its stages
+///
+/// Stages whose start wasn't timestamped, e.g. for results replayed from a recording, are
+/// left out. So is any stage spanning a backwards clock step.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpportunityLatency {
+    /// Router emission to confirmation
+    pub total: Option<Duration>,
+    pub solve_to_enqueue: Option<Duration>,
+    pub enqueue_to_submit: Option<Duration>,
+    pub submit_to_confirm: Option<Duration>,
+}
+
+impl OpportunityLatency {
+    /// Latency of `result`, submitted at `submitted_at` and confirmed at `confirmed_at`
+    pub fn measure(result: &ArbitrageResult, submitted_at: SystemTime, confirmed_at: SystemTime) -> Self {
+        Self {
+            total: result.emitted_at.and_then(|emitted_at| elapsed(emitted_at, confirmed_at)),
+            solve_to_enqueue: result
+                .emitted_at
+                .zip(result.enqueued_at)
+                .and_then(|(emitted_at, enqueued_at)| elapsed(emitted_at, enqueued_at)),
+            enqueue_to_submit: result.enqueued_at.and_then(|enqueued_at| elapsed(enqueued_at, submitted_at)),
+            submit_to_confirm: elapsed(submitted_at, confirmed_at),
+        }
+    }
+
+    /// Record the latency and each stage in the opportunity latency histogram
+    pub fn record(&self) {
+        if let Some(total) = self.total {
+            record_opportunity_latency(total);
+        }
+        let stages = [
+            ("solve_to_enqueue", self.solve_to_enqueue),
+            ("enqueue_to_submit", self.enqueue_to_submit),
+            ("submit_to_confirm", self.submit_to_confirm),
+        ];
+        for (stage, latency) in stages {
+            if let Some(latency) = latency {
+                record_opportunity_stage_latency(stage, latency);
+            }
+        }
+    }
+}
+
+fn elapsed(from: SystemTime, to: SystemTime) -> Option<Duration> {
+    to.duration_since(from).ok()
+}
+
+/// Record the latency of an execution that ended in a confirmed transaction
+///
+/// `submitted_at` is when submission started. Other outcomes record nothing.
+pub fn record_outcome(
+    result: &ArbitrageResult,
+    submitted_at: SystemTime,
+    outcome: &ExecutionOutcome,
+) -> Option<OpportunityLatency> {
+    if !matches!(outcome, ExecutionOutcome::Submitted { confirmation: Some(ConfirmationOutcome::Confirmed(_)), .. }) {
+        return None;
+    }
+
+    let latency = OpportunityLatency::measure(result, submitted_at, SystemTime::now());
+    info!(
+        total = ?latency.total,
+        solve_to_enqueue = ?latency.solve_to_enqueue,
+        enqueue_to_submit = ?latency.enqueue_to_submit,
+        submit_to_confirm = ?latency.submit_to_confirm,
+        "Opportunity landed"
+    );
+    latency.record();
+    Some(latency)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::arbitrage::{get_last_opportunity_latency, get_total_opportunity_latencies};
+    use solana_sdk::signature::Signature;
+
+    fn timestamped_result(emitted_at: SystemTime, enqueued_at: SystemTime) -> ArbitrageResult {
+        ArbitrageResult {
+            deltas: vec![],
+            lambdas: vec![],
+            a_matrices: vec![],
+            status: "optimal".to_string(),
+            execution_order: vec![],
+            market_values: vec![],
+            correlation_id: None,
+            emitted_at: Some(emitted_at),
+            enqueued_at: Some(enqueued_at),
+            pool_fees: vec![],
+            pool_states: vec![],
+            version: ArbitrageResult::VERSION,
+        }
+    }
+
+    #[test]
+    fn test_confirmed_opportunity_records_latency() {
+        let now = SystemTime::now();
+        let result = timestamped_result(now - Duration::from_millis(300), now - Duration::from_millis(200));
+        let submitted_at = now - Duration::from_millis(100);
+        let signature = Signature::new_unique();
+        let recorded_before = get_total_opportunity_latencies();
+
+        // Nothing is recorded until the transaction confirms
+        let unconfirmed = ExecutionOutcome::Submitted { signatures: vec![signature], confirmation: None };
+        assert!(record_outcome(&result, submitted_at, &unconfirmed).is_none());
+
+        let confirmed = ExecutionOutcome::Submitted {
+            signatures: vec![signature],
+            confirmation: Some(ConfirmationOutcome::Confirmed(signature)),
+        };
+        let latency = record_outcome(&result, submitted_at, &confirmed).unwrap();
+
+        assert!(latency.total.unwrap() >= Duration::from_millis(300));
+        assert_eq!(latency.solve_to_enqueue, Some(Duration::from_millis(100)));
+        assert_eq!(latency.enqueue_to_submit, Some(Duration::from_millis(100)));
+        assert!(latency.submit_to_confirm.unwrap() >= Duration::from_millis(100));
+
+        assert!(get_total_opportunity_latencies() > recorded_before);
+        assert!(get_last_opportunity_latency() > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_missing_timestamps_leave_stages_out() {
+        let now = SystemTime::now();
+        let mut result = timestamped_result(now, now);
+        result.emitted_at = None;
+
+        let latency = OpportunityLatency::measure(&result, now + Duration::from_millis(5), now + Duration::from_millis(20));
+        assert_eq!(latency.total, None);
+        assert_eq!(latency.solve_to_enqueue, None);
+        assert_eq!(latency.enqueue_to_submit, Some(Duration::from_millis(5)));
+        assert_eq!(latency.submit_to_confirm, Some(Duration::from_millis(15)));
+    }
+}
@@ -0,0 +1,96 @@
+//! Module for restricting which mints the relayer trades
+//!
+//! Operators can block mints outright (scams, fee-on-transfer or freezable tokens) and
+//! optionally restrict trading to an allow list. An opportunity touching any mint that
+//! isn't allowed is skipped, whatever the solver found.
+
+use anyhow::{anyhow, Result};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// Allowed and blocked mints
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MintFilter {
+    /// When set, only these mints may be traded
+    allowed: Option<HashSet<Pubkey>>,
+    /// Mints that are never traded, even if allowed
+    blocked: HashSet<Pubkey>,
+}
+
+impl MintFilter {
+    /// A filter from the allow and block lists; an empty allow list allows every mint
+    pub fn new(allowed: impl IntoIterator<Item = Pubkey>, blocked: impl IntoIterator<Item = Pubkey>) -> Self {
+        let allowed: HashSet<Pubkey> = allowed.into_iter().collect();
+        Self {
+            allowed: (!allowed.is_empty()).then_some(allowed),
+            blocked: blocked.into_iter().collect(),
+        }
+    }
+
+    /// A filter from base58 mint addresses, failing on any that don't parse
+    pub fn from_strs(allowed: &[String], blocked: &[String]) -> Result<Self> {
+        Ok(Self::new(parse_mints(allowed)?, parse_mints(blocked)?))
+    }
+
+    /// Whether `mint` may be traded
+    pub fn is_allowed(&self, mint: &Pubkey) -> bool {
+        if self.blocked.contains(mint) {
+            return false;
+        }
+
+        match &self.allowed {
+            Some(allowed) => allowed.contains(mint),
+            None => true,
+        }
+    }
+
+    /// The first of `mints` that may not be traded, if any
+    pub fn first_disallowed<'a>(&self, mints: impl IntoIterator<Item = &'a Pubkey>) -> Option<Pubkey> {
+        mints.into_iter().find(|mint| !self.is_allowed(mint)).copied()
+    }
+}
+
+fn parse_mints(mints: &[String]) -> Result<Vec<Pubkey>> {
+    mints
+        .iter()
+        .map(|mint| Pubkey::from_str(mint.trim()).map_err(|e| anyhow!("Invalid mint address {}: {}", mint, e)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocked_mint_is_rejected() {
+        let usdc = Pubkey::new_unique();
+        let scam = Pubkey::new_unique();
+        let filter = MintFilter::new([], [scam]);
+
+        assert!(filter.is_allowed(&usdc));
+        assert_eq!(filter.first_disallowed(&[usdc, scam]), Some(scam));
+        assert_eq!(filter.first_disallowed(&[usdc]), None);
+    }
+
+    #[test]
+    fn test_allow_list_restricts_mints() {
+        let sol = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let filter = MintFilter::new([sol, usdc], []);
+
+        assert_eq!(filter.first_disallowed(&[sol, usdc]), None);
+        assert_eq!(filter.first_disallowed(&[sol, other]), Some(other));
+
+        // Blocking wins over allowing
+        let filter = MintFilter::new([sol, usdc], [usdc]);
+        assert_eq!(filter.first_disallowed(&[sol, usdc]), Some(usdc));
+    }
+
+    #[test]
+    fn test_invalid_mint_address_is_an_error() {
+        assert!(MintFilter::from_strs(&["not-a-mint".to_string()], &[]).is_err());
+        assert_eq!(MintFilter::from_strs(&[], &[]).unwrap(), MintFilter::default());
+    }
+}
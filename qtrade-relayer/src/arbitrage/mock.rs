@@ -0,0 +1,66 @@
+//! Module for executing arbitrage results against in-memory mock RPC providers
+//!
+//! With `mock_execution` set, results are validated and priced as usual, then simulated
+//! through a [`MockRpc`] per simulation provider instead of the network. Mock pools have
+//! no on-chain accounts to swap through, so each simulation sends a memo carrying the
+//! result's correlation id, signed by a throwaway key. Only built with the `mock` feature.
+
+use anyhow::Result;
+use qtrade_shared_types::ArbitrageResult;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::arbitrage::outcome::{ExecutionOutcome, SkipReason};
+use crate::arbitrage::prepare::validate_arbitrage_result;
+use crate::arbitrage::profit::{estimate_net_profit, TransactionCosts};
+use crate::arbitrage::submit::{provider_label, run_simulations, RpcSubmissionResult};
+use crate::rpc::mock::MockRpc;
+use crate::rpc::{RpcActions, RpcProvider};
+use crate::settings::RelayerSettings;
+
+/// Execute an arbitrage result without leaving the process
+///
+/// Invalid and unprofitable results are skipped as they would be for real. Everything
+/// else ends as [`ExecutionOutcome::Simulated`], with one result per simulation provider.
+pub async fn execute_mock(
+    arbitrage_result: &ArbitrageResult,
+    settings: &RelayerSettings,
+    correlation_id: Uuid,
+) -> Result<ExecutionOutcome> {
+    info!("Running in MOCK mode - simulating against in-memory RPC providers");
+
+    if !validate_arbitrage_result(arbitrage_result)? {
+        return Ok(ExecutionOutcome::Skipped(SkipReason::InvalidResult));
+    }
+
+    let sol_price = crate::oracle::current_sol_price(settings).await;
+    let profit_estimate = estimate_net_profit(arbitrage_result, &TransactionCosts::estimate(0), sol_price)?;
+    info!("Estimated mock profit: net {:.6}", profit_estimate.net_profit);
+    if !profit_estimate.is_profitable(settings.get_min_profit_usd()) {
+        return Ok(ExecutionOutcome::Skipped(SkipReason::Unprofitable {
+            net_profit: profit_estimate.net_profit,
+            min_profit: settings.get_min_profit_usd(),
+        }));
+    }
+
+    let signer = Keypair::new();
+    let instructions = vec![spl_memo::build_memo(correlation_id.to_string().as_bytes(), &[&signer.pubkey()])];
+    let results = run_simulations(&settings.get_simulation_rpcs(), |provider| {
+        let result = simulate_with_mock(provider, &instructions, &signer);
+        async move { result }
+    })
+    .await;
+
+    Ok(ExecutionOutcome::from_submission(results, true))
+}
+
+/// Simulate through a mock standing in for `provider`, reported under the provider's name
+fn simulate_with_mock(provider: RpcProvider, instructions: &[Instruction], signer: &Keypair) -> RpcSubmissionResult {
+    let name = format!("{} (mock simulation)", provider_label(provider));
+    match MockRpc::succeeding().send_tx(&mut instructions.to_vec(), signer) {
+        Ok(signature) => (name, true, signature),
+        Err(e) => (name, false, e.to_string()),
+    }
+}
@@ -1,7 +1,58 @@
 //! Arbitrage module for handling preparation, execution, and monitoring of arbitrage opportunities
 
+pub mod audit;
+pub mod bundle;
+pub mod circuit_breaker;
+pub mod compute;
+pub mod confirm;
+pub mod cooldown;
+pub mod dedup;
+pub mod drain;
+pub mod executor;
+pub mod in_flight;
+pub mod latency;
+pub mod mint_filter;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod outcome;
 pub mod prepare;
+pub mod profit;
+pub mod recheck;
+pub mod reconcile;
+pub mod replay;
+pub mod resubmit;
+pub mod split;
 pub mod submit;
+pub mod token_checks;
+pub mod tx_builder;
 
 #[cfg(test)]
 mod submit_test;
+
+/// Swap of 1000 base units of token A for at least 990 of token B in an Orca pool, with
+/// every account a new key and both mints on the legacy token program
+///
+/// Tests override the fields they care about with struct update syntax.
+#[cfg(test)]
+pub(crate) fn test_swap_params() -> prepare::ArbitrageSwapParams {
+    use solana_sdk::pubkey::Pubkey;
+
+    prepare::ArbitrageSwapParams {
+        pool_index: 0,
+        dex_type: crate::dex::DexType::Orca,
+        pool_pubkey: Pubkey::new_unique(),
+        pool_state: None,
+        token_a_wallet: Pubkey::new_unique(),
+        token_a_mint: Pubkey::new_unique(),
+        token_a_vault: Pubkey::new_unique(),
+        token_a_program: spl_token::id(),
+        token_b_wallet: Pubkey::new_unique(),
+        token_b_mint: Pubkey::new_unique(),
+        token_b_vault: Pubkey::new_unique(),
+        token_b_program: spl_token::id(),
+        token_a_decimals: 6,
+        token_b_decimals: 6,
+        amount_in: 1000,
+        min_amount_out: 990,
+    }
+}
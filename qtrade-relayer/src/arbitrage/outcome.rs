@@ -0,0 +1,199 @@
+//! Module describing how the execution of an arbitrage opportunity ended
+
+use solana_sdk::signature::Signature;
+use uuid::Uuid;
+
+use crate::arbitrage::confirm::ConfirmationOutcome;
+use crate::arbitrage::submit::{submission_signature, RpcSubmissionResult};
+
+/// Why an opportunity was skipped before anything was submitted
+#[derive(Debug, Clone, PartialEq)]
+pub enum SkipReason {
+    /// The router's result isn't executable (not optimal, or no significant deltas)
+    InvalidResult,
+    /// The opportunity was already submitted, possibly before a restart
+    AlreadySubmitted,
+    /// No profitable swap could be built from the result
+    NoSwaps,
+    /// Net profit after fees and tips is below the configured minimum
+    Unprofitable {
+        net_profit: f64,
+        min_profit: f64,
+    },
+    /// Live reserves no longer make the opportunity profitable
+    Expired,
+    /// A mint charges too high a transfer fee, or a token account the swaps use is frozen
+    RiskyToken,
+    /// The pre-flight simulation failed in simulate-then-submit mode
+    SimulationRejected,
+    /// Landing fees would take more than the configured share of the profit
+    FeeCapExceeded {
+        fees: f64,
+        max_fees: f64,
+    },
+    /// A pool the swaps trade failed within the cooldown window
+    PoolCoolingDown,
+}
+
+impl SkipReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipReason::InvalidResult => "invalid_result",
+            SkipReason::AlreadySubmitted => "already_submitted",
+            SkipReason::NoSwaps => "no_swaps",
+            SkipReason::Unprofitable { .. } => "unprofitable",
+            SkipReason::Expired => "expired",
+            SkipReason::RiskyToken => "risky_token",
+            SkipReason::SimulationRejected => "simulation_rejected",
+            SkipReason::FeeCapExceeded { .. } => "fee_cap_exceeded",
+            SkipReason::PoolCoolingDown => "pool_cooling_down",
+        }
+    }
+}
+
+/// Outcome of executing a single arbitrage opportunity
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecutionOutcome {
+    /// Nothing was submitted
+    Skipped(SkipReason),
+    /// The transaction was only simulated
+    Simulated {
+        results: Vec<RpcSubmissionResult>,
+    },
+    /// At least one provider accepted the transaction
+    Submitted {
+        signatures: Vec<Signature>,
+        /// How confirmation monitoring ended, if any signature was returned to monitor
+        confirmation: Option<ConfirmationOutcome>,
+    },
+    /// Every provider rejected the transaction
+    Failed {
+        results: Vec<RpcSubmissionResult>,
+    },
+}
+
+impl ExecutionOutcome {
+    /// Classify the per-provider results of a submission (or simulation)
+    ///
+    /// Confirmation isn't known yet; see [`ExecutionOutcome::with_confirmation`].
+    pub fn from_submission(results: Vec<RpcSubmissionResult>, is_simulation: bool) -> Self {
+        if is_simulation {
+            return ExecutionOutcome::Simulated { results };
+        }
+
+        if !results.iter().any(|(_, success, _)| *success) {
+            return ExecutionOutcome::Failed { results };
+        }
+
+        ExecutionOutcome::Submitted {
+            signatures: results.iter().filter_map(submission_signature).collect(),
+            confirmation: None,
+        }
+    }
+
+    /// Attach the confirmation outcome to a submitted outcome
+    pub fn with_confirmation(self, outcome: ConfirmationOutcome) -> Self {
+        match self {
+            ExecutionOutcome::Submitted { signatures, .. } => ExecutionOutcome::Submitted {
+                signatures,
+                confirmation: Some(outcome),
+            },
+            other => other,
+        }
+    }
+
+    /// Whether any provider accepted the transaction
+    pub fn is_submitted(&self) -> bool {
+        matches!(self, ExecutionOutcome::Submitted { .. })
+    }
+
+    /// Whether the submitted transaction confirmed
+    pub fn is_confirmed(&self) -> bool {
+        matches!(
+            self,
+            ExecutionOutcome::Submitted { confirmation: Some(ConfirmationOutcome::Confirmed(_)), .. }
+        )
+    }
+
+    /// Whether the execution counts as a failure for the circuit breaker
+    pub fn is_failure(&self) -> bool {
+        matches!(self, ExecutionOutcome::Failed { .. })
+    }
+
+    /// Short label for logs and metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecutionOutcome::Skipped(_) => "skipped",
+            ExecutionOutcome::Simulated { .. } => "simulated",
+            ExecutionOutcome::Submitted { .. } => "submitted",
+            ExecutionOutcome::Failed { .. } => "failed",
+        }
+    }
+}
+
+/// How the execution of one opportunity ended, under the opportunity's correlation id
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionReport {
+    pub correlation_id: Uuid,
+    pub outcome: ExecutionOutcome,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulation_yields_simulated() {
+        let results = vec![("Solana RPC (simulation)".to_string(), true, "ok".to_string())];
+        let outcome = ExecutionOutcome::from_submission(results.clone(), true);
+
+        assert_eq!(outcome, ExecutionOutcome::Simulated { results });
+        assert!(!outcome.is_submitted());
+        assert!(!outcome.is_failure());
+    }
+
+    #[test]
+    fn test_all_rejected_yields_failed() {
+        let results = vec![
+            ("Solana RPC".to_string(), false, "blockhash not found".to_string()),
+            ("Jito".to_string(), false, "bundle rejected".to_string()),
+        ];
+        let outcome = ExecutionOutcome::from_submission(results.clone(), false);
+
+        assert_eq!(outcome, ExecutionOutcome::Failed { results });
+        assert!(outcome.is_failure());
+    }
+
+    #[test]
+    fn test_accepted_yields_submitted_with_signatures() {
+        let signature = Signature::new_unique();
+        let results = vec![
+            ("Solana RPC".to_string(), true, signature.to_string()),
+            ("Helius".to_string(), false, "rate limited".to_string()),
+        ];
+        let outcome = ExecutionOutcome::from_submission(results, false);
+
+        assert_eq!(outcome, ExecutionOutcome::Submitted { signatures: vec![signature], confirmation: None });
+        assert!(outcome.is_submitted());
+        assert!(!outcome.is_confirmed());
+        assert!(!outcome.clone().with_confirmation(ConfirmationOutcome::TimedOut).is_confirmed());
+
+        let confirmed = outcome.with_confirmation(ConfirmationOutcome::Confirmed(signature));
+        assert!(confirmed.is_confirmed());
+        assert_eq!(
+            confirmed,
+            ExecutionOutcome::Submitted {
+                signatures: vec![signature],
+                confirmation: Some(ConfirmationOutcome::Confirmed(signature)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_skip_labels() {
+        let outcome = ExecutionOutcome::Skipped(SkipReason::Unprofitable { net_profit: -1.0, min_profit: 0.0 });
+        assert_eq!(outcome.as_str(), "skipped");
+        assert!(!outcome.is_failure());
+        assert_eq!(SkipReason::SimulationRejected.as_str(), "simulation_rejected");
+    }
+}
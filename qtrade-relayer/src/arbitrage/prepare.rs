@@ -1,22 +1,90 @@
 //! Module for preparing arbitrage transactions
 
-use anyhow::{Result, anyhow};
-use qtrade_shared_types::ArbitrageResult;
+use anyhow::{Context, Result, anyhow};
+use once_cell::sync::OnceCell;
+use qtrade_shared_types::{ArbitrageResult, MintDecimals, PoolState};
+use std::sync::Arc;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Keypair;
 use solana_sdk::instruction::Instruction;
 use tracing::{info, warn, error};
+use crate::arbitrage::mint_filter::MintFilter;
 use crate::dex;
 use crate::determine_pool_pubkey;
 use crate::determine_token_indices;
 use crate::metrics::arbitrage::record_failed_arbitrage_transaction;
-use qtrade_wallets::{get_explorer_keypair, return_explorer_keypair};
+use crate::settings::RelayerSettings;
+use crate::utils::checked_u64_amount;
+use qtrade_wallets::{get_funded_explorer_keypair, return_explorer_keypair, trigger_balance, RetirementPolicy};
+
+/// Base fee charged per transaction signature
+pub(crate) const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+/// Signatures on an arbitrage transaction (explorer + nonce authority), before any fee payer
+pub(crate) const SIGNATURES_PER_TRANSACTION: u64 = 2;
+/// Upper bound on compute units requested by an arbitrage transaction
+pub(crate) const MAX_COMPUTE_UNITS: u64 = 1_400_000;
+/// Priority fee paid per compute unit
+pub(crate) const PRIORITY_FEE_MICRO_LAMPORTS_PER_CU: u64 = 100_000;
+/// Largest tip any RPC provider requires
+const MAX_PROVIDER_TIP_LAMPORTS: u64 = 1_000_000;
+
+/// Decimals assumed for mints whose decimals aren't known
+pub const DEFAULT_TOKEN_DECIMALS: u8 = 6;
+
+/// Source of mint decimals, registered by the runtime (the indexer's mint cache)
+static MINT_DECIMALS: OnceCell<Arc<dyn MintDecimals>> = OnceCell::new();
+
+/// Register where swap amounts look up mint decimals
+///
+/// Only the first registration takes effect.
+pub fn set_mint_decimals_source(source: Arc<dyn MintDecimals>) {
+    if MINT_DECIMALS.set(source).is_err() {
+        warn!("Mint decimals source already registered, ignoring");
+    }
+}
+
+/// Decimals of `mint`, falling back to [`DEFAULT_TOKEN_DECIMALS`] when it isn't known
+pub async fn token_decimals(mint: &Pubkey) -> u8 {
+    let decimals = match MINT_DECIMALS.get() {
+        Some(source) => source.get_decimals(mint).await,
+        None => None,
+    };
+    decimals.unwrap_or_else(|| {
+        warn!("Decimals unknown for mint {}, assuming {}", mint, DEFAULT_TOKEN_DECIMALS);
+        DEFAULT_TOKEN_DECIMALS
+    })
+}
+
+/// Convert a token amount in whole units into base units (`amount * 10^decimals`)
+///
+/// Fails on negative, non-finite and too large amounts rather than saturating.
+pub fn to_base_units(amount: f64, decimals: u8) -> Result<u64> {
+    checked_u64_amount(amount * 10f64.powi(decimals as i32))
+        .with_context(|| format!("Can't convert {} to base units with {} decimals", amount, decimals))
+}
+
+/// Signatures on an arbitrage transaction, including the fee payer when one is registered
+pub(crate) fn signatures_per_transaction() -> u64 {
+    SIGNATURES_PER_TRANSACTION + crate::fee_payer::extra_signatures()
+}
+
+/// Lamports an explorer key needs to cover signature fees, priority fees and provider tips
+///
+/// Nothing is needed when a dedicated fee payer covers them.
+pub fn required_explorer_lamports() -> u64 {
+    if crate::fee_payer::fee_payer().is_some() {
+        return 0;
+    }
+    let signature_fees = LAMPORTS_PER_SIGNATURE * SIGNATURES_PER_TRANSACTION;
+    let priority_fees = MAX_COMPUTE_UNITS * PRIORITY_FEE_MICRO_LAMPORTS_PER_CU / 1_000_000;
+    signature_fees + priority_fees + MAX_PROVIDER_TIP_LAMPORTS
+}
 
 /// Validates an arbitrage result to ensure it's valid for execution
 ///
 /// Returns Ok(true) if the arbitrage result is valid and profitable
 /// Returns Ok(false) if the arbitrage result is invalid or not profitable
-/// Returns Err if there was an error during validation
+/// Returns Err if the arbitrage result is malformed (mismatched dimensions or non-finite values)
 pub fn validate_arbitrage_result(arbitrage_result: &ArbitrageResult) -> Result<bool> {
     // 1. Validate the arbitrage result
     if arbitrage_result.status != "optimal" {
@@ -24,6 +92,8 @@ pub fn validate_arbitrage_result(arbitrage_result: &ArbitrageResult) -> Result<b
         return Ok(false);
     }
 
+    check_result_dimensions(arbitrage_result)?;
+
     // Check for at least one pool with non-zero deltas
     let mut has_profitable_pools = false;
     for deltas in &arbitrage_result.deltas {
@@ -41,45 +111,177 @@ pub fn validate_arbitrage_result(arbitrage_result: &ArbitrageResult) -> Result<b
     Ok(true)
 }
 
+/// Checks that the solver output is internally consistent before it is indexed
+///
+/// Every pool must have as many lambdas as deltas, and when A-matrices are present there
+/// must be one per pool, each with one column per local token and the same number of
+/// (global token) rows. All values must be finite.
+fn check_result_dimensions(arbitrage_result: &ArbitrageResult) -> Result<()> {
+    let pool_count = arbitrage_result.deltas.len();
+    if arbitrage_result.lambdas.len() != pool_count {
+        return Err(anyhow!(
+            "Arbitrage result has deltas for {} pools but lambdas for {}",
+            pool_count,
+            arbitrage_result.lambdas.len()
+        ));
+    }
+
+    let a_matrices = &arbitrage_result.a_matrices;
+    if !a_matrices.is_empty() && a_matrices.len() != pool_count {
+        return Err(anyhow!(
+            "Arbitrage result has {} pools but {} A-matrices",
+            pool_count,
+            a_matrices.len()
+        ));
+    }
+
+    let global_token_count = a_matrices.first().map(|a_matrix| a_matrix.len());
+    for (pool_index, (deltas, lambdas)) in arbitrage_result.deltas.iter().zip(&arbitrage_result.lambdas).enumerate() {
+        let token_count = deltas.len();
+        if lambdas.len() != token_count {
+            return Err(anyhow!(
+                "Pool {} has {} deltas but {} lambdas",
+                pool_index,
+                token_count,
+                lambdas.len()
+            ));
+        }
+        if let Some(value) = deltas.iter().chain(lambdas).find(|v| !v.is_finite()) {
+            return Err(anyhow!("Pool {} has a non-finite delta or lambda: {}", pool_index, value));
+        }
+
+        let Some(a_matrix) = a_matrices.get(pool_index) else {
+            continue;
+        };
+        if Some(a_matrix.len()) != global_token_count {
+            return Err(anyhow!(
+                "A-matrix for pool {} has {} rows, expected {}",
+                pool_index,
+                a_matrix.len(),
+                global_token_count.unwrap_or_default()
+            ));
+        }
+        for (row_index, row) in a_matrix.iter().enumerate() {
+            if row.len() != token_count {
+                return Err(anyhow!(
+                    "A-matrix for pool {} has {} columns in row {}, expected {} (one per pool token)",
+                    pool_index,
+                    row.len(),
+                    row_index,
+                    token_count
+                ));
+            }
+            if let Some(value) = row.iter().find(|v| !v.is_finite()) {
+                return Err(anyhow!("A-matrix for pool {} has a non-finite value: {}", pool_index, value));
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Struct to hold swap parameters for an arbitrage operation
 #[derive(Debug, Clone)]
 pub struct ArbitrageSwapParams {
     pub pool_index: usize,
     pub dex_type: dex::DexType,
     pub pool_pubkey: Pubkey,
+    /// The pool's indexed state, which some DEXes build their swaps from (None if the
+    /// router didn't pass it on)
+    pub pool_state: Option<PoolState>,
     pub token_a_wallet: Pubkey,
     pub token_a_mint: Pubkey,
     pub token_a_vault: Pubkey,
+    /// Token program owning token A's mint (legacy SPL Token or Token-2022)
+    pub token_a_program: Pubkey,
     pub token_b_wallet: Pubkey,
     pub token_b_mint: Pubkey,
     pub token_b_vault: Pubkey,
+    /// Token program owning token B's mint
+    pub token_b_program: Pubkey,
+    pub token_a_decimals: u8,
+    pub token_b_decimals: u8,
     pub amount_in: u64,
     pub min_amount_out: u64,
 }
 
+/// Pool indices in the order their swaps should be built
+///
+/// Uses the router's execution order when it covers every pool exactly once, so that
+/// tokens received from earlier swaps fund later ones. Otherwise falls back to pool order.
+pub fn execution_order(arbitrage_result: &ArbitrageResult) -> Vec<usize> {
+    let pool_count = arbitrage_result.deltas.len().min(arbitrage_result.lambdas.len());
+
+    let mut sorted = arbitrage_result.execution_order.clone();
+    sorted.sort_unstable();
+    if sorted.iter().copied().eq(0..pool_count) {
+        arbitrage_result.execution_order.clone()
+    } else {
+        if !arbitrage_result.execution_order.is_empty() {
+            warn!("Ignoring execution order {:?} that doesn't cover all {} pools",
+                arbitrage_result.execution_order, pool_count);
+        }
+        (0..pool_count).collect()
+    }
+}
+
+/// The first mint traded by `swaps` that `mint_filter` doesn't allow, if any
+pub fn disallowed_mint<'a>(
+    swaps: impl IntoIterator<Item = &'a ArbitrageSwapParams>,
+    mint_filter: &MintFilter,
+) -> Option<Pubkey> {
+    mint_filter.first_disallowed(
+        swaps.into_iter().flat_map(|swap| [&swap.token_a_mint, &swap.token_b_mint]),
+    )
+}
+
+/// Keep the `max_legs` most profitable legs, in their original (execution) order
+///
+/// Each leg is paired with its estimated profit. Ties keep the earlier leg.
+pub fn most_profitable_legs<T>(legs: Vec<(T, f64)>, max_legs: usize) -> Vec<(T, f64)> {
+    if legs.len() <= max_legs {
+        return legs;
+    }
+
+    let mut by_profit: Vec<usize> = (0..legs.len()).collect();
+    by_profit.sort_by(|&a, &b| legs[b].1.total_cmp(&legs[a].1).then(a.cmp(&b)));
+    let mut keep = vec![false; legs.len()];
+    for &index in &by_profit[..max_legs] {
+        keep[index] = true;
+    }
+
+    legs.into_iter()
+        .zip(keep)
+        .filter_map(|(leg, keep)| keep.then_some(leg))
+        .collect()
+}
+
 /// Constructs swap parameters based on the arbitrage result
 ///
 /// This function:
-/// 1. Processes each pool in the arbitrage result
+/// 1. Processes each pool in the arbitrage result, in the router's execution order
 /// 2. Calculates profit for each pool
 /// 3. Constructs swap parameters for each profitable operation
+/// 4. Skips the whole opportunity if any swap trades a mint the settings don't allow
+/// 5. Keeps only the `max_pools_per_tx` most profitable swaps, if capped
 ///
 /// Returns Ok(Some((swap_params_list, estimated_profit))) if profitable swap operations were found
-/// Returns Ok(None) if no profitable swap operations were found
+/// Returns Ok(None) if no profitable (and allowed) swap operations were found
 /// Returns Err if there was an error during parameter construction
-pub fn construct_swap_parameters(arbitrage_result: &ArbitrageResult) -> Result<Option<(Vec<ArbitrageSwapParams>, f64)>> {
+pub async fn construct_swap_parameters(
+    arbitrage_result: &ArbitrageResult,
+    settings: &RelayerSettings,
+) -> Result<Option<(Vec<ArbitrageSwapParams>, f64)>> {
     // Record metrics for processing an arbitrage opportunity
     crate::metrics::arbitrage::record_arbitrage_opportunity_processed();
 
-    // Initialize values for tracking profit
-    let mut estimated_profit = 0.0;
-    let mut swap_params_list = Vec::new();
+    // Each prepared swap, paired with the profit estimated for its pool
+    let mut legs = Vec::new();
 
     // Create a more structured approach to creating swap instructions based on deltas and lambdas
-    for (pool_index, (deltas, lambdas)) in arbitrage_result.deltas.iter()
-        .zip(arbitrage_result.lambdas.iter())
-        .enumerate()
-    {
+    for pool_index in execution_order(arbitrage_result) {
+        let (deltas, lambdas) = (&arbitrage_result.deltas[pool_index], &arbitrage_result.lambdas[pool_index]);
+
         // Skip pools with no significant deltas
         let has_nonzero_deltas = deltas.iter().any(|&d| d.abs() > 1e-6);
         if !has_nonzero_deltas {
@@ -107,7 +309,6 @@ pub fn construct_swap_parameters(arbitrage_result: &ArbitrageResult) -> Result<O
 
             if pool_profit > 0.0 {
                 info!("Pool {} estimated profit: {:.6}", pool_index, pool_profit);
-                estimated_profit += pool_profit;
 
                 // Store the necessary parameters for this swap operation
                 // We'll create the actual instruction after obtaining the explorer keypair
@@ -117,17 +318,25 @@ pub fn construct_swap_parameters(arbitrage_result: &ArbitrageResult) -> Result<O
                 let dex_type = dex::determine_dex_type(&pool_pubkey);
                 info!("Determined DEX type: {:?} for pool {}", dex_type, pool_index);
 
-                // Determine token parameters based on deltas
-                // Deltas > 0 means we're spending this token, < 0 means we're receiving
-                let (token_a_index, token_b_index) = determine_token_indices(deltas);
-
-                if token_a_index.is_none() || token_b_index.is_none() {
-                    warn!("Could not determine token indices for pool {}. Skipping.", pool_index);
+                if dex_type == dex::DexType::Unknown {
+                    warn!("Could not determine DEX for pool {} ({}). Skipping.", pool_index, pool_pubkey);
+                    crate::metrics::arbitrage::record_unknown_dex_pool_skipped();
                     continue;
                 }
 
-                let token_a_index = token_a_index.unwrap();
-                let token_b_index = token_b_index.unwrap();
+                // Determine token parameters based on deltas
+                // Deltas > 0 means we're spending this token, < 0 means we're receiving
+                let token_indices = determine_token_indices(deltas);
+
+                // A swap spends one token for another; anything else can't be built
+                let Some((token_a_index, token_b_index)) = token_indices.two_sided() else {
+                    warn!(
+                        "Pool {} spends tokens {:?} for tokens {:?}, which isn't a two-sided swap. Skipping.",
+                        pool_index, token_indices.spent, token_indices.received
+                    );
+                    crate::metrics::arbitrage::record_unswappable_pool_skipped();
+                    continue;
+                };
 
                 // In a real implementation, we would retrieve these from our token registry
                 // For now, creating placeholders
@@ -140,36 +349,72 @@ pub fn construct_swap_parameters(arbitrage_result: &ArbitrageResult) -> Result<O
                 let token_a_vault = Pubkey::new_unique(); // Pool's token A vault
                 let token_b_vault = Pubkey::new_unique(); // Pool's token B vault
 
-                // Calculate the swap amounts
-                let amount_in = (deltas[token_a_index].abs() * 1_000_000.0) as u64;
-                let min_amount_out = (deltas[token_b_index].abs() * 0.99 * 1_000_000.0) as u64; // 1% slippage
+                // Calculate the swap amounts in each mint's base units
+                let token_a_decimals = token_decimals(&token_a_mint).await;
+                let token_b_decimals = token_decimals(&token_b_mint).await;
+                let amounts = to_base_units(deltas[token_a_index].abs(), token_a_decimals).and_then(|amount_in| {
+                    // 1% slippage
+                    let min_amount_out = to_base_units(deltas[token_b_index].abs() * 0.99, token_b_decimals)?;
+                    Ok((amount_in, min_amount_out))
+                });
+                let (amount_in, min_amount_out) = match amounts {
+                    Ok(amounts) => amounts,
+                    Err(e) => {
+                        warn!("Skipping pool {} with an anomalous swap amount: {:#}", pool_index, e);
+                        continue;
+                    }
+                };
 
                 // Create and store the swap parameters
                 let swap_params = ArbitrageSwapParams {
                     pool_index,
                     dex_type,
                     pool_pubkey,
+                    pool_state: arbitrage_result.pool_states.get(pool_index).cloned(),
                     token_a_wallet,
                     token_a_mint,
                     token_a_vault,
+                    // Assumed legacy until the mint accounts are read before landing
+                    token_a_program: spl_token::id(),
                     token_b_wallet,
                     token_b_mint,
                     token_b_vault,
+                    token_b_program: spl_token::id(),
+                    token_a_decimals,
+                    token_b_decimals,
                     amount_in,
                     min_amount_out,
                 };
 
-                swap_params_list.push(swap_params);
+                legs.push((swap_params, pool_profit));
                 info!("Prepared swap parameters for pool {}", pool_index);
             }
         }
     }
 
-    if swap_params_list.is_empty() {
+    if legs.is_empty() {
         info!("No profitable swap operations prepared, skipping execution");
         return Ok(None);
     }
 
+    let mint_filter = settings.get_mint_filter()?;
+    let swaps = legs.iter().map(|(params, _)| params);
+    if let Some(mint) = disallowed_mint(swaps, &mint_filter) {
+        warn!("Skipping opportunity trading mint {}, which isn't allowed", mint);
+        crate::metrics::arbitrage::record_blocked_mint_opportunity_skipped();
+        return Ok(None);
+    }
+
+    if let Some(max_pools) = settings.get_max_pools_per_tx() {
+        if legs.len() > max_pools {
+            info!("Keeping the {} most profitable of {} swap operations", max_pools, legs.len());
+            legs = most_profitable_legs(legs, max_pools);
+        }
+    }
+
+    let estimated_profit = legs.iter().map(|(_, profit)| profit).sum::<f64>();
+    let swap_params_list: Vec<ArbitrageSwapParams> = legs.into_iter().map(|(params, _)| params).collect();
+
     info!("Prepared {} swap operations with estimated profit: {:.6}",
         swap_params_list.len(), estimated_profit);
 
@@ -178,10 +423,12 @@ pub fn construct_swap_parameters(arbitrage_result: &ArbitrageResult) -> Result<O
 
 /// Acquires an explorer keypair from the tiered wallet system for transaction signing
 ///
-/// Returns Ok((pubkey, keypair)) if an explorer keypair is available
-/// Returns Err if no explorer keypairs are available
+/// Only keys holding enough lamports to cover fees and tips are handed out.
+///
+/// Returns Ok((pubkey, keypair)) if a funded explorer keypair is available
+/// Returns Err if no funded explorer keypairs are available
 pub fn acquire_explorer_keypair() -> Result<(Pubkey, Keypair)> {
-    match get_explorer_keypair() {
+    match get_funded_explorer_keypair(required_explorer_lamports()) {
         Some(keypair) => {
             info!("Using explorer keypair with public key: {}", keypair.0);
             Ok(keypair)
@@ -189,6 +436,8 @@ pub fn acquire_explorer_keypair() -> Result<(Pubkey, Keypair)> {
         None => {
             error!("No explorer keypairs available for transaction signing");
             record_failed_arbitrage_transaction();
+            // Top up the explorer pool now rather than waiting for the next scheduled run
+            trigger_balance();
             Err(anyhow!("No explorer keypairs available for transaction signing"))
         }
     }
@@ -207,6 +456,59 @@ pub fn return_explorer_keypair_to_pool(pubkey: &Pubkey, retire: bool) -> Result<
     Ok(())
 }
 
+/// An explorer keypair acquired from the wallet system, released back to it when dropped
+///
+/// Unless [`ExplorerKeyGuard::release`] says otherwise, the key's transaction is taken as
+/// not having landed, so every early return hands the key to the retirement policy.
+pub struct ExplorerKeyGuard {
+    pubkey: Pubkey,
+    keypair: Keypair,
+    landed: bool,
+    retirement_policy: RetirementPolicy,
+    return_to_pool: fn(&Pubkey, bool) -> Result<()>,
+}
+
+impl ExplorerKeyGuard {
+    /// Acquire a funded explorer keypair, as [`acquire_explorer_keypair`] does, to be
+    /// retired on release as `retirement_policy` says
+    pub fn acquire(retirement_policy: RetirementPolicy) -> Result<Self> {
+        let (pubkey, keypair) = acquire_explorer_keypair()?;
+        Ok(Self::new(pubkey, keypair, retirement_policy, return_explorer_keypair_to_pool))
+    }
+
+    fn new(
+        pubkey: Pubkey,
+        keypair: Keypair,
+        retirement_policy: RetirementPolicy,
+        return_to_pool: fn(&Pubkey, bool) -> Result<()>,
+    ) -> Self {
+        Self { pubkey, keypair, landed: false, retirement_policy, return_to_pool }
+    }
+
+    pub fn pubkey(&self) -> &Pubkey {
+        &self.pubkey
+    }
+
+    pub fn keypair(&self) -> &Keypair {
+        &self.keypair
+    }
+
+    /// Release the key now, reporting whether the transaction it signed landed
+    pub fn release(mut self, landed: bool) {
+        self.landed = landed;
+    }
+}
+
+impl Drop for ExplorerKeyGuard {
+    fn drop(&mut self) {
+        let retire = self.retirement_policy.should_retire(self.landed);
+        info!("Releasing explorer keypair {} (landed: {}, retiring: {})", self.pubkey, self.landed, retire);
+        if let Err(e) = (self.return_to_pool)(&self.pubkey, retire) {
+            error!("Failed to release explorer key {}: {:?}", self.pubkey, e);
+        }
+    }
+}
+
 /// Create swap instructions for each swap parameter using the explorer keypair public key
 ///
 /// This function converts the high-level swap parameters into Solana instruction objects
@@ -222,7 +524,10 @@ pub fn create_swap_instructions(
 
     for params in swap_params_list {
         // Create the appropriate DEX swap implementation
-        let dex_swap = dex::create_dex_swap(params.dex_type);
+        let dex_swap = dex::create_dex_swap(params.dex_type, params.pool_state.as_ref()).map_err(|e| {
+            warn!("Failed to create swap instruction for pool {}: {}", params.pool_index, e);
+            anyhow!("Failed to create swap instruction")
+        })?;
 
         // Create the swap instruction with the explorer keypair as the authority
         let swap_instruction = dex_swap.create_swap_instruction(
@@ -231,9 +536,11 @@ pub fn create_swap_instructions(
             &params.token_a_wallet,
             &params.token_a_mint,
             &params.token_a_vault,
+            &params.token_a_program,
             &params.token_b_wallet,
             &params.token_b_mint,
             &params.token_b_vault,
+            &params.token_b_program,
             params.amount_in,
             params.min_amount_out,
             true, // Direction A to B
@@ -253,6 +560,52 @@ pub fn create_swap_instructions(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::arbitrage::test_swap_params;
+
+    static RETURNED: std::sync::Mutex<Vec<(Pubkey, bool)>> = std::sync::Mutex::new(Vec::new());
+
+    fn record_return(pubkey: &Pubkey, retire: bool) -> Result<()> {
+        RETURNED.lock().unwrap().push((*pubkey, retire));
+        Ok(())
+    }
+
+    fn retired(pubkey: &Pubkey) -> Vec<bool> {
+        RETURNED.lock().unwrap().iter().filter(|(returned, _)| returned == pubkey).map(|(_, retire)| *retire).collect()
+    }
+
+    #[test]
+    fn test_explorer_key_is_released_on_every_path() {
+        // Retired when the execution reports its transaction landed
+        let policy = RetirementPolicy::OnSuccessOnly;
+        let key = ExplorerKeyGuard::new(Pubkey::new_unique(), Keypair::new(), policy, record_return);
+        let pubkey = *key.pubkey();
+        key.release(true);
+        assert_eq!(retired(&pubkey), [true]);
+
+        // An early return releases the key as not landed, so it goes back to the pool
+        let keypair = Keypair::new();
+        let pubkey = solana_sdk::signer::Signer::pubkey(&keypair);
+        let bails_after_acquiring = || -> Result<()> {
+            let _key = ExplorerKeyGuard::new(pubkey, keypair, policy, record_return);
+            Err(anyhow!("failed to build swap instructions"))
+        };
+        assert!(bails_after_acquiring().is_err());
+        assert_eq!(retired(&pubkey), [false]);
+    }
+
+    #[test]
+    fn test_explorer_key_retirement_follows_the_policy() {
+        for (policy, expected) in [
+            (RetirementPolicy::Always, [true, true]),
+            (RetirementPolicy::OnSuccessOnly, [true, false]),
+            (RetirementPolicy::Never, [false, false]),
+        ] {
+            let pubkey = Pubkey::new_unique();
+            ExplorerKeyGuard::new(pubkey, Keypair::new(), policy, record_return).release(true);
+            ExplorerKeyGuard::new(pubkey, Keypair::new(), policy, record_return).release(false);
+            assert_eq!(retired(&pubkey), expected, "{}", policy.as_str());
+        }
+    }
 
     #[test]
     fn test_validate_arbitrage_result_optimal() {
@@ -261,7 +614,15 @@ mod tests {
             status: "optimal".to_string(),
             deltas: vec![vec![0.001, -0.0009]],
             lambdas: vec![vec![0.0, 0.0]],
-            a_matrices: vec![vec![vec![0.0]]],
+            a_matrices: vec![vec![vec![1.0, 0.0], vec![0.0, 1.0]]],
+            execution_order: vec![],
+            market_values: vec![],
+            correlation_id: None,
+            emitted_at: None,
+            enqueued_at: None,
+            pool_fees: vec![],
+            pool_states: vec![],
+            version: ArbitrageResult::VERSION,
         };
 
         let result = validate_arbitrage_result(&arbitrage_result).unwrap();
@@ -275,7 +636,15 @@ mod tests {
             status: "suboptimal".to_string(),
             deltas: vec![vec![0.001, -0.0009]],
             lambdas: vec![vec![0.0, 0.0]],
-            a_matrices: vec![vec![vec![0.0]]],
+            a_matrices: vec![vec![vec![1.0, 0.0], vec![0.0, 1.0]]],
+            execution_order: vec![],
+            market_values: vec![],
+            correlation_id: None,
+            emitted_at: None,
+            enqueued_at: None,
+            pool_fees: vec![],
+            pool_states: vec![],
+            version: ArbitrageResult::VERSION,
         };
 
         let result = validate_arbitrage_result(&arbitrage_result).unwrap();
@@ -289,49 +658,217 @@ mod tests {
             status: "optimal".to_string(),
             deltas: vec![vec![0.0, 0.0]],
             lambdas: vec![vec![0.0, 0.0]],
-            a_matrices: vec![vec![vec![0.0]]],
+            a_matrices: vec![vec![vec![1.0, 0.0], vec![0.0, 1.0]]],
+            execution_order: vec![],
+            market_values: vec![],
+            correlation_id: None,
+            emitted_at: None,
+            enqueued_at: None,
+            pool_fees: vec![],
+            pool_states: vec![],
+            version: ArbitrageResult::VERSION,
         };
 
         let result = validate_arbitrage_result(&arbitrage_result).unwrap();
         assert!(!result, "Should validate as false for zero deltas");
     }
 
+    fn two_pool_result() -> ArbitrageResult {
+        ArbitrageResult {
+            status: "optimal".to_string(),
+            deltas: vec![vec![0.001, 0.0], vec![0.0, 0.002]],
+            lambdas: vec![vec![0.0, 0.0009], vec![0.0019, 0.0]],
+            a_matrices: vec![
+                vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![0.0, 0.0]],
+                vec![vec![0.0, 0.0], vec![1.0, 0.0], vec![0.0, 1.0]],
+            ],
+            execution_order: vec![],
+            market_values: vec![],
+            correlation_id: None,
+            emitted_at: None,
+            enqueued_at: None,
+            pool_fees: vec![],
+            pool_states: vec![],
+            version: ArbitrageResult::VERSION,
+        }
+    }
+
+    #[test]
+    fn test_validate_arbitrage_result_consistent_dimensions() {
+        assert!(validate_arbitrage_result(&two_pool_result()).unwrap());
+
+        // A-matrices are optional
+        let mut result = two_pool_result();
+        result.a_matrices.clear();
+        assert!(validate_arbitrage_result(&result).unwrap());
+    }
+
+    #[test]
+    fn test_validate_arbitrage_result_rejects_malformed_results() {
+        let cases: [(&str, fn(&mut ArbitrageResult)); 8] = [
+            ("missing lambdas for a pool", |r| {
+                r.lambdas.pop();
+            }),
+            ("lambdas shorter than deltas", |r| {
+                r.lambdas[1].pop();
+            }),
+            ("A-matrix missing for a pool", |r| {
+                r.a_matrices.pop();
+            }),
+            ("A-matrix row count differs between pools", |r| {
+                r.a_matrices[1].pop();
+            }),
+            ("A-matrix columns don't match pool tokens", |r| {
+                r.a_matrices[0][2].push(0.0);
+            }),
+            ("NaN delta", |r| r.deltas[0][1] = f64::NAN),
+            ("infinite lambda", |r| r.lambdas[1][0] = f64::INFINITY),
+            ("non-finite A-matrix entry", |r| r.a_matrices[1][0][0] = f64::NEG_INFINITY),
+        ];
+
+        for (description, corrupt) in cases {
+            let mut result = two_pool_result();
+            corrupt(&mut result);
+            assert!(validate_arbitrage_result(&result).is_err(), "Should reject result with {}", description);
+        }
+    }
+
+    #[test]
+    fn test_validate_non_optimal_result_skips_dimension_checks() {
+        let mut result = two_pool_result();
+        result.status = "infeasible".to_string();
+        result.deltas[0][0] = f64::NAN;
+        result.lambdas.clear();
+        assert!(!validate_arbitrage_result(&result).unwrap());
+    }
+
+    struct TestMints(Vec<(Pubkey, u8)>);
+
+    #[async_trait::async_trait]
+    impl MintDecimals for TestMints {
+        async fn get_decimals(&self, mint: &Pubkey) -> Option<u8> {
+            self.0.iter().find(|(key, _)| key == mint).map(|(_, decimals)| *decimals)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_amounts_scale_by_mint_decimals() {
+        let usdc = Pubkey::new_unique();
+        let sol = Pubkey::new_unique();
+        set_mint_decimals_source(Arc::new(TestMints(vec![(usdc, 6), (sol, 9)])));
+
+        assert_eq!(token_decimals(&usdc).await, 6);
+        assert_eq!(token_decimals(&sol).await, 9);
+        assert_eq!(token_decimals(&Pubkey::new_unique()).await, DEFAULT_TOKEN_DECIMALS);
+
+        // 1.5 tokens in each mint's base units
+        assert_eq!(to_base_units(1.5, token_decimals(&usdc).await).unwrap(), 1_500_000);
+        assert_eq!(to_base_units(1.5, token_decimals(&sol).await).unwrap(), 1_500_000_000);
+    }
+
+    #[test]
+    fn test_to_base_units_rejects_anomalous_amounts() {
+        assert_eq!(to_base_units(0.25, 9).unwrap(), 250_000_000);
+        assert_eq!(to_base_units(0.0, 9).unwrap(), 0);
+        // 1.8e10 SOL is about the most that fits in a u64 of lamports
+        assert_eq!(to_base_units(18_000_000_000.0, 9).unwrap(), 18_000_000_000_000_000_000);
+        assert!(to_base_units(1e12, 9).is_err());
+        assert!(to_base_units(-1.0, 6).is_err());
+        assert!(to_base_units(f64::NAN, 6).is_err());
+        assert!(to_base_units(f64::INFINITY, 6).is_err());
+    }
+
+    #[test]
+    fn test_execution_order_follows_router_order() {
+        let mut arbitrage_result = ArbitrageResult {
+            status: "optimal".to_string(),
+            deltas: vec![vec![0.0, 0.5], vec![2.0, 0.0], vec![0.1, 0.0]],
+            lambdas: vec![vec![0.6, 0.0], vec![0.0, 0.15], vec![0.0, 0.4]],
+            a_matrices: vec![],
+            execution_order: vec![1, 2, 0],
+            market_values: vec![],
+            correlation_id: None,
+            emitted_at: None,
+            enqueued_at: None,
+            pool_fees: vec![],
+            pool_states: vec![],
+            version: ArbitrageResult::VERSION,
+        };
+        assert_eq!(execution_order(&arbitrage_result), vec![1, 2, 0]);
+
+        // Orders that skip or repeat a pool fall back to pool order
+        arbitrage_result.execution_order = vec![1, 1, 0];
+        assert_eq!(execution_order(&arbitrage_result), vec![0, 1, 2]);
+
+        arbitrage_result.execution_order = vec![];
+        assert_eq!(execution_order(&arbitrage_result), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_most_profitable_legs_respects_cap() {
+        // Legs in execution order, tagged by pool index
+        let legs = vec![(0, 0.2), (1, 0.9), (2, 0.1), (3, 0.5), (4, 0.5)];
+
+        // The two most profitable, kept in execution order
+        assert_eq!(most_profitable_legs(legs.clone(), 2), vec![(1, 0.9), (3, 0.5)]);
+        assert_eq!(most_profitable_legs(legs.clone(), 3), vec![(1, 0.9), (3, 0.5), (4, 0.5)]);
+        assert_eq!(most_profitable_legs(legs.clone(), 1), vec![(1, 0.9)]);
+        // Under the cap, nothing is dropped
+        assert_eq!(most_profitable_legs(legs.clone(), 5), legs);
+    }
+
+    fn swap_between(token_a_mint: Pubkey, token_b_mint: Pubkey) -> ArbitrageSwapParams {
+        ArbitrageSwapParams { token_a_mint, token_b_mint, ..test_swap_params() }
+    }
+
+    #[test]
+    fn test_opportunity_with_blocked_mint_is_rejected() {
+        let (sol, usdc, scam) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let swaps = vec![swap_between(sol, usdc), swap_between(usdc, scam)];
+
+        let mut settings = RelayerSettings::default();
+        settings.blocked_mints = vec![scam.to_string()];
+        let mint_filter = settings.get_mint_filter().unwrap();
+
+        assert_eq!(disallowed_mint(&swaps, &mint_filter), Some(scam));
+        assert_eq!(disallowed_mint(&swaps[..1], &mint_filter), None);
+    }
+
+    #[test]
+    fn test_opportunity_within_allowed_mints_passes() {
+        let (sol, usdc, bonk) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let swaps = vec![swap_between(sol, usdc), swap_between(usdc, sol)];
+
+        let mut settings = RelayerSettings::default();
+        settings.allowed_mints = vec![sol.to_string(), usdc.to_string()];
+        let mint_filter = settings.get_mint_filter().unwrap();
+
+        assert_eq!(disallowed_mint(&swaps, &mint_filter), None);
+        assert_eq!(disallowed_mint(&[swap_between(sol, bonk)], &mint_filter), Some(bonk));
+    }
+
     // Note: For this task's focused scope, we're skipping the unit tests for construct_swap_parameters.
     // These tests will require mock implementations of determine_pool_pubkey and determine_dex_type,
     // which would be better implemented using a proper dependency injection pattern.
     // This will be addressed in a future task when we implement more sophisticated testing infrastructure.
 
     #[test]
-    fn test_create_swap_instructions() {
-        // Create a mock DEX type and pool pubkey
-        let pool_pubkey = Pubkey::new_unique();
-        let dex_type = dex::DexType::Orca;
-
-        // Create mock token accounts
-        let token_a_wallet = Pubkey::new_unique();
-        let token_a_mint = Pubkey::new_unique();
-        let token_a_vault = Pubkey::new_unique();
-        let token_b_wallet = Pubkey::new_unique();
-        let token_b_mint = Pubkey::new_unique();
-        let token_b_vault = Pubkey::new_unique();
+    fn test_create_swap_instructions_rejects_unknown_dex() {
+        let swap_param = ArbitrageSwapParams {
+            dex_type: dex::DexType::Unknown,
+            ..test_swap_params()
+        };
+
+        assert!(create_swap_instructions(&[swap_param], &Pubkey::new_unique()).is_err());
+    }
 
+    #[test]
+    fn test_create_swap_instructions() {
         // Create mock explorer pubkey
         let explorer_pubkey = Pubkey::new_unique();
 
-        // Create a swap parameter
-        let swap_param = ArbitrageSwapParams {
-            pool_index: 0,
-            dex_type,
-            pool_pubkey,
-            token_a_wallet,
-            token_a_mint,
-            token_a_vault,
-            token_b_wallet,
-            token_b_mint,
-            token_b_vault,
-            amount_in: 1000,
-            min_amount_out: 990,
-        };
+        // Create a swap parameter on an Orca pool
+        let swap_param = test_swap_params();
 
         // Call the function with a list containing one swap parameter
         let result = create_swap_instructions(&[swap_param], &explorer_pubkey);
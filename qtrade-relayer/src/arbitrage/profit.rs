@@ -0,0 +1,314 @@
+//! Module for estimating the net profit of an arbitrage opportunity
+//!
+//! The router reports per-pool tendered (delta) and received (lambda) amounts in each
+//! token's own units. Profit is only meaningful once those amounts are priced in a single
+//! numeraire using the router's per-token market values, and once the costs of landing
+//! the transaction (signature fees, priority fees and tips) are taken off.
+//!
+//! DEX fees are already reflected in the lambdas, since the router's pool models apply
+//! each pool's fee when computing what a trade receives.
+
+use anyhow::{Result, anyhow};
+use qtrade_shared_types::ArbitrageResult;
+use tracing::{info, warn};
+
+use crate::arbitrage::outcome::SkipReason;
+use crate::arbitrage::prepare::{
+    LAMPORTS_PER_SIGNATURE,
+    MAX_COMPUTE_UNITS,
+    PRIORITY_FEE_MICRO_LAMPORTS_PER_CU,
+    signatures_per_transaction,
+};
+
+/// Lamports per SOL
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// SOL price used to value landing costs when none is configured
+pub const DEFAULT_SOL_PRICE_USD: f64 = 150.0;
+
+/// Largest share of the gross profit that landing costs may take (50%)
+pub const DEFAULT_MAX_FEE_FRACTION_OF_PROFIT: f64 = 0.5;
+
+/// Estimated costs of landing an arbitrage transaction, in lamports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionCosts {
+    /// Base fee for the transaction's signatures
+    pub network_fee_lamports: u64,
+    /// Priority fee for the requested compute units
+    pub priority_fee_lamports: u64,
+    /// Tip paid to the submission provider
+    pub tip_lamports: u64,
+}
+
+impl TransactionCosts {
+    /// Costs of a transaction requesting `compute_units` and paying `tip_lamports`
+    pub fn new(compute_units: u64, tip_lamports: u64) -> Self {
+        Self {
+            network_fee_lamports: LAMPORTS_PER_SIGNATURE * signatures_per_transaction(),
+            priority_fee_lamports: compute_units * PRIORITY_FEE_MICRO_LAMPORTS_PER_CU / 1_000_000,
+            tip_lamports,
+        }
+    }
+
+    /// Worst-case costs: the full compute budget plus `tip_lamports`
+    pub fn estimate(tip_lamports: u64) -> Self {
+        Self::new(MAX_COMPUTE_UNITS, tip_lamports)
+    }
+
+    pub fn total_lamports(&self) -> u64 {
+        self.network_fee_lamports + self.priority_fee_lamports + self.tip_lamports
+    }
+}
+
+/// Profit of an arbitrage opportunity in the router's numeraire, net of landing costs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetProfitEstimate {
+    /// Value received minus value tendered across all pools
+    pub gross_profit: f64,
+    /// Value of the signature fees
+    pub network_fee: f64,
+    /// Value of the priority fees
+    pub priority_fee: f64,
+    /// Value of the provider tip
+    pub tip: f64,
+    /// Gross profit minus every cost
+    pub net_profit: f64,
+}
+
+impl NetProfitEstimate {
+    /// Price `gross_profit` net of `costs`, converting lamports at `sol_price`
+    fn from_gross(gross_profit: f64, costs: &TransactionCosts, sol_price: f64) -> Self {
+        let to_value = |lamports: u64| lamports as f64 / LAMPORTS_PER_SOL * sol_price;
+
+        let network_fee = to_value(costs.network_fee_lamports);
+        let priority_fee = to_value(costs.priority_fee_lamports);
+        let tip = to_value(costs.tip_lamports);
+
+        Self {
+            gross_profit,
+            network_fee,
+            priority_fee,
+            tip,
+            net_profit: gross_profit - network_fee - priority_fee - tip,
+        }
+    }
+
+    /// The same opportunity priced with other landing `costs`, at the `sol_price` it was
+    /// estimated with
+    pub fn with_costs(&self, costs: &TransactionCosts, sol_price: f64) -> Self {
+        Self::from_gross(self.gross_profit, costs, sol_price)
+    }
+
+    /// Total value of all landing costs
+    pub fn total_costs(&self) -> f64 {
+        self.network_fee + self.priority_fee + self.tip
+    }
+
+    pub fn is_profitable(&self, min_profit: f64) -> bool {
+        self.net_profit >= min_profit
+    }
+
+    /// Whether the landing costs take more than `max_fraction` of the gross profit
+    pub fn fees_exceed(&self, max_fraction: f64) -> bool {
+        self.total_costs() > self.gross_profit * max_fraction
+    }
+}
+
+/// Reason to skip an opportunity whose landing costs take more than `max_fraction` of
+/// its gross profit, or `None` when they're within the cap
+///
+/// Skips are counted in the `fee_cap_exceeded` metric.
+pub fn check_fee_cap(estimate: &NetProfitEstimate, max_fraction: f64) -> Option<SkipReason> {
+    if !estimate.fees_exceed(max_fraction) {
+        return None;
+    }
+
+    let max_fees = estimate.gross_profit * max_fraction;
+    info!("Fees {:.6} exceed the cap of {:.6} ({}% of gross profit {:.6})",
+        estimate.total_costs(), max_fees, max_fraction * 100.0, estimate.gross_profit);
+    crate::metrics::arbitrage::record_arbitrage_fee_cap_exceeded();
+
+    Some(SkipReason::FeeCapExceeded {
+        fees: estimate.total_costs(),
+        max_fees,
+    })
+}
+
+/// Global token index of local token `local_index` in pool `pool_index`
+///
+/// Reads the pool's A-matrix (global rows by local columns). Pools without a usable
+/// A-matrix are assumed to use global indices directly.
+pub fn global_token_index(result: &ArbitrageResult, pool_index: usize, local_index: usize) -> usize {
+    result
+        .a_matrices
+        .get(pool_index)
+        .and_then(|a_matrix| {
+            a_matrix
+                .iter()
+                .position(|row| row.get(local_index).is_some_and(|&entry| entry > 0.5))
+        })
+        .unwrap_or(local_index)
+}
+
+/// Value received minus value tendered across every pool, priced with `result.market_values`
+///
+/// Results without market values (or tokens beyond them) are priced at 1.0 per unit.
+pub fn gross_profit(result: &ArbitrageResult) -> f64 {
+    result
+        .deltas
+        .iter()
+        .zip(&result.lambdas)
+        .enumerate()
+        .flat_map(|(pool_index, (deltas, lambdas))| {
+            deltas
+                .iter()
+                .zip(lambdas)
+                .enumerate()
+                .map(move |(local_index, (delta, lambda))| (pool_index, local_index, lambda - delta))
+        })
+        .map(|(pool_index, local_index, net_tokens)| {
+            let token = global_token_index(result, pool_index, local_index);
+            let value = result.market_values.get(token).copied().unwrap_or(1.0);
+            net_tokens * value
+        })
+        .sum()
+}
+
+/// Estimate the profit of `result` net of `costs`
+///
+/// `sol_price` converts lamport costs into the numeraire of the router's market values.
+pub fn estimate_net_profit(
+    result: &ArbitrageResult,
+    costs: &TransactionCosts,
+    sol_price: f64,
+) -> Result<NetProfitEstimate> {
+    if !sol_price.is_finite() || sol_price < 0.0 {
+        return Err(anyhow!("Invalid SOL price for profit estimate: {}", sol_price));
+    }
+    if result.market_values.is_empty() {
+        warn!("Arbitrage result has no market values; pricing every token at 1.0");
+    }
+
+    Ok(NetProfitEstimate::from_gross(gross_profit(result), costs, sol_price))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+
+    // TOKEN-0 is worth 2.0 and TOKEN-1 0.5. Pool 0 sells 1 TOKEN-0 for 5 TOKEN-1,
+    // pool 1 sells 4.5 TOKEN-1 for 1.2 TOKEN-0, leaving +0.2 TOKEN-0 and +0.5 TOKEN-1
+    fn two_pool_result(market_values: Vec<f64>) -> ArbitrageResult {
+        ArbitrageResult {
+            deltas: vec![vec![1.0, 0.0], vec![0.0, 4.5]],
+            lambdas: vec![vec![0.0, 5.0], vec![1.2, 0.0]],
+            a_matrices: vec![
+                vec![vec![1.0, 0.0], vec![0.0, 1.0]],
+                vec![vec![1.0, 0.0], vec![0.0, 1.0]],
+            ],
+            status: "optimal".to_string(),
+            execution_order: vec![],
+            market_values,
+            correlation_id: None,
+            emitted_at: None,
+            enqueued_at: None,
+            pool_fees: vec![],
+            pool_states: vec![],
+            version: ArbitrageResult::VERSION,
+        }
+    }
+
+    #[test]
+    fn test_transaction_costs() {
+        // 2 signatures * 5_000 + 200_000 CU * 0.1 lamports + 10_000 tip
+        let costs = TransactionCosts::new(200_000, 10_000);
+        assert_eq!(costs.network_fee_lamports, 10_000);
+        assert_eq!(costs.priority_fee_lamports, 20_000);
+        assert_eq!(costs.total_lamports(), 40_000);
+    }
+
+    #[test]
+    fn test_net_profit_matches_hand_calculation() {
+        let result = two_pool_result(vec![2.0, 0.5]);
+        let costs = TransactionCosts::new(200_000, 10_000);
+
+        let estimate = estimate_net_profit(&result, &costs, 150.0).unwrap();
+
+        // 0.2 * 2.0 + 0.5 * 0.5 = 0.65
+        assert!((estimate.gross_profit - 0.65).abs() < EPSILON);
+        // 10_000 / 20_000 / 10_000 lamports at 150 per SOL
+        assert!((estimate.network_fee - 0.0015).abs() < EPSILON);
+        assert!((estimate.priority_fee - 0.003).abs() < EPSILON);
+        assert!((estimate.tip - 0.0015).abs() < EPSILON);
+        assert!((estimate.total_costs() - 0.006).abs() < EPSILON);
+        assert!((estimate.net_profit - 0.644).abs() < EPSILON);
+
+        assert!(estimate.is_profitable(0.5));
+        assert!(!estimate.is_profitable(0.65));
+
+        // Repricing with the final costs matches a fresh estimate
+        let final_costs = TransactionCosts::new(50_000, 10_000);
+        assert_eq!(estimate.with_costs(&final_costs, 150.0), estimate_net_profit(&result, &final_costs, 150.0).unwrap());
+    }
+
+    #[test]
+    fn test_a_matrix_maps_local_tokens_to_global() {
+        // Pool 1 lists its tokens in the opposite order to the global indices
+        let mut result = two_pool_result(vec![2.0, 0.5]);
+        result.deltas[1] = vec![4.5, 0.0];
+        result.lambdas[1] = vec![0.0, 1.2];
+        result.a_matrices[1] = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+
+        assert!((gross_profit(&result) - 0.65).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_costs_can_erase_profit() {
+        let result = two_pool_result(vec![2.0, 0.5]);
+        let costs = TransactionCosts::new(200_000, 10_000_000);
+
+        // A 0.01 SOL tip costs 1.5 at 150 per SOL, more than the 0.65 gross profit
+        let estimate = estimate_net_profit(&result, &costs, 150.0).unwrap();
+        assert!(estimate.net_profit < 0.0);
+        assert!(!estimate.is_profitable(0.0));
+    }
+
+    #[test]
+    fn test_high_priority_fee_exceeds_fee_cap() {
+        use crate::metrics::arbitrage::get_total_fee_cap_exceeded;
+
+        let result = two_pool_result(vec![2.0, 0.5]);
+
+        // Standard fees are a sliver of the 0.65 gross profit
+        let estimate = estimate_net_profit(&result, &TransactionCosts::new(200_000, 10_000), 150.0).unwrap();
+        assert_eq!(check_fee_cap(&estimate, 0.4), None);
+
+        // A 0.002 SOL priority fee costs 0.3: still profitable, but over 40% of the profit
+        let costs = TransactionCosts { priority_fee_lamports: 2_000_000, ..TransactionCosts::new(200_000, 10_000) };
+        let estimate = estimate_net_profit(&result, &costs, 150.0).unwrap();
+        assert!(estimate.is_profitable(0.0));
+
+        let skipped_before = get_total_fee_cap_exceeded();
+        match check_fee_cap(&estimate, 0.4) {
+            Some(SkipReason::FeeCapExceeded { fees, max_fees }) => {
+                assert!((fees - 0.303).abs() < EPSILON);
+                assert!((max_fees - 0.26).abs() < EPSILON);
+            },
+            other => panic!("expected the fee cap to skip submission, got {:?}", other),
+        }
+        assert!(get_total_fee_cap_exceeded() > skipped_before);
+
+        // A looser cap lets the same fees through
+        assert_eq!(check_fee_cap(&estimate, 0.5), None);
+    }
+
+    #[test]
+    fn test_missing_market_values_price_tokens_at_one() {
+        let result = two_pool_result(vec![]);
+        assert!((gross_profit(&result) - 0.7).abs() < EPSILON);
+
+        assert!(estimate_net_profit(&result, &TransactionCosts::new(0, 0), f64::NAN).is_err());
+    }
+}
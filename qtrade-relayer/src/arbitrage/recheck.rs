@@ -0,0 +1,324 @@
+//! Module for re-checking arbitrage profitability against live reserves before landing
+
+use anyhow::{Result, anyhow};
+use qtrade_shared_types::PoolFee;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::extension::StateWithExtensions;
+use spl_token_2022::state::Account as TokenAccount;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+use crate::arbitrage::prepare::ArbitrageSwapParams;
+use crate::metrics::arbitrage::record_arbitrage_opportunity_expired;
+
+/// Fee assumed for pools whose fee rate wasn't indexed (0.3%)
+const DEFAULT_POOL_FEE: PoolFee = PoolFee::new(30, 10_000);
+
+/// Balances of a pool's vaults at the time of the re-check
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LiveReserves {
+    /// Mint and balance of each of the pool's two vaults
+    pub vaults: [(Pubkey, u64); 2],
+    /// Fee the pool charges on each input
+    pub fee: PoolFee,
+}
+
+impl LiveReserves {
+    /// Reserves of `mint_in` and of the other token, None if neither vault holds `mint_in`
+    pub fn oriented(&self, mint_in: &Pubkey) -> Option<(u64, u64)> {
+        let [(mint_a, reserve_a), (mint_b, reserve_b)] = self.vaults;
+        if mint_a == *mint_in {
+            Some((reserve_a, reserve_b))
+        } else if mint_b == *mint_in {
+            Some((reserve_b, reserve_a))
+        } else {
+            None
+        }
+    }
+}
+
+/// Source of live pool reserves
+pub trait ReserveSource {
+    /// Fetch the current reserves for the pool in the given swap
+    fn get_reserves(&self, params: &ArbitrageSwapParams) -> Result<LiveReserves>;
+}
+
+/// Reads live reserves from the pool's token vaults over RPC
+///
+/// Each pool is charged the fee the indexer parsed for it, by pool index, or
+/// [`DEFAULT_POOL_FEE`] if it has none.
+pub struct RpcReserveSource<'a> {
+    rpc_client: &'a RpcClient,
+    pool_fees: &'a [Option<PoolFee>],
+}
+
+impl<'a> RpcReserveSource<'a> {
+    pub fn new(rpc_client: &'a RpcClient, pool_fees: &'a [Option<PoolFee>]) -> Self {
+        Self { rpc_client, pool_fees }
+    }
+}
+
+impl ReserveSource for RpcReserveSource<'_> {
+    fn get_reserves(&self, params: &ArbitrageSwapParams) -> Result<LiveReserves> {
+        let vault_pubkeys = [params.token_a_vault, params.token_b_vault];
+        let accounts = self.rpc_client
+            .get_multiple_accounts(&vault_pubkeys)
+            .map_err(|e| anyhow!("Failed to fetch vaults of pool {}: {}", params.pool_pubkey, e))?;
+
+        let mut vaults = [(Pubkey::default(), 0); 2];
+        for ((vault, pubkey), account) in vaults.iter_mut().zip(&vault_pubkeys).zip(accounts) {
+            let account = account.ok_or_else(|| anyhow!("Vault {} not found", pubkey))?;
+            let token_account = StateWithExtensions::<TokenAccount>::unpack(&account.data)
+                .map_err(|e| anyhow!("Invalid vault account {}: {}", pubkey, e))?;
+            *vault = (token_account.base.mint, token_account.base.amount);
+        }
+
+        let fee = self.pool_fees.get(params.pool_index).copied().flatten().unwrap_or(DEFAULT_POOL_FEE);
+        Ok(LiveReserves { vaults, fee })
+    }
+}
+
+/// Expected output of a constant-product swap of `amount_in` against the given reserves
+pub fn expected_amount_out(amount_in: u64, reserve_in: u64, reserve_out: u64, fee: PoolFee) -> u64 {
+    if reserve_in == 0 || reserve_out == 0 {
+        return 0;
+    }
+
+    let amount_in_after_fee = if fee.denominator == 0 {
+        amount_in as u128
+    } else {
+        let fee_numerator = fee.numerator.min(fee.denominator) as u128;
+        amount_in as u128 * (fee.denominator as u128 - fee_numerator) / fee.denominator as u128
+    };
+    let numerator = reserve_out as u128 * amount_in_after_fee;
+    let denominator = reserve_in as u128 + amount_in_after_fee;
+
+    (numerator / denominator) as u64
+}
+
+/// Recomputes the expected profit of the swaps against live reserves, hop by hop
+///
+/// Each swap spends what the one before it received, starting from the first swap's
+/// `amount_in`, in the direction of its own input mint and with its pool's fee. The
+/// route's profit is what comes back in the first swap's input mint less what went in,
+/// valued with `mint_values` (wrapped SOL at `sol_price` without one, other mints at 1.0).
+///
+/// Returns true if every swap still meets its minimum output and the profit is at least
+/// `min_profit_usd`. Returns false and records an "opportunity_expired" metric
+/// otherwise. Also returns false if a pool's reserves can't be fetched or the swaps
+/// don't form a route back to their input mint, since the opportunity can't be priced.
+pub fn recheck_profitability(
+    swap_params_list: &[ArbitrageSwapParams],
+    reserve_source: &dyn ReserveSource,
+    mint_values: &HashMap<Pubkey, f64>,
+    sol_price: f64,
+    min_profit_usd: f64,
+) -> bool {
+    let Some(first) = swap_params_list.first() else {
+        return false;
+    };
+    let (start_mint, start_amount) = (first.token_a_mint, first.amount_in);
+    let (mut mint, mut amount) = (start_mint, start_amount);
+
+    for params in swap_params_list {
+        if params.token_a_mint != mint {
+            warn!("Pool {} spends {}, not the {} the previous swap received; skipping opportunity",
+                params.pool_index, params.token_a_mint, mint);
+            return false;
+        }
+
+        let reserves = match reserve_source.get_reserves(params) {
+            Ok(reserves) => reserves,
+            Err(e) => {
+                warn!("Couldn't re-check pool {} against live reserves, skipping opportunity: {:#}", params.pool_index, e);
+                return false;
+            }
+        };
+        let Some((reserve_in, reserve_out)) = reserves.oriented(&params.token_a_mint) else {
+            warn!("Pool {} holds no {} vault, skipping opportunity", params.pool_index, params.token_a_mint);
+            return false;
+        };
+
+        let amount_out = expected_amount_out(amount, reserve_in, reserve_out, reserves.fee);
+        if amount_out < params.min_amount_out {
+            warn!(
+                "Pool {} now returns {} (< minimum {}), opportunity expired",
+                params.pool_index, amount_out, params.min_amount_out
+            );
+            record_arbitrage_opportunity_expired();
+            return false;
+        }
+
+        (mint, amount) = (params.token_b_mint, amount_out);
+    }
+
+    if mint != start_mint {
+        warn!("Swaps end in {} rather than their input mint {}, skipping opportunity", mint, start_mint);
+        return false;
+    }
+
+    // Amounts are in base units of the input mint
+    let unit_value = match mint_values.get(&start_mint) {
+        Some(value) => *value,
+        None if start_mint == spl_token::native_mint::id() => sol_price,
+        None => 1.0,
+    };
+    let profit = (amount as f64 - start_amount as f64) / 10f64.powi(first.token_a_decimals as i32);
+    let expected_profit = profit * unit_value;
+
+    if expected_profit < min_profit_usd {
+        warn!(
+            "Expected profit {:.6} against live reserves is below minimum {:.6}, opportunity expired",
+            expected_profit, min_profit_usd
+        );
+        record_arbitrage_opportunity_expired();
+        return false;
+    }
+
+    info!("Live reserve re-check passed with expected profit: {:.6}", expected_profit);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::test_swap_params;
+
+    const FEE_30_BPS: PoolFee = PoolFee::new(30, 10_000);
+
+    /// Reserve source returning fixed reserves per pool, failing for unknown pools
+    struct FixedReserves(HashMap<Pubkey, LiveReserves>);
+
+    impl ReserveSource for FixedReserves {
+        fn get_reserves(&self, params: &ArbitrageSwapParams) -> Result<LiveReserves> {
+            self.0.get(&params.pool_pubkey).copied().ok_or_else(|| anyhow!("connection refused"))
+        }
+    }
+
+    fn swap_params(mint_in: Pubkey, mint_out: Pubkey, amount_in: u64, min_amount_out: u64) -> ArbitrageSwapParams {
+        ArbitrageSwapParams {
+            token_a_mint: mint_in,
+            token_b_mint: mint_out,
+            amount_in,
+            min_amount_out,
+            ..test_swap_params()
+        }
+    }
+
+    /// A round trip of 1 USDC through X and back, with both pools' vaults listed USDC first
+    struct RoundTrip {
+        usdc: Pubkey,
+        x: Pubkey,
+        swaps: Vec<ArbitrageSwapParams>,
+    }
+
+    impl RoundTrip {
+        fn new() -> Self {
+            let (usdc, x) = (Pubkey::new_unique(), Pubkey::new_unique());
+            let swaps = vec![swap_params(usdc, x, 1_000_000, 0), swap_params(x, usdc, 1_000_000, 0)];
+            Self { usdc, x, swaps }
+        }
+
+        /// Reserves with the given (USDC, X) balances for each pool
+        fn reserves(&self, pools: [(u64, u64, PoolFee); 2]) -> FixedReserves {
+            let reserves = self.swaps.iter().zip(pools).map(|(params, (usdc, x, fee))| {
+                (params.pool_pubkey, LiveReserves { vaults: [(self.usdc, usdc), (self.x, x)], fee })
+            });
+            FixedReserves(reserves.collect())
+        }
+    }
+
+    #[test]
+    fn test_expected_amount_out() {
+        assert_eq!(expected_amount_out(1_000, 1_000_000, 2_000_000, PoolFee::new(0, 10_000)), 1_998);
+        assert_eq!(expected_amount_out(1_000, 0, 2_000_000, FEE_30_BPS), 0);
+
+        // Fees in hundredths of a basis point are applied exactly
+        let fee = PoolFee::from_hundredths_bps(100);
+        assert_eq!(expected_amount_out(1_000_000, 1_000_000_000, 1_000_000_000, fee), 998_901);
+    }
+
+    #[test]
+    fn test_stale_vs_fresh_reserves_flip_decision() {
+        let route = RoundTrip::new();
+        let no_values = HashMap::new();
+
+        // Reserves the router saw: X is cheap in the first pool and dear in the second
+        let stale = route.reserves([(1_000_000_000, 1_200_000_000, FEE_30_BPS), (1_000_000_000, 1_100_000_000, FEE_30_BPS)]);
+        assert!(recheck_profitability(&route.swaps, &stale, &no_values, 150.0, 0.01));
+
+        // Someone else took the opportunity: the first pool is back at parity
+        let fresh = route.reserves([(1_000_000_000, 1_000_000_000, FEE_30_BPS), (1_000_000_000, 1_100_000_000, FEE_30_BPS)]);
+        assert!(!recheck_profitability(&route.swaps, &fresh, &no_values, 150.0, 0.01));
+    }
+
+    #[test]
+    fn test_reversed_swap_reads_reserves_in_its_own_direction() {
+        let route = RoundTrip::new();
+        let reserves = route.reserves([(1_000_000_000, 1_200_000_000, FEE_30_BPS), (1_000_000_000, 1_100_000_000, FEE_30_BPS)]);
+
+        // The second swap spends X, so its reserve in is the pool's second vault
+        let second = reserves.get_reserves(&route.swaps[1]).unwrap();
+        assert_eq!(second.oriented(&route.x), Some((1_100_000_000, 1_000_000_000)));
+        assert_eq!(second.oriented(&Pubkey::new_unique()), None);
+
+        // 1 USDC -> 1.195208 X -> 1.082120 USDC, ~0.082 profit. Read the other way round,
+        // the second pool would have paid ~0.309
+        let no_values = HashMap::new();
+        assert!(recheck_profitability(&route.swaps, &reserves, &no_values, 150.0, 0.08));
+        assert!(!recheck_profitability(&route.swaps, &reserves, &no_values, 150.0, 0.09));
+    }
+
+    #[test]
+    fn test_each_swap_spends_the_previous_output() {
+        let route = RoundTrip::new();
+        let reserves = route.reserves([(1_000_000_000, 1_200_000_000, FEE_30_BPS), (1_000_000_000, 1_100_000_000, FEE_30_BPS)]);
+        let no_values = HashMap::new();
+
+        // The second swap's own amount_in isn't what it receives: 1.195208 X comes in
+        let mut swaps = route.swaps.clone();
+        swaps[1].min_amount_out = 1_082_120;
+        assert!(recheck_profitability(&swaps, &reserves, &no_values, 150.0, 0.0));
+        swaps[1].min_amount_out = 1_082_121;
+        assert!(!recheck_profitability(&swaps, &reserves, &no_values, 150.0, 0.0));
+
+        // Swaps that don't chain into each other can't be priced
+        swaps[1] = swap_params(Pubkey::new_unique(), route.usdc, 1_000_000, 0);
+        assert!(!recheck_profitability(&swaps, &reserves, &no_values, 150.0, 0.0));
+    }
+
+    #[test]
+    fn test_pool_fee_is_charged() {
+        let route = RoundTrip::new();
+        let no_values = HashMap::new();
+
+        // A 5% fee on the first pool leaves ~0.031 of the ~0.082 profit
+        let reserves = route.reserves([
+            (1_000_000_000, 1_200_000_000, PoolFee::new(500, 10_000)),
+            (1_000_000_000, 1_100_000_000, FEE_30_BPS),
+        ]);
+        assert!(!recheck_profitability(&route.swaps, &reserves, &no_values, 150.0, 0.05));
+        assert!(recheck_profitability(&route.swaps, &reserves, &no_values, 150.0, 0.03));
+    }
+
+    #[test]
+    fn test_min_profit_is_valued_in_the_input_mint() {
+        let route = RoundTrip::new();
+        let reserves = route.reserves([(1_000_000_000, 1_200_000_000, FEE_30_BPS), (1_000_000_000, 1_100_000_000, FEE_30_BPS)]);
+
+        // ~0.082 units of the input mint, worth ~0.164 at 2.0 each
+        let mint_values = HashMap::from([(route.usdc, 2.0)]);
+        assert!(recheck_profitability(&route.swaps, &reserves, &mint_values, 150.0, 0.16));
+        assert!(!recheck_profitability(&route.swaps, &reserves, &mint_values, 150.0, 0.17));
+    }
+
+    #[test]
+    fn test_unfetchable_reserves_skip_the_opportunity() {
+        let route = RoundTrip::new();
+        let mut reserves = route.reserves([(1_000_000_000, 1_200_000_000, FEE_30_BPS), (1_000_000_000, 1_100_000_000, FEE_30_BPS)]);
+        reserves.0.remove(&route.swaps[1].pool_pubkey);
+
+        assert!(!recheck_profitability(&route.swaps, &reserves, &HashMap::new(), 150.0, 0.0));
+    }
+}
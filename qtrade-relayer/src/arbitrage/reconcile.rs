@@ -0,0 +1,311 @@
+//! Module for reconciling the profit of a confirmed arbitrage with what actually landed
+//!
+//! Profit is estimated from the router's amounts before submission. Once a transaction
+//! confirms, its meta holds the balances of every account it touched before and after it
+//! ran. The signer's SOL balance change, which already has fees and tips taken off, and
+//! the changes of the token accounts it owns give the profit realized. Both are priced
+//! like the estimate: tokens at the router's market values and SOL at the oracle price.
+
+use anyhow::{anyhow, Context, Result};
+use qtrade_shared_types::ArbitrageResult;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{UiTransactionEncoding, UiTransactionStatusMeta, UiTransactionTokenBalance};
+use std::collections::HashMap;
+use std::str::FromStr;
+use tracing::{info, warn};
+
+use crate::arbitrage::prepare::ArbitrageSwapParams;
+use crate::arbitrage::profit::global_token_index;
+
+/// Lamports per SOL
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// How a confirmed transaction changed the signer's balances
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BalanceChanges {
+    /// Change of the signer's SOL balance, in lamports
+    pub lamports: i128,
+    /// Change of the signer's balance of each mint, in the mint's UI units
+    pub tokens: HashMap<Pubkey, f64>,
+}
+
+impl BalanceChanges {
+    /// Balance changes of `signer` recorded in `meta`
+    ///
+    /// `account_keys` are the transaction's account keys, in the order the meta's SOL
+    /// balances are listed. Token accounts count when `signer` owns them.
+    pub fn from_meta(meta: &UiTransactionStatusMeta, account_keys: &[Pubkey], signer: &Pubkey) -> Result<Self> {
+        let signer_index = account_keys
+            .iter()
+            .position(|key| key == signer)
+            .ok_or_else(|| anyhow!("Signer {} isn't an account of the transaction", signer))?;
+        let pre = meta.pre_balances.get(signer_index).copied();
+        let post = meta.post_balances.get(signer_index).copied();
+        let (Some(pre), Some(post)) = (pre, post) else {
+            return Err(anyhow!("Transaction meta has no SOL balance for signer {}", signer));
+        };
+
+        let signer = signer.to_string();
+        let owned = |balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>| -> Result<Vec<TokenBalance>> {
+            match balances {
+                OptionSerializer::Some(balances) => balances
+                    .iter()
+                    .filter(|balance| matches!(&balance.owner, OptionSerializer::Some(owner) if *owner == signer))
+                    .map(TokenBalance::parse)
+                    .collect(),
+                _ => Ok(Vec::new()),
+            }
+        };
+
+        // Accounts opened by the transaction have no pre balance and closed ones no post balance
+        let mut raw_changes: HashMap<u8, (TokenBalance, i128)> = HashMap::new();
+        for balance in owned(&meta.pre_token_balances)? {
+            let amount = balance.amount;
+            raw_changes.insert(balance.account_index, (balance, -amount));
+        }
+        for balance in owned(&meta.post_token_balances)? {
+            let change = raw_changes.remove(&balance.account_index).map_or(0, |(_, change)| change);
+            let amount = balance.amount;
+            raw_changes.insert(balance.account_index, (balance, change + amount));
+        }
+
+        let mut tokens = HashMap::new();
+        for (balance, change) in raw_changes.into_values() {
+            let ui_change = change as f64 / 10f64.powi(balance.decimals as i32);
+            *tokens.entry(balance.mint).or_insert(0.0) += ui_change;
+        }
+
+        Ok(Self { lamports: post as i128 - pre as i128, tokens })
+    }
+
+    /// Value of the changes, priced with `mint_values` and `sol_price`
+    ///
+    /// Wrapped SOL without a market value is priced at `sol_price`. Other mints without
+    /// one are priced at 1.0 per unit, as the estimate does.
+    pub fn value(&self, mint_values: &HashMap<Pubkey, f64>, sol_price: f64) -> f64 {
+        let sol = self.lamports as f64 / LAMPORTS_PER_SOL * sol_price;
+        let tokens: f64 = self
+            .tokens
+            .iter()
+            .map(|(mint, change)| {
+                let value = match mint_values.get(mint) {
+                    Some(value) => *value,
+                    None if *mint == spl_token::native_mint::id() => sol_price,
+                    None => 1.0,
+                };
+                change * value
+            })
+            .sum();
+
+        sol + tokens
+    }
+}
+
+// A token balance from the transaction meta, in the mint's smallest unit
+struct TokenBalance {
+    account_index: u8,
+    mint: Pubkey,
+    amount: i128,
+    decimals: u8,
+}
+
+impl TokenBalance {
+    fn parse(balance: &UiTransactionTokenBalance) -> Result<Self> {
+        Ok(Self {
+            account_index: balance.account_index,
+            mint: Pubkey::from_str(&balance.mint).with_context(|| format!("Invalid mint in token balance: {}", balance.mint))?,
+            amount: balance
+                .ui_token_amount
+                .amount
+                .parse()
+                .with_context(|| format!("Invalid token amount: {}", balance.ui_token_amount.amount))?,
+            decimals: balance.ui_token_amount.decimals,
+        })
+    }
+}
+
+/// Market value per UI unit of each mint the swaps trade, from the router's market values
+pub fn mint_values(result: &ArbitrageResult, swap_params_list: &[ArbitrageSwapParams]) -> HashMap<Pubkey, f64> {
+    let mut values = HashMap::new();
+    for swap_params in swap_params_list {
+        let Some(deltas) = result.deltas.get(swap_params.pool_index) else {
+            continue;
+        };
+        let Some((token_a_index, token_b_index)) = crate::determine_token_indices(deltas).two_sided() else {
+            continue;
+        };
+
+        for (mint, local_index) in [(swap_params.token_a_mint, token_a_index), (swap_params.token_b_mint, token_b_index)] {
+            let token = global_token_index(result, swap_params.pool_index, local_index);
+            if let Some(value) = result.market_values.get(token) {
+                values.insert(mint, *value);
+            }
+        }
+    }
+    values
+}
+
+/// Read how the confirmed transaction `signature` changed `signer`'s balances
+///
+/// Transactions can't be fetched at processed commitment, so that reads at confirmed.
+pub fn fetch_balance_changes(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+    signer: &Pubkey,
+    commitment: CommitmentConfig,
+) -> Result<BalanceChanges> {
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(if commitment.is_at_least_confirmed() { commitment } else { CommitmentConfig::confirmed() }),
+        max_supported_transaction_version: Some(0),
+    };
+    let confirmed = rpc_client
+        .get_transaction_with_config(signature, config)
+        .map_err(|e| anyhow!("Failed to fetch transaction {}: {}", signature, e))?;
+
+    let transaction = confirmed
+        .transaction
+        .transaction
+        .decode()
+        .ok_or_else(|| anyhow!("Failed to decode transaction {}", signature))?;
+    let meta = confirmed
+        .transaction
+        .meta
+        .ok_or_else(|| anyhow!("Transaction {} has no status meta", signature))?;
+
+    BalanceChanges::from_meta(&meta, transaction.message.static_account_keys(), signer)
+}
+
+/// Profit realized by the confirmed transactions `signatures`, or `None` if any of their
+/// balances couldn't be read
+///
+/// A split arbitrage lands as several transactions. The tokens they pass on are valued
+/// the same going out of one and into the next, so their balance changes add up to the
+/// profit of the whole arbitrage. The difference from `estimated_profit` is logged and
+/// both are recorded in metrics.
+pub fn reconcile_profit(
+    rpc_client: &RpcClient,
+    signatures: &[Signature],
+    signer: &Pubkey,
+    commitment: CommitmentConfig,
+    mint_values: &HashMap<Pubkey, f64>,
+    sol_price: f64,
+    estimated_profit: f64,
+) -> Option<f64> {
+    let mut realized_profit = 0.0;
+    for signature in signatures {
+        match fetch_balance_changes(rpc_client, signature, signer, commitment) {
+            Ok(changes) => realized_profit += changes.value(mint_values, sol_price),
+            Err(e) => {
+                warn!("Failed to reconcile the profit of {}, keeping the estimate: {:#}", signature, e);
+                return None;
+            }
+        }
+    }
+
+    info!(
+        "Transactions {:?} realized profit {:.6} against an estimate of {:.6} ({:+.6})",
+        signatures,
+        realized_profit,
+        estimated_profit,
+        realized_profit - estimated_profit
+    );
+    crate::metrics::arbitrage::record_arbitrage_realized_profit(estimated_profit, realized_profit);
+    Some(realized_profit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+    const SIGNER: &str = "7xLk17EQQ5KLDLDe44wCmupJKJjTGd8hs3eSVVhCx932";
+
+    fn token_balance(account_index: u8, mint: &str, owner: &str, amount: u64, decimals: u8) -> serde_json::Value {
+        json!({
+            "accountIndex": account_index,
+            "mint": mint,
+            "owner": owner,
+            "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+            "uiTokenAmount": {
+                "amount": amount.to_string(),
+                "decimals": decimals,
+                "uiAmount": amount as f64 / 10f64.powi(decimals as i32),
+                "uiAmountString": (amount as f64 / 10f64.powi(decimals as i32)).to_string()
+            }
+        })
+    }
+
+    #[test]
+    fn test_realized_profit_from_transaction_meta() {
+        let signer = Pubkey::from_str(SIGNER).unwrap();
+        let pool = Pubkey::new_unique().to_string();
+        let account_keys = vec![signer, Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+
+        // The signer buys BONK with 100 USDC and sells most of it back for 101.5 USDC,
+        // keeping 500 BONK in a new account. The pool's USDC vault at index 3 isn't the
+        // signer's, so it doesn't count.
+        let meta: UiTransactionStatusMeta = serde_json::from_value(json!({
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 15000,
+            "preBalances": [1_000_000_000u64, 2_039_280, 2_039_280, 2_039_280],
+            "postBalances": [999_975_000u64, 2_039_280, 2_039_280, 2_039_280],
+            "innerInstructions": [],
+            "logMessages": [],
+            "preTokenBalances": [
+                token_balance(1, USDC, SIGNER, 500_000_000, 6),
+                token_balance(3, USDC, &pool, 9_000_000_000, 6),
+            ],
+            "postTokenBalances": [
+                token_balance(1, USDC, SIGNER, 501_500_000, 6),
+                token_balance(2, BONK, SIGNER, 50_000_000, 5),
+                token_balance(3, USDC, &pool, 8_998_500_000, 6),
+            ],
+            "rewards": [],
+            "loadedAddresses": { "writable": [], "readonly": [] },
+            "computeUnitsConsumed": 180_000
+        }))
+        .unwrap();
+
+        let changes = BalanceChanges::from_meta(&meta, &account_keys, &signer).unwrap();
+        let usdc = Pubkey::from_str(USDC).unwrap();
+        let bonk = Pubkey::from_str(BONK).unwrap();
+        assert_eq!(changes.lamports, -25_000);
+        assert!((changes.tokens[&usdc] - 1.5).abs() < 1e-9);
+        assert!((changes.tokens[&bonk] - 500.0).abs() < 1e-9);
+
+        // 1.5 USDC + 500 BONK at 0.00002 - 25,000 lamports at 150 = 1.5 + 0.01 - 0.00375
+        let mint_values = HashMap::from([(usdc, 1.0), (bonk, 0.00002)]);
+        let realized = changes.value(&mint_values, 150.0);
+        assert!((realized - 1.50625).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_signer_missing_from_transaction_is_an_error() {
+        let meta: UiTransactionStatusMeta = serde_json::from_value(json!({
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 5000,
+            "preBalances": [1_000_000_000u64],
+            "postBalances": [999_995_000u64],
+            "innerInstructions": [],
+            "logMessages": [],
+            "preTokenBalances": [],
+            "postTokenBalances": [],
+            "rewards": [],
+            "loadedAddresses": { "writable": [], "readonly": [] }
+        }))
+        .unwrap();
+
+        let other = Pubkey::new_unique();
+        assert!(BalanceChanges::from_meta(&meta, &[other], &Pubkey::from_str(SIGNER).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+//! Module for recording arbitrage results and replaying them offline
+//!
+//! With `record_results_path` set, every `ArbitrageResult` the relayer receives from the
+//! router is appended to that file as one JSON line. [`replay_file`] feeds a recording
+//! back through execution in simulation mode, so production behavior can be debugged
+//! without submitting anything.
+
+use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
+use qtrade_shared_types::ArbitrageResult;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::arbitrage::outcome::ExecutionOutcome;
+use crate::settings::RelayerSettings;
+
+/// Appends arbitrage results to a JSON lines file
+#[derive(Debug)]
+pub struct ResultRecorder {
+    file: Mutex<File>,
+    path: PathBuf,
+}
+
+impl ResultRecorder {
+    /// Open the recording at `path` for appending, creating it if needed
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open result recording {}", path.display()))?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+            path,
+        })
+    }
+
+    /// Append `result` as a single JSON line
+    pub fn record(&self, result: &ArbitrageResult) -> Result<()> {
+        let mut line = serde_json::to_string(result).context("Failed to serialize arbitrage result")?;
+        line.push('\n');
+
+        let mut file = self.file.lock().unwrap();
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("Failed to write result recording {}", self.path.display()))?;
+        file.flush()
+            .with_context(|| format!("Failed to flush result recording {}", self.path.display()))?;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+// Process-wide recorder, set up from the relayer settings in run_relayer
+static RESULT_RECORDER: OnceCell<Option<ResultRecorder>> = OnceCell::new();
+
+/// Initialize the process-wide recorder from the relayer settings
+///
+/// Recording is off unless `record_results_path` is configured. Only the first call has
+/// any effect.
+pub fn init_result_recorder(settings: &RelayerSettings) -> Result<()> {
+    RESULT_RECORDER.get_or_try_init(|| match settings.get_record_results_path() {
+        Some(path) => {
+            let recorder = ResultRecorder::open(path)?;
+            info!("Recording arbitrage results to {}", path);
+            Ok::<_, anyhow::Error>(Some(recorder))
+        }
+        None => Ok(None),
+    })?;
+    Ok(())
+}
+
+/// Record `result` if recording is enabled
+///
+/// Failures are logged rather than returned, so recording never blocks execution.
+pub fn record_result(result: &ArbitrageResult) {
+    if let Some(Some(recorder)) = RESULT_RECORDER.get() {
+        if let Err(e) = recorder.record(result) {
+            warn!("Failed to record arbitrage result: {:?}", e);
+        }
+    }
+}
+
+/// Read every arbitrage result from a JSON lines recording, skipping blank lines
+pub fn read_results(path: impl AsRef<Path>) -> Result<Vec<ArbitrageResult>> {
+    let path = path.as_ref();
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read result recording {}", path.display()))?;
+
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Failed to parse line {} of {}", index + 1, path.display()))
+        })
+        .collect()
+}
+
+/// Replay a recording through execution in simulation mode, using settings from the environment
+pub async fn replay_file(path: impl AsRef<Path>) -> Result<Vec<ExecutionOutcome>> {
+    replay_file_with_settings(path, &RelayerSettings::from_env()).await
+}
+
+/// Replay a recording through execution in simulation mode
+///
+/// `settings` are used as given except that simulation is forced on, so nothing is
+/// submitted. Returns one outcome per recorded result, in order.
+pub async fn replay_file_with_settings(
+    path: impl AsRef<Path>,
+    settings: &RelayerSettings,
+) -> Result<Vec<ExecutionOutcome>> {
+    let results = read_results(&path)?;
+    info!("Replaying {} arbitrage results from {}", results.len(), path.as_ref().display());
+
+    let mut settings = settings.clone();
+    settings.simulate = true;
+
+    let mut outcomes = Vec::with_capacity(results.len());
+    for result in &results {
+        outcomes.push(crate::execute_arbitrage_with_settings(result, &settings).await?.outcome);
+    }
+    Ok(outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::outcome::SkipReason;
+
+    fn arbitrage_result(status: &str) -> ArbitrageResult {
+        ArbitrageResult {
+            deltas: vec![vec![1.0, 0.0]],
+            lambdas: vec![vec![0.0, 1.5]],
+            a_matrices: vec![vec![vec![1.0, 0.0], vec![0.0, 1.0]]],
+            status: status.to_string(),
+            execution_order: vec![0],
+            market_values: vec![1.0, 1.0],
+            correlation_id: None,
+            emitted_at: None,
+            enqueued_at: None,
+            pool_fees: vec![],
+            pool_states: vec![],
+            version: ArbitrageResult::VERSION,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.jsonl");
+
+        let recorded = vec![arbitrage_result("infeasible"), arbitrage_result("unbounded")];
+        {
+            let recorder = ResultRecorder::open(&path).unwrap();
+            for result in &recorded {
+                recorder.record(result).unwrap();
+            }
+        }
+
+        // Reopening appends rather than truncating
+        ResultRecorder::open(&path).unwrap().record(&arbitrage_result("infeasible")).unwrap();
+
+        let read_back = read_results(&path).unwrap();
+        assert_eq!(read_back.len(), 3);
+        for (read, expected) in read_back.iter().zip(&recorded) {
+            assert_eq!(serde_json::to_value(read).unwrap(), serde_json::to_value(expected).unwrap());
+        }
+
+        let outcomes = replay_file_with_settings(&path, &RelayerSettings::default()).await.unwrap();
+        assert_eq!(outcomes, vec![ExecutionOutcome::Skipped(SkipReason::InvalidResult); 3]);
+    }
+
+    #[test]
+    fn test_malformed_line_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.jsonl");
+        fs::write(&path, "{\"not\": \"a result\"}\n").unwrap();
+
+        assert!(read_results(&path).is_err());
+    }
+}
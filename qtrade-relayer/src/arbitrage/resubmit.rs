@@ -0,0 +1,183 @@
+//! Module for resubmitting an arbitrage whose blockhash expired
+//!
+//! When building an opportunity takes too long, its blockhash can expire before any
+//! provider accepts the transaction, and every submission fails with
+//! `BlockhashNotFound`. The instructions are still good, so rather than give up the
+//! relayer fetches a fresh blockhash and submits them again, without re-solving, as long
+//! as the opportunity is young enough to still be worth taking. Resubmits are bounded.
+
+use anyhow::Result;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::arbitrage::submit::RpcSubmissionResult;
+use crate::metrics::arbitrage::record_blockhash_resubmit;
+use crate::settings::RelayerSettings;
+
+/// Resubmits allowed after the first submission of an opportunity
+pub const DEFAULT_MAX_BLOCKHASH_RESUBMITS: u32 = 1;
+
+/// How old an opportunity may be and still be resubmitted
+pub const DEFAULT_RESUBMIT_FRESHNESS_WINDOW: Duration = Duration::from_secs(10);
+
+// How providers report an expired blockhash: the transaction error name, and the
+// message of a failed preflight simulation
+const BLOCKHASH_EXPIRED_MARKERS: [&str; 2] = ["BlockhashNotFound", "Blockhash not found"];
+
+/// Whether every provider rejected the submission because its blockhash expired
+pub fn is_blockhash_expired(results: &[RpcSubmissionResult]) -> bool {
+    !results.is_empty()
+        && results.iter().all(|(_, success, message)| {
+            !success && BLOCKHASH_EXPIRED_MARKERS.iter().any(|marker| message.contains(marker))
+        })
+}
+
+/// When an opportunity whose blockhash expired is resubmitted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResubmitPolicy {
+    /// Most resubmits after the first submission (0 never resubmits)
+    pub max_resubmits: u32,
+    /// Opportunities older than this are abandoned instead
+    pub freshness_window: Duration,
+}
+
+impl ResubmitPolicy {
+    pub fn from_settings(settings: &RelayerSettings) -> Self {
+        Self {
+            max_resubmits: settings.get_max_blockhash_resubmits(),
+            freshness_window: settings.get_resubmit_freshness_window(),
+        }
+    }
+}
+
+impl Default for ResubmitPolicy {
+    fn default() -> Self {
+        Self {
+            max_resubmits: DEFAULT_MAX_BLOCKHASH_RESUBMITS,
+            freshness_window: DEFAULT_RESUBMIT_FRESHNESS_WINDOW,
+        }
+    }
+}
+
+/// Submit through `submit`, resubmitting while every provider reports an expired blockhash
+///
+/// `refresh_blockhash` is called before each resubmit so the next round signs with a
+/// fresh blockhash. Gives up after `max_resubmits` resubmits, or once the opportunity,
+/// first seen at `started`, is older than the freshness window, returning the results of
+/// the last round.
+pub async fn submit_with_blockhash_retry<S, Fut, R>(
+    policy: &ResubmitPolicy,
+    started: Instant,
+    mut refresh_blockhash: R,
+    mut submit: S,
+) -> Result<Vec<RpcSubmissionResult>>
+where
+    S: FnMut() -> Fut,
+    Fut: Future<Output = Result<Vec<RpcSubmissionResult>>>,
+    R: FnMut(),
+{
+    let mut resubmits = 0;
+    loop {
+        let results = submit().await?;
+        if !is_blockhash_expired(&results) {
+            return Ok(results);
+        }
+
+        if resubmits >= policy.max_resubmits {
+            warn!("Blockhash expired on every provider after {} resubmits, giving up", resubmits);
+            return Ok(results);
+        }
+        let age = started.elapsed();
+        if age > policy.freshness_window {
+            warn!("Blockhash expired on every provider and the opportunity is {:?} old, giving up", age);
+            return Ok(results);
+        }
+
+        resubmits += 1;
+        info!("Blockhash expired on every provider, resubmitting with a fresh blockhash ({} of {})",
+            resubmits, policy.max_resubmits);
+        record_blockhash_resubmit();
+        refresh_blockhash();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expired() -> Vec<RpcSubmissionResult> {
+        vec![
+            ("Solana RPC".to_string(), false, "Transaction simulation failed: Blockhash not found".to_string()),
+            ("Jito".to_string(), false, "{\"err\":\"BlockhashNotFound\"}".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_only_unanimous_expiry_counts() {
+        assert!(is_blockhash_expired(&expired()));
+
+        let mut mixed = expired();
+        mixed.push(("Helius".to_string(), false, "connection refused".to_string()));
+        assert!(!is_blockhash_expired(&mixed));
+
+        assert!(!is_blockhash_expired(&[]));
+    }
+
+    #[tokio::test]
+    async fn test_expired_round_is_resubmitted_once() {
+        let mut rounds = vec![
+            expired(),
+            vec![("Solana RPC".to_string(), true, "signature".to_string())],
+        ]
+        .into_iter();
+        let mut refreshes = 0;
+        let mut submissions = 0;
+
+        let results = submit_with_blockhash_retry(
+            &ResubmitPolicy::default(),
+            Instant::now(),
+            || refreshes += 1,
+            || {
+                submissions += 1;
+                let round = rounds.next().unwrap();
+                async move { Ok(round) }
+            },
+        ).await.unwrap();
+
+        assert_eq!(submissions, 2);
+        assert_eq!(refreshes, 1);
+        assert!(results[0].1);
+    }
+
+    #[tokio::test]
+    async fn test_resubmits_are_bounded_and_fresh_only() {
+        let mut submissions = 0;
+        let results = submit_with_blockhash_retry(
+            &ResubmitPolicy { max_resubmits: 2, ..ResubmitPolicy::default() },
+            Instant::now(),
+            || {},
+            || {
+                submissions += 1;
+                async { Ok(expired()) }
+            },
+        ).await.unwrap();
+        assert_eq!(submissions, 3);
+        assert!(is_blockhash_expired(&results));
+
+        // An opportunity past the freshness window isn't resubmitted at all
+        let started = Instant::now();
+        std::thread::sleep(Duration::from_millis(5));
+        let mut submissions = 0;
+        submit_with_blockhash_retry(
+            &ResubmitPolicy { freshness_window: Duration::from_millis(1), ..ResubmitPolicy::default() },
+            started,
+            || {},
+            || {
+                submissions += 1;
+                async { Ok(expired()) }
+            },
+        ).await.unwrap();
+        assert_eq!(submissions, 1);
+    }
+}
@@ -0,0 +1,293 @@
+//! Module for splitting an arbitrage across several transactions
+//!
+//! A multi-pool arbitrage can need more instructions than fit in one transaction, either
+//! by serialized size or by compute units. [`split_instructions`] packs the swap
+//! instructions, in order, into as few transactions as fit the budget, and
+//! [`submit_in_sequence`] sends them one after another. Each part must confirm before
+//! the next is sent, since later swaps spend what earlier ones received, and the
+//! opportunity only counts as landed once every part has confirmed.
+
+use anyhow::{anyhow, Result};
+use solana_sdk::compute_budget;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use tracing::{info, warn};
+
+use crate::arbitrage::bundle::{confirm_submission, take_submitted_bundles};
+use crate::arbitrage::confirm::{ConfirmationConfig, ConfirmationOutcome, RpcSignatureStatusSource};
+use crate::arbitrage::outcome::ExecutionOutcome;
+use crate::arbitrage::prepare::MAX_COMPUTE_UNITS;
+use crate::arbitrage::submit::{submission_signature, submit_transaction, SubmissionProviders};
+use crate::rpc::solana::Solana;
+use crate::rpc::RpcActions;
+use crate::settings::RelayerSettings;
+
+/// Compute units budgeted for each swap instruction when packing transactions
+pub const DEFAULT_INSTRUCTION_COMPUTE_UNITS: u64 = 200_000;
+
+/// Size and compute limits every transaction of a split arbitrage must fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionBudget {
+    /// Largest serialized transaction, in bytes
+    pub max_bytes: usize,
+    /// Largest compute unit limit a transaction may request
+    pub max_compute_units: u64,
+    /// Compute units assumed for each instruction other than ComputeBudget ones
+    pub compute_units_per_instruction: u64,
+}
+
+impl Default for TransactionBudget {
+    fn default() -> Self {
+        Self {
+            max_bytes: PACKET_DATA_SIZE,
+            max_compute_units: MAX_COMPUTE_UNITS,
+            compute_units_per_instruction: DEFAULT_INSTRUCTION_COMPUTE_UNITS,
+        }
+    }
+}
+
+impl TransactionBudget {
+    /// Whether a transaction made of `instructions` fits this budget
+    ///
+    /// The size includes room for what submission adds to every transaction: a nonce
+    /// advance signed by a separate nonce authority, and a provider tip paid by `payer`.
+    pub fn fits(&self, instructions: &[Instruction], payer: &Pubkey) -> Result<bool> {
+        let compute_units = instructions
+            .iter()
+            .filter(|instruction| !compute_budget::check_id(&instruction.program_id))
+            .count() as u64
+            * self.compute_units_per_instruction;
+
+        Ok(compute_units <= self.max_compute_units
+            && submitted_transaction_size(instructions, payer)? <= self.max_bytes)
+    }
+}
+
+/// Serialized size of a transaction carrying `instructions` once submission has added
+/// its nonce advance and tip instructions
+pub fn submitted_transaction_size(instructions: &[Instruction], payer: &Pubkey) -> Result<usize> {
+    let mut submitted = Vec::with_capacity(instructions.len() + 2);
+    submitted.push(system_instruction::advance_nonce_account(&Pubkey::new_unique(), &Pubkey::new_unique()));
+    submitted.extend_from_slice(instructions);
+    submitted.push(system_instruction::transfer(payer, &Pubkey::new_unique(), 1));
+
+    let message = Message::new_with_blockhash(&submitted, Some(payer), &Hash::default());
+    let size = bincode::serialized_size(&Transaction::new_unsigned(message))
+        .map_err(|e| anyhow!("Failed to size transaction: {}", e))?;
+    Ok(size as usize)
+}
+
+/// Pack `instructions`, in order, into as few transactions as fit `budget`
+///
+/// ComputeBudget instructions are repeated at the start of every part. Returns a single
+/// part when everything fits in one transaction, and an error if some instruction
+/// doesn't fit even on its own.
+pub fn split_instructions(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    budget: &TransactionBudget,
+) -> Result<Vec<Vec<Instruction>>> {
+    let (budget_instructions, swap_instructions): (Vec<Instruction>, Vec<Instruction>) = instructions
+        .iter()
+        .cloned()
+        .partition(|instruction| compute_budget::check_id(&instruction.program_id));
+
+    let mut parts = Vec::new();
+    let mut current = budget_instructions.clone();
+
+    for instruction in swap_instructions {
+        current.push(instruction);
+        if budget.fits(&current, payer)? {
+            continue;
+        }
+
+        // Close the part without the instruction that overflowed it, and start the next with it
+        let overflow = current.pop().expect("instruction was just pushed");
+        if current.len() == budget_instructions.len() {
+            return Err(anyhow!(
+                "Instruction for program {} doesn't fit in a transaction on its own",
+                overflow.program_id
+            ));
+        }
+        parts.push(std::mem::replace(&mut current, budget_instructions.clone()));
+
+        current.push(overflow);
+        if !budget.fits(&current, payer)? {
+            return Err(anyhow!(
+                "Instruction for program {} doesn't fit in a transaction on its own",
+                current[current.len() - 1].program_id
+            ));
+        }
+    }
+
+    if current.len() > budget_instructions.len() || parts.is_empty() {
+        parts.push(current);
+    }
+
+    Ok(parts)
+}
+
+/// A part of a split arbitrage that confirmed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfirmedPart {
+    /// Signature the part landed under
+    pub signature: Signature,
+    /// Provider that returned the landed signature
+    pub provider: String,
+}
+
+/// How submitting a split arbitrage in sequence ended
+#[derive(Debug)]
+pub struct SplitOutcome {
+    pub outcome: ExecutionOutcome,
+    /// The parts that confirmed, in order
+    pub confirmed_parts: Vec<ConfirmedPart>,
+}
+
+/// Submit the parts of a split arbitrage one after another
+///
+/// Each part is submitted to every active one of `providers` and must confirm before the
+/// next is sent. Stops at the first part every provider rejects (`Failed`) or that doesn't
+/// confirm (`Submitted` with that confirmation outcome). Only when every part confirms
+/// is the outcome `Submitted` with a `Confirmed` confirmation. In simulation mode every
+/// part is simulated and the results combined, and no part counts as confirmed.
+pub async fn submit_in_sequence(
+    parts: &[Vec<Instruction>],
+    explorer_keypair: &Keypair,
+    providers: &SubmissionProviders<'_>,
+    settings: &RelayerSettings,
+    is_simulation: bool,
+) -> Result<SplitOutcome> {
+    let solana_rpc = Solana::new(settings.get_solana_endpoint());
+    let status_source = RpcSignatureStatusSource::new(solana_rpc.rpc_client());
+    let confirmation_config = ConfirmationConfig::from_settings(settings);
+
+    let mut all_results = Vec::new();
+    let mut signatures = Vec::new();
+    let mut confirmation = None;
+    let mut confirmed_parts = Vec::new();
+
+    for (index, part) in parts.iter().enumerate() {
+        info!("Submitting transaction {} of {} ({} instructions)", index + 1, parts.len(), part.len());
+        let results = submit_transaction(part, explorer_keypair, providers, settings, is_simulation).await?;
+        let bundles = take_submitted_bundles(&results);
+        all_results.extend(results.iter().cloned());
+
+        if is_simulation {
+            continue;
+        }
+
+        // Providers by the signature they returned, to attribute the one that confirms
+        let providers_by_signature: std::collections::HashMap<_, _> = results.iter()
+            .filter_map(|result| submission_signature(result).map(|signature| (signature, result.0.clone())))
+            .collect();
+
+        let ExecutionOutcome::Submitted { signatures: part_signatures, .. } = ExecutionOutcome::from_submission(results, false) else {
+            warn!("Transaction {} of {} was rejected by every provider, abandoning the rest", index + 1, parts.len());
+            return Ok(SplitOutcome { outcome: ExecutionOutcome::Failed { results: all_results }, confirmed_parts });
+        };
+        signatures.extend(part_signatures.iter().copied());
+
+        if part_signatures.is_empty() {
+            warn!("No signature to confirm transaction {} of {}, abandoning the rest", index + 1, parts.len());
+            return Ok(SplitOutcome { outcome: ExecutionOutcome::Submitted { signatures, confirmation: None }, confirmed_parts });
+        }
+
+        let signature_confirmation = crate::arbitrage::confirm::confirm_signatures(
+            settings,
+            &status_source,
+            &part_signatures,
+            &confirmation_config,
+        );
+        let part_confirmation = confirm_submission(settings, signature_confirmation, &bundles, &confirmation_config).await;
+        let ConfirmationOutcome::Confirmed(signature) = part_confirmation else {
+            warn!("Transaction {} of {} didn't confirm ({:?}), abandoning the rest", index + 1, parts.len(), part_confirmation);
+            let outcome = ExecutionOutcome::Submitted { signatures, confirmation: Some(part_confirmation) };
+            return Ok(SplitOutcome { outcome, confirmed_parts });
+        };
+        let provider = providers_by_signature.get(&signature).cloned().unwrap_or_else(|| "unknown".to_string());
+        confirmed_parts.push(ConfirmedPart { signature, provider });
+        confirmation = Some(part_confirmation);
+    }
+
+    if is_simulation {
+        return Ok(SplitOutcome { outcome: ExecutionOutcome::from_submission(all_results, true), confirmed_parts });
+    }
+
+    info!("All {} transactions confirmed", parts.len());
+    Ok(SplitOutcome { outcome: ExecutionOutcome::Submitted { signatures, confirmation }, confirmed_parts })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::compute::ComputeBudget;
+    use solana_sdk::instruction::AccountMeta;
+
+    /// A swap-like instruction touching `accounts` fresh accounts
+    fn swap_instruction(authority: &Pubkey, accounts: usize) -> Instruction {
+        let mut metas = vec![AccountMeta::new(*authority, true)];
+        metas.extend((0..accounts).map(|_| AccountMeta::new(Pubkey::new_unique(), false)));
+        Instruction::new_with_bytes(Pubkey::new_unique(), &[0u8; 24], metas)
+    }
+
+    #[test]
+    fn test_small_arbitrage_stays_in_one_transaction() {
+        let authority = Pubkey::new_unique();
+        let instructions = vec![swap_instruction(&authority, 4), swap_instruction(&authority, 4)];
+
+        let parts = split_instructions(&instructions, &authority, &TransactionBudget::default()).unwrap();
+        assert_eq!(parts, vec![instructions]);
+    }
+
+    #[test]
+    fn test_oversized_arbitrage_is_split_by_size() {
+        let authority = Pubkey::new_unique();
+        // Each swap adds 7 account keys (about 260 bytes), so three fit per packet
+        let instructions: Vec<Instruction> = (0..7).map(|_| swap_instruction(&authority, 6)).collect();
+        assert!(!TransactionBudget::default().fits(&instructions, &authority).unwrap());
+
+        let parts = split_instructions(&instructions, &authority, &TransactionBudget::default()).unwrap();
+
+        assert_eq!(parts.iter().map(Vec::len).collect::<Vec<_>>(), vec![3, 3, 1]);
+        // Order is preserved across the parts
+        assert_eq!(parts.concat(), instructions);
+        for part in &parts {
+            assert!(submitted_transaction_size(part, &authority).unwrap() <= PACKET_DATA_SIZE);
+        }
+    }
+
+    #[test]
+    fn test_split_by_compute_units_repeats_compute_budget() {
+        let authority = Pubkey::new_unique();
+        let budget_instructions = ComputeBudget { unit_limit: 400_000, unit_price_micro_lamports: 1 }.instructions();
+        let swaps: Vec<Instruction> = (0..5).map(|_| swap_instruction(&authority, 1)).collect();
+        let instructions: Vec<Instruction> = budget_instructions.iter().chain(&swaps).cloned().collect();
+
+        let budget = TransactionBudget {
+            compute_units_per_instruction: 600_000,
+            ..TransactionBudget::default()
+        };
+        let parts = split_instructions(&instructions, &authority, &budget).unwrap();
+
+        assert_eq!(parts.len(), 3);
+        for part in &parts {
+            assert_eq!(&part[..2], &budget_instructions[..]);
+        }
+        assert_eq!(parts.iter().map(|part| part.len() - 2).sum::<usize>(), swaps.len());
+    }
+
+    #[test]
+    fn test_instruction_too_large_on_its_own_is_an_error() {
+        let authority = Pubkey::new_unique();
+        let instructions = vec![swap_instruction(&authority, 40)];
+
+        assert!(split_instructions(&instructions, &authority, &TransactionBudget::default()).is_err());
+    }
+}
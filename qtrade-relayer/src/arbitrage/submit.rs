@@ -1,630 +1,743 @@
 //! Module for submitting arbitrage transactions via multiple RPC providers
 
 use anyhow::{Result, anyhow};
-use solana_sdk::{instruction::Instruction, signature::{Keypair, Signer}, transaction::Transaction};
-use serde_json::json;
+use async_trait::async_trait;
+use solana_sdk::{hash::Hash, instruction::Instruction, pubkey::Pubkey, signature::{Keypair, Signature}};
+use serde_json::{json, Value};
+use std::str::FromStr;
 use tracing::{info, warn};
-use bincode;
-
-use crate::rpc::{RpcActions, NonceInfo};
-use crate::rpc::solana::{Solana, SolanaEndpoint};
+use opentelemetry::{global, KeyValue};
+use opentelemetry::global::BoxedSpan;
+use opentelemetry::trace::{Span, Status, Tracer};
+use once_cell::sync::OnceCell;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::rpc::{is_timeout, RpcActions, RpcProvider, SimulationDetails};
+use crate::rpc::solana::Solana;
 use crate::rpc::helius::Helius;
 use crate::rpc::temporal::Temporal;
-use crate::rpc::jito::JitoJsonRpcSDK;
+use crate::rpc::jito::{BlockEngineScoreboard, JitoJsonRpcSDK, TipAccountRotation};
 use crate::rpc::nextblock::Nextblock;
 use crate::rpc::bloxroute::Bloxroute;
 use crate::rpc::quicknode::Quicknode;
-use crate::metrics::arbitrage::record_failed_arbitrage_transaction;
+use crate::metrics::arbitrage::{
+    record_arbitrage_simulation_rejected,
+    record_failed_arbitrage_transaction,
+    record_rpc_request_timeout,
+    record_simulation_units_consumed,
+};
 use crate::nonce::NoncePool;
-use crate::settings::RelayerSettings;
+use crate::arbitrage::bundle::jito_bundle_submission_result;
+use crate::arbitrage::tx_builder::{latest_blockhash, BuiltTx, NonceLease, TxBuilder};
+use crate::settings::{JitoEndpointStrategy, RelayerSettings};
+use crate::constants::QTRADE_RELAYER_TRACER_NAME;
+
+// For help in naming per-provider submission spans
+const SUBMIT: &str = "relayer::submit";
 
 /// Result of transaction submission to an RPC provider
+///
+/// Successful entries carry a canonical base58 signature, or a message starting with
+/// [`NON_SIGNATURE_RESULT_PREFIX`] when the provider didn't return one.
 pub type RpcSubmissionResult = (String, bool, String);
 
-/// Submits transactions via multiple RPC providers
-///
-/// Attempts to send the transaction through various RPC providers for redundancy
-/// Uses nonce accounts when available, falling back to recent blockhashes
+/// Marks a successful submission whose result is not a transaction signature, so that
+/// confirmation monitoring and tax records skip it
+pub const NON_SIGNATURE_RESULT_PREFIX: &str = "non-signature: ";
+
+/// Marks a failed submission whose request timed out. The provider may be briefly slow
+/// rather than rejecting the transaction, so the failure is transient.
+pub const TIMED_OUT_RESULT_PREFIX: &str = "timed out: ";
+
+/// Whether a submission failed only because the provider's request timed out
+pub fn is_transient_failure(result: &RpcSubmissionResult) -> bool {
+    let (_, success, message) = result;
+    !success && message.starts_with(TIMED_OUT_RESULT_PREFIX)
+}
+
+/// Failure message for a provider's error, flagging timeouts with [`TIMED_OUT_RESULT_PREFIX`]
+fn failure_message(name: &str, error: &(dyn std::error::Error + 'static)) -> String {
+    if is_timeout(error) {
+        record_rpc_request_timeout(name);
+        format!("{}{}", TIMED_OUT_RESULT_PREFIX, error)
+    } else {
+        error.to_string()
+    }
+}
+
+/// Extract the transaction signature from a Jito `sendTransaction` response
+pub fn signature_from_jito_response(response: &Value) -> Option<String> {
+    response
+        .get("result")
+        .and_then(Value::as_str)
+        .and_then(|result| Signature::from_str(result.trim()).ok())
+        .map(|signature| signature.to_string())
+}
+
+/// Canonicalize a submission result so successful entries always carry a base58 signature
 ///
-/// Returns a vector of (provider name, success flag, signature/error message) tuples
-pub async fn submit_transaction(
-    instructions: &[Instruction],
-    explorer_keypair: &Keypair,
-    settings: &RelayerSettings,
-    is_simulation: bool,
-) -> Result<Vec<RpcSubmissionResult>> {
-    let mut rpc_results: Vec<RpcSubmissionResult> = Vec::new();
+/// Successful results that don't parse as a signature are flagged with
+/// [`NON_SIGNATURE_RESULT_PREFIX`]. Failed results are returned unchanged.
+pub fn normalize_submission_result(result: RpcSubmissionResult) -> RpcSubmissionResult {
+    let (provider, success, message) = result;
+    if !success {
+        return (provider, success, message);
+    }
 
-    if is_simulation {
-        info!("SIMULATION MODE: Simulating transaction instead of submitting");
+    match Signature::from_str(message.trim()) {
+        Ok(signature) => (provider, true, signature.to_string()),
+        Err(_) if message.starts_with(NON_SIGNATURE_RESULT_PREFIX) => (provider, true, message),
+        Err(_) => {
+            warn!("{} returned a non-signature result: {}", provider, message);
+            (provider, true, format!("{}{}", NON_SIGNATURE_RESULT_PREFIX, message))
+        }
+    }
+}
 
-        // Create RPC providers with our settings
-        let (_, helius, nextblock, _, _) = create_rpc_with_settings(settings);
+/// Signature of a successful submission, or `None` for failed or flagged results
+pub fn submission_signature(result: &RpcSubmissionResult) -> Option<Signature> {
+    let (_, success, message) = result;
+    if !success || message.starts_with(NON_SIGNATURE_RESULT_PREFIX) {
+        return None;
+    }
 
-        // Solana RPC (preferred simulation provider)
-        if is_rpc_active(settings, "solana") {
-            let solana_rpc = Solana::new(SolanaEndpoint::Mainnet);
-            let solana_instructions = instructions.to_vec();
+    Signature::from_str(message).ok()
+}
 
-            match solana_rpc.simulate_tx(&mut solana_instructions.clone(), explorer_keypair) {
-                Ok(simulation_result) => {
-                    info!("Transaction simulation result from Solana RPC:");
-                    info!("{}", simulation_result);
-                    rpc_results.push(("Solana RPC (simulation)".to_string(), true, simulation_result));
-                },
-                Err(e) => {
-                    warn!("Failed to simulate transaction with Solana RPC: {}", e);
-                    rpc_results.push(("Solana RPC (simulation)".to_string(), false, e.to_string()));
-                }
+/// Start the span timing one provider's submission, as a child of the current span
+fn start_provider_span(provider: RpcProvider) -> BoxedSpan {
+    let tracer = global::tracer(QTRADE_RELAYER_TRACER_NAME);
+    tracer.start(format!("{}::{}", SUBMIT, provider.as_str()))
+}
+
+/// Record the outcome of one provider's submission on its span and end it
+///
+/// `results` are the entries the provider pushed. The span succeeds if any of them did
+/// and carries the signature of the last successful one.
+fn end_provider_span(mut span: BoxedSpan, provider: RpcProvider, used_nonce: bool, results: &[RpcSubmissionResult]) {
+    let succeeded = results.iter().rev().find(|(_, success, _)| *success);
+
+    span.set_attribute(KeyValue::new("provider", provider.as_str()));
+    span.set_attribute(KeyValue::new("used_nonce", used_nonce));
+    span.set_attribute(KeyValue::new("success", succeeded.is_some()));
+
+    match succeeded {
+        Some(result) => {
+            if let Some(signature) = submission_signature(result) {
+                span.set_attribute(KeyValue::new("signature", signature.to_string()));
             }
+        },
+        None => {
+            let error = results
+                .last()
+                .map(|(_, _, message)| message.clone())
+                .unwrap_or_else(|| "no submission attempted".to_string());
+            span.set_status(Status::error(error));
+        }
+    }
+
+    span.end();
+}
+
+/// Tip account rotation shared by every Jito submission, built from the first settings seen
+static JITO_TIP_ROTATION: OnceCell<Arc<TipAccountRotation>> = OnceCell::new();
+
+fn jito_tip_rotation(settings: &RelayerSettings) -> Arc<TipAccountRotation> {
+    Arc::clone(JITO_TIP_ROTATION.get_or_init(|| {
+        Arc::new(TipAccountRotation::new(settings.get_jito_tip_accounts().to_vec()))
+    }))
+}
+
+/// Block engine latencies shared by every Jito submission, built from the first settings seen
+static JITO_BLOCK_ENGINES: OnceCell<Arc<BlockEngineScoreboard>> = OnceCell::new();
+
+pub fn jito_block_engines(settings: &RelayerSettings) -> Arc<BlockEngineScoreboard> {
+    Arc::clone(JITO_BLOCK_ENGINES.get_or_init(|| {
+        Arc::new(BlockEngineScoreboard::new(settings.get_jito_block_engine_urls().to_vec()))
+    }))
+}
+
+/// Jito SDK for one block engine endpoint
+fn jito_sdk(settings: &RelayerSettings, endpoint: &str) -> JitoJsonRpcSDK {
+    JitoJsonRpcSDK::with_tip_rotation(endpoint, None, jito_tip_rotation(settings))
+        .with_request_timeout(settings.get_request_timeout(RpcProvider::Jito))
+}
+
+/// Sends a signed, base64 encoded transaction to the block engines picked by the
+/// endpoint strategy, as a bundle if `jito_bundles` is set
+///
+/// The engines are sent to concurrently, with one result each, and every request's
+/// latency is recorded on `block_engines`.
+pub async fn send_to_jito_block_engines(
+    settings: &RelayerSettings,
+    block_engines: &BlockEngineScoreboard,
+    serialized_tx: &str,
+    signature: Signature,
+) -> Vec<RpcSubmissionResult> {
+    let endpoints = match settings.get_jito_endpoint_strategy() {
+        JitoEndpointStrategy::Fastest => vec![block_engines.fastest().to_string()],
+        JitoEndpointStrategy::All => block_engines.endpoints().to_vec(),
+    };
+
+    let sends = endpoints.iter().map(|endpoint| async move {
+        let jito_sdk = jito_sdk(settings, endpoint);
+        let started = Instant::now();
+        let result = if settings.uses_jito_bundles() {
+            let params = json!([[serialized_tx], { "encoding": "base64" }]);
+            jito_bundle_submission_result(jito_sdk.send_bundle(Some(params), None).await, signature)
         } else {
-            info!("Skipping Solana RPC simulation (not in active RPCs list)");
+            let params = json!({
+                "tx": serialized_tx,
+                "skipPreflight": true
+            });
+            jito_submission_result(jito_sdk.send_txn(Some(params), false).await)
+        };
+        info!("Jito block engine {} answered in {:?}", endpoint, started.elapsed());
+        block_engines.record(endpoint, started.elapsed(), result.1);
+        result
+    });
+
+    futures::future::join_all(sends).await
+}
+
+/// Tip transfer from the fee payer, for providers that take a tip
+fn tip_instructions(explorer_keypair: &Keypair, tip: Option<(Pubkey, u64)>) -> Vec<Instruction> {
+    match tip {
+        Some((tip_wallet, tip_amount)) => vec![solana_sdk::system_instruction::transfer(
+            &crate::fee_payer::payer_pubkey(explorer_keypair),
+            &tip_wallet,
+            tip_amount,
+        )],
+        None => Vec::new(),
+    }
+}
+
+/// One provider's submission result, marking nonce submissions in its name
+fn submission_result<E: Into<Box<dyn std::error::Error>>>(
+    name: &str,
+    used_nonce: bool,
+    result: std::result::Result<String, E>,
+) -> RpcSubmissionResult {
+    let label = if used_nonce { format!("{} (nonce)", name) } else { name.to_string() };
+    match result {
+        Ok(signature) => {
+            info!("Transaction submitted successfully via {}: {}", label, signature);
+            (label, true, signature)
+        },
+        Err(e) => {
+            let e = e.into();
+            warn!("Failed to submit transaction via {}: {}", label, e);
+            let message = failure_message(name, e.as_ref());
+            (label, false, message)
         }
+    }
+}
 
-        // Helius RPC simulation
-        if is_rpc_active(settings, "helius") {
-            let helius_instructions = instructions.to_vec();
-            match helius.simulate_tx(&mut helius_instructions.clone(), explorer_keypair) {
-                Ok(simulation_result) => {
-                    info!("Transaction simulation result from Helius:");
-                    info!("{}", simulation_result);
-                    rpc_results.push(("Helius (simulation)".to_string(), true, simulation_result));
-                },
-                Err(e) => {
-                    warn!("Failed to simulate transaction with Helius: {}", e);
-                    rpc_results.push(("Helius (simulation)".to_string(), false, e.to_string()));
-                }
+/// Acquire a durable nonce for `provider`, unless it is configured for blockhash only
+///
+/// `acquire` is only called when the provider may use a nonce, so blockhash-only providers
+/// never contend for nonce accounts.
+pub fn acquire_provider_nonce<F>(settings: &RelayerSettings, provider: RpcProvider, acquire: F) -> Result<(Pubkey, Hash)>
+where
+    F: FnOnce() -> Result<(Pubkey, Hash)>,
+{
+    if !settings.uses_durable_nonce(provider) {
+        return Err(anyhow!("durable nonce disabled for {}", provider.as_str()));
+    }
+    acquire()
+}
+
+/// Logs a structured simulation result, records its compute usage and returns a one-line summary
+fn log_simulation_details(provider: &str, details: &SimulationDetails) -> String {
+    info!("Transaction simulation result from {}:", provider);
+    for log in &details.logs {
+        info!("  {}", log);
+    }
+
+    if let Some(units) = details.units_consumed {
+        info!("{} simulation consumed {} compute units", provider, units);
+        record_simulation_units_consumed(provider, units);
+    }
+
+    if let Some(return_data) = &details.return_data {
+        info!("{} simulation return data from {}: {:?}", provider, return_data.program_id, return_data.data);
+    }
+
+    match &details.err {
+        Some(err) => {
+            warn!("{} simulation failed with program error: {:?}", provider, err);
+            format!("simulation error: {:?} (units consumed: {:?})", err, details.units_consumed)
+        },
+        None => format!("simulation succeeded (units consumed: {:?})", details.units_consumed),
+    }
+}
+
+/// Runs a pre-flight simulation, returning its details if the transaction executed without error
+///
+/// The details carry the compute units consumed, used to size the compute budget.
+/// Returns `None` when the simulation failed or could not be run.
+pub fn simulate_preflight<R: RpcActions>(
+    rpc: &R,
+    instructions: &[Instruction],
+    signer: &Keypair,
+) -> Option<SimulationDetails> {
+    match rpc.simulate_tx_detailed(&mut instructions.to_vec(), signer) {
+        Ok(details) => {
+            let summary = log_simulation_details("Solana RPC", &details);
+            if details.is_success() {
+                info!("Pre-flight simulation passed: {}", summary);
+                Some(details)
+            } else {
+                warn!("Pre-flight simulation failed: {}", summary);
+                None
             }
-        } else {
-            info!("Skipping Helius simulation (not in active RPCs list)");
+        },
+        Err(e) => {
+            warn!("Pre-flight simulation could not be run: {}", e);
+            None
         }
+    }
+}
 
-        // Nextblock RPC simulation (async)
-        if is_rpc_active(settings, "nextblock") {
-            let nextblock_instructions = instructions.to_vec();
-            match nextblock.simulate_tx(&mut nextblock_instructions.clone(), explorer_keypair).await {
+/// Simulates the transaction and decides whether it is worth submitting
+///
+/// Used by `SubmitMode::SimulateThenSubmit` to avoid spending keys and fees on transactions
+/// that are bound to fail. Returns `true` only when the simulation ran and the transaction
+/// executed without error; otherwise the rejection is recorded and `false` is returned.
+pub fn simulation_allows_submission<R: RpcActions>(
+    rpc: &R,
+    instructions: &[Instruction],
+    signer: &Keypair,
+) -> bool {
+    let allowed = simulate_preflight(rpc, instructions, signer).is_some();
+    if !allowed {
+        record_arbitrage_simulation_rejected();
+    }
+    allowed
+}
+
+/// Name a provider's submission and simulation results are reported under
+pub(crate) fn provider_label(provider: RpcProvider) -> &'static str {
+    match provider {
+        RpcProvider::Bloxroute => "Bloxroute",
+        RpcProvider::Helius => "Helius",
+        RpcProvider::Jito => "Jito",
+        RpcProvider::Nextblock => "Nextblock",
+        RpcProvider::Quicknode => "QuickNode",
+        RpcProvider::Solana => "Solana RPC",
+        RpcProvider::Temporal => "Temporal",
+        RpcProvider::Triton => "Triton",
+    }
+}
+
+/// Simulates the transaction through one provider, reporting it as a submission result
+async fn simulate_with_provider(
+    provider: RpcProvider,
+    instructions: &[Instruction],
+    explorer_keypair: &Keypair,
+    settings: &RelayerSettings,
+) -> RpcSubmissionResult {
+    let label = provider_label(provider);
+    let name = format!("{} (simulation)", label);
+    let mut provider_instructions = instructions.to_vec();
+
+    let details = match provider {
+        RpcProvider::Solana => Solana::with_settings(settings)
+            .simulate_tx_detailed(&mut provider_instructions, explorer_keypair),
+        RpcProvider::Helius => Helius::with_settings(settings)
+            .simulate_tx_detailed(&mut provider_instructions, explorer_keypair),
+        // Nextblock simulates asynchronously and returns its raw response
+        RpcProvider::Nextblock => {
+            return match Nextblock::with_settings(settings).simulate_tx(&mut provider_instructions, explorer_keypair).await {
                 Ok(simulation_result) => {
                     info!("Transaction simulation result from Nextblock:");
                     info!("{}", simulation_result);
-                    rpc_results.push(("Nextblock (simulation)".to_string(), true, simulation_result));
+                    (name, true, simulation_result)
                 },
                 Err(e) => {
                     warn!("Failed to simulate transaction with Nextblock: {}", e);
-                    rpc_results.push(("Nextblock (simulation)".to_string(), false, e.to_string()));
+                    (name, false, e.to_string())
                 }
-            }
-        } else {
-            info!("Skipping Nextblock simulation (not in active RPCs list)");
+            };
+        },
+        other => return (name, false, format!("Simulation not supported by {}", other.as_str())),
+    };
+
+    match details {
+        Ok(details) => {
+            let summary = log_simulation_details(label, &details);
+            (name, details.is_success(), summary)
+        },
+        Err(e) => {
+            warn!("Failed to simulate transaction with {}: {}", label, e);
+            (name, false, e.to_string())
         }
+    }
+}
 
-        // Check if all simulations failed
-        if !rpc_results.iter().any(|(_, success, _)| *success) && !rpc_results.is_empty() {
-            record_failed_arbitrage_transaction();
-            warn!("All transaction simulations failed.");
-        } else if rpc_results.is_empty() {
-            warn!("No simulations were run as no active RPCs were configured for simulation");
-        }
+/// Runs `simulate` for each of `providers`, in order, collecting the results
+pub async fn run_simulations<F, Fut>(providers: &[RpcProvider], mut simulate: F) -> Vec<RpcSubmissionResult>
+where
+    F: FnMut(RpcProvider) -> Fut,
+    Fut: std::future::Future<Output = RpcSubmissionResult>,
+{
+    let mut results = Vec::with_capacity(providers.len());
+    for provider in providers {
+        info!("Simulating transaction with {}", provider_label(*provider));
+        results.push(simulate(*provider).await);
+    }
+    results
+}
 
-        // Log detailed simulation results
-        info!("Transaction simulation complete with results:");
-        for (provider, success, message) in &rpc_results {
-            if *success {
-                info!("{}: Simulation successful", provider);
+/// Turn a Jito `sendTransaction` response into a submission result
+pub fn jito_submission_result(response: std::result::Result<Value, reqwest::Error>) -> RpcSubmissionResult {
+    match response {
+        Ok(response) => {
+            if let Some(error) = response.get("error") {
+                warn!("Jito rejected transaction: {}", error);
+                ("Jito".to_string(), false, error.to_string())
+            } else if let Some(signature) = signature_from_jito_response(&response) {
+                info!("Transaction submitted successfully via Jito: {}", signature);
+                ("Jito".to_string(), true, signature)
             } else {
-                warn!("{}: Simulation failed ({})", provider, message);
+                warn!("Jito accepted transaction without returning a signature: {}", response);
+                (
+                    "Jito".to_string(),
+                    true,
+                    format!("{}{}", NON_SIGNATURE_RESULT_PREFIX, response),
+                )
             }
+        },
+        Err(e) => {
+            warn!("Failed to submit transaction via Jito: {}", e);
+            ("Jito".to_string(), false, failure_message("Jito", &e))
         }
+    }
+}
 
-        return Ok(rpc_results);
+/// A provider transactions are submitted through
+///
+/// [`submit_transaction`] sends through the [`SubmissionProviders`] its caller built, so
+/// any provider's answers can be scripted.
+#[async_trait]
+pub trait Submitter: Send + Sync {
+    /// Provider this submitter sends to
+    fn provider(&self) -> RpcProvider;
+
+    /// Account and lamports the provider asks to be tipped, if any
+    fn tip(&self) -> Option<(Pubkey, u64)>;
+
+    /// Sends a built transaction, with one result per endpoint it was sent to
+    async fn send(&self, built: &BuiltTx) -> Vec<RpcSubmissionResult>;
+
+    /// Sends an already signed, base64 encoded transaction through the provider's raw
+    /// `sendTransaction`
+    async fn send_encoded(&self, encoded_tx: String, used_nonce: bool) -> RpcSubmissionResult;
+}
+
+/// Submits through a synchronous [`RpcActions`] provider
+pub struct RpcSubmitter {
+    provider: RpcProvider,
+    rpc: Box<dyn RpcActions + Send + Sync>,
+}
+
+impl RpcSubmitter {
+    pub fn new<R: RpcActions + Send + Sync + 'static>(provider: RpcProvider, rpc: R) -> Self {
+        Self { provider, rpc: Box::new(rpc) }
     }
+}
 
-    // Regular submission mode
-    info!("Submitting transaction to multiple RPC providers");
+#[async_trait]
+impl Submitter for RpcSubmitter {
+    fn provider(&self) -> RpcProvider {
+        self.provider
+    }
 
-    // Create RPC providers with our settings
-    let (bloxroute, helius, nextblock, quicknode, temporal) = create_rpc_with_settings(settings);
+    fn tip(&self) -> Option<(Pubkey, u64)> {
+        Some((*self.rpc.tip_wallet()?, self.rpc.min_tip_amount()?))
+    }
 
-    // Setup nonce pool and Solana RPC client for nonce operations
-    let solana_rpc = Solana::new(SolanaEndpoint::Mainnet);
-    let solana_rpc_client = solana_rpc.rpc_client();
-    let nonce_pool = NoncePool::instance();
+    async fn send(&self, built: &BuiltTx) -> Vec<RpcSubmissionResult> {
+        let result = self.rpc.send_signed_tx(&built.transaction);
+        vec![submission_result(provider_label(self.provider), built.used_nonce, result)]
+    }
 
-    // -- Solana RPC --
-    if is_rpc_active(settings, "solana") {
-        info!("Attempting submission via Solana RPC");
-        let mut solana_instructions = instructions.to_vec();
-
-        // Try to use nonce if available
-        let mut solana_used_nonce = false;
-        match nonce_pool.acquire_nonce(&solana_rpc_client) {
-            Ok((nonce_pubkey, nonce_hash)) => {
-                match nonce_pool.get_authority() {
-                    Ok(nonce_authority) => {
-                        info!("Using nonce account {} with hash {} for Solana RPC", nonce_pubkey, nonce_hash);
-
-                        let nonce_info = NonceInfo {
-                            nonce_pubkey: &nonce_pubkey,
-                            nonce_authority: &nonce_authority,
-                            nonce_hash,
-                        };
-
-                        // Send with nonce
-                        let mut nonce_instructions = instructions.to_vec();
-                        match solana_rpc.send_nonce_tx(&mut nonce_instructions, explorer_keypair, nonce_info) {
-                            Ok(signature) => {
-                                info!("Transaction submitted successfully via Solana RPC with nonce: {}", signature);
-                                rpc_results.push(("Solana RPC (nonce)".to_string(), true, signature));
-                                solana_used_nonce = true;
-                            },
-                            Err(e) => {
-                                warn!("Failed to submit transaction via Solana RPC with nonce: {}", e);
-                                rpc_results.push(("Solana RPC (nonce)".to_string(), false, e.to_string()));
-                            }
-                        }
-
-                        // Release the nonce account back to the pool
-                        if let Err(e) = nonce_pool.release_nonce(&nonce_pubkey) {
-                            warn!("Failed to release nonce account {}: {}", nonce_pubkey, e);
-                        }
-                    },
-                    Err(e) => {
-                        warn!("Failed to get nonce authority: {}, falling back to blockhash", e);
-                    }
-                }
-            },
-            Err(e) => {
-                warn!("No nonce accounts available for Solana RPC: {}, using blockhash instead", e);
-            }
-        }
+    async fn send_encoded(&self, encoded_tx: String, used_nonce: bool) -> RpcSubmissionResult {
+        let result = self.rpc.send_encoded_tx(&encoded_tx);
+        submission_result(provider_label(self.provider), used_nonce, result)
+    }
+}
 
-        // If nonce wasn't used, fall back to blockhash
-        if !solana_used_nonce {
-            match solana_rpc.send_tx(&mut solana_instructions, explorer_keypair) {
-                Ok(signature) => {
-                    info!("Transaction submitted successfully via Solana RPC: {}", signature);
-                    rpc_results.push(("Solana RPC".to_string(), true, signature));
-                },
-                Err(e) => {
-                    warn!("Failed to submit transaction via Solana RPC: {}", e);
-                    rpc_results.push(("Solana RPC".to_string(), false, e.to_string()));
-                }
-            }
-        }
+#[async_trait]
+impl Submitter for Nextblock {
+    fn provider(&self) -> RpcProvider {
+        RpcProvider::Nextblock
     }
 
-    // -- Helius RPC --
-    if is_rpc_active(settings, "helius") {
-        info!("Attempting submission via Helius");
-        let mut helius_instructions = instructions.to_vec();
-
-        // Try to use nonce if available
-        let mut helius_used_nonce = false;
-        match nonce_pool.acquire_nonce(&solana_rpc_client) {
-            Ok((nonce_pubkey, nonce_hash)) => {
-                match nonce_pool.get_authority() {
-                    Ok(nonce_authority) => {
-                        info!("Using nonce account {} with hash {} for Helius", nonce_pubkey, nonce_hash);
-
-                        let nonce_info = NonceInfo {
-                            nonce_pubkey: &nonce_pubkey,
-                            nonce_authority: &nonce_authority,
-                            nonce_hash,
-                        };
-
-                        // Send with nonce
-                        let mut nonce_instructions = instructions.to_vec();
-                        match helius.send_nonce_tx(&mut nonce_instructions, explorer_keypair, nonce_info) {
-                            Ok(signature) => {
-                                info!("Transaction submitted successfully via Helius with nonce: {}", signature);
-                                rpc_results.push(("Helius (nonce)".to_string(), true, signature));
-                                helius_used_nonce = true;
-                            },
-                            Err(e) => {
-                                warn!("Failed to submit transaction via Helius with nonce: {}", e);
-                                rpc_results.push(("Helius (nonce)".to_string(), false, e.to_string()));
-                            }
-                        }
-
-                        // Release the nonce account back to the pool
-                        if let Err(e) = nonce_pool.release_nonce(&nonce_pubkey) {
-                            warn!("Failed to release nonce account {}: {}", nonce_pubkey, e);
-                        }
-                    },
-                    Err(e) => {
-                        warn!("Failed to get nonce authority for Helius: {}, falling back to blockhash", e);
-                    }
-                }
-            },
-            Err(e) => {
-                warn!("No nonce accounts available for Helius: {}, using blockhash instead", e);
-            }
-        }
+    fn tip(&self) -> Option<(Pubkey, u64)> {
+        Some((*self.tip_wallet()?, self.min_tip_amount()?))
+    }
 
-        // If nonce wasn't used, fall back to blockhash
-        if !helius_used_nonce {
-            match helius.send_tx(&mut helius_instructions, explorer_keypair) {
-                Ok(signature) => {
-                    info!("Transaction submitted successfully via Helius: {}", signature);
-                    rpc_results.push(("Helius".to_string(), true, signature));
-                },
-                Err(e) => {
-                    warn!("Failed to submit transaction via Helius: {}", e);
-                    rpc_results.push(("Helius".to_string(), false, e.to_string()));
-                }
-            }
-        }
+    async fn send(&self, built: &BuiltTx) -> Vec<RpcSubmissionResult> {
+        let result = self.send_signed_tx(&built.transaction).await;
+        vec![submission_result("Nextblock", built.used_nonce, result)]
     }
 
-    // -- QuickNode RPC --
-    if is_rpc_active(settings, "quicknode") {
-        info!("Attempting submission via QuickNode");
-        let mut quicknode_instructions = instructions.to_vec();
-
-        // Try to use nonce if available
-        let mut quicknode_used_nonce = false;
-        match nonce_pool.acquire_nonce(&solana_rpc_client) {
-            Ok((nonce_pubkey, nonce_hash)) => {
-                match nonce_pool.get_authority() {
-                    Ok(nonce_authority) => {
-                        info!("Using nonce account {} with hash {} for QuickNode", nonce_pubkey, nonce_hash);
-
-                        let nonce_info = NonceInfo {
-                            nonce_pubkey: &nonce_pubkey,
-                            nonce_authority: &nonce_authority,
-                            nonce_hash,
-                        };
-
-                        // Send with nonce
-                        let mut nonce_instructions = instructions.to_vec();
-                        match quicknode.send_nonce_tx(&mut nonce_instructions, explorer_keypair, nonce_info) {
-                            Ok(signature) => {
-                                info!("Transaction submitted successfully via QuickNode with nonce: {}", signature);
-                                rpc_results.push(("QuickNode (nonce)".to_string(), true, signature));
-                                quicknode_used_nonce = true;
-                            },
-                            Err(e) => {
-                                warn!("Failed to submit transaction via QuickNode with nonce: {}", e);
-                                rpc_results.push(("QuickNode (nonce)".to_string(), false, e.to_string()));
-                            }
-                        }
-
-                        // Release the nonce account back to the pool
-                        if let Err(e) = nonce_pool.release_nonce(&nonce_pubkey) {
-                            warn!("Failed to release nonce account {}: {}", nonce_pubkey, e);
-                        }
-                    },
-                    Err(e) => {
-                        warn!("Failed to get nonce authority for QuickNode: {}, falling back to blockhash", e);
-                    }
-                }
-            },
-            Err(e) => {
-                warn!("No nonce accounts available for QuickNode: {}, using blockhash instead", e);
-            }
-        }
+    async fn send_encoded(&self, encoded_tx: String, used_nonce: bool) -> RpcSubmissionResult {
+        let result = self.send_encoded_tx(encoded_tx).await;
+        submission_result("Nextblock", used_nonce, result)
+    }
+}
 
-        // If nonce wasn't used, fall back to blockhash
-        if !quicknode_used_nonce {
-            match quicknode.send_tx(&mut quicknode_instructions, explorer_keypair) {
-                Ok(signature) => {
-                    info!("Transaction submitted successfully via QuickNode: {}", signature);
-                    rpc_results.push(("QuickNode".to_string(), true, signature));
-                },
-                Err(e) => {
-                    warn!("Failed to submit transaction via QuickNode: {}", e);
-                    rpc_results.push(("QuickNode".to_string(), false, e.to_string()));
-                }
-            }
-        }
+#[async_trait]
+impl Submitter for Bloxroute {
+    fn provider(&self) -> RpcProvider {
+        RpcProvider::Bloxroute
     }
 
-    // -- Temporal RPC --
-    if is_rpc_active(settings, "temporal") {
-        info!("Attempting submission via Temporal");
-        let mut temporal_instructions = instructions.to_vec();
-
-        // Try to use nonce if available
-        let mut temporal_used_nonce = false;
-        match nonce_pool.acquire_nonce(&solana_rpc_client) {
-            Ok((nonce_pubkey, nonce_hash)) => {
-                match nonce_pool.get_authority() {
-                    Ok(nonce_authority) => {
-                        info!("Using nonce account {} with hash {} for Temporal", nonce_pubkey, nonce_hash);
-
-                        let nonce_info = NonceInfo {
-                            nonce_pubkey: &nonce_pubkey,
-                            nonce_authority: &nonce_authority,
-                            nonce_hash,
-                        };
-
-                        // Send with nonce
-                        let mut nonce_instructions = instructions.to_vec();
-                        match temporal.send_nonce_tx(&mut nonce_instructions, explorer_keypair, nonce_info) {
-                            Ok(signature) => {
-                                info!("Transaction submitted successfully via Temporal with nonce: {}", signature);
-                                rpc_results.push(("Temporal (nonce)".to_string(), true, signature));
-                                temporal_used_nonce = true;
-                            },
-                            Err(e) => {
-                                warn!("Failed to submit transaction via Temporal with nonce: {}", e);
-                                rpc_results.push(("Temporal (nonce)".to_string(), false, e.to_string()));
-                            }
-                        }
-
-                        // Release the nonce account back to the pool
-                        if let Err(e) = nonce_pool.release_nonce(&nonce_pubkey) {
-                            warn!("Failed to release nonce account {}: {}", nonce_pubkey, e);
-                        }
-                    },
-                    Err(e) => {
-                        warn!("Failed to get nonce authority for Temporal: {}, falling back to blockhash", e);
-                    }
-                }
+    fn tip(&self) -> Option<(Pubkey, u64)> {
+        Some((*self.tip_wallet()?, self.min_tip_amount()?))
+    }
+
+    async fn send(&self, built: &BuiltTx) -> Vec<RpcSubmissionResult> {
+        let result = self.send_signed_tx(&built.transaction).await;
+        vec![submission_result("Bloxroute", built.used_nonce, result)]
+    }
+
+    async fn send_encoded(&self, encoded_tx: String, used_nonce: bool) -> RpcSubmissionResult {
+        let result = self.send_encoded_tx(encoded_tx).await;
+        submission_result("Bloxroute", used_nonce, result)
+    }
+}
+
+/// Submits to the Jito block engines picked by the endpoint strategy
+pub struct JitoSubmitter<'a> {
+    settings: &'a RelayerSettings,
+    block_engines: Arc<BlockEngineScoreboard>,
+}
+
+impl<'a> JitoSubmitter<'a> {
+    pub fn with_settings(settings: &'a RelayerSettings) -> Self {
+        Self { settings, block_engines: jito_block_engines(settings) }
+    }
+}
+
+#[async_trait]
+impl Submitter for JitoSubmitter<'_> {
+    fn provider(&self) -> RpcProvider {
+        RpcProvider::Jito
+    }
+
+    // Tip the next account in the rotation so no single tip account becomes a hot spot
+    fn tip(&self) -> Option<(Pubkey, u64)> {
+        let tip_lamports = self.settings.jito_tip_lamports(self.settings.get_jito_min_tip_lamports());
+        match jito_tip_rotation(self.settings).next_account().parse::<Pubkey>() {
+            Ok(tip_account) => {
+                info!("Tipping Jito account {} with {} lamports", tip_account, tip_lamports);
+                Some((tip_account, tip_lamports))
             },
             Err(e) => {
-                warn!("No nonce accounts available for Temporal: {}, using blockhash instead", e);
-            }
-        }
-
-        // If nonce wasn't used, fall back to blockhash
-        if !temporal_used_nonce {
-            match temporal.send_tx(&mut temporal_instructions, explorer_keypair) {
-                Ok(signature) => {
-                    info!("Transaction submitted successfully via Temporal: {}", signature);
-                    rpc_results.push(("Temporal".to_string(), true, signature));
-                },
-                Err(e) => {
-                    warn!("Failed to submit transaction via Temporal: {}", e);
-                    rpc_results.push(("Temporal".to_string(), false, e.to_string()));
-                }
+                warn!("Invalid Jito tip account, submitting without a tip: {}", e);
+                None
             }
         }
     }
 
-    // -- Jito RPC (async) --
-    if is_rpc_active(settings, "jito") {
-        info!("Attempting submission via Jito");
-        let jito_sdk = JitoJsonRpcSDK::new("https://mainnet.block-engine.jito.wtf/api/v1/bundles", None);
-
-        // Try to use nonce for Jito if available
-        let mut tx_created = false;
-        let mut serialized_tx = String::new();
-
-        // Try to use nonce if available
-        match nonce_pool.acquire_nonce(&solana_rpc_client) {
-            Ok((nonce_pubkey, nonce_hash)) => {
-                match nonce_pool.get_authority() {
-                    Ok(nonce_authority) => {
-                        info!("Using nonce account {} with hash {} for Jito", nonce_pubkey, nonce_hash);
-
-                        // Create nonce instruction
-                        let advance_nonce_instruction = solana_sdk::system_instruction::advance_nonce_account(
-                            &nonce_pubkey,
-                            &nonce_authority.pubkey(),
-                        );
-
-                        // Create full instruction set
-                        let mut jito_instructions = vec![advance_nonce_instruction];
-                        jito_instructions.extend_from_slice(instructions);
-
-                        // Create transaction
-                        let tx = Transaction::new_signed_with_payer(
-                            &jito_instructions,
-                            Some(&explorer_keypair.pubkey()),
-                            &[explorer_keypair, &nonce_authority],
-                            nonce_hash,
-                        );
-
-                        serialized_tx = match bincode::serialize(&tx) {
-                            Ok(data) => {
-                                // Use the new way to encode base64
-                                use base64::Engine;
-                                tx_created = true;
-                                base64::engine::general_purpose::STANDARD.encode(data)
-                            },
-                            Err(e) => {
-                                warn!("Failed to serialize nonce transaction for Jito: {}", e);
-                                String::new()
-                            }
-                        };
-
-                        // Release the nonce account back to the pool
-                        if let Err(e) = nonce_pool.release_nonce(&nonce_pubkey) {
-                            warn!("Failed to release nonce account {}: {}", nonce_pubkey, e);
-                        }
-                    },
-                    Err(e) => {
-                        warn!("Failed to get nonce authority for Jito: {}, falling back to blockhash", e);
-                    }
-                }
+    async fn send(&self, built: &BuiltTx) -> Vec<RpcSubmissionResult> {
+        match built.to_base64() {
+            Ok(serialized_tx) => {
+                let signature = built.transaction.signatures[0];
+                send_to_jito_block_engines(self.settings, &self.block_engines, &serialized_tx, signature).await
             },
             Err(e) => {
-                warn!("No nonce accounts available for Jito: {}, using blockhash instead", e);
+                warn!("Failed to build transaction for Jito: {}", e);
+                vec![("Jito".to_string(), false, e.to_string())]
             }
         }
+    }
 
-        // Fall back to blockhash if nonce transaction creation failed
-        if !tx_created {
-            let blockhash = {
-                // Try to get from blockhash cache first
-                if let Ok(cached_blockhash) = crate::blockhash::BlockhashCache::instance().get_blockhash(&solana_rpc_client) {
-                    cached_blockhash
-                } else {
-                    // Otherwise get from RPC
-                    match solana_rpc_client.get_latest_blockhash() {
-                        Ok(bh) => bh,
-                        Err(e) => {
-                            warn!("Failed to get blockhash for Jito submission: {}", e);
-                            return Err(anyhow!("Failed to get blockhash for Jito submission: {}", e));
-                        }
-                    }
-                }
-            };
-
-            let tx = Transaction::new_signed_with_payer(
-                instructions,
-                Some(&explorer_keypair.pubkey()),
-                &[explorer_keypair],
-                blockhash
-            );
-
-            serialized_tx = match bincode::serialize(&tx) {
-                Ok(data) => {
-                    // Use the engine to encode base64
-                    use base64::Engine;
-                    base64::engine::general_purpose::STANDARD.encode(data)
-                },
-                Err(e) => {
-                    warn!("Failed to serialize transaction for Jito: {}", e);
-                    return Err(anyhow!("Failed to serialize transaction for Jito: {}", e));
-                }
-            };
-        }
-
-        // Prepare Jito transaction parameters
+    async fn send_encoded(&self, encoded_tx: String, _used_nonce: bool) -> RpcSubmissionResult {
+        let jito_sdk = jito_sdk(self.settings, self.block_engines.fastest());
         let params = json!({
-            "tx": serialized_tx,
+            "tx": encoded_tx,
             "skipPreflight": true
         });
+        jito_submission_result(jito_sdk.send_txn(Some(params), false).await)
+    }
+}
 
-        match jito_sdk.send_txn(Some(params), false).await {
-            Ok(response) => {
-                info!("Transaction submitted successfully via Jito");
-                rpc_results.push(("Jito".to_string(), true, format!("{:?}", response)));
-            },
-            Err(e) => {
-                warn!("Failed to submit transaction via Jito: {}", e);
-                rpc_results.push(("Jito".to_string(), false, e.to_string()));
-            }
-        }
+/// The providers a transaction is submitted through, and the RPC its nonce or blockhash
+/// is fetched from
+pub struct SubmissionProviders<'a> {
+    chain_rpc: Box<dyn RpcActions + Send + Sync>,
+    submitters: Vec<Box<dyn Submitter + 'a>>,
+}
+
+impl<'a> SubmissionProviders<'a> {
+    /// `submitters`, sent to in order, with nonces and blockhashes fetched from `chain_rpc`
+    ///
+    /// Inactive providers may be included; [`submit_transaction`] skips them.
+    pub fn new<R: RpcActions + Send + Sync + 'static>(chain_rpc: R, submitters: Vec<Box<dyn Submitter + 'a>>) -> Self {
+        Self { chain_rpc: Box::new(chain_rpc), submitters }
     }
 
-    // -- Nextblock RPC (async) --
-    if is_rpc_active(settings, "nextblock") {
-        info!("Attempting submission via Nextblock");
-        let mut nextblock_instructions = instructions.to_vec();
-
-        // Try to use nonce if available
-        let mut nextblock_used_nonce = false;
-        match nonce_pool.acquire_nonce(&solana_rpc_client) {
-            Ok((nonce_pubkey, nonce_hash)) => {
-                match nonce_pool.get_authority() {
-                    Ok(nonce_authority) => {
-                        info!("Using nonce account {} with hash {} for Nextblock", nonce_pubkey, nonce_hash);
-
-                        let nonce_info = NonceInfo {
-                            nonce_pubkey: &nonce_pubkey,
-                            nonce_authority: &nonce_authority,
-                            nonce_hash,
-                        };
-
-                        // Send with nonce
-                        let mut nonce_instructions = instructions.to_vec();
-                        match nextblock.send_nonce_tx(&mut nonce_instructions, explorer_keypair, nonce_info).await {
-                            Ok(signature) => {
-                                info!("Transaction submitted successfully via Nextblock with nonce: {}", signature);
-                                rpc_results.push(("Nextblock (nonce)".to_string(), true, signature));
-                                nextblock_used_nonce = true;
-                            },
-                            Err(e) => {
-                                warn!("Failed to submit transaction via Nextblock with nonce: {}", e);
-                                rpc_results.push(("Nextblock (nonce)".to_string(), false, e.to_string()));
-                            }
-                        }
-
-                        // Release the nonce account back to the pool
-                        if let Err(e) = nonce_pool.release_nonce(&nonce_pubkey) {
-                            warn!("Failed to release nonce account {}: {}", nonce_pubkey, e);
-                        }
-                    },
-                    Err(e) => {
-                        warn!("Failed to get nonce authority for Nextblock: {}, falling back to blockhash", e);
-                    }
-                }
-            },
-            Err(e) => {
-                warn!("No nonce accounts available for Nextblock: {}, using blockhash instead", e);
-            }
+    /// Every supported provider, built from `settings`
+    pub fn with_settings(settings: &'a RelayerSettings) -> Self {
+        Self::new(Solana::with_settings(settings), vec![
+            Box::new(RpcSubmitter::new(RpcProvider::Solana, Solana::with_settings(settings))),
+            Box::new(RpcSubmitter::new(RpcProvider::Helius, Helius::with_settings(settings))),
+            Box::new(RpcSubmitter::new(RpcProvider::Quicknode, Quicknode::with_settings(settings))),
+            Box::new(RpcSubmitter::new(RpcProvider::Temporal, Temporal::with_settings(settings))),
+            Box::new(JitoSubmitter::with_settings(settings)),
+            Box::new(Nextblock::with_settings(settings)),
+            Box::new(Bloxroute::with_settings(settings)),
+        ])
+    }
+
+    /// The submitters whose provider is active in `settings`, in order
+    fn active(&self, settings: &RelayerSettings) -> Vec<&(dyn Submitter + 'a)> {
+        self.submitters
+            .iter()
+            .map(|submitter| submitter.as_ref())
+            .filter(|submitter| settings.is_provider_active(submitter.provider()))
+            .collect()
+    }
+}
+
+/// Sends the same serialized transaction through each of `submitters`, in order
+///
+/// Every submitter is sent a copy of `encoded_tx`, so all of them get byte-identical
+/// transactions and a landed transaction has a single signature.
+async fn broadcast_identical(
+    submitters: &[&(dyn Submitter + '_)],
+    encoded_tx: &str,
+    used_nonce: bool,
+) -> Vec<RpcSubmissionResult> {
+    let mut results = Vec::with_capacity(submitters.len());
+    for submitter in submitters {
+        let provider = submitter.provider();
+        info!("Sending identical transaction via {}", provider_label(provider));
+        let span = start_provider_span(provider);
+        let result = submitter.send_encoded(encoded_tx.to_string(), used_nonce).await;
+        end_provider_span(span, provider, used_nonce, std::slice::from_ref(&result));
+        results.push(result);
+    }
+    results
+}
+
+/// Submits transactions via multiple RPC providers
+///
+/// Attempts to send the transaction through each active one of `providers` for redundancy
+/// Uses nonce accounts when available, falling back to recent blockhashes
+/// With `identical_transaction` set, one signed transaction is sent to every provider
+///
+/// Returns a vector of (provider name, success flag, signature/error message) tuples
+pub async fn submit_transaction(
+    instructions: &[Instruction],
+    explorer_keypair: &Keypair,
+    providers: &SubmissionProviders<'_>,
+    settings: &RelayerSettings,
+    is_simulation: bool,
+) -> Result<Vec<RpcSubmissionResult>> {
+    if is_simulation {
+        info!("SIMULATION MODE: Simulating transaction instead of submitting");
+
+        // Simulate with each configured provider, in order
+        let simulation_rpcs = settings.get_simulation_rpcs();
+        let rpc_results = run_simulations(&simulation_rpcs, |provider| {
+            simulate_with_provider(provider, instructions, explorer_keypair, settings)
+        }).await;
+
+        // Check if all simulations failed
+        if !rpc_results.iter().any(|(_, success, _)| *success) && !rpc_results.is_empty() {
+            record_failed_arbitrage_transaction();
+            warn!("All transaction simulations failed.");
+        } else if rpc_results.is_empty() {
+            warn!("No simulations were run as no RPC providers were configured for simulation");
         }
 
-    // If nonce wasn't used, fall back to blockhash
-    if !nextblock_used_nonce {
-        match nextblock.send_tx(&mut nextblock_instructions, explorer_keypair).await {
-            Ok(signature) => {
-                info!("Transaction submitted successfully via Nextblock: {}", signature);
-                rpc_results.push(("Nextblock".to_string(), true, signature));
-            },
-            Err(e) => {
-                warn!("Failed to submit transaction via Nextblock: {}", e);
-                rpc_results.push(("Nextblock".to_string(), false, e.to_string()));
+        // Log detailed simulation results
+        info!("Transaction simulation complete with results:");
+        for (provider, success, message) in &rpc_results {
+            if *success {
+                info!("{}: Simulation successful", provider);
+            } else {
+                warn!("{}: Simulation failed ({})", provider, message);
             }
         }
+
+        return Ok(rpc_results);
     }
-}
 
-    // -- Bloxroute RPC (async) --
-    if is_rpc_active(settings, "bloxroute") {
-        info!("Attempting submission via Bloxroute");
-        let mut bloxroute_instructions = instructions.to_vec();
-
-        // Try to use nonce if available
-        let mut bloxroute_used_nonce = false;
-        match nonce_pool.acquire_nonce(&solana_rpc_client) {
-            Ok((nonce_pubkey, nonce_hash)) => {
-                match nonce_pool.get_authority() {
-                    Ok(nonce_authority) => {
-                        info!("Using nonce account {} with hash {} for Bloxroute", nonce_pubkey, nonce_hash);
-
-                        let nonce_info = NonceInfo {
-                            nonce_pubkey: &nonce_pubkey,
-                            nonce_authority: &nonce_authority,
-                            nonce_hash,
-                        };
-
-                        // Send with nonce
-                        let mut nonce_instructions = instructions.to_vec();
-                        match bloxroute.send_nonce_tx(&mut nonce_instructions, explorer_keypair, nonce_info).await {
-                            Ok(signature) => {
-                                info!("Transaction submitted successfully via Bloxroute with nonce: {}", signature);
-                                rpc_results.push(("Bloxroute (nonce)".to_string(), true, signature));
-                                bloxroute_used_nonce = true;
-                            },
-                            Err(e) => {
-                                warn!("Failed to submit transaction via Bloxroute with nonce: {}", e);
-                                rpc_results.push(("Bloxroute (nonce)".to_string(), false, e.to_string()));
-                            }
-                        }
-
-                        // Release the nonce account back to the pool
-                        if let Err(e) = nonce_pool.release_nonce(&nonce_pubkey) {
-                            warn!("Failed to release nonce account {}: {}", nonce_pubkey, e);
-                        }
-                    },
-                    Err(e) => {
-                        warn!("Failed to get nonce authority for Bloxroute: {}, falling back to blockhash", e);
-                    }
-                }
+    // Regular submission mode
+    info!("Submitting transaction to multiple RPC providers");
+    let mut rpc_results: Vec<RpcSubmissionResult> = Vec::new();
+
+    // Nonces and blockhashes come from the providers' chain RPC
+    let chain_rpc_client = providers.chain_rpc.rpc_client();
+    let nonce_pool = NoncePool::instance();
+
+    // Choose nonce or blockhash once, so every provider sends the same transaction. The
+    // builder holds the cycle's nonce and returns it to the pool however this function exits
+    let tx_builder = TxBuilder::for_cycle(
+        instructions,
+        explorer_keypair,
+        settings,
+        || NonceLease::acquire(&nonce_pool, chain_rpc_client),
+        || latest_blockhash(chain_rpc_client),
+    )?;
+
+    let active = providers.active(settings);
+
+    if settings.is_identical_transaction() {
+        // One transaction for everyone: only use the nonce if every active provider may
+        let allow_nonce = active.iter().all(|submitter| settings.uses_durable_nonce(submitter.provider()));
+        let built = tx_builder
+            .build(allow_nonce, &[])
+            .and_then(|built| Ok((built.to_base64()?, built.used_nonce)));
+        let rpc_results = match built {
+            Ok((encoded_tx, used_nonce)) => {
+                info!("Sending one identical transaction to every active RPC provider");
+                broadcast_identical(&active, &encoded_tx, used_nonce).await
             },
-            Err(e) => {
-                warn!("No nonce accounts available for Bloxroute: {}, using blockhash instead", e);
-            }
-        }
+            Err(e) => return Err(anyhow!("Failed to build identical transaction: {}", e)),
+        };
+
+        return Ok(finish_submission(rpc_results));
+    }
 
-    // If nonce wasn't used, fall back to blockhash
-    if !bloxroute_used_nonce {
-        match bloxroute.send_tx(&mut bloxroute_instructions, explorer_keypair).await {
-            Ok(signature) => {
-                info!("Transaction submitted successfully via Bloxroute: {}", signature);
-                rpc_results.push(("Bloxroute".to_string(), true, signature));
+    for submitter in active {
+        let provider = submitter.provider();
+        let name = provider_label(provider);
+
+        info!("Attempting submission via {}", name);
+        let span = start_provider_span(provider);
+        let results_start = rpc_results.len();
+
+        let tip = tip_instructions(explorer_keypair, submitter.tip());
+        let used_nonce = match tx_builder.build(settings.uses_durable_nonce(provider), &tip) {
+            Ok(built) => {
+                rpc_results.extend(submitter.send(&built).await);
+                built.used_nonce
             },
             Err(e) => {
-                warn!("Failed to submit transaction via Bloxroute: {}", e);
-                rpc_results.push(("Bloxroute".to_string(), false, e.to_string()));
+                rpc_results.push(submission_result(name, false, Err::<String, _>(e)));
+                false
             }
-        }
+        };
+
+        end_provider_span(span, provider, used_nonce, &rpc_results[results_start..]);
     }
+
+    // Every provider has been sent this cycle's transaction, so the nonce can go back to the pool
+    drop(tx_builder);
+
+    Ok(finish_submission(rpc_results))
 }
 
+/// Check the submission results for systemic errors and canonicalize their signatures
+pub fn finish_submission(rpc_results: Vec<RpcSubmissionResult>) -> Vec<RpcSubmissionResult> {
     // Check circuit breakers - if multiple providers report the same critical error
     let sim_error_types = ["InsufficientFundsForFee", "InvalidAccount", "AccountNotFound"];
     let mut fatal_simulation_errors = 0;
@@ -653,8 +766,8 @@ pub async fn submit_transaction(
 
     info!("Completed transaction submission to all RPC providers");
 
-    // Return the results of all submission attempts
-    Ok(rpc_results)
+    // Return the results of all submission attempts, with canonical signatures
+    rpc_results.into_iter().map(normalize_submission_result).collect()
 }
 
 /// Helper function: Create RPC service instances with the provided settings
@@ -704,5 +817,11 @@ pub fn create_rpc_with_settings(settings: &RelayerSettings) -> (Bloxroute, Heliu
 /// assert!(!is_rpc_active(&settings, "helius"));
 /// ```
 pub fn is_rpc_active(settings: &RelayerSettings, rpc_name: &str) -> bool {
-    settings.active_rpcs.iter().any(|name| name.to_lowercase() == rpc_name.to_lowercase())
+    match RpcProvider::from_str(rpc_name) {
+        Some(provider) => settings.is_provider_active(provider),
+        None => {
+            warn!("Unknown RPC provider: {}", rpc_name);
+            false
+        }
+    }
 }
@@ -1,6 +1,83 @@
 //! Tests for the submit.rs module
-use crate::arbitrage::submit::is_rpc_active;
-use crate::settings::RelayerSettings;
+use crate::arbitrage::submit::{
+    acquire_provider_nonce,
+    is_rpc_active,
+    is_transient_failure,
+    jito_submission_result,
+    normalize_submission_result,
+    run_simulations,
+    send_to_jito_block_engines,
+    signature_from_jito_response,
+    simulation_allows_submission,
+    submission_signature,
+    submit_transaction,
+    RpcSubmissionResult,
+    RpcSubmitter,
+    SubmissionProviders,
+    Submitter,
+    NON_SIGNATURE_RESULT_PREFIX,
+    TIMED_OUT_RESULT_PREFIX,
+};
+use crate::metrics::arbitrage::{
+    get_total_failed_transactions,
+    get_total_rpc_request_timeouts,
+    get_total_simulations_rejected,
+};
+use crate::rpc::jito::{BlockEngineScoreboard, JitoJsonRpcSDK};
+use crate::rpc::mock::MockRpc;
+use crate::rpc::{RpcActions, RpcProvider};
+use crate::settings::{JitoEndpointStrategy, RelayerSettings, SubmitMode};
+use opentelemetry::{global, Value};
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+use serial_test::serial;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use std::error::Error;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// RPC provider whose simulation returns a canned response
+struct MockSimulationRpc {
+    rpc_client: RpcClient,
+    simulation: Result<String, String>,
+}
+
+impl MockSimulationRpc {
+    fn new(simulation: Result<&str, &str>) -> Self {
+        Self {
+            rpc_client: RpcClient::new_mock("succeeds".to_string()),
+            simulation: simulation.map(str::to_string).map_err(str::to_string),
+        }
+    }
+}
+
+impl RpcActions for MockSimulationRpc {
+    fn send_tx(&self, _ixs: &mut Vec<Instruction>, _signer: &Keypair) -> Result<String, Box<dyn Error>> {
+        Ok("mock-signature".to_string())
+    }
+
+    fn simulate_tx(&self, _ixs: &mut Vec<Instruction>, _signer: &Keypair) -> Result<String, Box<dyn Error>> {
+        self.simulation.clone().map_err(|e| e.into())
+    }
+
+    fn rpc_client(&self) -> &RpcClient {
+        &self.rpc_client
+    }
+
+    fn rpc_url(&self) -> &str {
+        "mock"
+    }
+
+    fn tip_wallet(&self) -> Option<&Pubkey> {
+        None
+    }
+
+    fn min_tip_amount(&self) -> Option<u64> {
+        None
+    }
+}
 
 #[test]
 fn test_is_rpc_active() {
@@ -65,3 +142,596 @@ fn test_is_rpc_active() {
     assert!(is_rpc_active(&settings, "jito"));
     assert!(is_rpc_active(&settings, "JITO"));
 }
+
+#[test]
+fn test_submit_mode_from_settings() {
+    let settings = RelayerSettings::default();
+    assert_eq!(settings.get_submit_mode(), SubmitMode::SubmitOnly);
+
+    // The legacy simulate flag always means simulate-only
+    let settings = RelayerSettings {
+        simulate: true,
+        submit_mode: SubmitMode::SimulateThenSubmit,
+        ..RelayerSettings::default()
+    };
+    assert_eq!(settings.get_submit_mode(), SubmitMode::SimulateOnly);
+
+    assert_eq!(SubmitMode::from_str("simulate-then-submit"), Ok(SubmitMode::SimulateThenSubmit));
+    assert_eq!(SubmitMode::from_str("SIMULATE_ONLY"), Ok(SubmitMode::SimulateOnly));
+    assert!(SubmitMode::from_str("sometimes").is_err());
+}
+
+#[test]
+fn test_passing_simulation_allows_submission() {
+    let rpc = MockSimulationRpc::new(Ok(r#"{"err": null, "logs": [], "unitsConsumed": 1500}"#));
+
+    assert!(simulation_allows_submission(&rpc, &[], &Keypair::new()));
+}
+
+#[test]
+fn test_failing_simulation_blocks_submission() {
+    let rejected_before = get_total_simulations_rejected();
+    let rpc = MockSimulationRpc::new(Ok(
+        r#"{"err": {"InstructionError": [0, {"Custom": 6001}]}, "logs": [], "unitsConsumed": 4000}"#,
+    ));
+
+    assert!(!simulation_allows_submission(&rpc, &[], &Keypair::new()));
+    assert!(get_total_simulations_rejected() > rejected_before);
+}
+
+#[test]
+fn test_simulation_error_blocks_submission() {
+    let rejected_before = get_total_simulations_rejected();
+    let rpc = MockSimulationRpc::new(Err("connection refused"));
+
+    assert!(!simulation_allows_submission(&rpc, &[], &Keypair::new()));
+    assert!(get_total_simulations_rejected() > rejected_before);
+}
+
+#[test]
+fn test_successful_results_are_signatures_or_flagged() {
+    let signature = Keypair::new().sign_message(b"arbitrage");
+    let jito_response = serde_json::json!({
+        "jsonrpc": "2.0",
+        "result": signature.to_string(),
+        "id": "1"
+    });
+    let jito_signature = signature_from_jito_response(&jito_response).unwrap();
+    assert_eq!(jito_signature, signature.to_string());
+
+    let raw_results: Vec<RpcSubmissionResult> = vec![
+        ("Solana RPC".to_string(), true, format!(" {} ", signature)),
+        ("Jito".to_string(), true, jito_signature),
+        ("Jito".to_string(), true, format!("{:?}", serde_json::json!({"result": "bundle-id"}))),
+        ("Helius".to_string(), false, "rate limited".to_string()),
+    ];
+    let results: Vec<RpcSubmissionResult> = raw_results.into_iter().map(normalize_submission_result).collect();
+
+    for (provider, success, message) in &results {
+        if *success {
+            assert!(
+                Signature::from_str(message).is_ok() || message.starts_with(NON_SIGNATURE_RESULT_PREFIX),
+                "{} returned unparseable result {}",
+                provider,
+                message
+            );
+        }
+    }
+
+    assert_eq!(submission_signature(&results[0]), Some(signature));
+    assert_eq!(submission_signature(&results[1]), Some(signature));
+    assert_eq!(submission_signature(&results[2]), None);
+    assert_eq!(submission_signature(&results[3]), None);
+    assert_eq!(results[3].2, "rate limited");
+}
+
+#[test]
+fn test_jito_response_without_signature() {
+    assert!(signature_from_jito_response(&serde_json::json!({"result": "not-a-signature"})).is_none());
+    assert!(signature_from_jito_response(&serde_json::json!({"error": {"code": -32602}})).is_none());
+}
+
+#[test]
+fn test_unknown_rpc_provider_fails_validation() {
+    let settings = RelayerSettings::new_with_rpcs(
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        vec!["solana".to_string(), "jitoo".to_string()],
+        false
+    );
+
+    // The typo is neither silently active nor silently accepted
+    assert_eq!(settings.get_active_rpcs(), &[RpcProvider::Solana]);
+    assert_eq!(settings.unknown_rpcs, vec!["jitoo".to_string()]);
+    assert!(!is_rpc_active(&settings, "jito"));
+    assert!(!is_rpc_active(&settings, "jitoo"));
+
+    let err = settings.validate().unwrap_err();
+    assert!(err.to_string().contains("jitoo"));
+
+    assert!(RelayerSettings::default().validate().is_ok());
+}
+
+#[test]
+fn test_active_provider_without_api_key_is_skipped() {
+    let mut settings = RelayerSettings::default();
+    settings.helius_api_key = "".to_string();
+
+    // Helius is configured but skipped, rather than failing every submission
+    assert!(settings.active_rpcs.contains(&RpcProvider::Helius));
+    assert!(settings.validate().is_ok());
+    assert!(!is_rpc_active(&settings, "helius"));
+    assert!(!settings.get_active_rpcs().contains(&RpcProvider::Helius));
+    assert!(!settings.get_simulation_rpcs().contains(&RpcProvider::Helius));
+    assert!(is_rpc_active(&settings, "solana"));
+    assert!(is_rpc_active(&settings, "nextblock"));
+
+    // Providers that need no key are unaffected
+    settings.active_rpcs = vec![RpcProvider::Solana, RpcProvider::Jito];
+    settings.bloxroute_api_key = "".to_string();
+    assert_eq!(settings.get_active_rpcs(), vec![RpcProvider::Solana, RpcProvider::Jito]);
+
+    // Mock providers need no keys
+    settings.active_rpcs = vec![RpcProvider::Helius];
+    settings.mock_execution = true;
+    assert!(is_rpc_active(&settings, "helius"));
+}
+
+#[test]
+fn test_no_usable_active_provider_fails_validation() {
+    // Every configured provider is missing its key, so all of them would be skipped
+    let mut settings = RelayerSettings {
+        active_rpcs: vec![RpcProvider::Helius, RpcProvider::Nextblock],
+        helius_api_key: "".to_string(),
+        nextblock_api_key: "".to_string(),
+        ..RelayerSettings::default()
+    };
+    assert!(settings.get_active_rpcs().is_empty());
+    let err = settings.validate().unwrap_err();
+    assert!(err.to_string().contains("helius, nextblock"));
+
+    // As does configuring none at all
+    settings.active_rpcs = Vec::new();
+    assert!(settings.validate().is_err());
+
+    // One usable provider is enough
+    settings.active_rpcs = vec![RpcProvider::Helius, RpcProvider::Solana];
+    assert!(settings.validate().is_ok());
+}
+
+#[tokio::test]
+async fn test_simulation_uses_only_configured_providers() {
+    // By default, the active ones of Solana RPC, Helius and Nextblock, in that order
+    let settings = RelayerSettings {
+        active_rpcs: vec![RpcProvider::Jito, RpcProvider::Nextblock, RpcProvider::Solana],
+        ..RelayerSettings::default()
+    };
+    assert_eq!(settings.get_simulation_rpcs(), vec![RpcProvider::Solana, RpcProvider::Nextblock]);
+
+    let settings = RelayerSettings {
+        simulation_rpcs: vec![RpcProvider::Helius],
+        ..RelayerSettings::default()
+    };
+    assert!(settings.validate().is_ok());
+
+    let mut called = Vec::new();
+    let results = run_simulations(&settings.get_simulation_rpcs(), |provider| {
+        called.push(provider);
+        async move { (provider.as_str().to_string(), true, "simulated".to_string()) }
+    }).await;
+
+    assert_eq!(called, vec![RpcProvider::Helius]);
+    assert_eq!(results.len(), 1);
+
+    // Providers that can't simulate are rejected
+    let settings = RelayerSettings {
+        simulation_rpcs: vec![RpcProvider::Jito],
+        ..RelayerSettings::default()
+    };
+    assert!(settings.validate().is_err());
+}
+
+#[test]
+fn test_zero_pools_per_tx_fails_validation() {
+    let mut settings = RelayerSettings::default();
+    settings.max_pools_per_tx = Some(0);
+    assert!(settings.validate().is_err());
+
+    settings.max_pools_per_tx = Some(1);
+    assert!(settings.validate().is_ok());
+}
+
+#[test]
+fn test_route_through_executor_fails_validation() {
+    // The executor program doesn't swap on the DEX yet, so routed input would be stranded
+    let mut settings = RelayerSettings::default();
+    settings.route_through_executor = true;
+    let err = settings.validate().unwrap_err();
+    assert!(err.to_string().contains("route_through_executor"));
+
+    settings.route_through_executor = false;
+    assert!(settings.validate().is_ok());
+}
+
+#[test]
+fn test_unknown_key_retirement_policy_fails_validation() {
+    let mut settings = RelayerSettings::default();
+    settings.key_retirement_policy = "sometimes".to_string();
+    let err = settings.validate().unwrap_err();
+    assert!(err.to_string().contains("key_retirement_policy"));
+
+    settings.key_retirement_policy = "on_success_only".to_string();
+    assert!(settings.validate().is_ok());
+    assert_eq!(settings.get_key_retirement_policy(), qtrade_wallets::RetirementPolicy::OnSuccessOnly);
+}
+
+#[test]
+fn test_identical_transaction_to_tip_requiring_providers_fails_validation() {
+    // The shared transaction carries no tips, so these providers wouldn't land it
+    for provider in [RpcProvider::Jito, RpcProvider::Nextblock, RpcProvider::Bloxroute, RpcProvider::Temporal] {
+        let settings = RelayerSettings {
+            identical_transaction: true,
+            ..settings_with_providers(vec![RpcProvider::Solana, provider])
+        };
+        let err = settings.validate().unwrap_err();
+        assert!(err.to_string().contains(provider.as_str()), "{}", err);
+    }
+
+    let settings = RelayerSettings {
+        identical_transaction: true,
+        ..settings_with_providers(vec![RpcProvider::Solana, RpcProvider::Helius, RpcProvider::Quicknode])
+    };
+    assert!(settings.validate().is_ok());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+async fn test_submission_emits_span_per_active_provider() {
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    global::set_tracer_provider(provider.clone());
+
+    // Unreachable endpoints make both providers fail fast without leaving the machine
+    let settings = RelayerSettings {
+        solana_rpc_url: "http://127.0.0.1:1".to_string(),
+        jito_block_engine_urls: vec!["http://127.0.0.1:1/api/v1/bundles".to_string()],
+        ..RelayerSettings::new_with_providers(
+            "".to_string(), // bloxroute_api_key
+            "".to_string(), // helius_api_key
+            "".to_string(), // nextblock_api_key
+            "".to_string(), // quicknode_api_key
+            "".to_string(), // temporal_api_key
+            vec![RpcProvider::Solana, RpcProvider::Jito],
+            false,
+        )
+    };
+
+    // Jito can't fetch a blockhash, so the submission as a whole errors out
+    let providers = SubmissionProviders::with_settings(&settings);
+    let _ = submit_transaction(&[], &Keypair::new(), &providers, &settings, false).await;
+    provider.force_flush().unwrap();
+
+    let spans = exporter.get_finished_spans().unwrap();
+    let provider_spans: Vec<_> = spans
+        .iter()
+        .filter(|span| span.name.starts_with("relayer::submit::"))
+        .collect();
+
+    for provider in [RpcProvider::Solana, RpcProvider::Jito] {
+        let name = format!("relayer::submit::{}", provider.as_str());
+        let span = provider_spans
+            .iter()
+            .find(|span| span.name == name)
+            .unwrap_or_else(|| panic!("no span named {}", name));
+
+        let attribute = |key: &str| {
+            span.attributes
+                .iter()
+                .find(|kv| kv.key.as_str() == key)
+                .map(|kv| kv.value.clone())
+        };
+        assert_eq!(attribute("provider"), Some(Value::from(provider.as_str())));
+        assert_eq!(attribute("used_nonce"), Some(Value::Bool(false)));
+        assert_eq!(attribute("success"), Some(Value::Bool(false)));
+        assert_eq!(attribute("signature"), None);
+    }
+
+    // Inactive providers never start a span
+    assert!(!provider_spans.iter().any(|span| span.name == "relayer::submit::helius"));
+}
+
+#[test]
+fn test_blockhash_only_provider_skips_nonce_acquisition() {
+    let settings = RelayerSettings {
+        blockhash_only_rpcs: vec![RpcProvider::Jito],
+        ..RelayerSettings::default()
+    };
+    assert!(!settings.uses_durable_nonce(RpcProvider::Jito));
+    assert!(settings.uses_durable_nonce(RpcProvider::Solana));
+
+    let acquired = std::cell::Cell::new(0);
+    let acquire = || {
+        acquired.set(acquired.get() + 1);
+        Ok((Pubkey::new_unique(), solana_sdk::hash::Hash::new_unique()))
+    };
+
+    // Jito never touches the nonce pool
+    assert!(acquire_provider_nonce(&settings, RpcProvider::Jito, acquire).is_err());
+    assert_eq!(acquired.get(), 0);
+
+    // Other providers still try a nonce first
+    assert!(acquire_provider_nonce(&settings, RpcProvider::Solana, acquire).is_ok());
+    assert_eq!(acquired.get(), 1);
+}
+
+/// Settings with only the given providers active, all of them sending with a blockhash
+fn settings_with_providers(providers: Vec<RpcProvider>) -> RelayerSettings {
+    RelayerSettings {
+        blockhash_only_rpcs: providers.clone(),
+        active_rpcs: providers,
+        ..RelayerSettings::default()
+    }
+}
+
+/// Providers backed by `submitters`, with blockhashes fetched from a mock RPC
+fn mock_providers(submitters: Vec<(RpcProvider, MockRpc)>) -> SubmissionProviders<'static> {
+    SubmissionProviders::new(
+        MockRpc::succeeding(),
+        submitters
+            .into_iter()
+            .map(|(provider, rpc)| Box::new(RpcSubmitter::new(provider, rpc)) as Box<dyn Submitter>)
+            .collect(),
+    )
+}
+
+#[tokio::test]
+#[serial]
+async fn test_identical_transaction_is_sent_byte_for_byte() {
+    let settings = RelayerSettings {
+        identical_transaction: true,
+        ..settings_with_providers(vec![RpcProvider::Solana, RpcProvider::Helius, RpcProvider::Quicknode])
+    };
+    assert!(settings.is_identical_transaction());
+
+    let mut sent_logs = Vec::new();
+    let submitters = settings
+        .get_active_rpcs()
+        .into_iter()
+        .map(|provider| {
+            // Tips are left out of the one shared transaction
+            let rpc = MockRpc::succeeding().with_tip(Pubkey::new_unique(), 1_000);
+            sent_logs.push(rpc.sent_log());
+            (provider, rpc)
+        })
+        .collect();
+    let providers = mock_providers(submitters);
+
+    let signer = Keypair::new();
+    let instructions = vec![solana_sdk::system_instruction::transfer(&signer.pubkey(), &Pubkey::new_unique(), 1)];
+    let results = submit_transaction(&instructions, &signer, &providers, &settings, false).await.unwrap();
+
+    let names: Vec<&str> = results.iter().map(|(name, _, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["Solana RPC", "Helius", "QuickNode"]);
+
+    // Every provider gets exactly the bytes of the one signed transaction
+    let sent: Vec<_> = sent_logs.iter().map(|log| log.lock().unwrap().clone()).collect();
+    assert!(sent.iter().all(|transactions| transactions.len() == 1));
+    let expected = bincode::serialize(&sent[0][0]).unwrap();
+    assert!(sent.iter().all(|transactions| bincode::serialize(&transactions[0]).unwrap() == expected));
+    assert_eq!(sent[0][0].message.instructions.len(), instructions.len());
+
+    // So every success carries the same signature
+    let signatures: Vec<_> = results.iter().map(submission_signature).collect();
+    assert!(signatures.iter().all(|signature| signature.is_some() && *signature == signatures[0]));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_partial_success_is_aggregated_per_provider() {
+    let signer = Keypair::new();
+    let instructions = vec![solana_sdk::system_instruction::transfer(&signer.pubkey(), &Pubkey::new_unique(), 1)];
+    let settings = settings_with_providers(vec![RpcProvider::Solana, RpcProvider::Helius, RpcProvider::Temporal]);
+
+    let tip_wallet = Pubkey::new_unique();
+    let tipping = MockRpc::succeeding().with_tip(tip_wallet, 1_000);
+    let tipped = tipping.sent_log();
+    let inactive = MockRpc::succeeding();
+    let never_sent = inactive.sent_log();
+    let providers = mock_providers(vec![
+        (RpcProvider::Solana, MockRpc::succeeding()),
+        (RpcProvider::Helius, MockRpc::failing("rate limited")),
+        // Inactive, so never sent anything
+        (RpcProvider::Quicknode, inactive),
+        (RpcProvider::Temporal, tipping),
+    ]);
+
+    let failed_before = get_total_failed_transactions();
+    let results = submit_transaction(&instructions, &signer, &providers, &settings, false).await.unwrap();
+
+    let names: Vec<&str> = results.iter().map(|(name, _, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["Solana RPC", "Helius", "Temporal"]);
+    assert!(results[0].1 && !results[1].1 && results[2].1);
+    assert_eq!(results[1].2, "rate limited");
+
+    // Successes carry the signature of the transaction each provider was sent
+    assert!(submission_signature(&results[0]).is_some());
+    assert_ne!(submission_signature(&results[0]), submission_signature(&results[2]));
+
+    // The tipping provider's transaction carries its tip transfer
+    let tipped = tipped.lock().unwrap();
+    assert_eq!(tipped.len(), 1);
+    assert!(tipped[0].message.account_keys.contains(&tip_wallet));
+    assert!(never_sent.lock().unwrap().is_empty());
+
+    // A single failure isn't systemic
+    assert_eq!(get_total_failed_transactions(), failed_before);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_jito_nextblock_and_bloxroute_failures_are_reported() {
+    let signer = Keypair::new();
+    let instructions = vec![solana_sdk::system_instruction::transfer(&signer.pubkey(), &Pubkey::new_unique(), 1)];
+    let settings = settings_with_providers(vec![
+        RpcProvider::Solana,
+        RpcProvider::Jito,
+        RpcProvider::Nextblock,
+        RpcProvider::Bloxroute,
+    ]);
+
+    let jito_tip = Pubkey::new_unique();
+    let jito = MockRpc::failing("bundle dropped").with_tip(jito_tip, 10_000);
+    let jito_sent = jito.sent_log();
+    let providers = mock_providers(vec![
+        (RpcProvider::Solana, MockRpc::succeeding()),
+        (RpcProvider::Jito, jito),
+        (RpcProvider::Nextblock, MockRpc::failing("connection reset")),
+        (RpcProvider::Bloxroute, MockRpc::failing("rate limited")),
+    ]);
+
+    let results = submit_transaction(&instructions, &signer, &providers, &settings, false).await.unwrap();
+
+    assert_eq!(
+        results,
+        vec![
+            ("Solana RPC".to_string(), true, results[0].2.clone()),
+            ("Jito".to_string(), false, "bundle dropped".to_string()),
+            ("Nextblock".to_string(), false, "connection reset".to_string()),
+            ("Bloxroute".to_string(), false, "rate limited".to_string()),
+        ]
+    );
+    assert!(submission_signature(&results[0]).is_some());
+
+    // The failed Jito submission was still sent its tip
+    let jito_sent = jito_sent.lock().unwrap();
+    assert_eq!(jito_sent.len(), 1);
+    assert!(jito_sent[0].message.account_keys.contains(&jito_tip));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_repeated_critical_error_trips_circuit_breaker() {
+    let signer = Keypair::new();
+    let settings = settings_with_providers(vec![RpcProvider::Solana, RpcProvider::Helius, RpcProvider::Quicknode]);
+
+    let providers = mock_providers(vec![
+        (RpcProvider::Solana, MockRpc::failing("Transaction error: InsufficientFundsForFee")),
+        (RpcProvider::Helius, MockRpc::failing("InsufficientFundsForFee")),
+        (RpcProvider::Quicknode, MockRpc::succeeding()),
+    ]);
+
+    let failed_before = get_total_failed_transactions();
+    let results = submit_transaction(&[], &signer, &providers, &settings, false).await.unwrap();
+
+    assert_eq!(results.iter().filter(|(_, success, _)| !success).count(), 2);
+    assert_eq!(get_total_failed_transactions(), failed_before + 1);
+}
+
+/// Block engine that accepts connections but never answers
+async fn unresponsive_block_engine() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        // Hold the connections open so requests hang instead of failing
+        let mut connections = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            connections.push(socket);
+        }
+    });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_provider_request_timeout_is_transient_failure() {
+    let mut settings = RelayerSettings::default();
+    settings.rpc_request_timeouts.insert(RpcProvider::Jito, Duration::from_millis(100));
+    assert_eq!(settings.get_request_timeout(RpcProvider::Jito), Duration::from_millis(100));
+    assert_eq!(settings.get_request_timeout(RpcProvider::Helius), settings.rpc_request_timeout);
+
+    let jito_sdk = JitoJsonRpcSDK::new(&unresponsive_block_engine().await, None)
+        .with_request_timeout(settings.get_request_timeout(RpcProvider::Jito));
+    let timeouts_before = get_total_rpc_request_timeouts();
+    let started = Instant::now();
+
+    let result = jito_submission_result(jito_sdk.send_txn(Some(serde_json::json!({ "tx": "" })), false).await);
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(!result.1);
+    assert!(result.2.starts_with(TIMED_OUT_RESULT_PREFIX), "unexpected result: {}", result.2);
+    assert!(is_transient_failure(&result));
+    assert!(get_total_rpc_request_timeouts() > timeouts_before);
+
+    // Other failures aren't transient
+    assert!(!is_transient_failure(&("Helius".to_string(), false, "rate limited".to_string())));
+}
+
+/// Block engine answering every request with `result`, reporting each request line it receives
+async fn answering_block_engine(result: String) -> (String, tokio::sync::mpsc::UnboundedReceiver<String>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (requests, received) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut buf = vec![0; 16 * 1024];
+            let read = socket.read(&mut buf).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buf[..read]);
+            let _ = requests.send(request.lines().next().unwrap_or_default().to_string());
+
+            let body = serde_json::json!({ "jsonrpc": "2.0", "result": result, "id": 1 }).to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+    (format!("http://{}/api/v1/bundles", addr), received)
+}
+
+#[tokio::test]
+async fn test_custom_block_engine_endpoint_is_used() {
+    let signature = Signature::new_unique();
+    let (endpoint, mut received) = answering_block_engine(signature.to_string()).await;
+    let settings = RelayerSettings {
+        jito_block_engine_urls: vec![endpoint.clone()],
+        ..RelayerSettings::default()
+    };
+    let block_engines = BlockEngineScoreboard::new(settings.get_jito_block_engine_urls().to_vec());
+    assert_eq!(block_engines.fastest(), endpoint);
+
+    let results = send_to_jito_block_engines(&settings, &block_engines, "dHg=", signature).await;
+
+    assert_eq!(results, vec![("Jito".to_string(), true, signature.to_string())]);
+    assert!(received.recv().await.unwrap().starts_with("POST /api/v1/bundles/tx "));
+    assert!(block_engines.latency(&endpoint).is_some());
+}
+
+#[tokio::test]
+async fn test_all_strategy_sends_to_every_block_engine() {
+    let signature = Signature::new_unique();
+    let (answering, mut received) = answering_block_engine(signature.to_string()).await;
+    let unreachable = "http://127.0.0.1:1/api/v1/bundles".to_string();
+    let settings = RelayerSettings {
+        jito_block_engine_urls: vec![unreachable.clone(), answering.clone()],
+        jito_endpoint_strategy: JitoEndpointStrategy::All,
+        ..RelayerSettings::default()
+    };
+    let block_engines = BlockEngineScoreboard::new(settings.get_jito_block_engine_urls().to_vec());
+
+    let results = send_to_jito_block_engines(&settings, &block_engines, "dHg=", signature).await;
+
+    assert_eq!(results.len(), 2);
+    assert!(!results[0].1);
+    assert_eq!(results[1], ("Jito".to_string(), true, signature.to_string()));
+    assert!(received.recv().await.is_some());
+    // The failing engine falls behind the one that answered
+    assert_eq!(block_engines.fastest(), answering);
+}
@@ -0,0 +1,359 @@
+//! Module for checking the mints and token accounts an arbitrage swaps through
+//!
+//! Mint lists ([`crate::arbitrage::mint_filter`]) only catch tokens known to be bad.
+//! Before landing, the relayer also reads each mint on chain: a Token-2022 transfer fee
+//! shrinks what every swap actually receives, and a mint with a freeze authority may
+//! have frozen the pool's vaults. Small transfer fees are folded into `min_amount_out`;
+//! larger ones, and frozen vaults, skip the opportunity.
+
+use anyhow::{Result, anyhow};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+use spl_token_2022::state::{Account as TokenAccount, AccountState, Mint};
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+use crate::arbitrage::prepare::ArbitrageSwapParams;
+use crate::metrics::arbitrage::{record_fee_on_transfer_mint_skipped, record_frozen_token_account_skipped};
+
+/// Largest transfer fee folded into `min_amount_out`; higher fees skip the opportunity,
+/// since they eat more than the 1% slippage allowance
+pub const MAX_TRANSFER_FEE_BPS: u16 = 100;
+
+/// Transfer fee a Token-2022 mint charges in the current epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferFeeRate {
+    pub basis_points: u16,
+    /// Cap on the fee for a single transfer, in base units
+    pub maximum_fee: u64,
+}
+
+impl TransferFeeRate {
+    /// Fee withheld from a transfer of `amount`, rounded up as the token program does
+    pub fn fee(&self, amount: u64) -> u64 {
+        let fee = (amount as u128 * self.basis_points as u128).div_ceil(10_000);
+        fee.min(self.maximum_fee as u128) as u64
+    }
+}
+
+/// What the relayer needs to know about a mint before swapping it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MintTraits {
+    /// Program owning the mint, which its token accounts are transferred through
+    pub token_program: Pubkey,
+    pub freeze_authority: Option<Pubkey>,
+    /// Set only for mints charging a non-zero transfer fee
+    pub transfer_fee: Option<TransferFeeRate>,
+}
+
+/// Read a mint account owned by the Token or Token-2022 program
+///
+/// Returns Ok(None) if the account isn't owned by either token program.
+pub fn mint_traits(account: &Account, epoch: u64) -> Result<Option<MintTraits>> {
+    if account.owner != spl_token::id() && account.owner != spl_token_2022::id() {
+        return Ok(None);
+    }
+
+    let mint = StateWithExtensions::<Mint>::unpack(&account.data)
+        .map_err(|e| anyhow!("Invalid mint account data: {}", e))?;
+
+    let transfer_fee = mint
+        .get_extension::<TransferFeeConfig>()
+        .ok()
+        .map(|config| {
+            let fee = config.get_epoch_fee(epoch);
+            TransferFeeRate {
+                basis_points: u16::from(fee.transfer_fee_basis_points),
+                maximum_fee: u64::from(fee.maximum_fee),
+            }
+        })
+        .filter(|rate| rate.basis_points > 0 && rate.maximum_fee > 0);
+
+    Ok(Some(MintTraits {
+        token_program: account.owner,
+        freeze_authority: mint.base.freeze_authority.into(),
+        transfer_fee,
+    }))
+}
+
+/// Whether `account` is a token account that has been frozen
+pub fn is_frozen_token_account(account: &Account) -> bool {
+    StateWithExtensions::<TokenAccount>::unpack(&account.data)
+        .map(|token_account| token_account.base.state == AccountState::Frozen)
+        .unwrap_or(false)
+}
+
+/// Lower `params.min_amount_out` by the transfer fees of the swap's mints
+///
+/// The pool receives `amount_in` less the input mint's fee, so the output shrinks in
+/// proportion, and the wallet then receives that output less the output mint's fee.
+pub fn adjust_for_transfer_fees(
+    params: &mut ArbitrageSwapParams,
+    fee_in: Option<TransferFeeRate>,
+    fee_out: Option<TransferFeeRate>,
+) {
+    let mut min_amount_out = params.min_amount_out;
+
+    if let Some(rate) = fee_in {
+        if params.amount_in > 0 {
+            let received_by_pool = params.amount_in - rate.fee(params.amount_in);
+            min_amount_out = (min_amount_out as u128 * received_by_pool as u128 / params.amount_in as u128) as u64;
+        }
+    }
+    if let Some(rate) = fee_out {
+        min_amount_out -= rate.fee(min_amount_out);
+    }
+
+    if min_amount_out != params.min_amount_out {
+        info!("Lowered minimum output for pool {} from {} to {} for transfer fees",
+            params.pool_index, params.min_amount_out, min_amount_out);
+        params.min_amount_out = min_amount_out;
+    }
+}
+
+/// Source of on-chain token program accounts
+pub trait TokenAccountSource {
+    /// Fetch the given accounts, `None` for those that don't exist
+    fn get_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>>;
+
+    /// The current epoch, which selects the transfer fee in force
+    fn current_epoch(&self) -> Result<u64>;
+}
+
+/// Reads token program accounts over RPC
+pub struct RpcTokenAccountSource<'a> {
+    rpc_client: &'a RpcClient,
+}
+
+impl<'a> RpcTokenAccountSource<'a> {
+    pub fn new(rpc_client: &'a RpcClient) -> Self {
+        Self { rpc_client }
+    }
+}
+
+impl TokenAccountSource for RpcTokenAccountSource<'_> {
+    fn get_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.rpc_client
+            .get_multiple_accounts(pubkeys)
+            .map_err(|e| anyhow!("Failed to fetch token accounts: {}", e))
+    }
+
+    fn current_epoch(&self) -> Result<u64> {
+        self.rpc_client
+            .get_epoch_info()
+            .map(|epoch_info| epoch_info.epoch)
+            .map_err(|e| anyhow!("Failed to fetch epoch info: {}", e))
+    }
+}
+
+/// Checks the mints and vaults of every swap, adjusting minimum outputs for transfer fees
+///
+/// Returns Ok(true) if the swaps can go ahead, with `min_amount_out` lowered for any
+/// transfer fees and each swap's token programs set from the owners of its mints. Mints that can't be found are assumed to charge no fee.
+/// Returns Ok(false) and records a metric if a mint's transfer fee exceeds
+/// [`MAX_TRANSFER_FEE_BPS`] or a pool vault of a freezable mint is frozen.
+/// Returns Err if the accounts could not be fetched or a mint could not be read.
+pub fn check_token_risks(
+    swap_params_list: &mut [ArbitrageSwapParams],
+    account_source: &dyn TokenAccountSource,
+) -> Result<bool> {
+    let epoch = account_source.current_epoch()?;
+
+    let mut mints: Vec<Pubkey> = swap_params_list
+        .iter()
+        .flat_map(|params| [params.token_a_mint, params.token_b_mint])
+        .collect();
+    mints.sort_unstable();
+    mints.dedup();
+
+    let mut traits = HashMap::new();
+    for (mint, account) in mints.iter().zip(account_source.get_accounts(&mints)?) {
+        let Some(account) = account else {
+            warn!("Mint account {} not found, assuming no transfer fee", mint);
+            continue;
+        };
+        if let Some(mint_traits) = mint_traits(&account, epoch)? {
+            traits.insert(*mint, mint_traits);
+        }
+    }
+
+    for mint in &mints {
+        let Some(rate) = traits.get(mint).and_then(|mint_traits| mint_traits.transfer_fee) else {
+            continue;
+        };
+        if rate.basis_points > MAX_TRANSFER_FEE_BPS {
+            warn!("Skipping opportunity trading mint {} with a {} bps transfer fee (max {})",
+                mint, rate.basis_points, MAX_TRANSFER_FEE_BPS);
+            record_fee_on_transfer_mint_skipped();
+            return Ok(false);
+        }
+    }
+
+    // Only vaults of mints with a freeze authority can be frozen
+    let vaults: Vec<Pubkey> = swap_params_list
+        .iter()
+        .flat_map(|params| [(params.token_a_mint, params.token_a_vault), (params.token_b_mint, params.token_b_vault)])
+        .filter(|(mint, _)| traits.get(mint).is_some_and(|mint_traits| mint_traits.freeze_authority.is_some()))
+        .map(|(_, vault)| vault)
+        .collect();
+    if !vaults.is_empty() {
+        for (vault, account) in vaults.iter().zip(account_source.get_accounts(&vaults)?) {
+            if account.as_ref().is_some_and(is_frozen_token_account) {
+                warn!("Skipping opportunity using frozen token account {}", vault);
+                record_frozen_token_account_skipped();
+                return Ok(false);
+            }
+        }
+    }
+
+    for params in swap_params_list.iter_mut() {
+        if let Some(mint_traits) = traits.get(&params.token_a_mint) {
+            params.token_a_program = mint_traits.token_program;
+        }
+        if let Some(mint_traits) = traits.get(&params.token_b_mint) {
+            params.token_b_program = mint_traits.token_program;
+        }
+
+        let fee_in = traits.get(&params.token_a_mint).and_then(|mint_traits| mint_traits.transfer_fee);
+        let fee_out = traits.get(&params.token_b_mint).and_then(|mint_traits| mint_traits.transfer_fee);
+        adjust_for_transfer_fees(params, fee_in, fee_out);
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::test_swap_params;
+    use solana_program::program_option::COption;
+    use solana_program::program_pack::Pack;
+    use spl_token_2022::extension::transfer_fee::TransferFee;
+    use spl_token_2022::extension::{ExtensionType, StateWithExtensionsMut};
+
+    /// Account source serving fixed accounts at epoch 0
+    struct FixedAccounts(HashMap<Pubkey, Account>);
+
+    impl TokenAccountSource for FixedAccounts {
+        fn get_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+            Ok(pubkeys.iter().map(|pubkey| self.0.get(pubkey).cloned()).collect())
+        }
+
+        fn current_epoch(&self) -> Result<u64> {
+            Ok(0)
+        }
+    }
+
+    fn token_program_account(data: Vec<u8>) -> Account {
+        Account {
+            lamports: 1_000_000,
+            data,
+            owner: spl_token_2022::id(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    /// A Token-2022 mint, with a transfer fee extension if `transfer_fee_bps` is set
+    fn token_2022_mint(transfer_fee_bps: Option<u16>, freeze_authority: Option<Pubkey>) -> Account {
+        let extensions: Vec<ExtensionType> = transfer_fee_bps.iter().map(|_| ExtensionType::TransferFeeConfig).collect();
+        let mut data = vec![0u8; ExtensionType::try_calculate_account_len::<Mint>(&extensions).unwrap()];
+
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        if let Some(basis_points) = transfer_fee_bps {
+            let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+            let fee = TransferFee {
+                epoch: 0u64.into(),
+                maximum_fee: u64::MAX.into(),
+                transfer_fee_basis_points: basis_points.into(),
+            };
+            config.older_transfer_fee = fee;
+            config.newer_transfer_fee = fee;
+        }
+        state.base = Mint {
+            mint_authority: COption::None,
+            supply: 0,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: freeze_authority.into(),
+        };
+        state.pack_base();
+        if !extensions.is_empty() {
+            state.init_account_type().unwrap();
+        }
+
+        token_program_account(data)
+    }
+
+    fn frozen_token_account(mint: Pubkey) -> Account {
+        let mut data = vec![0u8; TokenAccount::LEN];
+        let token_account = TokenAccount {
+            mint,
+            owner: Pubkey::new_unique(),
+            amount: 1_000_000,
+            state: AccountState::Frozen,
+            ..TokenAccount::default()
+        };
+        TokenAccount::pack(token_account, &mut data).unwrap();
+        token_program_account(data)
+    }
+
+    fn swap_params(token_a_mint: Pubkey, token_b_mint: Pubkey) -> ArbitrageSwapParams {
+        ArbitrageSwapParams {
+            token_a_mint,
+            token_b_mint,
+            amount_in: 1_000_000,
+            min_amount_out: 990_000,
+            ..test_swap_params()
+        }
+    }
+
+    #[test]
+    fn test_transfer_fee_mint_adjusts_or_skips() {
+        let plain_mint = Pubkey::new_unique();
+        let fee_mint = Pubkey::new_unique();
+        let costly_mint = Pubkey::new_unique();
+        let source = FixedAccounts(HashMap::from([
+            (plain_mint, token_2022_mint(None, None)),
+            (fee_mint, token_2022_mint(Some(50), None)),
+            (costly_mint, token_2022_mint(Some(500), None)),
+        ]));
+
+        assert_eq!(
+            mint_traits(&source.0[&fee_mint], 0).unwrap().unwrap().transfer_fee,
+            Some(TransferFeeRate { basis_points: 50, maximum_fee: u64::MAX })
+        );
+
+        // A 0.5% fee on the output is taken out of the minimum output
+        let mut swaps = vec![swap_params(plain_mint, fee_mint)];
+        assert!(check_token_risks(&mut swaps, &source).unwrap());
+        assert_eq!(swaps[0].min_amount_out, 990_000 - 4_950);
+        assert_eq!(swaps[0].token_b_program, spl_token_2022::id());
+
+        // And on the input it shrinks what the pool receives, and so the output
+        let mut swaps = vec![swap_params(fee_mint, plain_mint)];
+        assert!(check_token_risks(&mut swaps, &source).unwrap());
+        assert_eq!(swaps[0].min_amount_out, 985_050);
+
+        // A 5% fee is beyond the slippage allowance
+        let mut swaps = vec![swap_params(plain_mint, fee_mint), swap_params(fee_mint, costly_mint)];
+        assert!(!check_token_risks(&mut swaps, &source).unwrap());
+        assert_eq!(swaps[0].min_amount_out, 990_000);
+    }
+
+    #[test]
+    fn test_frozen_vault_of_freezable_mint_skips() {
+        let freezable_mint = Pubkey::new_unique();
+        let other_mint = Pubkey::new_unique();
+        let mut swaps = vec![swap_params(other_mint, freezable_mint)];
+
+        let mut accounts = HashMap::from([(freezable_mint, token_2022_mint(None, Some(Pubkey::new_unique())))]);
+        assert!(check_token_risks(&mut swaps, &FixedAccounts(accounts.clone())).unwrap());
+
+        accounts.insert(swaps[0].token_b_vault, frozen_token_account(freezable_mint));
+        assert!(!check_token_risks(&mut swaps, &FixedAccounts(accounts)).unwrap());
+    }
+}
@@ -0,0 +1,293 @@
+//! Module for building the signed transactions of one submission cycle
+//!
+//! Every provider is sent the same instructions, plus its own tip, made recent the same
+//! way: with a durable nonce leased from the pool, or with a recent blockhash when no
+//! nonce is available. [`TxBuilder`] makes that choice once per cycle and signs each
+//! provider's transaction from it, so submitting to a provider is "build, then send".
+//! Providers configured for blockhash only get the blockhash even when a nonce was leased.
+
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use tracing::{info, warn};
+
+use crate::blockhash::BlockhashCache;
+use crate::nonce::{NonceGuard, NoncePool};
+use crate::settings::RelayerSettings;
+
+/// A durable nonce account leased from the pool for one submission cycle
+///
+/// The account goes back to the pool when the lease is dropped.
+pub struct NonceLease<'a> {
+    nonce: NonceGuard<'a>,
+    pub nonce_authority: Keypair,
+}
+
+impl<'a> NonceLease<'a> {
+    /// Lease a nonce account and its authority from `nonce_pool`
+    ///
+    /// The account goes back to the pool if the authority can't be had.
+    pub fn acquire(nonce_pool: &'a NoncePool, rpc_client: &RpcClient) -> Result<Self> {
+        let nonce = nonce_pool.acquire_guarded(rpc_client)?;
+        let nonce_authority = nonce_pool
+            .get_authority()
+            .map_err(|e| anyhow!("Failed to get nonce authority: {}", e))?;
+        Ok(Self { nonce, nonce_authority })
+    }
+
+    pub fn nonce_pubkey(&self) -> &Pubkey {
+        self.nonce.pubkey()
+    }
+
+    /// The nonce value, used in place of a recent blockhash
+    pub fn nonce_hash(&self) -> Hash {
+        self.nonce.hash()
+    }
+}
+
+/// Latest blockhash, from the cache when possible and directly from `rpc_client` otherwise
+pub fn latest_blockhash(rpc_client: &RpcClient) -> Result<Hash> {
+    let blockhash_cache = BlockhashCache::instance();
+    match blockhash_cache.get_blockhash(rpc_client, blockhash_cache.default_commitment()) {
+        Ok(hash) => Ok(hash),
+        Err(e) => {
+            warn!("Failed to get cached blockhash: {}, falling back to direct RPC", e);
+            rpc_client
+                .get_latest_blockhash()
+                .map_err(|e| anyhow!("Failed to get latest blockhash: {}", e))
+        }
+    }
+}
+
+/// A transaction signed and ready to send to one provider
+pub struct BuiltTx {
+    pub transaction: Transaction,
+    /// Whether the transaction advances the cycle's durable nonce
+    pub used_nonce: bool,
+}
+
+impl BuiltTx {
+    /// The transaction serialized and base64 encoded, as HTTP submission APIs expect
+    pub fn to_base64(&self) -> Result<String> {
+        let data = bincode::serialize(&self.transaction)
+            .map_err(|e| anyhow!("Failed to serialize transaction: {}", e))?;
+        Ok(base64::engine::general_purpose::STANDARD.encode(data))
+    }
+}
+
+/// Builds every provider's transaction for one submission cycle
+///
+/// The cycle's nonce, if one was leased, goes back to the pool when the builder is dropped.
+pub struct TxBuilder<'a> {
+    instructions: &'a [Instruction],
+    signer: &'a Keypair,
+    nonce: Option<NonceLease<'a>>,
+    blockhash: Option<Hash>,
+}
+
+impl<'a> TxBuilder<'a> {
+    /// A builder signing `instructions` with `signer`, made recent with `nonce` where
+    /// providers allow it and with `blockhash` otherwise
+    pub fn new(instructions: &'a [Instruction], signer: &'a Keypair, nonce: Option<NonceLease<'a>>, blockhash: Option<Hash>) -> Self {
+        Self { instructions, signer, nonce, blockhash }
+    }
+
+    /// Choose how this cycle's transactions are made recent
+    ///
+    /// A nonce is leased through `acquire_nonce` only if some active provider may use
+    /// one, and a blockhash fetched through `latest_blockhash` only if some active
+    /// provider will need it. Fails when neither is available.
+    pub fn for_cycle<N, B>(
+        instructions: &'a [Instruction],
+        signer: &'a Keypair,
+        settings: &RelayerSettings,
+        acquire_nonce: N,
+        latest_blockhash: B,
+    ) -> Result<Self>
+    where
+        N: FnOnce() -> Result<NonceLease<'a>>,
+        B: FnOnce() -> Result<Hash>,
+    {
+        let active_rpcs = settings.get_active_rpcs();
+
+        let nonce = if active_rpcs.iter().any(|provider| settings.uses_durable_nonce(*provider)) {
+            match acquire_nonce() {
+                Ok(lease) => {
+                    info!("Using nonce account {} with hash {} for this submission", lease.nonce_pubkey(), lease.nonce_hash());
+                    Some(lease)
+                },
+                Err(e) => {
+                    warn!("No nonce accounts available: {}, using blockhash instead", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let needs_blockhash = nonce.is_none()
+            || active_rpcs.iter().any(|provider| !settings.uses_durable_nonce(*provider));
+        let blockhash = if needs_blockhash {
+            match latest_blockhash() {
+                Ok(hash) => Some(hash),
+                Err(e) if nonce.is_some() => {
+                    warn!("Failed to get blockhash, blockhash-only providers will be skipped: {}", e);
+                    None
+                },
+                Err(e) => return Err(anyhow!("Failed to get blockhash for submission: {}", e)),
+            }
+        } else {
+            None
+        };
+
+        Ok(Self::new(instructions, signer, nonce, blockhash))
+    }
+
+    /// The nonce account leased for this cycle, if any
+    pub fn nonce_pubkey(&self) -> Option<&Pubkey> {
+        self.nonce.as_ref().map(|lease| lease.nonce_pubkey())
+    }
+
+    /// Sign the cycle's instructions, followed by `extra_instructions` (e.g. a provider tip)
+    ///
+    /// Uses the nonce when `allow_nonce` is set and one was leased, prepending the nonce
+    /// advance and signing with the nonce authority; otherwise uses the blockhash.
+    pub fn build(&self, allow_nonce: bool, extra_instructions: &[Instruction]) -> Result<BuiltTx> {
+        if let Some(lease) = self.nonce.as_ref().filter(|_| allow_nonce) {
+            let mut instructions = Vec::with_capacity(self.instructions.len() + extra_instructions.len() + 1);
+            instructions.push(crate::nonce::create_nonce_instruction(
+                lease.nonce_pubkey(),
+                &lease.nonce_authority.pubkey(),
+            ));
+            instructions.extend_from_slice(self.instructions);
+            instructions.extend_from_slice(extra_instructions);
+
+            let transaction = crate::fee_payer::sign_tx(&instructions, self.signer, &[&lease.nonce_authority], lease.nonce_hash());
+            return Ok(BuiltTx { transaction, used_nonce: true });
+        }
+
+        let blockhash = self.blockhash.ok_or_else(|| anyhow!("No blockhash available to build the transaction"))?;
+        let instructions: Vec<Instruction> = self.instructions.iter().chain(extra_instructions).cloned().collect();
+        let transaction = crate::fee_payer::sign_tx(&instructions, self.signer, &[], blockhash);
+        Ok(BuiltTx { transaction, used_nonce: false })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use solana_sdk::system_instruction;
+    use solana_sdk::system_program;
+
+    fn swap_instruction(signer: &Keypair) -> Instruction {
+        Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![solana_sdk::instruction::AccountMeta::new(signer.pubkey(), true)],
+        )
+    }
+
+    fn lease(nonce_pool: &NoncePool) -> NonceLease<'_> {
+        NonceLease {
+            nonce: nonce_pool.acquire_guarded(&RpcClient::new_mock("succeeds".to_string())).unwrap(),
+            nonce_authority: Keypair::new(),
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_nonce_path_advances_nonce_first() {
+        let signer = Keypair::new();
+        let instructions = vec![swap_instruction(&signer)];
+        let nonce_pool = NoncePool::with_available_nonces(1);
+        let lease = lease(&nonce_pool);
+        let (nonce_pubkey, nonce_authority, nonce_hash) = (*lease.nonce_pubkey(), lease.nonce_authority.pubkey(), lease.nonce_hash());
+        let tip = system_instruction::transfer(&signer.pubkey(), &Pubkey::new_unique(), 1_000);
+
+        let builder = TxBuilder::new(&instructions, &signer, Some(lease), Some(Hash::new_unique()));
+        let built = builder.build(true, &[tip]).unwrap();
+
+        assert!(built.used_nonce);
+        assert_eq!(builder.nonce_pubkey(), Some(&nonce_pubkey));
+        let message = &built.transaction.message;
+        assert_eq!(message.recent_blockhash, nonce_hash);
+        assert_eq!(message.instructions.len(), 3);
+        assert_eq!(message.account_keys[message.instructions[0].program_id_index as usize], system_program::id());
+        assert!(message.account_keys.contains(&nonce_pubkey));
+        assert!(message.account_keys.contains(&nonce_authority));
+        assert!(built.transaction.verify().is_ok());
+        assert!(built.to_base64().is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn test_blockhash_path_without_nonce() {
+        let signer = Keypair::new();
+        let instructions = vec![swap_instruction(&signer)];
+        let blockhash = Hash::new_unique();
+        let nonce_pool = NoncePool::with_available_nonces(2);
+
+        let builder = TxBuilder::new(&instructions, &signer, None, Some(blockhash));
+        let built = builder.build(true, &[]).unwrap();
+
+        assert!(!built.used_nonce);
+        assert_eq!(built.transaction.message.recent_blockhash, blockhash);
+        assert_eq!(built.transaction.message.instructions.len(), 1);
+        assert!(built.transaction.verify().is_ok());
+
+        // A blockhash-only provider gets the blockhash even when a nonce was leased
+        let builder = TxBuilder::new(&instructions, &signer, Some(lease(&nonce_pool)), Some(blockhash));
+        let built = builder.build(false, &[]).unwrap();
+        assert!(!built.used_nonce);
+        assert_eq!(built.transaction.message.recent_blockhash, blockhash);
+
+        // Without a blockhash such a provider can't be served
+        let builder = TxBuilder::new(&instructions, &signer, Some(lease(&nonce_pool)), None);
+        assert!(builder.build(false, &[]).is_err());
+        assert!(builder.build(true, &[]).is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn test_for_cycle_chooses_once() {
+        let signer = Keypair::new();
+        let instructions = vec![swap_instruction(&signer)];
+        let settings = RelayerSettings::default();
+        let nonce_pool = NoncePool::with_available_nonces(1);
+
+        // With a nonce and no blockhash-only provider, no blockhash is fetched
+        let builder = TxBuilder::for_cycle(&instructions, &signer, &settings, || Ok(lease(&nonce_pool)), || panic!("blockhash not needed")).unwrap();
+        assert!(builder.nonce_pubkey().is_some());
+        assert!(builder.build(true, &[]).unwrap().used_nonce);
+
+        // Without a nonce the blockhash is used
+        let blockhash = Hash::new_unique();
+        let builder = TxBuilder::for_cycle(&instructions, &signer, &settings, || Err(anyhow!("pool empty")), || Ok(blockhash)).unwrap();
+        assert!(builder.nonce_pubkey().is_none());
+        assert_eq!(builder.build(true, &[]).unwrap().transaction.message.recent_blockhash, blockhash);
+
+        // With neither the cycle can't submit
+        assert!(TxBuilder::for_cycle(&instructions, &signer, &settings, || Err(anyhow!("pool empty")), || Err(anyhow!("rpc down"))).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_dropping_builder_returns_nonce_to_pool() {
+        let signer = Keypair::new();
+        let instructions = vec![swap_instruction(&signer)];
+        let nonce_pool = NoncePool::with_available_nonces(1);
+
+        let builder = TxBuilder::new(&instructions, &signer, Some(lease(&nonce_pool)), None);
+        assert_eq!(nonce_pool.get_stats().unwrap(), (1, 1));
+        assert!(builder.build(true, &[]).is_ok());
+
+        drop(builder);
+        assert_eq!(nonce_pool.get_stats().unwrap(), (1, 0));
+    }
+}
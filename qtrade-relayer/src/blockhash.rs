@@ -1,6 +1,10 @@
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, Once};
 use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_response::{Response, RpcBlockhash};
+use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
 use solana_sdk::hash::Hash;
 use tokio::time::{interval, Duration};
 use std::time::Instant;
@@ -9,15 +13,49 @@ use crate::constants::QTRADE_RELAYER_TRACER_NAME;
 use opentelemetry::global;
 use opentelemetry::trace::Tracer;
 use anyhow::Result;
+use serde_json::json;
+use std::str::FromStr;
 
 const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
-const BLOCKHASH_MAX_AGE: Duration = Duration::from_secs(90); // Conservative max age for Solana blockhashes (150 blocks)
+pub const BLOCKHASH_MAX_AGE: Duration = Duration::from_secs(90); // Conservative max age for Solana blockhashes (150 blocks)
 
-/// Structure for caching the latest blockhash
+/// Source of the current time for staleness checks
+///
+/// Abstracted so tests can advance time without sleeping.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// Clock backed by `Instant::now`
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A blockhash cached for a single commitment level
+#[derive(Debug, Clone, Copy)]
+struct CachedBlockhash {
+    blockhash: Hash,
+    /// Slot at which the blockhash was fetched
+    slot: u64,
+    /// Last block height at which the blockhash is still valid
+    last_valid_block_height: u64,
+    /// Time at which the blockhash was fetched
+    fetched_at: Instant,
+}
+
+/// Structure for caching the latest blockhash, keyed by commitment level
+///
+/// Each commitment level keeps its own cached value so callers asking for
+/// `processed` blockhashes don't overwrite `finalized` ones and vice versa.
 pub struct BlockhashCache {
-    blockhash: Mutex<Hash>,
-    last_update: Mutex<Instant>,
-    is_initialized: AtomicBool,
+    entries: Mutex<HashMap<CommitmentLevel, CachedBlockhash>>,
+    default_commitment: Mutex<CommitmentConfig>,
+    max_age: Mutex<Duration>,
+    clock: Arc<dyn Clock>,
     is_running: AtomicBool,
 }
 
@@ -26,22 +64,72 @@ static mut BLOCKHASH_CACHE_INSTANCE: Option<Arc<BlockhashCache>> = None;
 static INIT_INSTANCE: Once = Once::new();
 
 impl BlockhashCache {
+    /// Create a new BlockhashCache using the given default commitment
+    pub fn new(commitment: CommitmentConfig) -> Self {
+        Self::with_clock(commitment, Arc::new(SystemClock))
+    }
+
+    /// Create a new BlockhashCache using a custom clock
+    pub fn with_clock(commitment: CommitmentConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            default_commitment: Mutex::new(commitment),
+            max_age: Mutex::new(BLOCKHASH_MAX_AGE),
+            clock,
+            is_running: AtomicBool::new(false),
+        }
+    }
+
     /// Get or initialize the global BlockhashCache instance
     pub fn instance() -> Arc<BlockhashCache> {
         unsafe {
             INIT_INSTANCE.call_once(|| {
-                BLOCKHASH_CACHE_INSTANCE = Some(Arc::new(BlockhashCache {
-                    blockhash: Mutex::new(Hash::default()),
-                    last_update: Mutex::new(Instant::now()),
-                    is_initialized: AtomicBool::new(false),
-                    is_running: AtomicBool::new(false),
-                }));
+                BLOCKHASH_CACHE_INSTANCE = Some(Arc::new(BlockhashCache::new(CommitmentConfig::confirmed())));
             });
             BLOCKHASH_CACHE_INSTANCE.clone().unwrap()
         }
     }
 
+    /// Returns the commitment used by the update task and by callers without a preference
+    pub fn default_commitment(&self) -> CommitmentConfig {
+        self.default_commitment
+            .lock()
+            .map(|commitment| *commitment)
+            .unwrap_or_else(|_| CommitmentConfig::confirmed())
+    }
+
+    /// Sets the commitment used by the update task and by callers without a preference
+    pub fn set_default_commitment(&self, commitment: CommitmentConfig) {
+        if let Ok(mut default_commitment) = self.default_commitment.lock() {
+            *default_commitment = commitment;
+            info!("Blockhash cache default commitment set to {:?}", commitment.commitment);
+        } else {
+            error!("Failed to lock default commitment for update");
+        }
+    }
+
+    /// Returns the maximum age of a cached blockhash before it is refreshed synchronously
+    pub fn max_age(&self) -> Duration {
+        self.max_age
+            .lock()
+            .map(|max_age| *max_age)
+            .unwrap_or(BLOCKHASH_MAX_AGE)
+    }
+
+    /// Sets the maximum age of a cached blockhash before it is refreshed synchronously
+    pub fn set_max_age(&self, max_age: Duration) {
+        if let Ok(mut current) = self.max_age.lock() {
+            *current = max_age;
+            info!("Blockhash cache max age set to {:?}", max_age);
+        } else {
+            error!("Failed to lock max age for update");
+        }
+    }
+
     /// Starts the blockhash update task
+    ///
+    /// The task refreshes the default commitment plus every commitment level
+    /// that callers have requested through `get_blockhash`.
     pub async fn start_update_task(&self, rpc_url: &str) -> Result<()> {
         let already_running = self.is_running.swap(true, Ordering::SeqCst);
         if already_running {
@@ -53,7 +141,7 @@ impl BlockhashCache {
         let rpc_client = RpcClient::new(rpc_url.to_string());
 
         // Update once immediately before starting the interval
-        self.update_blockhash(&rpc_client)?;
+        self.update_blockhash(&rpc_client, self.default_commitment())?;
 
         // Clone Arc for the task
         let cache_ref = Arc::clone(&BlockhashCache::instance());
@@ -68,8 +156,10 @@ impl BlockhashCache {
 
                 let span_name = format!("{}::update_task", "blockhash_cache");
                 let result = tracer.in_span(span_name, |_cx| {
-                    if let Err(e) = cache_ref.update_blockhash(&rpc_client) {
-                        error!("Failed to update blockhash: {:?}", e);
+                    for commitment in cache_ref.tracked_commitments() {
+                        if let Err(e) = cache_ref.update_blockhash(&rpc_client, commitment) {
+                            error!("Failed to update blockhash for {:?}: {:?}", commitment.commitment, e);
+                        }
                     }
                     // Return an empty result since we're in a synchronous closure
                     Ok::<_, anyhow::Error>(())
@@ -85,29 +175,40 @@ impl BlockhashCache {
         Ok(())
     }
 
-    /// Updates the cached blockhash
-    fn update_blockhash(&self, rpc_client: &RpcClient) -> Result<()> {
-        match rpc_client.get_latest_blockhash() {
-            Ok(hash) => {
-                // Lock and update the blockhash
-                if let Ok(mut blockhash) = self.blockhash.lock() {
-                    *blockhash = hash;
-                } else {
-                    error!("Failed to lock blockhash for update");
-                    return Err(anyhow::anyhow!("Failed to lock blockhash for update"));
-                }
+    /// Commitment levels the update task should refresh
+    fn tracked_commitments(&self) -> Vec<CommitmentConfig> {
+        let default_commitment = self.default_commitment();
+        let mut commitments = vec![default_commitment];
 
-                // Lock and update the timestamp
-                if let Ok(mut last_update) = self.last_update.lock() {
-                    *last_update = Instant::now();
-                    self.is_initialized.store(true, Ordering::SeqCst);
-                    debug!("Updated blockhash cache: {}", hash);
-                } else {
-                    error!("Failed to lock last_update timestamp");
-                    return Err(anyhow::anyhow!("Failed to lock last_update timestamp"));
-                }
+        if let Ok(entries) = self.entries.lock() {
+            commitments.extend(
+                entries
+                    .keys()
+                    .filter(|level| **level != default_commitment.commitment)
+                    .map(|level| CommitmentConfig { commitment: *level }),
+            );
+        }
+
+        commitments
+    }
 
-                Ok(())
+    /// Updates the cached blockhash for a commitment level
+    ///
+    /// Uses the raw `getLatestBlockhash` request so the response context slot
+    /// can be recorded alongside the blockhash.
+    fn update_blockhash(&self, rpc_client: &RpcClient, commitment: CommitmentConfig) -> Result<Hash> {
+        let response = rpc_client.send::<Response<RpcBlockhash>>(
+            RpcRequest::GetLatestBlockhash,
+            json!([{ "commitment": commitment.commitment }]),
+        );
+
+        match response {
+            Ok(response) => {
+                let hash = Hash::from_str(&response.value.blockhash)
+                    .map_err(|e| anyhow::anyhow!("Failed to parse blockhash {}: {:?}", response.value.blockhash, e))?;
+                self.store_blockhash(commitment, hash, response.context.slot, response.value.last_valid_block_height)?;
+                debug!("Updated blockhash cache ({:?}) at slot {}: {}", commitment.commitment, response.context.slot, hash);
+                Ok(hash)
             },
             Err(e) => {
                 error!("Failed to get latest blockhash from RPC client: {:?}", e);
@@ -116,37 +217,149 @@ impl BlockhashCache {
         }
     }
 
-    /// Gets the cached blockhash, or fetches a new one if too old
-    pub fn get_blockhash(&self, rpc_client: &RpcClient) -> Result<Hash> {
-        // Check if cache is initialized
-        if !self.is_initialized.load(Ordering::SeqCst) {
-            warn!("Blockhash cache not initialized yet, fetching directly");
-            return rpc_client.get_latest_blockhash()
-                .map_err(|e| anyhow::anyhow!("Failed to get latest blockhash: {:?}", e));
+    /// Stores a blockhash for a commitment level
+    fn store_blockhash(&self, commitment: CommitmentConfig, blockhash: Hash, slot: u64, last_valid_block_height: u64) -> Result<()> {
+        let mut entries = self.entries.lock().map_err(|_| {
+            error!("Failed to lock blockhash entries for update");
+            anyhow::anyhow!("Failed to lock blockhash entries for update")
+        })?;
+
+        entries.insert(
+            commitment.commitment,
+            CachedBlockhash {
+                blockhash,
+                slot,
+                last_valid_block_height,
+                fetched_at: self.clock.now(),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Drops every cached blockhash, so the next `get_blockhash` fetches a fresh one
+    ///
+    /// Used when providers report the cached blockhash as expired before `max_age` is up.
+    pub fn invalidate(&self) {
+        match self.entries.lock() {
+            Ok(mut entries) => entries.clear(),
+            Err(_) => error!("Failed to lock blockhash entries for invalidation"),
         }
+    }
 
-        // Check if cached blockhash is still fresh
-        let is_expired = {
-            if let Ok(last_update) = self.last_update.lock() {
-                last_update.elapsed() > BLOCKHASH_MAX_AGE
-            } else {
-                // If we can't lock, assume it's expired
-                true
-            }
+    /// Gets the cached blockhash for a commitment level, or fetches a new one if missing or too old
+    pub fn get_blockhash(&self, rpc_client: &RpcClient, commitment: CommitmentConfig) -> Result<Hash> {
+        let cached = {
+            let entries = self.entries.lock().map_err(|_| {
+                error!("Failed to lock blockhash entries for reading");
+                anyhow::anyhow!("Failed to lock blockhash entries for reading")
+            })?;
+            entries.get(&commitment.commitment).copied()
         };
 
-        if is_expired {
-            warn!("Cached blockhash is expired, fetching new one");
-            return rpc_client.get_latest_blockhash()
-                .map_err(|e| anyhow::anyhow!("Failed to get latest blockhash: {:?}", e));
+        match cached {
+            None => {
+                warn!("Blockhash cache not initialized for {:?}, fetching directly", commitment.commitment);
+                self.update_blockhash(rpc_client, commitment)
+            },
+            Some(entry) if self.clock.now().saturating_duration_since(entry.fetched_at) > self.max_age() => {
+                warn!(
+                    "Cached blockhash for {:?} fetched at slot {} (valid until height {}) is stale, refreshing",
+                    commitment.commitment, entry.slot, entry.last_valid_block_height
+                );
+                self.update_blockhash(rpc_client, commitment)
+            },
+            Some(entry) => Ok(entry.blockhash),
         }
+    }
+}
 
-        // Return the cached blockhash
-        if let Ok(blockhash) = self.blockhash.lock() {
-            Ok(*blockhash)
-        } else {
-            error!("Failed to lock blockhash for reading");
-            Err(anyhow::anyhow!("Failed to lock blockhash for reading"))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commitments_are_cached_independently() {
+        let cache = BlockhashCache::new(CommitmentConfig::confirmed());
+        // Mock client would answer any fetch with the same fixed hash
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+
+        let processed_hash = Hash::new_unique();
+        let finalized_hash = Hash::new_unique();
+        cache.store_blockhash(CommitmentConfig::processed(), processed_hash, 1, 150).unwrap();
+        cache.store_blockhash(CommitmentConfig::finalized(), finalized_hash, 1, 150).unwrap();
+
+        assert_eq!(cache.get_blockhash(&rpc_client, CommitmentConfig::processed()).unwrap(), processed_hash);
+        assert_eq!(cache.get_blockhash(&rpc_client, CommitmentConfig::finalized()).unwrap(), finalized_hash);
+
+        // Refreshing one commitment must not touch the other
+        let refreshed = Hash::new_unique();
+        cache.store_blockhash(CommitmentConfig::processed(), refreshed, 2, 151).unwrap();
+        assert_eq!(cache.get_blockhash(&rpc_client, CommitmentConfig::processed()).unwrap(), refreshed);
+        assert_eq!(cache.get_blockhash(&rpc_client, CommitmentConfig::finalized()).unwrap(), finalized_hash);
+    }
+
+    #[test]
+    fn test_invalidate_forces_a_fresh_fetch() {
+        let cache = BlockhashCache::new(CommitmentConfig::confirmed());
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+
+        let expired = Hash::new_unique();
+        cache.store_blockhash(CommitmentConfig::confirmed(), expired, 1, 150).unwrap();
+        assert_eq!(cache.get_blockhash(&rpc_client, CommitmentConfig::confirmed()).unwrap(), expired);
+
+        cache.invalidate();
+        assert_ne!(cache.get_blockhash(&rpc_client, CommitmentConfig::confirmed()).unwrap(), expired);
+    }
+
+    #[test]
+    fn test_tracked_commitments_include_requested_levels() {
+        let cache = BlockhashCache::new(CommitmentConfig::confirmed());
+        cache.store_blockhash(CommitmentConfig::finalized(), Hash::new_unique(), 1, 150).unwrap();
+
+        let tracked: Vec<CommitmentLevel> = cache.tracked_commitments().iter().map(|c| c.commitment).collect();
+        assert_eq!(tracked[0], CommitmentLevel::Confirmed);
+        assert!(tracked.contains(&CommitmentLevel::Finalized));
+    }
+
+    /// Clock that only moves when told to
+    struct MockClock {
+        now: Mutex<Instant>,
+    }
+
+    impl MockClock {
+        fn advance(&self, by: Duration) {
+            let mut now = self.now.lock().unwrap();
+            *now += by;
         }
     }
+
+    impl Clock for MockClock {
+        fn now(&self) -> Instant {
+            *self.now.lock().unwrap()
+        }
+    }
+
+    #[test]
+    fn test_stale_blockhash_is_refreshed() {
+        let clock = Arc::new(MockClock { now: Mutex::new(Instant::now()) });
+        let cache = BlockhashCache::with_clock(CommitmentConfig::confirmed(), clock.clone());
+        cache.set_max_age(Duration::from_secs(30));
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+
+        let cached_hash = Hash::new_unique();
+        cache.store_blockhash(CommitmentConfig::confirmed(), cached_hash, 1, 150).unwrap();
+
+        // Still within the bound: cached value is served
+        clock.advance(Duration::from_secs(29));
+        assert_eq!(cache.get_blockhash(&rpc_client, CommitmentConfig::confirmed()).unwrap(), cached_hash);
+
+        // Past the bound: a synchronous refresh replaces the cached value
+        clock.advance(Duration::from_secs(2));
+        let refreshed = cache.get_blockhash(&rpc_client, CommitmentConfig::confirmed()).unwrap();
+        assert_ne!(refreshed, cached_hash);
+
+        // The refreshed value is cached with a new fetch time
+        assert_eq!(cache.get_blockhash(&rpc_client, CommitmentConfig::confirmed()).unwrap(), refreshed);
+    }
 }
@@ -0,0 +1,234 @@
+// Meteora DLMM DEX implementation for LbPair swaps
+
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use anyhow::{Result, anyhow};
+use super::DexSwap;
+
+/// Number of bins held by each bin array account
+const MAX_BIN_PER_ARRAY: i64 = 70;
+
+/// Anchor discriminator of the `swap` instruction (exact input)
+const SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+/// Anchor discriminator of the `swap_exact_out` instruction
+const SWAP_EXACT_OUT_DISCRIMINATOR: [u8; 8] = [250, 73, 101, 33, 38, 207, 75, 184];
+
+/// Implementation for Meteora DLMM swaps
+pub struct MeteoraDlmmSwap {
+    /// Bin the pair trades in, which picks the bin arrays a swap passes
+    active_id: i32,
+}
+
+impl MeteoraDlmmSwap {
+    /// Create a MeteoraDlmmSwap for a pair whose indexed LbPair trades in bin `active_id`
+    pub fn new(active_id: i32) -> Self {
+        Self { active_id }
+    }
+
+    /// Get the Meteora DLMM program ID
+    pub fn program_id() -> Pubkey {
+        // Mainnet Meteora DLMM (lb_clmm) program ID
+        "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo".parse().unwrap()
+    }
+
+    /// Derive the oracle PDA of an LbPair
+    fn find_oracle(&self, lb_pair: &Pubkey) -> Pubkey {
+        let seeds = [b"oracle".as_ref(), lb_pair.as_ref()];
+        Pubkey::find_program_address(&seeds, &Self::program_id()).0
+    }
+
+    /// Derive the Anchor event authority PDA used by the program's CPI events
+    fn find_event_authority(&self) -> Pubkey {
+        Pubkey::find_program_address(&[b"__event_authority".as_ref()], &Self::program_id()).0
+    }
+
+    /// Derive the PDA of the bin array with `index` for an LbPair
+    fn find_bin_array(&self, lb_pair: &Pubkey, index: i64) -> Pubkey {
+        let index_bytes = index.to_le_bytes();
+        let seeds = [b"bin_array".as_ref(), lb_pair.as_ref(), index_bytes.as_ref()];
+        Pubkey::find_program_address(&seeds, &Self::program_id()).0
+    }
+
+    /// Find the bin arrays a swap starting at `active_id` may cross
+    ///
+    /// Starts at the array holding the active bin and walks down when selling X,
+    /// up when selling Y.
+    fn find_bin_arrays(&self, lb_pair: &Pubkey, active_id: i32, is_x_to_y: bool) -> Vec<Pubkey> {
+        let active_array_index = i64::from(active_id).div_euclid(MAX_BIN_PER_ARRAY);
+        let step = if is_x_to_y { -1 } else { 1 };
+
+        (0..3)
+            .map(|i| self.find_bin_array(lb_pair, active_array_index + i * step))
+            .collect()
+    }
+}
+
+impl DexSwap for MeteoraDlmmSwap {
+    /// Token A is the pair's token X and token B its token Y
+    fn create_swap_instruction(
+        &self,
+        pool_address: &Pubkey,
+        token_authority: &Pubkey,
+        token_a_address: &Pubkey,
+        token_a_mint: &Pubkey,
+        token_a_vault: &Pubkey,
+        token_a_program: &Pubkey,
+        token_b_address: &Pubkey,
+        token_b_mint: &Pubkey,
+        token_b_vault: &Pubkey,
+        token_b_program: &Pubkey,
+        amount: u64,
+        amount_threshold: u64,
+        is_token_a_to_b: bool,
+        is_exact_input: bool
+    ) -> Result<Instruction> {
+        let program_id = Self::program_id();
+        let oracle = self.find_oracle(pool_address);
+        let event_authority = self.find_event_authority();
+
+        let (user_token_in, user_token_out) = if is_token_a_to_b {
+            (token_a_address, token_b_address)
+        } else {
+            (token_b_address, token_a_address)
+        };
+
+        // Optional accounts are passed as the program ID itself
+        let mut accounts = vec![
+            AccountMeta::new(*pool_address, false),
+            // Bin array bitmap extension (only needed for bins far from 0)
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new(*token_a_vault, false),
+            AccountMeta::new(*token_b_vault, false),
+            AccountMeta::new(*user_token_in, false),
+            AccountMeta::new(*user_token_out, false),
+            AccountMeta::new_readonly(*token_a_mint, false),
+            AccountMeta::new_readonly(*token_b_mint, false),
+            AccountMeta::new(oracle, false),
+            // Host fee account (none)
+            AccountMeta::new_readonly(program_id, false),
+            // Token Authority (signer)
+            AccountMeta::new_readonly(*token_authority, true),
+            AccountMeta::new_readonly(*token_a_program, false),
+            AccountMeta::new_readonly(*token_b_program, false),
+            AccountMeta::new_readonly(event_authority, false),
+            AccountMeta::new_readonly(program_id, false),
+        ];
+
+        // Bin arrays the swap may cross go in the remaining accounts
+        accounts.extend(
+            self.find_bin_arrays(pool_address, self.active_id, is_token_a_to_b)
+                .into_iter()
+                .map(|bin_array| AccountMeta::new(bin_array, false)),
+        );
+
+        // Define the instruction data
+        use borsh::BorshSerialize;
+
+        #[derive(BorshSerialize)]
+        struct SwapInstructionData {
+            discriminator: [u8; 8],
+            amount: u64,
+            other_amount_threshold: u64,
+        }
+
+        // `swap` takes (amount_in, min_amount_out), `swap_exact_out` (max_in_amount, out_amount)
+        let data = if is_exact_input {
+            SwapInstructionData {
+                discriminator: SWAP_DISCRIMINATOR,
+                amount,
+                other_amount_threshold: amount_threshold,
+            }
+        } else {
+            SwapInstructionData {
+                discriminator: SWAP_EXACT_OUT_DISCRIMINATOR,
+                amount: amount_threshold,
+                other_amount_threshold: amount,
+            }
+        }
+        .try_to_vec()
+        .map_err(|e| anyhow!("Failed to serialize swap instruction data: {}", e))?;
+
+        Ok(Instruction {
+            program_id,
+            accounts,
+            data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_instruction_layout() {
+        let swap = MeteoraDlmmSwap::new(0);
+        let pool = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let (token_x, token_y) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let instruction = swap
+            .create_swap_instruction(
+                &pool, &authority,
+                &token_x, &Pubkey::new_unique(), &Pubkey::new_unique(), &spl_token::id(),
+                &token_y, &Pubkey::new_unique(), &Pubkey::new_unique(), &spl_token::id(),
+                1_000, 990, false, true,
+            )
+            .unwrap();
+
+        assert_eq!(instruction.program_id, MeteoraDlmmSwap::program_id());
+        // 15 fixed accounts plus 3 bin arrays
+        assert_eq!(instruction.accounts.len(), 18);
+        assert_eq!(instruction.accounts[0].pubkey, pool);
+        // Selling Y: the user's Y account is debited and the X account credited
+        assert_eq!(instruction.accounts[4].pubkey, token_y);
+        assert_eq!(instruction.accounts[5].pubkey, token_x);
+        assert!(instruction.accounts[10].is_signer);
+        assert_eq!(instruction.accounts[10].pubkey, authority);
+
+        assert_eq!(&instruction.data[..8], &SWAP_DISCRIMINATOR);
+        assert_eq!(&instruction.data[8..16], &1_000u64.to_le_bytes());
+        assert_eq!(&instruction.data[16..24], &990u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_exact_out_swap_data() {
+        let swap = MeteoraDlmmSwap::new(0);
+        let instruction = swap
+            .create_swap_instruction(
+                &Pubkey::new_unique(), &Pubkey::new_unique(),
+                &Pubkey::new_unique(), &Pubkey::new_unique(), &Pubkey::new_unique(), &spl_token::id(),
+                &Pubkey::new_unique(), &Pubkey::new_unique(), &Pubkey::new_unique(), &spl_token::id(),
+                1_000, 1_010, true, false,
+            )
+            .unwrap();
+
+        // swap_exact_out takes the max input first, then the exact output
+        assert_eq!(&instruction.data[..8], &SWAP_EXACT_OUT_DISCRIMINATOR);
+        assert_eq!(&instruction.data[8..16], &1_010u64.to_le_bytes());
+        assert_eq!(&instruction.data[16..24], &1_000u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_bin_arrays_start_at_the_active_bin() {
+        let pool = Pubkey::new_unique();
+        let swap = MeteoraDlmmSwap::new(150);
+        let instruction = swap
+            .create_swap_instruction(
+                &pool, &Pubkey::new_unique(),
+                &Pubkey::new_unique(), &Pubkey::new_unique(), &Pubkey::new_unique(), &spl_token::id(),
+                &Pubkey::new_unique(), &Pubkey::new_unique(), &Pubkey::new_unique(), &spl_token::id(),
+                1_000, 990, true, true,
+            )
+            .unwrap();
+
+        // Bin 150 sits in array 2; selling X walks down from there
+        let bin_arrays: Vec<Pubkey> = instruction.accounts[15..].iter().map(|account| account.pubkey).collect();
+        let expected: Vec<Pubkey> = [2, 1, 0].iter().map(|&index| swap.find_bin_array(&pool, index)).collect();
+        assert_eq!(bin_arrays, expected);
+
+        // Negative bins round down to the array below zero
+        assert_eq!(swap.find_bin_arrays(&pool, -1, false)[0], swap.find_bin_array(&pool, -1));
+    }
+}
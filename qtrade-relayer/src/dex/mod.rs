@@ -3,33 +3,44 @@
 // This module contains the implementations for various DEXes swap instructions.
 // Current supported DEXes:
 // - Orca (Whirlpool)
+// - Meteora DLMM
+// - Phoenix (order book, immediate-or-cancel orders)
+// - Raydium CLMM
 //
 // Planned support:
 // - Raydium
 // - Raydium CPMM
-// - Raydium CLMM
 
 pub mod orca;
 pub mod raydium;
 pub mod raydium_cpmm;
 pub mod raydium_clmm;
+pub mod meteora_dlmm;
+pub mod phoenix;
 
 use solana_sdk::instruction::Instruction;
 use solana_sdk::pubkey::Pubkey;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use qtrade_shared_types::PoolState;
 
 /// Trait for DEX implementations
 pub trait DexSwap {
     /// Create a swap instruction for the DEX
+    ///
+    /// `token_a_program` and `token_b_program` are the programs owning each mint, legacy
+    /// SPL Token or Token-2022; builders pass them explicitly or pick the instruction
+    /// variant that supports them.
     fn create_swap_instruction(&self,
         pool_address: &Pubkey,
         token_authority: &Pubkey,
         token_a_address: &Pubkey,
         token_a_mint: &Pubkey,
         token_a_vault: &Pubkey,
+        token_a_program: &Pubkey,
         token_b_address: &Pubkey,
         token_b_mint: &Pubkey,
         token_b_vault: &Pubkey,
+        token_b_program: &Pubkey,
         amount: u64,
         amount_threshold: u64,
         is_token_a_to_b: bool,
@@ -37,6 +48,11 @@ pub trait DexSwap {
     ) -> Result<Instruction>;
 }
 
+/// Whether `token_program` is the Token-2022 program rather than legacy SPL Token
+pub fn is_token_2022(token_program: &Pubkey) -> bool {
+    *token_program == spl_token_2022::id()
+}
+
 /// Identifies the DEX type for swap instruction creation
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DexType {
@@ -44,24 +60,113 @@ pub enum DexType {
     Raydium,
     RaydiumCpmm,
     RaydiumClmm,
+    MeteoraDlmm,
+    Phoenix,
+    /// A pool that couldn't be attributed to a supported DEX; never swapped
+    Unknown,
+}
+
+impl DexType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DexType::Orca => "orca",
+            DexType::Raydium => "raydium",
+            DexType::RaydiumCpmm => "raydium_cpmm",
+            DexType::RaydiumClmm => "raydium_clmm",
+            DexType::MeteoraDlmm => "meteora_dlmm",
+            DexType::Phoenix => "phoenix",
+            DexType::Unknown => "unknown",
+        }
+    }
 }
 
 /// Factory function to create a DEX swap implementation
-pub fn create_dex_swap(dex_type: DexType) -> Box<dyn DexSwap> {
+///
+/// Fails for `DexType::Unknown`, which has no swap instruction to build, for a Meteora
+/// DLMM pair without its indexed `pool_state`, whose active bin picks the swap's accounts,
+/// and for a Phoenix market without it, whose lot sizes size the order.
+pub fn create_dex_swap(dex_type: DexType, pool_state: Option<&PoolState>) -> Result<Box<dyn DexSwap>> {
     match dex_type {
-        DexType::Orca => Box::new(orca::OrcaSwap::new()),
-        DexType::Raydium => Box::new(raydium::RaydiumSwap::new()),
-        DexType::RaydiumCpmm => Box::new(raydium_cpmm::RaydiumCpmmSwap::new()),
-        DexType::RaydiumClmm => Box::new(raydium_clmm::RaydiumClmmSwap::new()),
+        DexType::Orca => Ok(Box::new(orca::OrcaSwap::new())),
+        DexType::Raydium => Ok(Box::new(raydium::RaydiumSwap::new())),
+        DexType::RaydiumCpmm => Ok(Box::new(raydium_cpmm::RaydiumCpmmSwap::new())),
+        DexType::RaydiumClmm => Ok(Box::new(raydium_clmm::RaydiumClmmSwap::new())),
+        DexType::MeteoraDlmm => match pool_state {
+            Some(PoolState::MeteoraDlmm(pair)) => Ok(Box::new(meteora_dlmm::MeteoraDlmmSwap::new(pair.active_id))),
+            _ => Err(anyhow!("Cannot create Meteora DLMM swap instructions without the pair's indexed state")),
+        },
+        DexType::Phoenix => match pool_state {
+            Some(PoolState::Phoenix(market)) => Ok(Box::new(phoenix::PhoenixSwap::for_market(&market.order_book))),
+            _ => Err(anyhow!("Cannot create Phoenix swap instructions without the market's indexed state")),
+        },
+        DexType::Unknown => Err(anyhow!("Cannot create swap instructions for an unknown DEX")),
+    }
+}
+
+/// Determine DEX type from the program that owns a pool account
+pub fn dex_type_from_program_id(program_id: &Pubkey) -> Option<DexType> {
+    if *program_id == orca::OrcaSwap::program_id() {
+        Some(DexType::Orca)
+    } else if *program_id == raydium::RaydiumSwap::program_id() {
+        Some(DexType::Raydium)
+    } else if *program_id == raydium_cpmm::RaydiumCpmmSwap::program_id() {
+        Some(DexType::RaydiumCpmm)
+    } else if *program_id == raydium_clmm::RaydiumClmmSwap::program_id() {
+        Some(DexType::RaydiumClmm)
+    } else if *program_id == meteora_dlmm::MeteoraDlmmSwap::program_id() {
+        Some(DexType::MeteoraDlmm)
+    } else if *program_id == phoenix::PhoenixSwap::program_id() {
+        Some(DexType::Phoenix)
+    } else {
+        None
     }
 }
 
 /// Determine DEX type based on pool address
 ///
 /// This function tries to determine the DEX type based on the pool address format or prefix.
-/// Currently it's a placeholder, to be implemented with actual DEX detection logic.
-pub fn determine_dex_type(pool_address: &Pubkey) -> DexType {
-    // For now, default to Orca
+/// No address patterns are recognized yet, so every pool is `DexType::Unknown`; callers
+/// skip such pools rather than building instructions for the wrong DEX.
+pub fn determine_dex_type(_pool_address: &Pubkey) -> DexType {
     // In the future, we'll implement proper detection based on the pool address
-    DexType::Orca
+    DexType::Unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_dex_has_no_swap() {
+        assert_eq!(determine_dex_type(&Pubkey::new_unique()), DexType::Unknown);
+        assert!(create_dex_swap(DexType::Unknown, None).is_err());
+        assert!(create_dex_swap(DexType::Orca, None).is_ok());
+        assert_eq!(dex_type_from_program_id(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_meteora_dlmm_swap_needs_the_pair_state() {
+        assert!(create_dex_swap(DexType::MeteoraDlmm, None).is_err());
+        assert!(create_dex_swap(DexType::MeteoraDlmm, Some(&PoolState::Unknown)).is_err());
+
+        let pair = PoolState::MeteoraDlmm(qtrade_shared_types::DlmmState { active_id: 150, ..Default::default() });
+        assert!(create_dex_swap(DexType::MeteoraDlmm, Some(&pair)).is_ok());
+    }
+
+    #[test]
+    fn test_phoenix_swap_needs_the_market_state() {
+        assert!(create_dex_swap(DexType::Phoenix, None).is_err());
+        assert!(create_dex_swap(DexType::Phoenix, Some(&PoolState::MeteoraDlmm(Default::default()))).is_err());
+
+        let market = PoolState::Phoenix(qtrade_shared_types::PhoenixMarketState {
+            order_book: qtrade_shared_types::OrderBookState {
+                base_lot_size: 1_000,
+                quote_lot_size: 10,
+                tick_size_in_quote_lots_per_base_unit: 1,
+                ..Default::default()
+            },
+            taker_fee_bps: 2,
+        });
+        assert!(create_dex_swap(DexType::Phoenix, Some(&market)).is_ok());
+    }
 }
@@ -53,9 +53,11 @@ impl DexSwap for OrcaSwap {
         token_a_address: &Pubkey,
         token_a_mint: &Pubkey,
         token_a_vault: &Pubkey,
+        token_a_program: &Pubkey,
         token_b_address: &Pubkey,
         token_b_mint: &Pubkey,
         token_b_vault: &Pubkey,
+        token_b_program: &Pubkey,
         amount: u64,
         amount_threshold: u64,
         is_token_a_to_b: bool,
@@ -65,9 +67,8 @@ impl DexSwap for OrcaSwap {
         let (tick_array0, tick_array1, tick_array2) = self.find_tick_arrays(pool_address)?;
         let oracle = self.find_oracle(pool_address)?;
 
-        // For Whirlpool V2, we need token programs and memo program
-        let token_program_a = spl_token::id();
-        let token_program_b = spl_token::id();  // Same as A unless using token-2022
+        // SwapV2 takes each mint's token program, so it handles both legacy and
+        // Token-2022 mints, plus the memo program Token-2022 transfers may require
         let memo_program = spl_memo::id();
 
         // Create the SwapV2 instruction
@@ -76,8 +77,8 @@ impl DexSwap for OrcaSwap {
         // Define the accounts for the swap instruction
         let accounts = vec![
             // Token Programs and Memo
-            solana_sdk::instruction::AccountMeta::new_readonly(token_program_a, false),
-            solana_sdk::instruction::AccountMeta::new_readonly(token_program_b, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(*token_a_program, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(*token_b_program, false),
             solana_sdk::instruction::AccountMeta::new_readonly(memo_program, false),
 
             // Token Authority (signer)
@@ -129,3 +130,28 @@ impl DexSwap for OrcaSwap {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_references_each_mints_token_program() {
+        let swap = OrcaSwap::new();
+        let (token_a_mint, token_b_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let instruction = swap
+            .create_swap_instruction(
+                &Pubkey::new_unique(), &Pubkey::new_unique(),
+                &Pubkey::new_unique(), &token_a_mint, &Pubkey::new_unique(), &spl_token::id(),
+                &Pubkey::new_unique(), &token_b_mint, &Pubkey::new_unique(), &spl_token_2022::id(),
+                1_000, 990, true, true,
+            )
+            .unwrap();
+
+        assert_eq!(instruction.accounts[0].pubkey, spl_token::id());
+        assert_eq!(instruction.accounts[1].pubkey, spl_token_2022::id());
+        assert_eq!(instruction.accounts[5].pubkey, token_a_mint);
+        assert_eq!(instruction.accounts[6].pubkey, token_b_mint);
+    }
+}
@@ -0,0 +1,242 @@
+// Phoenix DEX implementation for order book swaps
+//
+// Phoenix swaps are immediate-or-cancel (IOC) orders that take liquidity from the
+// book and never rest. Token A is the market's base token and token B its quote token.
+
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use anyhow::{Result, anyhow};
+use borsh::BorshSerialize;
+use qtrade_shared_types::OrderBookState;
+use super::{is_token_2022, DexSwap};
+
+/// Tag of the `Swap` instruction
+const SWAP_INSTRUCTION_TAG: u8 = 0;
+
+/// Borsh variant index of `OrderPacket::ImmediateOrCancel`
+const IMMEDIATE_OR_CANCEL_TAG: u8 = 2;
+
+/// Order side, matching Phoenix's `Side`
+#[derive(BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    /// Buy base with quote
+    Bid,
+    /// Sell base for quote
+    Ask,
+}
+
+/// What to do when an order would match the trader's own resting order
+#[derive(BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+enum SelfTradeBehavior {
+    Abort,
+    CancelProvide,
+    DecrementTake,
+}
+
+/// Fields of `OrderPacket::ImmediateOrCancel`
+#[derive(BorshSerialize, Debug)]
+struct ImmediateOrCancelOrder {
+    side: Side,
+    price_in_ticks: Option<u64>,
+    num_base_lots: u64,
+    num_quote_lots: u64,
+    min_base_lots_to_fill: u64,
+    min_quote_lots_to_fill: u64,
+    self_trade_behavior: SelfTradeBehavior,
+    match_limit: Option<u64>,
+    client_order_id: u128,
+    use_only_deposited_funds: bool,
+    last_valid_slot: Option<u64>,
+    last_valid_unix_timestamp_in_seconds: Option<u64>,
+}
+
+/// Implementation for Phoenix swaps
+///
+/// Orders are sized in the market's lots, so a swap is always built for one market's
+/// indexed header.
+pub struct PhoenixSwap {
+    base_lot_size: u64,
+    quote_lot_size: u64,
+    tick_size_in_quote_lots_per_base_unit: u64,
+}
+
+impl PhoenixSwap {
+    /// Create a PhoenixSwap for a market with the given lot sizes (in token atoms) and
+    /// tick size (in quote lots per base unit)
+    pub fn with_lot_sizes(base_lot_size: u64, quote_lot_size: u64, tick_size_in_quote_lots_per_base_unit: u64) -> Self {
+        Self { base_lot_size, quote_lot_size, tick_size_in_quote_lots_per_base_unit }
+    }
+
+    /// Create a PhoenixSwap for the market whose indexed book is `order_book`
+    pub fn for_market(order_book: &OrderBookState) -> Self {
+        Self::with_lot_sizes(
+            order_book.base_lot_size,
+            order_book.quote_lot_size,
+            order_book.tick_size_in_quote_lots_per_base_unit,
+        )
+    }
+
+    /// Get the Phoenix program ID
+    pub fn program_id() -> Pubkey {
+        // Mainnet Phoenix v1 program ID
+        "PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY".parse().unwrap()
+    }
+
+    /// Derive the log authority PDA the program emits events through
+    fn find_log_authority(&self) -> Pubkey {
+        Pubkey::find_program_address(&[b"log".as_ref()], &Self::program_id()).0
+    }
+
+    /// Build the IOC order for a swap of `amount` with a minimum output of `min_amount_out`
+    ///
+    /// The input rounds down to whole lots, so leftover atoms stay with the trader, and
+    /// the minimum fill rounds up so the trader never accepts less than `min_amount_out`.
+    fn immediate_or_cancel_order(&self, amount: u64, min_amount_out: u64, is_token_a_to_b: bool) -> Result<ImmediateOrCancelOrder> {
+        // A market without lots or ticks was never initialized, and can't be traded
+        if self.base_lot_size == 0 || self.quote_lot_size == 0 || self.tick_size_in_quote_lots_per_base_unit == 0 {
+            return Err(anyhow!("Invalid lot or tick sizes for Phoenix market"));
+        }
+
+        let (side, num_base_lots, num_quote_lots, min_base_lots_to_fill, min_quote_lots_to_fill) = if is_token_a_to_b {
+            // Sell base: spend base lots, receive at least the minimum in quote lots
+            (Side::Ask, amount / self.base_lot_size, 0, 0, min_amount_out.div_ceil(self.quote_lot_size))
+        } else {
+            // Buy base: spend quote lots, receive at least the minimum in base lots
+            (Side::Bid, 0, amount / self.quote_lot_size, min_amount_out.div_ceil(self.base_lot_size), 0)
+        };
+
+        if num_base_lots == 0 && num_quote_lots == 0 {
+            return Err(anyhow!("Swap amount {} is less than one lot", amount));
+        }
+
+        Ok(ImmediateOrCancelOrder {
+            side,
+            price_in_ticks: None, // No limit price: the minimum fill bounds the slippage
+            num_base_lots,
+            num_quote_lots,
+            min_base_lots_to_fill,
+            min_quote_lots_to_fill,
+            self_trade_behavior: SelfTradeBehavior::CancelProvide,
+            match_limit: None,
+            client_order_id: 0,
+            use_only_deposited_funds: false,
+            last_valid_slot: None,
+            last_valid_unix_timestamp_in_seconds: None,
+        })
+    }
+}
+
+impl DexSwap for PhoenixSwap {
+    fn create_swap_instruction(
+        &self,
+        pool_address: &Pubkey,
+        token_authority: &Pubkey,
+        token_a_address: &Pubkey,
+        _token_a_mint: &Pubkey,
+        token_a_vault: &Pubkey,
+        token_a_program: &Pubkey,
+        token_b_address: &Pubkey,
+        _token_b_mint: &Pubkey,
+        token_b_vault: &Pubkey,
+        token_b_program: &Pubkey,
+        amount: u64,
+        amount_threshold: u64,
+        is_token_a_to_b: bool,
+        is_exact_input: bool
+    ) -> Result<Instruction> {
+        // An IOC order can cap what it spends or guarantee what it receives, not both
+        if !is_exact_input {
+            return Err(anyhow!("Phoenix swaps only support an exact input amount"));
+        }
+
+        // Phoenix markets only hold legacy SPL Token vaults
+        if is_token_2022(token_a_program) || is_token_2022(token_b_program) {
+            return Err(anyhow!("Phoenix swaps don't support Token-2022 mints"));
+        }
+
+        let order = self.immediate_or_cancel_order(amount, amount_threshold, is_token_a_to_b)?;
+
+        let mut data = vec![SWAP_INSTRUCTION_TAG, IMMEDIATE_OR_CANCEL_TAG];
+        order
+            .serialize(&mut data)
+            .map_err(|e| anyhow!("Failed to serialize swap instruction data: {}", e))?;
+
+        let program_id = Self::program_id();
+        let accounts = vec![
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new_readonly(self.find_log_authority(), false),
+            AccountMeta::new(*pool_address, false),
+            // Trader (signer)
+            AccountMeta::new_readonly(*token_authority, true),
+            AccountMeta::new(*token_a_address, false),
+            AccountMeta::new(*token_b_address, false),
+            AccountMeta::new(*token_a_vault, false),
+            AccountMeta::new(*token_b_vault, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ];
+
+        Ok(Instruction {
+            program_id,
+            accounts,
+            data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sell_order_rounds_to_lots() {
+        let swap = PhoenixSwap::with_lot_sizes(1_000, 10, 1);
+
+        // 25_999 base atoms is 25 whole lots; 2_482 quote atoms needs at least 249 quote lots
+        let order = swap.immediate_or_cancel_order(25_999, 2_482, true).unwrap();
+        assert_eq!(order.side, Side::Ask);
+        assert_eq!(order.num_base_lots, 25);
+        assert_eq!(order.num_quote_lots, 0);
+        assert_eq!(order.min_quote_lots_to_fill, 249);
+
+        let order = swap.immediate_or_cancel_order(2_543, 25_000, false).unwrap();
+        assert_eq!(order.side, Side::Bid);
+        assert_eq!(order.num_quote_lots, 254);
+        assert_eq!(order.min_base_lots_to_fill, 25);
+
+        assert!(swap.immediate_or_cancel_order(999, 0, true).is_err());
+        assert!(PhoenixSwap::with_lot_sizes(1_000, 10, 0).immediate_or_cancel_order(25_999, 0, true).is_err());
+    }
+
+    #[test]
+    fn test_swap_instruction_layout() {
+        let swap = PhoenixSwap::with_lot_sizes(1_000, 10, 1);
+        let market = Pubkey::new_unique();
+        let trader = Pubkey::new_unique();
+
+        let instruction = swap
+            .create_swap_instruction(
+                &market, &trader,
+                &Pubkey::new_unique(), &Pubkey::new_unique(), &Pubkey::new_unique(), &spl_token::id(),
+                &Pubkey::new_unique(), &Pubkey::new_unique(), &Pubkey::new_unique(), &spl_token::id(),
+                25_000, 2_480, true, true,
+            )
+            .unwrap();
+
+        assert_eq!(instruction.program_id, PhoenixSwap::program_id());
+        assert_eq!(instruction.accounts.len(), 9);
+        assert_eq!(instruction.accounts[2].pubkey, market);
+        assert!(instruction.accounts[3].is_signer);
+        // Swap tag, IOC tag, then Side::Ask
+        assert_eq!(&instruction.data[..3], &[0, 2, 1]);
+
+        assert!(swap
+            .create_swap_instruction(
+                &market, &trader,
+                &Pubkey::new_unique(), &Pubkey::new_unique(), &Pubkey::new_unique(), &spl_token::id(),
+                &Pubkey::new_unique(), &Pubkey::new_unique(), &Pubkey::new_unique(), &spl_token::id(),
+                25_000, 30_000, true, false,
+            )
+            .is_err());
+    }
+}
@@ -2,8 +2,8 @@
 
 use solana_sdk::instruction::Instruction;
 use solana_sdk::pubkey::Pubkey;
-use anyhow::Result;
-use super::DexSwap;
+use anyhow::{Result, anyhow};
+use super::{is_token_2022, DexSwap};
 
 /// Implementation for Raydium swaps
 pub struct RaydiumSwap;
@@ -29,14 +29,21 @@ impl DexSwap for RaydiumSwap {
         token_a_address: &Pubkey,
         token_a_mint: &Pubkey,
         token_a_vault: &Pubkey,
+        token_a_program: &Pubkey,
         token_b_address: &Pubkey,
         token_b_mint: &Pubkey,
         token_b_vault: &Pubkey,
+        token_b_program: &Pubkey,
         amount: u64,
         amount_threshold: u64,
         is_token_a_to_b: bool,
         is_exact_input: bool
     ) -> Result<Instruction> {
+        // AMM v4 pools only hold legacy SPL Token vaults
+        if is_token_2022(token_a_program) || is_token_2022(token_b_program) {
+            return Err(anyhow!("Raydium AMM v4 swaps don't support Token-2022 mints"));
+        }
+
         // This is a placeholder. The actual implementation would create a Raydium swap instruction
         // For now, return a placeholder instruction
         Ok(Instruction {
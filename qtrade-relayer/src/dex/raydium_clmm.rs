@@ -1,9 +1,15 @@
-// Raydium CLMM (Concentrated Liquidity Market Maker) DEX implementation (placeholder)
+// Raydium CLMM (Concentrated Liquidity Market Maker) DEX implementation
 
-use solana_sdk::instruction::Instruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
 use solana_sdk::pubkey::Pubkey;
-use anyhow::Result;
-use super::DexSwap;
+use anyhow::{Result, anyhow};
+use super::{is_token_2022, DexSwap};
+
+/// Anchor discriminator of the `swap` instruction (legacy SPL Token only)
+const SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+/// Anchor discriminator of the `swap_v2` instruction (legacy SPL Token and Token-2022)
+const SWAP_V2_DISCRIMINATOR: [u8; 8] = [43, 4, 237, 11, 26, 201, 30, 98];
 
 /// Implementation for Raydium CLMM swaps
 pub struct RaydiumClmmSwap;
@@ -19,9 +25,35 @@ impl RaydiumClmmSwap {
         // Placeholder - replace with actual Raydium CLMM program ID
         "CLMMv7dZBQjVeJBMNCnMDfy1AcfPTzn6LwDNfWfgVzUh".parse().unwrap()
     }
+
+    /// Derive the AMM config PDA with the given index
+    /// This is a placeholder - the config should come from the indexed pool state,
+    /// for now the first config is used
+    fn find_amm_config(&self, index: u16) -> Pubkey {
+        let index_bytes = index.to_be_bytes();
+        let seeds = [b"amm_config".as_ref(), index_bytes.as_ref()];
+        Pubkey::find_program_address(&seeds, &Self::program_id()).0
+    }
+
+    /// Derive the observation PDA of a pool
+    fn find_observation(&self, pool_address: &Pubkey) -> Pubkey {
+        let seeds = [b"observation".as_ref(), pool_address.as_ref()];
+        Pubkey::find_program_address(&seeds, &Self::program_id()).0
+    }
+
+    /// Derive the PDA of the tick array starting at `start_index`
+    /// This is a placeholder - the start index should follow the pool's current tick,
+    /// for now the array at tick 0 is used
+    fn find_tick_array(&self, pool_address: &Pubkey, start_index: i32) -> Pubkey {
+        let index_bytes = start_index.to_be_bytes();
+        let seeds = [b"tick_array".as_ref(), pool_address.as_ref(), index_bytes.as_ref()];
+        Pubkey::find_program_address(&seeds, &Self::program_id()).0
+    }
 }
 
 impl DexSwap for RaydiumClmmSwap {
+    /// Builds `swap` when both mints are legacy SPL Token, and `swap_v2` (which takes
+    /// both token programs and the mints) when either is Token-2022
     fn create_swap_instruction(
         &self,
         pool_address: &Pubkey,
@@ -29,20 +61,112 @@ impl DexSwap for RaydiumClmmSwap {
         token_a_address: &Pubkey,
         token_a_mint: &Pubkey,
         token_a_vault: &Pubkey,
+        token_a_program: &Pubkey,
         token_b_address: &Pubkey,
         token_b_mint: &Pubkey,
         token_b_vault: &Pubkey,
+        token_b_program: &Pubkey,
         amount: u64,
         amount_threshold: u64,
         is_token_a_to_b: bool,
         is_exact_input: bool
     ) -> Result<Instruction> {
-        // This is a placeholder. The actual implementation would create a Raydium CLMM swap instruction
-        // For now, return a placeholder instruction
+        let (input_account, input_vault, input_mint, output_account, output_vault, output_mint) = if is_token_a_to_b {
+            (token_a_address, token_a_vault, token_a_mint, token_b_address, token_b_vault, token_b_mint)
+        } else {
+            (token_b_address, token_b_vault, token_b_mint, token_a_address, token_a_vault, token_a_mint)
+        };
+        let uses_token_2022 = is_token_2022(token_a_program) || is_token_2022(token_b_program);
+
+        let mut accounts = vec![
+            // Payer (signer)
+            AccountMeta::new_readonly(*token_authority, true),
+            AccountMeta::new_readonly(self.find_amm_config(0), false),
+            AccountMeta::new(*pool_address, false),
+            AccountMeta::new(*input_account, false),
+            AccountMeta::new(*output_account, false),
+            AccountMeta::new(*input_vault, false),
+            AccountMeta::new(*output_vault, false),
+            AccountMeta::new(self.find_observation(pool_address), false),
+        ];
+
+        if uses_token_2022 {
+            accounts.extend([
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(spl_token_2022::id(), false),
+                AccountMeta::new_readonly(spl_memo::id(), false),
+                AccountMeta::new_readonly(*input_mint, false),
+                AccountMeta::new_readonly(*output_mint, false),
+            ]);
+        } else {
+            accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+        }
+
+        // The tick array the swap starts in
+        accounts.push(AccountMeta::new(self.find_tick_array(pool_address, 0), false));
+
+        // Define the instruction data
+        use borsh::BorshSerialize;
+
+        #[derive(BorshSerialize)]
+        struct SwapInstructionData {
+            discriminator: [u8; 8],
+            amount: u64,
+            other_amount_threshold: u64,
+            sqrt_price_limit_x64: u128,
+            is_base_input: bool,
+        }
+
+        let data = SwapInstructionData {
+            discriminator: if uses_token_2022 { SWAP_V2_DISCRIMINATOR } else { SWAP_DISCRIMINATOR },
+            amount,
+            other_amount_threshold: amount_threshold,
+            sqrt_price_limit_x64: 0, // 0 means no price limit
+            is_base_input: is_exact_input,
+        }
+        .try_to_vec()
+        .map_err(|e| anyhow!("Failed to serialize swap instruction data: {}", e))?;
+
         Ok(Instruction {
             program_id: Self::program_id(),
-            accounts: vec![],
-            data: vec![],
+            accounts,
+            data,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(swap: &RaydiumClmmSwap, token_a_program: &Pubkey, token_b_program: &Pubkey) -> Instruction {
+        swap.create_swap_instruction(
+            &Pubkey::new_unique(), &Pubkey::new_unique(),
+            &Pubkey::new_unique(), &Pubkey::new_unique(), &Pubkey::new_unique(), token_a_program,
+            &Pubkey::new_unique(), &Pubkey::new_unique(), &Pubkey::new_unique(), token_b_program,
+            1_000, 990, true, true,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_legacy_mints_use_swap() {
+        let instruction = build(&RaydiumClmmSwap::new(), &spl_token::id(), &spl_token::id());
+
+        assert_eq!(&instruction.data[..8], &SWAP_DISCRIMINATOR);
+        assert_eq!(instruction.accounts.len(), 10);
+        assert_eq!(instruction.accounts[8].pubkey, spl_token::id());
+        assert!(!instruction.accounts.iter().any(|meta| meta.pubkey == spl_token_2022::id()));
+    }
+
+    #[test]
+    fn test_token_2022_mint_uses_swap_v2() {
+        let instruction = build(&RaydiumClmmSwap::new(), &spl_token::id(), &spl_token_2022::id());
+
+        assert_eq!(&instruction.data[..8], &SWAP_V2_DISCRIMINATOR);
+        assert_eq!(instruction.accounts.len(), 14);
+        assert_eq!(instruction.accounts[8].pubkey, spl_token::id());
+        assert_eq!(instruction.accounts[9].pubkey, spl_token_2022::id());
+        assert_eq!(instruction.accounts[10].pubkey, spl_memo::id());
+    }
+}
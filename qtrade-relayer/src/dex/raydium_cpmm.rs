@@ -29,9 +29,11 @@ impl DexSwap for RaydiumCpmmSwap {
         token_a_address: &Pubkey,
         token_a_mint: &Pubkey,
         token_a_vault: &Pubkey,
+        token_a_program: &Pubkey,
         token_b_address: &Pubkey,
         token_b_mint: &Pubkey,
         token_b_vault: &Pubkey,
+        token_b_program: &Pubkey,
         amount: u64,
         amount_threshold: u64,
         is_token_a_to_b: bool,
@@ -0,0 +1,144 @@
+//! Dedicated fee-payer account for arbitrage transactions
+//!
+//! By default the explorer key both pays transaction fees and signs the swaps. When a
+//! fee payer is registered it pays fees and provider tips instead, while the explorer
+//! key stays the swap authority. Explorer keys then only need enough SOL to exist.
+
+use once_cell::sync::OnceCell;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use tracing::{info, warn};
+
+/// Fee payer registered by the runtime, if any
+static FEE_PAYER: OnceCell<Keypair> = OnceCell::new();
+
+/// Register the keypair that pays transaction fees and tips
+///
+/// Only the first registration takes effect.
+pub fn set_fee_payer(keypair: Keypair) {
+    let pubkey = keypair.pubkey();
+    if FEE_PAYER.set(keypair).is_err() {
+        warn!("Fee payer already registered, ignoring {}", pubkey);
+    } else {
+        info!("Transaction fees and tips will be paid by {}", pubkey);
+    }
+}
+
+/// The registered fee payer, if any
+pub fn fee_payer() -> Option<&'static Keypair> {
+    FEE_PAYER.get()
+}
+
+/// Account paying fees and tips for a transaction signed by `authority`
+pub fn payer_pubkey(authority: &Keypair) -> Pubkey {
+    fee_payer().unwrap_or(authority).pubkey()
+}
+
+/// Signatures added to each transaction by the fee payer (0 or 1)
+pub fn extra_signatures() -> u64 {
+    u64::from(fee_payer().is_some())
+}
+
+/// Build and sign a transaction with the registered fee payer, if any
+///
+/// `recent_hash` is either a recent blockhash or a durable nonce value.
+pub fn sign_tx(
+    instructions: &[Instruction],
+    authority: &Keypair,
+    extra_signers: &[&Keypair],
+    recent_hash: Hash,
+) -> Transaction {
+    build_signed_tx(instructions, authority, fee_payer(), extra_signers, recent_hash)
+}
+
+/// Build and sign a transaction paid for by `fee_payer`, or by `authority` when `None`
+///
+/// The payer, the swap authority and any extra signers (e.g. a nonce authority) all
+/// sign; a key appearing more than once signs only once.
+pub fn build_signed_tx(
+    instructions: &[Instruction],
+    authority: &Keypair,
+    fee_payer: Option<&Keypair>,
+    extra_signers: &[&Keypair],
+    recent_hash: Hash,
+) -> Transaction {
+    let payer = fee_payer.unwrap_or(authority);
+
+    let mut signers: Vec<&Keypair> = Vec::with_capacity(extra_signers.len() + 2);
+    for signer in [payer, authority].into_iter().chain(extra_signers.iter().copied()) {
+        if !signers.iter().any(|s| s.pubkey() == signer.pubkey()) {
+            signers.push(signer);
+        }
+    }
+
+    Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &signers, recent_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::AccountMeta;
+    use solana_sdk::system_instruction;
+
+    /// A swap-like instruction requiring the authority's signature
+    fn swap_instruction(authority: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![AccountMeta::new(*authority, true)],
+        )
+    }
+
+    #[test]
+    fn test_fee_payer_pays_and_both_keys_sign() {
+        let authority = Keypair::new();
+        let fee_payer = Keypair::new();
+        let tip_account = Pubkey::new_unique();
+
+        let instructions = vec![
+            swap_instruction(&authority.pubkey()),
+            system_instruction::transfer(&fee_payer.pubkey(), &tip_account, 1_000),
+        ];
+
+        let tx = build_signed_tx(&instructions, &authority, Some(&fee_payer), &[], Hash::new_unique());
+
+        // The fee payer is always the first account key
+        assert_eq!(tx.message.account_keys[0], fee_payer.pubkey());
+        assert_eq!(tx.message.header.num_required_signatures, 2);
+        assert!(tx.message.account_keys[..2].contains(&authority.pubkey()));
+        assert!(tx.is_signed());
+        assert!(tx.verify().is_ok());
+    }
+
+    #[test]
+    fn test_authority_pays_without_fee_payer() {
+        let authority = Keypair::new();
+        let nonce_authority = Keypair::new();
+        let instructions = vec![
+            system_instruction::advance_nonce_account(&Pubkey::new_unique(), &nonce_authority.pubkey()),
+            swap_instruction(&authority.pubkey()),
+        ];
+
+        let tx = build_signed_tx(&instructions, &authority, None, &[&nonce_authority], Hash::new_unique());
+
+        assert_eq!(tx.message.account_keys[0], authority.pubkey());
+        assert_eq!(tx.message.header.num_required_signatures, 2);
+        assert!(tx.verify().is_ok());
+    }
+
+    #[test]
+    fn test_duplicate_signers_sign_once() {
+        let authority = Keypair::new();
+        let instructions = vec![swap_instruction(&authority.pubkey())];
+
+        // Registering the explorer key as its own fee payer degrades to a single signer
+        let tx = build_signed_tx(&instructions, &authority, Some(&authority), &[&authority], Hash::new_unique());
+
+        assert_eq!(tx.message.account_keys[0], authority.pubkey());
+        assert_eq!(tx.signatures.len(), 1);
+        assert!(tx.verify().is_ok());
+    }
+}
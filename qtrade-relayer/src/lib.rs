@@ -9,16 +9,21 @@
 //! a simple interface for building and landing transactions on the Solana blockchain.
 
 use anyhow::Result;
-use opentelemetry::global;
-use opentelemetry::trace::Tracer;
+use futures::FutureExt;
+use opentelemetry::{global, KeyValue};
+use opentelemetry::trace::{TraceContextExt, Tracer};
 use qtrade_shared_types::ArbitrageResult;
 use std::collections::VecDeque;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Mutex;
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::time::sleep;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, warn, Instrument};
 use solana_sdk::pubkey::Pubkey;
+use uuid::Uuid;
 
 // Arbitrage modules
 pub mod arbitrage;
@@ -30,24 +35,37 @@ pub mod settings;
 pub mod dex;
 
 // For help in naming spans
+use crate::arbitrage::outcome::{ExecutionOutcome, ExecutionReport, SkipReason};
 use crate::constants::QTRADE_RELAYER_TRACER_NAME;
 use crate::metrics::arbitrage::{
+    record_arbitrage_queue_depth,
+    record_arbitrage_result_dropped,
     record_arbitrage_result_received,
 };
 
 pub mod blockhash;
 pub mod constants;
+pub mod fee_payer;
 pub mod metrics;
 pub mod nonce;
+pub mod oracle;
 pub mod rpc;
 pub mod utils;
 
 const RELAYER: &str = "relayer";
 const CHECK_INTERVAL: Duration = Duration::from_secs(60);
-const MAX_QUEUE_SIZE: usize = 100;
 
-// Global receiver for arbitrage results from router
-pub static ARBITRAGE_RECEIVER: Mutex<Option<mpsc::Receiver<ArbitrageResult>>> = Mutex::new(None);
+/// Default capacity of the arbitrage queue
+pub const DEFAULT_MAX_QUEUE_SIZE: usize = 100;
+
+// Capacity of the arbitrage queue, set from the relayer settings in run_relayer
+static MAX_QUEUE_SIZE: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_QUEUE_SIZE);
+
+// Receiver for arbitrage results from the router, waiting to be taken by the relayer loop
+//
+// This is only a handoff slot: the loop takes the receiver out and owns it from then on,
+// so the lock is held just long enough to move it and never across an await.
+static ARBITRAGE_RECEIVER: Mutex<Option<mpsc::Receiver<ArbitrageResult>>> = Mutex::new(None);
 
 // FIFO queue for storing arbitrage results
 pub static ARBITRAGE_QUEUE: Mutex<VecDeque<ArbitrageResult>> = Mutex::new(VecDeque::new());
@@ -57,26 +75,98 @@ static mut RELAYER_SETTINGS: Option<settings::RelayerSettings> = None;
 
 /// Initialize the arbitrage receiver
 /// This is called from the router module when it creates the channel
+///
+/// A receiver handed over later replaces the one the relayer loop holds.
 pub fn init_arbitrage_receiver(rx: mpsc::Receiver<ArbitrageResult>) {
     let mut receiver = ARBITRAGE_RECEIVER.lock().unwrap();
     *receiver = Some(rx);
 }
 
+/// Take the receiver handed over by the router, if one is waiting
+pub fn take_arbitrage_receiver() -> Option<mpsc::Receiver<ArbitrageResult>> {
+    ARBITRAGE_RECEIVER.lock().unwrap().take()
+}
+
+/// Set the capacity of the arbitrage queue (at least 1)
+pub fn set_max_queue_size(max_queue_size: usize) {
+    MAX_QUEUE_SIZE.store(max_queue_size.max(1), Ordering::Relaxed);
+}
+
+/// Get the capacity of the arbitrage queue
+pub fn max_queue_size() -> usize {
+    MAX_QUEUE_SIZE.load(Ordering::Relaxed)
+}
+
+/// Gross profitability of an arbitrage result used to rank queued results
+///
+/// Values received (lambda) minus tendered (delta) amounts across all pools at the
+/// router's market values. Landing costs are the same for every result, so they're
+/// left out of the ranking.
+pub fn estimated_profit(result: &ArbitrageResult) -> f64 {
+    crate::arbitrage::profit::gross_profit(result)
+}
+
+/// Push `result` onto `queue`, dropping the least profitable result if it is at `capacity`
+///
+/// Returns the dropped result, which may be `result` itself if everything already
+/// queued is more profitable.
+fn enqueue_bounded(
+    queue: &mut VecDeque<ArbitrageResult>,
+    result: ArbitrageResult,
+    capacity: usize,
+) -> Option<ArbitrageResult> {
+    let dropped = if queue.len() >= capacity {
+        // Newest isn't necessarily best, so drop whichever result is least profitable
+        let least_profitable = queue
+            .iter()
+            .map(estimated_profit)
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        let dropped = match least_profitable {
+            Some((index, profit)) if profit < estimated_profit(&result) => {
+                let dropped = queue.remove(index);
+                queue.push_back(result);
+                dropped
+            }
+            _ => Some(result),
+        };
+
+        record_arbitrage_result_dropped();
+        warn!("Arbitrage queue reached maximum capacity of {}, dropped least profitable result", capacity);
+
+        dropped
+    } else {
+        queue.push_back(result);
+        None
+    };
+
+    record_arbitrage_queue_depth(queue.len());
+    qtrade_shared_types::HEALTH_STATUS.set_queue_depth(queue.len());
+
+    dropped
+}
+
 /// Add an arbitrage result to the FIFO queue
-pub fn enqueue_arbitrage_result(result: ArbitrageResult) -> Result<()> {
+///
+/// Returns the result's correlation id, assigned here if the router didn't set one.
+pub fn enqueue_arbitrage_result(mut result: ArbitrageResult) -> Result<Uuid> {
     let mut queue = ARBITRAGE_QUEUE.lock().map_err(|e| anyhow::anyhow!("Failed to lock arbitrage queue: {:?}", e))?;
+    let correlation_id = result.assign_correlation_id();
+    result.mark_enqueued();
 
-    // If queue is at max capacity, remove the oldest result
-    if queue.len() >= MAX_QUEUE_SIZE {
-        queue.pop_front();
-        warn!("Arbitrage queue reached maximum capacity, dropped oldest result");
+    // If queue is at max capacity, the least profitable result is dropped
+    if let Some(dropped_id) = enqueue_bounded(&mut queue, result, max_queue_size()).and_then(|dropped| dropped.correlation_id) {
+        info!("Dropped arbitrage result {} from the full queue", dropped_id);
     }
+    debug!("Added arbitrage result {} to queue, current queue size: {}", correlation_id, queue.len());
 
-    // Add the new result to the queue
-    queue.push_back(result);
-    debug!("Added arbitrage result to queue, current queue size: {}", queue.len());
+    Ok(correlation_id)
+}
 
-    Ok(())
+/// Number of arbitrage results waiting in the queue
+pub fn arbitrage_queue_len() -> usize {
+    ARBITRAGE_QUEUE.lock().map(|queue| queue.len()).unwrap_or(0)
 }
 
 /// Get the next arbitrage result from the FIFO queue
@@ -91,6 +181,8 @@ pub fn dequeue_arbitrage_result() -> Option<ArbitrageResult> {
 
     // Remove and return the oldest result from the queue
     let result = queue.pop_front();
+    record_arbitrage_queue_depth(queue.len());
+    qtrade_shared_types::HEALTH_STATUS.set_queue_depth(queue.len());
     if result.is_some() {
         debug!("Removed arbitrage result from queue, current queue size: {}", queue.len());
     }
@@ -109,89 +201,296 @@ pub fn determine_pool_pubkey(pool_index: usize, _arbitrage_result: &ArbitrageRes
     Pubkey::new_from_array(hash.to_bytes()[0..32].try_into().unwrap())
 }
 
+/// Tokens a pool's deltas spend and receive, by local token index
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenIndices {
+    /// Tokens being spent (positive delta), in index order
+    pub spent: Vec<usize>,
+    /// Tokens being received (negative delta), in index order
+    pub received: Vec<usize>,
+}
+
+impl TokenIndices {
+    /// The (token_a_index, token_b_index) pair of a swap spending one token for another
+    ///
+    /// `None` unless exactly one token is spent and exactly one received, since a swap
+    /// instruction can't represent anything else.
+    pub fn two_sided(&self) -> Option<(usize, usize)> {
+        match (self.spent.as_slice(), self.received.as_slice()) {
+            ([spent], [received]) => Some((*spent, *received)),
+            _ => None,
+        }
+    }
+}
+
 /// Determine which tokens are being swapped based on the delta values.
-/// Returns a tuple of (token_a_index, token_b_index) where:
-/// - token_a_index is the index of the token being spent (positive delta)
-/// - token_b_index is the index of the token being received (negative delta)
-pub fn determine_token_indices(deltas: &[f64]) -> (Option<usize>, Option<usize>) {
-    let mut token_a_index = None; // Token we're spending (positive delta)
-    let mut token_b_index = None; // Token we're receiving (negative delta)
+///
+/// Every token with a positive delta is spent and every token with a negative delta is
+/// received; deltas within 1e-6 of zero are left out.
+pub fn determine_token_indices(deltas: &[f64]) -> TokenIndices {
+    let mut indices = TokenIndices::default();
 
     for (i, delta) in deltas.iter().enumerate() {
         if *delta > 1e-6 {
-            // Positive delta means we're spending this token
-            token_a_index = Some(i);
+            indices.spent.push(i);
         } else if *delta < -1e-6 {
-            // Negative delta means we're receiving this token
-            token_b_index = Some(i);
+            indices.received.push(i);
         }
     }
 
-    (token_a_index, token_b_index)
+    indices
 }
 
 /// Executes an arbitrage opportunity by constructing and submitting a transaction
-async fn execute_arbitrage(arbitrage_result: &ArbitrageResult) -> Result<()> {
-    // Get the global relayer settings
-    let settings = get_relayer_settings();
+async fn execute_arbitrage(arbitrage_result: &ArbitrageResult) -> Result<ExecutionReport> {
+    execute_arbitrage_with_settings(arbitrage_result, get_relayer_settings()).await
+}
+
+/// Executes an arbitrage opportunity with the given settings, reporting how it ended
+///
+/// Logs and spans of the execution carry the result's correlation id, as does the report.
+pub(crate) async fn execute_arbitrage_with_settings(
+    arbitrage_result: &ArbitrageResult,
+    settings: &settings::RelayerSettings,
+) -> Result<ExecutionReport> {
+    // Results that never went through the queue, e.g. replayed ones, get an id of their own
+    let correlation_id = arbitrage_result.correlation_id.unwrap_or_else(Uuid::new_v4);
+
     // Start a new span for the arbitrage execution
     let tracer = global::tracer(QTRADE_RELAYER_TRACER_NAME);
     let span_name = format!("{}::execute_arbitrage", RELAYER);
 
-    tracer.in_span(span_name, |_cx| async move {
+    let outcome = tracer.in_span(span_name, |cx| async move {
+        cx.span().set_attribute(KeyValue::new("correlation_id", correlation_id.to_string()));
+
+        // Resubmits after a blockhash expiry are only worth it while the opportunity is fresh
+        let execution_started = std::time::Instant::now();
+
+        // Mock execution stays in memory, whatever the submit mode
+        #[cfg(feature = "mock")]
+        if settings.is_mock_execution() {
+            return crate::arbitrage::mock::execute_mock(arbitrage_result, settings, correlation_id).await;
+        }
+
         // Check if we're in simulation mode
-        let is_simulation = settings.simulate;
+        let submit_mode = settings.get_submit_mode();
+        let is_simulation = submit_mode == settings::SubmitMode::SimulateOnly;
         if is_simulation {
             info!("Running in SIMULATION mode - transactions will not be submitted to the network");
         } else {
-            info!("Starting execution of arbitrage opportunity");
+            info!("Starting execution of arbitrage opportunity ({})", submit_mode.as_str());
         }
 
         // 1. Validate the arbitrage result using the extracted validation function
         if !crate::arbitrage::prepare::validate_arbitrage_result(arbitrage_result)? {
             // If validation fails, we return early
-            return Ok(());
+            return Ok(ExecutionOutcome::Skipped(SkipReason::InvalidResult));
+        }
+
+        // Skip opportunities already submitted, including before a restart
+        let submission_store = crate::arbitrage::dedup::submission_store();
+        let opportunity_key = crate::arbitrage::dedup::opportunity_key(arbitrage_result);
+        if !is_simulation && submission_store.is_submitted(&opportunity_key) {
+            info!("Opportunity {} was already submitted, skipping execution", opportunity_key);
+            return Ok(ExecutionOutcome::Skipped(SkipReason::AlreadySubmitted));
         }
 
         // 2. Construct swap parameters based on the arbitrage result
         info!("Constructing transaction instructions for arbitrage execution");
 
-        let swap_params_result = crate::arbitrage::prepare::construct_swap_parameters(arbitrage_result)?;
+        let swap_params_result = crate::arbitrage::prepare::construct_swap_parameters(arbitrage_result, settings).await?;
 
         // If no profitable swap operations were found, return early
-        let (swap_params_list, _estimated_profit) = match swap_params_result {
+        let (mut swap_params_list, _estimated_profit) = match swap_params_result {
             Some((params, profit)) => (params, profit),
-            None => return Ok(()),
+            None => return Ok(ExecutionOutcome::Skipped(SkipReason::NoSwaps)),
         };
 
-        // 3. Get an explorer keypair from our tiered wallet system for transaction signing
-        let (explorer_pubkey, explorer_keypair) = crate::arbitrage::prepare::acquire_explorer_keypair()?;
+        // Skip pools that just failed, rather than failing on them again every cycle
+        let pool_cooldown = crate::arbitrage::cooldown::pool_cooldown();
+        let pools: Vec<Pubkey> = swap_params_list.iter().map(|swap_params| swap_params.pool_pubkey).collect();
+        if !is_simulation && pool_cooldown.check_pools(&pools).is_some() {
+            return Ok(ExecutionOutcome::Skipped(SkipReason::PoolCoolingDown));
+        }
+
+        // Price the opportunity net of fees and tips before spending a key on it
+        let tip_lamports = if settings.is_provider_active(rpc::RpcProvider::Jito) {
+            settings.jito_tip_lamports(settings.get_jito_min_tip_lamports())
+        } else {
+            0
+        };
+        let mut costs = crate::arbitrage::profit::TransactionCosts::estimate(tip_lamports);
+        let sol_price = crate::oracle::current_sol_price(settings).await;
+        let profit_estimate = crate::arbitrage::profit::estimate_net_profit(
+            arbitrage_result,
+            &costs,
+            sol_price,
+        )?;
+        info!("Estimated profit: gross {:.6}, costs {:.6}, net {:.6}",
+            profit_estimate.gross_profit, profit_estimate.total_costs(), profit_estimate.net_profit);
+        if !profit_estimate.is_profitable(settings.get_min_profit_usd()) {
+            info!("Net profit {:.6} is below minimum {:.6}, skipping execution",
+                profit_estimate.net_profit, settings.get_min_profit_usd());
+            return Ok(ExecutionOutcome::Skipped(SkipReason::Unprofitable {
+                net_profit: profit_estimate.net_profit,
+                min_profit: settings.get_min_profit_usd(),
+            }));
+        }
+
+        // Re-check profitability against live reserves, since the router quoted from cached reserves
+        if !is_simulation {
+            use crate::rpc::RpcActions;
+            let solana_rpc = rpc::solana::Solana::new(settings.get_solana_endpoint());
+
+            // Fee-on-transfer mints lower what each swap receives, and frozen vaults fail it outright
+            let account_source = crate::arbitrage::token_checks::RpcTokenAccountSource::new(solana_rpc.rpc_client());
+            if !crate::arbitrage::token_checks::check_token_risks(&mut swap_params_list, &account_source)? {
+                info!("Opportunity trades a risky token, skipping execution");
+                return Ok(ExecutionOutcome::Skipped(SkipReason::RiskyToken));
+            }
+
+            let reserve_source = crate::arbitrage::recheck::RpcReserveSource::new(
+                solana_rpc.rpc_client(),
+                &arbitrage_result.pool_fees,
+            );
+            let still_profitable = crate::arbitrage::recheck::recheck_profitability(
+                &swap_params_list,
+                &reserve_source,
+                &crate::arbitrage::reconcile::mint_values(arbitrage_result, &swap_params_list),
+                sol_price,
+                settings.get_min_profit_usd(),
+            );
+            if !still_profitable {
+                info!("Opportunity expired before submission, skipping execution");
+                return Ok(ExecutionOutcome::Skipped(SkipReason::Expired));
+            }
+        }
+
+        // 3. Get an explorer keypair from our tiered wallet system for transaction signing.
+        // The guard hands it back to the pool on every return from here on.
+        let explorer_key = crate::arbitrage::prepare::ExplorerKeyGuard::acquire(settings.get_key_retirement_policy())?;
+        let explorer_pubkey = *explorer_key.pubkey();
+        let explorer_keypair = explorer_key.keypair();
 
         info!("Using explorer keypair with public key: {}", explorer_pubkey);
 
-        // 4. Create the swap instructions using the explorer keypair
-        let instructions = crate::arbitrage::prepare::create_swap_instructions(&swap_params_list, &explorer_pubkey)?;
+        // 4. Create the swap instructions using the explorer keypair, either as direct DEX
+        // calls or wrapped in the executor program's swap
+        let mut instructions = if settings.is_route_through_executor() {
+            crate::arbitrage::executor::create_executor_swap_instructions(
+                &swap_params_list,
+                &explorer_pubkey,
+                settings.get_executor_referral_code(),
+            )?
+        } else {
+            crate::arbitrage::prepare::create_swap_instructions(&swap_params_list, &explorer_pubkey)?
+        };
+        crate::arbitrage::audit::audit_swaps(correlation_id, &swap_params_list);
+
+        // Large arbitrages may not fit in one transaction, by size or by compute units
+        let parts = crate::arbitrage::split::split_instructions(
+            &instructions,
+            &crate::fee_payer::payer_pubkey(explorer_keypair),
+            &crate::arbitrage::split::TransactionBudget::default(),
+        )?;
+        let is_split = parts.len() > 1;
+        if is_split {
+            info!("Arbitrage needs {} transactions, submitting them in sequence", parts.len());
+        }
+
+        // Simulate before submitting, to size the compute budget and, in simulate-then-submit
+        // mode, to only submit transactions that simulate cleanly. Later parts of a split
+        // arbitrage depend on earlier ones landing, so they can't be simulated up front.
+        let simulation_required = submit_mode == settings::SubmitMode::SimulateThenSubmit;
+        if is_split && (simulation_required || settings.is_simulate_compute_units()) {
+            warn!("Submitting split arbitrage without a pre-flight simulation");
+        }
+        if !is_split && (simulation_required || (!is_simulation && settings.is_simulate_compute_units())) {
+            let solana_rpc = rpc::solana::Solana::new(settings.get_solana_endpoint());
+            match crate::arbitrage::submit::simulate_preflight(&solana_rpc, &instructions, explorer_keypair) {
+                Some(details) => {
+                    if let Some(units_consumed) = details.units_consumed {
+                        let budget = crate::arbitrage::compute::ComputeBudget::from_simulation(
+                            units_consumed,
+                            settings.get_compute_unit_margin(),
+                            crate::arbitrage::compute::value_to_lamports(
+                                profit_estimate.gross_profit,
+                                sol_price,
+                            ),
+                            settings.get_max_priority_fee_profit_fraction(),
+                        );
+                        info!("Compute budget from simulation: {} units at {} micro-lamports per unit ({} lamports)",
+                            budget.unit_limit, budget.unit_price_micro_lamports, budget.priority_fee_lamports());
+                        instructions = budget.apply(&instructions);
+                        costs.priority_fee_lamports = budget.priority_fee_lamports();
+                    }
+                },
+                None if simulation_required => {
+                    info!("Skipping submission after failed pre-flight simulation");
+                    crate::metrics::arbitrage::record_arbitrage_simulation_rejected();
+                    return Ok(ExecutionOutcome::Skipped(SkipReason::SimulationRejected));
+                },
+                None => warn!("Submitting without a simulated compute budget"),
+            }
+        }
+
+        // Fees are final once the compute budget is sized; don't let them eat the profit
+        let fee_estimate = profit_estimate.with_costs(&costs, sol_price);
+        if let Some(reason) = crate::arbitrage::profit::check_fee_cap(&fee_estimate, settings.get_max_fee_fraction_of_profit()) {
+            info!("Skipping submission of an opportunity whose fees exceed the cap");
+            return Ok(ExecutionOutcome::Skipped(reason));
+        }
+
+        // Record the submission before sending, so a crash mid-submission can't resubmit it
+        let submitted_at = std::time::SystemTime::now();
+        if !is_simulation {
+            if let Err(e) = submission_store.record_submission(&opportunity_key, Vec::new()) {
+                error!("Failed to persist submission record {}: {:?}", opportunity_key, e);
+            }
+        }
+
+        if is_split {
+            let record = SplitRecord {
+                opportunity_key: &opportunity_key,
+                correlation_id,
+                net_profit: profit_estimate.net_profit,
+                sol_price,
+                mint_values: crate::arbitrage::reconcile::mint_values(arbitrage_result, &swap_params_list),
+                legs: taxable_legs(&swap_params_list),
+            };
+            let outcome = finish_split_execution(&parts, explorer_key, settings, is_simulation, record).await?;
+            pool_cooldown.record_outcome(&pools, &outcome);
+            crate::arbitrage::latency::record_outcome(arbitrage_result, submitted_at, &outcome);
+            return Ok(outcome);
+        }
 
         // 5. Submit the transaction to multiple RPC providers
         info!("Submitting transaction to multiple RPC providers");
-        let rpc_results = crate::arbitrage::submit::submit_transaction(
-            &instructions,
-            &explorer_keypair,
-            settings,
-            is_simulation
+        let providers = crate::arbitrage::submit::SubmissionProviders::with_settings(settings);
+        // An expired blockhash is retried once it's refreshed, while the opportunity is fresh
+        let rpc_results = crate::arbitrage::resubmit::submit_with_blockhash_retry(
+            &crate::arbitrage::resubmit::ResubmitPolicy::from_settings(settings),
+            execution_started,
+            || crate::blockhash::BlockhashCache::instance().invalidate(),
+            || crate::arbitrage::submit::submit_transaction(
+                &instructions,
+                explorer_keypair,
+                &providers,
+                settings,
+                is_simulation
+            ),
         ).await?;
+        // Bundle ids sent to Jito are taken now, so none outlive this submission
+        let bundles = crate::arbitrage::bundle::take_submitted_bundles(&rpc_results);
 
         // 6. Analyze results and record metrics
         info!("Analyzing transaction submission results");
 
         // Check if we're in simulation mode
         if is_simulation {
-            // We still want to retire the keypair to prevent reuse
-            info!("Retiring explorer keypair after simulation: {}", explorer_pubkey);
-            if let Err(e) = crate::arbitrage::prepare::return_explorer_keypair_to_pool(&explorer_pubkey, true) {
-                error!("Failed to retire explorer key {}: {:?}", explorer_pubkey, e);
-            }
-            return Ok(());
+            // Nothing landed in simulation; the retirement policy decides whether the key is reused
+            explorer_key.release(false);
+            return Ok(ExecutionOutcome::from_submission(rpc_results, true));
         }
 
         // Log detailed results for monitoring and debugging
@@ -205,25 +504,317 @@ async fn execute_arbitrage(arbitrage_result: &ArbitrageResult) -> Result<()> {
             }
         }
 
+        // Providers by the signature they returned, to attribute the one that confirms
+        let providers_by_signature: std::collections::HashMap<_, _> = rpc_results.iter()
+            .filter_map(|result| crate::arbitrage::submit::submission_signature(result).map(|signature| (signature, result.0.clone())))
+            .collect();
+
+        let mut outcome = ExecutionOutcome::from_submission(rpc_results, false);
         if successful_submissions == 0 {
             error!("Transaction submission failed on all RPC providers");
             crate::metrics::arbitrage::record_failed_arbitrage_transaction();
-        } else {
+            // Nothing landed, so the opportunity may be retried
+            if let Err(e) = submission_store.remove(&opportunity_key) {
+                error!("Failed to clear submission record {}: {:?}", opportunity_key, e);
+            }
+        } else if let ExecutionOutcome::Submitted { signatures, .. } = &outcome {
             info!("Transaction successfully submitted to {} RPC providers", successful_submissions);
-            // Record successful submission metrics would go here
-        }
+            let signatures = signatures.clone();
+            let signature_strings = signatures.iter().map(|signature| signature.to_string()).collect();
+            if let Err(e) = submission_store.record_submission(&opportunity_key, signature_strings) {
+                error!("Failed to record submission signatures for {}: {:?}", opportunity_key, e);
+            }
 
-        // Mark the Explorer key as used so it will be retired
-        info!("Retiring explorer keypair after transaction use: {}", explorer_pubkey);
-        // We retire the key no matter what happened - success or failure
-        // This ensures keys aren't reused even if transactions failed to submit
-        if let Err(e) = crate::arbitrage::prepare::return_explorer_keypair_to_pool(&explorer_pubkey, true) {
-            error!("Failed to retire explorer key {}: {:?}", explorer_pubkey, e);
+            // 7. Wait for the transaction to reach the configured commitment
+            if !signatures.is_empty() {
+                use crate::rpc::RpcActions;
+                use crate::arbitrage::confirm::{ConfirmationConfig, ConfirmationOutcome, RpcSignatureStatusSource};
+                let solana_rpc = rpc::solana::Solana::new(settings.get_solana_endpoint());
+                let status_source = RpcSignatureStatusSource::new(solana_rpc.rpc_client());
+                let confirmation_config = ConfirmationConfig::from_settings(settings);
+                let signature_confirmation = crate::arbitrage::confirm::confirm_signatures(settings, &status_source, &signatures, &confirmation_config);
+                // Bundles sent to Jito are also tracked by their bundle status
+                let confirmation = crate::arbitrage::bundle::confirm_submission(
+                    settings,
+                    signature_confirmation,
+                    &bundles,
+                    &confirmation_config,
+                ).await;
+                match &confirmation {
+                    ConfirmationOutcome::Confirmed(signature) => {
+                        crate::metrics::arbitrage::record_arbitrage_transaction_confirmed(profit_estimate.net_profit);
+                        // Check the estimate against the balances the transaction actually changed
+                        let realized_profit = crate::arbitrage::reconcile::reconcile_profit(
+                            solana_rpc.rpc_client(),
+                            std::slice::from_ref(signature),
+                            &explorer_pubkey,
+                            confirmation_config.commitment,
+                            &crate::arbitrage::reconcile::mint_values(arbitrage_result, &swap_params_list),
+                            sol_price,
+                            profit_estimate.net_profit,
+                        );
+                        let provider = providers_by_signature.get(signature).map(String::as_str).unwrap_or("unknown");
+                        crate::metrics::database::record_transaction_taxable_event(crate::metrics::database::TaxableEvent::new(
+                            provider,
+                            &signature.to_string(),
+                            profit_estimate.net_profit,
+                            correlation_id,
+                            taxable_legs(&swap_params_list),
+                        ).with_realized_profit(realized_profit));
+                        if let Err(e) = submission_store.remove(&opportunity_key) {
+                            error!("Failed to clear submission record {}: {:?}", opportunity_key, e);
+                        }
+                    },
+                    ConfirmationOutcome::Failed(_, _) => crate::metrics::arbitrage::record_arbitrage_transaction_failed(),
+                    ConfirmationOutcome::TimedOut => crate::metrics::arbitrage::record_arbitrage_transaction_timeout(),
+                }
+                outcome = outcome.with_confirmation(confirmation);
+            }
         }
 
+        // Release the Explorer key, passing the outcome so the retirement policy can decide
+        // whether it is retired or returned to the pool. Only a confirmed transaction landed
+        let landed = outcome.is_confirmed();
+        explorer_key.release(landed);
+
+        pool_cooldown.record_outcome(&pools, &outcome);
+        crate::arbitrage::latency::record_outcome(arbitrage_result, submitted_at, &outcome);
+
         info!("Arbitrage execution complete");
-        Ok(())
-    }).await
+        Ok(outcome)
+    }).instrument(tracing::info_span!("arbitrage", correlation_id = %correlation_id)).await?;
+
+    Ok(ExecutionReport { correlation_id, outcome })
+}
+
+/// What a split arbitrage is recorded with once submitted
+struct SplitRecord<'a> {
+    opportunity_key: &'a str,
+    correlation_id: Uuid,
+    net_profit: f64,
+    sol_price: f64,
+    /// Market values the parts' balance changes are priced at
+    mint_values: std::collections::HashMap<Pubkey, f64>,
+    legs: Vec<crate::metrics::database::TaxableSwapLeg>,
+}
+
+/// Submit a split arbitrage in sequence, then record and release as for a single transaction
+///
+/// The opportunity only counts as confirmed once every part has confirmed, and is then
+/// recorded as one taxable event under the signature of its last part. `explorer_key`
+/// is released however submission ends.
+async fn finish_split_execution(
+    parts: &[Vec<solana_sdk::instruction::Instruction>],
+    explorer_key: crate::arbitrage::prepare::ExplorerKeyGuard,
+    settings: &settings::RelayerSettings,
+    is_simulation: bool,
+    record: SplitRecord<'_>,
+) -> Result<ExecutionOutcome> {
+    use crate::arbitrage::confirm::{ConfirmationConfig, ConfirmationOutcome};
+    use crate::rpc::RpcActions;
+
+    let SplitRecord { opportunity_key, correlation_id, net_profit, sol_price, mint_values, legs } = record;
+    let providers = crate::arbitrage::submit::SubmissionProviders::with_settings(settings);
+    let split = crate::arbitrage::split::submit_in_sequence(parts, explorer_key.keypair(), &providers, settings, is_simulation).await?;
+    let outcome = split.outcome;
+
+    if !is_simulation {
+        let submission_store = crate::arbitrage::dedup::submission_store();
+        match &outcome {
+            ExecutionOutcome::Submitted { signatures, confirmation } => {
+                let signature_strings = signatures.iter().map(|signature| signature.to_string()).collect();
+                if let Err(e) = submission_store.record_submission(opportunity_key, signature_strings) {
+                    error!("Failed to record submission signatures for {}: {:?}", opportunity_key, e);
+                }
+
+                match confirmation {
+                    Some(ConfirmationOutcome::Confirmed(_)) => {
+                        crate::metrics::arbitrage::record_arbitrage_transaction_confirmed(net_profit);
+                        // Every part's balance changes together make up the realized profit
+                        let solana_rpc = rpc::solana::Solana::new(settings.get_solana_endpoint());
+                        let part_signatures: Vec<_> = split.confirmed_parts.iter().map(|part| part.signature).collect();
+                        let realized_profit = crate::arbitrage::reconcile::reconcile_profit(
+                            solana_rpc.rpc_client(),
+                            &part_signatures,
+                            explorer_key.pubkey(),
+                            ConfirmationConfig::from_settings(settings).commitment,
+                            &mint_values,
+                            sol_price,
+                            net_profit,
+                        );
+                        if let Some(last_part) = split.confirmed_parts.last() {
+                            crate::metrics::database::record_transaction_taxable_event(crate::metrics::database::TaxableEvent::new(
+                                &last_part.provider,
+                                &last_part.signature.to_string(),
+                                net_profit,
+                                correlation_id,
+                                legs,
+                            ).with_realized_profit(realized_profit));
+                        }
+                        if let Err(e) = submission_store.remove(opportunity_key) {
+                            error!("Failed to clear submission record {}: {:?}", opportunity_key, e);
+                        }
+                    },
+                    Some(ConfirmationOutcome::Failed(_, _)) => crate::metrics::arbitrage::record_arbitrage_transaction_failed(),
+                    Some(ConfirmationOutcome::TimedOut) => crate::metrics::arbitrage::record_arbitrage_transaction_timeout(),
+                    None => {},
+                }
+            },
+            _ => {
+                error!("A transaction of the split arbitrage failed on all RPC providers");
+                crate::metrics::arbitrage::record_failed_arbitrage_transaction();
+                if let Err(e) = submission_store.remove(opportunity_key) {
+                    error!("Failed to clear submission record {}: {:?}", opportunity_key, e);
+                }
+            },
+        }
+    }
+
+    // The key's transactions landed if any part confirmed, even if a later part didn't
+    let landed = !split.confirmed_parts.is_empty();
+    explorer_key.release(landed);
+
+    info!("Arbitrage execution complete");
+    Ok(outcome)
+}
+
+/// Swap legs of an arbitrage, as recorded with its taxable event
+fn taxable_legs(swap_params_list: &[crate::arbitrage::prepare::ArbitrageSwapParams]) -> Vec<crate::metrics::database::TaxableSwapLeg> {
+    swap_params_list.iter()
+        .map(|swap_params| crate::metrics::database::TaxableSwapLeg {
+            input_mint: swap_params.token_a_mint.to_string(),
+            output_mint: swap_params.token_b_mint.to_string(),
+            amount_in: swap_params.amount_in,
+            min_amount_out: swap_params.min_amount_out,
+        })
+        .collect()
+}
+
+/// Pass every arbitrage result waiting in `rx` to `on_result`, without blocking
+///
+/// Returns how many results were received.
+pub fn drain_arbitrage_channel(
+    rx: &mut mpsc::Receiver<ArbitrageResult>,
+    mut on_result: impl FnMut(ArbitrageResult),
+) -> usize {
+    let mut received = 0;
+    loop {
+        match rx.try_recv() {
+            Ok(arbitrage_result) => {
+                on_result(arbitrage_result);
+                received += 1;
+            },
+            Err(mpsc::error::TryRecvError::Empty) => {
+                // No more arbitrage results in the channel, break the loop
+                debug!("No more arbitrage results in the channel");
+                break;
+            },
+            Err(mpsc::error::TryRecvError::Disconnected) => {
+                // Channel is disconnected, log an error and break the loop
+                error!("Arbitrage channel disconnected");
+                break;
+            }
+        }
+    }
+
+    received
+}
+
+/// Move every arbitrage result waiting in the router channel onto the queue
+///
+/// `receiver` is owned by the relayer loop. It is replaced by any receiver the router has
+/// handed over since the last call.
+fn receive_arbitrage_results(receiver: &mut Option<mpsc::Receiver<ArbitrageResult>>) {
+    if let Some(rx) = take_arbitrage_receiver() {
+        *receiver = Some(rx);
+    }
+    let Some(rx) = receiver.as_mut() else {
+        return;
+    };
+
+    drain_arbitrage_channel(rx, |arbitrage_result| {
+        info!("Received arbitrage result with status: {}", arbitrage_result.status);
+
+        // Record metrics for received arbitrage result
+        record_arbitrage_result_received();
+        crate::arbitrage::replay::record_result(&arbitrage_result);
+
+        // Add the result to our FIFO queue
+        if let Err(e) = enqueue_arbitrage_result(arbitrage_result) {
+            error!("Failed to enqueue arbitrage result: {:?}", e);
+        }
+    });
+}
+
+/// Drain the queue before shutting down, if configured, then flush taxable events
+async fn shut_down(
+    taxable_event_writer: &crate::metrics::database::TaxableEventWriter,
+    receiver: &mut Option<mpsc::Receiver<ArbitrageResult>>,
+) {
+    let settings = get_relayer_settings();
+    if settings.is_drain_on_shutdown() || settings.get_queue_spool_path().is_some() {
+        receive_arbitrage_results(receiver);
+
+        // Without draining, or with submissions paused, everything left is only spooled
+        let budget = if settings.is_drain_on_shutdown()
+            && crate::arbitrage::circuit_breaker::circuit_breaker().allows_submission()
+        {
+            settings.get_drain_budget()
+        } else {
+            Duration::ZERO
+        };
+        info!("Draining {} queued arbitrage results for up to {:?}", arbitrage_queue_len(), budget);
+
+        let report = crate::arbitrage::drain::drain_queue(
+            &ARBITRAGE_QUEUE,
+            budget,
+            settings.get_queue_spool_path().map(std::path::Path::new),
+            |arbitrage_result| async move {
+                match contain_panic(execute_arbitrage(&arbitrage_result)).await {
+                    Ok(report) => record_execution_outcome(&report),
+                    Err(e) => error!("Failed to execute arbitrage: {:?}", e),
+                }
+            },
+        ).await;
+        record_arbitrage_queue_depth(0);
+        qtrade_shared_types::HEALTH_STATUS.set_queue_depth(0);
+        info!("Queue drained: {:?}", report);
+    }
+
+    taxable_event_writer.shutdown().await;
+}
+
+/// Run `execution`, turning a panic into an error
+///
+/// Opportunities run inside the relayer loop's task, so an unwinding panic (e.g. an
+/// `unimplemented!()` in a DEX module) would otherwise take the whole relayer down.
+pub async fn contain_panic<T>(execution: impl Future<Output = Result<T>>) -> Result<T> {
+    match AssertUnwindSafe(execution).catch_unwind().await {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            error!("Arbitrage execution panicked: {}", message);
+            Err(anyhow::anyhow!("Arbitrage execution panicked: {}", message))
+        }
+    }
+}
+
+/// Log how an execution ended and count it by outcome
+fn record_execution_outcome(report: &ExecutionReport) {
+    let id = report.correlation_id;
+    match &report.outcome {
+        ExecutionOutcome::Skipped(reason) => info!("Arbitrage {} skipped: {}", id, reason.as_str()),
+        ExecutionOutcome::Simulated { results } => info!("Arbitrage {} simulated on {} providers", id, results.len()),
+        ExecutionOutcome::Submitted { signatures, confirmation } => {
+            info!("Arbitrage {} submitted with {} signatures (confirmation: {:?})", id, signatures.len(), confirmation)
+        },
+        ExecutionOutcome::Failed { results } => warn!("Arbitrage {} failed on all {} providers", id, results.len()),
+    }
+    crate::metrics::arbitrage::record_arbitrage_execution_outcome(report.outcome.as_str());
 }
 
 /// Get the global relayer settings instance
@@ -234,6 +825,105 @@ pub fn get_relayer_settings() -> &'static settings::RelayerSettings {
     }
 }
 
+/// Start keeping the blockhash cache and the nonce pool fresh from the Solana RPC
+async fn start_chain_tasks() {
+    // Initialize and start the blockhash cache update task
+    let blockhash_cache = crate::blockhash::BlockhashCache::instance();
+    blockhash_cache.set_default_commitment(get_relayer_settings().get_blockhash_commitment());
+    blockhash_cache.set_max_age(get_relayer_settings().get_blockhash_max_age());
+    if let Err(e) = blockhash_cache.start_update_task(get_relayer_settings().get_solana_rpc_url()).await {
+        error!("Failed to start blockhash cache update task: {:?}", e);
+    }
+
+    // Initialize the nonce pool
+    info!("Initializing nonce pool from environment variables");
+    let nonce_pool = crate::nonce::NoncePool::instance();
+    nonce_pool.set_target_pool_size(get_relayer_settings().get_nonce_target_pool_size());
+    nonce_pool.set_min_available(get_relayer_settings().get_nonce_min_available());
+    match nonce_pool.init_from_env() {
+        Ok(_) => {
+            info!("Nonce pool initialized successfully");
+            // Start the nonce pool maintenance task
+            if let Err(e) = nonce_pool.start_maintenance_task(get_relayer_settings().get_solana_rpc_url()).await {
+                error!("Failed to start nonce pool maintenance task: {:?}", e);
+            } else {
+                info!("Nonce pool maintenance task started");
+            }
+        },
+        Err(e) => {
+            warn!("Failed to initialize nonce pool: {:?}. Continuing with blockhash only.", e);
+        }
+    }
+}
+
+/// Execute queued arbitrage results with `execute`, as many at once as the in-flight
+/// limit allows, unless repeated failures have paused submissions
+///
+/// Executions make blocking RPC calls, so each one runs on its own blocking-pool thread
+/// rather than on the async runtime. Each outcome is recorded with the circuit breaker.
+/// A panic is contained to the opportunity that raised it and counted as a failure.
+async fn process_arbitrage_queue<F, Fut>(execute: F)
+where
+    F: Fn(ArbitrageResult) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = Result<ExecutionReport>>,
+{
+    let circuit_breaker = crate::arbitrage::circuit_breaker::circuit_breaker();
+    if !circuit_breaker.allows_submission() {
+        debug!("Circuit breaker open, skipping submission this cycle");
+    } else {
+        let in_flight_limiter = crate::arbitrage::in_flight::in_flight_limiter();
+        let mut executions = Vec::new();
+        while arbitrage_queue_len() > 0 {
+            // Whatever doesn't get a permit stays queued for the next cycle
+            let Some(permit) = in_flight_limiter.try_acquire() else {
+                break;
+            };
+            let Some(arbitrage_result) = dequeue_arbitrage_result() else {
+                break;
+            };
+            info!("Processing arbitrage result from queue with status: {}", arbitrage_result.status);
+
+            // Log information about the arbitrage result
+            info!("Arbitrage result contains {} delta entries, {} lambda entries, and {} A-matrices",
+                arbitrage_result.deltas.len(),
+                arbitrage_result.lambdas.len(),
+                arbitrage_result.a_matrices.len()
+            );
+
+            // Execute the arbitrage opportunity, holding the permit until it's done.
+            // A panic is contained to this opportunity and counted as a failure.
+            let runtime = tokio::runtime::Handle::current();
+            let execute = execute.clone();
+            executions.push(tokio::task::spawn_blocking(move || {
+                let _permit = permit;
+                runtime.block_on(contain_panic(execute(arbitrage_result)))
+            }));
+        }
+
+        if executions.is_empty() {
+            debug!("No arbitrage results in the queue to process");
+        }
+        for execution in futures::future::join_all(executions).await {
+            let execution = execution
+                .unwrap_or_else(|e| Err(anyhow::anyhow!("Arbitrage execution task failed: {}", e)));
+            match execution {
+                Ok(report) => {
+                    record_execution_outcome(&report);
+                    if report.outcome.is_failure() {
+                        circuit_breaker.record_failure();
+                    } else if report.outcome.is_submitted() {
+                        circuit_breaker.record_success();
+                    }
+                },
+                Err(e) => {
+                    error!("Failed to execute arbitrage: {:?}", e);
+                    circuit_breaker.record_failure();
+                }
+            }
+        }
+    }
+}
+
 /// Listens to the relayer queue and handles transaction submissions.
 ///
 /// This function performs the following tasks:
@@ -263,94 +953,75 @@ pub async fn run_relayer(
         info!("Initialized relayer settings from environment variables");
     }
 
-    // Initialize and start the blockhash cache update task
-    let blockhash_cache = crate::blockhash::BlockhashCache::instance();
-    if let Err(e) = blockhash_cache.start_update_task(rpc::solana::MAINNET_RPC_URL).await {
-        error!("Failed to start blockhash cache update task: {:?}", e);
+    get_relayer_settings().validate()?;
+
+    crate::arbitrage::dedup::init_submission_store(get_relayer_settings())?;
+    crate::arbitrage::circuit_breaker::init_circuit_breaker(get_relayer_settings());
+    crate::arbitrage::cooldown::init_pool_cooldown(get_relayer_settings());
+    let in_flight_limiter = crate::arbitrage::in_flight::init_in_flight_limiter(get_relayer_settings());
+    info!("Allowing up to {} arbitrage executions in flight", in_flight_limiter.max_in_flight());
+    crate::arbitrage::replay::init_result_recorder(get_relayer_settings())?;
+    crate::arbitrage::audit::init_swap_audit_log(get_relayer_settings())?;
+
+    // Write taxable events in batches alongside the queue, flushing what's left on shutdown
+    let taxable_event_writer = crate::metrics::database::init_taxable_event_writer(get_relayer_settings())?;
+    let taxable_event_flush_interval = get_relayer_settings().get_taxable_event_flush_interval();
+    let taxable_event_token = cancellation_token.clone();
+    tokio::spawn(async move {
+        taxable_event_writer.run(taxable_event_flush_interval, taxable_event_token).await;
+    });
+
+    let price_oracle = crate::oracle::price_oracle(get_relayer_settings());
+    info!("Valuing profit at SOL prices from the {} price source", price_oracle.source_name());
+
+    if let Some(fee_payer) = get_relayer_settings().load_fee_payer()? {
+        crate::fee_payer::set_fee_payer(fee_payer);
     }
 
-    // Initialize the nonce pool
-    info!("Initializing nonce pool from environment variables");
-    let nonce_pool = crate::nonce::NoncePool::instance();
-    match nonce_pool.init_from_env() {
-        Ok(_) => {
-            info!("Nonce pool initialized successfully");
-            // Start the nonce pool maintenance task
-            if let Err(e) = nonce_pool.start_maintenance_task(rpc::solana::MAINNET_RPC_URL).await {
-                error!("Failed to start nonce pool maintenance task: {:?}", e);
-            } else {
-                info!("Nonce pool maintenance task started");
-            }
-        },
-        Err(e) => {
-            warn!("Failed to initialize nonce pool: {:?}. Continuing with blockhash only.", e);
+    set_max_queue_size(get_relayer_settings().get_max_queue_size());
+    info!("Arbitrage queue capacity set to {}", max_queue_size());
+
+    // Put back whatever the last shutdown left queued
+    if let Some(path) = get_relayer_settings().get_queue_spool_path() {
+        let spooled = crate::arbitrage::drain::take_queue_spool(std::path::Path::new(path))?;
+        if !spooled.is_empty() {
+            info!("Reloading {} arbitrage results queued at the last shutdown", spooled.len());
         }
+        for arbitrage_result in spooled {
+            enqueue_arbitrage_result(arbitrage_result)?;
+        }
+    }
+
+    // Mock execution never touches the chain, so it needs no blockhashes or nonces
+    if !get_relayer_settings().is_mock_execution() {
+        start_chain_tasks().await;
     }
 
+    // The router's channel, owned by this loop once the router has handed it over
+    let mut arbitrage_receiver = None;
+
     loop  {
         // Check if we've been asked to cancel
         if cancellation_token.is_cancelled() {
             info!("Cancellation token activated, shutting down relayer");
+            shut_down(taxable_event_writer, &mut arbitrage_receiver).await;
             return Ok(());
         }
 
         let span_name = format!("{}::run_relayer", RELAYER);
+        let receiver = &mut arbitrage_receiver;
 
         let result: Result<(), anyhow::Error> = tracer.in_span(span_name, |_cx| async move {
             // Listen to relayer queue for transaction submissions
             info!("Listening to relayer queue for transaction submissions...");
 
             // Step 1: Check the channel for new arbitrage results and add them to the queue
-            {
-                let mut receiver_guard = ARBITRAGE_RECEIVER.lock().map_err(|e| anyhow::anyhow!("Failed to lock arbitrage receiver: {:?}", e))?;
-                if let Some(ref mut rx) = *receiver_guard {
-                    // Try to receive all available arbitrage results without blocking
-                    loop {
-                        match rx.try_recv() {
-                            Ok(arbitrage_result) => {
-                                info!("Received arbitrage result with status: {}", arbitrage_result.status);
-
-                                // Record metrics for received arbitrage result
-                                record_arbitrage_result_received();
-
-                                // Add the result to our FIFO queue
-                                if let Err(e) = enqueue_arbitrage_result(arbitrage_result) {
-                                    error!("Failed to enqueue arbitrage result: {:?}", e);
-                                }
-                            },
-                            Err(mpsc::error::TryRecvError::Empty) => {
-                                // No more arbitrage results in the channel, break the loop
-                                debug!("No more arbitrage results in the channel");
-                                break;
-                            },
-                            Err(mpsc::error::TryRecvError::Disconnected) => {
-                                // Channel is disconnected, log an error and break the loop
-                                error!("Arbitrage channel disconnected");
-                                break;
-                            }
-                        }
-                    }
-                }
-            }
+            receive_arbitrage_results(receiver);
 
-            // Step 2: Process the next arbitrage result from the queue if available
-            if let Some(arbitrage_result) = dequeue_arbitrage_result() {
-                info!("Processing arbitrage result from queue with status: {}", arbitrage_result.status);
-
-                // Log information about the arbitrage result
-                info!("Arbitrage result contains {} delta entries, {} lambda entries, and {} A-matrices",
-                    arbitrage_result.deltas.len(),
-                    arbitrage_result.lambdas.len(),
-                    arbitrage_result.a_matrices.len()
-                );
-
-                // Execute the arbitrage opportunity
-                if let Err(e) = execute_arbitrage(&arbitrage_result).await {
-                    error!("Failed to execute arbitrage: {:?}", e);
-                }
-            } else {
-                debug!("No arbitrage results in the queue to process");
-            }
+            // Step 2: Process queued arbitrage results
+            process_arbitrage_queue(|arbitrage_result| async move {
+                execute_arbitrage(&arbitrage_result).await
+            }).await;
 
             Ok(())
         }).await;
@@ -360,9 +1031,315 @@ pub async fn run_relayer(
             error!("Error running relayer: {:?}", e);
         }
 
-        // Wait for specified duration before running the check again
-        sleep(CHECK_INTERVAL).await;
+        // Wait for specified duration before running the check again, unless cancelled
+        tokio::select! {
+            _ = cancellation_token.cancelled() => {
+                info!("Cancellation token activated, shutting down relayer");
+                shut_down(taxable_event_writer, &mut arbitrage_receiver).await;
+                return Ok(());
+            }
+            _ = sleep(CHECK_INTERVAL) => {}
+        }
     }
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::arbitrage::get_total_results_dropped;
+    use serial_test::serial;
+    use std::sync::Arc;
+
+    fn result_with_profit(profit: f64) -> ArbitrageResult {
+        ArbitrageResult {
+            deltas: vec![vec![1.0, 0.0]],
+            lambdas: vec![vec![0.0, 1.0 + profit]],
+            a_matrices: vec![],
+            status: format!("profit {}", profit),
+            execution_order: vec![],
+            market_values: vec![],
+            correlation_id: None,
+            emitted_at: None,
+            enqueued_at: None,
+            pool_fees: vec![],
+            pool_states: vec![],
+            version: ArbitrageResult::VERSION,
+        }
+    }
+
+    #[test]
+    fn test_two_token_deltas_in_a_three_token_pool() {
+        let indices = determine_token_indices(&[1.0, -0.5, 0.0]);
+        assert_eq!(indices.spent, vec![0]);
+        assert_eq!(indices.received, vec![1]);
+        assert_eq!(indices.two_sided(), Some((0, 1)));
+
+        // Dust deltas don't count as a side of the swap
+        let indices = determine_token_indices(&[1e-9, -1.0, 2.0]);
+        assert_eq!(indices, TokenIndices { spent: vec![2], received: vec![1] });
+        assert_eq!(indices.two_sided(), Some((2, 1)));
+    }
+
+    #[test]
+    fn test_three_token_deltas_are_not_two_sided() {
+        // Two tokens spent for one
+        let indices = determine_token_indices(&[1.0, 0.5, -0.8]);
+        assert_eq!(indices.spent, vec![0, 1]);
+        assert_eq!(indices.received, vec![2]);
+        assert_eq!(indices.two_sided(), None);
+
+        // One token spent for two
+        let indices = determine_token_indices(&[-0.3, 1.0, -0.4]);
+        assert_eq!(indices.spent, vec![1]);
+        assert_eq!(indices.received, vec![0, 2]);
+        assert_eq!(indices.two_sided(), None);
+
+        // Nothing received
+        assert_eq!(determine_token_indices(&[1.0, 0.0, 0.0]).two_sided(), None);
+    }
+
+    #[test]
+    fn test_enqueue_below_capacity_keeps_everything() {
+        let mut queue = VecDeque::new();
+        for profit in [1.0, 2.0, 3.0] {
+            assert!(enqueue_bounded(&mut queue, result_with_profit(profit), 3).is_none());
+        }
+        assert_eq!(queue.len(), 3);
+    }
+
+    #[test]
+    fn test_overflow_drops_least_profitable() {
+        let mut queue = VecDeque::new();
+        for profit in [5.0, 1.0, 3.0] {
+            enqueue_bounded(&mut queue, result_with_profit(profit), 3);
+        }
+
+        let dropped_before = get_total_results_dropped();
+        let dropped = enqueue_bounded(&mut queue, result_with_profit(4.0), 3).unwrap();
+
+        assert_eq!(estimated_profit(&dropped), 1.0);
+        assert_eq!(queue.len(), 3);
+        let profits: Vec<f64> = queue.iter().map(estimated_profit).collect();
+        assert_eq!(profits, vec![5.0, 3.0, 4.0]);
+        assert!(get_total_results_dropped() > dropped_before);
+    }
+
+    #[test]
+    fn test_overflow_drops_incoming_when_least_profitable() {
+        let mut queue = VecDeque::new();
+        for profit in [5.0, 3.0] {
+            enqueue_bounded(&mut queue, result_with_profit(profit), 2);
+        }
+
+        let dropped_before = get_total_results_dropped();
+        let dropped = enqueue_bounded(&mut queue, result_with_profit(0.5), 2).unwrap();
+
+        assert_eq!(estimated_profit(&dropped), 0.5);
+        let profits: Vec<f64> = queue.iter().map(estimated_profit).collect();
+        assert_eq!(profits, vec![5.0, 3.0]);
+        assert!(get_total_results_dropped() > dropped_before);
+    }
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_channel_drains_without_deadlock_under_concurrent_sends() {
+        const SENDERS: usize = 8;
+        const RESULTS_PER_SENDER: usize = 50;
+        let (tx, mut rx) = mpsc::channel::<ArbitrageResult>(16);
+
+        let senders: Vec<_> = (0..SENDERS)
+            .map(|_| {
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    for i in 0..RESULTS_PER_SENDER {
+                        tx.send(result_with_profit(i as f64)).await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        // The channel holds fewer results than are sent, so the senders only finish if
+        // draining keeps up with them
+        let mut received = Vec::new();
+        let drained = tokio::time::timeout(Duration::from_secs(5), async {
+            while received.len() < SENDERS * RESULTS_PER_SENDER {
+                drain_arbitrage_channel(&mut rx, |result| received.push(result));
+                tokio::task::yield_now().await;
+            }
+        })
+        .await;
+
+        assert!(drained.is_ok(), "draining stalled after {} results", received.len());
+        for sender in senders {
+            sender.await.unwrap();
+        }
+        assert_eq!(drain_arbitrage_channel(&mut rx, |_| panic!("channel should be empty")), 0);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_execution_is_contained() {
+        let executions = (0..3).map(|i| async move {
+            contain_panic(async move {
+                if i == 1 {
+                    unimplemented!("swap instruction for this DEX");
+                }
+                Ok(i)
+            })
+            .await
+        });
+
+        // The panic fails its own execution only, and the others still run
+        let results = futures::future::join_all(executions).await;
+        assert_eq!(results[0].as_ref().unwrap(), &0);
+        assert_eq!(results[2].as_ref().unwrap(), &2);
+        let error = results[1].as_ref().unwrap_err().to_string();
+        assert!(error.contains("not implemented: swap instruction for this DEX"), "{}", error);
+
+        // And later executions are unaffected
+        assert_eq!(contain_panic(async { Ok(7) }).await.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_relayer_loop_survives_a_panicking_execution() {
+        while dequeue_arbitrage_result().is_some() {}
+
+        let executed = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&executed);
+        let execute = move |arbitrage_result: ArbitrageResult| {
+            let executed = Arc::clone(&recorded);
+            async move {
+                if arbitrage_result.status == "panics" {
+                    unimplemented!("swap instruction for this DEX");
+                }
+                executed.lock().unwrap().push(arbitrage_result.status.clone());
+                Ok(ExecutionReport {
+                    correlation_id: arbitrage_result.correlation_id.unwrap_or_default(),
+                    outcome: ExecutionOutcome::Skipped(SkipReason::InvalidResult),
+                })
+            }
+        };
+        let with_status = |status: &str| ArbitrageResult { status: status.to_string(), ..result_with_profit(1.0) };
+
+        // The panic fails its own opportunity, and the one queued behind it still runs
+        enqueue_arbitrage_result(with_status("panics")).unwrap();
+        enqueue_arbitrage_result(with_status("queued behind")).unwrap();
+        process_arbitrage_queue(execute.clone()).await;
+        assert_eq!(*executed.lock().unwrap(), vec!["queued behind"]);
+        assert_eq!(arbitrage_queue_len(), 0);
+
+        // And the loop goes on to the next cycle's opportunities
+        enqueue_arbitrage_result(with_status("next cycle")).unwrap();
+        process_arbitrage_queue(execute.clone()).await;
+        assert_eq!(*executed.lock().unwrap(), vec!["queued behind", "next cycle"]);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_blocking_executions_run_concurrently() {
+        while dequeue_arbitrage_result().is_some() {}
+
+        // Each execution blocks its thread until the other has started, as a blocking RPC
+        // call would. Run one after another, the first would give up waiting.
+        let started = Arc::new((Mutex::new(0), std::sync::Condvar::new()));
+        let overlapped = Arc::new(Mutex::new(Vec::new()));
+        let execute = {
+            let started = Arc::clone(&started);
+            let overlapped = Arc::clone(&overlapped);
+            move |arbitrage_result: ArbitrageResult| {
+                let started = Arc::clone(&started);
+                let overlapped = Arc::clone(&overlapped);
+                async move {
+                    let (count, condvar) = &*started;
+                    *count.lock().unwrap() += 1;
+                    condvar.notify_all();
+                    let (count, _) = condvar
+                        .wait_timeout_while(count.lock().unwrap(), Duration::from_secs(5), |count| *count < 2)
+                        .unwrap();
+                    overlapped.lock().unwrap().push(*count == 2);
+                    Ok(ExecutionReport {
+                        correlation_id: arbitrage_result.correlation_id.unwrap_or_default(),
+                        outcome: ExecutionOutcome::Skipped(SkipReason::InvalidResult),
+                    })
+                }
+            }
+        };
+
+        enqueue_arbitrage_result(result_with_profit(1.0)).unwrap();
+        enqueue_arbitrage_result(result_with_profit(2.0)).unwrap();
+        process_arbitrage_queue(execute).await;
+        assert_eq!(*overlapped.lock().unwrap(), vec![true, true]);
+    }
+
+    #[tokio::test]
+    async fn test_non_optimal_result_is_skipped() {
+        let mut result = result_with_profit(1.0);
+        result.status = "infeasible".to_string();
+
+        let outcome = execute_arbitrage_with_settings(&result, &settings::RelayerSettings::default())
+            .await
+            .unwrap()
+            .outcome;
+        assert_eq!(outcome, ExecutionOutcome::Skipped(SkipReason::InvalidResult));
+        assert!(!outcome.is_failure());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_dex_pools_yield_no_swaps() {
+        let result = ArbitrageResult {
+            deltas: vec![vec![1.0, -0.5]],
+            lambdas: vec![vec![-2.0, 0.0]],
+            a_matrices: vec![vec![vec![1.0, 0.0], vec![0.0, 1.0]]],
+            status: "optimal".to_string(),
+            execution_order: vec![],
+            market_values: vec![],
+            correlation_id: None,
+            emitted_at: None,
+            enqueued_at: None,
+            pool_fees: vec![],
+            pool_states: vec![],
+            version: ArbitrageResult::VERSION,
+        };
+        let settings = settings::RelayerSettings { simulate: true, ..settings::RelayerSettings::default() };
+
+        let outcome = execute_arbitrage_with_settings(&result, &settings).await.unwrap().outcome;
+        assert_eq!(outcome, ExecutionOutcome::Skipped(SkipReason::NoSwaps));
+    }
+
+    #[tokio::test]
+    async fn test_result_without_deltas_is_skipped() {
+        let mut result = result_with_profit(0.0);
+        result.status = "optimal".to_string();
+        result.deltas = vec![vec![0.0, 0.0]];
+
+        let outcome = execute_arbitrage_with_settings(&result, &settings::RelayerSettings::default())
+            .await
+            .unwrap()
+            .outcome;
+        assert_eq!(outcome, ExecutionOutcome::Skipped(SkipReason::InvalidResult));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_correlation_id_follows_result_from_enqueue_to_outcome() {
+        let mut result = result_with_profit(1.0);
+        result.status = "infeasible".to_string();
+
+        let correlation_id = enqueue_arbitrage_result(result).unwrap();
+        let queued = dequeue_arbitrage_result().unwrap();
+        assert_eq!(queued.correlation_id, Some(correlation_id));
+
+        let report = execute_arbitrage_with_settings(&queued, &settings::RelayerSettings::default())
+            .await
+            .unwrap();
+        assert_eq!(report.correlation_id, correlation_id);
+        assert_eq!(report.outcome, ExecutionOutcome::Skipped(SkipReason::InvalidResult));
+
+        // An id set upstream by the router is kept rather than replaced
+        let mut tagged = result_with_profit(1.0);
+        let router_id = tagged.assign_correlation_id();
+        assert_eq!(enqueue_arbitrage_result(tagged).unwrap(), router_id);
+        assert_eq!(dequeue_arbitrage_result().unwrap().correlation_id, Some(router_id));
+    }
+}
@@ -3,7 +3,7 @@ use crate::constants::QTRADE_RELAYER_METER;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use lazy_static::lazy_static;
-use opentelemetry::metrics::{Counter, Histogram};
+use opentelemetry::metrics::{Counter, Histogram, ObservableGauge};
 
 /// Metrics for tracking arbitrage operations
 pub struct ArbitrageMetrics {
@@ -17,6 +17,30 @@ pub struct ArbitrageMetrics {
     pub total_failed_transactions: Arc<AtomicU64>,
     /// Counter for total profit in USD (stored as integer with 3 decimal places)
     pub total_profit_usd: Arc<AtomicU64>,
+    /// Counter for total number of arbitrage results dropped because the queue was full
+    pub total_results_dropped: Arc<AtomicU64>,
+    /// Current number of arbitrage results waiting in the queue
+    pub queue_depth: Arc<AtomicU64>,
+    /// Counter for total number of transactions not submitted because pre-flight simulation failed
+    pub total_simulations_rejected: Arc<AtomicU64>,
+    /// Counter for total number of times the circuit breaker opened
+    pub total_circuit_breaker_opens: Arc<AtomicU64>,
+    /// Counter for total number of opportunities skipped because their fees exceeded the cap
+    pub total_fee_cap_exceeded: Arc<AtomicU64>,
+    /// Current number of arbitrage executions in flight
+    pub in_flight: Arc<AtomicU64>,
+    /// Counter for total number of executions deferred because the in-flight limit was reached
+    pub total_in_flight_rejected: Arc<AtomicU64>,
+    /// Counter for total number of opportunities skipped because a pool was cooling down
+    pub total_pool_cooldown_skips: Arc<AtomicU64>,
+    /// Counter for total number of provider requests that timed out
+    pub total_rpc_request_timeouts: Arc<AtomicU64>,
+    /// Counter for total number of confirmed opportunities whose end-to-end latency was recorded
+    pub total_opportunity_latencies: Arc<AtomicU64>,
+    /// End-to-end latency of the last confirmed opportunity, in microseconds
+    pub last_opportunity_latency_us: Arc<AtomicU64>,
+    /// Counter for total number of arbitrage executions that ended simulated
+    pub total_simulated_executions: Arc<AtomicU64>,
 }
 
 lazy_static! {
@@ -28,12 +52,149 @@ lazy_static! {
             total_successful_transactions: Arc::new(AtomicU64::new(0)),
             total_failed_transactions: Arc::new(AtomicU64::new(0)),
             total_profit_usd: Arc::new(AtomicU64::new(0)),
+            total_results_dropped: Arc::new(AtomicU64::new(0)),
+            queue_depth: Arc::new(AtomicU64::new(0)),
+            total_simulations_rejected: Arc::new(AtomicU64::new(0)),
+            total_circuit_breaker_opens: Arc::new(AtomicU64::new(0)),
+            total_fee_cap_exceeded: Arc::new(AtomicU64::new(0)),
+            in_flight: Arc::new(AtomicU64::new(0)),
+            total_in_flight_rejected: Arc::new(AtomicU64::new(0)),
+            total_pool_cooldown_skips: Arc::new(AtomicU64::new(0)),
+            total_rpc_request_timeouts: Arc::new(AtomicU64::new(0)),
+            total_opportunity_latencies: Arc::new(AtomicU64::new(0)),
+            last_opportunity_latency_us: Arc::new(AtomicU64::new(0)),
+            total_simulated_executions: Arc::new(AtomicU64::new(0)),
         }
     };
 }
 
 // Transaction monitoring metrics
 lazy_static! {
+    static ref RESULTS_DROPPED_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.arbitrage.results_dropped")
+            .with_description("Number of arbitrage results dropped because the relayer queue was full")
+            .build()
+    };
+
+    static ref QUEUE_DEPTH_GAUGE: ObservableGauge<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_observable_gauge("qtrade.arbitrage.queue_depth")
+            .with_description("Number of arbitrage results waiting in the relayer queue")
+            .with_callback(|observer| {
+                observer.observe(ARBITRAGE_METRICS.queue_depth.load(Ordering::Relaxed), &[]);
+            })
+            .build()
+    };
+
+    static ref SIMULATION_REJECTED_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.arbitrage.simulation_rejected")
+            .with_description("Number of arbitrage transactions not submitted because pre-flight simulation failed")
+            .build()
+    };
+
+    static ref CIRCUIT_BREAKER_OPENED_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.arbitrage.circuit_breaker_opened")
+            .with_description("Number of times submissions were paused after repeated failures")
+            .build()
+    };
+
+    static ref FEE_CAP_EXCEEDED_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.arbitrage.fee_cap_exceeded")
+            .with_description("Number of arbitrage opportunities skipped because their fees exceeded the configured share of profit")
+            .build()
+    };
+
+    static ref IN_FLIGHT_GAUGE: ObservableGauge<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_observable_gauge("qtrade.arbitrage.in_flight")
+            .with_description("Number of arbitrage executions in flight")
+            .with_callback(|observer| {
+                observer.observe(ARBITRAGE_METRICS.in_flight.load(Ordering::Relaxed), &[]);
+            })
+            .build()
+    };
+
+    static ref IN_FLIGHT_REJECTED_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.arbitrage.in_flight_rejected")
+            .with_description("Number of arbitrage executions deferred because the in-flight limit was reached")
+            .build()
+    };
+
+    static ref POOL_COOLDOWN_SKIPPED_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.arbitrage.pool_cooldown_skipped")
+            .with_description("Number of arbitrage opportunities skipped because a pool they trade failed recently")
+            .build()
+    };
+
+    static ref RPC_REQUEST_TIMEOUT_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.arbitrage.rpc_request_timeout")
+            .with_description("Number of RPC provider requests that timed out")
+            .build()
+    };
+
+    static ref OPPORTUNITY_EXPIRED_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.arbitrage.opportunity_expired")
+            .with_description("Number of arbitrage opportunities abandoned after a live reserve re-check")
+            .build()
+    };
+
+    static ref UNKNOWN_DEX_POOL_SKIPPED_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.arbitrage.unknown_dex_pool_skipped")
+            .with_description("Number of pools left out of arbitrage execution because their DEX couldn't be determined")
+            .build()
+    };
+
+    static ref UNSWAPPABLE_POOL_SKIPPED_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.arbitrage.unswappable_pool_skipped")
+            .with_description("Number of pools left out of arbitrage execution because their deltas aren't a two-sided swap")
+            .build()
+    };
+
+    static ref BLOCKED_MINT_OPPORTUNITY_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.arbitrage.blocked_mint_skipped")
+            .with_description("Number of arbitrage opportunities skipped because they trade a blocked or non-allowed mint")
+            .build()
+    };
+
+    static ref FEE_ON_TRANSFER_MINT_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.arbitrage.fee_on_transfer_mint_skipped")
+            .with_description("Number of arbitrage opportunities skipped because a mint's transfer fee is too high")
+            .build()
+    };
+
+    static ref FROZEN_TOKEN_ACCOUNT_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.arbitrage.frozen_token_account_skipped")
+            .with_description("Number of arbitrage opportunities skipped because a token account they use is frozen")
+            .build()
+    };
+
+    static ref BLOCKHASH_RESUBMIT_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.arbitrage.blockhash_resubmits")
+            .with_description("Number of arbitrage transactions resubmitted after their blockhash expired")
+            .build()
+    };
+
+    static ref EXECUTION_OUTCOME_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.arbitrage.execution_outcome")
+            .with_description("Number of arbitrage executions by outcome (skipped, simulated, submitted, failed)")
+            .build()
+    };
+
     static ref TX_CONFIRMED_COUNTER: Counter<u64> = {
         QTRADE_RELAYER_METER
             .u64_counter("qtrade.arbitrage.transaction_confirmed")
@@ -55,6 +216,27 @@ lazy_static! {
             .build()
     };
 
+    static ref SIMULATION_UNITS_CONSUMED: Histogram<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_histogram("qtrade.arbitrage.simulation_units_consumed")
+            .with_description("Compute units consumed by simulated arbitrage transactions")
+            .build()
+    };
+
+    static ref OPPORTUNITY_LATENCY: Histogram<f64> = {
+        QTRADE_RELAYER_METER
+            .f64_histogram("qtrade.arbitrage.opportunity_latency")
+            .with_description("Time from the router emitting an arbitrage result to its transaction confirming, and of each stage in between (ms)")
+            .build()
+    };
+
+    static ref CONFIRMED_PROFIT: Histogram<f64> = {
+        QTRADE_RELAYER_METER
+            .f64_histogram("qtrade.arbitrage.confirmed_profit")
+            .with_description("Profit of confirmed arbitrage transactions, as estimated before submission and as realized on-chain")
+            .build()
+    };
+
     static ref TX_CONFIRMATION_RATE: Histogram<f64> = {
         QTRADE_RELAYER_METER
             .f64_histogram("qtrade.arbitrage.transaction_confirmation_rate")
@@ -68,11 +250,184 @@ pub fn record_arbitrage_result_received() {
     ARBITRAGE_METRICS.total_results_received.fetch_add(1, Ordering::SeqCst);
 }
 
+/// Record metrics for an arbitrage result dropped from a full queue
+pub fn record_arbitrage_result_dropped() {
+    ARBITRAGE_METRICS.total_results_dropped.fetch_add(1, Ordering::SeqCst);
+    RESULTS_DROPPED_COUNTER.add(1, &[]);
+}
+
+/// Get the total number of arbitrage results dropped from a full queue
+pub fn get_total_results_dropped() -> u64 {
+    ARBITRAGE_METRICS.total_results_dropped.load(Ordering::SeqCst)
+}
+
+/// Record the current depth of the arbitrage queue
+pub fn record_arbitrage_queue_depth(depth: usize) {
+    lazy_static::initialize(&QUEUE_DEPTH_GAUGE);
+    ARBITRAGE_METRICS.queue_depth.store(depth as u64, Ordering::Relaxed);
+}
+
+/// Record metrics for a transaction held back because its pre-flight simulation failed
+pub fn record_arbitrage_simulation_rejected() {
+    ARBITRAGE_METRICS.total_simulations_rejected.fetch_add(1, Ordering::SeqCst);
+    SIMULATION_REJECTED_COUNTER.add(1, &[]);
+}
+
+/// Get the total number of transactions held back by a failed pre-flight simulation
+pub fn get_total_simulations_rejected() -> u64 {
+    ARBITRAGE_METRICS.total_simulations_rejected.load(Ordering::SeqCst)
+}
+
+/// Record metrics for the circuit breaker opening and pausing submissions
+pub fn record_arbitrage_circuit_breaker_opened() {
+    ARBITRAGE_METRICS.total_circuit_breaker_opens.fetch_add(1, Ordering::SeqCst);
+    CIRCUIT_BREAKER_OPENED_COUNTER.add(1, &[]);
+}
+
+/// Get the total number of times the circuit breaker opened
+pub fn get_total_circuit_breaker_opens() -> u64 {
+    ARBITRAGE_METRICS.total_circuit_breaker_opens.load(Ordering::SeqCst)
+}
+
+/// Record metrics for an opportunity skipped because its fees exceeded the cap
+pub fn record_arbitrage_fee_cap_exceeded() {
+    ARBITRAGE_METRICS.total_fee_cap_exceeded.fetch_add(1, Ordering::SeqCst);
+    FEE_CAP_EXCEEDED_COUNTER.add(1, &[]);
+}
+
+/// Get the total number of opportunities skipped because their fees exceeded the cap
+pub fn get_total_fee_cap_exceeded() -> u64 {
+    ARBITRAGE_METRICS.total_fee_cap_exceeded.load(Ordering::SeqCst)
+}
+
+/// Record an arbitrage execution starting
+pub fn record_arbitrage_in_flight_started() {
+    lazy_static::initialize(&IN_FLIGHT_GAUGE);
+    ARBITRAGE_METRICS.in_flight.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Record an arbitrage execution finishing
+pub fn record_arbitrage_in_flight_finished() {
+    // Never wraps below zero, even if a start went unrecorded
+    let _ = ARBITRAGE_METRICS.in_flight.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| count.checked_sub(1));
+}
+
+/// Get the current number of arbitrage executions in flight
+pub fn get_arbitrage_in_flight() -> u64 {
+    ARBITRAGE_METRICS.in_flight.load(Ordering::SeqCst)
+}
+
+/// Record metrics for an execution deferred because the in-flight limit was reached
+pub fn record_arbitrage_in_flight_rejected() {
+    ARBITRAGE_METRICS.total_in_flight_rejected.fetch_add(1, Ordering::SeqCst);
+    IN_FLIGHT_REJECTED_COUNTER.add(1, &[]);
+}
+
+/// Get the total number of executions deferred because the in-flight limit was reached
+pub fn get_total_in_flight_rejected() -> u64 {
+    ARBITRAGE_METRICS.total_in_flight_rejected.load(Ordering::SeqCst)
+}
+
+/// Record metrics for an opportunity skipped because a pool it trades is cooling down
+pub fn record_arbitrage_pool_cooldown_skipped() {
+    ARBITRAGE_METRICS.total_pool_cooldown_skips.fetch_add(1, Ordering::SeqCst);
+    POOL_COOLDOWN_SKIPPED_COUNTER.add(1, &[]);
+}
+
+/// Get the total number of opportunities skipped because a pool was cooling down
+pub fn get_total_pool_cooldown_skips() -> u64 {
+    ARBITRAGE_METRICS.total_pool_cooldown_skips.load(Ordering::SeqCst)
+}
+
+/// Record metrics for a provider request that timed out
+pub fn record_rpc_request_timeout(provider: &str) {
+    ARBITRAGE_METRICS.total_rpc_request_timeouts.fetch_add(1, Ordering::SeqCst);
+    RPC_REQUEST_TIMEOUT_COUNTER.add(1, &[opentelemetry::KeyValue::new("provider", provider.to_string())]);
+}
+
+/// Get the total number of provider requests that timed out
+pub fn get_total_rpc_request_timeouts() -> u64 {
+    ARBITRAGE_METRICS.total_rpc_request_timeouts.load(Ordering::SeqCst)
+}
+
+/// Record the end-to-end latency of a confirmed opportunity, from router emission to confirmation
+pub fn record_opportunity_latency(latency: std::time::Duration) {
+    ARBITRAGE_METRICS.total_opportunity_latencies.fetch_add(1, Ordering::SeqCst);
+    ARBITRAGE_METRICS.last_opportunity_latency_us.store(latency.as_micros() as u64, Ordering::SeqCst);
+    record_opportunity_stage_latency("total", latency);
+}
+
+/// Record the latency of one stage of a confirmed opportunity, e.g. "submit_to_confirm"
+pub fn record_opportunity_stage_latency(stage: &str, latency: std::time::Duration) {
+    OPPORTUNITY_LATENCY.record(
+        latency.as_secs_f64() * 1000.0,
+        &[opentelemetry::KeyValue::new("stage", stage.to_string())],
+    );
+}
+
+/// Get the total number of confirmed opportunities whose end-to-end latency was recorded
+pub fn get_total_opportunity_latencies() -> u64 {
+    ARBITRAGE_METRICS.total_opportunity_latencies.load(Ordering::SeqCst)
+}
+
+/// Get the end-to-end latency of the last confirmed opportunity
+pub fn get_last_opportunity_latency() -> std::time::Duration {
+    std::time::Duration::from_micros(ARBITRAGE_METRICS.last_opportunity_latency_us.load(Ordering::SeqCst))
+}
+
 /// Record metrics for an arbitrage opportunity being processed
 pub fn record_arbitrage_opportunity_processed() {
     ARBITRAGE_METRICS.total_opportunities_processed.fetch_add(1, Ordering::SeqCst);
 }
 
+/// Record metrics for an arbitrage opportunity that expired before landing
+pub fn record_arbitrage_opportunity_expired() {
+    OPPORTUNITY_EXPIRED_COUNTER.add(1, &[]);
+}
+
+/// Record a pool skipped because its DEX couldn't be determined
+pub fn record_unknown_dex_pool_skipped() {
+    UNKNOWN_DEX_POOL_SKIPPED_COUNTER.add(1, &[]);
+}
+
+/// Record a pool skipped because its deltas don't spend exactly one token for another
+pub fn record_unswappable_pool_skipped() {
+    UNSWAPPABLE_POOL_SKIPPED_COUNTER.add(1, &[]);
+}
+
+/// Record an opportunity skipped because it trades a mint that isn't allowed
+pub fn record_blocked_mint_opportunity_skipped() {
+    BLOCKED_MINT_OPPORTUNITY_COUNTER.add(1, &[]);
+}
+
+/// Record an opportunity skipped because a mint charges too high a transfer fee
+pub fn record_fee_on_transfer_mint_skipped() {
+    FEE_ON_TRANSFER_MINT_COUNTER.add(1, &[]);
+}
+
+/// Record an opportunity skipped because a token account it uses is frozen
+pub fn record_frozen_token_account_skipped() {
+    FROZEN_TOKEN_ACCOUNT_COUNTER.add(1, &[]);
+}
+
+/// Record a resubmit of an arbitrage transaction with a fresh blockhash
+pub fn record_blockhash_resubmit() {
+    BLOCKHASH_RESUBMIT_COUNTER.add(1, &[]);
+}
+
+/// Record how the execution of an arbitrage opportunity ended
+pub fn record_arbitrage_execution_outcome(outcome: &str) {
+    if outcome == "simulated" {
+        ARBITRAGE_METRICS.total_simulated_executions.fetch_add(1, Ordering::SeqCst);
+    }
+    EXECUTION_OUTCOME_COUNTER.add(1, &[opentelemetry::KeyValue::new("outcome", outcome.to_string())]);
+}
+
+/// Get the total number of arbitrage executions that ended simulated
+pub fn get_total_simulated_executions() -> u64 {
+    ARBITRAGE_METRICS.total_simulated_executions.load(Ordering::SeqCst)
+}
+
 /// Record metrics for a successful arbitrage transaction
 pub fn record_successful_arbitrage_transaction(profit_usd: f64) {
     ARBITRAGE_METRICS.total_successful_transactions.fetch_add(1, Ordering::SeqCst);
@@ -87,6 +442,11 @@ pub fn record_failed_arbitrage_transaction() {
     ARBITRAGE_METRICS.total_failed_transactions.fetch_add(1, Ordering::SeqCst);
 }
 
+/// Get the total number of failed arbitrage transactions
+pub fn get_total_failed_transactions() -> u64 {
+    ARBITRAGE_METRICS.total_failed_transactions.load(Ordering::SeqCst)
+}
+
 /// Get the total profit in USD (converted back to float with 3 decimal precision)
 pub fn get_total_profit_usd() -> f64 {
     let total_int = ARBITRAGE_METRICS.total_profit_usd.load(Ordering::SeqCst);
@@ -99,6 +459,12 @@ pub fn record_arbitrage_transaction_confirmed(profit: f64) {
     record_successful_arbitrage_transaction(profit);
 }
 
+/// Record the estimated and realized profit of a confirmed transaction
+pub fn record_arbitrage_realized_profit(estimated_profit: f64, realized_profit: f64) {
+    CONFIRMED_PROFIT.record(estimated_profit, &[opentelemetry::KeyValue::new("basis", "estimated")]);
+    CONFIRMED_PROFIT.record(realized_profit, &[opentelemetry::KeyValue::new("basis", "realized")]);
+}
+
 /// Record metrics for a transaction that failed on-chain
 pub fn record_arbitrage_transaction_failed() {
     TX_FAILED_COUNTER.add(1, &[]);
@@ -115,3 +481,8 @@ pub fn record_arbitrage_transaction_timeout() {
 pub fn record_arbitrage_transaction_confirmation_rate(rate: f64) {
     TX_CONFIRMATION_RATE.record(rate, &[]);
 }
+
+/// Record the compute units consumed by a simulated transaction
+pub fn record_simulation_units_consumed(provider: &str, units: u64) {
+    SIMULATION_UNITS_CONSUMED.record(units, &[opentelemetry::KeyValue::new("provider", provider.to_string())]);
+}
@@ -2,13 +2,138 @@
 //!
 //! This module provides functions for recording transaction data to a PostgreSQL database
 //! for use by accounting software. Transaction records are stored as taxable events.
+//!
+//! Events are buffered by a [`TaxableEventWriter`] and written in batches, periodically
+//! and on shutdown. A failed batch is retried with backoff and otherwise stays buffered;
+//! with a spool file configured, buffered events also survive a restart.
+//!
+//! Events are keyed on their transaction signature: a signature recorded twice, e.g. by
+//! a retried confirmation, is only written once.
 
-use anyhow::{anyhow, Result};
-use chrono::Utc;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
 use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
 use std::env;
+use std::fs;
+use std::path::PathBuf;
 use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::settings::RelayerSettings;
+
+/// Events written per batch unless configured otherwise
+pub const DEFAULT_TAXABLE_EVENT_BATCH_SIZE: usize = 100;
+
+/// How often buffered events are flushed unless configured otherwise
+pub const DEFAULT_TAXABLE_EVENT_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Attempts at writing a batch before it's left buffered for the next flush
+pub const TAXABLE_EVENT_WRITE_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry of a batch, doubled on every further retry
+pub const TAXABLE_EVENT_RETRY_BACKOFF: Duration = Duration::from_millis(200);
+
+/// Most events buffered while the database is unreachable; the oldest are dropped past it
+pub const MAX_BUFFERED_TAXABLE_EVENTS: usize = 10_000;
+
+/// Signatures remembered by a writer to drop duplicate events before they reach the database
+pub const MAX_REMEMBERED_TAXABLE_SIGNATURES: usize = 10_000;
+
+/// Table taxable events are written to
+///
+/// One row per landed transaction. The swap legs are kept as JSON, since an arbitrage
+/// has any number of them.
+pub const TAXABLE_EVENTS_SCHEMA: &str = "\
+CREATE TABLE IF NOT EXISTS taxable_events (
+    signature      TEXT PRIMARY KEY,
+    recorded_at    TIMESTAMPTZ NOT NULL,
+    provider       TEXT NOT NULL,
+    profit_usd     DOUBLE PRECISION NOT NULL,
+    correlation_id UUID NOT NULL,
+    legs           JSONB NOT NULL,
+    realized_profit_usd DOUBLE PRECISION
+)";
+
+/// Insert of one event; batches run it once per event in a single transaction
+///
+/// Conflicting signatures are skipped, so a batch retried after a partial write
+/// doesn't duplicate events.
+pub const INSERT_TAXABLE_EVENT_SQL: &str = "\
+INSERT INTO taxable_events (signature, recorded_at, provider, profit_usd, correlation_id, legs, realized_profit_usd)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+ON CONFLICT (signature) DO NOTHING";
+
+/// One swap of a taxable transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxableSwapLeg {
+    pub input_mint: String,
+    pub output_mint: String,
+    /// Amount of the input mint spent, in its smallest unit
+    pub amount_in: u64,
+    /// Least amount of the output mint the swap could return, in its smallest unit
+    pub min_amount_out: u64,
+}
+
+/// A landed arbitrage transaction, as recorded for accounting
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxableEvent {
+    pub timestamp: DateTime<Utc>,
+    /// Provider whose submission landed
+    pub provider: String,
+    pub signature: String,
+    /// Profit estimated before submission
+    pub profit_usd: f64,
+    /// Profit from the signer's balance changes once confirmed (None if they couldn't be read)
+    #[serde(default)]
+    pub realized_profit_usd: Option<f64>,
+    /// Id of the opportunity the transaction executed, tying the record to the router
+    /// solve and relayer logs behind it
+    pub correlation_id: Uuid,
+    pub legs: Vec<TaxableSwapLeg>,
+}
+
+impl TaxableEvent {
+    /// Event for a transaction landing now
+    pub fn new(
+        provider: &str,
+        signature: &str,
+        profit_usd: f64,
+        correlation_id: Uuid,
+        legs: Vec<TaxableSwapLeg>,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            provider: provider.to_string(),
+            signature: signature.to_string(),
+            profit_usd,
+            realized_profit_usd: None,
+            correlation_id,
+            legs,
+        }
+    }
+
+    /// The event with the profit its transaction realized
+    pub fn with_realized_profit(mut self, realized_profit_usd: Option<f64>) -> Self {
+        self.realized_profit_usd = realized_profit_usd;
+        self
+    }
+}
+
+/// Destination of taxable event batches
+pub trait TaxableEventSink: Send + Sync {
+    /// Write every event of `events`, or none of them
+    ///
+    /// Events whose signature was already written are skipped rather than written again
+    /// (see [`INSERT_TAXABLE_EVENT_SQL`]), so retrying a batch is always safe.
+    fn write_batch(&self, events: &[TaxableEvent]) -> Result<()>;
+}
 
 // Postgres client would be initialized here in production
 // For now, we'll create a placeholder that simulates connection status
@@ -38,6 +163,7 @@ impl PostgresClient {
             Ok(val) if val == "true" => {
                 self.is_connected = true;
                 info!("Connected to PostgreSQL database");
+                // In production, this would execute TAXABLE_EVENTS_SCHEMA
                 Ok(())
             }
             _ => {
@@ -47,34 +173,39 @@ impl PostgresClient {
             }
         }
     }
+}
 
-    /// Record a transaction as a taxable event
-    pub fn record_taxable_transaction(
-        &self,
-        provider: &str,
-        signature: &str,
-        profit_usd: f64,
-        timestamp: chrono::DateTime<Utc>,
-    ) -> Result<()> {
+impl TaxableEventSink for PostgresClient {
+    fn write_batch(&self, events: &[TaxableEvent]) -> Result<()> {
         if !self.is_connected {
-            warn!("Database not connected, transaction not recorded: {}", signature);
-            return Ok(());
+            return Err(anyhow!("Database not connected"));
         }
 
-        // In production, this would execute a SQL INSERT
-        info!(
-            "Recording taxable transaction from {}: signature={}, profit_usd={:.3}, timestamp={}",
-            provider, signature, profit_usd, timestamp
-        );
-
-        // Example SQL we would execute in production:
-        // INSERT INTO arbitrage_transactions (provider, signature, profit_usd, timestamp)
-        // VALUES ($1, $2, $3, $4)
+        // In production, this would run INSERT_TAXABLE_EVENT_SQL for each event in one transaction
+        for event in events {
+            info!(
+                "Recording taxable transaction from {}: signature={}, profit_usd={:.3}, realized_profit_usd={:?}, correlation_id={}, legs={}, timestamp={}",
+                event.provider, event.signature, event.profit_usd, event.realized_profit_usd, event.correlation_id, event.legs.len(), event.timestamp
+            );
+        }
 
         Ok(())
     }
 }
 
+/// Sink writing to the process-wide connection set up by [`init_database`]
+pub struct DatabaseSink;
+
+impl TaxableEventSink for DatabaseSink {
+    fn write_batch(&self, events: &[TaxableEvent]) -> Result<()> {
+        let connection = DB_CONNECTION.lock().map_err(|e| anyhow!("Failed to lock DB connection: {:?}", e))?;
+        match &*connection {
+            Some(client) => client.write_batch(events),
+            None => Err(anyhow!("Database not initialized")),
+        }
+    }
+}
+
 /// Initialize the database connection
 pub fn init_database() -> Result<()> {
     let mut connection = DB_CONNECTION.lock().map_err(|e| anyhow!("Failed to lock DB connection: {:?}", e))?;
@@ -91,23 +222,385 @@ pub fn init_database() -> Result<()> {
     Ok(())
 }
 
+/// Signatures of the most recently recorded events, oldest first
+#[derive(Default)]
+struct RecentSignatures {
+    order: VecDeque<String>,
+    signatures: HashSet<String>,
+}
+
+impl RecentSignatures {
+    /// Remember `signature`, returning false if it already was
+    fn insert(&mut self, signature: &str) -> bool {
+        if !self.signatures.insert(signature.to_string()) {
+            return false;
+        }
+        self.order.push_back(signature.to_string());
+        if self.order.len() > MAX_REMEMBERED_TAXABLE_SIGNATURES {
+            if let Some(oldest) = self.order.pop_front() {
+                self.signatures.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Buffers taxable events and writes them to a [`TaxableEventSink`] in batches
+///
+/// Events stay buffered until a batch holding them is written. With a spool file, the
+/// buffer is saved whenever a flush fails and on shutdown, and loaded back on open.
+///
+/// An event whose signature the writer recorded recently is dropped; older duplicates
+/// are left to the sink, which skips signatures it already wrote.
+pub struct TaxableEventWriter {
+    sink: Box<dyn TaxableEventSink>,
+    buffer: Mutex<VecDeque<TaxableEvent>>,
+    recent_signatures: Mutex<RecentSignatures>,
+    batch_size: usize,
+    spool_path: Option<PathBuf>,
+    retry_backoff: Duration,
+    // Serializes flushes, so a batch is never written twice
+    flush_lock: tokio::sync::Mutex<()>,
+    batch_ready: Notify,
+}
+
+impl TaxableEventWriter {
+    pub fn new(sink: Box<dyn TaxableEventSink>, batch_size: usize) -> Self {
+        Self {
+            sink,
+            buffer: Mutex::new(VecDeque::new()),
+            recent_signatures: Mutex::new(RecentSignatures::default()),
+            batch_size: batch_size.max(1),
+            spool_path: None,
+            retry_backoff: TAXABLE_EVENT_RETRY_BACKOFF,
+            flush_lock: tokio::sync::Mutex::new(()),
+            batch_ready: Notify::new(),
+        }
+    }
+
+    /// Keep unwritten events in the JSON file at `path`, buffering any already spooled there
+    pub fn with_spool(mut self, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if path.exists() {
+            let contents = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read taxable event spool {}", path.display()))?;
+            let spooled: Vec<TaxableEvent> = serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse taxable event spool {}", path.display()))?;
+            if !spooled.is_empty() {
+                info!("Recovered {} unwritten taxable events from {}", spooled.len(), path.display());
+            }
+            let mut recent_signatures = self.recent_signatures.lock().unwrap();
+            let mut buffer = self.buffer.lock().unwrap();
+            for event in spooled {
+                if recent_signatures.insert(&event.signature) {
+                    buffer.push_back(event);
+                }
+            }
+        }
+
+        self.spool_path = Some(path);
+        Ok(self)
+    }
+
+    /// Wait `retry_backoff` before the first retry of a failed batch
+    pub fn with_retry_backoff(mut self, retry_backoff: Duration) -> Self {
+        self.retry_backoff = retry_backoff;
+        self
+    }
+
+    /// Writer for the configured batch size and spool file, writing to the database
+    pub fn from_settings(settings: &RelayerSettings) -> Result<Self> {
+        let writer = Self::new(Box::new(DatabaseSink), settings.get_taxable_event_batch_size());
+        match settings.get_taxable_event_spool_path() {
+            Some(path) => writer.with_spool(path),
+            None => Ok(writer),
+        }
+    }
+
+    /// Buffer `event` for the next flush, returning false if its signature was already recorded
+    ///
+    /// Past [`MAX_BUFFERED_TAXABLE_EVENTS`], the oldest event is dropped and logged so it
+    /// can still be recovered from the logs.
+    pub fn record(&self, event: TaxableEvent) -> bool {
+        if !self.recent_signatures.lock().unwrap().insert(&event.signature) {
+            info!("Taxable event for {} already recorded, skipping duplicate", event.signature);
+            return false;
+        }
+
+        let mut buffer = self.buffer.lock().unwrap();
+        if buffer.len() >= MAX_BUFFERED_TAXABLE_EVENTS {
+            if let Some(dropped) = buffer.pop_front() {
+                error!("Taxable event buffer full, dropping event: {:?}", dropped);
+            }
+        }
+        buffer.push_back(event);
+
+        if buffer.len() >= self.batch_size {
+            self.batch_ready.notify_one();
+        }
+        true
+    }
+
+    /// Number of events not yet written
+    pub fn pending(&self) -> usize {
+        self.buffer.lock().unwrap().len()
+    }
+
+    /// Write every buffered event, a batch at a time, returning how many were written
+    ///
+    /// Each batch is attempted [`TAXABLE_EVENT_WRITE_ATTEMPTS`] times. If one still fails,
+    /// it and the events after it stay buffered (and spooled) and the error is returned.
+    pub async fn flush(&self) -> Result<usize> {
+        let _flushing = self.flush_lock.lock().await;
+
+        let mut written = 0;
+        loop {
+            let batch: Vec<TaxableEvent> = {
+                let buffer = self.buffer.lock().unwrap();
+                buffer.iter().take(self.batch_size).cloned().collect()
+            };
+            if batch.is_empty() {
+                break;
+            }
+
+            if let Err(e) = self.write_with_retry(&batch).await {
+                if let Err(spool_error) = self.save_spool() {
+                    error!("Failed to spool taxable events: {:?}", spool_error);
+                }
+                return Err(e);
+            }
+
+            self.buffer.lock().unwrap().drain(..batch.len());
+            written += batch.len();
+        }
+
+        if written > 0 {
+            self.save_spool()?;
+        }
+        Ok(written)
+    }
+
+    async fn write_with_retry(&self, batch: &[TaxableEvent]) -> Result<()> {
+        let mut backoff = self.retry_backoff;
+        let mut attempt = 1;
+        loop {
+            match self.sink.write_batch(batch) {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < TAXABLE_EVENT_WRITE_ATTEMPTS => {
+                    warn!("Failed to write {} taxable events (attempt {}), retrying in {:?}: {:?}",
+                        batch.len(), attempt, backoff, e);
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    return Err(e.context(format!(
+                        "Failed to write {} taxable events after {} attempts", batch.len(), attempt)));
+                }
+            }
+        }
+    }
+
+    /// Flush every `flush_interval`, and as soon as a full batch is buffered, until cancelled
+    pub async fn run(&self, flush_interval: Duration, cancellation_token: CancellationToken) {
+        let mut interval = tokio::time::interval(flush_interval);
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => return,
+                _ = interval.tick() => {}
+                _ = self.batch_ready.notified() => {}
+            }
+
+            if let Err(e) = self.flush().await {
+                warn!("Taxable events left buffered for the next flush: {:?}", e);
+            }
+        }
+    }
+
+    /// Flush what's left before shutting down, spooling anything that couldn't be written
+    pub async fn shutdown(&self) {
+        match self.flush().await {
+            Ok(written) => info!("Flushed {} taxable events on shutdown", written),
+            Err(e) => {
+                let pending = self.pending();
+                match &self.spool_path {
+                    Some(path) => error!("{} taxable events spooled to {} on shutdown: {:?}", pending, path.display(), e),
+                    None => error!("{} taxable events lost on shutdown, no spool configured: {:?}", pending, e),
+                }
+            }
+        }
+    }
+
+    // Write to a temporary file and rename it over the spool, so a crash mid-write
+    // never leaves a truncated file behind
+    fn save_spool(&self) -> Result<()> {
+        let Some(path) = &self.spool_path else {
+            return Ok(());
+        };
+
+        let events: Vec<TaxableEvent> = self.buffer.lock().unwrap().iter().cloned().collect();
+        let contents = serde_json::to_string(&events).context("Failed to serialize taxable events")?;
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, contents)
+            .with_context(|| format!("Failed to write taxable event spool {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, path)
+            .with_context(|| format!("Failed to replace taxable event spool {}", path.display()))?;
+        Ok(())
+    }
+}
+
+// Process-wide writer, set up from the relayer settings in run_relayer
+static TAXABLE_EVENT_WRITER: OnceCell<TaxableEventWriter> = OnceCell::new();
+
+/// Initialize the process-wide taxable event writer from the relayer settings
+///
+/// Only the first call has any effect.
+pub fn init_taxable_event_writer(settings: &RelayerSettings) -> Result<&'static TaxableEventWriter> {
+    TAXABLE_EVENT_WRITER.get_or_try_init(|| TaxableEventWriter::from_settings(settings))
+}
+
+/// The process-wide taxable event writer (unspooled, with the default batch size, if never initialized)
+pub fn taxable_event_writer() -> &'static TaxableEventWriter {
+    TAXABLE_EVENT_WRITER.get_or_init(|| TaxableEventWriter::new(Box::new(DatabaseSink), DEFAULT_TAXABLE_EVENT_BATCH_SIZE))
+}
+
 /// Record a transaction as a taxable event
-pub fn record_transaction_taxable_event(
-    provider: &str,
-    signature: &str,
-    profit_usd: f64,
-) -> Result<()> {
-    let connection = DB_CONNECTION.lock().map_err(|e| anyhow!("Failed to lock DB connection: {:?}", e))?;
-
-    let timestamp = Utc::now();
-
-    match &*connection {
-        Some(client) => {
-            client.record_taxable_transaction(provider, signature, profit_usd, timestamp)
-        },
-        None => {
-            error!("Database not initialized, transaction not recorded: {}", signature);
+///
+/// The event is buffered and written with the next batch. Recording a signature that
+/// was already recorded does nothing and returns false.
+pub fn record_transaction_taxable_event(event: TaxableEvent) -> bool {
+    taxable_event_writer().record(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Sink failing its first `failures` writes, then keeping every batch written
+    struct FlakySink {
+        failures: Mutex<u32>,
+        batches: Arc<Mutex<Vec<Vec<TaxableEvent>>>>,
+    }
+
+    impl TaxableEventSink for FlakySink {
+        fn write_batch(&self, events: &[TaxableEvent]) -> Result<()> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(anyhow!("connection reset"));
+            }
+            self.batches.lock().unwrap().push(events.to_vec());
+            Ok(())
+        }
+    }
+
+    fn writer(failures: u32, batch_size: usize) -> (TaxableEventWriter, Arc<Mutex<Vec<Vec<TaxableEvent>>>>) {
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let sink = FlakySink { failures: Mutex::new(failures), batches: Arc::clone(&batches) };
+        let writer = TaxableEventWriter::new(Box::new(sink), batch_size).with_retry_backoff(Duration::ZERO);
+        (writer, batches)
+    }
+
+    /// Sink keeping one row per signature, like the `taxable_events` table
+    #[derive(Default)]
+    struct TableSink {
+        rows: Arc<Mutex<Vec<TaxableEvent>>>,
+    }
+
+    impl TaxableEventSink for TableSink {
+        fn write_batch(&self, events: &[TaxableEvent]) -> Result<()> {
+            let mut rows = self.rows.lock().unwrap();
+            for event in events {
+                if !rows.iter().any(|row| row.signature == event.signature) {
+                    rows.push(event.clone());
+                }
+            }
             Ok(())
         }
     }
+
+    fn event(signature: &str) -> TaxableEvent {
+        TaxableEvent::new("helius", signature, 1.25, Uuid::new_v4(), vec![TaxableSwapLeg {
+            input_mint: "So11111111111111111111111111111111111111112".to_string(),
+            output_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            amount_in: 1_000_000_000,
+            min_amount_out: 150_000_000,
+        }])
+    }
+
+    #[tokio::test]
+    async fn test_events_are_flushed_in_batches() {
+        let (writer, batches) = writer(0, 2);
+        for signature in ["a", "b", "c"] {
+            writer.record(event(signature));
+        }
+
+        assert_eq!(writer.flush().await.unwrap(), 3);
+        assert_eq!(writer.pending(), 0);
+
+        let batches = batches.lock().unwrap();
+        let sizes: Vec<usize> = batches.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![2, 1]);
+        assert_eq!(batches[1][0].signature, "c");
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried() {
+        // Fails twice, then succeeds on the last attempt
+        let (writer, batches) = writer(TAXABLE_EVENT_WRITE_ATTEMPTS - 1, 10);
+        writer.record(event("a"));
+
+        assert_eq!(writer.flush().await.unwrap(), 1);
+        assert_eq!(batches.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unwritten_events_are_recovered_from_the_spool() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("taxable_events.json");
+
+        // The database stays down through every attempt and the shutdown flush
+        let (writer, batches) = writer(u32::MAX, 10);
+        let writer = writer.with_spool(&path).unwrap();
+        writer.record(event("a"));
+        writer.record(event("b"));
+        assert!(writer.flush().await.is_err());
+        writer.shutdown().await;
+        assert_eq!(writer.pending(), 2);
+        assert!(batches.lock().unwrap().is_empty());
+
+        // A restarted writer picks the events up and writes them once the database is back
+        let (restarted, batches) = writer_with_spool(&path);
+        assert_eq!(restarted.pending(), 2);
+        assert_eq!(restarted.flush().await.unwrap(), 2);
+        let signatures: Vec<String> = batches.lock().unwrap()[0].iter().map(|e| e.signature.clone()).collect();
+        assert_eq!(signatures, vec!["a", "b"]);
+
+        // Written events are cleared from the spool
+        let (restarted_again, _) = writer_with_spool(&path);
+        assert_eq!(restarted_again.pending(), 0);
+    }
+
+    fn writer_with_spool(path: &std::path::Path) -> (TaxableEventWriter, Arc<Mutex<Vec<Vec<TaxableEvent>>>>) {
+        let (writer, batches) = writer(0, 10);
+        (writer.with_spool(path).unwrap(), batches)
+    }
+
+    #[tokio::test]
+    async fn test_signature_recorded_twice_is_written_once() {
+        let rows = Arc::new(Mutex::new(Vec::new()));
+        let writer = TaxableEventWriter::new(Box::new(TableSink { rows: Arc::clone(&rows) }), 10);
+
+        // A confirmation retried before the flush, and again after it
+        assert!(writer.record(event("a")));
+        assert!(!writer.record(event("a")));
+        writer.flush().await.unwrap();
+        assert!(!writer.record(event("a")));
+        writer.flush().await.unwrap();
+
+        let rows = rows.lock().unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].signature, "a");
+    }
 }
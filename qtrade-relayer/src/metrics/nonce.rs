@@ -19,6 +19,8 @@ pub struct NonceMetrics {
     pub needs_advance_nonce_accounts: Arc<AtomicU64>,
     /// Total number of nonce account acquisitions
     pub total_nonce_acquisitions: Arc<AtomicU64>,
+    /// Total number of failed nonce account acquisitions
+    pub total_nonce_acquisition_failures: Arc<AtomicU64>,
     /// Total number of nonce account releases
     pub total_nonce_releases: Arc<AtomicU64>,
     /// Total number of nonce account initialization attempts
@@ -41,6 +43,7 @@ lazy_static! {
             needs_init_nonce_accounts: Arc::new(AtomicU64::new(0)),
             needs_advance_nonce_accounts: Arc::new(AtomicU64::new(0)),
             total_nonce_acquisitions: Arc::new(AtomicU64::new(0)),
+            total_nonce_acquisition_failures: Arc::new(AtomicU64::new(0)),
             total_nonce_releases: Arc::new(AtomicU64::new(0)),
             total_init_attempts: Arc::new(AtomicU64::new(0)),
             successful_init_attempts: Arc::new(AtomicU64::new(0)),
@@ -56,6 +59,9 @@ lazy_static! {
         QTRADE_RELAYER_METER
             .i64_observable_gauge("qtrade.nonce.pool_total")
             .with_description("Total number of nonce accounts in the pool")
+            .with_callback(|observer| {
+                observer.observe(NONCE_METRICS.total_nonce_accounts.load(Ordering::Relaxed) as i64, &[]);
+            })
             .build()
     };
 
@@ -63,6 +69,9 @@ lazy_static! {
         QTRADE_RELAYER_METER
             .i64_observable_gauge("qtrade.nonce.pool_available")
             .with_description("Number of available nonce accounts in the pool")
+            .with_callback(|observer| {
+                observer.observe(NONCE_METRICS.available_nonce_accounts.load(Ordering::Relaxed) as i64, &[]);
+            })
             .build()
     };
 
@@ -70,6 +79,9 @@ lazy_static! {
         QTRADE_RELAYER_METER
             .i64_observable_gauge("qtrade.nonce.pool_in_use")
             .with_description("Number of nonce accounts currently in use")
+            .with_callback(|observer| {
+                observer.observe(NONCE_METRICS.in_use_nonce_accounts.load(Ordering::Relaxed) as i64, &[]);
+            })
             .build()
     };
 
@@ -77,6 +89,9 @@ lazy_static! {
         QTRADE_RELAYER_METER
             .i64_observable_gauge("qtrade.nonce.pool_needs_init")
             .with_description("Number of nonce accounts needing initialization")
+            .with_callback(|observer| {
+                observer.observe(NONCE_METRICS.needs_init_nonce_accounts.load(Ordering::Relaxed) as i64, &[]);
+            })
             .build()
     };
 
@@ -84,6 +99,9 @@ lazy_static! {
         QTRADE_RELAYER_METER
             .i64_observable_gauge("qtrade.nonce.pool_needs_advance")
             .with_description("Number of nonce accounts needing advancement")
+            .with_callback(|observer| {
+                observer.observe(NONCE_METRICS.needs_advance_nonce_accounts.load(Ordering::Relaxed) as i64, &[]);
+            })
             .build()
     };
 
@@ -94,6 +112,13 @@ lazy_static! {
             .build()
     };
 
+    static ref NONCE_ACQUISITION_FAILURE_COUNTER: Counter<u64> = {
+        QTRADE_RELAYER_METER
+            .u64_counter("qtrade.nonce.acquisition_failures")
+            .with_description("Number of failed attempts to acquire a nonce account from the pool")
+            .build()
+    };
+
     static ref NONCE_RELEASE_COUNTER: Counter<u64> = {
         QTRADE_RELAYER_METER
             .u64_counter("qtrade.nonce.releases")
@@ -154,6 +179,28 @@ pub fn record_nonce_pool_state(
 
     // These values will be collected by the OpenTelemetry metrics system
     // and sent to the monitoring backend (like DataDog)
+    force_gauge_registration();
+}
+
+/// Record the number of nonce accounts currently in use
+pub fn record_nonce_in_use(in_use: usize) {
+    NONCE_METRICS.in_use_nonce_accounts.store(in_use as u64, Ordering::Relaxed);
+    force_gauge_registration();
+}
+
+/// Record a failed nonce acquisition (pool empty or exhausted)
+pub fn record_nonce_acquisition_failure() {
+    NONCE_METRICS.total_nonce_acquisition_failures.fetch_add(1, Ordering::Relaxed);
+    NONCE_ACQUISITION_FAILURE_COUNTER.add(1, &[]);
+}
+
+/// Touch the observable gauges so their callbacks are registered with the meter
+fn force_gauge_registration() {
+    lazy_static::initialize(&NONCE_POOL_TOTAL_GAUGE);
+    lazy_static::initialize(&NONCE_POOL_AVAILABLE_GAUGE);
+    lazy_static::initialize(&NONCE_POOL_IN_USE_GAUGE);
+    lazy_static::initialize(&NONCE_POOL_NEEDS_INIT_GAUGE);
+    lazy_static::initialize(&NONCE_POOL_NEEDS_ADVANCE_GAUGE);
 }
 
 /// Record a nonce acquisition event
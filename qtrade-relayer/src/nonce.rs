@@ -12,7 +12,7 @@ use solana_sdk::system_instruction;
 use solana_sdk::system_program;
 use solana_sdk::sysvar;
 use std::collections::VecDeque;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 use tokio::time::{interval, Duration};
 use anyhow::Result;
 use std::str::FromStr;
@@ -23,7 +23,8 @@ use crate::constants::QTRADE_RELAYER_TRACER_NAME;
 use crate::metrics::nonce::{
     record_nonce_acquisition, record_nonce_acquisition_with_latency,
     record_nonce_initialization_attempt, record_nonce_advancement_attempt,
-    record_nonce_pool_state, record_nonce_release
+    record_nonce_pool_state, record_nonce_release, record_nonce_in_use,
+    record_nonce_acquisition_failure
 };
 use opentelemetry::global;
 use opentelemetry::trace::Tracer;
@@ -31,6 +32,10 @@ use opentelemetry::trace::Tracer;
 const UPDATE_INTERVAL: Duration = Duration::from_secs(5); // Check nonce pool every 5 seconds
 const MAX_RETRY_ATTEMPTS: usize = 3;
 const NONCE_ACCOUNT_RENT_EXEMPT_LAMPORTS: u64 = 1_000_000; // Approximate, adjust as needed
+/// Nonce accounts the pool tops up to by default: none, as creating them spends SOL
+pub const DEFAULT_TARGET_POOL_SIZE: usize = 0;
+/// Available nonce count below which the pool is topped up by default
+pub const DEFAULT_MIN_AVAILABLE_NONCES: usize = 1;
 
 // Environment variable names
 const NONCE_ACCOUNTS_ENV: &str = "QTRADE_NONCE_ACCOUNTS";
@@ -61,6 +66,10 @@ pub struct NoncePool {
     is_initialized: AtomicBool,
     is_running: AtomicBool,
     in_use_count: AtomicUsize,
+    /// Number of nonce accounts the pool tops up to when running low
+    target_pool_size: AtomicUsize,
+    /// Available nonce count below which the pool is topped up
+    min_available: AtomicUsize,
 }
 
 /// Global singleton instance of the NoncePool
@@ -68,27 +77,45 @@ static mut NONCE_POOL_INSTANCE: Option<Arc<NoncePool>> = None;
 static INIT_INSTANCE: Once = Once::new();
 
 impl NoncePool {
+    fn new() -> Self {
+        NoncePool {
+            accounts: Mutex::new(VecDeque::new()),
+            authority: Mutex::new(None),
+            is_initialized: AtomicBool::new(false),
+            is_running: AtomicBool::new(false),
+            in_use_count: AtomicUsize::new(0),
+            target_pool_size: AtomicUsize::new(DEFAULT_TARGET_POOL_SIZE),
+            min_available: AtomicUsize::new(DEFAULT_MIN_AVAILABLE_NONCES),
+        }
+    }
+
     /// Get or initialize the global NoncePool instance
     pub fn instance() -> Arc<NoncePool> {
         unsafe {
             INIT_INSTANCE.call_once(|| {
-                NONCE_POOL_INSTANCE = Some(Arc::new(NoncePool {
-                    accounts: Mutex::new(VecDeque::new()),
-                    authority: Mutex::new(None),
-                    is_initialized: AtomicBool::new(false),
-                    is_running: AtomicBool::new(false),
-                    in_use_count: AtomicUsize::new(0),
-                }));
+                NONCE_POOL_INSTANCE = Some(Arc::new(NoncePool::new()));
             });
             NONCE_POOL_INSTANCE.clone().unwrap()
         }
     }
 
     /// Initialize the nonce pool with accounts and authority from environment variables
+    ///
+    /// If no nonce accounts are configured but a target pool size was set with
+    /// [`Self::set_target_pool_size`], the pool starts empty and the maintenance task
+    /// creates accounts on demand.
     pub fn init_from_env(&self) -> Result<()> {
+        let target_pool_size = self.target_pool_size.load(Ordering::SeqCst);
+
         // Load nonce accounts from environment variable
-        let nonce_accounts_str = env::var(NONCE_ACCOUNTS_ENV)
-            .map_err(|_| anyhow::anyhow!("Environment variable {} not found", NONCE_ACCOUNTS_ENV))?;
+        let nonce_accounts_str = match env::var(NONCE_ACCOUNTS_ENV) {
+            Ok(value) => value,
+            Err(_) if target_pool_size > 0 => {
+                info!("{} not set, nonce accounts will be created on demand", NONCE_ACCOUNTS_ENV);
+                String::new()
+            },
+            Err(_) => return Err(anyhow::anyhow!("Environment variable {} not found", NONCE_ACCOUNTS_ENV)),
+        };
 
         // Parse the comma-separated list of nonce account public keys
         let nonce_pubkeys_vec: Vec<Pubkey> = nonce_accounts_str
@@ -109,7 +136,7 @@ impl NoncePool {
             })
             .collect();
 
-        if nonce_pubkeys_vec.is_empty() {
+        if nonce_pubkeys_vec.is_empty() && target_pool_size == 0 {
             return Err(anyhow::anyhow!("No valid nonce account pubkeys found in {}", NONCE_ACCOUNTS_ENV));
         }
 
@@ -152,6 +179,7 @@ impl NoncePool {
         }
 
         self.is_initialized.store(true, Ordering::SeqCst);
+        qtrade_shared_types::HEALTH_STATUS.set_nonce_pool_initialized(true);
         info!("Nonce pool initialized with {} accounts", nonce_pubkeys_count);
 
         Ok(())
@@ -193,6 +221,9 @@ impl NoncePool {
                     if let Err(e) = nonce_pool.refresh_nonce_accounts(&rpc_client) {
                         error!("Failed to refresh nonce accounts: {:?}", e);
                     }
+                    if let Err(e) = nonce_pool.top_up(&rpc_client) {
+                        error!("Failed to top up nonce pool: {:?}", e);
+                    }
                     // Return an empty result since we're in a synchronous closure
                     Ok::<_, anyhow::Error>(())
                 });
@@ -284,9 +315,109 @@ impl NoncePool {
         Ok(())
     }
 
+    /// Set the number of nonce accounts the pool tops up to
+    pub fn set_target_pool_size(&self, target_pool_size: usize) {
+        self.target_pool_size.store(target_pool_size, Ordering::SeqCst);
+    }
+
+    /// Set the available nonce count below which the pool is topped up
+    pub fn set_min_available(&self, min_available: usize) {
+        self.min_available.store(min_available, Ordering::SeqCst);
+    }
+
+    /// Create nonce accounts when available nonces drop below the threshold
+    ///
+    /// New accounts are funded by a bank key from the wallet system, falling back
+    /// to the nonce authority when no key manager is running.
+    pub fn top_up(&self, rpc_client: &RpcClient) -> Result<usize> {
+        let target_pool_size = self.target_pool_size.load(Ordering::SeqCst);
+        let min_available = self.min_available.load(Ordering::SeqCst);
+
+        let (total, available) = {
+            let accounts = self.accounts.lock()
+                .map_err(|_| anyhow::anyhow!("Failed to lock nonce accounts mutex"))?;
+            let available = accounts.iter().filter(|a| a.status == NonceStatus::Available).count();
+            (accounts.len(), available)
+        };
+
+        if available >= min_available || total >= target_pool_size {
+            return Ok(0);
+        }
+
+        let count = target_pool_size - total;
+        info!("Nonce pool low ({} available, {} total), creating {} accounts", available, total, count);
+
+        let key_manager = qtrade_wallets::get_key_manager();
+        let bank_key = key_manager.as_ref().and_then(|km| km.bank_pool().get_keypair());
+
+        let result = match bank_key {
+            Some((_, ref funder)) => self.create_nonce_accounts(rpc_client, count, funder),
+            None => {
+                warn!("No bank key available, funding nonce accounts from the nonce authority");
+                let funder = self.get_authority()?;
+                self.create_nonce_accounts(rpc_client, count, &funder)
+            }
+        };
+
+        if let (Some(km), Some((bank_pubkey, _))) = (key_manager.as_ref(), bank_key.as_ref()) {
+            if let Err(e) = km.bank_pool().return_keypair(bank_pubkey, false) {
+                error!("Failed to return bank key {} to the pool: {}", bank_pubkey, e);
+            }
+        }
+
+        result.map(|created| created.len())
+    }
+
+    /// Create and initialize new durable nonce accounts and add them to the pool
+    pub fn create_nonce_accounts(&self, rpc_client: &RpcClient, count: usize, funder: &Keypair) -> Result<Vec<Pubkey>> {
+        let authority = self.get_authority()?;
+        let mut created = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let (nonce_keypair, instructions) = build_create_nonce_account_instructions(
+                rpc_client,
+                &funder.pubkey(),
+                &authority.pubkey(),
+            )?;
+            let nonce_pubkey = nonce_keypair.pubkey();
+
+            let blockhash = rpc_client.get_latest_blockhash()?;
+            let transaction = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&funder.pubkey()),
+                &[funder, &nonce_keypair],
+                blockhash,
+            );
+
+            let result = rpc_client.send_and_confirm_transaction(&transaction);
+            record_nonce_initialization_attempt(result.is_ok());
+
+            match result {
+                Ok(_) => {
+                    let mut accounts = self.accounts.lock()
+                        .map_err(|_| anyhow::anyhow!("Failed to lock nonce accounts mutex"))?;
+                    accounts.push_back(NonceAccount {
+                        pubkey: nonce_pubkey,
+                        status: NonceStatus::NeedsInitialization, // Will be updated during refresh
+                        current_nonce: None,
+                        last_used: None,
+                    });
+                    info!("Created nonce account {}", nonce_pubkey);
+                    created.push(nonce_pubkey);
+                },
+                Err(e) => {
+                    error!("Failed to create nonce account {}: {}", nonce_pubkey, e);
+                }
+            }
+        }
+
+        Ok(created)
+    }
+
     /// Acquire a nonce account from the pool
     pub fn acquire_nonce(&self, _rpc_client: &RpcClient) -> Result<(Pubkey, Hash)> {
         if !self.is_initialized.load(Ordering::SeqCst) {
+            record_nonce_acquisition_failure();
             return Err(anyhow::anyhow!("Nonce pool not initialized"));
         }
 
@@ -307,7 +438,8 @@ impl NoncePool {
                 // Mark as in use
                 account.status = NonceStatus::InUse;
                 account.last_used = Some(std::time::Instant::now());
-                self.in_use_count.fetch_add(1, Ordering::SeqCst);
+                let in_use = self.in_use_count.fetch_add(1, Ordering::SeqCst) + 1;
+                record_nonce_in_use(in_use);
 
                 // Calculate and record acquisition latency
                 let elapsed_ms = start_time.elapsed().as_secs_f64() * 1000.0;
@@ -319,6 +451,7 @@ impl NoncePool {
         }
 
         // No available nonce account found
+        record_nonce_acquisition_failure();
         Err(anyhow::anyhow!("No available nonce accounts in the pool"))
     }
 
@@ -338,7 +471,8 @@ impl NoncePool {
                 if account.status == NonceStatus::InUse {
                     // Mark as needing advance (after use, the nonce needs to be advanced for reuse)
                     account.status = NonceStatus::NeedsAdvance;
-                    self.in_use_count.fetch_sub(1, Ordering::SeqCst);
+                    let in_use = self.in_use_count.fetch_sub(1, Ordering::SeqCst) - 1;
+                    record_nonce_in_use(in_use);
 
                     // Record metric for nonce release
                     record_nonce_release();
@@ -381,6 +515,55 @@ impl NoncePool {
 
         Ok((total, in_use))
     }
+
+    /// Initialized pool of `count` available nonce accounts and no authority
+    #[cfg(test)]
+    pub(crate) fn with_available_nonces(count: usize) -> Self {
+        let pool = Self::new();
+        pool.accounts.lock().unwrap().extend((0..count).map(|_| NonceAccount {
+            pubkey: Pubkey::new_unique(),
+            status: NonceStatus::Available,
+            current_nonce: Some(Hash::new_unique()),
+            last_used: None,
+        }));
+        pool.is_initialized.store(true, Ordering::SeqCst);
+        pool
+    }
+
+    /// Acquire a nonce account from the pool, to be released when the guard is dropped
+    pub fn acquire_guarded(&self, rpc_client: &RpcClient) -> Result<NonceGuard<'_>> {
+        let (pubkey, hash) = self.acquire_nonce(rpc_client)?;
+        Ok(NonceGuard { pool: self, pubkey, hash })
+    }
+}
+
+/// A nonce account acquired from the pool, released back to it when dropped
+///
+/// Holding the guard from acquisition on means no early return, `?` or panic can leak
+/// the account, so nothing releases nonces by hand.
+pub struct NonceGuard<'a> {
+    pool: &'a NoncePool,
+    pubkey: Pubkey,
+    hash: Hash,
+}
+
+impl NonceGuard<'_> {
+    pub fn pubkey(&self) -> &Pubkey {
+        &self.pubkey
+    }
+
+    /// The nonce value, used in place of a recent blockhash
+    pub fn hash(&self) -> Hash {
+        self.hash
+    }
+}
+
+impl Drop for NonceGuard<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.pool.release_nonce(&self.pubkey) {
+            error!("Failed to release nonce account {}: {}", self.pubkey, e);
+        }
+    }
 }
 
 /// Get nonce account data
@@ -443,6 +626,29 @@ fn initialize_nonce_account(rpc_client: &RpcClient, nonce_pubkey: &Pubkey, autho
     }
 }
 
+/// Build the instructions that fund, allocate and initialize a new durable nonce account
+///
+/// Returns the freshly generated nonce account keypair, which must co-sign the transaction.
+pub fn build_create_nonce_account_instructions(
+    rpc_client: &RpcClient,
+    funder_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+) -> Result<(Keypair, Vec<Instruction>)> {
+    let lamports = rpc_client
+        .get_minimum_balance_for_rent_exemption(State::size())
+        .map_err(|e| anyhow::anyhow!("Failed to get nonce account rent exemption: {}", e))?;
+
+    let nonce_keypair = Keypair::new();
+    let instructions = system_instruction::create_nonce_account(
+        funder_pubkey,
+        &nonce_keypair.pubkey(),
+        authority_pubkey,
+        lamports,
+    );
+
+    Ok((nonce_keypair, instructions))
+}
+
 /// Advance a nonce account to get a new value
 pub fn advance_nonce_account(rpc_client: &RpcClient, nonce_pubkey: &Pubkey, authority: &Keypair) -> Result<Hash> {
     // Create instruction to advance nonce
@@ -516,3 +722,120 @@ impl KeypairExt for Keypair {
         Keypair::from_bytes(&self.to_bytes()).expect("Failed to clone keypair")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::nonce::NONCE_METRICS;
+    use serial_test::serial;
+    use solana_sdk::system_instruction::SystemInstruction;
+
+    #[test]
+    #[serial]
+    fn test_in_use_gauge_tracks_acquire_and_release() {
+        let pool = NoncePool::with_available_nonces(1);
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+
+        let (nonce_pubkey, _) = pool.acquire_nonce(&rpc_client).unwrap();
+        assert_eq!(NONCE_METRICS.in_use_nonce_accounts.load(Ordering::Relaxed), 1);
+
+        // Pool is exhausted, so a second acquisition is recorded as a failure
+        let failures = NONCE_METRICS.total_nonce_acquisition_failures.load(Ordering::Relaxed);
+        assert!(pool.acquire_nonce(&rpc_client).is_err());
+        assert_eq!(NONCE_METRICS.total_nonce_acquisition_failures.load(Ordering::Relaxed), failures + 1);
+
+        pool.release_nonce(&nonce_pubkey).unwrap();
+        assert_eq!(NONCE_METRICS.in_use_nonce_accounts.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_nonce_is_released_when_authority_is_missing() {
+        let pool = NoncePool::with_available_nonces(2);
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+
+        // The pool has no authority, so leasing fails after the nonce was acquired
+        assert!(pool.get_authority().is_err());
+        assert!(crate::arbitrage::tx_builder::NonceLease::acquire(&pool, &rpc_client).is_err());
+        assert_eq!(pool.get_stats().unwrap(), (2, 0));
+        assert_eq!(pool.accounts.lock().unwrap()[0].status, NonceStatus::NeedsAdvance);
+    }
+
+    #[test]
+    #[serial]
+    fn test_dropped_guard_releases_nonce_on_early_return_and_panic() {
+        let pool = NoncePool::with_available_nonces(3);
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+
+        let guard = pool.acquire_guarded(&rpc_client).unwrap();
+        assert_eq!(pool.get_stats().unwrap(), (3, 1));
+        drop(guard);
+        assert_eq!(pool.get_stats().unwrap(), (3, 0));
+
+        let bails_after_acquiring = || -> Result<()> {
+            let _nonce = pool.acquire_guarded(&rpc_client)?;
+            Err(anyhow::anyhow!("submission failed"))
+        };
+        assert!(bails_after_acquiring().is_err());
+        assert_eq!(pool.get_stats().unwrap(), (3, 0));
+
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _nonce = pool.acquire_guarded(&rpc_client).unwrap();
+            panic!("submission panicked");
+        }));
+        assert!(panicked.is_err());
+        assert_eq!(pool.get_stats().unwrap(), (3, 0));
+    }
+
+    #[test]
+    #[serial]
+    fn test_top_up_creates_no_accounts_by_default() {
+        // Every nonce is in use, but creating accounts is opt-in
+        let pool = NoncePool::with_available_nonces(1);
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let _nonce = pool.acquire_guarded(&rpc_client).unwrap();
+
+        assert_eq!(pool.top_up(&rpc_client).unwrap(), 0);
+        assert_eq!(pool.get_stats().unwrap(), (1, 1));
+    }
+
+    #[test]
+    fn test_build_create_nonce_account_instructions() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let funder = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+
+        let (nonce_keypair, instructions) =
+            build_create_nonce_account_instructions(&rpc_client, &funder, &authority).unwrap();
+        let nonce_pubkey = nonce_keypair.pubkey();
+        let expected_lamports = rpc_client.get_minimum_balance_for_rent_exemption(State::size()).unwrap();
+
+        assert_eq!(instructions.len(), 2);
+
+        // Fund and allocate the nonce account
+        let create = &instructions[0];
+        assert_eq!(create.program_id, system_program::id());
+        assert_eq!(create.accounts[0].pubkey, funder);
+        assert_eq!(create.accounts[1].pubkey, nonce_pubkey);
+        assert!(create.accounts[1].is_signer);
+        match bincode::deserialize::<SystemInstruction>(&create.data).unwrap() {
+            SystemInstruction::CreateAccount { lamports, space, owner } => {
+                assert_eq!(lamports, expected_lamports);
+                assert_eq!(space, State::size() as u64);
+                assert_eq!(owner, system_program::id());
+            },
+            other => panic!("Unexpected instruction: {:?}", other),
+        }
+
+        // Initialize it with our authority
+        let initialize = &instructions[1];
+        assert_eq!(initialize.program_id, system_program::id());
+        assert_eq!(initialize.accounts[0].pubkey, nonce_pubkey);
+        match bincode::deserialize::<SystemInstruction>(&initialize.data).unwrap() {
+            SystemInstruction::InitializeNonceAccount(nonce_authority) => {
+                assert_eq!(nonce_authority, authority);
+            },
+            other => panic!("Unexpected instruction: {:?}", other),
+        }
+    }
+}
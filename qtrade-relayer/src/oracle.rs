@@ -0,0 +1,367 @@
+//! Module for pricing SOL and other tokens in USD
+//!
+//! Landing costs are paid in lamports while profit is in the router's USD-like numeraire,
+//! so profit and fee checks need a SOL price. A [`PriceOracle`] fetches prices from the
+//! configured [`PriceSource`] (a fixed price, Pyth price accounts read over RPC, or an
+//! HTTP price API) and caches each one for a short TTL, so a burst of opportunities
+//! doesn't fetch the price once per opportunity.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use once_cell::sync::OnceCell;
+use pyth_sdk_solana::state::SolanaPriceAccount;
+use pyth_sdk_solana::Price;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+use crate::blockhash::{Clock, SystemClock};
+use crate::settings::{PriceSourceKind, RelayerSettings};
+
+/// Symbol of SOL, the token landing costs are paid in
+pub const SOL: &str = "SOL";
+
+/// How long a fetched price is reused before it is fetched again
+pub const DEFAULT_PRICE_CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// CoinGecko-style `simple/price` endpoint queried by the HTTP source
+pub const DEFAULT_PRICE_API_URL: &str = "https://api.coingecko.com/api/v3/simple/price";
+
+/// Pyth SOL/USD price account on mainnet
+const PYTH_SOL_USD_ACCOUNT: Pubkey = pubkey!("H6ARHf6YXhGYeQfUzQNGk6rDNnLBQKrenN712K4AQJEG");
+
+/// Oldest Pyth price accepted, in seconds
+const PYTH_MAX_PRICE_AGE_SECS: u64 = 60;
+
+/// Somewhere current USD prices can be fetched from
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    /// Name the source is logged under
+    fn name(&self) -> &'static str;
+
+    /// Current USD price of one whole `token`, by symbol (e.g. "SOL")
+    async fn fetch_usd_price(&self, token: &str) -> Result<f64>;
+}
+
+/// Prices fixed in configuration
+pub struct FixedPriceSource {
+    prices: HashMap<String, f64>,
+}
+
+impl FixedPriceSource {
+    /// Source pricing SOL at `sol_price_usd` and nothing else
+    pub fn new(sol_price_usd: f64) -> Self {
+        Self {
+            prices: HashMap::from([(SOL.to_string(), sol_price_usd)]),
+        }
+    }
+}
+
+#[async_trait]
+impl PriceSource for FixedPriceSource {
+    fn name(&self) -> &'static str {
+        "fixed"
+    }
+
+    async fn fetch_usd_price(&self, token: &str) -> Result<f64> {
+        self.prices
+            .get(token)
+            .copied()
+            .ok_or_else(|| anyhow!("No fixed price configured for {}", token))
+    }
+}
+
+/// Prices read from Pyth price accounts on chain
+pub struct PythPriceSource {
+    rpc_client: RpcClient,
+    accounts: HashMap<String, Pubkey>,
+}
+
+impl PythPriceSource {
+    /// Source reading the SOL/USD account through `rpc_url`
+    pub fn new(rpc_url: &str) -> Self {
+        Self {
+            rpc_client: RpcClient::new(rpc_url.to_string()),
+            accounts: HashMap::from([(SOL.to_string(), PYTH_SOL_USD_ACCOUNT)]),
+        }
+    }
+
+    /// Also price `token` from the Pyth price account `price_account`
+    pub fn with_account(mut self, token: &str, price_account: Pubkey) -> Self {
+        self.accounts.insert(token.to_string(), price_account);
+        self
+    }
+}
+
+#[async_trait]
+impl PriceSource for PythPriceSource {
+    fn name(&self) -> &'static str {
+        "pyth"
+    }
+
+    async fn fetch_usd_price(&self, token: &str) -> Result<f64> {
+        let price_key = self
+            .accounts
+            .get(token)
+            .ok_or_else(|| anyhow!("No Pyth price account configured for {}", token))?;
+
+        let mut account = self.rpc_client.get_account(price_key)?;
+        let feed = SolanaPriceAccount::account_to_feed(price_key, &mut account)?;
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        let price = feed
+            .get_price_no_older_than(now, PYTH_MAX_PRICE_AGE_SECS)
+            .ok_or_else(|| anyhow!("Pyth price of {} is older than {}s", token, PYTH_MAX_PRICE_AGE_SECS))?;
+
+        Ok(pyth_price_to_f64(&price))
+    }
+}
+
+/// A Pyth fixed-point price (`price * 10^expo`) as a float
+pub fn pyth_price_to_f64(price: &Price) -> f64 {
+    price.price as f64 * 10f64.powi(price.expo)
+}
+
+/// Prices from a CoinGecko-style `simple/price` HTTP API
+pub struct HttpPriceSource {
+    client: reqwest::Client,
+    url: String,
+    ids: HashMap<String, String>,
+}
+
+impl HttpPriceSource {
+    /// Source querying `url`, knowing SOL by its CoinGecko id
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            ids: HashMap::from([(SOL.to_string(), "solana".to_string())]),
+        }
+    }
+
+    /// Also price `token`, known to the API as `id`
+    pub fn with_id(mut self, token: &str, id: &str) -> Self {
+        self.ids.insert(token.to_string(), id.to_string());
+        self
+    }
+}
+
+/// USD price of `id` in a `simple/price` response (`{"<id>": {"usd": <price>}}`)
+pub fn parse_simple_price(response: &serde_json::Value, id: &str) -> Option<f64> {
+    response.get(id)?.get("usd")?.as_f64()
+}
+
+#[async_trait]
+impl PriceSource for HttpPriceSource {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    async fn fetch_usd_price(&self, token: &str) -> Result<f64> {
+        let id = self
+            .ids
+            .get(token)
+            .ok_or_else(|| anyhow!("No price API id configured for {}", token))?;
+
+        let response: serde_json::Value = self
+            .client
+            .get(&self.url)
+            .query(&[("ids", id.as_str()), ("vs_currencies", "usd")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        parse_simple_price(&response, id).ok_or_else(|| anyhow!("Price API returned no USD price for {}: {}", id, response))
+    }
+}
+
+/// Prices from a [`PriceSource`], each cached for a TTL
+pub struct PriceOracle {
+    source: Box<dyn PriceSource>,
+    ttl: Duration,
+    clock: Arc<dyn Clock>,
+    cache: Mutex<HashMap<String, (f64, Instant)>>,
+}
+
+impl PriceOracle {
+    pub fn new(source: Box<dyn PriceSource>, ttl: Duration) -> Self {
+        Self::with_clock(source, ttl, Arc::new(SystemClock))
+    }
+
+    /// Oracle timing its cache with `clock`
+    pub fn with_clock(source: Box<dyn PriceSource>, ttl: Duration, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            source,
+            ttl,
+            clock,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Oracle for the source configured in `settings`
+    pub fn from_settings(settings: &RelayerSettings) -> Self {
+        let source: Box<dyn PriceSource> = match settings.get_price_source() {
+            PriceSourceKind::Fixed => Box::new(FixedPriceSource::new(settings.get_sol_price_usd())),
+            PriceSourceKind::Pyth => Box::new(PythPriceSource::new(settings.get_solana_rpc_url())),
+            PriceSourceKind::Http => Box::new(HttpPriceSource::new(settings.get_price_api_url())),
+        };
+        Self::new(source, settings.get_price_cache_ttl())
+    }
+
+    /// Current USD price of one whole `token`, from the cache while it's fresh
+    pub async fn usd_price(&self, token: &str) -> Result<f64> {
+        let now = self.clock.now();
+        if let Some((price, fetched_at)) = self.cache.lock().unwrap().get(token) {
+            if now.duration_since(*fetched_at) < self.ttl {
+                return Ok(*price);
+            }
+        }
+
+        let price = self.source.fetch_usd_price(token).await?;
+        if !price.is_finite() || price <= 0.0 {
+            return Err(anyhow!("{} price source returned an invalid price for {}: {}", self.source.name(), token, price));
+        }
+        debug!("Fetched {} price from {}: {}", token, self.source.name(), price);
+
+        self.cache.lock().unwrap().insert(token.to_string(), (price, now));
+        Ok(price)
+    }
+
+    /// Name of the source prices are fetched from
+    pub fn source_name(&self) -> &'static str {
+        self.source.name()
+    }
+
+    /// Current USD price of SOL
+    pub async fn sol_price_usd(&self) -> Result<f64> {
+        self.usd_price(SOL).await
+    }
+}
+
+/// Price oracle shared by every execution, built from the first settings seen
+static PRICE_ORACLE: OnceCell<PriceOracle> = OnceCell::new();
+
+pub fn price_oracle(settings: &RelayerSettings) -> &'static PriceOracle {
+    PRICE_ORACLE.get_or_init(|| PriceOracle::from_settings(settings))
+}
+
+/// Current SOL price from the oracle, or the configured price if it can't be fetched
+pub async fn current_sol_price(settings: &RelayerSettings) -> f64 {
+    match price_oracle(settings).sol_price_usd().await {
+        Ok(price) => price,
+        Err(e) => {
+            warn!("Failed to fetch SOL price, using configured {}: {}", settings.get_sol_price_usd(), e);
+            settings.get_sol_price_usd()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::profit::{estimate_net_profit, TransactionCosts};
+    use qtrade_shared_types::ArbitrageResult;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Source returning a scripted price and counting fetches
+    struct MockPriceSource {
+        price: f64,
+        fetches: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl PriceSource for MockPriceSource {
+        fn name(&self) -> &'static str {
+            "mock"
+        }
+
+        async fn fetch_usd_price(&self, _token: &str) -> Result<f64> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(self.price)
+        }
+    }
+
+    /// Clock that only moves when told to
+    struct ManualClock(Mutex<Instant>);
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Instant {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn mock_oracle(price: f64, clock: Arc<dyn Clock>) -> (PriceOracle, Arc<AtomicUsize>) {
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let source = MockPriceSource { price, fetches: Arc::clone(&fetches) };
+        (PriceOracle::with_clock(Box::new(source), Duration::from_secs(10), clock), fetches)
+    }
+
+    #[tokio::test]
+    async fn test_profit_is_valued_at_fetched_price() {
+        let (oracle, _) = mock_oracle(200.0, Arc::new(SystemClock));
+        let sol_price = oracle.sol_price_usd().await.unwrap();
+        assert_eq!(sol_price, 200.0);
+
+        // One pool nets 1 unit of a token worth 1.0
+        let result = ArbitrageResult {
+            deltas: vec![vec![1.0, 0.0]],
+            lambdas: vec![vec![0.0, 2.0]],
+            a_matrices: vec![vec![vec![1.0, 0.0], vec![0.0, 1.0]]],
+            status: "optimal".to_string(),
+            execution_order: vec![],
+            market_values: vec![1.0, 1.0],
+            correlation_id: None,
+            emitted_at: None,
+            enqueued_at: None,
+            pool_fees: vec![],
+            pool_states: vec![],
+            version: ArbitrageResult::VERSION,
+        };
+
+        // A 0.01 SOL tip is worth 2.0 at 200 per SOL, not the default 150's 1.5
+        let costs = TransactionCosts { network_fee_lamports: 0, priority_fee_lamports: 0, tip_lamports: 10_000_000 };
+        let estimate = estimate_net_profit(&result, &costs, sol_price).unwrap();
+        assert!((estimate.tip - 2.0).abs() < 1e-9);
+        assert!((estimate.net_profit + 1.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_prices_are_cached_for_the_ttl() {
+        let clock = Arc::new(ManualClock(Mutex::new(Instant::now())));
+        let (oracle, fetches) = mock_oracle(150.0, clock.clone());
+
+        oracle.sol_price_usd().await.unwrap();
+        oracle.sol_price_usd().await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        *clock.0.lock().unwrap() += Duration::from_secs(10);
+        oracle.sol_price_usd().await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_prices_are_rejected() {
+        let (oracle, _) = mock_oracle(0.0, Arc::new(SystemClock));
+        assert!(oracle.sol_price_usd().await.is_err());
+
+        let fixed = PriceOracle::new(Box::new(FixedPriceSource::new(120.0)), DEFAULT_PRICE_CACHE_TTL);
+        assert_eq!(fixed.sol_price_usd().await.unwrap(), 120.0);
+        assert!(fixed.usd_price("USDC").await.is_err());
+    }
+
+    #[test]
+    fn test_price_formats() {
+        let price = Price { price: 14_523_000_000, conf: 0, expo: -8, publish_time: 0 };
+        assert!((pyth_price_to_f64(&price) - 145.23).abs() < 1e-9);
+
+        let response = serde_json::json!({"solana": {"usd": 145.23}});
+        assert_eq!(parse_simple_price(&response, "solana"), Some(145.23));
+        assert_eq!(parse_simple_price(&response, "usd-coin"), None);
+    }
+}
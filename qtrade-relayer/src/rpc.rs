@@ -1,19 +1,110 @@
 use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_response::{Response, RpcSimulateTransactionResult};
+use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::instruction::Instruction;
 use solana_sdk::signature::Keypair;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::hash::Hash;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use serde::{Deserialize, Serialize};
 use std::error::Error;
+use std::io;
+use std::time::Duration;
+use tracing::warn;
 
 pub mod bloxroute;
 pub mod helius;
 pub mod jito;
+pub mod mock;
 pub mod nextblock;
 pub mod quicknode;
 pub mod solana;
 pub mod temporal;
 pub mod triton;
 
+/// Represents available RPC providers for transaction submissions.
+///
+/// This enum allows the system to specify which RPC providers should
+/// be active for submitting transactions. By default, all providers
+/// are active, but users can restrict which ones are used via
+/// command-line arguments, environment variables, or configuration files.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum RpcProvider {
+    /// Bloxroute RPC provider
+    Bloxroute,
+    /// Helius RPC provider
+    Helius,
+    /// Jito RPC provider
+    Jito,
+    /// Nextblock RPC provider
+    Nextblock,
+    /// Quicknode RPC provider
+    Quicknode,
+    /// Solana RPC provider
+    Solana,
+    /// Temporal RPC provider
+    Temporal,
+    /// Triton RPC provider
+    Triton,
+}
+
+impl RpcProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RpcProvider::Bloxroute => "bloxroute",
+            RpcProvider::Helius => "helius",
+            RpcProvider::Jito => "jito",
+            RpcProvider::Nextblock => "nextblock",
+            RpcProvider::Quicknode => "quicknode",
+            RpcProvider::Solana => "solana",
+            RpcProvider::Temporal => "temporal",
+            RpcProvider::Triton => "triton",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "bloxroute" => Some(RpcProvider::Bloxroute),
+            "helius" => Some(RpcProvider::Helius),
+            "jito" => Some(RpcProvider::Jito),
+            "nextblock" => Some(RpcProvider::Nextblock),
+            "quicknode" => Some(RpcProvider::Quicknode),
+            "solana" => Some(RpcProvider::Solana),
+            "temporal" => Some(RpcProvider::Temporal),
+            "triton" => Some(RpcProvider::Triton),
+            _ => None,
+        }
+    }
+
+    /// Whether simulation mode can simulate transactions through this provider
+    pub fn supports_simulation(&self) -> bool {
+        matches!(self, RpcProvider::Solana | RpcProvider::Helius | RpcProvider::Nextblock)
+    }
+
+    /// Whether this provider only lands transactions that tip it
+    pub fn requires_tip(&self) -> bool {
+        matches!(self, RpcProvider::Jito | RpcProvider::Nextblock | RpcProvider::Bloxroute | RpcProvider::Temporal)
+    }
+
+    /// Providers simulated with, in order, when none are configured
+    pub fn default_simulation() -> Vec<Self> {
+        vec![RpcProvider::Solana, RpcProvider::Helius, RpcProvider::Nextblock]
+    }
+
+    /// Providers used when none are configured (every provider except Triton)
+    pub fn default_active() -> Vec<Self> {
+        vec![
+            RpcProvider::Bloxroute,
+            RpcProvider::Helius,
+            RpcProvider::Jito,
+            RpcProvider::Nextblock,
+            RpcProvider::Quicknode,
+            RpcProvider::Solana,
+            RpcProvider::Temporal,
+        ]
+    }
+}
+
 /// Optional nonce transaction details for use with durable nonces
 pub struct NonceInfo<'a> {
     /// The nonce account public key
@@ -24,6 +115,123 @@ pub struct NonceInfo<'a> {
     pub nonce_hash: Hash,
 }
 
+/// Data returned by a program through `set_return_data` during simulation
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationReturnData {
+    /// Program that set the return data
+    pub program_id: String,
+    /// Decoded return data bytes
+    pub data: Vec<u8>,
+}
+
+/// Structured result of a transaction simulation
+#[derive(Debug, Clone, Default)]
+pub struct SimulationDetails {
+    /// Transaction error, if the simulation failed
+    pub err: Option<TransactionError>,
+    /// Program logs emitted during the simulation
+    pub logs: Vec<String>,
+    /// Compute units consumed by the simulation
+    pub units_consumed: Option<u64>,
+    /// Return data set by the last program invoked, if any
+    pub return_data: Option<SimulationReturnData>,
+}
+
+impl SimulationDetails {
+    /// Parse the JSON returned by a `simulateTransaction` RPC call
+    ///
+    /// Accepts either the full `{context, value}` response or just the `value` object.
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+
+        let result: RpcSimulateTransactionResult = if value.get("context").is_some() {
+            serde_json::from_value::<Response<RpcSimulateTransactionResult>>(value)?.value
+        } else {
+            serde_json::from_value(value)?
+        };
+
+        Ok(Self::from(result))
+    }
+
+    /// Whether the simulated transaction executed without error
+    pub fn is_success(&self) -> bool {
+        self.err.is_none()
+    }
+}
+
+impl From<RpcSimulateTransactionResult> for SimulationDetails {
+    fn from(result: RpcSimulateTransactionResult) -> Self {
+        use base64::Engine;
+
+        let return_data = result.return_data.map(|return_data| SimulationReturnData {
+            program_id: return_data.program_id,
+            // Return data is always base64 encoded by the RPC
+            data: base64::engine::general_purpose::STANDARD
+                .decode(return_data.data.0)
+                .unwrap_or_default(),
+        });
+
+        Self {
+            err: result.err,
+            logs: result.logs.unwrap_or_default(),
+            units_consumed: result.units_consumed,
+            return_data,
+        }
+    }
+}
+
+/// How long a provider request may take when no timeout is configured
+pub const DEFAULT_RPC_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// HTTP client for a provider's own API that abandons requests after `timeout`
+pub fn http_client(timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .unwrap_or_else(|e| {
+            warn!("Failed to build HTTP client with a {:?} timeout, using the default client: {}", timeout, e);
+            reqwest::Client::new()
+        })
+}
+
+/// Whether a provider request failed because it timed out
+///
+/// The error's sources are walked, since each client wraps the timeout differently:
+/// reqwest flags it, the Solana RPC client wraps its own reqwest error, and sockets
+/// report it as an I/O error.
+pub fn is_timeout(error: &(dyn Error + 'static)) -> bool {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if error.downcast_ref::<reqwest::Error>().is_some_and(reqwest::Error::is_timeout)
+            || error.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::TimedOut)
+            || error.to_string().contains("timed out")
+        {
+            return true;
+        }
+        current = error.source();
+    }
+    false
+}
+
+/// Config for `simulateTransaction`
+///
+/// The recent blockhash is replaced with the latest one, so a stale cached blockhash can't
+/// fail the simulation with `BlockhashNotFound`. Signatures aren't verified, since replacing
+/// the blockhash invalidates them.
+pub fn simulation_config() -> serde_json::Value {
+    serde_json::json!({
+        "sigVerify": false,
+        "replaceRecentBlockhash": true,
+        "commitment": CommitmentConfig::confirmed().commitment,
+        "encoding": "jsonParsed",
+    })
+}
+
+/// Params of a `simulateTransaction` request for an encoded transaction
+pub fn simulate_transaction_params(encoded_tx: &str) -> serde_json::Value {
+    serde_json::json!([encoded_tx, simulation_config()])
+}
+
 pub trait RpcActions {
     /// Send a transaction with either a blockhash or nonce
     fn send_tx(&self, ixs: &mut Vec<Instruction>, signer: &Keypair) -> Result<String, Box<dyn Error>>;
@@ -35,12 +243,35 @@ pub trait RpcActions {
         self.send_tx(ixs, signer)
     }
 
+    /// Send a transaction that is already built and signed
+    fn send_signed_tx(&self, tx: &Transaction) -> Result<String, Box<dyn Error>> {
+        let signature = self.rpc_client().send_transaction(tx)?;
+        Ok(signature.to_string())
+    }
+
+    /// Send a transaction that is already serialized and base64 encoded, via the raw
+    /// `sendTransaction` method
+    fn send_encoded_tx(&self, encoded_tx: &str) -> Result<String, Box<dyn Error>> {
+        let signature: String = self.rpc_client().send(
+            solana_client::rpc_request::RpcRequest::SendTransaction,
+            serde_json::json!([encoded_tx, { "encoding": "base64" }]),
+        )?;
+        Ok(signature)
+    }
+
     /// Simulate a transaction and return the result
     fn simulate_tx(&self, ixs: &mut Vec<Instruction>, signer: &Keypair) -> Result<String, Box<dyn Error>> {
         // Default implementation returns an error since not all providers support simulation
         Err("Transaction simulation not supported by this RPC provider".into())
     }
 
+    /// Simulate a transaction and return structured results
+    fn simulate_tx_detailed(&self, ixs: &mut Vec<Instruction>, signer: &Keypair) -> Result<SimulationDetails, Box<dyn Error>> {
+        // Default implementation parses the raw simulation response
+        let simulation_result = self.simulate_tx(ixs, signer)?;
+        SimulationDetails::from_json(&simulation_result)
+    }
+
     fn rpc_client(&self) -> &RpcClient;
     fn rpc_url(&self) -> &str;
     fn tip_wallet(&self) -> Option<&Pubkey>;
@@ -53,3 +284,64 @@ pub trait RpcActions {
         ""
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::InstructionError;
+
+    const SAMPLE_SIMULATE_RESPONSE: &str = r#"{
+        "context": { "apiVersion": "2.1.0", "slot": 218 },
+        "value": {
+            "accounts": null,
+            "err": { "InstructionError": [1, { "Custom": 6001 }] },
+            "logs": [
+                "Program whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc invoke [1]",
+                "Program log: Error: slippage tolerance exceeded",
+                "Program whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc consumed 45123 of 200000 compute units"
+            ],
+            "returnData": {
+                "data": ["AQIDBA==", "base64"],
+                "programId": "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc"
+            },
+            "unitsConsumed": 45123
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_simulation_details() {
+        let details = SimulationDetails::from_json(SAMPLE_SIMULATE_RESPONSE).unwrap();
+
+        assert!(!details.is_success());
+        assert_eq!(
+            details.err,
+            Some(TransactionError::InstructionError(1, InstructionError::Custom(6001)))
+        );
+        assert_eq!(details.logs.len(), 3);
+        assert_eq!(details.units_consumed, Some(45123));
+
+        let return_data = details.return_data.unwrap();
+        assert_eq!(return_data.program_id, "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+        assert_eq!(return_data.data, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_simulation_replaces_blockhash_without_verifying_signatures() {
+        let params = simulate_transaction_params("encoded");
+
+        assert_eq!(params[0], "encoded");
+        assert_eq!(params[1]["replaceRecentBlockhash"], true);
+        assert_eq!(params[1]["sigVerify"], false);
+        assert_eq!(params[1]["commitment"], "confirmed");
+    }
+
+    #[test]
+    fn test_parse_simulation_value_only() {
+        let details = SimulationDetails::from_json(r#"{"err": null, "logs": [], "unitsConsumed": 1500}"#).unwrap();
+
+        assert!(details.is_success());
+        assert!(details.logs.is_empty());
+        assert_eq!(details.units_consumed, Some(1500));
+        assert!(details.return_data.is_none());
+    }
+}
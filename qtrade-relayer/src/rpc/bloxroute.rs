@@ -21,7 +21,7 @@ use tracing::{info, warn};
 
 use crate::settings::RelayerSettings;
 use crate::rpc::solana::MAINNET_RPC_URL;
-use crate::rpc::RpcActions;
+use crate::rpc::{http_client, RpcActions, RpcProvider};
 
 // For help in naming spans
 use crate::constants::QTRADE_RELAYER_TRACER_NAME;
@@ -49,12 +49,13 @@ impl Bloxroute {
 
     pub fn with_settings(settings: &RelayerSettings) -> Self {
         let rpc_url = BLOXROUTE_BASE_URL.to_string();
+        let timeout = settings.get_request_timeout(RpcProvider::Bloxroute);
         Self {
             rpc_url,
             tip_wallet: BLOXROUTE_TIP_WALLET,
             min_tip_amount: BLOXROUTE_MIN_TIP_AMOUNT,
-            http_client: Client::new(),
-            rpc_client: RpcClient::new(MAINNET_RPC_URL.to_string()),
+            http_client: http_client(timeout),
+            rpc_client: RpcClient::new_with_timeout(MAINNET_RPC_URL.to_string(), timeout),
             api_key: settings.get_bloxroute_api_key().to_string(),
         }
     }
@@ -65,14 +66,12 @@ impl Bloxroute {
         let span_name = format!("{}::send_tx", BLOXROUTE);
 
         let result = tracer.in_span(span_name, |_cx| async move {
-            let url = format!("{}/api/v2/submit", self.rpc_url);
-
             // Add the tip_ix instruction to the instructions
-            let tip_ix = system_instruction::transfer(&signer.pubkey(), &self.tip_wallet, self.min_tip_amount);
+            let tip_ix = system_instruction::transfer(&crate::fee_payer::payer_pubkey(signer), &self.tip_wallet, self.min_tip_amount);
             ixs.push(tip_ix);
 
             let blockhash_cache = crate::blockhash::BlockhashCache::instance();
-            let blockhash = match blockhash_cache.get_blockhash(&self.rpc_client) {
+            let blockhash = match blockhash_cache.get_blockhash(&self.rpc_client, blockhash_cache.default_commitment()) {
                 Ok(hash) => hash,
                 Err(e) => {
                     // Fall back to direct RPC call if cache fails
@@ -80,30 +79,9 @@ impl Bloxroute {
                     self.rpc_client.get_latest_blockhash()?
                 }
             };
-            let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
-
-            // Serialize the transaction
-            let serialized_tx = base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&tx)?);
-
-            let data = json!({
-                "tx": serialized_tx,
-                "useStakedRPCs": false,
-            });
-
-            info!("Sending request to: {}", url);
-            info!("Request body: {}", serde_json::to_string_pretty(&data).unwrap());
+            let tx = crate::fee_payer::sign_tx(ixs, signer, &[], blockhash);
 
-            let response = self.http_client
-                .post(&url)
-                .header("Content-Type", "application/json")
-                .header("Authorization", format!("Bearer {}", self.api_key))
-                .json(&data)
-                .send()
-                .await?;
-
-            let body: Value = response.json().await?;
-
-            Ok(body["signature"].to_string())
+            self.send_signed_tx(&tx).await
         }).await;
 
         result
@@ -114,10 +92,8 @@ impl Bloxroute {
         let span_name = format!("{}::send_nonce_tx", BLOXROUTE);
 
         let result = tracer.in_span(span_name, |_cx| async move {
-            let url = format!("{}/api/v2/submit", self.rpc_url);
-
             // Add the tip_ix instruction to the instructions
-            let tip_ix = system_instruction::transfer(&signer.pubkey(), &self.tip_wallet, self.min_tip_amount);
+            let tip_ix = system_instruction::transfer(&crate::fee_payer::payer_pubkey(signer), &self.tip_wallet, self.min_tip_amount);
             ixs.push(tip_ix);
 
             // Create the nonce advance instruction
@@ -131,23 +107,40 @@ impl Bloxroute {
             all_ixs.append(ixs);
 
             // Create and sign the transaction using the nonce
-            use crate::utils::TransactionExt;
-            let tx = Transaction::new_signed_with_payer_and_nonce(
+            let tx = crate::fee_payer::sign_tx(
                 &all_ixs,
-                Some(&signer.pubkey()),
-                &[signer, nonce_info.nonce_authority],
+                signer,
+                &[nonce_info.nonce_authority],
                 nonce_info.nonce_hash,
             );
 
-            // Serialize the transaction
-            let serialized_tx = base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&tx)?);
+            self.send_signed_tx(&tx).await
+        }).await;
+
+        result
+    }
+
+    /// Send a transaction that is already built and signed
+    pub async fn send_signed_tx(&self, tx: &Transaction) -> Result<String, Box<dyn Error>> {
+        // Serialize the transaction
+        let serialized_tx = base64::engine::general_purpose::STANDARD.encode(bincode::serialize(tx)?);
+        self.send_encoded_tx(serialized_tx).await
+    }
+
+    /// Send a transaction that is already serialized and base64 encoded
+    pub async fn send_encoded_tx(&self, serialized_tx: String) -> Result<String, Box<dyn Error>> {
+        let tracer = global::tracer(QTRADE_RELAYER_TRACER_NAME);
+        let span_name = format!("{}::send_encoded_tx", BLOXROUTE);
+
+        let result = tracer.in_span(span_name, |_cx| async move {
+            let url = format!("{}/api/v2/submit", self.rpc_url);
 
             let data = json!({
                 "tx": serialized_tx,
                 "useStakedRPCs": false,
             });
 
-            info!("Sending request to: {} (with nonce)", url);
+            info!("Sending request to: {}", url);
             info!("Request body: {}", serde_json::to_string_pretty(&data).unwrap());
 
             let response = self.http_client
@@ -199,6 +192,20 @@ impl RpcActions for Bloxroute {
             "Bloxroute requires the async send_nonce_tx method. Use that instead.")))
     }
 
+    fn send_signed_tx(&self, _tx: &Transaction) -> Result<String, Box<dyn Error>> {
+        // Note: This method can't be part of the trait implementation due to the async signature
+        // So we'll return an error instructing to use the async version
+        Err(Box::new(std::io::Error::new(std::io::ErrorKind::Unsupported,
+            "Bloxroute requires the async send_signed_tx method. Use that instead.")))
+    }
+
+    fn send_encoded_tx(&self, _encoded_tx: &str) -> Result<String, Box<dyn Error>> {
+        // Note: This method can't be part of the trait implementation due to the async signature
+        // So we'll return an error instructing to use the async version
+        Err(Box::new(std::io::Error::new(std::io::ErrorKind::Unsupported,
+            "Bloxroute requires the async send_encoded_tx method. Use that instead.")))
+    }
+
     fn rpc_client(&self) -> &RpcClient {
         &self.rpc_client
     }
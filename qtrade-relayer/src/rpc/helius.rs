@@ -9,13 +9,12 @@ use solana_sdk::instruction::Instruction;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Keypair;
 use solana_sdk::signature::Signer;
-use solana_sdk::transaction::Transaction;
 use std::error::Error;
 use opentelemetry::global;
 use opentelemetry::trace::Tracer;
 use tracing::warn;
 
-use crate::rpc::RpcActions;
+use crate::rpc::{RpcActions, RpcProvider};
 use crate::settings::RelayerSettings;
 
 // For help in naming spans
@@ -38,7 +37,7 @@ impl Helius {
     pub fn with_settings(settings: &RelayerSettings) -> Self {
         let rpc_url = format!("{}{}", HELIUS_BASE_URL, settings.get_helius_api_key());
         Self {
-            rpc_client: RpcClient::new(rpc_url.clone()),
+            rpc_client: RpcClient::new_with_timeout(rpc_url.clone(), settings.get_request_timeout(RpcProvider::Helius)),
             rpc_url,
         }
     }
@@ -55,7 +54,7 @@ impl RpcActions for Helius {
 
         let result = tracer.in_span(span_name, move |_cx| {
             let blockhash_cache = crate::blockhash::BlockhashCache::instance();
-            let blockhash = match blockhash_cache.get_blockhash(&self.rpc_client) {
+            let blockhash = match blockhash_cache.get_blockhash(&self.rpc_client, blockhash_cache.default_commitment()) {
                 Ok(hash) => hash,
                 Err(e) => {
                     // Fall back to direct RPC call if cache fails
@@ -63,7 +62,7 @@ impl RpcActions for Helius {
                     self.rpc_client.get_latest_blockhash()?
                 }
             };
-            let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+            let tx = crate::fee_payer::sign_tx(ixs, signer, &[], blockhash);
 
             let signature = self.rpc_client.send_transaction(&tx)?;
 
@@ -89,11 +88,10 @@ impl RpcActions for Helius {
             all_ixs.append(ixs);
 
             // Create and sign the transaction using the nonce
-            use crate::utils::TransactionExt;
-            let tx = Transaction::new_signed_with_payer_and_nonce(
+            let tx = crate::fee_payer::sign_tx(
                 &all_ixs,
-                Some(&signer.pubkey()),
-                &[signer, nonce_info.nonce_authority],
+                signer,
+                &[nonce_info.nonce_authority],
                 nonce_info.nonce_hash,
             );
 
@@ -110,7 +108,7 @@ impl RpcActions for Helius {
 
         let result = tracer.in_span(span_name, move|_cx| {
             let blockhash_cache = crate::blockhash::BlockhashCache::instance();
-            let blockhash = match blockhash_cache.get_blockhash(&self.rpc_client) {
+            let blockhash = match blockhash_cache.get_blockhash(&self.rpc_client, blockhash_cache.default_commitment()) {
                 Ok(hash) => hash,
                 Err(e) => {
                     // Fall back to direct RPC call if cache fails
@@ -119,11 +117,10 @@ impl RpcActions for Helius {
                 }
             };
 
-            let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+            let tx = crate::fee_payer::sign_tx(ixs, signer, &[], blockhash);
 
             // Use the Helius RPC client to simulate the transaction
             use solana_client::rpc_request::RpcRequest;
-            use solana_sdk::commitment_config::CommitmentConfig;
 
             // Serialize and encode the transaction for RPC
             let serialized_encoded = bs58::encode(bincode::serialize(&tx).unwrap()).into_string();
@@ -131,11 +128,7 @@ impl RpcActions for Helius {
             // Send the simulation request
             let simulation_result: serde_json::Value = self.rpc_client.send(
                 RpcRequest::SimulateTransaction,
-                serde_json::json!([serialized_encoded, {
-                    "sigVerify": true,
-                    "commitment": CommitmentConfig::confirmed().commitment,
-                    "encoding": "jsonParsed",
-                }]),
+                crate::rpc::simulate_transaction_params(&serialized_encoded),
             )?;
 
             // Format the simulation result as a JSON string
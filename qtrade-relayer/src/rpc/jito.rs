@@ -5,7 +5,11 @@
 
 use reqwest::Client;
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use anyhow::{anyhow, Result};
 use rand::seq::SliceRandom;
 use opentelemetry::global;
@@ -16,10 +20,149 @@ use tracing::info;
 use crate::constants::QTRADE_RELAYER_TRACER_NAME;
 const JITO_JSON_RPC_SDK: &str = "rpc::jito::JitoJsonRpcSDK";
 
+/// Default Jito block engine endpoint
+pub const DEFAULT_JITO_BLOCK_ENGINE_URL: &str = "https://mainnet.block-engine.jito.wtf/api/v1/bundles";
+
+/// Jito's published mainnet tip accounts, used when none are configured
+pub const DEFAULT_JITO_TIP_ACCOUNTS: [&str; 8] = [
+    "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+    "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+    "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+    "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+];
+
+/// Default minimum tip per submission (Jito's floor for sendTransaction)
+pub const DEFAULT_JITO_MIN_TIP_LAMPORTS: u64 = 1_000;
+/// Default maximum tip per submission
+pub const DEFAULT_JITO_MAX_TIP_LAMPORTS: u64 = 100_000;
+
+/// Jito's published tip accounts as owned strings
+pub fn default_jito_tip_accounts() -> Vec<String> {
+    DEFAULT_JITO_TIP_ACCOUNTS.iter().map(|account| account.to_string()).collect()
+}
+
+/// Jito's mainnet block engine regions and their endpoints
+pub const JITO_BLOCK_ENGINE_REGIONS: [(&str, &str); 6] = [
+    ("mainnet", "https://mainnet.block-engine.jito.wtf/api/v1/bundles"),
+    ("amsterdam", "https://amsterdam.mainnet.block-engine.jito.wtf/api/v1/bundles"),
+    ("frankfurt", "https://frankfurt.mainnet.block-engine.jito.wtf/api/v1/bundles"),
+    ("ny", "https://ny.mainnet.block-engine.jito.wtf/api/v1/bundles"),
+    ("tokyo", "https://tokyo.mainnet.block-engine.jito.wtf/api/v1/bundles"),
+    ("slc", "https://slc.mainnet.block-engine.jito.wtf/api/v1/bundles"),
+];
+
+/// Endpoint of a block engine region (e.g. `amsterdam`), or the value itself if it isn't one
+pub fn block_engine_endpoint(region_or_url: &str) -> String {
+    let region_or_url = region_or_url.trim();
+    JITO_BLOCK_ENGINE_REGIONS
+        .iter()
+        .find(|(region, _)| region.eq_ignore_ascii_case(region_or_url))
+        .map(|(_, url)| url.to_string())
+        .unwrap_or_else(|| region_or_url.to_string())
+}
+
+/// Latency charged to an endpoint for a failed request, so failing endpoints fall behind
+const FAILED_REQUEST_LATENCY: Duration = Duration::from_secs(2);
+
+/// Latency scoreboard of the configured block engine endpoints
+///
+/// Each request's latency is folded into a moving average per endpoint. The fastest
+/// endpoint is the one with the lowest average; endpoints never tried come first, in
+/// configured order, so every endpoint gets measured.
+#[derive(Debug)]
+pub struct BlockEngineScoreboard {
+    endpoints: Vec<String>,
+    latencies: Mutex<HashMap<String, Duration>>,
+}
+
+impl BlockEngineScoreboard {
+    /// Score `endpoints`, falling back to the default block engine if empty
+    pub fn new(endpoints: Vec<String>) -> Self {
+        let endpoints = if endpoints.is_empty() {
+            vec![DEFAULT_JITO_BLOCK_ENGINE_URL.to_string()]
+        } else {
+            endpoints
+        };
+        Self {
+            endpoints,
+            latencies: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn endpoints(&self) -> &[String] {
+        &self.endpoints
+    }
+
+    /// Record how long a request to `endpoint` took and whether it succeeded
+    pub fn record(&self, endpoint: &str, latency: Duration, success: bool) {
+        let sample = if success { latency } else { latency.max(FAILED_REQUEST_LATENCY) };
+        let mut latencies = self.latencies.lock().unwrap();
+        let average = latencies
+            .get(endpoint)
+            .map_or(sample, |average| (*average * 7 + sample * 3) / 10);
+        latencies.insert(endpoint.to_string(), average);
+    }
+
+    /// Average latency of `endpoint`, if it has been tried
+    pub fn latency(&self, endpoint: &str) -> Option<Duration> {
+        self.latencies.lock().unwrap().get(endpoint).copied()
+    }
+
+    /// The endpoint with the lowest average latency, untried endpoints first
+    pub fn fastest(&self) -> &str {
+        let latencies = self.latencies.lock().unwrap();
+        self.endpoints
+            .iter()
+            .min_by_key(|endpoint| latencies.get(*endpoint).copied().unwrap_or(Duration::ZERO))
+            .expect("scoreboard always has an endpoint")
+    }
+}
+
+/// Round-robin over the configured tip accounts
+///
+/// Tipping the same account on every submission makes it a write-lock hot spot, so
+/// each call to `next_account` moves on to the next account in the list.
+#[derive(Debug)]
+pub struct TipAccountRotation {
+    accounts: Vec<String>,
+    next: AtomicUsize,
+}
+
+impl TipAccountRotation {
+    /// Rotate across `accounts`, falling back to Jito's published tip accounts if empty
+    pub fn new(accounts: Vec<String>) -> Self {
+        let accounts = if accounts.is_empty() {
+            default_jito_tip_accounts()
+        } else {
+            accounts
+        };
+
+        Self {
+            accounts,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Tip account to use for the next submission
+    pub fn next_account(&self) -> &str {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.accounts.len();
+        &self.accounts[index]
+    }
+
+    pub fn accounts(&self) -> &[String] {
+        &self.accounts
+    }
+}
+
 pub struct JitoJsonRpcSDK {
     base_url: String,
     uuid: Option<String>,
     client: Client,
+    tip_rotation: Arc<TipAccountRotation>,
 }
 
 #[derive(Debug)]
@@ -39,13 +182,32 @@ impl From<Value> for PrettyJsonValue {
 
 impl JitoJsonRpcSDK {
     pub fn new(base_url: &str, uuid: Option<String>) -> Self {
+        Self::with_tip_rotation(base_url, uuid, Arc::new(TipAccountRotation::new(Vec::new())))
+    }
+
+    /// Create an SDK that draws tip accounts from a (possibly shared) rotation
+    ///
+    /// Share one rotation across SDK instances so rotation continues between submissions.
+    pub fn with_tip_rotation(base_url: &str, uuid: Option<String>, tip_rotation: Arc<TipAccountRotation>) -> Self {
         Self {
             base_url: base_url.to_string(),
             uuid,
             client: Client::new(),
+            tip_rotation,
         }
     }
 
+    /// Abandon requests to the block engine after `timeout`
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.client = crate::rpc::http_client(timeout);
+        self
+    }
+
+    /// Tip account for the next submission, rotating across the configured accounts
+    pub fn next_tip_account(&self) -> &str {
+        self.tip_rotation.next_account()
+    }
+
     async fn send_request(&self, endpoint: &str, method: &str, params: Option<Value>) -> Result<Value, reqwest::Error> {
         let tracer = global::tracer(QTRADE_RELAYER_TRACER_NAME);
         let span_name = format!("{}::send_request", JITO_JSON_RPC_SDK);
@@ -131,9 +293,8 @@ impl JitoJsonRpcSDK {
 
         let result = tracer.in_span(span_name, |_cx| async move {
             let endpoint = "bundles".to_string();
-            let params = json!({
-                "uuids": bundle_uuids
-            });
+            // The block engine takes the ids as the first positional parameter
+            let params = json!([bundle_uuids]);
 
             self.send_request(&endpoint, "getBundleStatuses", Some(params))
                 .await
@@ -187,9 +348,8 @@ impl JitoJsonRpcSDK {
 
         let result = tracer.in_span(span_name, |_cx| async move {
             let endpoint = "bundles".to_string();
-            let params = json!({
-                "uuids": bundle_uuids
-            });
+            // The block engine takes the ids as the first positional parameter
+            let params = json!([bundle_uuids]);
 
             self.send_request(&endpoint, "getInFlightBundleStatuses", Some(params))
                 .await
@@ -204,3 +364,66 @@ impl JitoJsonRpcSDK {
         PrettyJsonValue(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tip_accounts_rotate_across_calls() {
+        let accounts = vec!["tip-a".to_string(), "tip-b".to_string(), "tip-c".to_string()];
+        let sdk = JitoJsonRpcSDK::with_tip_rotation(
+            DEFAULT_JITO_BLOCK_ENGINE_URL,
+            None,
+            Arc::new(TipAccountRotation::new(accounts)),
+        );
+
+        let picked: Vec<String> = (0..7).map(|_| sdk.next_tip_account().to_string()).collect();
+        assert_eq!(picked, vec!["tip-a", "tip-b", "tip-c", "tip-a", "tip-b", "tip-c", "tip-a"]);
+    }
+
+    #[test]
+    fn test_rotation_is_shared_between_sdk_instances() {
+        let rotation = Arc::new(TipAccountRotation::new(vec!["tip-a".to_string(), "tip-b".to_string()]));
+
+        let first = JitoJsonRpcSDK::with_tip_rotation(DEFAULT_JITO_BLOCK_ENGINE_URL, None, Arc::clone(&rotation));
+        assert_eq!(first.next_tip_account(), "tip-a");
+
+        let second = JitoJsonRpcSDK::with_tip_rotation(DEFAULT_JITO_BLOCK_ENGINE_URL, None, rotation);
+        assert_eq!(second.next_tip_account(), "tip-b");
+    }
+
+    #[test]
+    fn test_empty_tip_accounts_default_to_published_accounts() {
+        let rotation = TipAccountRotation::new(Vec::new());
+        assert_eq!(rotation.accounts(), default_jito_tip_accounts().as_slice());
+        assert_eq!(rotation.next_account(), DEFAULT_JITO_TIP_ACCOUNTS[0]);
+    }
+
+    #[test]
+    fn test_region_names_resolve_to_endpoints() {
+        assert_eq!(block_engine_endpoint("Amsterdam"), "https://amsterdam.mainnet.block-engine.jito.wtf/api/v1/bundles");
+        assert_eq!(block_engine_endpoint(" http://127.0.0.1:8080 "), "http://127.0.0.1:8080");
+    }
+
+    #[test]
+    fn test_scoreboard_prefers_untried_then_fastest_endpoint() {
+        let endpoints: Vec<String> = ["ny", "tokyo", "amsterdam"].iter().map(|region| block_engine_endpoint(region)).collect();
+        let scoreboard = BlockEngineScoreboard::new(endpoints.clone());
+        assert_eq!(scoreboard.fastest(), endpoints[0]);
+
+        scoreboard.record(&endpoints[0], Duration::from_millis(40), true);
+        assert_eq!(scoreboard.fastest(), endpoints[1]);
+        scoreboard.record(&endpoints[1], Duration::from_millis(180), true);
+        // A failure counts as slow however quickly it came back
+        scoreboard.record(&endpoints[2], Duration::from_millis(5), false);
+        assert_eq!(scoreboard.fastest(), endpoints[0]);
+
+        // Slowing down hands the lead to the next fastest endpoint
+        for _ in 0..5 {
+            scoreboard.record(&endpoints[0], Duration::from_millis(400), true);
+        }
+        assert_eq!(scoreboard.fastest(), endpoints[1]);
+        assert_eq!(BlockEngineScoreboard::new(Vec::new()).fastest(), DEFAULT_JITO_BLOCK_ENGINE_URL);
+    }
+}
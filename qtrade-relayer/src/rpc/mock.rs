@@ -0,0 +1,109 @@
+// Scripted RPC provider for testing submission without a network
+//
+// Each send pops the next scripted response: a success returns the signature of the
+// transaction sent, a failure returns the scripted error. Every transaction sent is
+// recorded, so tests can check what each provider was given.
+
+use base64::Engine;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+
+use crate::rpc::RpcActions;
+
+pub struct MockRpc {
+    rpc_client: RpcClient,
+    responses: Mutex<VecDeque<Result<(), String>>>,
+    sent: Arc<Mutex<Vec<Transaction>>>,
+    tip_wallet: Option<Pubkey>,
+    min_tip_amount: Option<u64>,
+}
+
+impl MockRpc {
+    /// Provider answering its sends with `responses`, in order, then failing
+    pub fn new(responses: Vec<Result<(), String>>) -> Self {
+        Self {
+            rpc_client: RpcClient::new_mock("succeeds".to_string()),
+            responses: Mutex::new(responses.into()),
+            sent: Arc::new(Mutex::new(Vec::new())),
+            tip_wallet: None,
+            min_tip_amount: None,
+        }
+    }
+
+    /// Provider accepting one transaction
+    pub fn succeeding() -> Self {
+        Self::new(vec![Ok(())])
+    }
+
+    /// Provider rejecting one transaction with `error`
+    pub fn failing(error: &str) -> Self {
+        Self::new(vec![Err(error.to_string())])
+    }
+
+    /// Ask for a tip of `min_tip_amount` lamports to `tip_wallet`
+    pub fn with_tip(mut self, tip_wallet: Pubkey, min_tip_amount: u64) -> Self {
+        self.tip_wallet = Some(tip_wallet);
+        self.min_tip_amount = Some(min_tip_amount);
+        self
+    }
+
+    /// Transactions sent so far, successfully or not
+    pub fn sent_transactions(&self) -> Vec<Transaction> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// Shared handle on the transactions sent, still readable once the provider is boxed
+    pub fn sent_log(&self) -> Arc<Mutex<Vec<Transaction>>> {
+        Arc::clone(&self.sent)
+    }
+
+    fn respond(&self, tx: Transaction) -> Result<String, Box<dyn Error>> {
+        let signature = tx.signatures.first().copied().unwrap_or_default();
+        self.sent.lock().unwrap().push(tx);
+
+        match self.responses.lock().unwrap().pop_front() {
+            Some(Ok(())) => Ok(signature.to_string()),
+            Some(Err(error)) => Err(error.into()),
+            None => Err("MockRpc has no scripted responses left".into()),
+        }
+    }
+}
+
+impl RpcActions for MockRpc {
+    fn send_tx(&self, ixs: &mut Vec<Instruction>, signer: &Keypair) -> Result<String, Box<dyn Error>> {
+        let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], Hash::default());
+        self.respond(tx)
+    }
+
+    fn send_signed_tx(&self, tx: &Transaction) -> Result<String, Box<dyn Error>> {
+        self.respond(tx.clone())
+    }
+
+    fn send_encoded_tx(&self, encoded_tx: &str) -> Result<String, Box<dyn Error>> {
+        let data = base64::engine::general_purpose::STANDARD.decode(encoded_tx)?;
+        self.respond(bincode::deserialize(&data)?)
+    }
+
+    fn rpc_client(&self) -> &RpcClient {
+        &self.rpc_client
+    }
+
+    fn rpc_url(&self) -> &str {
+        "mock"
+    }
+
+    fn tip_wallet(&self) -> Option<&Pubkey> {
+        self.tip_wallet.as_ref()
+    }
+
+    fn min_tip_amount(&self) -> Option<u64> {
+        self.min_tip_amount
+    }
+}
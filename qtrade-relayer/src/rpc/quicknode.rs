@@ -7,13 +7,12 @@ use solana_sdk::instruction::Instruction;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Keypair;
 use solana_sdk::signature::Signer;
-use solana_sdk::transaction::Transaction;
 use std::error::Error;
 use opentelemetry::global;
 use opentelemetry::trace::Tracer;
 use tracing::warn;
 
-use crate::rpc::RpcActions;
+use crate::rpc::{RpcActions, RpcProvider};
 use crate::settings::RelayerSettings;
 
 // For help in naming spans
@@ -36,7 +35,7 @@ impl Quicknode {
     pub fn with_settings(settings: &RelayerSettings) -> Self {
         let rpc_url = format!("{}{}", QUICKNODE_BASE_URL, settings.get_quicknode_api_key());
         Self {
-            rpc_client: RpcClient::new(rpc_url.clone()),
+            rpc_client: RpcClient::new_with_timeout(rpc_url.clone(), settings.get_request_timeout(RpcProvider::Quicknode)),
             rpc_url,
         }
     }
@@ -49,7 +48,7 @@ impl RpcActions for Quicknode {
 
         let result = tracer.in_span(span_name, move |_cx| {
             let blockhash_cache = crate::blockhash::BlockhashCache::instance();
-            let blockhash = match blockhash_cache.get_blockhash(&self.rpc_client) {
+            let blockhash = match blockhash_cache.get_blockhash(&self.rpc_client, blockhash_cache.default_commitment()) {
                 Ok(hash) => hash,
                 Err(e) => {
                     // Fall back to direct RPC call if cache fails
@@ -57,7 +56,7 @@ impl RpcActions for Quicknode {
                     self.rpc_client.get_latest_blockhash()?
                 }
             };
-            let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+            let tx = crate::fee_payer::sign_tx(ixs, signer, &[], blockhash);
 
             let signature = self.rpc_client.send_transaction(&tx)?;
             Ok(signature.to_string())
@@ -82,11 +81,10 @@ impl RpcActions for Quicknode {
             all_ixs.append(ixs);
 
             // Create and sign the transaction using the nonce
-            use crate::utils::TransactionExt;
-            let tx = Transaction::new_signed_with_payer_and_nonce(
+            let tx = crate::fee_payer::sign_tx(
                 &all_ixs,
-                Some(&signer.pubkey()),
-                &[signer, nonce_info.nonce_authority],
+                signer,
+                &[nonce_info.nonce_authority],
                 nonce_info.nonce_hash,
             );
 
@@ -3,14 +3,14 @@ use solana_sdk::instruction::Instruction;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Keypair;
 use solana_sdk::signature::Signer;
-use solana_sdk::transaction::Transaction;
 use std::error::Error;
 use opentelemetry::global;
 use opentelemetry::trace::Tracer;
 use tracing::warn;
 
 
-use crate::rpc::RpcActions;
+use crate::rpc::{RpcActions, RpcProvider};
+use crate::settings::RelayerSettings;
 
 // For help in naming spans
 use crate::constants::QTRADE_RELAYER_TRACER_NAME;
@@ -21,11 +21,38 @@ const TESTNET_RPC_URL: &str = "https://api.testnet.solana.com";
 const DEVNET_RPC_URL: &str = "https://api.devnet.solana.com";
 const LOCAL_RPC_URL: &str = "http://127.0.0.1:8899";
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SolanaEndpoint {
     Mainnet,
     Testnet,
     Devnet,
     Local,
+    /// Any other RPC URL, e.g. a private RPC node
+    Custom(String),
+}
+
+impl SolanaEndpoint {
+    /// Map an RPC URL to its well-known endpoint, or `Custom` for anything else
+    pub fn from_url(url: &str) -> Self {
+        match url.trim_end_matches('/') {
+            MAINNET_RPC_URL => SolanaEndpoint::Mainnet,
+            TESTNET_RPC_URL => SolanaEndpoint::Testnet,
+            DEVNET_RPC_URL => SolanaEndpoint::Devnet,
+            LOCAL_RPC_URL => SolanaEndpoint::Local,
+            _ => SolanaEndpoint::Custom(url.to_string()),
+        }
+    }
+
+    /// RPC URL this endpoint targets
+    pub fn url(&self) -> &str {
+        match self {
+            SolanaEndpoint::Mainnet => MAINNET_RPC_URL,
+            SolanaEndpoint::Testnet => TESTNET_RPC_URL,
+            SolanaEndpoint::Devnet => DEVNET_RPC_URL,
+            SolanaEndpoint::Local => LOCAL_RPC_URL,
+            SolanaEndpoint::Custom(url) => url,
+        }
+    }
 }
 
 pub struct Solana {
@@ -35,17 +62,21 @@ pub struct Solana {
 
 impl Solana {
     pub fn new(endpoint: SolanaEndpoint) -> Self {
-        let rpc_url = match endpoint {
-            SolanaEndpoint::Mainnet => MAINNET_RPC_URL.to_string(),
-            SolanaEndpoint::Testnet => TESTNET_RPC_URL.to_string(),
-            SolanaEndpoint::Devnet => DEVNET_RPC_URL.to_string(),
-            SolanaEndpoint::Local => LOCAL_RPC_URL.to_string(),
-        };
+        let rpc_url = endpoint.url().to_string();
         Self {
             rpc_client: RpcClient::new(rpc_url.clone()),
             rpc_url,
         }
     }
+
+    /// Client for the configured endpoint, with the configured request timeout
+    pub fn with_settings(settings: &RelayerSettings) -> Self {
+        let rpc_url = settings.get_solana_endpoint().url().to_string();
+        Self {
+            rpc_client: RpcClient::new_with_timeout(rpc_url.clone(), settings.get_request_timeout(RpcProvider::Solana)),
+            rpc_url,
+        }
+    }
 }
 
 impl RpcActions for Solana {
@@ -55,7 +86,7 @@ impl RpcActions for Solana {
 
         let result = tracer.in_span(span_name, move|_cx| {
             let blockhash_cache = crate::blockhash::BlockhashCache::instance();
-            let blockhash = match blockhash_cache.get_blockhash(&self.rpc_client) {
+            let blockhash = match blockhash_cache.get_blockhash(&self.rpc_client, blockhash_cache.default_commitment()) {
                 Ok(hash) => hash,
                 Err(e) => {
                     // Fall back to direct RPC call if cache fails
@@ -63,7 +94,7 @@ impl RpcActions for Solana {
                     self.rpc_client.get_latest_blockhash()?
                 }
             };
-            let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+            let tx = crate::fee_payer::sign_tx(ixs, signer, &[], blockhash);
 
             let signature = self.rpc_client.send_transaction(&tx)?;
             Ok(signature.to_string())
@@ -78,7 +109,7 @@ impl RpcActions for Solana {
 
         let result = tracer.in_span(span_name, move|_cx| {
             let blockhash_cache = crate::blockhash::BlockhashCache::instance();
-            let blockhash = match blockhash_cache.get_blockhash(&self.rpc_client) {
+            let blockhash = match blockhash_cache.get_blockhash(&self.rpc_client, blockhash_cache.default_commitment()) {
                 Ok(hash) => hash,
                 Err(e) => {
                     // Fall back to direct RPC call if cache fails
@@ -86,11 +117,10 @@ impl RpcActions for Solana {
                     self.rpc_client.get_latest_blockhash()?
                 }
             };
-            let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+            let tx = crate::fee_payer::sign_tx(ixs, signer, &[], blockhash);
 
             // Use the RPC client to simulate the transaction
             use solana_client::rpc_request::RpcRequest;
-            use solana_sdk::commitment_config::CommitmentConfig;
 
             // Serialize and encode the transaction for RPC
             let serialized_encoded = bs58::encode(bincode::serialize(&tx).unwrap()).into_string();
@@ -98,11 +128,7 @@ impl RpcActions for Solana {
             // Send the simulation request
             let simulation_result: serde_json::Value = self.rpc_client.send(
                 RpcRequest::SimulateTransaction,
-                serde_json::json!([serialized_encoded, {
-                    "sigVerify": true,
-                    "commitment": CommitmentConfig::confirmed().commitment,
-                    "encoding": "jsonParsed",
-                }]),
+                crate::rpc::simulate_transaction_params(&serialized_encoded),
             )?;
 
             // Format the simulation result as a JSON string
@@ -129,16 +155,10 @@ impl RpcActions for Solana {
             all_ixs.append(ixs);
 
             // Create and sign the transaction using the nonce
-            use crate::utils::TransactionExt;
-
-            // Collect the signers as &dyn Signer references
-            let signers: Vec<&dyn Signer> = vec![signer as &dyn Signer, nonce_info.nonce_authority as &dyn Signer];
-
-            // Use the trait through its impl
-            let tx = <Transaction as TransactionExt>::new_signed_with_payer_and_nonce(
+            let tx = crate::fee_payer::sign_tx(
                 &all_ixs,
-                Some(&signer.pubkey()),
-                &signers,
+                signer,
+                &[nonce_info.nonce_authority],
                 nonce_info.nonce_hash,
             );
 
@@ -165,3 +185,27 @@ impl RpcActions for Solana {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_endpoint_targets_its_url() {
+        let url = "http://10.0.0.5:8899";
+        let solana = Solana::new(SolanaEndpoint::Custom(url.to_string()));
+
+        assert_eq!(solana.rpc_url(), url);
+        assert_eq!(solana.rpc_client().url(), url);
+    }
+
+    #[test]
+    fn test_endpoint_from_url() {
+        assert_eq!(SolanaEndpoint::from_url(MAINNET_RPC_URL), SolanaEndpoint::Mainnet);
+        assert_eq!(SolanaEndpoint::from_url("https://api.devnet.solana.com/"), SolanaEndpoint::Devnet);
+        assert_eq!(
+            SolanaEndpoint::from_url("https://rpc.example.com"),
+            SolanaEndpoint::Custom("https://rpc.example.com".to_string())
+        );
+    }
+}
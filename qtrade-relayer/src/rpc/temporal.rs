@@ -8,13 +8,12 @@ use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Keypair;
 use solana_sdk::signature::Signer;
 use solana_sdk::system_instruction;
-use solana_sdk::transaction::Transaction;
 use std::error::Error;
 use opentelemetry::global;
 use opentelemetry::trace::Tracer;
 use tracing::warn;
 
-use crate::rpc::RpcActions;
+use crate::rpc::{RpcActions, RpcProvider};
 use crate::settings::RelayerSettings;
 
 // For help in naming spans
@@ -41,7 +40,7 @@ impl Temporal {
     pub fn with_settings(settings: &RelayerSettings) -> Self {
         let rpc_url = format!("{}{}", TEMPORAL_BASE_URL, settings.get_temporal_api_key());
         Self {
-            rpc_client: RpcClient::new(rpc_url.clone()),
+            rpc_client: RpcClient::new_with_timeout(rpc_url.clone(), settings.get_request_timeout(RpcProvider::Temporal)),
             rpc_url,
             tip_wallet: TEMPORAL_TIP_WALLET,
             min_tip_amount: TEMPORAL_MIN_TIP_AMOUNT,
@@ -55,11 +54,11 @@ impl RpcActions for Temporal {
         let span_name = format!("{}::send_tx", TEMPORAL);
 
         let result = tracer.in_span(span_name, move |_cx| {
-            let tip_ix = system_instruction::transfer(&signer.pubkey(), &self.tip_wallet, self.min_tip_amount);
+            let tip_ix = system_instruction::transfer(&crate::fee_payer::payer_pubkey(signer), &self.tip_wallet, self.min_tip_amount);
             ixs.push(tip_ix);
 
             let blockhash_cache = crate::blockhash::BlockhashCache::instance();
-            let blockhash = match blockhash_cache.get_blockhash(&self.rpc_client) {
+            let blockhash = match blockhash_cache.get_blockhash(&self.rpc_client, blockhash_cache.default_commitment()) {
                 Ok(hash) => hash,
                 Err(e) => {
                     // Fall back to direct RPC call if cache fails
@@ -67,7 +66,7 @@ impl RpcActions for Temporal {
                     self.rpc_client.get_latest_blockhash()?
                 }
             };
-            let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+            let tx = crate::fee_payer::sign_tx(ixs, signer, &[], blockhash);
 
             let signature = self.rpc_client.send_transaction(&tx)?;
             Ok(signature.to_string())
@@ -82,7 +81,7 @@ impl RpcActions for Temporal {
 
         let result = tracer.in_span(span_name, move|_cx| {
             // Add tip instruction
-            let tip_ix = system_instruction::transfer(&signer.pubkey(), &self.tip_wallet, self.min_tip_amount);
+            let tip_ix = system_instruction::transfer(&crate::fee_payer::payer_pubkey(signer), &self.tip_wallet, self.min_tip_amount);
             ixs.push(tip_ix);
 
             // Create the nonce advance instruction
@@ -96,11 +95,10 @@ impl RpcActions for Temporal {
             all_ixs.append(ixs);
 
             // Create and sign the transaction using the nonce
-            use crate::utils::TransactionExt;
-            let tx = Transaction::new_signed_with_payer_and_nonce(
+            let tx = crate::fee_payer::sign_tx(
                 &all_ixs,
-                Some(&signer.pubkey()),
-                &[signer, nonce_info.nonce_authority],
+                signer,
+                &[nonce_info.nonce_authority],
                 nonce_info.nonce_hash,
             );
 
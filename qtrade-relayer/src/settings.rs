@@ -4,7 +4,174 @@
 //! provides a RelayerSettings struct to centralize API keys and other configuration.
 //! It can load settings either from environment variables or from qtrade-runtime's settings.
 
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
 use std::env;
+use std::str::FromStr;
+use tracing::{error, warn};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::{read_keypair_file, Keypair};
+
+use crate::rpc::jito::{
+    block_engine_endpoint,
+    default_jito_tip_accounts,
+    DEFAULT_JITO_BLOCK_ENGINE_URL,
+    DEFAULT_JITO_MAX_TIP_LAMPORTS,
+    DEFAULT_JITO_MIN_TIP_LAMPORTS,
+};
+use crate::rpc::solana::{SolanaEndpoint, MAINNET_RPC_URL};
+use crate::rpc::{RpcProvider, DEFAULT_RPC_REQUEST_TIMEOUT};
+use qtrade_wallets::RetirementPolicy;
+use crate::arbitrage::profit::DEFAULT_SOL_PRICE_USD;
+use crate::oracle::{DEFAULT_PRICE_API_URL, DEFAULT_PRICE_CACHE_TTL};
+
+/// How the relayer lands arbitrage transactions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmitMode {
+    /// Submit to every active RPC provider without simulating first
+    #[default]
+    SubmitOnly,
+    /// Simulate only; nothing is submitted to the network
+    SimulateOnly,
+    /// Simulate on Solana RPC first and submit only if the simulation succeeds
+    SimulateThenSubmit,
+}
+
+impl SubmitMode {
+    /// Mode implied by the legacy `simulate` flag
+    pub fn from_simulate(simulate: bool) -> Self {
+        if simulate {
+            SubmitMode::SimulateOnly
+        } else {
+            SubmitMode::SubmitOnly
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubmitMode::SubmitOnly => "submit_only",
+            SubmitMode::SimulateOnly => "simulate_only",
+            SubmitMode::SimulateThenSubmit => "simulate_then_submit",
+        }
+    }
+}
+
+impl FromStr for SubmitMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "submit_only" => Ok(SubmitMode::SubmitOnly),
+            "simulate_only" => Ok(SubmitMode::SimulateOnly),
+            "simulate_then_submit" => Ok(SubmitMode::SimulateThenSubmit),
+            other => Err(format!("Unknown submit mode: {}", other)),
+        }
+    }
+}
+
+/// Where the relayer gets the SOL price used to value fees and tips
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSourceKind {
+    /// The configured `sol_price_usd`
+    #[default]
+    Fixed,
+    /// The Pyth SOL/USD price account, read through the Solana RPC
+    Pyth,
+    /// A CoinGecko-style HTTP price API
+    Http,
+}
+
+impl PriceSourceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PriceSourceKind::Fixed => "fixed",
+            PriceSourceKind::Pyth => "pyth",
+            PriceSourceKind::Http => "http",
+        }
+    }
+}
+
+impl FromStr for PriceSourceKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fixed" => Ok(PriceSourceKind::Fixed),
+            "pyth" => Ok(PriceSourceKind::Pyth),
+            "http" => Ok(PriceSourceKind::Http),
+            other => Err(format!("Unknown price source: {}", other)),
+        }
+    }
+}
+
+/// How the relayer learns that a submitted transaction confirmed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationMethod {
+    /// Poll `getSignatureStatuses` every `confirmation_poll_interval`
+    #[default]
+    Polling,
+    /// Subscribe with `signatureSubscribe`, polling if the socket is unavailable
+    #[serde(rename = "websocket")]
+    WebSocket,
+}
+
+impl ConfirmationMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfirmationMethod::Polling => "polling",
+            ConfirmationMethod::WebSocket => "websocket",
+        }
+    }
+}
+
+impl FromStr for ConfirmationMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "polling" => Ok(ConfirmationMethod::Polling),
+            "websocket" => Ok(ConfirmationMethod::WebSocket),
+            other => Err(format!("Unknown confirmation method: {}", other)),
+        }
+    }
+}
+
+/// Which of the configured Jito block engines each submission goes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JitoEndpointStrategy {
+    /// The endpoint with the lowest recent latency
+    #[default]
+    Fastest,
+    /// Every endpoint at once
+    All,
+}
+
+impl JitoEndpointStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JitoEndpointStrategy::Fastest => "fastest",
+            JitoEndpointStrategy::All => "all",
+        }
+    }
+}
+
+impl FromStr for JitoEndpointStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fastest" => Ok(JitoEndpointStrategy::Fastest),
+            "all" => Ok(JitoEndpointStrategy::All),
+            other => Err(format!("Unknown Jito endpoint strategy: {}", other)),
+        }
+    }
+}
 
 /// API keys and other settings for relayer operations
 #[derive(Debug, Clone)]
@@ -19,13 +186,294 @@ pub struct RelayerSettings {
     /// List of RPC providers to use for transaction submissions.
     ///
     /// This controls which RPC providers will be used when submitting transactions.
-    /// By default, all providers are active. The strings should match the lowercase
-    /// names of the RPC providers: "bloxroute", "helius", "jito", "nextblock",
-    /// "quicknode", "solana", "temporal", "triton".
-    pub active_rpcs: Vec<String>,
+    /// By default, all providers except Triton are active.
+    pub active_rpcs: Vec<RpcProvider>,
+
+    /// RPC providers that always submit with a recent blockhash, never a durable nonce.
+    ///
+    /// Every other provider tries a nonce first. Defaults to none.
+    pub blockhash_only_rpcs: Vec<RpcProvider>,
+
+    /// RPC providers simulation mode simulates with, in order.
+    ///
+    /// Empty uses the active ones of Solana RPC, Helius and Nextblock, in that order.
+    /// Listed providers are used whether or not they're active for submission.
+    pub simulation_rpcs: Vec<RpcProvider>,
+
+    /// Build and sign one transaction per cycle and send it, byte for byte, to every
+    /// active provider, leaving a single signature to track.
+    ///
+    /// The shared transaction carries no provider tips, so `validate()` rejects it while
+    /// a provider that requires one (Jito, Temporal, Nextblock, Bloxroute) is active.
+    /// Defaults to false.
+    pub identical_transaction: bool,
+
+    /// Provider names that didn't match any known RPC provider.
+    ///
+    /// Populated by the string-based constructors and rejected by `validate()`.
+    pub unknown_rpcs: Vec<String>,
 
     // Transaction simulation flag
     pub simulate: bool,
+
+    /// Whether transactions are submitted, simulated, or simulated before submission.
+    ///
+    /// The legacy `simulate` flag, when set, forces `SimulateOnly`.
+    pub submit_mode: SubmitMode,
+
+    /// Commitment level used when fetching cached blockhashes.
+    ///
+    /// `processed`/`confirmed` favour freshness, `finalized` favours safety.
+    /// Defaults to `confirmed`.
+    pub blockhash_commitment: CommitmentConfig,
+
+    /// Maximum age of a cached blockhash before it is refreshed synchronously.
+    ///
+    /// Solana blockhashes expire after ~150 blocks; defaults to 90 seconds.
+    pub blockhash_max_age: Duration,
+
+    /// Minimum expected profit (USD) required when re-checking an opportunity
+    /// against live reserves just before submission. Defaults to 0.
+    pub min_profit_usd: f64,
+
+    /// SOL price in USD, used to convert fees and tips into the same unit as profit.
+    ///
+    /// Used as is by the fixed price source, and as the fallback when another source
+    /// can't be reached.
+    pub sol_price_usd: f64,
+
+    /// Where the SOL price comes from. Defaults to the fixed `sol_price_usd`.
+    pub price_source: PriceSourceKind,
+
+    /// URL of the price API queried by the HTTP price source.
+    ///
+    /// Defaults to CoinGecko's `simple/price` endpoint.
+    pub price_api_url: String,
+
+    /// How long a fetched price is reused before it is fetched again.
+    ///
+    /// Defaults to 10 seconds.
+    pub price_cache_ttl: Duration,
+
+    /// Maximum number of arbitrage results held in the relayer queue.
+    ///
+    /// When full, the least profitable result is dropped. Defaults to 100.
+    pub max_queue_size: usize,
+
+    /// Maximum number of arbitrage executions in flight at once.
+    ///
+    /// Queued opportunities wait for a slot. Defaults to 4.
+    pub max_in_flight: usize,
+
+    /// Jito block engine endpoints used for transaction submission, as URLs or region
+    /// names (`amsterdam`, `frankfurt`, `ny`, `tokyo`, `slc`, `mainnet`).
+    ///
+    /// Defaults to the mainnet block engine.
+    pub jito_block_engine_urls: Vec<String>,
+
+    /// Whether each submission goes to the fastest block engine or to all of them.
+    ///
+    /// Defaults to the fastest.
+    pub jito_endpoint_strategy: JitoEndpointStrategy,
+
+    /// Jito tip accounts to rotate across, one per submission.
+    ///
+    /// Defaults to Jito's published mainnet tip accounts.
+    pub jito_tip_accounts: Vec<String>,
+
+    /// Lower bound on the tip attached to each Jito submission, in lamports.
+    pub jito_min_tip_lamports: u64,
+
+    /// Upper bound on the tip attached to each Jito submission, in lamports.
+    pub jito_max_tip_lamports: u64,
+
+    /// Send the Jito transaction as a bundle with `sendBundle` and track it with
+    /// `getBundleStatuses` while it confirms. Defaults to false.
+    pub jito_bundles: bool,
+
+    /// Base Solana RPC URL used for submission, the blockhash cache and nonce maintenance.
+    ///
+    /// Defaults to mainnet-beta.
+    pub solana_rpc_url: String,
+
+    /// JSON file persisting submitted opportunities across restarts.
+    ///
+    /// When unset, submissions are only deduplicated within a single run.
+    pub submission_store_path: Option<String>,
+
+    /// How long a submitted opportunity is remembered if it never confirms.
+    ///
+    /// Defaults to 10 minutes.
+    pub submission_ttl: Duration,
+
+    /// JSON file keeping taxable events that couldn't be written to the database yet.
+    ///
+    /// When unset, unwritten events are lost on shutdown.
+    pub taxable_event_spool_path: Option<String>,
+
+    /// Keep executing queued results for up to `drain_budget` after cancellation instead
+    /// of returning at once. Defaults to false.
+    pub drain_on_shutdown: bool,
+
+    /// How long queued results keep being executed on shutdown.
+    ///
+    /// Defaults to 10 seconds.
+    pub drain_budget: Duration,
+
+    /// JSON file keeping results still queued at shutdown, reloaded on the next start.
+    ///
+    /// When unset, results left in the queue are lost on shutdown.
+    pub queue_spool_path: Option<String>,
+
+    /// Taxable events written to the database per batch.
+    ///
+    /// Defaults to 100.
+    pub taxable_event_batch_size: usize,
+
+    /// How often buffered taxable events are written to the database.
+    ///
+    /// Defaults to 5 seconds.
+    pub taxable_event_flush_interval: Duration,
+
+    /// Simulate before every submission to size the compute budget, not just in
+    /// `SimulateThenSubmit` mode. A failed simulation only skips the sizing here.
+    pub simulate_compute_units: bool,
+
+    /// Extra compute units requested on top of the simulated usage, as a fraction.
+    ///
+    /// Defaults to 0.1 (10%).
+    pub compute_unit_margin: f64,
+
+    /// Largest fraction of the estimated profit spent on priority fees.
+    ///
+    /// Defaults to 0.1 (10%).
+    pub max_priority_fee_profit_fraction: f64,
+
+    /// Largest fraction of the estimated profit that all landing fees together (base,
+    /// priority and tip) may take before the opportunity is skipped.
+    ///
+    /// Defaults to 0.5 (50%).
+    pub max_fee_fraction_of_profit: f64,
+
+    /// How long to wait for a submitted transaction to confirm before giving up.
+    ///
+    /// Defaults to 30 seconds.
+    pub confirmation_timeout: Duration,
+
+    /// How often signature statuses are polled while waiting for confirmation.
+    ///
+    /// Defaults to 500 milliseconds.
+    pub confirmation_poll_interval: Duration,
+
+    /// Commitment level a transaction must reach to count as confirmed.
+    ///
+    /// `confirmed` lands faster, `finalized` can't be rolled back. Defaults to `confirmed`.
+    pub confirmation_commitment: CommitmentConfig,
+
+    /// Whether confirmation is polled or pushed over a WebSocket subscription.
+    ///
+    /// Defaults to polling.
+    pub confirmation_method: ConfirmationMethod,
+
+    /// WebSocket endpoint for signature subscriptions.
+    ///
+    /// Defaults to `solana_rpc_url` with its scheme switched to `ws`/`wss`.
+    pub solana_ws_url: Option<String>,
+
+    /// How long a request to an RPC provider may take before it's abandoned as timed out.
+    ///
+    /// Defaults to 5 seconds.
+    pub rpc_request_timeout: Duration,
+
+    /// Per-provider overrides of `rpc_request_timeout`. Defaults to none.
+    pub rpc_request_timeouts: HashMap<RpcProvider, Duration>,
+
+    /// Consecutive failed execution cycles before submissions are paused.
+    ///
+    /// 0 disables the circuit breaker. Defaults to 5.
+    pub circuit_breaker_threshold: u32,
+
+    /// How long submissions stay paused once the circuit breaker opens.
+    ///
+    /// Defaults to 60 seconds.
+    pub circuit_breaker_cool_down: Duration,
+
+    /// How long opportunities trading a pool are skipped after an execution through it
+    /// fails. A confirmed execution clears it; zero disables the cooldown.
+    ///
+    /// Defaults to 30 seconds.
+    pub pool_cooldown: Duration,
+
+    /// Resubmits, with a fresh blockhash, of a transaction every provider rejected
+    /// because its blockhash expired. 0 never resubmits. Defaults to 1.
+    pub max_blockhash_resubmits: u32,
+
+    /// How long after execution starts an opportunity may still be resubmitted.
+    ///
+    /// Defaults to 10 seconds.
+    pub resubmit_freshness_window: Duration,
+
+    /// Solana CLI keypair file for a dedicated account paying transaction fees and tips.
+    ///
+    /// When unset, each explorer key pays for its own transactions.
+    pub fee_payer_keypair_path: Option<String>,
+
+    /// File that every received arbitrage result is appended to as a JSON line.
+    ///
+    /// When unset, results are not recorded. Recordings can be replayed with
+    /// `arbitrage::replay::replay_file`.
+    pub record_results_path: Option<String>,
+
+    /// File that the decoded parameters of every swap built are appended to as JSON lines.
+    ///
+    /// When unset, swaps are only logged.
+    pub swap_audit_path: Option<String>,
+
+    /// Most swap legs (pools) put into one arbitrage transaction.
+    ///
+    /// Past the cap, only the most profitable legs are kept. Must be at least 1;
+    /// unset means no cap.
+    pub max_pools_per_tx: Option<usize>,
+
+    /// Mints the relayer may trade (base58 addresses).
+    ///
+    /// When non-empty, opportunities touching any other mint are skipped.
+    pub allowed_mints: Vec<String>,
+
+    /// Mints the relayer never trades (base58 addresses), e.g. scam,
+    /// fee-on-transfer or freezable tokens. Takes precedence over `allowed_mints`.
+    pub blocked_mints: Vec<String>,
+
+    /// Route each swap through the qtrade executor program's `swap` instruction instead
+    /// of calling the DEX directly. Rejected by `validate()` until the executor performs
+    /// the DEX swap itself. Defaults to false.
+    pub route_through_executor: bool,
+
+    /// Referral code passed to the executor program on routed swaps.
+    ///
+    /// Codes above 2^31 charge the fee registered for them. Defaults to 0 (no fee).
+    pub executor_referral_code: u32,
+
+    /// When an explorer key is retired after signing a transaction: `always`,
+    /// `on_success_only` (only once its transaction landed) or `never`. Checked by
+    /// `validate()`. Defaults to always.
+    pub key_retirement_policy: String,
+
+    /// Execute arbitrage results against in-memory mock RPC providers instead of the
+    /// network, for running the loop locally. Needs the `mock` feature. Defaults to false.
+    pub mock_execution: bool,
+
+    /// Nonce accounts the maintenance task creates, funds and initializes, up to this
+    /// many in total, when available nonces run low.
+    ///
+    /// Creating accounts spends SOL, so it's opt-in: 0 never creates any, and the
+    /// accounts must then be listed in `QTRADE_NONCE_ACCOUNTS`. Defaults to 0.
+    pub nonce_target_pool_size: usize,
+
+    /// Available nonces below which the pool is topped up to `nonce_target_pool_size`.
+    ///
+    /// Defaults to 1.
+    pub nonce_min_available: usize,
 }
 
 impl RelayerSettings {
@@ -50,23 +498,334 @@ impl RelayerSettings {
             .map(|v| v == "true")
             .unwrap_or(false);
 
+        let submit_mode = env::var("QTRADE_SUBMIT_MODE")
+            .ok()
+            .and_then(|v| SubmitMode::from_str(&v).ok())
+            .unwrap_or_else(|| SubmitMode::from_simulate(simulate));
+
+        let blockhash_commitment = env::var("QTRADE_BLOCKHASH_COMMITMENT")
+            .ok()
+            .and_then(|v| CommitmentConfig::from_str(v.trim()).ok())
+            .unwrap_or_else(CommitmentConfig::confirmed);
+
+        let blockhash_max_age = env::var("QTRADE_BLOCKHASH_MAX_AGE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(crate::blockhash::BLOCKHASH_MAX_AGE);
+
+        let min_profit_usd = env::var("QTRADE_MIN_PROFIT_USD")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0);
+
+        let sol_price_usd = env::var("QTRADE_SOL_PRICE_USD")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|price| price.is_finite() && *price >= 0.0)
+            .unwrap_or(DEFAULT_SOL_PRICE_USD);
+
+        let price_source = env::var("QTRADE_PRICE_SOURCE")
+            .ok()
+            .and_then(|v| match PriceSourceKind::from_str(&v) {
+                Ok(price_source) => Some(price_source),
+                Err(e) => {
+                    warn!("{}, using the fixed SOL price", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        let price_api_url = env::var("QTRADE_PRICE_API_URL")
+            .unwrap_or_else(|_| DEFAULT_PRICE_API_URL.to_string());
+
+        let price_cache_ttl = env::var("QTRADE_PRICE_CACHE_TTL_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_PRICE_CACHE_TTL);
+
+        let max_queue_size = env::var("QTRADE_MAX_QUEUE_SIZE")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|size| *size > 0)
+            .unwrap_or(crate::DEFAULT_MAX_QUEUE_SIZE);
+
+        let max_in_flight = env::var("QTRADE_MAX_IN_FLIGHT")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|max| *max > 0)
+            .unwrap_or(crate::arbitrage::in_flight::DEFAULT_MAX_IN_FLIGHT);
+
+        // A list of endpoints or regions; the single-endpoint variable is still honoured
+        let jito_block_engine_urls: Vec<String> = env::var("QTRADE_JITO_BLOCK_ENGINE_URLS")
+            .or_else(|_| env::var("QTRADE_JITO_BLOCK_ENGINE_URL"))
+            .map(|urls| {
+                urls.split(',')
+                    .filter(|url| !url.trim().is_empty())
+                    .map(block_engine_endpoint)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let jito_block_engine_urls = if jito_block_engine_urls.is_empty() {
+            vec![DEFAULT_JITO_BLOCK_ENGINE_URL.to_string()]
+        } else {
+            jito_block_engine_urls
+        };
+
+        let jito_endpoint_strategy = env::var("QTRADE_JITO_ENDPOINT_STRATEGY")
+            .ok()
+            .and_then(|v| JitoEndpointStrategy::from_str(&v).ok())
+            .unwrap_or_default();
+
+        let jito_tip_accounts = match env::var("QTRADE_JITO_TIP_ACCOUNTS") {
+            Ok(accounts_str) if !accounts_str.is_empty() => {
+                accounts_str.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            },
+            _ => default_jito_tip_accounts()
+        };
+
+        let jito_min_tip_lamports = env::var("QTRADE_JITO_MIN_TIP_LAMPORTS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_JITO_MIN_TIP_LAMPORTS);
+
+        // Never let the maximum fall below the minimum
+        let jito_max_tip_lamports = env::var("QTRADE_JITO_MAX_TIP_LAMPORTS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_JITO_MAX_TIP_LAMPORTS)
+            .max(jito_min_tip_lamports);
+
+        let jito_bundles = env::var("QTRADE_JITO_BUNDLES")
+            .map(|v| v == "true")
+            .unwrap_or(false);
+
+        let solana_rpc_url = env::var("QTRADE_SOLANA_RPC_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty())
+            .unwrap_or_else(|| MAINNET_RPC_URL.to_string());
+
+        let submission_store_path = env::var("QTRADE_SUBMISSION_STORE_PATH")
+            .ok()
+            .filter(|path| !path.trim().is_empty());
+
+        let submission_ttl = env::var("QTRADE_SUBMISSION_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(crate::arbitrage::dedup::DEFAULT_SUBMISSION_TTL);
+
+        let taxable_event_spool_path = env::var("QTRADE_TAXABLE_EVENT_SPOOL_PATH")
+            .ok()
+            .filter(|path| !path.trim().is_empty());
+
+        let drain_on_shutdown = env::var("QTRADE_DRAIN_ON_SHUTDOWN")
+            .map(|v| v == "true")
+            .unwrap_or(false);
+
+        let drain_budget = env::var("QTRADE_DRAIN_BUDGET_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(crate::arbitrage::drain::DEFAULT_DRAIN_BUDGET);
+
+        let queue_spool_path = env::var("QTRADE_QUEUE_SPOOL_PATH")
+            .ok()
+            .filter(|path| !path.trim().is_empty());
+
+        let taxable_event_batch_size = env::var("QTRADE_TAXABLE_EVENT_BATCH_SIZE")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|size| *size > 0)
+            .unwrap_or(crate::metrics::database::DEFAULT_TAXABLE_EVENT_BATCH_SIZE);
+
+        let taxable_event_flush_interval = env::var("QTRADE_TAXABLE_EVENT_FLUSH_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|ms| *ms > 0)
+            .map(Duration::from_millis)
+            .unwrap_or(crate::metrics::database::DEFAULT_TAXABLE_EVENT_FLUSH_INTERVAL);
+
+        let simulate_compute_units = env::var("QTRADE_SIMULATE_COMPUTE_UNITS")
+            .map(|v| v == "true")
+            .unwrap_or(false);
+
+        let compute_unit_margin = env::var("QTRADE_COMPUTE_UNIT_MARGIN")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|margin| margin.is_finite() && *margin >= 0.0)
+            .unwrap_or(crate::arbitrage::compute::DEFAULT_COMPUTE_UNIT_MARGIN);
+
+        let max_priority_fee_profit_fraction = env::var("QTRADE_MAX_PRIORITY_FEE_PROFIT_FRACTION")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|fraction| (0.0..=1.0).contains(fraction))
+            .unwrap_or(crate::arbitrage::compute::DEFAULT_MAX_PRIORITY_FEE_PROFIT_FRACTION);
+
+        let max_fee_fraction_of_profit = env::var("QTRADE_MAX_FEE_FRACTION_OF_PROFIT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|fraction| (0.0..=1.0).contains(fraction))
+            .unwrap_or(crate::arbitrage::profit::DEFAULT_MAX_FEE_FRACTION_OF_PROFIT);
+
+        let confirmation_timeout = env::var("QTRADE_CONFIRMATION_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|ms| *ms > 0)
+            .map(Duration::from_millis)
+            .unwrap_or(crate::arbitrage::confirm::DEFAULT_CONFIRMATION_TIMEOUT);
+
+        let confirmation_poll_interval = env::var("QTRADE_CONFIRMATION_POLL_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|ms| *ms > 0)
+            .map(Duration::from_millis)
+            .unwrap_or(crate::arbitrage::confirm::DEFAULT_CONFIRMATION_POLL_INTERVAL);
+
+        let confirmation_commitment = env::var("QTRADE_CONFIRMATION_COMMITMENT")
+            .ok()
+            .and_then(|v| CommitmentConfig::from_str(v.trim()).ok())
+            .unwrap_or_else(CommitmentConfig::confirmed);
+
+        let confirmation_method = env::var("QTRADE_CONFIRMATION_METHOD")
+            .ok()
+            .and_then(|v| ConfirmationMethod::from_str(&v).ok())
+            .unwrap_or_default();
+
+        let solana_ws_url = env::var("QTRADE_SOLANA_WS_URL")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+
+        let rpc_request_timeout = env::var("QTRADE_RPC_REQUEST_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|ms| *ms > 0)
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_RPC_REQUEST_TIMEOUT);
+
+        let rpc_request_timeouts = env::var("QTRADE_RPC_REQUEST_TIMEOUTS_MS")
+            .map(|v| parse_rpc_request_timeouts(&v))
+            .unwrap_or_default();
+
+        let circuit_breaker_threshold = env::var("QTRADE_CIRCUIT_BREAKER_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(crate::arbitrage::circuit_breaker::DEFAULT_CIRCUIT_BREAKER_THRESHOLD);
+
+        let circuit_breaker_cool_down = env::var("QTRADE_CIRCUIT_BREAKER_COOL_DOWN_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(crate::arbitrage::circuit_breaker::DEFAULT_CIRCUIT_BREAKER_COOL_DOWN);
+
+        let pool_cooldown = env::var("QTRADE_POOL_COOLDOWN_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(crate::arbitrage::cooldown::DEFAULT_POOL_COOLDOWN);
+
+        let max_blockhash_resubmits = env::var("QTRADE_MAX_BLOCKHASH_RESUBMITS")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(crate::arbitrage::resubmit::DEFAULT_MAX_BLOCKHASH_RESUBMITS);
+
+        let resubmit_freshness_window = env::var("QTRADE_RESUBMIT_FRESHNESS_WINDOW_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(crate::arbitrage::resubmit::DEFAULT_RESUBMIT_FRESHNESS_WINDOW);
+
+        let fee_payer_keypair_path = env::var("QTRADE_FEE_PAYER_KEYPAIR_PATH")
+            .ok()
+            .filter(|path| !path.trim().is_empty());
+
+        let record_results_path = env::var("QTRADE_RECORD_RESULTS_PATH")
+            .ok()
+            .filter(|path| !path.trim().is_empty());
+
+        let swap_audit_path = env::var("QTRADE_SWAP_AUDIT_PATH")
+            .ok()
+            .filter(|path| !path.trim().is_empty());
+
+        let max_pools_per_tx = env::var("QTRADE_MAX_POOLS_PER_TX")
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok());
+
+        let allowed_mints = parse_mint_list(env::var("QTRADE_ALLOWED_MINTS").ok());
+        let blocked_mints = parse_mint_list(env::var("QTRADE_BLOCKED_MINTS").ok());
+
+        let route_through_executor = env::var("QTRADE_ROUTE_THROUGH_EXECUTOR")
+            .map(|v| v == "true")
+            .unwrap_or(false);
+
+        let executor_referral_code = env::var("QTRADE_EXECUTOR_REFERRAL_CODE")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(crate::arbitrage::executor::DEFAULT_EXECUTOR_REFERRAL_CODE);
+
+        let key_retirement_policy = env::var("QTRADE_KEY_RETIREMENT_POLICY")
+            .unwrap_or_else(|_| RetirementPolicy::default().as_str().to_string());
+
+        let mock_execution = env::var("QTRADE_MOCK_EXECUTION")
+            .map(|v| v == "true")
+            .unwrap_or(false);
+
+        let nonce_target_pool_size = env::var("QTRADE_NONCE_TARGET_POOL_SIZE")
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .unwrap_or(crate::nonce::DEFAULT_TARGET_POOL_SIZE);
+
+        let nonce_min_available = env::var("QTRADE_NONCE_MIN_AVAILABLE")
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .unwrap_or(crate::nonce::DEFAULT_MIN_AVAILABLE_NONCES);
+
         // Parse active RPCs from environment variable if available
-        let active_rpcs = match env::var("QTRADE_ACTIVE_RPCS") {
+        let (active_rpcs, mut unknown_rpcs) = match env::var("QTRADE_ACTIVE_RPCS") {
             Ok(rpcs_str) if !rpcs_str.is_empty() => {
-                rpcs_str.split(',')
+                let names: Vec<String> = rpcs_str.split(',')
                     .map(|s| s.trim().to_string())
-                    .collect()
+                    .collect();
+                parse_rpc_providers(&names)
+            },
+            _ => (RpcProvider::default_active(), Vec::new()) // Default to all RPC providers
+        };
+
+        let (blockhash_only_rpcs, unknown_blockhash_only_rpcs) = match env::var("QTRADE_BLOCKHASH_ONLY_RPCS") {
+            Ok(rpcs_str) if !rpcs_str.is_empty() => {
+                let names: Vec<String> = rpcs_str.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect();
+                parse_rpc_providers(&names)
+            },
+            _ => (Vec::new(), Vec::new())
+        };
+        unknown_rpcs.extend(unknown_blockhash_only_rpcs);
+
+        let (simulation_rpcs, unknown_simulation_rpcs) = match env::var("QTRADE_SIMULATION_RPCS") {
+            Ok(rpcs_str) if !rpcs_str.is_empty() => {
+                let names: Vec<String> = rpcs_str.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect();
+                parse_rpc_providers(&names)
             },
-            _ => vec![
-                "bloxroute".to_string(),
-                "helius".to_string(),
-                "jito".to_string(),
-                "nextblock".to_string(),
-                "quicknode".to_string(),
-                "solana".to_string(),
-                "temporal".to_string()
-            ] // Default to all RPC providers
+            _ => (Vec::new(), Vec::new())
         };
+        unknown_rpcs.extend(unknown_simulation_rpcs);
+
+        let identical_transaction = env::var("QTRADE_IDENTICAL_TRANSACTION")
+            .map(|v| v == "true")
+            .unwrap_or(false);
 
         Self {
             bloxroute_api_key,
@@ -75,7 +834,64 @@ impl RelayerSettings {
             quicknode_api_key,
             temporal_api_key,
             active_rpcs,
+            blockhash_only_rpcs,
+            simulation_rpcs,
+            identical_transaction,
+            unknown_rpcs,
             simulate,
+            submit_mode,
+            blockhash_commitment,
+            blockhash_max_age,
+            min_profit_usd,
+            sol_price_usd,
+            price_source,
+            price_api_url,
+            price_cache_ttl,
+            max_queue_size,
+            max_in_flight,
+            jito_block_engine_urls,
+            jito_endpoint_strategy,
+            jito_tip_accounts,
+            jito_min_tip_lamports,
+            jito_max_tip_lamports,
+            jito_bundles,
+            solana_rpc_url,
+            submission_store_path,
+            submission_ttl,
+            taxable_event_spool_path,
+            drain_on_shutdown,
+            drain_budget,
+            queue_spool_path,
+            taxable_event_batch_size,
+            taxable_event_flush_interval,
+            simulate_compute_units,
+            compute_unit_margin,
+            max_priority_fee_profit_fraction,
+            max_fee_fraction_of_profit,
+            confirmation_timeout,
+            confirmation_poll_interval,
+            confirmation_commitment,
+            confirmation_method,
+            solana_ws_url,
+            rpc_request_timeout,
+            rpc_request_timeouts,
+            circuit_breaker_threshold,
+            circuit_breaker_cool_down,
+            pool_cooldown,
+            max_blockhash_resubmits,
+            resubmit_freshness_window,
+            fee_payer_keypair_path,
+            record_results_path,
+            swap_audit_path,
+            max_pools_per_tx,
+            allowed_mints,
+            blocked_mints,
+            route_through_executor,
+            executor_referral_code,
+            key_retirement_policy,
+            mock_execution,
+            nonce_target_pool_size,
+            nonce_min_available,
         }
     }
 
@@ -89,28 +905,21 @@ impl RelayerSettings {
         simulate: bool,
     ) -> Self {
         // Default to all RPC providers
-        let active_rpcs = vec![
-            "bloxroute".to_string(),
-            "helius".to_string(),
-            "jito".to_string(),
-            "nextblock".to_string(),
-            "quicknode".to_string(),
-            "solana".to_string(),
-            "temporal".to_string()
-        ];
-
-        Self {
+        Self::new_with_providers(
             bloxroute_api_key,
             helius_api_key,
             nextblock_api_key,
             quicknode_api_key,
             temporal_api_key,
-            active_rpcs,
+            RpcProvider::default_active(),
             simulate,
-        }
+        )
     }
 
     /// Create a new RelayerSettings instance with specific values including active RPCs
+    ///
+    /// Kept for backward compatibility with string-based configuration. Names are parsed
+    /// case-insensitively; unknown names are logged and rejected by [`Self::validate`].
     pub fn new_with_rpcs(
         bloxroute_api_key: String,
         helius_api_key: String,
@@ -119,6 +928,31 @@ impl RelayerSettings {
         temporal_api_key: String,
         active_rpcs: Vec<String>,
         simulate: bool,
+    ) -> Self {
+        let (active_rpcs, unknown_rpcs) = parse_rpc_providers(&active_rpcs);
+
+        let mut settings = Self::new_with_providers(
+            bloxroute_api_key,
+            helius_api_key,
+            nextblock_api_key,
+            quicknode_api_key,
+            temporal_api_key,
+            active_rpcs,
+            simulate,
+        );
+        settings.unknown_rpcs = unknown_rpcs;
+        settings
+    }
+
+    /// Create a new RelayerSettings instance with specific values and typed active RPC providers
+    pub fn new_with_providers(
+        bloxroute_api_key: String,
+        helius_api_key: String,
+        nextblock_api_key: String,
+        quicknode_api_key: String,
+        temporal_api_key: String,
+        active_rpcs: Vec<RpcProvider>,
+        simulate: bool,
     ) -> Self {
         Self {
             bloxroute_api_key,
@@ -127,7 +961,64 @@ impl RelayerSettings {
             quicknode_api_key,
             temporal_api_key,
             active_rpcs,
+            blockhash_only_rpcs: Vec::new(),
+            simulation_rpcs: Vec::new(),
+            identical_transaction: false,
+            unknown_rpcs: Vec::new(),
             simulate,
+            submit_mode: SubmitMode::from_simulate(simulate),
+            blockhash_commitment: CommitmentConfig::confirmed(),
+            blockhash_max_age: crate::blockhash::BLOCKHASH_MAX_AGE,
+            min_profit_usd: 0.0,
+            sol_price_usd: DEFAULT_SOL_PRICE_USD,
+            price_source: PriceSourceKind::Fixed,
+            price_api_url: DEFAULT_PRICE_API_URL.to_string(),
+            price_cache_ttl: DEFAULT_PRICE_CACHE_TTL,
+            max_queue_size: crate::DEFAULT_MAX_QUEUE_SIZE,
+            max_in_flight: crate::arbitrage::in_flight::DEFAULT_MAX_IN_FLIGHT,
+            jito_block_engine_urls: vec![DEFAULT_JITO_BLOCK_ENGINE_URL.to_string()],
+            jito_endpoint_strategy: JitoEndpointStrategy::Fastest,
+            jito_tip_accounts: default_jito_tip_accounts(),
+            jito_min_tip_lamports: DEFAULT_JITO_MIN_TIP_LAMPORTS,
+            jito_max_tip_lamports: DEFAULT_JITO_MAX_TIP_LAMPORTS,
+            jito_bundles: false,
+            solana_rpc_url: MAINNET_RPC_URL.to_string(),
+            submission_store_path: None,
+            submission_ttl: crate::arbitrage::dedup::DEFAULT_SUBMISSION_TTL,
+            taxable_event_spool_path: None,
+            drain_on_shutdown: false,
+            drain_budget: crate::arbitrage::drain::DEFAULT_DRAIN_BUDGET,
+            queue_spool_path: None,
+            taxable_event_batch_size: crate::metrics::database::DEFAULT_TAXABLE_EVENT_BATCH_SIZE,
+            taxable_event_flush_interval: crate::metrics::database::DEFAULT_TAXABLE_EVENT_FLUSH_INTERVAL,
+            simulate_compute_units: false,
+            compute_unit_margin: crate::arbitrage::compute::DEFAULT_COMPUTE_UNIT_MARGIN,
+            max_priority_fee_profit_fraction: crate::arbitrage::compute::DEFAULT_MAX_PRIORITY_FEE_PROFIT_FRACTION,
+            max_fee_fraction_of_profit: crate::arbitrage::profit::DEFAULT_MAX_FEE_FRACTION_OF_PROFIT,
+            confirmation_timeout: crate::arbitrage::confirm::DEFAULT_CONFIRMATION_TIMEOUT,
+            confirmation_poll_interval: crate::arbitrage::confirm::DEFAULT_CONFIRMATION_POLL_INTERVAL,
+            confirmation_commitment: CommitmentConfig::confirmed(),
+            confirmation_method: ConfirmationMethod::Polling,
+            solana_ws_url: None,
+            rpc_request_timeout: DEFAULT_RPC_REQUEST_TIMEOUT,
+            rpc_request_timeouts: HashMap::new(),
+            circuit_breaker_threshold: crate::arbitrage::circuit_breaker::DEFAULT_CIRCUIT_BREAKER_THRESHOLD,
+            circuit_breaker_cool_down: crate::arbitrage::circuit_breaker::DEFAULT_CIRCUIT_BREAKER_COOL_DOWN,
+            pool_cooldown: crate::arbitrage::cooldown::DEFAULT_POOL_COOLDOWN,
+            max_blockhash_resubmits: crate::arbitrage::resubmit::DEFAULT_MAX_BLOCKHASH_RESUBMITS,
+            resubmit_freshness_window: crate::arbitrage::resubmit::DEFAULT_RESUBMIT_FRESHNESS_WINDOW,
+            fee_payer_keypair_path: None,
+            record_results_path: None,
+            swap_audit_path: None,
+            max_pools_per_tx: None,
+            allowed_mints: Vec::new(),
+            blocked_mints: Vec::new(),
+            route_through_executor: false,
+            executor_referral_code: crate::arbitrage::executor::DEFAULT_EXECUTOR_REFERRAL_CODE,
+            key_retirement_policy: RetirementPolicy::default().as_str().to_string(),
+            mock_execution: false,
+            nonce_target_pool_size: crate::nonce::DEFAULT_TARGET_POOL_SIZE,
+            nonce_min_available: crate::nonce::DEFAULT_MIN_AVAILABLE_NONCES,
         }
     }
 
@@ -152,9 +1043,463 @@ impl RelayerSettings {
         &self.temporal_api_key
     }
 
+    /// RPC providers transactions are submitted through, in order
+    ///
+    /// Providers whose required API key isn't set are left out.
+    pub fn get_active_rpcs(&self) -> Vec<RpcProvider> {
+        self.active_rpcs.iter().copied().filter(|provider| self.has_required_api_key(*provider)).collect()
+    }
+
+    /// Whether transactions should be submitted through `provider`
+    ///
+    /// A provider whose required API key isn't set is never active, so it's skipped
+    /// rather than failing every submission.
+    pub fn is_provider_active(&self, provider: RpcProvider) -> bool {
+        self.active_rpcs.contains(&provider) && self.has_required_api_key(provider)
+    }
+
+    pub fn get_blockhash_only_rpcs(&self) -> &[RpcProvider] {
+        &self.blockhash_only_rpcs
+    }
+
+    /// Whether submissions through `provider` should try a durable nonce before a blockhash
+    pub fn uses_durable_nonce(&self, provider: RpcProvider) -> bool {
+        !self.blockhash_only_rpcs.contains(&provider)
+    }
+
+    /// Check the settings for configuration mistakes
+    ///
+    /// Fails if any configured RPC provider name is unknown, so a typo like "jitoo"
+    /// doesn't silently skip a provider, if a simulation provider can't simulate, if the
+    /// pools-per-transaction cap is 0, if identical transactions would go to a provider
+    /// that requires a tip, if an allowed or blocked mint isn't a valid address, if the
+    /// key retirement policy is unknown, or if mock execution is on in a build without
+    /// the `mock` feature.
+    ///
+    /// Active or simulation providers that need an API key and have none only get a
+    /// warning, since they're skipped rather than used.
+    pub fn validate(&self) -> Result<()> {
+        if !self.unknown_rpcs.is_empty() {
+            for name in &self.unknown_rpcs {
+                error!("Unknown RPC provider: {}", name);
+            }
+            return Err(anyhow!("Unknown RPC providers: {}", self.unknown_rpcs.join(", ")));
+        }
+
+        let simulation_rpcs = self.get_simulation_rpcs();
+        if let Some(provider) = simulation_rpcs.iter().find(|provider| !provider.supports_simulation()) {
+            return Err(anyhow!("RPC provider {} can't simulate transactions", provider.as_str()));
+        }
+
+        // Providers missing their keys are skipped, which is worth a warning at startup
+        let mut used_rpcs = self.active_rpcs.clone();
+        used_rpcs.extend(self.simulation_rpcs.iter().filter(|provider| !self.active_rpcs.contains(provider)));
+        for provider in used_rpcs {
+            if !self.has_required_api_key(provider) {
+                if let Some((_, env_var)) = self.required_api_key(provider) {
+                    warn!("RPC provider {} is configured but {} is not set; skipping it", provider.as_str(), env_var);
+                }
+            }
+        }
+
+        // With every provider skipped, no transaction could ever be submitted
+        if !self.mock_execution && self.get_active_rpcs().is_empty() {
+            let configured: Vec<&str> = self.active_rpcs.iter().map(|provider| provider.as_str()).collect();
+            return Err(anyhow!(
+                "No active RPC provider can be used (configured: {}); set the API key of at least one",
+                if configured.is_empty() { "none".to_string() } else { configured.join(", ") }
+            ));
+        }
+
+        if self.max_pools_per_tx == Some(0) {
+            return Err(anyhow!("max_pools_per_tx must be at least 1"));
+        }
+
+        // The one shared transaction carries no tips, so these providers wouldn't land it
+        if self.identical_transaction {
+            let tipped: Vec<&str> = self.get_active_rpcs()
+                .into_iter()
+                .filter(|provider| provider.requires_tip())
+                .map(|provider| provider.as_str())
+                .collect();
+            if !tipped.is_empty() {
+                return Err(anyhow!(
+                    "identical_transaction can't be used with providers that require a tip: {}",
+                    tipped.join(", ")
+                ));
+            }
+        }
+
+        // The executor's swap takes the input but doesn't call the DEX or return the output yet
+        if self.route_through_executor {
+            return Err(anyhow!(
+                "route_through_executor is not supported yet: the executor program's swap doesn't execute the DEX swap or return its output"
+            ));
+        }
+
+        self.get_mint_filter()?;
+
+        self.key_retirement_policy
+            .parse::<RetirementPolicy>()
+            .map_err(|e| anyhow!("Invalid key_retirement_policy: {}", e))?;
+
+        if self.mock_execution && !cfg!(feature = "mock") {
+            return Err(anyhow!("mock_execution needs qtrade-relayer built with the mock feature"));
+        }
+
+        Ok(())
+    }
+
+    /// RPC providers simulation mode simulates with, in order
+    pub fn get_simulation_rpcs(&self) -> Vec<RpcProvider> {
+        let providers = if self.simulation_rpcs.is_empty() {
+            RpcProvider::default_simulation()
+                .into_iter()
+                .filter(|provider| self.active_rpcs.contains(provider))
+                .collect()
+        } else {
+            self.simulation_rpcs.clone()
+        };
+
+        providers.into_iter().filter(|provider| self.has_required_api_key(*provider)).collect()
+    }
+
+    /// Whether every active provider is sent the same signed transaction
+    pub fn is_identical_transaction(&self) -> bool {
+        self.identical_transaction
+    }
+
+    /// Whether `provider` has the API key it needs, if it needs one
+    ///
+    /// Mock providers stand in for every provider, so with mock execution none need keys.
+    pub fn has_required_api_key(&self, provider: RpcProvider) -> bool {
+        self.mock_execution || self.required_api_key(provider).map_or(true, |(key, _)| !key.trim().is_empty())
+    }
+
+    /// The API key `provider` submits with, and the environment variable it's read from
+    ///
+    /// Returns None for providers that don't need a key.
+    fn required_api_key(&self, provider: RpcProvider) -> Option<(&str, &'static str)> {
+        match provider {
+            RpcProvider::Bloxroute => Some((&self.bloxroute_api_key, "BLOXROUTE_API_KEY")),
+            RpcProvider::Helius => Some((&self.helius_api_key, "HELIUS_API_KEY")),
+            RpcProvider::Nextblock => Some((&self.nextblock_api_key, "NEXTBLOCK_API_KEY")),
+            RpcProvider::Quicknode => Some((&self.quicknode_api_key, "QUICKNODE_API_KEY")),
+            RpcProvider::Temporal => Some((&self.temporal_api_key, "TEMPORAL_API_KEY")),
+            RpcProvider::Jito | RpcProvider::Solana | RpcProvider::Triton => None,
+        }
+    }
+
     pub fn is_simulate(&self) -> bool {
         self.simulate
     }
+
+    /// Effective submit mode; the `simulate` flag always wins
+    pub fn get_submit_mode(&self) -> SubmitMode {
+        if self.simulate {
+            SubmitMode::SimulateOnly
+        } else {
+            self.submit_mode
+        }
+    }
+
+    pub fn get_blockhash_commitment(&self) -> CommitmentConfig {
+        self.blockhash_commitment
+    }
+
+    pub fn get_blockhash_max_age(&self) -> Duration {
+        self.blockhash_max_age
+    }
+
+    pub fn get_min_profit_usd(&self) -> f64 {
+        self.min_profit_usd
+    }
+
+    pub fn get_sol_price_usd(&self) -> f64 {
+        self.sol_price_usd
+    }
+
+    pub fn get_price_source(&self) -> PriceSourceKind {
+        self.price_source
+    }
+
+    pub fn get_price_api_url(&self) -> &str {
+        &self.price_api_url
+    }
+
+    pub fn get_price_cache_ttl(&self) -> Duration {
+        self.price_cache_ttl
+    }
+
+    pub fn get_max_queue_size(&self) -> usize {
+        self.max_queue_size
+    }
+
+    pub fn get_max_in_flight(&self) -> usize {
+        self.max_in_flight
+    }
+
+    pub fn get_max_pools_per_tx(&self) -> Option<usize> {
+        self.max_pools_per_tx
+    }
+
+    /// Filter built from the allowed and blocked mints
+    pub fn get_mint_filter(&self) -> Result<crate::arbitrage::mint_filter::MintFilter> {
+        crate::arbitrage::mint_filter::MintFilter::from_strs(&self.allowed_mints, &self.blocked_mints)
+    }
+
+    pub fn is_route_through_executor(&self) -> bool {
+        self.route_through_executor
+    }
+
+    pub fn get_executor_referral_code(&self) -> u32 {
+        self.executor_referral_code
+    }
+
+    /// Explorer key retirement policy, falling back to the default if it doesn't parse
+    pub fn get_key_retirement_policy(&self) -> RetirementPolicy {
+        self.key_retirement_policy.parse().unwrap_or_default()
+    }
+
+    pub fn is_mock_execution(&self) -> bool {
+        self.mock_execution
+    }
+
+    pub fn get_nonce_target_pool_size(&self) -> usize {
+        self.nonce_target_pool_size
+    }
+
+    pub fn get_nonce_min_available(&self) -> usize {
+        self.nonce_min_available
+    }
+
+    pub fn get_jito_block_engine_urls(&self) -> &[String] {
+        &self.jito_block_engine_urls
+    }
+
+    pub fn get_jito_endpoint_strategy(&self) -> JitoEndpointStrategy {
+        self.jito_endpoint_strategy
+    }
+
+    pub fn get_jito_tip_accounts(&self) -> &[String] {
+        &self.jito_tip_accounts
+    }
+
+    pub fn get_jito_min_tip_lamports(&self) -> u64 {
+        self.jito_min_tip_lamports
+    }
+
+    pub fn get_jito_max_tip_lamports(&self) -> u64 {
+        self.jito_max_tip_lamports
+    }
+
+    pub fn uses_jito_bundles(&self) -> bool {
+        self.jito_bundles
+    }
+
+    pub fn get_solana_rpc_url(&self) -> &str {
+        &self.solana_rpc_url
+    }
+
+    pub fn get_submission_store_path(&self) -> Option<&str> {
+        self.submission_store_path.as_deref()
+    }
+
+    pub fn get_submission_ttl(&self) -> Duration {
+        self.submission_ttl
+    }
+
+    pub fn get_taxable_event_spool_path(&self) -> Option<&str> {
+        self.taxable_event_spool_path.as_deref()
+    }
+
+    pub fn is_drain_on_shutdown(&self) -> bool {
+        self.drain_on_shutdown
+    }
+
+    pub fn get_drain_budget(&self) -> Duration {
+        self.drain_budget
+    }
+
+    pub fn get_queue_spool_path(&self) -> Option<&str> {
+        self.queue_spool_path.as_deref()
+    }
+
+    pub fn get_taxable_event_batch_size(&self) -> usize {
+        self.taxable_event_batch_size
+    }
+
+    pub fn get_taxable_event_flush_interval(&self) -> Duration {
+        self.taxable_event_flush_interval
+    }
+
+    pub fn is_simulate_compute_units(&self) -> bool {
+        self.simulate_compute_units
+    }
+
+    pub fn get_compute_unit_margin(&self) -> f64 {
+        self.compute_unit_margin
+    }
+
+    pub fn get_max_priority_fee_profit_fraction(&self) -> f64 {
+        self.max_priority_fee_profit_fraction
+    }
+
+    pub fn get_max_fee_fraction_of_profit(&self) -> f64 {
+        self.max_fee_fraction_of_profit
+    }
+
+    pub fn get_confirmation_timeout(&self) -> Duration {
+        self.confirmation_timeout
+    }
+
+    pub fn get_confirmation_poll_interval(&self) -> Duration {
+        self.confirmation_poll_interval
+    }
+
+    pub fn get_confirmation_commitment(&self) -> CommitmentConfig {
+        self.confirmation_commitment
+    }
+
+    pub fn get_confirmation_method(&self) -> ConfirmationMethod {
+        self.confirmation_method
+    }
+
+    /// WebSocket endpoint for signature subscriptions, derived from the RPC URL if unset
+    pub fn get_solana_ws_url(&self) -> String {
+        if let Some(url) = &self.solana_ws_url {
+            return url.clone();
+        }
+        if let Some(rest) = self.solana_rpc_url.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else if let Some(rest) = self.solana_rpc_url.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else {
+            self.solana_rpc_url.clone()
+        }
+    }
+
+    /// How long a request to `provider` may take before it times out
+    pub fn get_request_timeout(&self, provider: RpcProvider) -> Duration {
+        self.rpc_request_timeouts
+            .get(&provider)
+            .copied()
+            .unwrap_or(self.rpc_request_timeout)
+    }
+
+    pub fn get_circuit_breaker_threshold(&self) -> u32 {
+        self.circuit_breaker_threshold
+    }
+
+    pub fn get_circuit_breaker_cool_down(&self) -> Duration {
+        self.circuit_breaker_cool_down
+    }
+
+    pub fn get_pool_cooldown(&self) -> Duration {
+        self.pool_cooldown
+    }
+
+    pub fn get_max_blockhash_resubmits(&self) -> u32 {
+        self.max_blockhash_resubmits
+    }
+
+    pub fn get_resubmit_freshness_window(&self) -> Duration {
+        self.resubmit_freshness_window
+    }
+
+    pub fn get_fee_payer_keypair_path(&self) -> Option<&str> {
+        self.fee_payer_keypair_path.as_deref()
+    }
+
+    pub fn get_record_results_path(&self) -> Option<&str> {
+        self.record_results_path.as_deref()
+    }
+
+    pub fn get_swap_audit_path(&self) -> Option<&str> {
+        self.swap_audit_path.as_deref()
+    }
+
+    /// Load the dedicated fee payer, if one is configured
+    pub fn load_fee_payer(&self) -> Result<Option<Keypair>> {
+        match &self.fee_payer_keypair_path {
+            Some(path) => read_keypair_file(path)
+                .map(Some)
+                .map_err(|e| anyhow!("Failed to read fee payer keypair {}: {}", path, e)),
+            None => Ok(None),
+        }
+    }
+
+    /// Solana endpoint for the configured RPC URL
+    pub fn get_solana_endpoint(&self) -> SolanaEndpoint {
+        SolanaEndpoint::from_url(&self.solana_rpc_url)
+    }
+
+    /// Clamp a requested Jito tip to the configured min/max tip range
+    pub fn jito_tip_lamports(&self, requested: u64) -> u64 {
+        requested
+            .max(self.jito_min_tip_lamports)
+            .min(self.jito_max_tip_lamports.max(self.jito_min_tip_lamports))
+    }
+}
+
+/// Split a comma-separated list of mint addresses, dropping empty entries
+fn parse_mint_list(mints: Option<String>) -> Vec<String> {
+    mints
+        .map(|mints| {
+            mints.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parse per-provider request timeouts, e.g. "jito=1500,helius=2000" (milliseconds)
+///
+/// Unknown providers and invalid or zero timeouts are skipped with a warning.
+fn parse_rpc_request_timeouts(timeouts: &str) -> HashMap<RpcProvider, Duration> {
+    let mut parsed = HashMap::new();
+
+    for entry in timeouts.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let Some((name, ms)) = entry.split_once('=') else {
+            warn!("Invalid RPC request timeout (expected provider=ms): {}", entry);
+            continue;
+        };
+        let Some(provider) = RpcProvider::from_str(name) else {
+            warn!("Unknown RPC provider in request timeouts: {}", name);
+            continue;
+        };
+        match ms.trim().parse::<u64>() {
+            Ok(ms) if ms > 0 => {
+                parsed.insert(provider, Duration::from_millis(ms));
+            },
+            _ => warn!("Invalid request timeout for {}: {}", provider.as_str(), ms),
+        }
+    }
+
+    parsed
+}
+
+/// Parse RPC provider names, returning the known providers and the unknown names
+fn parse_rpc_providers(names: &[String]) -> (Vec<RpcProvider>, Vec<String>) {
+    let mut providers = Vec::new();
+    let mut unknown = Vec::new();
+
+    for name in names {
+        match RpcProvider::from_str(name) {
+            Some(provider) => {
+                if !providers.contains(&provider) {
+                    providers.push(provider);
+                }
+            },
+            None => {
+                warn!("Unknown RPC provider: {}", name);
+                unknown.push(name.clone());
+            }
+        }
+    }
+
+    (providers, unknown)
 }
 
 // For tests and examples, provide a way to create RelayerSettings with default values
@@ -162,21 +1507,71 @@ impl RelayerSettings {
 impl Default for RelayerSettings {
     fn default() -> Self {
         Self {
-            bloxroute_api_key: "".to_string(),
-            helius_api_key: "".to_string(),
-            nextblock_api_key: "".to_string(),
-            quicknode_api_key: "".to_string(),
-            temporal_api_key: "".to_string(),
-            active_rpcs: vec![
-                "bloxroute".to_string(),
-                "helius".to_string(),
-                "jito".to_string(),
-                "nextblock".to_string(),
-                "quicknode".to_string(),
-                "solana".to_string(),
-                "temporal".to_string()
-            ],
+            // Placeholder keys, so the default providers pass validation
+            bloxroute_api_key: "test-bloxroute-key".to_string(),
+            helius_api_key: "test-helius-key".to_string(),
+            nextblock_api_key: "test-nextblock-key".to_string(),
+            quicknode_api_key: "test-quicknode-key".to_string(),
+            temporal_api_key: "test-temporal-key".to_string(),
+            active_rpcs: RpcProvider::default_active(),
+            blockhash_only_rpcs: Vec::new(),
+            simulation_rpcs: Vec::new(),
+            identical_transaction: false,
+            unknown_rpcs: Vec::new(),
             simulate: false,
+            submit_mode: SubmitMode::SubmitOnly,
+            blockhash_commitment: CommitmentConfig::confirmed(),
+            blockhash_max_age: crate::blockhash::BLOCKHASH_MAX_AGE,
+            min_profit_usd: 0.0,
+            sol_price_usd: DEFAULT_SOL_PRICE_USD,
+            price_source: PriceSourceKind::Fixed,
+            price_api_url: DEFAULT_PRICE_API_URL.to_string(),
+            price_cache_ttl: DEFAULT_PRICE_CACHE_TTL,
+            max_queue_size: crate::DEFAULT_MAX_QUEUE_SIZE,
+            max_in_flight: crate::arbitrage::in_flight::DEFAULT_MAX_IN_FLIGHT,
+            jito_block_engine_urls: vec![DEFAULT_JITO_BLOCK_ENGINE_URL.to_string()],
+            jito_endpoint_strategy: JitoEndpointStrategy::Fastest,
+            jito_tip_accounts: default_jito_tip_accounts(),
+            jito_min_tip_lamports: DEFAULT_JITO_MIN_TIP_LAMPORTS,
+            jito_max_tip_lamports: DEFAULT_JITO_MAX_TIP_LAMPORTS,
+            jito_bundles: false,
+            solana_rpc_url: MAINNET_RPC_URL.to_string(),
+            submission_store_path: None,
+            submission_ttl: crate::arbitrage::dedup::DEFAULT_SUBMISSION_TTL,
+            taxable_event_spool_path: None,
+            drain_on_shutdown: false,
+            drain_budget: crate::arbitrage::drain::DEFAULT_DRAIN_BUDGET,
+            queue_spool_path: None,
+            taxable_event_batch_size: crate::metrics::database::DEFAULT_TAXABLE_EVENT_BATCH_SIZE,
+            taxable_event_flush_interval: crate::metrics::database::DEFAULT_TAXABLE_EVENT_FLUSH_INTERVAL,
+            simulate_compute_units: false,
+            compute_unit_margin: crate::arbitrage::compute::DEFAULT_COMPUTE_UNIT_MARGIN,
+            max_priority_fee_profit_fraction: crate::arbitrage::compute::DEFAULT_MAX_PRIORITY_FEE_PROFIT_FRACTION,
+            max_fee_fraction_of_profit: crate::arbitrage::profit::DEFAULT_MAX_FEE_FRACTION_OF_PROFIT,
+            confirmation_timeout: crate::arbitrage::confirm::DEFAULT_CONFIRMATION_TIMEOUT,
+            confirmation_poll_interval: crate::arbitrage::confirm::DEFAULT_CONFIRMATION_POLL_INTERVAL,
+            confirmation_commitment: CommitmentConfig::confirmed(),
+            confirmation_method: ConfirmationMethod::Polling,
+            solana_ws_url: None,
+            rpc_request_timeout: DEFAULT_RPC_REQUEST_TIMEOUT,
+            rpc_request_timeouts: HashMap::new(),
+            circuit_breaker_threshold: crate::arbitrage::circuit_breaker::DEFAULT_CIRCUIT_BREAKER_THRESHOLD,
+            circuit_breaker_cool_down: crate::arbitrage::circuit_breaker::DEFAULT_CIRCUIT_BREAKER_COOL_DOWN,
+            pool_cooldown: crate::arbitrage::cooldown::DEFAULT_POOL_COOLDOWN,
+            max_blockhash_resubmits: crate::arbitrage::resubmit::DEFAULT_MAX_BLOCKHASH_RESUBMITS,
+            resubmit_freshness_window: crate::arbitrage::resubmit::DEFAULT_RESUBMIT_FRESHNESS_WINDOW,
+            fee_payer_keypair_path: None,
+            record_results_path: None,
+            swap_audit_path: None,
+            max_pools_per_tx: None,
+            allowed_mints: Vec::new(),
+            blocked_mints: Vec::new(),
+            route_through_executor: false,
+            executor_referral_code: crate::arbitrage::executor::DEFAULT_EXECUTOR_REFERRAL_CODE,
+            key_retirement_policy: RetirementPolicy::default().as_str().to_string(),
+            mock_execution: false,
+            nonce_target_pool_size: crate::nonce::DEFAULT_TARGET_POOL_SIZE,
+            nonce_min_available: crate::nonce::DEFAULT_MIN_AVAILABLE_NONCES,
         }
     }
 }
@@ -5,13 +5,32 @@ use solana_sdk::hash::Hash;
 use solana_sdk::transaction::Transaction;
 use solana_sdk::message::Message;
 use solana_client::rpc_client::RpcClient;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use std::error::Error;
 use tracing::{info, warn};
 
 use crate::rpc;
 use crate::nonce;
 
+/// Convert a float amount into a whole `u64` amount, rounding down
+///
+/// Fails on NaN, infinite, negative and too large amounts rather than saturating, so an
+/// anomalous amount aborts the swap instead of becoming 0 or `u64::MAX`.
+pub fn checked_u64_amount(amount: f64) -> Result<u64> {
+    if !amount.is_finite() {
+        bail!("Amount {} is not finite", amount);
+    }
+    if amount < 0.0 {
+        bail!("Amount {} is negative", amount);
+    }
+    // u64::MAX as f64 rounds up to 2^64, the first whole amount that doesn't fit
+    let whole = amount.floor();
+    if whole >= u64::MAX as f64 {
+        bail!("Amount {} exceeds u64::MAX", amount);
+    }
+    Ok(whole as u64)
+}
+
 /// Create a transaction with a durable nonce
 pub fn create_nonce_tx(
     instructions: &[Instruction],
@@ -74,10 +93,12 @@ pub async fn try_with_nonce_or_blockhash<T: rpc::RpcActions>(
     rpc_results: &mut Vec<(String, bool, String)>,  // Changed from &str to String for first element
 ) -> Result<()> {
     // Try to use nonce if available
-    match nonce_pool.acquire_nonce(rpc_client) {
-        Ok((nonce_pubkey, nonce_hash)) => {
+    // The guard puts the nonce account back in the pool on every path out of this block
+    match nonce_pool.acquire_guarded(rpc_client) {
+        Ok(nonce) => {
             match nonce_pool.get_authority() {
                 Ok(nonce_authority) => {
+                    let (nonce_pubkey, nonce_hash) = (*nonce.pubkey(), nonce.hash());
                     info!("Using nonce account {} with hash {} for {}", nonce_pubkey, nonce_hash, rpc_name);
 
                     // Create nonce info for transactions
@@ -103,9 +124,7 @@ pub async fn try_with_nonce_or_blockhash<T: rpc::RpcActions>(
                     }
 
                     // Release the nonce account back to the pool
-                    if let Err(e) = nonce_pool.release_nonce(&nonce_pubkey) {
-                        warn!("Failed to release nonce account {}: {}", nonce_pubkey, e);
-                    }
+                    drop(nonce);
 
                     return Ok(());
                 },
@@ -156,10 +175,12 @@ where
     G: FnOnce(&mut Vec<Instruction>, &Keypair, rpc::NonceInfo<'_>) -> Result<String, Box<dyn Error>> + Send,
 {
     // Try to use nonce if available
-    match nonce_pool.acquire_nonce(rpc_client) {
-        Ok((nonce_pubkey, nonce_hash)) => {
+    // The guard puts the nonce account back in the pool on every path out of this block
+    match nonce_pool.acquire_guarded(rpc_client) {
+        Ok(nonce) => {
             match nonce_pool.get_authority() {
                 Ok(nonce_authority) => {
+                    let (nonce_pubkey, nonce_hash) = (*nonce.pubkey(), nonce.hash());
                     info!("Using nonce account {} with hash {} for {}", nonce_pubkey, nonce_hash, rpc_name);
 
                     // Create nonce info for transactions
@@ -185,9 +206,7 @@ where
                     }
 
                     // Release the nonce account back to the pool
-                    if let Err(e) = nonce_pool.release_nonce(&nonce_pubkey) {
-                        warn!("Failed to release nonce account {}: {}", nonce_pubkey, e);
-                    }
+                    drop(nonce);
 
                     return Ok(());
                 },
@@ -216,3 +235,54 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_u64_amount_boundaries() {
+        assert_eq!(checked_u64_amount(0.0).unwrap(), 0);
+        assert_eq!(checked_u64_amount(-0.0).unwrap(), 0);
+        assert_eq!(checked_u64_amount(1_500_000.9).unwrap(), 1_500_000);
+
+        // The largest float below 2^64 still fits, 2^64 itself doesn't
+        let largest_below = 18_446_744_073_709_549_568.0;
+        assert_eq!(checked_u64_amount(largest_below).unwrap(), 18_446_744_073_709_549_568);
+        assert!(checked_u64_amount(u64::MAX as f64).is_err());
+        assert!(checked_u64_amount(1e30).is_err());
+        assert!(checked_u64_amount(-1e-9).is_err());
+    }
+
+    #[test]
+    fn test_checked_u64_amount_rejects_non_finite() {
+        assert!(checked_u64_amount(f64::NAN).is_err());
+        assert!(checked_u64_amount(-f64::NAN).is_err());
+        assert!(checked_u64_amount(f64::INFINITY).is_err());
+        assert!(checked_u64_amount(f64::NEG_INFINITY).is_err());
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_missing_authority_falls_back_to_blockhash_and_releases_nonce() {
+        let pool = nonce::NoncePool::with_available_nonces(1);
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let mut results = Vec::new();
+
+        try_with_nonce_or_blockhash_async(
+            "Mock",
+            &mut vec![],
+            &Keypair::new(),
+            &pool,
+            &rpc_client,
+            &mut results,
+            |_, _| Ok("blockhash-signature".to_string()),
+            |_, _, _| panic!("no nonce transaction without an authority"),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(results, vec![("Mock".to_string(), true, "blockhash-signature".to_string())]);
+        assert_eq!(pool.get_stats().unwrap(), (1, 0));
+    }
+}
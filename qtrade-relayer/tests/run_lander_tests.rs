@@ -123,4 +123,37 @@ mod tests {
         env::remove_var("QUICKNODE_API_KEY");
         env::remove_var("TEMPORAL_API_KEY");
     }
+
+    #[test]
+    #[serial]
+    fn test_run_relayer_returns_promptly_on_cancel() {
+        let runtime = get_runtime();
+        let result = runtime.block_on(async {
+            let token = tokio_util::sync::CancellationToken::new();
+            let token_clone = token.clone();
+
+            // Cancel while the relayer is waiting between cycles
+            tokio::spawn(async move {
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                token_clone.cancel();
+            });
+
+            // The check interval is much longer than this timeout, so returning
+            // in time means the sleep was interrupted by the cancellation
+            tokio::time::timeout(
+                tokio::time::Duration::from_secs(10),
+                qtrade_relayer::run_relayer(Some(RelayerSettings::new(
+                    "test_bloxroute".to_string(),
+                    "test_helius".to_string(),
+                    "test_nextblock".to_string(),
+                    "test_quicknode".to_string(),
+                    "test_temporal".to_string(),
+                    false, // simulate
+                )), token),
+            ).await
+        });
+
+        assert!(result.is_ok(), "run_relayer should return promptly after cancellation");
+        assert!(result.unwrap().is_ok());
+    }
 }
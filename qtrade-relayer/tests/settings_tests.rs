@@ -70,6 +70,10 @@ mod tests {
         assert_eq!(settings.get_nextblock_api_key(), "");
         assert_eq!(settings.get_quicknode_api_key(), "");
         assert_eq!(settings.get_temporal_api_key(), "");
+
+        // Nonce accounts are only created when asked for, as that spends SOL
+        assert_eq!(settings.get_nonce_target_pool_size(), 0);
+        assert_eq!(settings.get_nonce_min_available(), 1);
     }
 
     #[test]
@@ -0,0 +1,41 @@
+// Solver backends for qtrade-router
+//
+// The operator picks how the router solves for arbitrage. The cvxpy backend solves the
+// convex program in Python, and the CFMMRouter backend routes by dual decomposition in
+// Rust (see `cfmm`). OpenQAOA has no implementation yet, so the router refuses it rather
+// than silently solving with cvxpy instead.
+
+use anyhow::{anyhow, Result};
+
+/// Solver the router uses each cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RouterBackend {
+    /// Convex optimization with cvxpy, through `qtrade.arbitrage.core`
+    #[default]
+    Cvxpy,
+    /// Dual decomposition routing after CFMMRouter.jl
+    CfmmRouter,
+    /// Quantum approximate optimization with OpenQAOA (not implemented)
+    OpenQaoa,
+}
+
+impl RouterBackend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RouterBackend::Cvxpy => "cvxpy",
+            RouterBackend::CfmmRouter => "cfmmrouter",
+            RouterBackend::OpenQaoa => "openqaoa",
+        }
+    }
+
+    /// Error out for a backend the router can't solve with
+    pub fn ensure_supported(&self) -> Result<()> {
+        match self {
+            RouterBackend::Cvxpy | RouterBackend::CfmmRouter => Ok(()),
+            RouterBackend::OpenQaoa => Err(anyhow!(
+                "Unsupported router backend: {} (use cvxpy or cfmmrouter)",
+                self.name()
+            )),
+        }
+    }
+}
@@ -0,0 +1,449 @@
+// Dual decomposition routing for qtrade-router (the CFMMRouter backend)
+//
+// Following CFMMRouter.jl, the routing problem is split up per pool. Given a price for
+// every token, each pool's arbitrage problem has a closed form, and the best prices
+// minimize the sum of the pools' arbitrage values over prices no lower than the tokens'
+// market values. The router finds those prices with projected gradient descent and
+// reads the route off the pools' arbitrage trades at them. Unlike the cvxpy backend,
+// nothing here goes through Python.
+
+use anyhow::{anyhow, Result};
+
+/// Iterations of the price search
+pub const DEFAULT_MAX_ITERATIONS: usize = 10_000;
+// Net flows more negative than this, relative to the largest trade, make a route infeasible
+const FEASIBILITY_TOLERANCE: f64 = 1e-6;
+// The price search stops once a step moves the prices by less than this, relatively
+const PRICE_TOLERANCE: f64 = 1e-12;
+// Bisection steps when solving a geometric mean pool's arbitrage problem
+const BISECTION_STEPS: usize = 100;
+// Price edge, relative to the tendered token's price, over which a constant-sum pool's
+// arbitrage ramps up to draining it. Without the ramp the pool's trade jumps between
+// nothing and everything, and the price search can't settle.
+const CONSTANT_SUM_RAMP: f64 = 1e-3;
+// Relative error in a computed arbitrage value, allowed for when checking a step descends
+const VALUE_NOISE: f64 = 1e-12;
+// Rounds of scaling back pools that overdraw a token, see `repair`
+const REPAIR_ROUNDS: usize = 50;
+
+/// A pool, described by its trading function
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cfmm {
+    /// Weighted geometric mean of the reserves (Balancer, or Uniswap v2 with equal weights)
+    GeometricMean { reserves: Vec<f64>, weights: Vec<f64>, fee: f64 },
+    /// Sum of the reserves, which must stay non-negative
+    ConstantSum { reserves: Vec<f64>, fee: f64 },
+}
+
+/// Tokens tendered to (delta) and received from (lambda) one pool, by local index
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolTrade {
+    pub delta: Vec<f64>,
+    pub lambda: Vec<f64>,
+}
+
+impl PoolTrade {
+    fn zero(token_count: usize) -> Self {
+        Self {
+            delta: vec![0.0; token_count],
+            lambda: vec![0.0; token_count],
+        }
+    }
+}
+
+impl Cfmm {
+    pub fn reserves(&self) -> &[f64] {
+        match self {
+            Cfmm::GeometricMean { reserves, .. } | Cfmm::ConstantSum { reserves, .. } => reserves,
+        }
+    }
+
+    /// Fee multiplier (1 - fee) applied to tendered tokens
+    pub fn fee(&self) -> f64 {
+        match self {
+            Cfmm::GeometricMean { fee, .. } | Cfmm::ConstantSum { fee, .. } => *fee,
+        }
+    }
+
+    /// Most valuable trade against this pool at `prices` (by local index, all positive)
+    ///
+    /// Also returns the trade's value at `prices`, less the constant-sum ramp penalty.
+    fn arbitrage(&self, prices: &[f64]) -> (PoolTrade, f64) {
+        match self {
+            Cfmm::GeometricMean { reserves, weights, fee } => {
+                let trade = geometric_mean_arbitrage(reserves, weights, *fee, prices);
+                let value = trade_value(&trade, prices);
+                (trade, value)
+            }
+            Cfmm::ConstantSum { reserves, fee } => constant_sum_arbitrage(reserves, *fee, prices),
+        }
+    }
+}
+
+fn trade_value(trade: &PoolTrade, prices: &[f64]) -> f64 {
+    trade.lambda.iter().zip(&trade.delta).zip(prices).map(|((l, d), p)| p * (l - d)).sum()
+}
+
+/// Arbitrage against a weighted geometric mean pool
+///
+/// With a multiplier `mu` on the trading function, a token the pool receives ends with
+/// reserves `mu * fee * w / price`, a token it gives out with `mu * w / price`, and any
+/// other token keeps its reserves. The new reserves grow with `mu`, so bisecting on it
+/// finds the trade that leaves the trading function exactly where it was.
+fn geometric_mean_arbitrage(reserves: &[f64], weights: &[f64], fee: f64, prices: &[f64]) -> PoolTrade {
+    let weight_sum: f64 = weights.iter().sum();
+    let weights: Vec<f64> = weights.iter().map(|w| w / weight_sum).collect();
+    let log_invariant = |new_reserves: &[f64]| -> f64 {
+        new_reserves.iter().zip(&weights).map(|(r, w)| w * r.ln()).sum()
+    };
+    let new_reserves = |mu: f64| -> Vec<f64> {
+        reserves.iter().zip(&weights).zip(prices)
+            .map(|((&r, &w), &p)| r.max(mu * fee * w / p).min(mu * w / p))
+            .collect()
+    };
+    let target = log_invariant(reserves);
+
+    // Below `low` every token is given out, above `high` every token is received
+    let ratios = || reserves.iter().zip(&weights).zip(prices).map(|((&r, &w), &p)| r * p / w);
+    let mut low = ratios().fold(f64::INFINITY, f64::min);
+    let mut high = ratios().fold(0.0, f64::max) / fee;
+    for _ in 0..BISECTION_STEPS {
+        let mid = (low * high).sqrt();
+        if log_invariant(&new_reserves(mid)) < target {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    // `high` always satisfies the trading function, so the trade stays valid
+    let mut trade = PoolTrade::zero(reserves.len());
+    for (i, (&r, new_r)) in reserves.iter().zip(new_reserves(high)).enumerate() {
+        if new_r > r {
+            trade.delta[i] = (new_r - r) / fee;
+        } else {
+            trade.lambda[i] = r - new_r;
+        }
+    }
+
+    trade
+}
+
+/// Arbitrage against a constant-sum pool
+///
+/// The pool swaps one for one less its fee, so the best trade tenders the cheapest token
+/// for the tokens priced above it after the fee. Each of those is drained in proportion
+/// to its price edge, fully once the edge reaches [`CONSTANT_SUM_RAMP`]; the ramp is a
+/// quadratic penalty on the amount received, which comes off the returned value.
+fn constant_sum_arbitrage(reserves: &[f64], fee: f64, prices: &[f64]) -> (PoolTrade, f64) {
+    let mut trade = PoolTrade::zero(reserves.len());
+    let mut value = 0.0;
+    let Some((cheapest, &cheapest_price)) = prices.iter().enumerate().min_by(|a, b| a.1.total_cmp(b.1)) else {
+        return (trade, value);
+    };
+
+    for (i, (&r, &p)) in reserves.iter().zip(prices).enumerate() {
+        let edge = (fee * p - cheapest_price) / cheapest_price;
+        if i == cheapest || edge <= 0.0 || r <= 0.0 {
+            continue;
+        }
+
+        let received = r * (edge / CONSTANT_SUM_RAMP).min(1.0);
+        let penalty = cheapest_price * CONSTANT_SUM_RAMP / (fee * r);
+        trade.lambda[i] = received;
+        trade.delta[cheapest] += received / fee;
+        value += received * (p - cheapest_price / fee) - penalty * received * received / 2.0;
+    }
+
+    (trade, value)
+}
+
+/// Trades for every pool of a network, from [`route`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Routing {
+    pub deltas: Vec<Vec<f64>>,
+    pub lambdas: Vec<Vec<f64>>,
+    /// Tokens the whole network nets, by global index
+    pub net_flows: Vec<f64>,
+    /// Market value of `net_flows`
+    pub value: f64,
+    /// Whether no token is netted below zero (within tolerance)
+    pub feasible: bool,
+}
+
+fn net_flows(trades: &[PoolTrade], local_indices: &[Vec<usize>], token_count: usize) -> Vec<f64> {
+    let mut net = vec![0.0; token_count];
+    for (trade, local) in trades.iter().zip(local_indices) {
+        for (i, &global) in local.iter().enumerate() {
+            net[global] += trade.lambda[i] - trade.delta[i];
+        }
+    }
+    net
+}
+
+/// Scale back the pools tendering a token the network nets below zero
+///
+/// The prices only converge so far, so the route can overdraw a token by a little. A
+/// pool's trade scaled toward zero still satisfies its trading function, so shrinking the
+/// pools that tender an overdrawn token clears the deficit at a small cost in value.
+/// Shrinking a pool also cuts what it hands out, which can overdraw another token, so
+/// this repeats for a few rounds.
+fn repair(trades: &mut [PoolTrade], local_indices: &[Vec<usize>], token_count: usize) {
+    for _ in 0..REPAIR_ROUNDS {
+        let net = net_flows(trades, local_indices, token_count);
+        if net.iter().all(|flow| *flow >= 0.0) {
+            return;
+        }
+
+        let mut tendered = vec![0.0; token_count];
+        for (trade, local) in trades.iter().zip(local_indices) {
+            for (i, &global) in local.iter().enumerate() {
+                tendered[global] += trade.delta[i];
+            }
+        }
+        // Fraction of each overdrawn token's tendered amount the network can cover
+        let cover: Vec<f64> = net.iter().zip(&tendered)
+            .map(|(&flow, &tendered)| if flow < 0.0 && tendered > 0.0 { ((tendered + flow) / tendered).max(0.0) } else { 1.0 })
+            .collect();
+
+        for (trade, local) in trades.iter_mut().zip(local_indices) {
+            let scale = local.iter().enumerate()
+                .filter(|(i, _)| trade.delta[*i] > 0.0)
+                .map(|(_, &global)| cover[global])
+                .fold(1.0, f64::min);
+            if scale < 1.0 {
+                trade.delta.iter_mut().chain(trade.lambda.iter_mut()).for_each(|amount| *amount *= scale);
+            }
+        }
+    }
+}
+
+fn validate_network(cfmms: &[Cfmm], local_indices: &[Vec<usize>], market_value: &[f64]) -> Result<()> {
+    if cfmms.len() != local_indices.len() {
+        return Err(anyhow!("{} pools but {} local index lists", cfmms.len(), local_indices.len()));
+    }
+    if let Some(value) = market_value.iter().find(|v| !v.is_finite() || **v <= 0.0) {
+        return Err(anyhow!("Market values must be positive, got {}", value));
+    }
+
+    for (pool_id, (cfmm, local)) in cfmms.iter().zip(local_indices).enumerate() {
+        if cfmm.reserves().len() != local.len() {
+            return Err(anyhow!("Pool {} has {} reserves for {} tokens", pool_id, cfmm.reserves().len(), local.len()));
+        }
+        if let Some(&global) = local.iter().find(|&&global| global >= market_value.len()) {
+            return Err(anyhow!("Pool {} trades token {} without a market value", pool_id, global));
+        }
+        if cfmm.fee().is_nan() || cfmm.fee() <= 0.0 || cfmm.fee() > 1.0 {
+            return Err(anyhow!("Pool {} has fee multiplier {} outside (0, 1]", pool_id, cfmm.fee()));
+        }
+        match cfmm {
+            Cfmm::GeometricMean { reserves, weights, .. } => {
+                if weights.len() != reserves.len() || weights.iter().any(|w| !w.is_finite() || *w <= 0.0) {
+                    return Err(anyhow!("Pool {} needs a positive weight for every token", pool_id));
+                }
+                if reserves.iter().any(|r| !r.is_finite() || *r <= 0.0) {
+                    return Err(anyhow!("Pool {} has non-positive reserves", pool_id));
+                }
+            }
+            Cfmm::ConstantSum { reserves, .. } => {
+                if reserves.iter().any(|r| !r.is_finite() || *r < 0.0) {
+                    return Err(anyhow!("Pool {} has negative reserves", pool_id));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Route trades through `cfmms` to maximize the market value of the tokens netted
+///
+/// `local_indices[i]` maps pool `i`'s tokens to global token indices, and `market_value`
+/// prices every global token. No token may be netted below zero, as in the cvxpy backend.
+pub fn route(
+    cfmms: &[Cfmm],
+    local_indices: &[Vec<usize>],
+    market_value: &[f64],
+    max_iterations: usize,
+) -> Result<Routing> {
+    validate_network(cfmms, local_indices, market_value)?;
+
+    let token_count = market_value.len();
+    let arbitrage = |prices: &[f64]| -> (Vec<PoolTrade>, f64) {
+        let mut total_value = 0.0;
+        let trades = cfmms.iter().zip(local_indices)
+            .map(|(cfmm, local)| {
+                let local_prices: Vec<f64> = local.iter().map(|&global| prices[global]).collect();
+                let (trade, value) = cfmm.arbitrage(&local_prices);
+                total_value += value;
+                trade
+            })
+            .collect();
+        (trades, total_value)
+    };
+
+    // The net flows are the gradient of the pools' total arbitrage value in the prices,
+    // so step the prices against them (with momentum), backtracking until the step
+    // actually descends and restarting the momentum whenever the value goes back up
+    let project = |prices: Vec<f64>| -> Vec<f64> {
+        prices.iter().zip(market_value).map(|(price, floor)| price.max(*floor)).collect()
+    };
+    let mut prices = market_value.to_vec();
+    let (mut trades, mut dual_value) = arbitrage(&prices);
+    let mut search_point = prices.clone();
+    let (mut search_trades, mut search_value) = (trades.clone(), dual_value);
+    let mut momentum = 1.0_f64;
+    let mut step = 1.0;
+    for _ in 0..max_iterations {
+        let gradient = net_flows(&search_trades, local_indices, token_count);
+        let (candidate, candidate_trades, candidate_value) = loop {
+            let candidate = project(search_point.iter().zip(&gradient).map(|(price, flow)| price - step * flow).collect());
+            let moved: Vec<f64> = candidate.iter().zip(&search_point).map(|(c, p)| c - p).collect();
+            let (candidate_trades, candidate_value) = arbitrage(&candidate);
+            let predicted = search_value
+                + moved.iter().zip(&gradient).map(|(m, g)| m * g).sum::<f64>()
+                + moved.iter().map(|m| m * m).sum::<f64>() / (2.0 * step);
+            if candidate_value <= predicted + VALUE_NOISE * search_value.abs().max(1.0) || step < f64::EPSILON {
+                break (candidate, candidate_trades, candidate_value);
+            }
+            step /= 2.0;
+        };
+
+        let moved: Vec<f64> = candidate.iter().zip(&prices).map(|(c, p)| c - p).collect();
+        let moved_norm = moved.iter().map(|m| m * m).sum::<f64>().sqrt();
+        let price_norm = prices.iter().map(|p| p * p).sum::<f64>().sqrt();
+        let restart = candidate_value > dual_value;
+        prices = candidate;
+        trades = candidate_trades;
+        dual_value = candidate_value;
+        if moved_norm <= PRICE_TOLERANCE * price_norm {
+            break;
+        }
+
+        let next_momentum = if restart { 1.0 } else { (1.0 + (1.0 + 4.0 * momentum * momentum).sqrt()) / 2.0 };
+        let extrapolation = if restart { 0.0 } else { (momentum - 1.0) / next_momentum };
+        momentum = next_momentum;
+        search_point = project(prices.iter().zip(&moved).map(|(p, m)| p + extrapolation * m).collect());
+        (search_trades, search_value) = arbitrage(&search_point);
+        step *= 1.5;
+    }
+
+    repair(&mut trades, local_indices, token_count);
+    let net_flows = net_flows(&trades, local_indices, token_count);
+    let value = net_flows.iter().zip(market_value).map(|(flow, value)| flow * value).sum();
+    let largest_trade = trades.iter()
+        .flat_map(|trade| trade.delta.iter().chain(&trade.lambda))
+        .cloned()
+        .fold(0.0, f64::max);
+    let feasible = net_flows.iter().all(|flow| *flow >= -FEASIBILITY_TOLERANCE * largest_trade.max(1.0));
+
+    let (deltas, lambdas) = trades.into_iter().map(|trade| (trade.delta, trade.lambda)).unzip();
+    Ok(Routing {
+        deltas,
+        lambdas,
+        net_flows,
+        value,
+        feasible,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uniswap(reserves: [f64; 2], fee: f64) -> Cfmm {
+        Cfmm::GeometricMean { reserves: reserves.to_vec(), weights: vec![1.0, 1.0], fee }
+    }
+
+    // The reference network of `solve_arbitrage`: a weighted 4-asset balancer pool, three
+    // uniswapV2 pools and a constant-sum pool over TOKEN-0..TOKEN-3
+    fn reference_network() -> (Vec<Cfmm>, Vec<Vec<usize>>) {
+        let cfmms = vec![
+            Cfmm::GeometricMean { reserves: vec![4.0; 4], weights: vec![4.0, 3.0, 2.0, 1.0], fee: 0.998 },
+            uniswap([10.0, 1.0], 0.997),
+            uniswap([1.0, 5.0], 0.997),
+            uniswap([40.0, 50.0], 0.997),
+            Cfmm::ConstantSum { reserves: vec![10.0, 10.0], fee: 0.999 },
+        ];
+        let local_indices = vec![vec![0, 1, 2, 3], vec![0, 1], vec![1, 2], vec![2, 3], vec![2, 3]];
+        (cfmms, local_indices)
+    }
+
+    #[test]
+    fn test_geometric_mean_arbitrage_keeps_the_invariant() {
+        // The pool prices TOKEN-1 at 2 TOKEN-0, the market at 4
+        let (trade, value) = uniswap([100.0, 50.0], 0.997).arbitrage(&[1.0, 4.0]);
+        assert!(trade.delta[0] > 0.0 && trade.lambda[1] > 0.0);
+        assert_eq!((trade.lambda[0], trade.delta[1]), (0.0, 0.0));
+        assert!(value > 0.0);
+
+        let new_reserves = [100.0 + 0.997 * trade.delta[0], 50.0 - trade.lambda[1]];
+        assert!((new_reserves[0] * new_reserves[1] / 5_000.0 - 1.0).abs() < 1e-9);
+
+        // Within the fee band there is nothing to gain
+        let (trade, _) = uniswap([100.0, 50.0], 0.997).arbitrage(&[1.0, 2.001]);
+        assert!(trade.delta.iter().chain(&trade.lambda).all(|amount| *amount < 1e-9), "{:?}", trade);
+    }
+
+    #[test]
+    fn test_constant_sum_arbitrage_tenders_the_cheapest_token() {
+        let pool = Cfmm::ConstantSum { reserves: vec![10.0, 10.0], fee: 0.999 };
+
+        let (trade, _) = pool.arbitrage(&[1.0, 2.0]);
+        assert_eq!(trade.lambda, vec![0.0, 10.0]);
+        assert!((trade.delta[0] - 10.0 / 0.999).abs() < 1e-12);
+
+        // An edge smaller than the fee isn't worth taking
+        let (trade, value) = pool.arbitrage(&[1.0, 1.0005]);
+        assert_eq!((trade.lambda, value), (vec![0.0, 0.0], 0.0));
+    }
+
+    #[test]
+    fn test_route_reference_network() {
+        let (cfmms, local_indices) = reference_network();
+        let market_value = [1.5, 10.0, 2.0, 3.0];
+        let routing = route(&cfmms, &local_indices, &market_value, DEFAULT_MAX_ITERATIONS).unwrap();
+
+        assert!(routing.feasible, "{:?}", routing.net_flows);
+        assert!(routing.net_flows.iter().all(|flow| *flow >= 0.0));
+        assert!(routing.value > 21.0 && routing.value < 22.0, "{}", routing.value);
+
+        // Every pool's trade leaves its trading function no lower than it was
+        for ((cfmm, delta), lambda) in cfmms.iter().zip(&routing.deltas).zip(&routing.lambdas) {
+            let new_reserves: Vec<f64> = cfmm.reserves().iter().zip(delta).zip(lambda)
+                .map(|((r, d), l)| r + cfmm.fee() * d - l)
+                .collect();
+            match cfmm {
+                Cfmm::GeometricMean { reserves, weights, .. } => {
+                    let log_mean = |values: &[f64]| -> f64 { values.iter().zip(weights).map(|(v, w)| w * v.ln()).sum() };
+                    assert!(log_mean(&new_reserves) >= log_mean(reserves) - 1e-9);
+                }
+                Cfmm::ConstantSum { reserves, .. } => {
+                    assert!(new_reserves.iter().sum::<f64>() >= reserves.iter().sum::<f64>() - 1e-9);
+                    assert!(new_reserves.iter().all(|r| *r >= -1e-9));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_route_without_arbitrage_trades_nothing() {
+        // Both pools price TOKEN-1 at 2 TOKEN-0, as the market does
+        let cfmms = vec![uniswap([200.0, 100.0], 0.997), uniswap([20.0, 10.0], 0.997)];
+        let routing = route(&cfmms, &[vec![0, 1], vec![0, 1]], &[1.0, 2.0], DEFAULT_MAX_ITERATIONS).unwrap();
+
+        assert!(routing.feasible);
+        assert!(routing.value.abs() < 1e-9);
+        assert!(routing.deltas.iter().chain(&routing.lambdas).flatten().all(|amount| amount.abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_route_rejects_malformed_networks() {
+        let (cfmms, mut local_indices) = reference_network();
+        assert!(route(&cfmms, &local_indices, &[1.5, 10.0, 2.0], 10).is_err());
+        assert!(route(&cfmms, &local_indices, &[1.5, 10.0, 0.0, 3.0], 10).is_err());
+
+        local_indices.pop();
+        assert!(route(&cfmms, &local_indices, &[1.5, 10.0, 2.0, 3.0], 10).is_err());
+    }
+}
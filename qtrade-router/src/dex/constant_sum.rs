@@ -0,0 +1,232 @@
+// Constant-sum DEX implementation for quoting
+//
+// This module provides quotes for constant-sum pools (x + y = k), which trade
+// two tokens 1:1 until one side of the pool is exhausted
+
+use solana_sdk::pubkey::Pubkey;
+use anyhow::{Result, anyhow};
+use super::DexQuoter;
+use super::types::{SwapQuote, PoolReserves, QuoteMode};
+
+/// Implementation for constant-sum pool quoting
+pub struct ConstantSumQuoter;
+
+impl ConstantSumQuoter {
+    /// Create a new ConstantSumQuoter instance
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Calculate output amount using the constant-sum formula: x + y = k
+    ///
+    /// Output is linear in the input (1:1 after fees) as long as it stays within
+    /// the pool's reserve of the output token.
+    fn calculate_constant_sum_output(
+        &self,
+        reserve_out: u64,
+        amount_in: u64,
+        fee_rate: u16,
+    ) -> Result<(u64, u64)> {
+        // A fee of 100% or more leaves nothing to swap
+        if fee_rate >= 10_000 {
+            return Err(anyhow!("Invalid fee rate {} for constant-sum pool", fee_rate));
+        }
+
+        // fee_rate is in basis points (1/100 of a percent), e.g., 10 = 0.1%
+        let fee_amount = (amount_in as u128 * fee_rate as u128).div_ceil(10_000) as u64;
+        let amount_out = amount_in - fee_amount;
+
+        if amount_out > reserve_out {
+            return Err(anyhow!(
+                "Constant-sum swap of {} exceeds output reserve of {}",
+                amount_out,
+                reserve_out
+            ));
+        }
+
+        Ok((amount_out, fee_amount))
+    }
+
+    /// Calculate the input needed to receive `amount_out`, returning (amount_in, fee_amount)
+    ///
+    /// The smallest input whose post-fee amount covers the target is
+    /// ceil(amount_out * 10_000 / (10_000 - fee_rate)).
+    fn calculate_constant_sum_input(
+        &self,
+        reserve_out: u64,
+        amount_out: u64,
+        fee_rate: u16,
+    ) -> Result<(u64, u64)> {
+        if amount_out > reserve_out {
+            return Err(anyhow!(
+                "Constant-sum swap of {} exceeds output reserve of {}",
+                amount_out,
+                reserve_out
+            ));
+        }
+        if fee_rate >= 10_000 {
+            return Err(anyhow!("Invalid fee rate {} for constant-sum pool", fee_rate));
+        }
+
+        let amount_in = (amount_out as u128 * 10_000).div_ceil(10_000 - fee_rate as u128);
+        let amount_in = u64::try_from(amount_in)
+            .map_err(|_| anyhow!("Constant-sum input for {} exceeds u64", amount_out))?;
+        let fee_amount = (amount_in as u128 * fee_rate as u128).div_ceil(10_000) as u64;
+
+        Ok((amount_in, fee_amount))
+    }
+}
+
+impl DexQuoter for ConstantSumQuoter {
+    fn get_swap_quote(
+        &self,
+        _pool_address: &Pubkey,
+        pool_reserves: &PoolReserves,
+        amount: u64,
+        is_token_a_to_b: bool,
+        mode: QuoteMode,
+        slippage_bps: u16,
+    ) -> Result<SwapQuote> {
+        let token_a_reserves = pool_reserves.token_a_reserves.ok_or_else(||
+            anyhow!("Token A reserves not available for constant-sum pool"))?;
+
+        let token_b_reserves = pool_reserves.token_b_reserves.ok_or_else(||
+            anyhow!("Token B reserves not available for constant-sum pool"))?;
+
+        let reserve_out = if is_token_a_to_b { token_b_reserves } else { token_a_reserves };
+
+        // The price is fixed at 1:1, so a constant-sum pool has no price impact within its bounds
+        match mode {
+            QuoteMode::ExactIn => {
+                let (estimated_out, fee_amount) = self.calculate_constant_sum_output(
+                    reserve_out,
+                    amount,
+                    pool_reserves.fee_rate,
+                )?;
+
+                // Calculate minimum output with slippage
+                let slippage_factor = 1.0 - (slippage_bps as f64 / 10000.0);
+                let min_out = (estimated_out as f64 * slippage_factor).floor() as u64;
+
+                Ok(SwapQuote {
+                    amount_in: amount,
+                    amount_out: estimated_out,
+                    min_amount_out: Some(min_out),
+                    max_amount_in: None,
+                    fee_amount,
+                    price_impact: 0.0,
+                })
+            }
+            QuoteMode::ExactOut => {
+                let (estimated_in, fee_amount) = self.calculate_constant_sum_input(
+                    reserve_out,
+                    amount,
+                    pool_reserves.fee_rate,
+                )?;
+
+                // Calculate maximum input with slippage
+                let slippage_factor = 1.0 + (slippage_bps as f64 / 10000.0);
+                let max_in = (estimated_in as f64 * slippage_factor).ceil() as u64;
+
+                Ok(SwapQuote {
+                    amount_in: estimated_in,
+                    amount_out: amount,
+                    min_amount_out: None,
+                    max_amount_in: Some(max_in),
+                    fee_amount,
+                    price_impact: 0.0,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reserves(token_a: u64, token_b: u64, fee_rate: u16) -> PoolReserves {
+        PoolReserves {
+            fee_rate,
+            token_a_reserves: Some(token_a),
+            token_b_reserves: Some(token_b),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_constant_sum_is_linear_within_bounds() {
+        let quoter = ConstantSumQuoter::new();
+        // 0.1% fee: 1_000_000 in -> 1_000 fee -> 999_000 out, in either direction
+        let pool = reserves(10_000_000, 10_000_000, 10);
+
+        for is_token_a_to_b in [true, false] {
+            let quote = quoter
+                .get_swap_quote(&Pubkey::default(), &pool, 1_000_000, is_token_a_to_b, QuoteMode::ExactIn, 50)
+                .unwrap();
+            assert_eq!(quote.amount_out, 999_000);
+            assert_eq!(quote.fee_amount, 1_000);
+            assert_eq!(quote.min_amount_out, Some(994_005));
+            assert_eq!(quote.price_impact, 0.0);
+        }
+
+        // Doubling the input doubles the output
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 2_000_000, true, QuoteMode::ExactIn, 50)
+            .unwrap();
+        assert_eq!(quote.amount_out, 1_998_000);
+    }
+
+    #[test]
+    fn test_constant_sum_exact_out() {
+        let quoter = ConstantSumQuoter::new();
+        let pool = reserves(10_000_000, 10_000_000, 10);
+
+        // Inverse of the exact-in quote: 999_000 out needs 1_000_000 in
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 999_000, true, QuoteMode::ExactOut, 50)
+            .unwrap();
+        assert_eq!(quote.amount_in, 1_000_000);
+        assert_eq!(quote.amount_out, 999_000);
+        assert_eq!(quote.fee_amount, 1_000);
+        assert_eq!(quote.max_amount_in, Some(1_005_000));
+
+        assert!(quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 10_000_001, true, QuoteMode::ExactOut, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_constant_sum_rejects_swap_beyond_reserves() {
+        let quoter = ConstantSumQuoter::new();
+        let pool = reserves(10_000_000, 500_000, 0);
+
+        // Exactly draining the output side is allowed
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 500_000, true, QuoteMode::ExactIn, 0)
+            .unwrap();
+        assert_eq!(quote.amount_out, 500_000);
+
+        assert!(quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 500_001, true, QuoteMode::ExactIn, 0)
+            .is_err());
+        // The other direction has plenty of token A
+        assert!(quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 500_001, false, QuoteMode::ExactIn, 0)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_constant_sum_rejects_fee_of_100_percent_or_more() {
+        let quoter = ConstantSumQuoter::new();
+
+        for fee_rate in [10_000, 10_001, u16::MAX] {
+            let pool = reserves(10_000_000, 10_000_000, fee_rate);
+            for mode in [QuoteMode::ExactIn, QuoteMode::ExactOut] {
+                assert!(quoter
+                    .get_swap_quote(&Pubkey::default(), &pool, 1_000_000, true, mode, 0)
+                    .is_err());
+            }
+        }
+    }
+}
@@ -0,0 +1,416 @@
+// Meteora DLMM DEX implementation for quoting
+//
+// This module provides quotes for Meteora DLMM (Dynamic Liquidity Market Maker) pairs.
+// Liquidity sits in discrete bins, each trading token X for token Y at a fixed
+// price of (1 + bin_step / 10_000)^bin_id. A swap drains bins one at a time,
+// moving the active bin down when selling X and up when selling Y.
+
+use solana_sdk::pubkey::Pubkey;
+use anyhow::{Result, anyhow};
+use super::DexQuoter;
+use super::types::{BinLiquidity, SwapQuote, PoolReserves, QuoteMode};
+
+/// Bin steps are quoted in basis points
+const BASIS_POINT_MAX: u128 = 10_000;
+
+/// 1.0 in Q64.64 fixed point
+const ONE_Q64: u128 = 1 << 64;
+
+/// Fee rates are hundredths of a basis point (`DexType::MeteoraDlmm.fee_rate_denominator()`)
+const FEE_RATE_DENOMINATOR: u128 = 1_000_000;
+
+/// Compute `(a * b) >> 64` without overflowing, rounding up if `round_up` and bits were dropped
+///
+/// Returns `None` if the result doesn't fit in a u128.
+fn mul_shr_64(a: u128, b: u128, round_up: bool) -> Option<u128> {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+
+    let lo_lo = a_lo * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_lo = a_hi * b_lo;
+    let hi_hi = a_hi * b_hi;
+
+    // Bits 64..128 of the 256-bit product, plus the carry into bit 128
+    let mid = (lo_lo >> 64) + (lo_hi & MASK) + (hi_lo & MASK);
+    let high = hi_hi
+        .checked_add(lo_hi >> 64)?
+        .checked_add(hi_lo >> 64)?
+        .checked_add(mid >> 64)?;
+    if high > MASK {
+        return None;
+    }
+
+    let result = (high << 64) | (mid & MASK);
+    if round_up && lo_lo & MASK != 0 {
+        result.checked_add(1)
+    } else {
+        Some(result)
+    }
+}
+
+/// Price of token X in token Y for `bin_id`, as Q64.64: (1 + bin_step / 10_000)^bin_id
+pub fn bin_price_q64(bin_id: i32, bin_step: u16) -> Result<u128> {
+    let base = ONE_Q64 + (u128::from(bin_step) << 64) / BASIS_POINT_MAX;
+
+    // Exponentiation by squaring
+    let mut exponent = bin_id.unsigned_abs();
+    let mut square = base;
+    let mut price = ONE_Q64;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            price = mul_shr_64(price, square, false)
+                .ok_or_else(|| anyhow!("Price of bin {} overflows for bin step {}", bin_id, bin_step))?;
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            square = mul_shr_64(square, square, false)
+                .ok_or_else(|| anyhow!("Price of bin {} overflows for bin step {}", bin_id, bin_step))?;
+        }
+    }
+
+    if bin_id < 0 {
+        // 1 / price in Q64.64 is 2^128 / price
+        price = u128::MAX / price;
+    }
+
+    if price == 0 {
+        return Err(anyhow!("Price of bin {} underflows for bin step {}", bin_id, bin_step));
+    }
+
+    Ok(price)
+}
+
+/// Implementation for Meteora DLMM quoting
+pub struct MeteoraDlmmQuoter;
+
+impl MeteoraDlmmQuoter {
+    /// Create a new MeteoraDlmmQuoter instance
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Bins a swap walks through, starting at the active bin and moving away from it
+    ///
+    /// Selling X (X -> Y) consumes the Y held at and below the active bin, highest
+    /// price first; selling Y consumes the X held at and above it, lowest price first.
+    fn swap_path(bins: &[BinLiquidity], active_id: i32, is_x_to_y: bool) -> Vec<BinLiquidity> {
+        let mut path: Vec<BinLiquidity> = bins
+            .iter()
+            .filter(|bin| if is_x_to_y { bin.bin_id <= active_id } else { bin.bin_id >= active_id })
+            .filter(|bin| if is_x_to_y { bin.amount_y > 0 } else { bin.amount_x > 0 })
+            .cloned()
+            .collect();
+
+        if is_x_to_y {
+            path.sort_by_key(|bin| std::cmp::Reverse(bin.bin_id));
+        } else {
+            path.sort_by_key(|bin| bin.bin_id);
+        }
+        path
+    }
+
+    /// Output of `amount_in` at a bin's price, rounded down
+    fn bin_amount_out(amount_in: u128, price: u128, is_x_to_y: bool) -> Option<u128> {
+        if is_x_to_y {
+            mul_shr_64(amount_in, price, false)
+        } else {
+            (amount_in <= u64::MAX as u128).then(|| (amount_in << 64) / price)
+        }
+    }
+
+    /// Input needed for `amount_out` at a bin's price, rounded up
+    fn bin_amount_in(amount_out: u128, price: u128, is_x_to_y: bool) -> Option<u128> {
+        if is_x_to_y {
+            (amount_out <= u64::MAX as u128).then(|| (amount_out << 64).div_ceil(price))
+        } else {
+            mul_shr_64(amount_out, price, true)
+        }
+    }
+
+    /// Walk the bins with `amount_in` (after fees), returning the amount out
+    fn calculate_output(
+        &self,
+        bins: &[BinLiquidity],
+        active_id: i32,
+        bin_step: u16,
+        amount_in: u64,
+        is_x_to_y: bool,
+    ) -> Result<u64> {
+        let mut remaining = u128::from(amount_in);
+        let mut amount_out: u128 = 0;
+
+        for bin in Self::swap_path(bins, active_id, is_x_to_y) {
+            if remaining == 0 {
+                break;
+            }
+
+            let price = bin_price_q64(bin.bin_id, bin_step)?;
+            let reserve_out = u128::from(if is_x_to_y { bin.amount_y } else { bin.amount_x });
+            let max_in = Self::bin_amount_in(reserve_out, price, is_x_to_y)
+                .ok_or_else(|| anyhow!("Input to drain bin {} overflows", bin.bin_id))?;
+
+            if remaining >= max_in {
+                amount_out += reserve_out;
+                remaining -= max_in;
+            } else {
+                let out = Self::bin_amount_out(remaining, price, is_x_to_y)
+                    .ok_or_else(|| anyhow!("Output of bin {} overflows", bin.bin_id))?;
+                amount_out += out.min(reserve_out);
+                remaining = 0;
+            }
+        }
+
+        if remaining > 0 {
+            return Err(anyhow!(
+                "Insufficient bin liquidity: {} of {} input left unswapped",
+                remaining,
+                amount_in
+            ));
+        }
+
+        u64::try_from(amount_out).map_err(|_| anyhow!("Meteora DLMM output exceeds u64"))
+    }
+
+    /// Walk the bins for `amount_out`, returning the input needed before fees
+    fn calculate_input(
+        &self,
+        bins: &[BinLiquidity],
+        active_id: i32,
+        bin_step: u16,
+        amount_out: u64,
+        is_x_to_y: bool,
+    ) -> Result<u64> {
+        let mut remaining = u128::from(amount_out);
+        let mut amount_in: u128 = 0;
+
+        for bin in Self::swap_path(bins, active_id, is_x_to_y) {
+            if remaining == 0 {
+                break;
+            }
+
+            let price = bin_price_q64(bin.bin_id, bin_step)?;
+            let reserve_out = u128::from(if is_x_to_y { bin.amount_y } else { bin.amount_x });
+            let out = remaining.min(reserve_out);
+            amount_in += Self::bin_amount_in(out, price, is_x_to_y)
+                .ok_or_else(|| anyhow!("Input for bin {} overflows", bin.bin_id))?;
+            remaining -= out;
+        }
+
+        if remaining > 0 {
+            return Err(anyhow!(
+                "Insufficient bin liquidity: {} of {} output unavailable",
+                remaining,
+                amount_out
+            ));
+        }
+
+        u64::try_from(amount_in).map_err(|_| anyhow!("Meteora DLMM input exceeds u64"))
+    }
+
+    /// Price impact of receiving `amount_out` for `amount_in` against the active bin's price
+    fn calculate_price_impact(active_price: u128, amount_in: u64, amount_out: u64, is_x_to_y: bool) -> f64 {
+        let price = active_price as f64 / ONE_Q64 as f64;
+        let spot_out = if is_x_to_y { amount_in as f64 * price } else { amount_in as f64 / price };
+        if spot_out <= 0.0 {
+            return 0.0;
+        }
+        (1.0 - amount_out as f64 / spot_out).max(0.0)
+    }
+}
+
+impl DexQuoter for MeteoraDlmmQuoter {
+    /// Token A is the pair's token X and token B its token Y
+    ///
+    /// The active bin id is read from `tick_current_index`, the bin step from
+    /// `tick_spacing`, and the fee is taken from the input before it reaches the bins.
+    fn get_swap_quote(
+        &self,
+        _pool_address: &Pubkey,
+        pool_reserves: &PoolReserves,
+        amount: u64,
+        is_token_a_to_b: bool,
+        mode: QuoteMode,
+        slippage_bps: u16,
+    ) -> Result<SwapQuote> {
+        let bins = pool_reserves.bins.as_ref().ok_or_else(||
+            anyhow!("Bin liquidity not available for Meteora DLMM pool"))?;
+        let active_id = pool_reserves.tick_current_index;
+        let bin_step = pool_reserves.tick_spacing;
+        if bin_step == 0 {
+            return Err(anyhow!("Invalid bin step 0 for Meteora DLMM pool"));
+        }
+
+        let fee_rate = u128::from(pool_reserves.fee_rate);
+        if fee_rate >= FEE_RATE_DENOMINATOR {
+            return Err(anyhow!("Invalid fee rate {} for Meteora DLMM pool", fee_rate));
+        }
+        let active_price = bin_price_q64(active_id, bin_step)?;
+
+        match mode {
+            QuoteMode::ExactIn => {
+                let fee_amount = (u128::from(amount) * fee_rate).div_ceil(FEE_RATE_DENOMINATOR) as u64;
+                let amount_in_after_fee = amount - fee_amount;
+
+                let estimated_out = self.calculate_output(bins, active_id, bin_step, amount_in_after_fee, is_token_a_to_b)?;
+                let price_impact = Self::calculate_price_impact(active_price, amount_in_after_fee, estimated_out, is_token_a_to_b);
+
+                // Calculate minimum output with slippage
+                let slippage_factor = 1.0 - (slippage_bps as f64 / 10000.0);
+                let min_out = (estimated_out as f64 * slippage_factor).floor() as u64;
+
+                Ok(SwapQuote {
+                    amount_in: amount,
+                    amount_out: estimated_out,
+                    min_amount_out: Some(min_out),
+                    max_amount_in: None,
+                    fee_amount,
+                    price_impact,
+                })
+            }
+            QuoteMode::ExactOut => {
+                let amount_in_after_fee = self.calculate_input(bins, active_id, bin_step, amount, is_token_a_to_b)?;
+
+                // Gross the input up so that it still covers the bins once the fee is taken
+                let estimated_in = (u128::from(amount_in_after_fee) * FEE_RATE_DENOMINATOR)
+                    .div_ceil(FEE_RATE_DENOMINATOR - fee_rate);
+                let estimated_in = u64::try_from(estimated_in)
+                    .map_err(|_| anyhow!("Meteora DLMM input for {} exceeds u64", amount))?;
+                let fee_amount = estimated_in - amount_in_after_fee;
+                let price_impact = Self::calculate_price_impact(active_price, amount_in_after_fee, amount, is_token_a_to_b);
+
+                // Calculate maximum input with slippage
+                let slippage_factor = 1.0 + (slippage_bps as f64 / 10000.0);
+                let max_in = (estimated_in as f64 * slippage_factor).ceil() as u64;
+
+                Ok(SwapQuote {
+                    amount_in: estimated_in,
+                    amount_out: amount,
+                    min_amount_out: None,
+                    max_amount_in: Some(max_in),
+                    fee_amount,
+                    price_impact,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BIN_STEP: u16 = 10; // 0.1% between bins
+    const FEE_RATE: u16 = 1_000; // 0.1% in hundredths of a basis point
+
+    fn bin(bin_id: i32, amount_x: u64, amount_y: u64) -> BinLiquidity {
+        BinLiquidity { bin_id, amount_x, amount_y }
+    }
+
+    fn reserves(active_id: i32, bins: Vec<BinLiquidity>) -> PoolReserves {
+        PoolReserves {
+            tick_current_index: active_id,
+            tick_spacing: BIN_STEP,
+            fee_rate: FEE_RATE,
+            bins: Some(bins),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_bin_price() {
+        assert_eq!(bin_price_q64(0, BIN_STEP).unwrap(), ONE_Q64);
+
+        let price = |bin_id| bin_price_q64(bin_id, BIN_STEP).unwrap() as f64 / ONE_Q64 as f64;
+        assert!((price(1) - 1.001).abs() < 1e-12);
+        assert!((price(-1) - 1.0 / 1.001).abs() < 1e-12);
+        assert!((price(1_000) - 1.001f64.powi(1_000)).abs() / 1.001f64.powi(1_000) < 1e-9);
+        assert!((price(-1_000) - 1.001f64.powi(-1_000)).abs() / 1.001f64.powi(-1_000) < 1e-9);
+    }
+
+    #[test]
+    fn test_single_bin_swap() {
+        let quoter = MeteoraDlmmQuoter::new();
+        // The active bin 0 trades 1:1 and holds enough of both tokens
+        let pool = reserves(0, vec![bin(0, 10_000_000, 10_000_000)]);
+
+        // 0.1% fee: 1_000_000 in -> 1_000 fee -> 999_000 out, in either direction
+        for is_token_a_to_b in [true, false] {
+            let quote = quoter
+                .get_swap_quote(&Pubkey::default(), &pool, 1_000_000, is_token_a_to_b, QuoteMode::ExactIn, 50)
+                .unwrap();
+            assert_eq!(quote.amount_out, 999_000);
+            assert_eq!(quote.fee_amount, 1_000);
+            assert_eq!(quote.min_amount_out, Some(994_005));
+            assert_eq!(quote.price_impact, 0.0);
+        }
+
+        // Inverse of the exact-in quote: 999_000 out needs 1_000_000 in
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 999_000, true, QuoteMode::ExactOut, 50)
+            .unwrap();
+        assert_eq!(quote.amount_in, 1_000_000);
+        assert_eq!(quote.fee_amount, 1_000);
+        assert_eq!(quote.max_amount_in, Some(1_005_000));
+    }
+
+    #[test]
+    fn test_swap_crosses_bins_at_worse_prices() {
+        let quoter = MeteoraDlmmQuoter::new();
+        // Selling X drains the active bin's Y, then moves down to bin -1 at a lower price
+        let pool = reserves(0, vec![bin(-1, 0, 10_000_000), bin(0, 0, 500_000), bin(1, 10_000_000, 0)]);
+
+        // 1_001_002 in leaves 1_000_000 after the 1_002 fee
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 1_001_002, true, QuoteMode::ExactIn, 0)
+            .unwrap();
+        // 1_000_000 after fees: 500_000 at 1.0, then 500_000 X at 1/1.001
+        let expected = 500_000.0 + 500_000.0 / 1.001;
+        assert!((quote.amount_out as f64 - expected).abs() <= 1.0, "Got {}", quote.amount_out);
+        assert!(quote.price_impact > 0.0);
+
+        // Selling Y skips the empty active bin side and fills from bin 1 at 1.001
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 1_001_002, false, QuoteMode::ExactIn, 0)
+            .unwrap();
+        let expected = 1_000_000.0 / 1.001;
+        assert!((quote.amount_out as f64 - expected).abs() <= 1.0, "Got {}", quote.amount_out);
+    }
+
+    #[test]
+    fn test_exact_out_round_trips_exact_in() {
+        let quoter = MeteoraDlmmQuoter::new();
+        let pool = reserves(3, vec![bin(2, 0, 2_000_000), bin(3, 1_000_000, 1_000_000), bin(4, 2_000_000, 0)]);
+
+        for is_token_a_to_b in [true, false] {
+            let exact_out = quoter
+                .get_swap_quote(&Pubkey::default(), &pool, 1_500_000, is_token_a_to_b, QuoteMode::ExactOut, 0)
+                .unwrap();
+            let exact_in = quoter
+                .get_swap_quote(&Pubkey::default(), &pool, exact_out.amount_in, is_token_a_to_b, QuoteMode::ExactIn, 0)
+                .unwrap();
+            // Rounding up the input never leaves the output short
+            assert!(exact_in.amount_out >= 1_500_000);
+            assert!(exact_in.amount_out - 1_500_000 <= 3);
+        }
+    }
+
+    #[test]
+    fn test_rejects_swap_beyond_bin_liquidity() {
+        let quoter = MeteoraDlmmQuoter::new();
+        let pool = reserves(0, vec![bin(0, 1_000, 1_000)]);
+
+        assert!(quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 1_000_000, true, QuoteMode::ExactIn, 0)
+            .is_err());
+        assert!(quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 1_001, false, QuoteMode::ExactOut, 0)
+            .is_err());
+
+        let no_bins = PoolReserves { bins: None, ..pool };
+        assert!(quoter
+            .get_swap_quote(&Pubkey::default(), &no_bins, 100, true, QuoteMode::ExactIn, 0)
+            .is_err());
+    }
+}
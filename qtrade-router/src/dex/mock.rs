@@ -3,7 +3,13 @@
 // This module provides mock data and implementations that can be used
 // for testing the DEX quoting functionality
 
-use orca_whirlpools_core::{TickArrays, TickArrayFacade, TickFacade, TICK_ARRAY_SIZE};
+use orca_whirlpools_core::{
+    get_tick_array_start_tick_index,
+    TickArrays,
+    TickArrayFacade,
+    TickFacade,
+    TICK_ARRAY_SIZE,
+};
 
 /// Create mock tick arrays for Orca testing
 ///
@@ -25,3 +31,26 @@ pub fn create_mock_tick_arrays() -> TickArrays {
     // Return the simplest variant of TickArrays with just one tick array
     TickArrays::One(tick_array)
 }
+
+/// Create uninitialized tick arrays centred on the array containing `tick_current_index`
+///
+/// Five contiguous arrays are returned (two either side of the current one) so that
+/// swaps can walk across tick array boundaries in either direction. None of the ticks
+/// are initialized, so liquidity stays constant for the whole sequence.
+pub fn create_tick_arrays_around(tick_current_index: i32, tick_spacing: u16) -> TickArrays {
+    let array_span = TICK_ARRAY_SIZE as i32 * tick_spacing as i32;
+    let current_start = get_tick_array_start_tick_index(tick_current_index, tick_spacing);
+
+    let make_array = |offset: i32| TickArrayFacade {
+        start_tick_index: current_start + offset * array_span,
+        ticks: [TickFacade::default(); TICK_ARRAY_SIZE],
+    };
+
+    TickArrays::Five(
+        make_array(-2),
+        make_array(-1),
+        make_array(0),
+        make_array(1),
+        make_array(2),
+    )
+}
@@ -5,42 +5,60 @@
 
 pub mod orca;
 pub mod raydium;
+pub mod constant_sum;
+pub mod weighted;
+pub mod meteora_dlmm;
+pub mod phoenix;
 pub mod types;
 pub mod mock;
 
 use solana_sdk::pubkey::Pubkey;
 use anyhow::Result;
-use crate::dex::types::{SwapQuote, PoolReserves, DexType};
+use crate::dex::types::{SwapQuote, PoolReserves, DexType, QuoteMode};
 
 /// Trait for DEX quote providers
 pub trait DexQuoter {
     /// Get a quote for a swap from the DEX
+    ///
+    /// With `QuoteMode::ExactIn`, `amount` is the input and the quote carries the
+    /// estimated output and `min_amount_out`. With `QuoteMode::ExactOut`, `amount` is
+    /// the target output and the quote carries the input needed and `max_amount_in`.
     fn get_swap_quote(
         &self,
         pool_address: &Pubkey,
         pool_reserves: &PoolReserves,
-        amount_in: u64,
+        amount: u64,
         is_token_a_to_b: bool,
+        mode: QuoteMode,
         slippage_bps: u16,
     ) -> Result<SwapQuote>;
 }
 
 /// Factory function to create a DEX quoter
-pub fn create_dex_quoter(dex_type: DexType) -> Box<dyn DexQuoter> {
+///
+/// Returns `None` for DEX types that can't be quoted: unknown pools, and Raydium CLMM
+/// until it has a quoter.
+pub fn create_dex_quoter(dex_type: DexType) -> Option<Box<dyn DexQuoter>> {
     match dex_type {
-        DexType::Orca => Box::new(orca::OrcaQuoter::new()),
-        DexType::Raydium => Box::new(raydium::RaydiumQuoter::new()),
-        DexType::RaydiumCpmm => Box::new(raydium::RaydiumQuoter::new()), // Using same implementation for now
-        DexType::RaydiumClmm => unimplemented!("Raydium CLMM quoter not yet implemented"),
+        DexType::Orca => Some(Box::new(orca::OrcaQuoter::new())),
+        DexType::Raydium => Some(Box::new(raydium::RaydiumQuoter::new())),
+        DexType::RaydiumCpmm => Some(Box::new(raydium::RaydiumQuoter::new())), // Using same implementation for now
+        DexType::RaydiumClmm => None,
+        DexType::ConstantSum => Some(Box::new(constant_sum::ConstantSumQuoter::new())),
+        DexType::WeightedPool => Some(Box::new(weighted::WeightedPoolQuoter::new())),
+        DexType::MeteoraDlmm => Some(Box::new(meteora_dlmm::MeteoraDlmmQuoter::new())),
+        DexType::Phoenix => Some(Box::new(phoenix::PhoenixQuoter::new())),
+        DexType::Unknown => None,
     }
 }
 
 /// Determine DEX type based on pool address
 ///
 /// This function tries to determine the DEX type based on the pool address format or prefix.
+/// No address patterns are recognized yet, so every pool is `DexType::Unknown` and is
+/// skipped rather than quoted as the wrong DEX.
 pub fn determine_dex_type(_pool_address: &Pubkey) -> DexType {
     // This is a placeholder implementation. In a real implementation, we would check
     // the pool address against known patterns or prefixes for each DEX.
-    // For now, default to Orca
-    DexType::Orca
+    DexType::Unknown
 }
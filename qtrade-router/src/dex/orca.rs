@@ -5,17 +5,56 @@
 use solana_sdk::pubkey::Pubkey;
 use anyhow::{Result, anyhow};
 use super::{DexQuoter};
-use super::types::{SwapQuote, PoolReserves};
+use super::types::{SwapQuote, PoolReserves, QuoteMode};
 use orca_whirlpools_core::{
-    swap_quote_by_input_token,
     swap_quote_by_output_token,
+    sqrt_price_to_tick_index,
+    tick_index_to_sqrt_price,
+    try_apply_swap_fee,
+    try_reverse_apply_swap_fee,
+    try_get_amount_delta_a,
+    try_get_amount_delta_b,
+    try_get_next_sqrt_price_from_a,
+    try_get_next_sqrt_price_from_b,
+    try_get_min_amount_with_slippage_tolerance,
+    CoreError,
     WhirlpoolFacade,
     TickArrays,
+    AMOUNT_EXCEEDS_MAX_U64,
+    MAX_TICK_INDEX,
+    MIN_TICK_INDEX,
 };
 
 /// Implementation for Orca Whirlpool quotes
 pub struct OrcaQuoter;
 
+/// Outcome of walking an exact-input swap across tick boundaries
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TickWalk {
+    /// Input consumed, including fees
+    amount_in: u64,
+    /// Output accumulated over every step
+    amount_out: u64,
+    /// Fees charged in the input token
+    fee_amount: u64,
+    /// Sqrt price after the swap
+    end_sqrt_price: u128,
+    /// Number of initializable tick boundaries the swap reached
+    ticks_crossed: u32,
+}
+
+/// Result of a single swap step between the current price and a tick boundary
+struct SwapStep {
+    amount_in: u64,
+    amount_out: u64,
+    fee_amount: u64,
+    next_sqrt_price: u128,
+}
+
+fn core_err(e: CoreError) -> anyhow::Error {
+    anyhow!("Orca quote error: {:?}", e)
+}
+
 impl OrcaQuoter {
     /// Create a new OrcaQuoter instance
     pub fn new() -> Self {
@@ -35,11 +74,193 @@ impl OrcaQuoter {
         }
     }
 
-    /// Create mock tick arrays for testing
+    /// Create tick arrays around the pool's current tick
     /// In a real implementation, this would use actual on-chain data
-    fn create_mock_tick_arrays(&self) -> TickArrays {
-        // Use our mock implementation
-        crate::dex::mock::create_mock_tick_arrays()
+    fn create_mock_tick_arrays(&self, reserves: &PoolReserves) -> TickArrays {
+        crate::dex::mock::create_tick_arrays_around(
+            reserves.tick_current_index,
+            reserves.tick_spacing,
+        )
+    }
+
+    /// Next initializable tick boundary in the swap direction
+    ///
+    /// For a→b swaps the price moves down, so this is the closest boundary strictly below
+    /// the current sqrt price; for b→a swaps it is the closest boundary above the current tick.
+    fn next_tick_boundary(
+        tick_current_index: i32,
+        sqrt_price: u128,
+        tick_spacing: u16,
+        is_token_a_to_b: bool,
+    ) -> i32 {
+        let spacing = tick_spacing as i32;
+        let floor = tick_current_index - tick_current_index.rem_euclid(spacing);
+
+        if is_token_a_to_b {
+            let boundary = if tick_index_to_sqrt_price(floor) < sqrt_price {
+                floor
+            } else {
+                floor - spacing
+            };
+            boundary.max(MIN_TICK_INDEX)
+        } else {
+            (floor + spacing).min(MAX_TICK_INDEX)
+        }
+    }
+
+    /// Swap as much of `amount_remaining` as fits between the current and target sqrt price
+    ///
+    /// Mirrors the Whirlpool program's exact-input step: the fee is taken from the input first,
+    /// and when the target is reached only the input needed to get there is charged.
+    fn compute_swap_step(
+        amount_remaining: u64,
+        fee_rate: u16,
+        liquidity: u128,
+        sqrt_price: u128,
+        target_sqrt_price: u128,
+        is_token_a_to_b: bool,
+    ) -> Result<SwapStep> {
+        let amount_after_fee = try_apply_swap_fee(amount_remaining, fee_rate).map_err(core_err)?;
+
+        let input_delta = |from: u128, to: u128| {
+            if is_token_a_to_b {
+                try_get_amount_delta_a(from, to, liquidity, true)
+            } else {
+                try_get_amount_delta_b(from, to, liquidity, true)
+            }
+        };
+
+        // An input requirement above u64::MAX simply means the target can't be reached
+        let reaches_target = match input_delta(sqrt_price, target_sqrt_price) {
+            Ok(max_in) => max_in <= amount_after_fee,
+            Err(e) if e == AMOUNT_EXCEEDS_MAX_U64 => false,
+            Err(e) => return Err(core_err(e)),
+        };
+
+        let next_sqrt_price = if reaches_target {
+            target_sqrt_price
+        } else if is_token_a_to_b {
+            try_get_next_sqrt_price_from_a(sqrt_price, liquidity, amount_after_fee, true)
+                .map_err(core_err)?
+        } else {
+            try_get_next_sqrt_price_from_b(sqrt_price, liquidity, amount_after_fee, true)
+                .map_err(core_err)?
+        };
+
+        let amount_in = input_delta(sqrt_price, next_sqrt_price).map_err(core_err)?;
+        let amount_out = if is_token_a_to_b {
+            try_get_amount_delta_b(sqrt_price, next_sqrt_price, liquidity, false)
+        } else {
+            try_get_amount_delta_a(sqrt_price, next_sqrt_price, liquidity, false)
+        }
+        .map_err(core_err)?;
+
+        let fee_amount = if reaches_target {
+            try_reverse_apply_swap_fee(amount_in, fee_rate).map_err(core_err)? - amount_in
+        } else {
+            amount_remaining - amount_in
+        };
+
+        Ok(SwapStep {
+            amount_in,
+            amount_out,
+            fee_amount,
+            next_sqrt_price,
+        })
+    }
+
+    /// Walk an exact-input swap tick boundary by tick boundary
+    ///
+    /// Liquidity is taken from `PoolReserves` and held constant across boundaries, since
+    /// the reserves snapshot carries no per-tick `liquidity_net`. The walk stops once the
+    /// input is consumed or the price reaches the edge of the tick range.
+    fn walk_ticks(
+        pool_reserves: &PoolReserves,
+        amount_in: u64,
+        is_token_a_to_b: bool,
+    ) -> Result<TickWalk> {
+        if amount_in == 0 {
+            return Err(anyhow!("Orca quote error: amount_in must be non-zero"));
+        }
+        if pool_reserves.liquidity == 0 {
+            return Err(anyhow!("Orca quote error: pool has no liquidity"));
+        }
+        if pool_reserves.tick_spacing == 0 {
+            return Err(anyhow!("Orca quote error: pool has zero tick spacing"));
+        }
+
+        let mut amount_remaining = amount_in;
+        let mut amount_out = 0u64;
+        let mut fee_amount = 0u64;
+        let mut ticks_crossed = 0u32;
+        let mut sqrt_price = pool_reserves.sqrt_price;
+        let mut tick_current_index = pool_reserves.tick_current_index;
+
+        while amount_remaining > 0 {
+            let boundary = Self::next_tick_boundary(
+                tick_current_index,
+                sqrt_price,
+                pool_reserves.tick_spacing,
+                is_token_a_to_b,
+            );
+            let target_sqrt_price = tick_index_to_sqrt_price(boundary);
+            if target_sqrt_price == sqrt_price {
+                // Price is pinned at the edge of the tick range
+                break;
+            }
+
+            let step = Self::compute_swap_step(
+                amount_remaining,
+                pool_reserves.fee_rate,
+                pool_reserves.liquidity,
+                sqrt_price,
+                target_sqrt_price,
+                is_token_a_to_b,
+            )?;
+
+            amount_remaining = amount_remaining
+                .checked_sub(step.amount_in + step.fee_amount)
+                .ok_or_else(|| anyhow!("Orca quote error: swap step overspent input"))?;
+            amount_out = amount_out
+                .checked_add(step.amount_out)
+                .ok_or_else(|| anyhow!("Orca quote error: output overflow"))?;
+            fee_amount += step.fee_amount;
+
+            if step.next_sqrt_price == target_sqrt_price {
+                ticks_crossed += 1;
+                tick_current_index = if is_token_a_to_b { boundary - 1 } else { boundary };
+            } else {
+                tick_current_index = sqrt_price_to_tick_index(step.next_sqrt_price);
+            }
+            sqrt_price = step.next_sqrt_price;
+        }
+
+        Ok(TickWalk {
+            amount_in: amount_in - amount_remaining,
+            amount_out,
+            fee_amount,
+            end_sqrt_price: sqrt_price,
+            ticks_crossed,
+        })
+    }
+
+    /// Price impact implied by moving from `start_sqrt_price` to `end_sqrt_price`
+    ///
+    /// Expressed from the trader's side, so it is non-negative in both directions
+    /// (0.01 = 1%).
+    fn price_impact(start_sqrt_price: u128, end_sqrt_price: u128, is_token_a_to_b: bool) -> f64 {
+        if start_sqrt_price == 0 || end_sqrt_price == 0 {
+            return 0.0;
+        }
+
+        let ratio = end_sqrt_price as f64 / start_sqrt_price as f64;
+        let price_ratio = if is_token_a_to_b {
+            ratio * ratio
+        } else {
+            1.0 / (ratio * ratio)
+        };
+
+        (1.0 - price_ratio).max(0.0)
     }
 }
 
@@ -58,7 +279,7 @@ impl OrcaQuoter {
         let whirlpool = self.to_whirlpool_facade(pool_reserves);
 
         // Create the tick arrays needed for the swap
-        let tick_arrays = self.create_mock_tick_arrays();
+        let tick_arrays = self.create_mock_tick_arrays(pool_reserves);
 
         // Get quote from Orca SDK using exact output method
         let quote_result = swap_quote_by_output_token(
@@ -118,70 +339,186 @@ impl OrcaQuoter {
 impl DexQuoter for OrcaQuoter {
     fn get_swap_quote(
         &self,
-        _pool_address: &Pubkey,
+        pool_address: &Pubkey,
         pool_reserves: &PoolReserves,
-        amount_in: u64,
+        amount: u64,
         is_token_a_to_b: bool,
+        mode: QuoteMode,
         slippage_bps: u16,
     ) -> Result<SwapQuote> {
-        // Create the WhirlpoolFacade from pool reserves
-        let whirlpool = self.to_whirlpool_facade(pool_reserves);
+        if mode == QuoteMode::ExactOut {
+            return self.get_swap_quote_by_output(pool_address, pool_reserves, amount, is_token_a_to_b, slippage_bps);
+        }
 
-        // Create the tick arrays needed for the swap
-        let tick_arrays = self.create_mock_tick_arrays();
+        // Walk the swap across tick boundaries until the input is consumed
+        let walk = Self::walk_ticks(pool_reserves, amount, is_token_a_to_b)?;
+
+        let min_amount_out = try_get_min_amount_with_slippage_tolerance(walk.amount_out, slippage_bps)
+            .map_err(core_err)?;
 
-        // Get quote from Orca SDK
-        let quote_result = swap_quote_by_input_token(
+        // Price impact comes from how far the swap moved the pool price
+        let price_impact =
+            Self::price_impact(pool_reserves.sqrt_price, walk.end_sqrt_price, is_token_a_to_b);
+
+        Ok(SwapQuote {
+            amount_in: walk.amount_in,
+            amount_out: walk.amount_out,
+            min_amount_out: Some(min_amount_out),
+            max_amount_in: None, // Not provided by input quote
+            fee_amount: walk.fee_amount,
+            price_impact,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orca_whirlpools_core::{
+        swap_quote_by_input_token,
+        TickArrayFacade,
+        TickFacade,
+        TICK_ARRAY_SIZE,
+    };
+
+    const LIQUIDITY: u128 = 1_000_000_000_000;
+    const TICK_SPACING: u16 = 64;
+    const FEE_RATE: u16 = 3000; // 0.3% in hundredths of a basis point
+
+    /// Pool sitting in the middle of the [0, 64) tick range at price ~1.0
+    fn test_reserves() -> PoolReserves {
+        let tick_current_index = 32;
+        PoolReserves {
+            sqrt_price: tick_index_to_sqrt_price(tick_current_index),
+            tick_current_index,
+            liquidity: LIQUIDITY,
+            fee_rate: FEE_RATE,
+            tick_spacing: TICK_SPACING,
+            ..Default::default()
+        }
+    }
+
+    /// Tick arrays with every initializable tick initialized (but no liquidity change),
+    /// so the Whirlpool reference quote steps at the same boundaries as our walk
+    fn reference_tick_arrays(reserves: &PoolReserves) -> TickArrays {
+        let initialized = TickFacade {
+            initialized: true,
+            ..Default::default()
+        };
+        let with_ticks = |array: TickArrayFacade| TickArrayFacade {
+            ticks: [initialized; TICK_ARRAY_SIZE],
+            ..array
+        };
+        match crate::dex::mock::create_tick_arrays_around(
+            reserves.tick_current_index,
+            reserves.tick_spacing,
+        ) {
+            TickArrays::Five(a, b, c, d, e) => TickArrays::Five(
+                with_ticks(a),
+                with_ticks(b),
+                with_ticks(c),
+                with_ticks(d),
+                with_ticks(e),
+            ),
+            other => other,
+        }
+    }
+
+    fn assert_matches_whirlpool(amount_in: u64, is_token_a_to_b: bool) -> TickWalk {
+        let reserves = test_reserves();
+        let quoter = OrcaQuoter::new();
+
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &reserves, amount_in, is_token_a_to_b, QuoteMode::ExactIn, 100)
+            .unwrap();
+        let reference = swap_quote_by_input_token(
             amount_in,
             is_token_a_to_b,
-            slippage_bps,
-            whirlpool,
-            tick_arrays,
-            None, // transfer fee for token A (None = no fee)
-            None  // transfer fee for token B (None = no fee)
-        ).map_err(|e| anyhow!("Orca quote error: {:?}", e))?;
+            100,
+            quoter.to_whirlpool_facade(&reserves),
+            reference_tick_arrays(&reserves),
+            None,
+            None,
+        )
+        .unwrap();
 
-        // Calculate price impact using a more sophisticated approach
-        // In a production environment, we would get oracle prices here
-        let amount_in_f64 = amount_in as f64;
-        let amount_out_f64 = quote_result.token_est_out as f64;
+        assert_eq!(quote.amount_in, reference.token_in);
+        assert_eq!(quote.amount_out, reference.token_est_out);
+        assert_eq!(quote.min_amount_out, Some(reference.token_min_out));
+        assert_eq!(quote.fee_amount, reference.trade_fee);
 
-        // Calculate the execution price
-        let execution_price = if amount_in_f64 > 0.0 {
-            amount_out_f64 / amount_in_f64
-        } else {
-            0.0
-        };
+        OrcaQuoter::walk_ticks(&reserves, amount_in, is_token_a_to_b).unwrap()
+    }
 
-        // In a real implementation, get the oracle/market price from a price oracle
-        // For now, we'll simulate this with a placeholder
-        // We could potentially pass this in as a parameter
-        let oracle_price = if is_token_a_to_b {
-            // Get A/B market price (how much of token B you get for 1 token A)
-            1.0 // Placeholder oracle price
-        } else {
-            // Get B/A market price (how much of token A you get for 1 token B)
-            1.0 // Placeholder oracle price
-        };
+    #[test]
+    fn test_swap_within_single_tick() {
+        // A small swap stays inside the current tick range in both directions.
+        // Price at tick 32 is 1.0001^32 ~= 1.0032, so 997_000 after fees maps to:
+        for (is_token_a_to_b, expected_out) in [(true, 1_000_194), (false, 993_813)] {
+            let walk = assert_matches_whirlpool(1_000_000, is_token_a_to_b);
+            assert_eq!(walk.ticks_crossed, 0);
+            assert_eq!(walk.amount_in, 1_000_000);
+            assert_eq!(walk.fee_amount, 3_000);
+            assert_eq!(walk.amount_out, expected_out);
+        }
+    }
 
-        // Calculate price impact as a percentage difference between oracle and execution price
-        let price_impact = if oracle_price > 0.0 {
-            (oracle_price - execution_price) / oracle_price
-        } else {
-            0.0
-        };
+    #[test]
+    fn test_swap_crossing_tick_boundaries() {
+        // ~32 ticks of liquidity on each side is roughly 1.6B units; 10B must cross several boundaries
+        for is_token_a_to_b in [true, false] {
+            let walk = assert_matches_whirlpool(10_000_000_000, is_token_a_to_b);
+            assert!(walk.ticks_crossed >= 2, "crossed {}", walk.ticks_crossed);
+            assert_eq!(walk.amount_in, 10_000_000_000);
 
-        // Ensure price impact is never negative (can happen if pool price is better than oracle)
-        let price_impact = price_impact.max(0.0);
+            let start = test_reserves().sqrt_price;
+            if is_token_a_to_b {
+                assert!(walk.end_sqrt_price < tick_index_to_sqrt_price(0));
+                assert!(walk.end_sqrt_price < start);
+            } else {
+                assert!(walk.end_sqrt_price > tick_index_to_sqrt_price(64));
+                assert!(walk.end_sqrt_price > start);
+            }
+        }
+    }
 
-        // Convert the Orca quote to our standard SwapQuote format
-        Ok(SwapQuote {
-            amount_in: quote_result.token_in,
-            amount_out: quote_result.token_est_out,
-            min_amount_out: Some(quote_result.token_min_out),
-            max_amount_in: None, // Not provided by input quote
-            fee_amount: quote_result.trade_fee,
-            price_impact,
-        })
+    #[test]
+    fn test_price_impact_from_sqrt_price_movement() {
+        let reserves = test_reserves();
+        let quoter = OrcaQuoter::new();
+
+        let small = quoter
+            .get_swap_quote(&Pubkey::default(), &reserves, 1_000_000, true, QuoteMode::ExactIn, 100)
+            .unwrap();
+        let large = quoter
+            .get_swap_quote(&Pubkey::default(), &reserves, 10_000_000_000, true, QuoteMode::ExactIn, 100)
+            .unwrap();
+
+        assert!(small.price_impact >= 0.0 && small.price_impact < 0.0001);
+        assert!(large.price_impact > small.price_impact);
+        assert!(large.price_impact < 1.0);
+    }
+
+    #[test]
+    fn test_exact_out_quotes_input_needed() {
+        let reserves = test_reserves();
+        let quote = OrcaQuoter::new()
+            .get_swap_quote(&Pubkey::default(), &reserves, 1_000_000, true, QuoteMode::ExactOut, 100)
+            .unwrap();
+
+        assert_eq!(quote.amount_out, 1_000_000);
+        assert!(quote.amount_in > 0);
+        assert!(quote.max_amount_in.unwrap() >= quote.amount_in);
+        assert_eq!(quote.min_amount_out, None);
+    }
+
+    #[test]
+    fn test_rejects_empty_pool() {
+        let reserves = PoolReserves {
+            liquidity: 0,
+            ..test_reserves()
+        };
+        let result = OrcaQuoter::new().get_swap_quote(&Pubkey::default(), &reserves, 1_000, true, QuoteMode::ExactIn, 100);
+        assert!(result.is_err());
     }
 }
@@ -0,0 +1,393 @@
+// Phoenix DEX implementation for quoting
+//
+// This module provides quotes for Phoenix markets. Phoenix is a central limit order
+// book rather than an AMM: a swap is an immediate-or-cancel order that fills against
+// resting orders level by level, so the output follows the depth of the book.
+// Token A is the market's base token and token B its quote token.
+
+use solana_sdk::pubkey::Pubkey;
+use anyhow::{Result, anyhow};
+use super::DexQuoter;
+use super::types::{OrderBookLevel, OrderBookState, SwapQuote, PoolReserves, QuoteMode};
+
+/// Taker fees are quoted in basis points
+const FEE_RATE_DENOMINATOR: u128 = 10_000;
+
+/// Result of matching an order against one side of the book
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fill {
+    /// Base lots traded
+    base_lots: u128,
+    /// Quote atoms traded, before fees
+    quote_atoms: u128,
+    /// Quote atoms per base lot at the best level matched
+    best_quote_per_lot: u128,
+}
+
+impl Fill {
+    /// Relative distance between the average fill price and the best price
+    fn price_impact(&self) -> f64 {
+        if self.base_lots == 0 || self.best_quote_per_lot == 0 {
+            return 0.0;
+        }
+        let average = self.quote_atoms as f64 / self.base_lots as f64;
+        let best = self.best_quote_per_lot as f64;
+        ((average - best) / best).abs()
+    }
+}
+
+/// Implementation for Phoenix order book quoting
+pub struct PhoenixQuoter;
+
+impl PhoenixQuoter {
+    /// Create a new PhoenixQuoter instance
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Non-empty levels of the side a swap fills against, best price first
+    ///
+    /// Selling base (A -> B) hits the bids, highest first; buying base hits the asks, lowest first.
+    fn matching_levels(book: &OrderBookState, is_sell: bool) -> Vec<OrderBookLevel> {
+        let mut levels: Vec<OrderBookLevel> = (if is_sell { &book.bids } else { &book.asks })
+            .iter()
+            .filter(|level| level.size_in_base_lots > 0)
+            .cloned()
+            .collect();
+
+        if is_sell {
+            levels.sort_by_key(|level| std::cmp::Reverse(level.price_in_ticks));
+        } else {
+            levels.sort_by_key(|level| level.price_in_ticks);
+        }
+        levels
+    }
+
+    /// Fill exactly `base_lots` against the book
+    fn fill_base_lots(&self, book: &OrderBookState, base_lots: u128, is_sell: bool) -> Result<Fill> {
+        let mut fill = Fill { base_lots: 0, quote_atoms: 0, best_quote_per_lot: 0 };
+        let mut remaining = base_lots;
+
+        for level in Self::matching_levels(book, is_sell) {
+            if remaining == 0 {
+                break;
+            }
+            let quote_per_lot = book.quote_atoms_per_base_lot(level.price_in_ticks);
+            if fill.best_quote_per_lot == 0 {
+                fill.best_quote_per_lot = quote_per_lot;
+            }
+
+            let lots = remaining.min(u128::from(level.size_in_base_lots));
+            fill.base_lots += lots;
+            fill.quote_atoms += lots * quote_per_lot;
+            remaining -= lots;
+        }
+
+        if remaining > 0 {
+            return Err(anyhow!(
+                "Insufficient order book depth: {} of {} base lots unfilled",
+                remaining,
+                base_lots
+            ));
+        }
+        Ok(fill)
+    }
+
+    /// Buy as many base lots as `quote_budget` quote atoms pay for
+    fn fill_quote_budget(&self, book: &OrderBookState, quote_budget: u128) -> Result<Fill> {
+        let mut fill = Fill { base_lots: 0, quote_atoms: 0, best_quote_per_lot: 0 };
+        let mut remaining = quote_budget;
+        let mut book_exhausted = true;
+
+        for level in Self::matching_levels(book, false) {
+            let quote_per_lot = book.quote_atoms_per_base_lot(level.price_in_ticks);
+            if quote_per_lot == 0 {
+                return Err(anyhow!("Order book level at {} ticks has no quote price", level.price_in_ticks));
+            }
+            if fill.best_quote_per_lot == 0 {
+                fill.best_quote_per_lot = quote_per_lot;
+            }
+
+            let lots = (remaining / quote_per_lot).min(u128::from(level.size_in_base_lots));
+            fill.base_lots += lots;
+            fill.quote_atoms += lots * quote_per_lot;
+            remaining -= lots * quote_per_lot;
+
+            if lots < u128::from(level.size_in_base_lots) {
+                // The budget ran out within this level
+                book_exhausted = false;
+                break;
+            }
+        }
+
+        if book_exhausted && remaining > 0 {
+            return Err(anyhow!(
+                "Insufficient order book depth: {} of {} quote atoms unspent",
+                remaining,
+                quote_budget
+            ));
+        }
+        if fill.base_lots == 0 {
+            return Err(anyhow!("Quote amount {} buys less than one base lot", quote_budget));
+        }
+        Ok(fill)
+    }
+
+    /// Sell the fewest base lots that receive at least `quote_target` quote atoms
+    fn fill_quote_target(&self, book: &OrderBookState, quote_target: u128) -> Result<Fill> {
+        let mut fill = Fill { base_lots: 0, quote_atoms: 0, best_quote_per_lot: 0 };
+        let mut remaining = quote_target;
+
+        for level in Self::matching_levels(book, true) {
+            if remaining == 0 {
+                break;
+            }
+            let quote_per_lot = book.quote_atoms_per_base_lot(level.price_in_ticks);
+            if quote_per_lot == 0 {
+                continue;
+            }
+            if fill.best_quote_per_lot == 0 {
+                fill.best_quote_per_lot = quote_per_lot;
+            }
+
+            let lots = remaining.div_ceil(quote_per_lot).min(u128::from(level.size_in_base_lots));
+            fill.base_lots += lots;
+            fill.quote_atoms += lots * quote_per_lot;
+            remaining = remaining.saturating_sub(lots * quote_per_lot);
+        }
+
+        if remaining > 0 {
+            return Err(anyhow!(
+                "Insufficient order book depth: {} of {} quote atoms unavailable",
+                remaining,
+                quote_target
+            ));
+        }
+        Ok(fill)
+    }
+
+    /// Taker fee on `quote_atoms`, rounded up
+    fn taker_fee(quote_atoms: u128, fee_rate: u128) -> u128 {
+        (quote_atoms * fee_rate).div_ceil(FEE_RATE_DENOMINATOR)
+    }
+
+    /// A quote-denominated fee expressed in base atoms at the fill's average price
+    fn fee_in_base(fee_quote: u128, base_atoms: u128, quote_atoms: u128) -> u128 {
+        if quote_atoms == 0 {
+            return 0;
+        }
+        (fee_quote * base_atoms).div_ceil(quote_atoms)
+    }
+}
+
+fn to_u64(value: u128, what: &str) -> Result<u64> {
+    u64::try_from(value).map_err(|_| anyhow!("Phoenix {} of {} exceeds u64", what, value))
+}
+
+impl DexQuoter for PhoenixQuoter {
+    /// Quote an immediate-or-cancel order against the market's order book
+    ///
+    /// Orders fill in whole base lots, so `amount_in` is the part of the input that
+    /// actually trades and may be less than an exact-input `amount`. Phoenix charges its
+    /// taker fee in the quote token; when selling base, `fee_amount` is that fee
+    /// converted to base atoms at the average fill price.
+    fn get_swap_quote(
+        &self,
+        _pool_address: &Pubkey,
+        pool_reserves: &PoolReserves,
+        amount: u64,
+        is_token_a_to_b: bool,
+        mode: QuoteMode,
+        slippage_bps: u16,
+    ) -> Result<SwapQuote> {
+        let book = pool_reserves.order_book.as_ref().ok_or_else(||
+            anyhow!("Order book not available for Phoenix market"))?;
+        if book.base_lot_size == 0 || book.quote_lot_size == 0 || book.base_lots_per_base_unit == 0 {
+            return Err(anyhow!("Invalid lot sizes for Phoenix market"));
+        }
+
+        let fee_rate = u128::from(pool_reserves.fee_rate);
+        if fee_rate >= FEE_RATE_DENOMINATOR {
+            return Err(anyhow!("Invalid fee rate {} for Phoenix market", fee_rate));
+        }
+        let base_lot_size = u128::from(book.base_lot_size);
+        let amount = u128::from(amount);
+
+        let (amount_in, amount_out, fee_amount, price_impact) = match (mode, is_token_a_to_b) {
+            // Sell base for quote
+            (QuoteMode::ExactIn, true) => {
+                let base_lots = amount / base_lot_size;
+                if base_lots == 0 {
+                    return Err(anyhow!("Amount {} is less than one base lot", amount));
+                }
+                let fill = self.fill_base_lots(book, base_lots, true)?;
+                let fee_quote = Self::taker_fee(fill.quote_atoms, fee_rate);
+                let amount_in = fill.base_lots * base_lot_size;
+                let fee_amount = Self::fee_in_base(fee_quote, amount_in, fill.quote_atoms);
+                (amount_in, fill.quote_atoms - fee_quote, fee_amount, fill.price_impact())
+            }
+            // Buy base with quote, keeping back enough of the input for the fee
+            (QuoteMode::ExactIn, false) => {
+                let quote_budget = amount * FEE_RATE_DENOMINATOR / (FEE_RATE_DENOMINATOR + fee_rate);
+                let fill = self.fill_quote_budget(book, quote_budget)?;
+                let fee_quote = Self::taker_fee(fill.quote_atoms, fee_rate);
+                (fill.quote_atoms + fee_quote, fill.base_lots * base_lot_size, fee_quote, fill.price_impact())
+            }
+            // Sell base until the quote received covers `amount` after fees
+            (QuoteMode::ExactOut, true) => {
+                let quote_target = (amount * FEE_RATE_DENOMINATOR).div_ceil(FEE_RATE_DENOMINATOR - fee_rate);
+                let fill = self.fill_quote_target(book, quote_target)?;
+                let fee_quote = Self::taker_fee(fill.quote_atoms, fee_rate);
+                let amount_in = fill.base_lots * base_lot_size;
+                let fee_amount = Self::fee_in_base(fee_quote, amount_in, fill.quote_atoms);
+                (amount_in, amount, fee_amount, fill.price_impact())
+            }
+            // Buy enough whole base lots to cover `amount`
+            (QuoteMode::ExactOut, false) => {
+                let fill = self.fill_base_lots(book, amount.div_ceil(base_lot_size), false)?;
+                let fee_quote = Self::taker_fee(fill.quote_atoms, fee_rate);
+                (fill.quote_atoms + fee_quote, amount, fee_quote, fill.price_impact())
+            }
+        };
+
+        let amount_in = to_u64(amount_in, "input")?;
+        let amount_out = to_u64(amount_out, "output")?;
+        let fee_amount = to_u64(fee_amount, "fee")?;
+
+        match mode {
+            QuoteMode::ExactIn => {
+                // Calculate minimum output with slippage
+                let slippage_factor = 1.0 - (slippage_bps as f64 / 10000.0);
+                let min_out = (amount_out as f64 * slippage_factor).floor() as u64;
+
+                Ok(SwapQuote {
+                    amount_in,
+                    amount_out,
+                    min_amount_out: Some(min_out),
+                    max_amount_in: None,
+                    fee_amount,
+                    price_impact,
+                })
+            }
+            QuoteMode::ExactOut => {
+                // Calculate maximum input with slippage
+                let slippage_factor = 1.0 + (slippage_bps as f64 / 10000.0);
+                let max_in = (amount_in as f64 * slippage_factor).ceil() as u64;
+
+                Ok(SwapQuote {
+                    amount_in,
+                    amount_out,
+                    min_amount_out: None,
+                    max_amount_in: Some(max_in),
+                    fee_amount,
+                    price_impact,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEE_RATE: u16 = 10; // 0.1% taker fee in basis points
+
+    fn level(price_in_ticks: u64, size_in_base_lots: u64) -> OrderBookLevel {
+        OrderBookLevel { price_in_ticks, size_in_base_lots }
+    }
+
+    /// Book where a base lot is 1_000 base atoms and one tick is one quote atom per base lot
+    fn reserves() -> PoolReserves {
+        PoolReserves {
+            fee_rate: FEE_RATE,
+            order_book: Some(OrderBookState {
+                bids: vec![level(100, 10), level(99, 20), level(95, 50)],
+                asks: vec![level(101, 10), level(102, 20), level(110, 50)],
+                base_lot_size: 1_000,
+                quote_lot_size: 1,
+                tick_size_in_quote_lots_per_base_unit: 1_000,
+                base_lots_per_base_unit: 1_000,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sell_fills_across_bid_levels() {
+        let quoter = PhoenixQuoter::new();
+
+        // 25 lots: 10 @ 100 + 15 @ 99 = 2_485 quote, less a 3 quote fee
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &reserves(), 25_000, true, QuoteMode::ExactIn, 0)
+            .unwrap();
+        assert_eq!(quote.amount_in, 25_000);
+        assert_eq!(quote.amount_out, 2_482);
+        // The 3 quote fee at the average price of 2_485 quote per 25_000 base
+        assert_eq!(quote.fee_amount, 31);
+        assert!((quote.price_impact - 0.006).abs() < 1e-12);
+
+        // Only whole lots trade
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &reserves(), 25_999, true, QuoteMode::ExactIn, 0)
+            .unwrap();
+        assert_eq!(quote.amount_in, 25_000);
+        assert_eq!(quote.amount_out, 2_482);
+    }
+
+    #[test]
+    fn test_buy_fills_across_ask_levels() {
+        let quoter = PhoenixQuoter::new();
+
+        // 25 lots: 10 @ 101 + 15 @ 102 = 2_540 quote, plus a 3 quote fee
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &reserves(), 25_000, false, QuoteMode::ExactOut, 0)
+            .unwrap();
+        assert_eq!(quote.amount_in, 2_543);
+        assert_eq!(quote.amount_out, 25_000);
+        assert_eq!(quote.fee_amount, 3);
+
+        // Spending that input buys the same 25 lots
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &reserves(), 2_543, false, QuoteMode::ExactIn, 0)
+            .unwrap();
+        assert_eq!(quote.amount_in, 2_543);
+        assert_eq!(quote.amount_out, 25_000);
+        assert!((quote.price_impact - 0.6 / 101.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_exact_out_sell_round_trips_exact_in() {
+        let quoter = PhoenixQuoter::new();
+
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &reserves(), 2_482, true, QuoteMode::ExactOut, 0)
+            .unwrap();
+        assert_eq!(quote.amount_in, 25_000);
+        assert_eq!(quote.amount_out, 2_482);
+    }
+
+    #[test]
+    fn test_rejects_orders_beyond_book_depth() {
+        let quoter = PhoenixQuoter::new();
+
+        // The bids hold 80 lots in total
+        assert!(quoter
+            .get_swap_quote(&Pubkey::default(), &reserves(), 80_000, true, QuoteMode::ExactIn, 0)
+            .is_ok());
+        assert!(quoter
+            .get_swap_quote(&Pubkey::default(), &reserves(), 81_000, true, QuoteMode::ExactIn, 0)
+            .is_err());
+        assert!(quoter
+            .get_swap_quote(&Pubkey::default(), &reserves(), 81_000, false, QuoteMode::ExactOut, 0)
+            .is_err());
+        assert!(quoter
+            .get_swap_quote(&Pubkey::default(), &reserves(), 1_000_000, false, QuoteMode::ExactIn, 0)
+            .is_err());
+
+        let no_book = PoolReserves { order_book: None, ..reserves() };
+        assert!(quoter
+            .get_swap_quote(&Pubkey::default(), &no_book, 25_000, true, QuoteMode::ExactIn, 0)
+            .is_err());
+    }
+}
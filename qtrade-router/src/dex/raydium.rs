@@ -5,7 +5,7 @@
 use solana_sdk::pubkey::Pubkey;
 use anyhow::{Result, anyhow};
 use super::DexQuoter;
-use super::types::{SwapQuote, PoolReserves};
+use super::types::{SwapQuote, PoolReserves, QuoteMode};
 
 /// Implementation for Raydium quoting functionality
 pub struct RaydiumQuoter;
@@ -18,6 +18,9 @@ impl RaydiumQuoter {
 
     /// Calculate output amount based on Constant Product Market Maker (CPMM) formula
     /// This is the formula used by most AMMs: x * y = k (constant product)
+    ///
+    /// amount_out = y * dx_with_fee / (x + dx_with_fee), in u128 so reserves near
+    /// u64::MAX can't overflow or lose precision
     fn calculate_cpmm_output(
         &self,
         reserve_in: u64,
@@ -29,35 +32,27 @@ impl RaydiumQuoter {
         if reserve_in == 0 || reserve_out == 0 {
             return Err(anyhow!("Invalid reserves for CPMM calculation"));
         }
+        if fee_rate as u128 > FEE_RATE_DENOMINATOR {
+            return Err(anyhow!("Invalid fee rate {} for CPMM calculation", fee_rate));
+        }
 
-        // Convert to f64 for calculation
-        let reserve_in_f = reserve_in as f64;
-        let reserve_out_f = reserve_out as f64;
-        let amount_in_f = amount_in as f64;
-
-        // Calculate fee
-        // fee_rate is in basis points (1/100 of a percent), e.g., 30 = 0.3%
-        let fee_rate_f = fee_rate as f64 / 10000.0;
-
-        // Calculate amount in after fee
-        let amount_in_with_fee = amount_in_f * (1.0 - fee_rate_f);
-
-        // Calculate output amount using constant product formula: x * y = k
-        // (reserve_in + amount_in_with_fee) * (reserve_out - out) = reserve_in * reserve_out
-        let product = reserve_in_f * reserve_out_f;
-        let new_reserve_in = reserve_in_f + amount_in_with_fee;
-        let new_reserve_out = product / new_reserve_in;
-
-        // Calculate output amount
-        let amount_out = reserve_out_f - new_reserve_out;
+        // Calculate amount in after fee, charging the fee rounded up
+        let amount_in_with_fee = amount_in - fee_amount(amount_in, fee_rate);
 
-        // Convert back to u64, ensuring we don't exceed available reserves
-        let amount_out_u64 = amount_out.min(reserve_out_f) as u64;
+        let numerator = (reserve_out as u128)
+            .checked_mul(amount_in_with_fee as u128)
+            .ok_or_else(|| anyhow!("CPMM output calculation overflowed"))?;
+        let denominator = (reserve_in as u128) + (amount_in_with_fee as u128);
 
-        Ok(amount_out_u64)
+        // Never more than reserve_out, since dx_with_fee / (x + dx_with_fee) < 1
+        u64::try_from(numerator / denominator)
+            .map_err(|_| anyhow!("CPMM output does not fit in u64"))
     }
 
     /// Calculate input amount required for desired output using CPMM formula
+    ///
+    /// amount_in = ceil(ceil(x * dy / (y - dy)) / (1 - fee)), in u128 and rounded up
+    /// so the input always covers the requested output
     fn calculate_cpmm_input_for_output(
         &self,
         reserve_in: u64,
@@ -69,65 +64,52 @@ impl RaydiumQuoter {
         if reserve_in == 0 || reserve_out == 0 || amount_out >= reserve_out {
             return Err(anyhow!("Invalid parameters for CPMM calculation"));
         }
-
-        // Convert to f64 for calculation
-        let reserve_in_f = reserve_in as f64;
-        let reserve_out_f = reserve_out as f64;
-        let amount_out_f = amount_out as f64;
-
-        // Calculate fee rate factor
-        // fee_rate is in basis points (1/100 of a percent), e.g., 30 = 0.3%
-        let fee_rate_f = fee_rate as f64 / 10000.0;
-        let fee_factor = 1.0 - fee_rate_f;
-
-        // Calculate new reserve out after removing the desired output
-        let new_reserve_out = reserve_out_f - amount_out_f;
+        if fee_rate as u128 >= FEE_RATE_DENOMINATOR {
+            return Err(anyhow!("Invalid fee rate {} for CPMM calculation", fee_rate));
+        }
 
         // Calculate required input before fees using constant product formula: x * y = k
-        // reserve_in * reserve_out = (reserve_in + amount_in) * new_reserve_out
-        let product = reserve_in_f * reserve_out_f;
-        let amount_in_before_fee = (product / new_reserve_out) - reserve_in_f;
+        // reserve_in * reserve_out = (reserve_in + amount_in) * (reserve_out - amount_out)
+        let amount_in_before_fee = (reserve_in as u128)
+            .checked_mul(amount_out as u128)
+            .ok_or_else(|| anyhow!("CPMM input calculation overflowed"))?
+            .div_ceil((reserve_out - amount_out) as u128);
 
         // Account for fees to get the actual input amount
-        let amount_in = amount_in_before_fee / fee_factor;
+        let amount_in = amount_in_before_fee
+            .checked_mul(FEE_RATE_DENOMINATOR)
+            .ok_or_else(|| anyhow!("CPMM input calculation overflowed"))?
+            .div_ceil(FEE_RATE_DENOMINATOR - fee_rate as u128);
 
-        // Convert back to u64 and add 1 to account for potential rounding
-        // This ensures we always provide enough input
-        let amount_in_u64 = amount_in.ceil() as u64;
-
-        Ok(amount_in_u64)
+        u64::try_from(amount_in).map_err(|_| anyhow!("CPMM input does not fit in u64"))
     }
 }
 
-impl DexQuoter for RaydiumQuoter {
-    fn get_swap_quote(
+/// fee_rate is in basis points (1/100 of a percent), e.g., 30 = 0.3%
+const FEE_RATE_DENOMINATOR: u128 = 10_000;
+
+/// Fee charged on `amount`, rounded up (at most `amount` for valid fee rates)
+fn fee_amount(amount: u64, fee_rate: u16) -> u64 {
+    let fee = (amount as u128 * fee_rate as u128).div_ceil(FEE_RATE_DENOMINATOR);
+    fee.min(amount as u128) as u64
+}
+
+impl RaydiumQuoter {
+    /// Quote the output for an exact input amount
+    fn quote_exact_in(
         &self,
-        _pool_address: &Pubkey,
-        pool_reserves: &PoolReserves,
+        reserve_in: u64,
+        reserve_out: u64,
         amount_in: u64,
-        is_token_a_to_b: bool,
+        fee_rate: u16,
         slippage_bps: u16,
     ) -> Result<SwapQuote> {
-        // For Raydium CPMM, we need token reserves
-        let token_a_reserves = pool_reserves.token_a_reserves.ok_or_else(||
-            anyhow!("Token A reserves not available for Raydium pool"))?;
-
-        let token_b_reserves = pool_reserves.token_b_reserves.ok_or_else(||
-            anyhow!("Token B reserves not available for Raydium pool"))?;
-
-        // Determine which token is being swapped in and which is being swapped out
-        let (reserve_in, reserve_out) = if is_token_a_to_b {
-            (token_a_reserves, token_b_reserves)
-        } else {
-            (token_b_reserves, token_a_reserves)
-        };
-
         // Calculate the expected output amount
         let estimated_out = self.calculate_cpmm_output(
             reserve_in,
             reserve_out,
             amount_in,
-            pool_reserves.fee_rate,
+            fee_rate,
         )?;
 
         // Calculate minimum output with slippage
@@ -135,8 +117,7 @@ impl DexQuoter for RaydiumQuoter {
         let min_out = (estimated_out as f64 * slippage_factor).floor() as u64;
 
         // Calculate fee amount
-        let fee_rate_f = pool_reserves.fee_rate as f64 / 10000.0;
-        let fee_amount = (amount_in as f64 * fee_rate_f).ceil() as u64;
+        let fee_amount = fee_amount(amount_in, fee_rate);
 
         // Calculate price impact
         // In a real implementation, we'd compare with oracle/market prices
@@ -155,4 +136,198 @@ impl DexQuoter for RaydiumQuoter {
             price_impact,
         })
     }
+
+    /// Quote the input needed to receive an exact output amount
+    fn quote_exact_out(
+        &self,
+        reserve_in: u64,
+        reserve_out: u64,
+        amount_out: u64,
+        fee_rate: u16,
+        slippage_bps: u16,
+    ) -> Result<SwapQuote> {
+        // Calculate the required input amount
+        let estimated_in = self.calculate_cpmm_input_for_output(
+            reserve_in,
+            reserve_out,
+            amount_out,
+            fee_rate,
+        )?;
+
+        // Calculate maximum input with slippage
+        let slippage_factor = 1.0 + (slippage_bps as f64 / 10000.0);
+        let max_in = (estimated_in as f64 * slippage_factor).ceil() as u64;
+
+        // Calculate fee amount
+        let fee_amount = fee_amount(estimated_in, fee_rate);
+
+        // Calculate price impact using the same approximation as exact input
+        let no_impact_rate = reserve_out as f64 / reserve_in as f64;
+        let execution_rate = amount_out as f64 / estimated_in as f64;
+        let price_impact = (no_impact_rate - execution_rate) / no_impact_rate;
+        let price_impact = price_impact.max(0.0);
+
+        Ok(SwapQuote {
+            amount_in: estimated_in,
+            amount_out,
+            min_amount_out: None,
+            max_amount_in: Some(max_in),
+            fee_amount,
+            price_impact,
+        })
+    }
+}
+
+impl DexQuoter for RaydiumQuoter {
+    fn get_swap_quote(
+        &self,
+        _pool_address: &Pubkey,
+        pool_reserves: &PoolReserves,
+        amount: u64,
+        is_token_a_to_b: bool,
+        mode: QuoteMode,
+        slippage_bps: u16,
+    ) -> Result<SwapQuote> {
+        // For Raydium CPMM, we need token reserves
+        let token_a_reserves = pool_reserves.token_a_reserves.ok_or_else(||
+            anyhow!("Token A reserves not available for Raydium pool"))?;
+
+        let token_b_reserves = pool_reserves.token_b_reserves.ok_or_else(||
+            anyhow!("Token B reserves not available for Raydium pool"))?;
+
+        // Determine which token is being swapped in and which is being swapped out
+        let (reserve_in, reserve_out) = if is_token_a_to_b {
+            (token_a_reserves, token_b_reserves)
+        } else {
+            (token_b_reserves, token_a_reserves)
+        };
+
+        match mode {
+            QuoteMode::ExactIn => self.quote_exact_in(
+                reserve_in,
+                reserve_out,
+                amount,
+                pool_reserves.fee_rate,
+                slippage_bps,
+            ),
+            QuoteMode::ExactOut => self.quote_exact_out(
+                reserve_in,
+                reserve_out,
+                amount,
+                pool_reserves.fee_rate,
+                slippage_bps,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reserves(token_a: u64, token_b: u64, fee_rate: u16) -> PoolReserves {
+        PoolReserves {
+            fee_rate,
+            token_a_reserves: Some(token_a),
+            token_b_reserves: Some(token_b),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cpmm_exact_in() {
+        // 0.3% fee: 1e6 - 1e12 / (1e6 + 9_970) = 9_871.58
+        let pool = reserves(1_000_000, 1_000_000, 30);
+        let quote = RaydiumQuoter::new()
+            .get_swap_quote(&Pubkey::default(), &pool, 10_000, true, QuoteMode::ExactIn, 50)
+            .unwrap();
+
+        assert_eq!(quote.amount_in, 10_000);
+        assert_eq!(quote.amount_out, 9_871);
+        assert_eq!(quote.min_amount_out, Some(9_821));
+        assert_eq!(quote.max_amount_in, None);
+        assert_eq!(quote.fee_amount, 30);
+    }
+
+    #[test]
+    fn test_cpmm_exact_out() {
+        // Inverse of the exact-in quote: (1e12 / 990_129 - 1e6) / 0.997 = 9_999.41
+        let pool = reserves(1_000_000, 1_000_000, 30);
+        let quoter = RaydiumQuoter::new();
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 9_871, true, QuoteMode::ExactOut, 50)
+            .unwrap();
+
+        assert_eq!(quote.amount_in, 10_000);
+        assert_eq!(quote.amount_out, 9_871);
+        assert_eq!(quote.min_amount_out, None);
+        assert_eq!(quote.max_amount_in, Some(10_050));
+        assert_eq!(quote.fee_amount, 30);
+
+        // Half the output side: (1e12 / 500_000 - 1e6) / 0.997 = 1_003_009.03
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 500_000, false, QuoteMode::ExactOut, 0)
+            .unwrap();
+        assert_eq!(quote.amount_in, 1_003_010);
+
+        // The pool can never pay out its entire reserve
+        assert!(quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 1_000_000, true, QuoteMode::ExactOut, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_cpmm_reserves_near_u64_max() {
+        // x * y is ~2^128 here, far past u64 and f64 precision
+        let pool = reserves(u64::MAX, u64::MAX, 30);
+        let quoter = RaydiumQuoter::new();
+
+        // Fee of 3_000 leaves 997_000 in: 997_000 - 997_000^2 / (2^64 + 997_000) floors to 996_999
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 1_000_000, true, QuoteMode::ExactIn, 0)
+            .unwrap();
+        assert_eq!(quote.amount_out, 996_999);
+        assert_eq!(quote.fee_amount, 3_000);
+
+        // ceil(2^64 * 1e6 / (2^64 - 1e6)) = 1_000_001, then ceil(1_000_001 / 0.997) = 1_003_011
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 1_000_000, true, QuoteMode::ExactOut, 0)
+            .unwrap();
+        assert_eq!(quote.amount_in, 1_003_011);
+
+        // Draining all but one unit needs ~2^128 input, which doesn't fit in u64
+        assert!(quoter
+            .get_swap_quote(&Pubkey::default(), &pool, u64::MAX - 1, true, QuoteMode::ExactOut, 0)
+            .is_err());
+        // Swapping u64::MAX into a one-unit reserve still prices exactly: M^2 / (M + 1)
+        let pool = reserves(1, u64::MAX, 0);
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, u64::MAX, true, QuoteMode::ExactIn, 0)
+            .unwrap();
+        assert_eq!(quote.amount_out, u64::MAX - 1);
+    }
+
+    #[test]
+    fn test_cpmm_small_swaps() {
+        // Fee of 1 leaves 99 in: 2e9 * 99 / (1e9 + 99) = 197.99998
+        let pool = reserves(1_000_000_000, 2_000_000_000, 30);
+        let quoter = RaydiumQuoter::new();
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 100, true, QuoteMode::ExactIn, 0)
+            .unwrap();
+        assert_eq!(quote.amount_out, 197);
+        assert_eq!(quote.fee_amount, 1);
+
+        // A single unit is eaten entirely by the rounded-up fee
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 1, true, QuoteMode::ExactIn, 0)
+            .unwrap();
+        assert_eq!(quote.amount_out, 0);
+
+        // Fee rates above 100% are rejected rather than quoted
+        let pool = reserves(1_000_000, 1_000_000, 10_001);
+        assert!(quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 100, true, QuoteMode::ExactIn, 0)
+            .is_err());
+    }
 }
@@ -2,6 +2,8 @@
 //
 // This module contains shared types for DEX quote providers.
 
+use std::str::FromStr;
+
 /// Identifies the DEX type for quote retrieval
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DexType {
@@ -9,6 +11,85 @@ pub enum DexType {
     Raydium,
     RaydiumCpmm,
     RaydiumClmm,
+    ConstantSum,
+    WeightedPool,
+    MeteoraDlmm,
+    Phoenix,
+    /// A pool the router can't attribute to a supported DEX; never quoted
+    Unknown,
+}
+
+impl DexType {
+    /// Every DEX type the router can quote (excludes [`DexType::Unknown`])
+    pub const ALL: [DexType; 8] = [
+        DexType::Orca,
+        DexType::Raydium,
+        DexType::RaydiumCpmm,
+        DexType::RaydiumClmm,
+        DexType::ConstantSum,
+        DexType::WeightedPool,
+        DexType::MeteoraDlmm,
+        DexType::Phoenix,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DexType::Orca => "orca",
+            DexType::Raydium => "raydium",
+            DexType::RaydiumCpmm => "raydium-cpmm",
+            DexType::RaydiumClmm => "raydium-clmm",
+            DexType::ConstantSum => "constant-sum",
+            DexType::WeightedPool => "weighted",
+            DexType::MeteoraDlmm => "meteora-dlmm",
+            DexType::Phoenix => "phoenix",
+            DexType::Unknown => "unknown",
+        }
+    }
+
+    /// Denominator this DEX's quoter applies to `PoolReserves::fee_rate`
+    ///
+    /// Concentrated-liquidity and bin-based quoters take hundredths of a basis point
+    /// (3000 = 0.3%), the reserve-based and order-book quoters take basis points (30 = 0.3%).
+    pub fn fee_rate_denominator(&self) -> u64 {
+        match self {
+            DexType::Orca | DexType::RaydiumClmm | DexType::MeteoraDlmm => 1_000_000,
+            DexType::Raydium
+            | DexType::RaydiumCpmm
+            | DexType::ConstantSum
+            | DexType::WeightedPool
+            | DexType::Phoenix
+            | DexType::Unknown => 10_000,
+        }
+    }
+}
+
+/// Parses a DEX name as used in the runtime's `active_dexes` (case-insensitive)
+impl FromStr for DexType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "orca" => Ok(DexType::Orca),
+            "raydium" => Ok(DexType::Raydium),
+            "raydium-cpmm" | "raydium_cpmm" => Ok(DexType::RaydiumCpmm),
+            "raydium-clmm" | "raydium_clmm" => Ok(DexType::RaydiumClmm),
+            "constant-sum" | "constant_sum" => Ok(DexType::ConstantSum),
+            "weighted" => Ok(DexType::WeightedPool),
+            "meteora-dlmm" | "meteora_dlmm" => Ok(DexType::MeteoraDlmm),
+            "phoenix" => Ok(DexType::Phoenix),
+            other => Err(format!("Unknown DEX: {}", other)),
+        }
+    }
+}
+
+/// Which side of a swap a quote's amount fixes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum QuoteMode {
+    /// The amount is the exact input; the quote estimates the output
+    #[default]
+    ExactIn,
+    /// The amount is the desired output; the quote estimates the input needed
+    ExactOut,
 }
 
 /// Represents pool reserves and state for quote calculation
@@ -23,7 +104,7 @@ pub struct PoolReserves {
     /// Current liquidity in the pool
     pub liquidity: u128,
 
-    /// Fee rate in basis points (e.g., 3000 for 0.3%)
+    /// Fee rate over `DexType::fee_rate_denominator` (e.g., 3000 for 0.3% on Orca, 30 on Raydium)
     pub fee_rate: u16,
 
     /// Tick spacing for the pool
@@ -34,6 +115,28 @@ pub struct PoolReserves {
 
     /// Token B reserves (for CPMM-style AMMs)
     pub token_b_reserves: Option<u64>,
+
+    /// Per-token balances (for multi-asset weighted pools), indexed by pool token position
+    pub token_balances: Option<Vec<u64>>,
+
+    /// Per-token weights matching `token_balances` (for weighted pools, e.g. 0.8/0.2)
+    pub token_weights: Option<Vec<f64>>,
+
+    /// Liquidity of each non-empty bin (for bin-based AMMs like Meteora DLMM)
+    pub bins: Option<Vec<BinLiquidity>>,
+
+    /// Resting orders (for order-book DEXes like Phoenix)
+    pub order_book: Option<OrderBookState>,
+}
+
+// Bin and order book state come from the indexed pool state
+pub use qtrade_shared_types::{BinLiquidity, OrderBookLevel, OrderBookState};
+
+impl PoolReserves {
+    /// Fraction of each input left after the pool's fee (the solver's gamma), e.g. 0.997 for 0.3%
+    pub fn fee_multiplier(&self, dex_type: DexType) -> f64 {
+        1.0 - self.fee_rate as f64 / dex_type.fee_rate_denominator() as f64
+    }
 }
 
 impl Default for PoolReserves {
@@ -46,6 +149,10 @@ impl Default for PoolReserves {
             tick_spacing: 0,
             token_a_reserves: None,
             token_b_reserves: None,
+            token_balances: None,
+            token_weights: None,
+            bins: None,
+            order_book: None,
         }
     }
 }
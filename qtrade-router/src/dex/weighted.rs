@@ -0,0 +1,264 @@
+// Weighted (Balancer-style) DEX implementation for quoting
+//
+// This module provides quotes for weighted pools, which hold two or more tokens
+// with fixed per-token weights and keep the weighted product of balances constant
+
+use solana_sdk::pubkey::Pubkey;
+use anyhow::{Result, anyhow};
+use super::DexQuoter;
+use super::types::{SwapQuote, PoolReserves, QuoteMode};
+
+/// Implementation for weighted pool quoting
+pub struct WeightedPoolQuoter;
+
+impl WeightedPoolQuoter {
+    /// Create a new WeightedPoolQuoter instance
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Calculate output amount using the Balancer out-given-in formula
+    ///
+    /// amount_out = balance_out * (1 - (balance_in / (balance_in + amount_in_after_fee)) ^ (weight_in / weight_out))
+    fn calculate_out_given_in(
+        &self,
+        balance_in: u64,
+        weight_in: f64,
+        balance_out: u64,
+        weight_out: f64,
+        amount_in: u64,
+        fee_rate: u16,
+    ) -> Result<u64> {
+        if balance_in == 0 || balance_out == 0 {
+            return Err(anyhow!("Invalid balances for weighted pool calculation"));
+        }
+        if weight_in <= 0.0 || weight_out <= 0.0 {
+            return Err(anyhow!("Invalid weights for weighted pool calculation"));
+        }
+
+        // fee_rate is in basis points (1/100 of a percent), e.g., 20 = 0.2%
+        let fee_rate_f = fee_rate as f64 / 10000.0;
+        let amount_in_with_fee = amount_in as f64 * (1.0 - fee_rate_f);
+
+        let balance_in_f = balance_in as f64;
+        let base = balance_in_f / (balance_in_f + amount_in_with_fee);
+        let amount_out = balance_out as f64 * (1.0 - base.powf(weight_in / weight_out));
+
+        // Never quote more than the pool holds
+        Ok(amount_out.max(0.0).min(balance_out as f64) as u64)
+    }
+
+    /// Calculate input amount required for a desired output using the Balancer in-given-out formula
+    ///
+    /// amount_in = balance_in * ((balance_out / (balance_out - amount_out)) ^ (weight_out / weight_in) - 1) / (1 - fee)
+    fn calculate_in_given_out(
+        &self,
+        balance_in: u64,
+        weight_in: f64,
+        balance_out: u64,
+        weight_out: f64,
+        amount_out: u64,
+        fee_rate: u16,
+    ) -> Result<u64> {
+        if balance_in == 0 || balance_out == 0 || amount_out >= balance_out {
+            return Err(anyhow!("Invalid parameters for weighted pool calculation"));
+        }
+        if weight_in <= 0.0 || weight_out <= 0.0 {
+            return Err(anyhow!("Invalid weights for weighted pool calculation"));
+        }
+
+        let balance_out_f = balance_out as f64;
+        let base = balance_out_f / (balance_out_f - amount_out as f64);
+        let amount_in_before_fee = balance_in as f64 * (base.powf(weight_out / weight_in) - 1.0);
+
+        // fee_rate is in basis points (1/100 of a percent), e.g., 20 = 0.2%
+        let fee_rate_f = fee_rate as f64 / 10000.0;
+        let amount_in = amount_in_before_fee / (1.0 - fee_rate_f);
+
+        // Round up so the input always covers the requested output
+        Ok(amount_in.ceil() as u64)
+    }
+
+    /// Get a swap quote between any two tokens of a weighted pool
+    ///
+    /// `token_in` and `token_out` index into `token_balances`/`token_weights`.
+    pub fn get_swap_quote_for_tokens(
+        &self,
+        pool_reserves: &PoolReserves,
+        token_in: usize,
+        token_out: usize,
+        amount: u64,
+        mode: QuoteMode,
+        slippage_bps: u16,
+    ) -> Result<SwapQuote> {
+        let balances = pool_reserves.token_balances.as_ref().ok_or_else(||
+            anyhow!("Token balances not available for weighted pool"))?;
+
+        let weights = pool_reserves.token_weights.as_ref().ok_or_else(||
+            anyhow!("Token weights not available for weighted pool"))?;
+
+        if balances.len() != weights.len() {
+            return Err(anyhow!(
+                "Weighted pool has {} balances but {} weights",
+                balances.len(),
+                weights.len()
+            ));
+        }
+        if token_in == token_out || token_in >= balances.len() || token_out >= balances.len() {
+            return Err(anyhow!(
+                "Invalid token indices {} -> {} for weighted pool with {} tokens",
+                token_in,
+                token_out,
+                balances.len()
+            ));
+        }
+
+        let (balance_in, weight_in) = (balances[token_in], weights[token_in]);
+        let (balance_out, weight_out) = (balances[token_out], weights[token_out]);
+
+        // Calculate the side of the swap the caller didn't fix
+        let (amount_in, amount_out) = match mode {
+            QuoteMode::ExactIn => {
+                let estimated_out = self.calculate_out_given_in(
+                    balance_in,
+                    weight_in,
+                    balance_out,
+                    weight_out,
+                    amount,
+                    pool_reserves.fee_rate,
+                )?;
+                (amount, estimated_out)
+            }
+            QuoteMode::ExactOut => {
+                let estimated_in = self.calculate_in_given_out(
+                    balance_in,
+                    weight_in,
+                    balance_out,
+                    weight_out,
+                    amount,
+                    pool_reserves.fee_rate,
+                )?;
+                (estimated_in, amount)
+            }
+        };
+
+        // Calculate slippage bounds on the estimated side
+        let (min_amount_out, max_amount_in) = match mode {
+            QuoteMode::ExactIn => {
+                let slippage_factor = 1.0 - (slippage_bps as f64 / 10000.0);
+                (Some((amount_out as f64 * slippage_factor).floor() as u64), None)
+            }
+            QuoteMode::ExactOut => {
+                let slippage_factor = 1.0 + (slippage_bps as f64 / 10000.0);
+                (None, Some((amount_in as f64 * slippage_factor).ceil() as u64))
+            }
+        };
+
+        // Calculate fee amount
+        let fee_rate_f = pool_reserves.fee_rate as f64 / 10000.0;
+        let fee_amount = (amount_in as f64 * fee_rate_f).ceil() as u64;
+
+        // Price impact relative to the spot price (balance_out / weight_out) / (balance_in / weight_in)
+        let spot_rate = (balance_out as f64 / weight_out) / (balance_in as f64 / weight_in);
+        let execution_rate = amount_out as f64 / amount_in as f64;
+        let price_impact = ((spot_rate - execution_rate) / spot_rate).max(0.0);
+
+        Ok(SwapQuote {
+            amount_in,
+            amount_out,
+            min_amount_out,
+            max_amount_in,
+            fee_amount,
+            price_impact,
+        })
+    }
+}
+
+impl DexQuoter for WeightedPoolQuoter {
+    /// Quote between the pool's first two tokens (index 0 is token A, index 1 is token B)
+    fn get_swap_quote(
+        &self,
+        _pool_address: &Pubkey,
+        pool_reserves: &PoolReserves,
+        amount: u64,
+        is_token_a_to_b: bool,
+        mode: QuoteMode,
+        slippage_bps: u16,
+    ) -> Result<SwapQuote> {
+        let (token_in, token_out) = if is_token_a_to_b { (0, 1) } else { (1, 0) };
+        self.get_swap_quote_for_tokens(pool_reserves, token_in, token_out, amount, mode, slippage_bps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reserves(balances: Vec<u64>, weights: Vec<f64>, fee_rate: u16) -> PoolReserves {
+        PoolReserves {
+            fee_rate,
+            token_balances: Some(balances),
+            token_weights: Some(weights),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_equal_weights_match_constant_product() {
+        // 50/50 with no fee: 1_000 * (1 - 1_000 / 1_100) = 90.909...
+        let pool = reserves(vec![1_000, 1_000], vec![0.5, 0.5], 0);
+        let quote = WeightedPoolQuoter::new()
+            .get_swap_quote(&Pubkey::default(), &pool, 100, true, QuoteMode::ExactIn, 0)
+            .unwrap();
+        assert_eq!(quote.amount_out, 90);
+        assert_eq!(quote.fee_amount, 0);
+    }
+
+    #[test]
+    fn test_unequal_weights_out_given_in() {
+        // 80/20 pool, swapping the heavy token in: exponent 0.8 / 0.2 = 4
+        let pool = reserves(vec![4_000_000_000, 1_000_000_000], vec![0.8, 0.2], 0);
+        let quoter = WeightedPoolQuoter::new();
+
+        // 1e9 * (1 - (4e9 / 4.1e9)^4) = 94_049_355.2
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 100_000_000, true, QuoteMode::ExactIn, 0)
+            .unwrap();
+        assert_eq!(quote.amount_out, 94_049_355);
+
+        // With a 0.3% fee the effective input is 99_700_000: 93_784_150.4
+        let pool = reserves(vec![4_000_000_000, 1_000_000_000], vec![0.8, 0.2], 30);
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 100_000_000, true, QuoteMode::ExactIn, 0)
+            .unwrap();
+        assert_eq!(quote.amount_out, 93_784_150);
+        assert_eq!(quote.fee_amount, 300_000);
+        assert!(quote.price_impact > 0.0);
+
+        // Exact out inverts the no-fee quote: 4e9 * ((1e9 / 905_950_645) ^ 0.25 - 1) = 99_999_999.77
+        let pool = reserves(vec![4_000_000_000, 1_000_000_000], vec![0.8, 0.2], 0);
+        let quote = quoter
+            .get_swap_quote(&Pubkey::default(), &pool, 94_049_355, true, QuoteMode::ExactOut, 0)
+            .unwrap();
+        assert_eq!(quote.amount_in, 100_000_000);
+        assert_eq!(quote.max_amount_in, Some(100_000_000));
+    }
+
+    #[test]
+    fn test_multi_asset_pool_token_indices() {
+        // Four equally weighted tokens, like the reference balancer pool in solve2
+        let pool = reserves(vec![10_000, 10_000, 10_000, 10_000], vec![0.25; 4], 0);
+        let quoter = WeightedPoolQuoter::new();
+
+        // Equal weights reduce to constant product: 10_000 * (1 - 10_000 / 13_000) = 2_307.69
+        let quote = quoter.get_swap_quote_for_tokens(&pool, 2, 3, 3_000, QuoteMode::ExactIn, 0).unwrap();
+        assert_eq!(quote.amount_out, 2_307);
+
+        // And back again: 10_000 * (10_000 / 7_693 - 1) = 2_998.83
+        let quote = quoter.get_swap_quote_for_tokens(&pool, 2, 3, 2_307, QuoteMode::ExactOut, 0).unwrap();
+        assert_eq!(quote.amount_in, 2_999);
+
+        assert!(quoter.get_swap_quote_for_tokens(&pool, 1, 1, 1_000, QuoteMode::ExactIn, 0).is_err());
+        assert!(quoter.get_swap_quote_for_tokens(&pool, 0, 4, 1_000, QuoteMode::ExactIn, 0).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+// Tracking of consecutive solve cycles without a profitable result
+//
+// An occasional non-optimal status or all-zero deltas is genuine no-arbitrage. Many
+// in a row usually means the inputs are bad (a stale pool cache, broken reserves),
+// so the router counts the streak and warns once it reaches a threshold.
+
+use lazy_static::lazy_static;
+use opentelemetry::global;
+use opentelemetry::metrics::{Counter, Gauge};
+use qtrade_shared_types::ArbitrageResult;
+use tracing::warn;
+
+use crate::QTRADE_ROUTER_METER_NAME;
+
+/// Consecutive empty cycles after which the router warns (an hour at the default interval)
+pub const DEFAULT_EMPTY_CYCLE_THRESHOLD: u64 = 60;
+
+// Deltas at or below this magnitude are treated as no trade, as in the relayer
+const MIN_SIGNIFICANT_DELTA: f64 = 1e-6;
+
+lazy_static! {
+    static ref CONSECUTIVE_EMPTY_CYCLES: Gauge<u64> = {
+        global::meter(QTRADE_ROUTER_METER_NAME)
+            .u64_gauge("qtrade.router.consecutive_empty_cycles")
+            .with_description("Number of solve cycles in a row that produced no profitable result")
+            .build()
+    };
+
+    static ref EMPTY_CYCLE_ALERTS: Counter<u64> = {
+        global::meter(QTRADE_ROUTER_METER_NAME)
+            .u64_counter("qtrade.router.empty_cycle_alerts")
+            .with_description("Number of times the empty solve cycle streak reached its threshold")
+            .build()
+    };
+}
+
+/// Whether a solver result has anything worth executing
+///
+/// Mirrors the relayer's validation: the status must be optimal and at least one
+/// pool must have a significant delta.
+pub fn is_profitable(result: &ArbitrageResult) -> bool {
+    result.status == "optimal"
+        && result
+            .deltas
+            .iter()
+            .flatten()
+            .any(|delta| delta.abs() > MIN_SIGNIFICANT_DELTA)
+}
+
+/// Counts consecutive solve cycles without a profitable result
+#[derive(Debug)]
+pub struct EmptyCycleTracker {
+    threshold: u64,
+    consecutive: u64,
+}
+
+impl EmptyCycleTracker {
+    pub fn new(threshold: u64) -> Self {
+        Self {
+            threshold,
+            consecutive: 0,
+        }
+    }
+
+    /// Record the outcome of one cycle (`None` if the solve failed)
+    ///
+    /// A profitable result resets the streak. Returns `true` when this cycle took the
+    /// streak to the threshold, which is when the warning fires; it fires again only
+    /// after the streak has been reset.
+    pub fn record(&mut self, result: Option<&ArbitrageResult>) -> bool {
+        if result.is_some_and(is_profitable) {
+            self.consecutive = 0;
+            CONSECUTIVE_EMPTY_CYCLES.record(0, &[]);
+            return false;
+        }
+
+        self.consecutive += 1;
+        CONSECUTIVE_EMPTY_CYCLES.record(self.consecutive, &[]);
+
+        if self.consecutive != self.threshold {
+            return false;
+        }
+
+        EMPTY_CYCLE_ALERTS.add(1, &[]);
+        warn!(
+            "Solver has produced no profitable result for {} consecutive cycles; \
+             check the pool cache and reserves for stale or bad data",
+            self.consecutive
+        );
+        true
+    }
+
+    /// Number of consecutive cycles without a profitable result
+    pub fn consecutive(&self) -> u64 {
+        self.consecutive
+    }
+}
+
+impl Default for EmptyCycleTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_EMPTY_CYCLE_THRESHOLD)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(status: &str, deltas: Vec<Vec<f64>>) -> ArbitrageResult {
+        ArbitrageResult {
+            deltas,
+            lambdas: vec![vec![0.0, 0.0]],
+            a_matrices: vec![],
+            status: status.to_string(),
+            execution_order: vec![0],
+            market_values: vec![1.0, 1.0],
+            correlation_id: None,
+            emitted_at: None,
+            enqueued_at: None,
+            pool_fees: vec![],
+            pool_states: vec![],
+            version: ArbitrageResult::VERSION,
+        }
+    }
+
+    #[test]
+    fn test_warning_fires_at_threshold_and_resets() {
+        let mut tracker = EmptyCycleTracker::new(3);
+        let zero_deltas = result("optimal", vec![vec![0.0, 0.0]]);
+        let infeasible = result("infeasible", vec![vec![1.0, 0.0]]);
+
+        assert!(!tracker.record(Some(&zero_deltas)));
+        assert!(!tracker.record(Some(&infeasible)));
+        // A failed solve counts as empty too
+        assert!(tracker.record(None));
+        assert_eq!(tracker.consecutive(), 3);
+
+        // The warning fires once per streak
+        assert!(!tracker.record(Some(&zero_deltas)));
+        assert_eq!(tracker.consecutive(), 4);
+
+        let profitable = result("optimal", vec![vec![0.5, 0.0]]);
+        assert!(!tracker.record(Some(&profitable)));
+        assert_eq!(tracker.consecutive(), 0);
+
+        for _ in 0..2 {
+            assert!(!tracker.record(Some(&zero_deltas)));
+        }
+        assert!(tracker.record(Some(&zero_deltas)));
+    }
+}
@@ -5,9 +5,10 @@ use pyo3::types::PyAny;
 use pyo3::types::PyList;
 use spl_pod::solana_pubkey::Pubkey;
 use anyhow::Result;
-use opentelemetry::global;
-use opentelemetry::trace::Tracer;
-use qtrade_shared_types::ArbitrageResult;
+use opentelemetry::{global, KeyValue};
+use opentelemetry::trace::{TraceContextExt, Tracer};
+use qtrade_shared_types::{ArbitrageResult, IndexedPool, PoolState};
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
@@ -15,50 +16,48 @@ use tokio::sync::mpsc;
 use tracing::{error, info};
 use tokio::sync::Mutex;
 use lazy_static::lazy_static;
+use opentelemetry::metrics::Counter;
 use qtrade_relayer;
 use async_trait::async_trait;
+use tokio_util::sync::CancellationToken;
 
 // Add our DEX quoting module
 pub mod dex;
 
-// Define placeholder structs for different pool data types
-// These would be replaced with actual data structures from your project
+// Trade execution ordering for solver output
+pub mod ordering;
 
-/// Placeholder for Orca Whirlpool data structure
-#[derive(Debug)]
-struct OrcaWhirlpoolData {
-    pub sqrt_price: u128,
-    pub tick_current_index: i32,
-    pub liquidity: u128,
-    pub fee_rate: u16,
-    pub tick_spacing: u16,
-    // Add other fields as needed
-}
+// Tracking of consecutive solve cycles without a profitable result
+pub mod empty_cycles;
 
-/// Placeholder for Raydium CPMM data structure
-#[derive(Debug)]
-struct RaydiumCpmmData {
-    pub token_a_amount: u64,
-    pub token_b_amount: u64,
-    pub fee_rate: u16,
-    // Add other fields as needed
-}
+// Caching of DEX quotes between solve cycles
+pub mod quote_cache;
 
-/// Placeholder for Raydium CLMM data structure
-#[derive(Debug)]
-struct RaydiumClmmData {
-    pub sqrt_price: u128,
-    pub tick_current_index: i32,
-    pub liquidity: u128,
-    pub fee_rate: u16,
-    pub tick_spacing: u16,
-    // Add other fields as needed
-}
+// Sizing of quote input amounts by pool depth
+pub mod probe_sizing;
 
+// Choice of solver backend
+pub mod backend;
+
+// Dual decomposition routing for the CFMMRouter backend
+pub mod cfmm;
 
 const ROUTER: &str = "router";
 const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+// Pools that haven't been updated within this window are left out of the optimization
+const MAX_POOL_AGE: Duration = Duration::from_secs(300);
 const QTRADE_ROUTER_TRACER_NAME: &str = "qtrade_router";
+const QTRADE_ROUTER_METER_NAME: &str = "qtrade_router";
+// Fee multipliers of the reference network in `solve`, used for pools without an indexed fee
+const REFERENCE_FEES: [f64; 5] = [0.998, 0.997, 0.997, 0.997, 0.999];
+// Global token indices of each pool in the reference network
+const REFERENCE_LOCAL_INDICES: [&[usize]; 5] = [&[0, 1, 2, 3], &[0, 1], &[1, 2], &[2, 3], &[2, 3]];
+// Reserves of each pool in the reference network
+const REFERENCE_RESERVES: [&[f64]; 5] = [&[4.0, 4.0, 4.0, 4.0], &[10.0, 1.0], &[1.0, 5.0], &[40.0, 50.0], &[10.0, 10.0]];
+// Market value of each token in the reference network
+const REFERENCE_MARKET_VALUE: [f64; 4] = [1.5, 10.0, 2.0, 3.0];
+/// Quotes moving the price by more than this fraction are dropped (0.2 = 20%)
+pub const DEFAULT_MAX_PRICE_IMPACT: f64 = 0.2;
 
 // Global channel for passing arbitrage results from router to relayer
 lazy_static! {
@@ -73,6 +72,29 @@ lazy_static! {
     };
 }
 
+lazy_static! {
+    static ref UNKNOWN_DEX_POOLS_SKIPPED: Counter<u64> = {
+        global::meter(QTRADE_ROUTER_METER_NAME)
+            .u64_counter("qtrade.router.unknown_dex_pools_skipped")
+            .with_description("Number of pools left out of quoting because their DEX couldn't be determined")
+            .build()
+    };
+
+    static ref HIGH_IMPACT_QUOTES_DROPPED: Counter<u64> = {
+        global::meter(QTRADE_ROUTER_METER_NAME)
+            .u64_counter("qtrade.router.high_impact_quotes_dropped")
+            .with_description("Number of quotes dropped because their price impact exceeded the maximum")
+            .build()
+    };
+
+    static ref BACKEND_SOLVES: Counter<u64> = {
+        global::meter(QTRADE_ROUTER_METER_NAME)
+            .u64_counter("qtrade.router.backend_solves")
+            .with_description("Number of solves attempted, by solver backend")
+            .build()
+    };
+}
+
 // Use the PoolCache trait and PoolEntry type from qtrade-shared-types
 pub use qtrade_shared_types::PoolCache;
 pub use qtrade_shared_types::PoolEntry;
@@ -85,55 +107,105 @@ pub use qtrade_shared_types::PoolEntry;
 /// - Call appropriate DEX module APIs for quotes based on reserves
 /// - Determine arbitrage opportunities
 /// - Output results to the relayer queue
-pub async fn run_router<T: PoolCache + 'static>(pool_cache: Arc<T>) -> Result<()> {
+///
+/// Only pools of a DEX in `active_dexes` are quoted, so a misbehaving DEX integration
+/// can be switched off from configuration. Quotes with a price impact above
+/// `max_price_impact` are dropped. Pools are probed as `probe_settings` says, and every
+/// cycle solves with `backend`.
+///
+/// Returns `Ok(())` once the cancellation token is cancelled, or an error straight away
+/// if `probe_settings` are invalid or `backend` isn't supported.
+pub async fn run_router<T: PoolCache + 'static>(
+    pool_cache: Arc<T>,
+    active_dexes: Vec<dex::types::DexType>,
+    max_price_impact: f64,
+    probe_settings: probe_sizing::ProbeSettings,
+    backend: backend::RouterBackend,
+    cancellation_token: CancellationToken,
+) -> Result<()> {
+    probe_settings.validate()?;
+    // Refuse up front rather than solving with a different backend than configured
+    backend.ensure_supported()?;
+    info!("Router solving with the {} backend", backend.name());
+
     let tracer = global::tracer(QTRADE_ROUTER_TRACER_NAME);
     // Clone the pool_cache Arc once outside the loop to avoid lifetime issues
     let pool_cache_ref = Arc::clone(&pool_cache);
+    // Long runs of unprofitable cycles usually point at bad input data
+    let mut empty_cycle_tracker = empty_cycles::EmptyCycleTracker::default();
+    // Quotes of pools whose reserves haven't changed are reused across cycles
+    let mut quote_cache = quote_cache::QuoteCache::new();
+
+    // Don't solve over an empty cache while the indexer is still priming it
+    info!("Waiting for pool cache to be ready...");
+    tokio::select! {
+        _ = cancellation_token.cancelled() => {
+            info!("Cancellation token activated, shutting down router");
+            return Ok(());
+        }
+        _ = pool_cache.wait_until_ready() => {
+            info!("Pool cache ready, starting router");
+        }
+    }
 
     loop {
+        // Check if we've been asked to cancel
+        if cancellation_token.is_cancelled() {
+            info!("Cancellation token activated, shutting down router");
+            return Ok(());
+        }
+
         let span_name = format!("{}::run_router", ROUTER);
         // Clone another reference to the pool_cache for this iteration
         let pool_cache_iteration = Arc::clone(&pool_cache_ref);
+        let active_dexes = &active_dexes;
+        let probe_settings = &probe_settings;
+        let empty_cycle_tracker = &mut empty_cycle_tracker;
+        let quote_cache = &mut quote_cache;
 
-        let result: Result<(), anyhow::Error> = tracer.in_span(span_name, move |_cx| async move {
+        let result: Result<(), anyhow::Error> = tracer.in_span(span_name, move |cx| async move {
             // Read pool reserves cache
             info!("Reading pool reserves cache...");
 
-            // Get entries from the PoolCache instance
-            let pool_entries = pool_cache_iteration.get_all_entries_as_slice().await;
+            // Get fresh entries from the PoolCache instance, skipping pools that haven't ticked recently
+            let pool_entries = pool_cache_iteration.get_fresh_entries(MAX_POOL_AGE).await;
             info!("Retrieved {} pool entries from cache", pool_entries.len());
 
             // Call appropriate DEX module APIs for quotes based on reserves
             info!("Calling DEX module APIs for quotes based on reserves...");
             // Get quotes from DEXes using our new module
-            let quotes = get_dex_quotes(&pool_entries)?;
+            let quotes = get_dex_quotes(&pool_entries, active_dexes, probe_settings, quote_cache)?;
+            let quotes = filter_price_impact(quotes, max_price_impact);
             info!("Retrieved {} quotes from DEXes", quotes.len());
 
             // Determine arbitrage opportunities
             info!("Determining arbitrage opportunities...");
 
-            // We need to use the original entries directly rather than trying to clone them
-            // The issue is that Box<dyn Any + Send + Sync> doesn't implement Clone
-            // Since the solve function takes a reference, we can pass references to the original entries
-            let router_entries = pool_entries;
-
-            match solve(&router_entries) {
-                Ok(result) => {
+            match solve_with_backend(backend, &pool_entries) {
+                Ok(mut result) => {
                     info!("Arbitrage opportunities determined successfully with status: {}", result.status);
+                    qtrade_shared_types::HEALTH_STATUS.record_router_solve();
+                    empty_cycle_tracker.record(Some(&result));
+
+                    // Tag the opportunity so its relayer logs and spans can be traced back to this solve
+                    let correlation_id = result.assign_correlation_id();
+                    cx.span().set_attribute(KeyValue::new("correlation_id", correlation_id.to_string()));
 
                     // Output results to relayer queue
-                    info!("Sending arbitrage results to relayer queue...");
+                    info!("Sending arbitrage result {} to relayer queue...", correlation_id);
 
                     // Acquire the mutex lock to access the sender
                     let sender = ARBITRAGE_SENDER.lock().await;
+                    result.mark_emitted();
                     if let Err(e) = sender.send(result).await {
                         error!("Failed to send arbitrage result to relayer: {:?}", e);
                     } else {
-                        info!("Successfully sent arbitrage result to relayer queue");
+                        info!("Successfully sent arbitrage result {} to relayer queue", correlation_id);
                     }
                 },
                 Err(e) => {
                     error!("Failed to determine arbitrage opportunities: {:?}", e);
+                    empty_cycle_tracker.record(None);
                 }
             }
 
@@ -148,121 +220,198 @@ pub async fn run_router<T: PoolCache + 'static>(pool_cache: Arc<T>) -> Result<()
             error!("Error running router: {:?}", e);
         }
 
-        // Wait for specified duration before running the check again
-        sleep(CHECK_INTERVAL).await;
+        // Wait for specified duration before running the check again, unless cancelled
+        tokio::select! {
+            _ = cancellation_token.cancelled() => {
+                info!("Cancellation token activated, shutting down router");
+                return Ok(());
+            }
+            _ = sleep(CHECK_INTERVAL) => {}
+        }
     }
 }
 
-/// Extract pool reserves from pool data based on DEX type
-fn extract_pool_reserves(pool_data: &Box<dyn std::any::Any + Send + Sync>, dex_type: dex::types::DexType) -> Option<dex::types::PoolReserves> {
-    match dex_type {
-        dex::types::DexType::Orca => {
-            // Try to extract Orca Whirlpool data
-            // This is just an example - you'll need to adjust based on actual data structure
-            if let Some(orca_data) = pool_data.downcast_ref::<OrcaWhirlpoolData>() {
-                Some(dex::types::PoolReserves {
-                    sqrt_price: orca_data.sqrt_price,
-                    tick_current_index: orca_data.tick_current_index,
-                    liquidity: orca_data.liquidity,
-                    fee_rate: orca_data.fee_rate,
-                    tick_spacing: orca_data.tick_spacing,
-                    token_a_reserves: None, // Orca doesn't use these directly
-                    token_b_reserves: None,
-                })
-            } else {
-                // If we can't downcast to Orca data, log a warning and return default
-                tracing::warn!("Could not extract Orca pool reserves data");
-                Some(dex::types::PoolReserves::default())
-            }
-        },
-        dex::types::DexType::RaydiumCpmm => {
-            // For CPMM-style AMMs, we need token reserves
-            if let Some(cpmm_data) = pool_data.downcast_ref::<RaydiumCpmmData>() {
-                Some(dex::types::PoolReserves {
-                    sqrt_price: 0, // Not used by CPMM
-                    tick_current_index: 0, // Not used by CPMM
-                    liquidity: 0, // Not used by CPMM
-                    fee_rate: cpmm_data.fee_rate,
-                    tick_spacing: 0, // Not used by CPMM
-                    token_a_reserves: Some(cpmm_data.token_a_amount),
-                    token_b_reserves: Some(cpmm_data.token_b_amount),
-                })
-            } else {
-                tracing::warn!("Could not extract Raydium CPMM pool reserves data");
-                Some(dex::types::PoolReserves::default())
-            }
+/// Extract pool reserves from an indexed pool based on DEX type
+///
+/// The fee parsed from the pool's accounts, when the indexer has one, overrides whatever
+/// the state itself reports, rescaled to the unit the DEX's quoter expects.
+fn extract_pool_reserves(pool: &IndexedPool, dex_type: dex::types::DexType) -> Option<dex::types::PoolReserves> {
+    let mut pool_reserves = extract_state_reserves(&pool.state)?;
+    if let Some(fee) = pool.fee {
+        pool_reserves.fee_rate = fee.scaled_rate(dex_type.fee_rate_denominator());
+    }
+
+    Some(pool_reserves)
+}
+
+/// Solver fee multipliers (gamma) for the first `default_fees.len()` pool entries
+///
+/// Entries with an indexed fee contribute it directly, whatever their DEX; the rest
+/// fall back to `default_fees` at the same position.
+pub fn pool_fee_multipliers(pool_entries: &[PoolEntry], default_fees: &[f64]) -> Vec<f64> {
+    default_fees
+        .iter()
+        .enumerate()
+        .map(|(i, &default_fee)| {
+            pool_entries
+                .get(i)
+                .and_then(|(_, pool)| pool.fee)
+                .map(|fee| 1.0 - fee.rate())
+                .unwrap_or(default_fee)
+        })
+        .collect()
+}
+
+/// Extract pool reserves from a pool's state, or `None` for state the router doesn't model
+fn extract_state_reserves(state: &PoolState) -> Option<dex::types::PoolReserves> {
+    match state {
+        PoolState::Orca(pool) | PoolState::RaydiumClmm(pool) => Some(dex::types::PoolReserves {
+            sqrt_price: pool.sqrt_price,
+            tick_current_index: pool.tick_current_index,
+            liquidity: pool.liquidity,
+            fee_rate: pool.fee_rate,
+            tick_spacing: pool.tick_spacing,
+            ..Default::default()
+        }),
+        PoolState::Raydium(pool) | PoolState::RaydiumCpmm(pool) | PoolState::ConstantSum(pool) => {
+            Some(dex::types::PoolReserves {
+                fee_rate: pool.fee_rate,
+                token_a_reserves: Some(pool.token_a_amount),
+                token_b_reserves: Some(pool.token_b_amount),
+                ..Default::default()
+            })
         },
-        // Add other DEX type handlers here
-        _ => {
-            // For unsupported types, just return default values for now
-            tracing::warn!("Unsupported DEX type for pool reserves extraction: {:?}", dex_type);
-            Some(dex::types::PoolReserves::default())
-        }
+        PoolState::WeightedPool(pool) => Some(dex::types::PoolReserves {
+            fee_rate: pool.fee_rate,
+            token_balances: Some(pool.balances.clone()),
+            token_weights: Some(pool.weights.clone()),
+            ..Default::default()
+        }),
+        PoolState::MeteoraDlmm(pair) => Some(dex::types::PoolReserves {
+            tick_current_index: pair.active_id, // Active bin id
+            fee_rate: pair.fee_rate,
+            tick_spacing: pair.bin_step,
+            bins: Some(pair.bins.clone()),
+            ..Default::default()
+        }),
+        PoolState::Phoenix(market) => Some(dex::types::PoolReserves {
+            fee_rate: market.taker_fee_bps,
+            order_book: Some(market.order_book.clone()),
+            ..Default::default()
+        }),
+        PoolState::Unknown => None,
+    }
+}
+
+/// Determine the DEX type of a pool entry
+///
+/// Typed pool state identifies its own DEX; state of a DEX the router doesn't model
+/// falls back to `dex::determine_dex_type` on the pool address, which yields
+/// `DexType::Unknown` for pools it can't attribute.
+fn pool_dex_type(pool_address: &Pubkey, pool: &IndexedPool) -> dex::types::DexType {
+    match pool.state {
+        PoolState::Orca(_) => dex::types::DexType::Orca,
+        PoolState::Raydium(_) => dex::types::DexType::Raydium,
+        PoolState::RaydiumCpmm(_) => dex::types::DexType::RaydiumCpmm,
+        PoolState::RaydiumClmm(_) => dex::types::DexType::RaydiumClmm,
+        PoolState::ConstantSum(_) => dex::types::DexType::ConstantSum,
+        PoolState::WeightedPool(_) => dex::types::DexType::WeightedPool,
+        PoolState::MeteoraDlmm(_) => dex::types::DexType::MeteoraDlmm,
+        PoolState::Phoenix(_) => dex::types::DexType::Phoenix,
+        PoolState::Unknown => dex::determine_dex_type(pool_address),
     }
 }
 
-/// Get quotes from DEXes for all pools
+/// Get quotes from DEXes for all pools of an active DEX
 ///
 /// This function takes the pool entries and returns a vector of quotes from each DEX
 /// The quotes can then be used to determine arbitrage opportunities
-pub fn get_dex_quotes(pool_entries: &[PoolEntry]) -> Result<Vec<dex::types::SwapQuote>, anyhow::Error> {
+/// Pools whose DEX can't be determined, or isn't in `active_dexes`, are skipped. Input
+/// amounts and slippage come from `probe_settings`.
+/// Quotes come from `quote_cache` while a pool's reserves are unchanged, and the cache
+/// only keeps the pools quoted here.
+pub fn get_dex_quotes(
+    pool_entries: &[PoolEntry],
+    active_dexes: &[dex::types::DexType],
+    probe_settings: &probe_sizing::ProbeSettings,
+    quote_cache: &mut quote_cache::QuoteCache,
+) -> Result<Vec<dex::types::SwapQuote>, anyhow::Error> {
     let mut quotes = Vec::new();
+    let mut skipped_pools = 0;
+    let mut unknown_pools = 0;
+    let mut quoted_pools = HashSet::new();
+    let (hits_before, misses_before) = (quote_cache.hits(), quote_cache.misses());
 
     // Use tracing for better diagnostic information
     tracing::debug!("Getting DEX quotes for {} pools", pool_entries.len());
 
     for (pool_address, pool_data) in pool_entries {
         // Determine the DEX type based on the pool address
-        let dex_type = dex::determine_dex_type(pool_address);
+        let dex_type = pool_dex_type(pool_address, pool_data);
         tracing::debug!("Pool {:?} identified as DEX type: {:?}", pool_address, dex_type);
 
+        if dex_type == dex::types::DexType::Unknown {
+            tracing::warn!("Skipping pool {:?}: unknown DEX", pool_address);
+            UNKNOWN_DEX_POOLS_SKIPPED.add(1, &[]);
+            unknown_pools += 1;
+            continue;
+        }
+
+        if !active_dexes.contains(&dex_type) {
+            tracing::debug!("Skipping pool {:?}: DEX {} is not active", pool_address, dex_type.as_str());
+            skipped_pools += 1;
+            continue;
+        }
+
         // Extract pool reserves based on DEX type
         if let Some(pool_reserves) = extract_pool_reserves(pool_data, dex_type) {
             // Create a quoter for this DEX type
-            let quoter = dex::create_dex_quoter(dex_type);
-
-            // Get quotes for varying input amounts to better understand the price impact curve
-            let input_amounts = [1_000_000u64, 10_000_000u64, 100_000_000u64]; // 1, 10, 100 units with 6 decimal places
-            let slippage_bps = 30; // 0.3% slippage tolerance
-
-            for &amount_in in &input_amounts {
-                // Get quote for A->B
-                match quoter.get_swap_quote(
-                    pool_address,
-                    &pool_reserves,
-                    amount_in,
-                    true, // A to B
-                    slippage_bps,
-                ) {
-                    Ok(quote) => {
-                        tracing::debug!(
-                            "A->B quote for pool {:?}: {} in, {} out, {} fee, {:.4}% impact",
-                            pool_address, quote.amount_in, quote.amount_out, quote.fee_amount, quote.price_impact * 100.0
-                        );
-                        quotes.push(quote);
-                    },
-                    Err(e) => {
-                        tracing::warn!("Failed to get A->B quote for pool {:?}: {}", pool_address, e);
-                    }
-                }
-
-                // Get quote for B->A
-                match quoter.get_swap_quote(
-                    pool_address,
-                    &pool_reserves,
-                    amount_in,
-                    false, // B to A
-                    slippage_bps,
-                ) {
-                    Ok(quote) => {
-                        tracing::debug!(
-                            "B->A quote for pool {:?}: {} in, {} out, {} fee, {:.4}% impact",
-                            pool_address, quote.amount_in, quote.amount_out, quote.fee_amount, quote.price_impact * 100.0
-                        );
-                        quotes.push(quote);
-                    },
-                    Err(e) => {
-                        tracing::warn!("Failed to get B->A quote for pool {:?}: {}", pool_address, e);
+            let Some(quoter) = dex::create_dex_quoter(dex_type) else {
+                tracing::warn!("No quoter for DEX {}, skipping pool {:?}", dex_type.as_str(), pool_address);
+                continue;
+            };
+
+            // Get quotes for input amounts sized to the pool's depth to sample its price impact curve
+            let input_amounts = [
+                probe_sizing::probe_amounts(&pool_reserves, true, &probe_settings.probe_amounts),
+                probe_sizing::probe_amounts(&pool_reserves, false, &probe_settings.probe_amounts),
+            ];
+            let slippage_bps = probe_settings.slippage_bps;
+            let reserves_hash = quote_cache::reserves_hash(&pool_reserves);
+            quoted_pools.insert(*pool_address);
+
+            let probes = input_amounts.iter().map(Vec::len).max().unwrap_or(0);
+            for probe in 0..probes {
+                for (is_token_a_to_b, direction) in [(true, "A->B"), (false, "B->A")] {
+                    let Some(&amount_in) = input_amounts[usize::from(!is_token_a_to_b)].get(probe) else {
+                        continue;
+                    };
+                    let key = quote_cache::QuoteKey {
+                        pool_address: *pool_address,
+                        reserves_hash,
+                        amount: amount_in,
+                        is_token_a_to_b,
+                        mode: dex::types::QuoteMode::ExactIn,
+                        slippage_bps,
+                    };
+                    match quote_cache.get_or_quote(&key, || quoter.get_swap_quote(
+                        pool_address,
+                        &pool_reserves,
+                        amount_in,
+                        is_token_a_to_b,
+                        dex::types::QuoteMode::ExactIn,
+                        slippage_bps,
+                    )) {
+                        Ok(quote) => {
+                            tracing::debug!(
+                                "{} quote for pool {:?}: {} in, {} out, {} fee, {:.4}% impact",
+                                direction, pool_address, quote.amount_in, quote.amount_out, quote.fee_amount, quote.price_impact * 100.0
+                            );
+                            quotes.push(quote);
+                        },
+                        Err(e) => {
+                            tracing::warn!("Failed to get {} quote for pool {:?}: {}", direction, pool_address, e);
+                        }
                     }
                 }
             }
@@ -271,10 +420,257 @@ pub fn get_dex_quotes(pool_entries: &[PoolEntry]) -> Result<Vec<dex::types::Swap
         }
     }
 
-    tracing::info!("Generated {} quotes from {} pools", quotes.len(), pool_entries.len());
+    quote_cache.retain_pools(&quoted_pools);
+    tracing::debug!(
+        "Quote cache: {} hits, {} misses",
+        quote_cache.hits() - hits_before,
+        quote_cache.misses() - misses_before
+    );
+
+    if skipped_pools > 0 {
+        tracing::info!("Skipped {} pools on inactive DEXes", skipped_pools);
+    }
+    if unknown_pools > 0 {
+        tracing::info!("Skipped {} pools on unknown DEXes", unknown_pools);
+    }
+    tracing::info!(
+        "Generated {} quotes from {} pools",
+        quotes.len(),
+        pool_entries.len() - skipped_pools - unknown_pools
+    );
     Ok(quotes)
 }
 
+/// Drop quotes whose price impact exceeds `max_price_impact` (a fraction, 0.2 = 20%)
+///
+/// Such quotes come from pools too illiquid for the amount and would produce bad fills.
+pub fn filter_price_impact(quotes: Vec<dex::types::SwapQuote>, max_price_impact: f64) -> Vec<dex::types::SwapQuote> {
+    let quoted = quotes.len();
+    let kept: Vec<dex::types::SwapQuote> = quotes
+        .into_iter()
+        .filter(|quote| quote.price_impact <= max_price_impact)
+        .collect();
+
+    let dropped = quoted - kept.len();
+    if dropped > 0 {
+        tracing::info!("Dropped {} quotes with price impact above {:.2}%", dropped, max_price_impact * 100.0);
+        HIGH_IMPACT_QUOTES_DROPPED.add(dropped as u64, &[]);
+    }
+    kept
+}
+
+/// Status reported when the solver returns non-finite values (e.g. for an infeasible problem)
+pub const SOLVER_STATUS_INFEASIBLE: &str = "infeasible";
+/// Status reported when the solver output does not have the expected shape or types
+pub const SOLVER_STATUS_ERROR: &str = "error";
+
+/// Reasons solver output can't be turned into trades
+#[derive(Debug)]
+enum SolverOutputError {
+    /// A value was NaN or infinite
+    NonFinite(f64),
+    /// A value was not a list of floats
+    Malformed(String),
+}
+
+fn extract_finite_vector(values: &Bound<'_, PyAny>) -> Result<Vec<f64>, SolverOutputError> {
+    let list = values
+        .downcast::<PyList>()
+        .map_err(|e| SolverOutputError::Malformed(e.to_string()))?;
+    list.iter()
+        .map(|val| {
+            let val = val
+                .extract::<f64>()
+                .map_err(|e| SolverOutputError::Malformed(e.to_string()))?;
+            if val.is_finite() {
+                Ok(val)
+            } else {
+                Err(SolverOutputError::NonFinite(val))
+            }
+        })
+        .collect()
+}
+
+fn extract_finite_matrix(values: &Bound<'_, PyAny>) -> Result<Vec<Vec<f64>>, SolverOutputError> {
+    let list = values
+        .downcast::<PyList>()
+        .map_err(|e| SolverOutputError::Malformed(e.to_string()))?;
+    list.iter().map(|row| extract_finite_vector(&row)).collect()
+}
+
+fn extract_finite_matrices(values: &Bound<'_, PyAny>) -> Result<Vec<Vec<Vec<f64>>>, SolverOutputError> {
+    let list = values
+        .downcast::<PyList>()
+        .map_err(|e| SolverOutputError::Malformed(e.to_string()))?;
+    list.iter().map(|matrix| extract_finite_matrix(&matrix)).collect()
+}
+
+/// Convert the `(problem, deltas, lambdas, A)` tuple returned by `solve_arbitrage`
+///
+/// If any delta, lambda or A-matrix entry is NaN or infinite, or isn't a float, the result
+/// has status [`SOLVER_STATUS_INFEASIBLE`] or [`SOLVER_STATUS_ERROR`] and no trades, so
+/// garbage values never reach the relayer's amount computations.
+pub fn arbitrage_result_from_solver(
+    output: &Bound<'_, PyAny>,
+    local_indices: &[Vec<usize>],
+    market_value: &[f64],
+) -> PyResult<ArbitrageResult> {
+    let (prob, deltas, lambdas, a): (Bound<'_, PyAny>, Bound<'_, PyAny>, Bound<'_, PyAny>, Bound<'_, PyAny>)
+        = output.extract()?;
+
+    // Get the optimization problem status
+    let status = prob.getattr("status")?.extract::<String>()?;
+    println!("Optimization problem status: {:?}", status);
+
+    let converted = extract_finite_matrix(&deltas).and_then(|deltas_vec| {
+        let lambdas_vec = extract_finite_matrix(&lambdas)?;
+        let a_vec = extract_finite_matrices(&a)?;
+        Ok((deltas_vec, lambdas_vec, a_vec))
+    });
+    let (deltas_vec, lambdas_vec, a_vec) = match converted {
+        Ok(converted) => converted,
+        Err(e) => {
+            let fallback_status = match e {
+                SolverOutputError::NonFinite(_) => SOLVER_STATUS_INFEASIBLE,
+                SolverOutputError::Malformed(_) => SOLVER_STATUS_ERROR,
+            };
+            println!("Discarding solver output with status {}: {:?}", status, e);
+            return Ok(ArbitrageResult {
+                deltas: Vec::new(),
+                lambdas: Vec::new(),
+                a_matrices: Vec::new(),
+                status: fallback_status.to_string(),
+                execution_order: Vec::new(),
+                market_values: market_value.to_vec(),
+                correlation_id: None,
+                emitted_at: None,
+                enqueued_at: None,
+                pool_fees: Vec::new(),
+                pool_states: Vec::new(),
+                version: ArbitrageResult::VERSION,
+            });
+        }
+    };
+
+    println!("Converted deltas: {:?}", deltas_vec);
+    println!("Converted lambdas: {:?}", lambdas_vec);
+    println!("Converted matrix A: {:?}", a_vec);
+
+    // Order the trades so tokens received from earlier pools fund later ones
+    let execution_order = execution_order(local_indices, &deltas_vec, &lambdas_vec, market_value);
+
+    // Create and return the arbitrage result
+    Ok(ArbitrageResult {
+        deltas: deltas_vec,
+        lambdas: lambdas_vec,
+        a_matrices: a_vec,
+        status,
+        execution_order,
+        market_values: market_value.to_vec(),
+        correlation_id: None,
+        emitted_at: None,
+        enqueued_at: None,
+        pool_fees: vec![],
+        pool_states: vec![],
+        version: ArbitrageResult::VERSION,
+    })
+}
+
+/// Execution order of a solution's trades, empty if it can't be determined
+fn execution_order(
+    local_indices: &[Vec<usize>],
+    deltas: &[Vec<f64>],
+    lambdas: &[Vec<f64>],
+    market_value: &[f64],
+) -> Vec<usize> {
+    match ordering::optimal_trade_order(local_indices, deltas, lambdas, market_value) {
+        Ok(trade_ordering) => {
+            println!("Execution order: {:?}", trade_ordering.order);
+            println!("Tokens required to kick-start arbitrage: {:?} (value {})",
+                trade_ordering.tokens_required, trade_ordering.value_required);
+            trade_ordering.order
+        }
+        Err(e) => {
+            println!("Failed to determine execution order: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Solve with the given backend
+///
+/// Errors for a backend that isn't supported instead of falling back to another one.
+pub fn solve_with_backend(
+    backend: backend::RouterBackend,
+    pool_entries: &[PoolEntry],
+) -> Result<ArbitrageResult, Box<dyn std::error::Error>> {
+    backend.ensure_supported()?;
+    BACKEND_SOLVES.add(1, &[KeyValue::new("backend", backend.name())]);
+
+    let mut result = match backend {
+        backend::RouterBackend::Cvxpy => solve(pool_entries)?,
+        backend::RouterBackend::CfmmRouter => solve_cfmm(pool_entries)?,
+        backend::RouterBackend::OpenQaoa => unreachable!("rejected by ensure_supported"),
+    };
+
+    // The relayer re-checks each trade against live reserves with its pool's own fee, and
+    // builds swaps from the pool's indexed state
+    result.pool_fees = pool_entries.iter().map(|(_, pool)| pool.fee).collect();
+    result.pool_states = pool_entries.iter().map(|(_, pool)| pool.state.clone()).collect();
+    Ok(result)
+}
+
+/// Solve the reference network with the CFMMRouter backend
+///
+/// Same network and fees as [`solve`], but routed by [`cfmm::route`] without Python.
+/// A route that still overdraws a token is reported as `optimal_inaccurate`, which the
+/// relayer won't execute.
+pub fn solve_cfmm(pool_entries: &[PoolEntry]) -> Result<ArbitrageResult, Box<dyn std::error::Error>> {
+    println!("Received {} pool entries for CFMM routing", pool_entries.len());
+
+    let local_indices: Vec<Vec<usize>> = REFERENCE_LOCAL_INDICES.iter().map(|local| local.to_vec()).collect();
+    let fees = pool_fee_multipliers(pool_entries, &REFERENCE_FEES);
+    let market_value = REFERENCE_MARKET_VALUE.to_vec();
+
+    // Pool 0 is a weighted balancer pool and pool 4 constant-sum, as in `solve_arbitrage`
+    let cfmms: Vec<cfmm::Cfmm> = REFERENCE_RESERVES.iter().zip(&fees).enumerate()
+        .map(|(pool_id, (reserves, &fee))| match pool_id {
+            0 => cfmm::Cfmm::GeometricMean { reserves: reserves.to_vec(), weights: vec![4.0, 3.0, 2.0, 1.0], fee },
+            4 => cfmm::Cfmm::ConstantSum { reserves: reserves.to_vec(), fee },
+            _ => cfmm::Cfmm::GeometricMean { reserves: reserves.to_vec(), weights: vec![1.0; reserves.len()], fee },
+        })
+        .collect();
+
+    let routing = cfmm::route(&cfmms, &local_indices, &market_value, cfmm::DEFAULT_MAX_ITERATIONS)?;
+    let status = if routing.feasible { "optimal" } else { "optimal_inaccurate" };
+    println!("CFMM routing finished with status {}, value {}", status, routing.value);
+
+    // Local-global matrices, as `solve_arbitrage` builds them
+    let a_matrices = local_indices.iter()
+        .map(|local| {
+            (0..market_value.len())
+                .map(|global| local.iter().map(|&idx| if idx == global { 1.0 } else { 0.0 }).collect())
+                .collect()
+        })
+        .collect();
+    let execution_order = execution_order(&local_indices, &routing.deltas, &routing.lambdas, &market_value);
+
+    Ok(ArbitrageResult {
+        deltas: routing.deltas,
+        lambdas: routing.lambdas,
+        a_matrices,
+        status: status.to_string(),
+        execution_order,
+        market_values: market_value,
+        correlation_id: None,
+        emitted_at: None,
+        enqueued_at: None,
+        pool_fees: vec![],
+        pool_states: vec![],
+        version: ArbitrageResult::VERSION,
+    })
+}
+
 pub fn solve(pool_entries: &[PoolEntry]) -> Result<ArbitrageResult, Box<dyn std::error::Error>> {
     println!("Received {} pool entries for solving", pool_entries.len());
 
@@ -283,22 +679,11 @@ pub fn solve(pool_entries: &[PoolEntry]) -> Result<ArbitrageResult, Box<dyn std:
 
         // Problem data
         let global_indices = vec![0, 1, 2, 3];
-        let local_indices = vec![
-            vec![0, 1, 2, 3],
-            vec![0, 1],
-            vec![1, 2],
-            vec![2, 3],
-            vec![2, 3],
-        ];
-        let reserves = vec![
-            vec![4.0, 4.0, 4.0, 4.0],
-            vec![10.0, 1.0],
-            vec![1.0, 5.0],
-            vec![40.0, 50.0],
-            vec![10.0, 10.0],
-        ];
-        let fees = vec![0.998, 0.997, 0.997, 0.997, 0.999];
-        let market_value = vec![1.5, 10.0, 2.0, 3.0];
+        let local_indices: Vec<Vec<usize>> = REFERENCE_LOCAL_INDICES.iter().map(|local| local.to_vec()).collect();
+        let reserves: Vec<Vec<f64>> = REFERENCE_RESERVES.iter().map(|reserves| reserves.to_vec()).collect();
+        // Fees come from the indexed pools where available, the reference values otherwise
+        let fees = pool_fee_multipliers(pool_entries, &REFERENCE_FEES);
+        let market_value = REFERENCE_MARKET_VALUE.to_vec();
 
         // Convert Rust data to Python objects
         let py_global_indices = PyList::new(py, &global_indices)?;
@@ -323,65 +708,7 @@ pub fn solve(pool_entries: &[PoolEntry]) -> Result<ArbitrageResult, Box<dyn std:
         );
         let inner_result = qtrade.call_method("solve_arbitrage", args, None)?;
 
-        // Extract results from the Python function
-        let (prob, deltas, lambdas, a): (Bound<'_, PyAny>, Bound<'_, PyList>, Bound<'_, PyList>, Bound<'_, PyList>)
-            = inner_result.extract()?;
-
-        // Print results for debugging
-        println!("Optimization problem status: {:?}", prob.getattr("status")?);
-
-        // Convert PyList objects to Rust vectors
-        let deltas_vec: Vec<Vec<f64>> = deltas
-            .iter()
-            .map(|delta_list| {
-                let delta_py_list = delta_list.downcast::<PyList>().unwrap();
-                delta_py_list.iter()
-                    .map(|val| val.extract::<f64>().unwrap())
-                    .collect::<Vec<f64>>()
-            })
-            .collect();
-
-        let lambdas_vec: Vec<Vec<f64>> = lambdas
-            .iter()
-            .map(|lambda_list| {
-                let lambda_py_list = lambda_list.downcast::<PyList>().unwrap();
-                lambda_py_list.iter()
-                    .map(|val| val.extract::<f64>().unwrap())
-                    .collect::<Vec<f64>>()
-            })
-            .collect();
-
-        let a_vec: Vec<Vec<Vec<f64>>> = a
-            .iter()
-            .map(|a_matrix| {
-                let a_matrix_py_list = a_matrix.downcast::<PyList>().unwrap();
-                a_matrix_py_list.iter()
-                    .map(|row| {
-                        let row_py_list = row.downcast::<PyList>().unwrap();
-                        row_py_list.iter()
-                            .map(|val| val.extract::<f64>().unwrap())
-                            .collect::<Vec<f64>>()
-                    })
-                    .collect::<Vec<Vec<f64>>>()
-            })
-            .collect();
-
-        println!("Converted deltas: {:?}", deltas_vec);
-        println!("Converted lambdas: {:?}", lambdas_vec);
-        println!("Converted matrix A: {:?}", a_vec);
-
-        // Get the optimization problem status
-        let status = prob.getattr("status")?.extract::<String>()?;
-
-        // Create and return the arbitrage result
-        let arbitrage_result = ArbitrageResult {
-            deltas: deltas_vec,
-            lambdas: lambdas_vec,
-            a_matrices: a_vec,
-            status,
-        };
-
-        Ok(arbitrage_result)
+        arbitrage_result_from_solver(&inner_result, &local_indices, &market_value)
     });
 
     match &result {
@@ -661,3 +988,287 @@ pub fn solve2() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
     */
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dex::types::DexType;
+    use qtrade_shared_types::{DlmmState, PhoenixMarketState, PoolFee, ReservesState};
+    use probe_sizing::ProbeSettings;
+    use quote_cache::QuoteCache;
+
+    fn cpmm_pool() -> PoolEntry {
+        let data = ReservesState {
+            token_a_amount: 1_000_000_000,
+            token_b_amount: 1_000_000_000,
+            fee_rate: 25,
+        };
+        (Pubkey::new_from_array([1; 32]), PoolState::RaydiumCpmm(data).into())
+    }
+
+    fn constant_sum_pool() -> PoolEntry {
+        let data = ReservesState {
+            token_a_amount: 1_000_000_000,
+            token_b_amount: 1_000_000_000,
+            fee_rate: 1,
+        };
+        (Pubkey::new_from_array([2; 32]), PoolState::ConstantSum(data).into())
+    }
+
+    fn meteora_dlmm_pool() -> PoolEntry {
+        let data = DlmmState {
+            active_id: 0,
+            bin_step: 10,
+            fee_rate: 1_000,
+            bins: vec![
+                dex::types::BinLiquidity { bin_id: -1, amount_x: 0, amount_y: 1_000_000_000 },
+                dex::types::BinLiquidity { bin_id: 0, amount_x: 1_000_000_000, amount_y: 1_000_000_000 },
+                dex::types::BinLiquidity { bin_id: 1, amount_x: 1_000_000_000, amount_y: 0 },
+            ],
+        };
+        (Pubkey::new_from_array([3; 32]), PoolState::MeteoraDlmm(data).into())
+    }
+
+    #[test]
+    fn test_get_dex_quotes_skips_inactive_dexes() {
+        let pool_entries = vec![cpmm_pool(), constant_sum_pool(), cpmm_pool()];
+
+        // 3 input amounts in both directions for each quoted pool
+        let quotes = get_dex_quotes(&pool_entries, &DexType::ALL, &ProbeSettings::default(), &mut QuoteCache::new()).unwrap();
+        assert_eq!(quotes.len(), 3 * 6);
+
+        // Only the CPMM pools are quoted, with a 0.25% fee: 1_000_000 * 25 / 10_000 = 2_500
+        let quotes = get_dex_quotes(&pool_entries, &[DexType::RaydiumCpmm], &ProbeSettings::default(), &mut QuoteCache::new()).unwrap();
+        assert_eq!(quotes.len(), 2 * 6);
+        assert!(quotes.iter().any(|quote| quote.fee_amount == 2_500));
+        assert!(quotes.iter().all(|quote| quote.fee_amount >= 2_500));
+
+        // The constant-sum pool on its own charges 0.01%: 1_000_000 * 1 / 10_000 = 100
+        let quotes = get_dex_quotes(&pool_entries, &[DexType::ConstantSum], &ProbeSettings::default(), &mut QuoteCache::new()).unwrap();
+        assert_eq!(quotes.len(), 6);
+        assert_eq!(quotes[0].fee_amount, 100);
+
+        assert!(get_dex_quotes(&pool_entries, &[], &ProbeSettings::default(), &mut QuoteCache::new()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_dex_quotes_uses_configured_probe_amounts() {
+        // No token A reserves, so the A->B depth is unknown and the configured amounts are quoted
+        let data = ReservesState { token_a_amount: 0, token_b_amount: 1_000_000_000, fee_rate: 0 };
+        let pool_entries = vec![(Pubkey::new_from_array([2; 32]), PoolState::ConstantSum(data).into())];
+        let probe_settings = ProbeSettings { probe_amounts: vec![7_000, 70_000], slippage_bps: 2_500 };
+
+        // B->A quotes fail on the empty token A side and are left out
+        let quotes = get_dex_quotes(&pool_entries, &[DexType::ConstantSum], &probe_settings, &mut QuoteCache::new()).unwrap();
+        let amounts_in: Vec<u64> = quotes.iter().map(|quote| quote.amount_in).collect();
+        assert_eq!(amounts_in, [7_000, 70_000]);
+        // 25% slippage tolerance at a fixed 1:1 price
+        assert_eq!(quotes[0].min_amount_out, Some(5_250));
+        assert_eq!(quotes[1].min_amount_out, Some(52_500));
+    }
+
+    fn phoenix_market() -> PoolEntry {
+        let level = |price_in_ticks, size_in_base_lots| dex::types::OrderBookLevel { price_in_ticks, size_in_base_lots };
+        let data = PhoenixMarketState {
+            order_book: dex::types::OrderBookState {
+                bids: vec![level(1_000, 1_000_000)],
+                asks: vec![level(1_001, 1_000_000)],
+                base_lot_size: 1_000,
+                quote_lot_size: 1,
+                tick_size_in_quote_lots_per_base_unit: 1_000,
+                base_lots_per_base_unit: 1_000,
+            },
+            taker_fee_bps: 10,
+        };
+        (Pubkey::new_from_array([4; 32]), PoolState::Phoenix(data).into())
+    }
+
+    #[test]
+    fn test_get_dex_quotes_reuses_cached_quotes() {
+        let mut quote_cache = QuoteCache::new();
+        let pool_entries = vec![cpmm_pool()];
+
+        let first = get_dex_quotes(&pool_entries, &DexType::ALL, &ProbeSettings::default(), &mut quote_cache).unwrap();
+        assert_eq!((quote_cache.hits(), quote_cache.misses()), (0, 6));
+
+        // Same reserves, same requests: every quote comes from the cache
+        let second = get_dex_quotes(&pool_entries, &DexType::ALL, &ProbeSettings::default(), &mut quote_cache).unwrap();
+        assert_eq!((quote_cache.hits(), quote_cache.misses()), (6, 6));
+        assert_eq!(
+            first.iter().map(|quote| quote.amount_out).collect::<Vec<_>>(),
+            second.iter().map(|quote| quote.amount_out).collect::<Vec<_>>()
+        );
+
+        // Once the pool's indexed state updates its quotes are recomputed
+        let (pool_address, mut pool) = cpmm_pool();
+        if let PoolState::RaydiumCpmm(reserves) = &mut pool.state {
+            reserves.token_a_amount *= 2;
+        }
+        get_dex_quotes(&[(pool_address, pool)], &DexType::ALL, &ProbeSettings::default(), &mut quote_cache).unwrap();
+        assert_eq!((quote_cache.hits(), quote_cache.misses()), (6, 12));
+
+        // Pools no longer quoted are dropped from the cache
+        get_dex_quotes(&pool_entries, &[], &ProbeSettings::default(), &mut quote_cache).unwrap();
+        assert!(quote_cache.is_empty());
+    }
+
+    #[test]
+    fn test_high_impact_quotes_are_filtered_out() {
+        let quote = |amount_out, price_impact| dex::types::SwapQuote {
+            amount_in: 1_000_000,
+            amount_out,
+            min_amount_out: None,
+            max_amount_in: None,
+            fee_amount: 0,
+            price_impact,
+        };
+        let quotes = vec![quote(999_000, 0.001), quote(400_000, 0.6), quote(800_000, DEFAULT_MAX_PRICE_IMPACT)];
+
+        let kept = filter_price_impact(quotes, DEFAULT_MAX_PRICE_IMPACT);
+        // Only the 60% impact quote goes; a quote exactly at the limit is kept
+        assert_eq!(kept.iter().map(|quote| quote.amount_out).collect::<Vec<_>>(), vec![999_000, 800_000]);
+
+        assert!(filter_price_impact(vec![quote(400_000, 0.6)], 0.5).is_empty());
+    }
+
+    #[test]
+    fn test_get_dex_quotes_phoenix() {
+        let pool_entries = vec![cpmm_pool(), phoenix_market()];
+
+        // Selling 1_000 lots into the bid at 1 quote atom per base atom, less the 0.1% taker fee
+        let quotes = get_dex_quotes(&pool_entries, &[DexType::Phoenix], &ProbeSettings::default(), &mut QuoteCache::new()).unwrap();
+        assert_eq!(quotes.len(), 6);
+        assert_eq!(quotes[0].amount_out, 999_000);
+    }
+
+    #[test]
+    fn test_get_dex_quotes_meteora_dlmm() {
+        let pool_entries = vec![cpmm_pool(), meteora_dlmm_pool()];
+
+        // The pair holds 2e9 of each token, so the smallest probe is 2_000_000. Its active
+        // bin trades 1:1, so after the 0.1% fee 2_000_000 in -> 1_998_000 out
+        let quotes = get_dex_quotes(&pool_entries, &[DexType::MeteoraDlmm], &ProbeSettings::default(), &mut QuoteCache::new()).unwrap();
+        assert_eq!(quotes.len(), 6);
+        assert_eq!(quotes[0].amount_in, 2_000_000);
+        assert_eq!(quotes[0].fee_amount, 2_000);
+        assert_eq!(quotes[0].amount_out, 1_998_000);
+        assert_eq!(quotes[1].amount_out, 1_998_000);
+    }
+
+    #[test]
+    fn test_get_dex_quotes_skips_unknown_pools() {
+        // Pool state the router doesn't model, with an indexed fee or not
+        let unknown_pool: PoolEntry = (Pubkey::new_from_array([5; 32]), PoolState::Unknown.into());
+        let indexed_unknown_pool: PoolEntry = (
+            Pubkey::new_from_array([6; 32]),
+            IndexedPool { fee: Some(PoolFee::from_hundredths_bps(3_000)), state: PoolState::Unknown },
+        );
+        assert_eq!(pool_dex_type(&unknown_pool.0, &unknown_pool.1), DexType::Unknown);
+        assert_eq!(pool_dex_type(&indexed_unknown_pool.0, &indexed_unknown_pool.1), DexType::Unknown);
+
+        let pool_entries = vec![unknown_pool, cpmm_pool(), indexed_unknown_pool];
+
+        // Only the CPMM pool is quoted, even with every DEX active
+        let quotes = get_dex_quotes(&pool_entries, &DexType::ALL, &ProbeSettings::default(), &mut QuoteCache::new()).unwrap();
+        assert_eq!(quotes.len(), 6);
+        assert!(quotes.iter().all(|quote| quote.fee_amount >= 2_500));
+
+        assert!(dex::create_dex_quoter(DexType::Unknown).is_none());
+    }
+
+    #[test]
+    fn test_typed_state_identifies_its_dex() {
+        let pools = [
+            (cpmm_pool(), DexType::RaydiumCpmm),
+            (constant_sum_pool(), DexType::ConstantSum),
+            (meteora_dlmm_pool(), DexType::MeteoraDlmm),
+            (phoenix_market(), DexType::Phoenix),
+        ];
+        for ((pool_address, pool), dex_type) in &pools {
+            assert_eq!(pool_dex_type(pool_address, pool), *dex_type);
+        }
+
+        // An indexed fee overrides the state's, in the DEX's own unit: 0.3% = 30 bps
+        let (_, mut pool) = cpmm_pool();
+        pool.fee = Some(PoolFee::from_hundredths_bps(3_000));
+        assert_eq!(extract_pool_reserves(&pool, DexType::RaydiumCpmm).unwrap().fee_rate, 30);
+        assert!(extract_pool_reserves(&PoolState::Unknown.into(), DexType::Unknown).is_none());
+
+        // Entries clone, so a cycle can keep its own copy
+        let entries: Vec<PoolEntry> = pools.iter().map(|(entry, _)| entry.clone()).collect();
+        assert_eq!(entries[0], pools[0].0);
+    }
+
+    #[test]
+    fn test_dex_type_names_round_trip() {
+        for dex_type in DexType::ALL {
+            assert_eq!(dex_type.as_str().parse::<DexType>().unwrap(), dex_type);
+        }
+        assert_eq!("Raydium_CPMM".parse::<DexType>().unwrap(), DexType::RaydiumCpmm);
+        assert!("uniswap".parse::<DexType>().is_err());
+        assert!(!DexType::ALL.contains(&DexType::Unknown));
+    }
+
+    /// Run a stand-in for `qtrade.arbitrage.core.solve_arbitrage` returning `deltas`
+    fn result_from_mock_solver(deltas: &str) -> ArbitrageResult {
+        let code = format!(
+            "class Problem:\n    status = 'optimal'\n\n\
+             def solve_arbitrage():\n    return (Problem(), {}, [[0.0, 0.0]], [[[1.0, 0.0], [0.0, 1.0]]])\n",
+            deltas
+        );
+        let code = std::ffi::CString::new(code).unwrap();
+
+        Python::with_gil(|py| {
+            let solver = PyModule::from_code(py, &code, c"mock_solver.py", c"mock_solver").unwrap();
+            let output = solver.call_method0("solve_arbitrage").unwrap();
+            arbitrage_result_from_solver(&output, &[vec![0, 1]], &[1.0, 2.0]).unwrap()
+        })
+    }
+
+    #[test]
+    fn test_solver_nan_and_inf_are_rejected() {
+        for deltas in ["[[float('nan'), 1.0]]", "[[1.0, float('inf')]]"] {
+            let result = result_from_mock_solver(deltas);
+            assert_eq!(result.status, SOLVER_STATUS_INFEASIBLE);
+            assert!(result.deltas.is_empty());
+            assert!(result.lambdas.is_empty());
+            assert!(result.a_matrices.is_empty());
+            assert!(result.execution_order.is_empty());
+        }
+
+        let result = result_from_mock_solver("[['not a number', 1.0]]");
+        assert_eq!(result.status, SOLVER_STATUS_ERROR);
+        assert!(result.deltas.is_empty());
+
+        let result = result_from_mock_solver("[[1.0, -0.5]]");
+        assert_eq!(result.status, "optimal");
+        assert_eq!(result.deltas, vec![vec![1.0, -0.5]]);
+    }
+
+    #[test]
+    fn test_solve_with_backend_uses_the_chosen_backend() {
+        // The CFMMRouter backend routes in Rust, so this needs no Python solver
+        let result = solve_with_backend(backend::RouterBackend::CfmmRouter, &[]).unwrap();
+        let direct = solve_cfmm(&[]).unwrap();
+        assert_eq!(result.status, "optimal");
+        assert_eq!(result.deltas, direct.deltas);
+        assert_eq!(result.lambdas, direct.lambdas);
+        assert_eq!(result.execution_order.len(), REFERENCE_LOCAL_INDICES.len());
+        assert_eq!(result.a_matrices[1], vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![0.0, 0.0], vec![0.0, 0.0]]);
+
+        // OpenQAOA is refused instead of quietly solving with cvxpy
+        let error = solve_with_backend(backend::RouterBackend::OpenQaoa, &[]).unwrap_err();
+        assert!(error.to_string().contains("Unsupported router backend"));
+    }
+
+    #[test]
+    fn test_solve_with_backend_reports_indexed_pool_fees_and_states() {
+        let mut cpmm = cpmm_pool();
+        cpmm.1.fee = Some(PoolFee::from_hundredths_bps(2_500));
+        let pool_entries = vec![cpmm, constant_sum_pool()];
+
+        let result = solve_with_backend(backend::RouterBackend::CfmmRouter, &pool_entries).unwrap();
+        assert_eq!(result.pool_fees, vec![Some(PoolFee::from_hundredths_bps(2_500)), None]);
+        assert_eq!(result.pool_states, vec![pool_entries[0].1.state.clone(), pool_entries[1].1.state.clone()]);
+    }
+}
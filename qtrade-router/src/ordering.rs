@@ -0,0 +1,262 @@
+// Trade execution ordering for qtrade-router
+//
+// The solver returns how much of each token to tender (delta) and receive (lambda)
+// in every pool, but not the order in which to run the trades. Running them in a
+// good order lets tokens received from one pool fund the next, which minimizes the
+// starting capital needed to kick off the arbitrage.
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+/// Largest number of active pools ordered by trying every permutation (8! = 40320)
+pub const MAX_EXHAUSTIVE_ORDERING_POOLS: usize = 8;
+
+/// Execution order for the trades in an arbitrage result
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeOrdering {
+    /// Pool indices in the order their trades should execute
+    pub order: Vec<usize>,
+    /// Tokens (by global index) needed up front to execute the trades in `order`
+    pub tokens_required: Vec<f64>,
+    /// Market value of `tokens_required`
+    pub value_required: f64,
+}
+
+/// Tokens needed up front to execute the pool trades in `order`
+///
+/// Walks the trades in order, tracking the running balance of every global token.
+/// Whenever a trade would take a balance below zero, the shortfall is added to the
+/// required starting tokens and the balance reset to zero.
+pub fn tokens_required_for_order(
+    order: &[usize],
+    local_indices: &[Vec<usize>],
+    deltas: &[Vec<f64>],
+    lambdas: &[Vec<f64>],
+    token_count: usize,
+) -> Vec<f64> {
+    let mut current_tokens = vec![0.0; token_count];
+    let mut tokens_required = vec![0.0; token_count];
+
+    for &pool_id in order {
+        for (local_token_index, &global_token_id) in local_indices[pool_id].iter().enumerate() {
+            let new_balance = current_tokens[global_token_id]
+                + (lambdas[pool_id][local_token_index] - deltas[pool_id][local_token_index]);
+
+            // Balances never go negative, so any shortfall must come from starting capital
+            if new_balance < 0.0 {
+                tokens_required[global_token_id] += -new_balance;
+                current_tokens[global_token_id] = 0.0;
+            } else {
+                current_tokens[global_token_id] = new_balance;
+            }
+        }
+    }
+
+    tokens_required
+}
+
+fn value_of(tokens: &[f64], market_value: &[f64]) -> f64 {
+    tokens.iter().zip(market_value).map(|(t, v)| t * v).sum()
+}
+
+/// Find the execution order that minimizes the market value of the starting capital
+///
+/// `local_indices[i]` maps pool `i`'s local token positions to global token indices,
+/// matching the layout of `deltas` and `lambdas`. Every permutation of the pools is
+/// tried and the first one with the lowest required value wins. Pools that don't
+/// trade are appended at the end, and networks with more than
+/// [`MAX_EXHAUSTIVE_ORDERING_POOLS`] trading pools fall back to a greedy ordering.
+pub fn optimal_trade_order(
+    local_indices: &[Vec<usize>],
+    deltas: &[Vec<f64>],
+    lambdas: &[Vec<f64>],
+    market_value: &[f64],
+) -> Result<TradeOrdering> {
+    let pool_count = local_indices.len();
+    if deltas.len() != pool_count || lambdas.len() != pool_count {
+        return Err(anyhow!(
+            "Expected deltas and lambdas for {} pools, got {} and {}",
+            pool_count,
+            deltas.len(),
+            lambdas.len()
+        ));
+    }
+
+    let token_count = market_value.len();
+    for (pool_id, pool) in local_indices.iter().enumerate() {
+        if deltas[pool_id].len() != pool.len() || lambdas[pool_id].len() != pool.len() {
+            return Err(anyhow!("Pool {} has mismatched delta/lambda lengths", pool_id));
+        }
+        if let Some(&token) = pool.iter().find(|&&token| token >= token_count) {
+            return Err(anyhow!("Pool {} references unknown token {}", pool_id, token));
+        }
+    }
+
+    // Only pools that actually trade affect the ordering
+    let (active, idle): (Vec<usize>, Vec<usize>) = (0..pool_count).partition(|&pool_id| {
+        deltas[pool_id]
+            .iter()
+            .zip(&lambdas[pool_id])
+            .any(|(d, l)| (l - d).abs() > f64::EPSILON)
+    });
+
+    let mut order = if active.len() <= MAX_EXHAUSTIVE_ORDERING_POOLS {
+        exhaustive_order(&active, local_indices, deltas, lambdas, market_value)
+    } else {
+        greedy_order(&active, local_indices, deltas, lambdas, market_value)
+    };
+    order.extend(idle);
+
+    let tokens_required = tokens_required_for_order(&order, local_indices, deltas, lambdas, token_count);
+    let value_required = value_of(&tokens_required, market_value);
+
+    Ok(TradeOrdering {
+        order,
+        tokens_required,
+        value_required,
+    })
+}
+
+/// Try every permutation of `pools` and keep the first with the lowest required value
+fn exhaustive_order(
+    pools: &[usize],
+    local_indices: &[Vec<usize>],
+    deltas: &[Vec<f64>],
+    lambdas: &[Vec<f64>],
+    market_value: &[f64],
+) -> Vec<usize> {
+    let token_count = market_value.len();
+    let mut best: Option<(Vec<usize>, f64)> = None;
+
+    for permutation in pools.iter().copied().permutations(pools.len()) {
+        let tokens_required =
+            tokens_required_for_order(&permutation, local_indices, deltas, lambdas, token_count);
+        let value = value_of(&tokens_required, market_value);
+
+        // Ties keep the earlier permutation
+        if best.as_ref().is_none_or(|(_, best_value)| value < best_value - f64::EPSILON) {
+            best = Some((permutation, value));
+        }
+    }
+
+    best.map(|(order, _)| order).unwrap_or_default()
+}
+
+/// Repeatedly append the pool that adds the least required value so far
+fn greedy_order(
+    pools: &[usize],
+    local_indices: &[Vec<usize>],
+    deltas: &[Vec<f64>],
+    lambdas: &[Vec<f64>],
+    market_value: &[f64],
+) -> Vec<usize> {
+    let token_count = market_value.len();
+    let mut remaining = pools.to_vec();
+    let mut order = Vec::with_capacity(pools.len());
+
+    while !remaining.is_empty() {
+        let (position, _) = remaining
+            .iter()
+            .enumerate()
+            .map(|(position, &pool_id)| {
+                let mut candidate = order.clone();
+                candidate.push(pool_id);
+                let tokens_required =
+                    tokens_required_for_order(&candidate, local_indices, deltas, lambdas, token_count);
+                (position, value_of(&tokens_required, market_value))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .expect("remaining is not empty");
+
+        order.push(remaining.remove(position));
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The reference network: a 4-asset balancer pool, three uniswapV2 pools and a
+    // constant-sum pool over TOKEN-0..TOKEN-3
+    fn reference_local_indices() -> Vec<Vec<usize>> {
+        vec![
+            vec![0, 1, 2, 3], // BALANCER 0/1/2/3
+            vec![0, 1],       // UNIV2 0/1
+            vec![1, 2],       // UNIV2 1/2
+            vec![2, 3],       // UNIV2 2/3
+            vec![2, 3],       // CONSTANT SUM 2/3
+        ]
+    }
+
+    const REFERENCE_MARKET_VALUE: [f64; 4] = [1.5, 10.0, 2.0, 3.0];
+
+    // A cyclic trade over the reference network: TOKEN-0 -> 1 -> 2 -> 3 -> 2
+    fn reference_trades() -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
+        let deltas = vec![
+            vec![0.0, 0.0, 0.0, 0.5],
+            vec![2.0, 0.0],
+            vec![0.1, 0.0],
+            vec![0.8, 0.0],
+            vec![0.0, 0.0],
+        ];
+        let lambdas = vec![
+            vec![0.0, 0.0, 0.6, 0.0],
+            vec![0.0, 0.15],
+            vec![0.0, 0.4],
+            vec![0.0, 0.7],
+            vec![0.0, 0.0],
+        ];
+        (deltas, lambdas)
+    }
+
+    #[test]
+    fn test_reference_example_ordering() {
+        let (deltas, lambdas) = reference_trades();
+        let ordering = optimal_trade_order(
+            &reference_local_indices(),
+            &deltas,
+            &lambdas,
+            &REFERENCE_MARKET_VALUE,
+        )
+        .unwrap();
+
+        // UNIV2 0/1 feeds UNIV2 1/2, which part-funds UNIV2 2/3, which funds the balancer
+        assert_eq!(ordering.order, vec![1, 2, 3, 0, 4]);
+        let expected_tokens = [2.0, 0.0, 0.4, 0.0];
+        for (required, expected) in ordering.tokens_required.iter().zip(expected_tokens) {
+            assert!((required - expected).abs() < 1e-9, "{:?}", ordering.tokens_required);
+        }
+        assert!((ordering.value_required - 3.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ordering_beats_pool_index_order() {
+        let (deltas, lambdas) = reference_trades();
+        let local_indices = reference_local_indices();
+
+        let naive = tokens_required_for_order(&[0, 1, 2, 3, 4], &local_indices, &deltas, &lambdas, 4);
+        let ordering =
+            optimal_trade_order(&local_indices, &deltas, &lambdas, &REFERENCE_MARKET_VALUE).unwrap();
+
+        assert!(ordering.value_required < value_of(&naive, &REFERENCE_MARKET_VALUE));
+    }
+
+    #[test]
+    fn test_greedy_order_covers_every_pool() {
+        let (deltas, lambdas) = reference_trades();
+        let local_indices = reference_local_indices();
+
+        let mut order = greedy_order(&[0, 1, 2, 3], &local_indices, &deltas, &lambdas, &REFERENCE_MARKET_VALUE);
+        order.sort();
+        assert_eq!(order, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_rejects_mismatched_shapes() {
+        let (mut deltas, lambdas) = reference_trades();
+        deltas.pop();
+        assert!(optimal_trade_order(&reference_local_indices(), &deltas, &lambdas, &REFERENCE_MARKET_VALUE).is_err());
+    }
+}
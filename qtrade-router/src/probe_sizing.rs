@@ -0,0 +1,188 @@
+// Sizing of the input amounts each pool is quoted at
+//
+// Quotes at a handful of input amounts sample a pool's price-impact curve. Fixed amounts
+// barely move a deep pool and drain a shallow one, so the amounts are instead fractions
+// of the pool's depth on the input side: its reserve of the input token, or the
+// equivalent for concentrated-liquidity, bin and order-book pools.
+
+use anyhow::{anyhow, Result};
+
+use crate::dex::types::PoolReserves;
+
+/// Fractions of a pool's input-side depth that are quoted
+pub const PROBE_FRACTIONS: [f64; 3] = [0.001, 0.01, 0.1];
+
+/// Amounts quoted when a pool's depth is unknown (1, 10, 100 units with 6 decimal places)
+pub const FALLBACK_PROBE_AMOUNTS: [u64; 3] = [1_000_000, 10_000_000, 100_000_000];
+
+/// Slippage tolerance quotes are requested with (0.3%)
+pub const DEFAULT_SLIPPAGE_BPS: u16 = 30;
+
+/// How pools are probed for quotes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeSettings {
+    /// Amounts quoted at pools whose depth is unknown, in input token atoms
+    pub probe_amounts: Vec<u64>,
+    /// Slippage tolerance of every quote, in basis points
+    pub slippage_bps: u16,
+}
+
+impl Default for ProbeSettings {
+    fn default() -> Self {
+        Self {
+            probe_amounts: FALLBACK_PROBE_AMOUNTS.to_vec(),
+            slippage_bps: DEFAULT_SLIPPAGE_BPS,
+        }
+    }
+}
+
+impl ProbeSettings {
+    /// Fails if there are no probe amounts, any of them is 0, or the slippage is above 100%
+    pub fn validate(&self) -> Result<()> {
+        if self.probe_amounts.is_empty() {
+            return Err(anyhow!("probe_amounts must not be empty"));
+        }
+        if self.probe_amounts.contains(&0) {
+            return Err(anyhow!("probe_amounts must all be at least 1"));
+        }
+        if self.slippage_bps > 10_000 {
+            return Err(anyhow!("slippage_bps must be at most 10000"));
+        }
+        Ok(())
+    }
+}
+
+// Q64.64 fixed-point scale of concentrated-liquidity square-root prices
+const Q64: f64 = 18_446_744_073_709_551_616.0;
+
+/// How much of the input token a pool can absorb, in input token atoms
+///
+/// Reserve-based and weighted pools use their input-token balance, bin pools the input
+/// token across their bins, and order books the size resting on the side the input
+/// trades against. Concentrated-liquidity pools use the virtual reserve implied by their
+/// in-range liquidity: L / sqrt(P) of token A and L * sqrt(P) of token B. `None` when the
+/// reserves don't say.
+pub fn input_depth(pool_reserves: &PoolReserves, is_token_a_to_b: bool) -> Option<u64> {
+    let depth = if let (Some(token_a), Some(token_b)) = (pool_reserves.token_a_reserves, pool_reserves.token_b_reserves) {
+        if is_token_a_to_b { token_a } else { token_b }
+    } else if let Some(balances) = &pool_reserves.token_balances {
+        *balances.get(if is_token_a_to_b { 0 } else { 1 })?
+    } else if let Some(bins) = &pool_reserves.bins {
+        bins.iter()
+            .map(|bin| if is_token_a_to_b { bin.amount_x } else { bin.amount_y })
+            .fold(0u64, u64::saturating_add)
+    } else if let Some(order_book) = &pool_reserves.order_book {
+        // Selling the base token fills bids; buying it with the quote token fills asks
+        if is_token_a_to_b {
+            order_book.bids.iter()
+                .map(|level| u128::from(level.size_in_base_lots) * u128::from(order_book.base_lot_size))
+                .sum::<u128>()
+                .min(u128::from(u64::MAX)) as u64
+        } else {
+            order_book.asks.iter()
+                .map(|level| u128::from(level.size_in_base_lots) * order_book.quote_atoms_per_base_lot(level.price_in_ticks))
+                .sum::<u128>()
+                .min(u128::from(u64::MAX)) as u64
+        }
+    } else {
+        if pool_reserves.liquidity == 0 || pool_reserves.sqrt_price == 0 {
+            return None;
+        }
+        let liquidity = pool_reserves.liquidity as f64;
+        let sqrt_price = pool_reserves.sqrt_price as f64 / Q64;
+        // Saturating float-to-int cast caps the depth at u64::MAX
+        if is_token_a_to_b { (liquidity / sqrt_price) as u64 } else { (liquidity * sqrt_price) as u64 }
+    };
+
+    (depth > 0).then_some(depth)
+}
+
+/// Input amounts to quote a pool at, one per [`PROBE_FRACTIONS`] of its input depth
+///
+/// Falls back to `fallback_amounts` (the configured probe amounts) when the depth is
+/// unknown. Amounts are at least one atom.
+pub fn probe_amounts(pool_reserves: &PoolReserves, is_token_a_to_b: bool, fallback_amounts: &[u64]) -> Vec<u64> {
+    match input_depth(pool_reserves, is_token_a_to_b) {
+        Some(depth) => PROBE_FRACTIONS.iter().map(|fraction| ((depth as f64 * fraction) as u64).max(1)).collect(),
+        None => fallback_amounts.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::types::{BinLiquidity, OrderBookLevel, OrderBookState};
+
+    fn reserves(token_a: u64, token_b: u64) -> PoolReserves {
+        PoolReserves {
+            token_a_reserves: Some(token_a),
+            token_b_reserves: Some(token_b),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_probe_amounts_scale_with_reserves() {
+        assert_eq!(probe_amounts(&reserves(1_000_000_000, 0), true, &FALLBACK_PROBE_AMOUNTS), [1_000_000, 10_000_000, 100_000_000]);
+
+        // A pool 100x deeper is probed at 100x the amounts, a shallow one at a fraction
+        let deep = probe_amounts(&reserves(100_000_000_000, 0), true, &FALLBACK_PROBE_AMOUNTS);
+        let shallow = probe_amounts(&reserves(10_000_000, 0), true, &FALLBACK_PROBE_AMOUNTS);
+        assert_eq!(deep, [100_000_000, 1_000_000_000, 10_000_000_000]);
+        assert_eq!(shallow, [10_000, 100_000, 1_000_000]);
+
+        // Each direction is sized by its own input token
+        assert_eq!(probe_amounts(&reserves(1_000_000_000, 50_000), false, &FALLBACK_PROBE_AMOUNTS), [50, 500, 5_000]);
+    }
+
+    #[test]
+    fn test_probe_amounts_without_depth_fall_back() {
+        assert_eq!(probe_amounts(&PoolReserves::default(), true, &FALLBACK_PROBE_AMOUNTS), FALLBACK_PROBE_AMOUNTS);
+        assert_eq!(probe_amounts(&reserves(0, 0), true, &FALLBACK_PROBE_AMOUNTS), FALLBACK_PROBE_AMOUNTS);
+
+        // Tiny pools still get a non-zero probe
+        assert_eq!(probe_amounts(&reserves(10, 10), true, &FALLBACK_PROBE_AMOUNTS), [1, 1, 1]);
+    }
+
+    #[test]
+    fn test_probe_settings_validation() {
+        assert!(ProbeSettings::default().validate().is_ok());
+        assert!(ProbeSettings { probe_amounts: vec![], ..ProbeSettings::default() }.validate().is_err());
+        assert!(ProbeSettings { probe_amounts: vec![5_000, 0], ..ProbeSettings::default() }.validate().is_err());
+        assert!(ProbeSettings { slippage_bps: 10_001, ..ProbeSettings::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_depth_of_other_pool_shapes() {
+        // Concentrated liquidity at a price of 4 (sqrt price 2): 1e9 / 2 of A, 1e9 * 2 of B
+        let concentrated = PoolReserves { liquidity: 1_000_000_000, sqrt_price: 2u128 << 64, ..Default::default() };
+        assert_eq!(input_depth(&concentrated, true), Some(500_000_000));
+        assert_eq!(input_depth(&concentrated, false), Some(2_000_000_000));
+
+        let bins = PoolReserves {
+            bins: Some(vec![
+                BinLiquidity { bin_id: 0, amount_x: 300, amount_y: 100 },
+                BinLiquidity { bin_id: 1, amount_x: 700, amount_y: 0 },
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(input_depth(&bins, true), Some(1_000));
+        assert_eq!(input_depth(&bins, false), Some(100));
+
+        // 1_000 lots of 1_000 atoms bid; 10 lots asked at 1 quote atom per base atom
+        let level = |price_in_ticks, size_in_base_lots| OrderBookLevel { price_in_ticks, size_in_base_lots };
+        let order_book = PoolReserves {
+            order_book: Some(OrderBookState {
+                bids: vec![level(1_000, 1_000)],
+                asks: vec![level(1_000, 10)],
+                base_lot_size: 1_000,
+                quote_lot_size: 1,
+                tick_size_in_quote_lots_per_base_unit: 1_000,
+                base_lots_per_base_unit: 1_000,
+            }),
+            ..Default::default()
+        };
+        assert_eq!(input_depth(&order_book, true), Some(1_000_000));
+        assert_eq!(input_depth(&order_book, false), Some(10_000));
+    }
+}
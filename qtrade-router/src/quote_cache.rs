@@ -0,0 +1,220 @@
+// Caching of DEX quotes between solve cycles
+//
+// Every cycle quotes the same fixed amounts in both directions for every pool, though
+// most pools' reserves haven't moved since the last cycle. Quotes are cached per pool
+// under a hash of the reserves they were computed from; once the pool's indexed state
+// changes the hash does too, and the pool's cached quotes are dropped.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use anyhow::Result;
+use lazy_static::lazy_static;
+use opentelemetry::global;
+use opentelemetry::metrics::Counter;
+use spl_pod::solana_pubkey::Pubkey;
+
+use crate::dex::types::{PoolReserves, QuoteMode, SwapQuote};
+use crate::QTRADE_ROUTER_METER_NAME;
+
+lazy_static! {
+    static ref QUOTE_CACHE_HITS: Counter<u64> = {
+        global::meter(QTRADE_ROUTER_METER_NAME)
+            .u64_counter("qtrade.router.quote_cache_hits")
+            .with_description("Number of DEX quotes served from the quote cache")
+            .build()
+    };
+
+    static ref QUOTE_CACHE_MISSES: Counter<u64> = {
+        global::meter(QTRADE_ROUTER_METER_NAME)
+            .u64_counter("qtrade.router.quote_cache_misses")
+            .with_description("Number of DEX quotes computed because the quote cache had none")
+            .build()
+    };
+}
+
+/// Hash of everything a quoter reads from a pool's reserves
+pub fn reserves_hash(pool_reserves: &PoolReserves) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    pool_reserves.sqrt_price.hash(&mut hasher);
+    pool_reserves.tick_current_index.hash(&mut hasher);
+    pool_reserves.liquidity.hash(&mut hasher);
+    pool_reserves.fee_rate.hash(&mut hasher);
+    pool_reserves.tick_spacing.hash(&mut hasher);
+    pool_reserves.token_a_reserves.hash(&mut hasher);
+    pool_reserves.token_b_reserves.hash(&mut hasher);
+    pool_reserves.token_balances.hash(&mut hasher);
+    // f64 isn't Hash; equal weights have equal bits
+    pool_reserves
+        .token_weights
+        .as_ref()
+        .map(|weights| weights.iter().map(|weight| weight.to_bits()).collect::<Vec<_>>())
+        .hash(&mut hasher);
+    pool_reserves.bins.hash(&mut hasher);
+    pool_reserves.order_book.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Identifies one quote: the pool, the reserves it was quoted at, and the request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QuoteKey {
+    pub pool_address: Pubkey,
+    /// [`reserves_hash`] of the reserves quoted from
+    pub reserves_hash: u64,
+    pub amount: u64,
+    pub is_token_a_to_b: bool,
+    pub mode: QuoteMode,
+    pub slippage_bps: u16,
+}
+
+/// Request part of a [`QuoteKey`], under which a pool's quotes are stored
+type QuoteRequest = (u64, bool, QuoteMode, u16);
+
+/// Quotes of one pool, all computed from the reserves with `reserves_hash`
+#[derive(Debug)]
+struct PoolQuotes {
+    reserves_hash: u64,
+    quotes: HashMap<QuoteRequest, SwapQuote>,
+}
+
+/// Quotes kept across solve cycles, until their pool's reserves change
+#[derive(Debug, Default)]
+pub struct QuoteCache {
+    pools: HashMap<Pubkey, PoolQuotes>,
+    hits: u64,
+    misses: u64,
+}
+
+impl QuoteCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The quote for `key`, from the cache or else computed by `quote` and cached
+    ///
+    /// A key with different reserves than the pool's cached quotes were computed from
+    /// drops those quotes first. Failed quotes aren't cached.
+    pub fn get_or_quote<F>(&mut self, key: &QuoteKey, quote: F) -> Result<SwapQuote>
+    where
+        F: FnOnce() -> Result<SwapQuote>,
+    {
+        let pool = self.pools.entry(key.pool_address).or_insert_with(|| PoolQuotes {
+            reserves_hash: key.reserves_hash,
+            quotes: HashMap::new(),
+        });
+        if pool.reserves_hash != key.reserves_hash {
+            pool.reserves_hash = key.reserves_hash;
+            pool.quotes.clear();
+        }
+
+        let request = (key.amount, key.is_token_a_to_b, key.mode, key.slippage_bps);
+        if let Some(cached) = pool.quotes.get(&request) {
+            self.hits += 1;
+            QUOTE_CACHE_HITS.add(1, &[]);
+            return Ok(cached.clone());
+        }
+
+        self.misses += 1;
+        QUOTE_CACHE_MISSES.add(1, &[]);
+        let fresh = quote()?;
+        pool.quotes.insert(request, fresh.clone());
+        Ok(fresh)
+    }
+
+    /// Drop the quotes of every pool not in `pool_addresses`, e.g. pools gone stale
+    pub fn retain_pools(&mut self, pool_addresses: &HashSet<Pubkey>) {
+        self.pools.retain(|pool_address, _| pool_addresses.contains(pool_address));
+    }
+
+    /// Number of pools with cached quotes
+    pub fn len(&self) -> usize {
+        self.pools.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty()
+    }
+
+    /// Quotes served from the cache so far
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Quotes computed so far because none was cached
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    fn reserves(token_a_reserves: u64) -> PoolReserves {
+        PoolReserves {
+            fee_rate: 25,
+            token_a_reserves: Some(token_a_reserves),
+            token_b_reserves: Some(1_000_000_000),
+            ..Default::default()
+        }
+    }
+
+    fn key(pool_address: Pubkey, pool_reserves: &PoolReserves) -> QuoteKey {
+        QuoteKey {
+            pool_address,
+            reserves_hash: reserves_hash(pool_reserves),
+            amount: 1_000_000,
+            is_token_a_to_b: true,
+            mode: QuoteMode::ExactIn,
+            slippage_bps: 30,
+        }
+    }
+
+    fn quote(amount_out: u64) -> Result<SwapQuote> {
+        Ok(SwapQuote {
+            amount_in: 1_000_000,
+            amount_out,
+            min_amount_out: None,
+            max_amount_in: None,
+            fee_amount: 2_500,
+            price_impact: 0.0,
+        })
+    }
+
+    #[test]
+    fn test_changed_reserves_invalidate_pool_quotes() {
+        let mut cache = QuoteCache::new();
+        let pool_address = Pubkey::new_unique();
+
+        cache.get_or_quote(&key(pool_address, &reserves(1_000)), || quote(1)).unwrap();
+        let moved = cache.get_or_quote(&key(pool_address, &reserves(2_000)), || quote(2)).unwrap();
+        assert_eq!(moved.amount_out, 2);
+        assert_eq!((cache.hits(), cache.misses()), (0, 2));
+
+        // Going back to the first reserves recomputes too: their quotes were dropped
+        let back = cache.get_or_quote(&key(pool_address, &reserves(1_000)), || quote(3)).unwrap();
+        assert_eq!(back.amount_out, 3);
+
+        // Any other field of the request is a different quote
+        let mut reverse = key(pool_address, &reserves(1_000));
+        reverse.is_token_a_to_b = false;
+        cache.get_or_quote(&reverse, || quote(4)).unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (0, 4));
+    }
+
+    #[test]
+    fn test_failed_quotes_and_dropped_pools_are_not_kept() {
+        let mut cache = QuoteCache::new();
+        let pool_address = Pubkey::new_unique();
+        let pool_key = key(pool_address, &reserves(1_000));
+
+        assert!(cache.get_or_quote(&pool_key, || Err(anyhow!("no liquidity"))).is_err());
+        assert_eq!(cache.get_or_quote(&pool_key, || quote(1)).unwrap().amount_out, 1);
+        assert_eq!(cache.misses(), 2);
+
+        cache.retain_pools(&HashSet::new());
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,48 @@
+use async_trait::async_trait;
+use qtrade_router::backend::RouterBackend;
+use qtrade_router::dex::types::DexType;
+use qtrade_router::probe_sizing::ProbeSettings;
+use qtrade_router::{run_router, PoolCache, PoolEntry, DEFAULT_MAX_PRICE_IMPACT};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Pool cache with no entries
+struct EmptyPoolCache;
+
+#[async_trait]
+impl PoolCache for EmptyPoolCache {
+    async fn get_all_entries_as_slice(&self) -> Vec<PoolEntry> {
+        Vec::new()
+    }
+}
+
+#[tokio::test]
+async fn test_run_router_returns_when_cancelled() {
+    let token = CancellationToken::new();
+    token.cancel();
+
+    // A cancelled token must stop the router before it starts another cycle
+    let result = tokio::time::timeout(
+        Duration::from_secs(5),
+        run_router(Arc::new(EmptyPoolCache), DexType::ALL.to_vec(), DEFAULT_MAX_PRICE_IMPACT, ProbeSettings::default(), RouterBackend::Cvxpy, token),
+    )
+    .await;
+
+    assert!(result.is_ok(), "run_router should return promptly after cancellation");
+    assert!(result.unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn test_run_router_refuses_unsupported_backend() {
+    // No cancellation: the router must fail up front instead of solving with cvxpy
+    let result = tokio::time::timeout(
+        Duration::from_secs(5),
+        run_router(Arc::new(EmptyPoolCache), DexType::ALL.to_vec(), DEFAULT_MAX_PRICE_IMPACT, ProbeSettings::default(), RouterBackend::OpenQaoa, CancellationToken::new()),
+    )
+    .await
+    .expect("run_router should fail promptly for an unsupported backend");
+
+    let error = result.unwrap_err();
+    assert!(error.to_string().contains("Unsupported router backend"));
+}
@@ -1,4 +1,5 @@
-use qtrade_router::{solve, PoolEntry};
+use qtrade_router::{pool_fee_multipliers, solve, PoolEntry};
+use qtrade_shared_types::{IndexedPool, PoolFee, PoolState};
 use spl_pod::solana_pubkey::Pubkey;
 use std::str::FromStr;
 use std::panic::AssertUnwindSafe;
@@ -9,15 +10,15 @@ fn test_solve() {
     let dummy_entries: Vec<PoolEntry> = vec![
         (
             Pubkey::from_str("11111111111111111111111111111111").unwrap(),
-            Box::new(()) as Box<dyn std::any::Any + Send + Sync>
+            PoolState::Unknown.into(),
         ),
         (
             Pubkey::from_str("22222222222222222222222222222222").unwrap(),
-            Box::new(()) as Box<dyn std::any::Any + Send + Sync>
+            PoolState::Unknown.into(),
         ),
         (
             Pubkey::from_str("33333333333333333333333333333333").unwrap(),
-            Box::new(()) as Box<dyn std::any::Any + Send + Sync>
+            PoolState::Unknown.into(),
         ),
     ];
 
@@ -33,3 +34,34 @@ fn test_solve() {
     assert!(output.contains("CONVEX OPTIMISATION SOLVER RESULT"));
     */
 }
+
+#[test]
+fn test_pool_fee_multipliers_use_indexed_fees() {
+    let entries: Vec<PoolEntry> = vec![
+        (
+            Pubkey::new_from_array([1; 32]),
+            IndexedPool {
+                fee: Some(PoolFee::from_hundredths_bps(500)),
+                state: PoolState::Unknown,
+            },
+        ),
+        (
+            Pubkey::new_from_array([2; 32]),
+            IndexedPool {
+                fee: None,
+                state: PoolState::Unknown,
+            },
+        ),
+        (
+            Pubkey::new_from_array([3; 32]),
+            PoolState::Unknown.into(),
+        ),
+    ];
+
+    let fees = pool_fee_multipliers(&entries, &[0.998, 0.997, 0.997, 0.999]);
+
+    // The indexed fee is used as-is: 500 hundredths of a basis point = 0.05%
+    assert!((fees[0] - 0.9995).abs() < 1e-12, "{:?}", fees);
+    // No indexed fee, not indexed, and no entry at all all keep the reference fee
+    assert_eq!(&fees[1..], &[0.997, 0.997, 0.999]);
+}
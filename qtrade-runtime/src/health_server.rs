@@ -0,0 +1,64 @@
+//! Health/readiness endpoint for orchestrators
+//!
+//! Serves `GET /health` with a JSON [`HealthSnapshot`] of every subsystem (streamer
+//! connection, relayer queue depth, available explorer keys, last router solve, ...).
+//! The endpoint answers `503 Service Unavailable` until the streamer has received its
+//! first account update, and `200 OK` afterwards while the stream stays connected.
+
+use anyhow::{Context, Result};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json,
+    Router,
+};
+use qtrade_shared_types::{HealthSnapshot, HealthStatus, HEALTH_STATUS};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+/// Default port for the health endpoint
+pub const DEFAULT_HEALTH_SERVER_PORT: u16 = 8080;
+
+async fn health_handler(State(status): State<&'static HealthStatus>) -> Response {
+    let snapshot: HealthSnapshot = status.snapshot();
+    let code = if snapshot.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (code, Json(snapshot)).into_response()
+}
+
+/// Serve `status` at `/health` on an already bound listener until the token is cancelled
+pub async fn serve_health(
+    listener: TcpListener,
+    status: &'static HealthStatus,
+    cancellation_token: CancellationToken,
+) -> Result<()> {
+    let app = Router::new()
+        .route("/health", get(health_handler))
+        .with_state(status);
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move { cancellation_token.cancelled().await })
+        .await
+        .context("Health server failed")?;
+
+    tracing::info!("Health server stopped");
+    Ok(())
+}
+
+/// Bind the health endpoint on `port` and serve the process-wide [`HEALTH_STATUS`]
+pub async fn run_health_server(port: u16, cancellation_token: CancellationToken) -> Result<()> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind health server to {}", addr))?;
+
+    tracing::info!("Health server listening on http://{}/health", addr);
+    serve_health(listener, &HEALTH_STATUS, cancellation_token).await
+}
@@ -12,6 +12,10 @@ use std::sync::Arc;
 use tokio_util::sync::CancellationToken;
 use tokio::try_join;
 
+pub mod health_server;
+pub mod metrics_server;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod settings;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +31,17 @@ pub enum Router {
     CFMMRouter,
 }
 
+impl Router {
+    /// The router backend this selection solves with
+    pub fn backend(&self) -> qtrade_router::backend::RouterBackend {
+        match self {
+            Router::Cvxpy => qtrade_router::backend::RouterBackend::Cvxpy,
+            Router::OpenQAOA => qtrade_router::backend::RouterBackend::OpenQaoa,
+            Router::CFMMRouter => qtrade_router::backend::RouterBackend::CfmmRouter,
+        }
+    }
+}
+
 /// Represents available DEX platforms that the system can interact with.
 ///
 /// This enum allows specifying which DEX platforms should be active
@@ -43,6 +58,10 @@ pub enum Dex {
     RaydiumCpmm,
     /// Raydium Concentrated Liquidity Market Maker (CLMM)
     RaydiumClmm,
+    /// Meteora Dynamic Liquidity Market Maker (DLMM)
+    MeteoraDlmm,
+    /// Phoenix order book, not active by default while only market headers are indexed
+    Phoenix,
 }
 
 impl Dex {
@@ -52,6 +71,8 @@ impl Dex {
             Dex::Raydium => "raydium",
             Dex::RaydiumCpmm => "raydium-cpmm",
             Dex::RaydiumClmm => "raydium-clmm",
+            Dex::MeteoraDlmm => "meteora-dlmm",
+            Dex::Phoenix => "phoenix",
         }
     }
 
@@ -63,65 +84,16 @@ impl Dex {
             "raydium_cpmm" => Some(Dex::RaydiumCpmm),
             "raydium-clmm" => Some(Dex::RaydiumClmm),
             "raydium_clmm" => Some(Dex::RaydiumClmm),
+            "meteora-dlmm" => Some(Dex::MeteoraDlmm),
+            "meteora_dlmm" => Some(Dex::MeteoraDlmm),
+            "phoenix" => Some(Dex::Phoenix),
             _ => None,
         }
     }
 }
 
-/// Represents available RPC providers for transaction submissions.
-///
-/// This enum allows the system to specify which RPC providers should
-/// be active for submitting transactions. By default, all providers
-/// are active, but users can restrict which ones are used via
-/// command-line arguments, environment variables, or configuration files.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub enum RpcProvider {
-    /// Bloxroute RPC provider
-    Bloxroute,
-    /// Helius RPC provider
-    Helius,
-    /// Jito RPC provider
-    Jito,
-    /// Nextblock RPC provider
-    Nextblock,
-    /// Quicknode RPC provider
-    Quicknode,
-    /// Solana RPC provider
-    Solana,
-    /// Temporal RPC provider
-    Temporal,
-    /// Triton RPC provider
-    Triton,
-}
-
-impl RpcProvider {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            RpcProvider::Bloxroute => "bloxroute",
-            RpcProvider::Helius => "helius",
-            RpcProvider::Jito => "jito",
-            RpcProvider::Nextblock => "nextblock",
-            RpcProvider::Quicknode => "quicknode",
-            RpcProvider::Solana => "solana",
-            RpcProvider::Temporal => "temporal",
-            RpcProvider::Triton => "triton",
-        }
-    }
-
-    pub fn from_str(s: &str) -> Option<Self> {
-        match s.to_lowercase().as_str() {
-            "bloxroute" => Some(RpcProvider::Bloxroute),
-            "helius" => Some(RpcProvider::Helius),
-            "jito" => Some(RpcProvider::Jito),
-            "nextblock" => Some(RpcProvider::Nextblock),
-            "quicknode" => Some(RpcProvider::Quicknode),
-            "solana" => Some(RpcProvider::Solana),
-            "temporal" => Some(RpcProvider::Temporal),
-            "triton" => Some(RpcProvider::Triton),
-            _ => None,
-        }
-    }
-}
+/// RPC providers for transaction submissions, shared with the relayer
+pub use qtrade_relayer::rpc::RpcProvider;
 
 // Our one global named tracer we will use throughout the runtime
 const QTRADE_RUNTIME_TRACER_NAME: &str = "qtrade_runtime";
@@ -171,6 +143,48 @@ pub async fn run_qtrade(
     result
 }
 
+/// Relayer settings from the runtime settings
+fn relayer_settings(settings: &settings::Settings) -> qtrade_relayer::settings::RelayerSettings {
+    let mut relayer_settings = qtrade_relayer::settings::RelayerSettings::new_with_providers(
+        settings.bloxroute_api_key.clone(),
+        settings.helius_api_key.clone(),
+        settings.nextblock_api_key.clone(),
+        settings.quicknode_api_key.clone(),
+        settings.temporal_api_key.clone(),
+        settings.active_rpcs.clone(),
+        settings.simulate,
+    );
+    relayer_settings.max_queue_size = settings.max_queue_size;
+    relayer_settings.max_in_flight = settings.max_in_flight;
+    relayer_settings.solana_rpc_url = settings.solana_rpc_url.clone();
+    relayer_settings.rpc_request_timeout = std::time::Duration::from_millis(settings.rpc_request_timeout_ms);
+    relayer_settings.rpc_request_timeouts = settings
+        .rpc_request_timeouts_ms
+        .iter()
+        .map(|(provider, ms)| (*provider, std::time::Duration::from_millis(*ms)))
+        .collect();
+    relayer_settings.submission_store_path = settings.submission_store_path.clone();
+    relayer_settings.submission_ttl = std::time::Duration::from_secs(settings.submission_ttl_secs);
+    relayer_settings.taxable_event_spool_path = settings.taxable_event_spool_path.clone();
+    relayer_settings.drain_on_shutdown = settings.drain_on_shutdown;
+    relayer_settings.drain_budget = std::time::Duration::from_secs(settings.drain_budget_secs);
+    relayer_settings.queue_spool_path = settings.queue_spool_path.clone();
+    relayer_settings.submit_mode = settings.submit_mode;
+    relayer_settings.blockhash_only_rpcs = settings.blockhash_only_rpcs.clone();
+    relayer_settings.simulation_rpcs = settings.simulation_rpcs.clone();
+    relayer_settings.identical_transaction = settings.identical_transaction;
+    relayer_settings.fee_payer_keypair_path = settings.fee_payer_keypair_path.clone();
+    relayer_settings.record_results_path = settings.record_results_path.clone();
+    relayer_settings.swap_audit_path = settings.swap_audit_path.clone();
+    relayer_settings.max_pools_per_tx = settings.max_pools_per_tx;
+    relayer_settings.allowed_mints = settings.allowed_mints.clone();
+    relayer_settings.blocked_mints = settings.blocked_mints.clone();
+    relayer_settings.route_through_executor = settings.route_through_executor;
+    relayer_settings.executor_referral_code = settings.executor_referral_code;
+    relayer_settings.key_retirement_policy = settings.key_retirement_policy.clone();
+    relayer_settings
+}
+
 async fn run_qtrade_inner(
     settings: settings::Settings,
     cancellation_token: tokio_util::sync::CancellationToken
@@ -189,54 +203,114 @@ async fn run_qtrade_inner(
                 .map_err(|e| anyhow::anyhow!("Failed to install default CryptoProvider: {:?}", e))?;
         }
 
+        // Mock mode runs the router and relayer alone, without the indexer or wallets
+        #[cfg(feature = "mock")]
+        if settings.mock {
+            return mock::run_mock(&settings, relayer_settings(&settings), cancellation_token).await;
+        }
+
         // Initialize database connection for transaction recording
         if let Err(e) = qtrade_relayer::metrics::database::init_database() {
-            // Log the error but continue execution - we'll fall back to logging
-            tracing::warn!("Failed to initialize database connection: {:?}. Will use log-based recording fallback.", e);
+            // Log the error but continue execution - taxable events stay buffered until written
+            tracing::warn!("Failed to initialize database connection: {:?}. Taxable events will be buffered.", e);
         }
 
         // Create wallet settings from runtime settings
         let wallet_settings = qtrade_wallets::WalletSettings {
             single_wallet: settings.single_wallet,
-            single_wallet_private_key: settings.single_wallet_private_key.clone(),
+            single_wallet_private_keys: settings.get_single_wallet_private_keys(),
+            funding: qtrade_wallets::FundingSettings::from_env(),
         };
         // Pass wallet settings to the wallet system
         let wallets_future = qtrade_wallets::run_wallets(wallet_settings);
 
         // Convert runtime settings to relayer settings
-        let relayer_settings = qtrade_relayer::settings::RelayerSettings::new_with_rpcs(
-            settings.bloxroute_api_key.clone(),
-            settings.helius_api_key.clone(),
-            settings.nextblock_api_key.clone(),
-            settings.quicknode_api_key.clone(),
-            settings.temporal_api_key.clone(),
-            settings.active_rpcs.iter().map(|rpc| rpc.as_str().to_string()).collect(),
-            settings.simulate,
-        );
+        let relayer_settings = relayer_settings(&settings);
         // Create a clone of the cancellation token for relayer
         let relayer_token = cancellation_token.clone();
+        // Scale swap amounts by the decimals of the mints the indexer has seen
+        qtrade_relayer::arbitrage::prepare::set_mint_decimals_source(qtrade_indexer::MINT_CACHE.clone());
         let relayer_future = qtrade_relayer::run_relayer(Some(relayer_settings), relayer_token);
 
         // Using the PoolCache from the runtime to pass to the router
-        let router_future = qtrade_router::run_router(Arc::clone(&qtrade_indexer::POOL_CACHE));
+        let router_token = cancellation_token.clone();
+        // Only quote pools of the DEXes the operator enabled
+        let router_dexes = settings.active_dexes.iter()
+            .filter_map(|dex| dex.as_str().parse::<qtrade_router::dex::types::DexType>().ok())
+            .collect();
+        let router_future = qtrade_router::run_router(
+            Arc::clone(&qtrade_indexer::POOL_CACHE),
+            router_dexes,
+            settings.max_price_impact,
+            settings.probe_settings(),
+            settings.router.backend(),
+            router_token,
+        );
 
         // Create indexer settings from runtime settings
-        let indexer_settings = qtrade_indexer::settings::IndexerSettings::new_with_config(
+        let mut indexer_settings = qtrade_indexer::settings::IndexerSettings::new_with_config(
             settings.active_dexes.iter().map(|dex| dex.as_str().to_string()).collect(),
             settings.vixon_config_path.clone()
-        );
+        ).with_pool_cache_ttl_secs(settings.pool_cache_ttl_secs);
+        if settings.pool_backfill_enabled {
+            indexer_settings = indexer_settings.with_backfill_rpc_url(settings.solana_rpc_url.clone());
+        }
+        if let Some(max_cache_entries) = settings.max_cache_entries {
+            indexer_settings = indexer_settings.with_max_cache_entries(max_cache_entries);
+        }
+        if settings.price_feeds_enabled {
+            indexer_settings = indexer_settings.with_price_feeds();
+        }
 
-        // Pass indexer settings to the streamer
-        let indexer_future = qtrade_indexer::streamer::run_streamer(
-            Some(indexer_settings)
-        );
+        // Pass indexer settings to the streamer. It reconnects on its own after a dropped
+        // stream, so an error reaching here is fatal and stops qtrade
+        let indexer_token = cancellation_token.clone();
+        let indexer_future = async move {
+            qtrade_indexer::streamer::run_streamer(Some(indexer_settings), indexer_token)
+                .await
+                .map_err(|e| {
+                    match e.downcast_ref::<qtrade_indexer::streamer::StreamerError>() {
+                        Some(streamer_error) if streamer_error.is_fatal() => {
+                            tracing::error!("Indexer can't stream with its configuration, shutting down: {}", streamer_error);
+                        }
+                        _ => tracing::error!("Indexer failed, shutting down: {:?}", e),
+                    }
+                    e
+                })
+        };
+
+        // Optionally serve metrics for Prometheus to scrape
+        let metrics_token = cancellation_token.clone();
+        let metrics_server_enabled = settings.metrics_server_enabled;
+        let metrics_server_port = settings.metrics_server_port;
+        let metrics_future = async move {
+            if metrics_server_enabled {
+                metrics_server::run_metrics_server(metrics_server_port, metrics_token).await
+            } else {
+                Ok(())
+            }
+        };
+
+        // Optionally serve the health/readiness endpoint
+        let health_token = cancellation_token.clone();
+        let health_server_enabled = settings.health_server_enabled;
+        let health_server_port = settings.health_server_port;
+        let health_future = async move {
+            if health_server_enabled {
+                health_server::run_health_server(health_server_port, health_token).await
+            } else {
+                Ok(())
+            }
+        };
 
         // Run async run_xxx functions concurrently
         try_join!(
             relayer_future,
             router_future,
             indexer_future,
-            wallets_future
+            wallets_future,
+            metrics_future,
+            health_future
         )?;
 
         Ok(())
@@ -0,0 +1,95 @@
+//! Prometheus scrape endpoint for qtrade metrics
+//!
+//! The OpenTelemetry meters used throughout qtrade (arbitrage, wallet, nonce, ...)
+//! normally flow only through the OTLP exporter configured in qtrade-client. This
+//! module bridges them into a Prometheus registry and serves that registry in the
+//! Prometheus text format at `GET /metrics`.
+//!
+//! The bridge is a metric reader that must be attached to the meter provider when
+//! it is built (see [`prometheus_reader`]); the HTTP server itself is optional and
+//! only started when `metrics_server_enabled` is set in the runtime settings.
+
+use anyhow::{anyhow, Context, Result};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use once_cell::sync::Lazy;
+use opentelemetry_prometheus::PrometheusExporter;
+use prometheus::{Encoder, Registry, TextEncoder};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+/// Default port for the Prometheus scrape endpoint
+pub const DEFAULT_METRICS_SERVER_PORT: u16 = 9464;
+
+/// Registry backing the process-wide Prometheus bridge
+pub static PROMETHEUS_REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
+
+/// Create a metric reader that exports OpenTelemetry metrics into [`PROMETHEUS_REGISTRY`]
+///
+/// Attach the returned reader to the `SdkMeterProvider` alongside the OTLP exporter.
+pub fn prometheus_reader() -> Result<PrometheusExporter> {
+    prometheus_reader_with_registry(&PROMETHEUS_REGISTRY)
+}
+
+/// Create a metric reader that exports OpenTelemetry metrics into `registry`
+pub fn prometheus_reader_with_registry(registry: &Registry) -> Result<PrometheusExporter> {
+    opentelemetry_prometheus::exporter()
+        .with_registry(registry.clone())
+        .build()
+        .map_err(|e| anyhow!("Failed to build Prometheus exporter: {:?}", e))
+}
+
+async fn metrics_handler(State(registry): State<Registry>) -> Response {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+
+    match encoder.encode(&registry.gather(), &mut buffer) {
+        Ok(()) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, encoder.format_type().to_string())],
+            buffer,
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to encode Prometheus metrics: {:?}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to encode metrics: {}", e))
+                .into_response()
+        }
+    }
+}
+
+/// Serve `registry` at `/metrics` on an already bound listener until the token is cancelled
+pub async fn serve_metrics(
+    listener: TcpListener,
+    registry: Registry,
+    cancellation_token: CancellationToken,
+) -> Result<()> {
+    let app = Router::new()
+        .route("/metrics", get(metrics_handler))
+        .with_state(registry);
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move { cancellation_token.cancelled().await })
+        .await
+        .context("Prometheus metrics server failed")?;
+
+    tracing::info!("Prometheus metrics server stopped");
+    Ok(())
+}
+
+/// Bind the Prometheus scrape endpoint on `port` and serve [`PROMETHEUS_REGISTRY`]
+pub async fn run_metrics_server(port: u16, cancellation_token: CancellationToken) -> Result<()> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind Prometheus metrics server to {}", addr))?;
+
+    tracing::info!("Prometheus metrics server listening on http://{}/metrics", addr);
+    serve_metrics(listener, PROMETHEUS_REGISTRY.clone(), cancellation_token).await
+}
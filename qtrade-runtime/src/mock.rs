@@ -0,0 +1,96 @@
+//! Mock mode for running the whole loop locally
+//!
+//! With `--mock`, the router quotes a [`MockPoolCache`] of synthetic pools instead of the
+//! indexer's cache and solves with the native CFMMRouter backend, and the relayer
+//! simulates against in-memory RPC providers. Nothing touches the network, so developers
+//! can watch simulated arbitrage without API keys, a geyser stream or funded wallets.
+
+use anyhow::Result;
+use qtrade_relayer::settings::{RelayerSettings, SubmitMode};
+use qtrade_router::backend::RouterBackend;
+use qtrade_router::dex::types::DexType;
+use qtrade_shared_types::{IndexedPool, PoolCache, PoolEntry, PoolState, ReservesState};
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+use tokio::try_join;
+use tokio_util::sync::CancellationToken;
+
+use crate::settings::{MockPool, Settings};
+
+/// Pools used when no `mock_pools` are configured, shaped like the router's reference
+/// network so the solver finds an opportunity
+pub fn default_mock_pools() -> Vec<MockPool> {
+    vec![
+        MockPool { token_a_amount: 4_000_000_000, token_b_amount: 4_000_000_000, fee_rate: 20 },
+        MockPool { token_a_amount: 10_000_000_000, token_b_amount: 1_000_000_000, fee_rate: 30 },
+        MockPool { token_a_amount: 1_000_000_000, token_b_amount: 5_000_000_000, fee_rate: 30 },
+        MockPool { token_a_amount: 40_000_000_000, token_b_amount: 50_000_000_000, fee_rate: 30 },
+        MockPool { token_a_amount: 10_000_000_000, token_b_amount: 10_000_000_000, fee_rate: 10 },
+    ]
+}
+
+/// Pool cache of synthetic constant product pools whose reserves never change
+#[derive(Debug, Clone)]
+pub struct MockPoolCache {
+    entries: Vec<PoolEntry>,
+}
+
+impl MockPoolCache {
+    /// Cache holding one Raydium CPMM pool per mock pool, each at a fresh address
+    pub fn new(pools: &[MockPool]) -> Self {
+        let entries = pools
+            .iter()
+            .map(|pool| {
+                let state = PoolState::RaydiumCpmm(ReservesState {
+                    token_a_amount: pool.token_a_amount,
+                    token_b_amount: pool.token_b_amount,
+                    fee_rate: pool.fee_rate,
+                });
+                (Pubkey::new_unique(), IndexedPool::from(state))
+            })
+            .collect();
+
+        Self { entries }
+    }
+}
+
+#[async_trait::async_trait]
+impl PoolCache for MockPoolCache {
+    async fn get_all_entries_as_slice(&self) -> Vec<PoolEntry> {
+        self.entries.clone()
+    }
+}
+
+/// Run the router and relayer on synthetic pools until cancelled
+///
+/// The relayer always simulates with mock providers, whatever the submit mode, and
+/// the router always solves with the native backend.
+pub async fn run_mock(
+    settings: &Settings,
+    mut relayer_settings: RelayerSettings,
+    cancellation_token: CancellationToken,
+) -> Result<()> {
+    let pools = if settings.mock_pools.is_empty() {
+        default_mock_pools()
+    } else {
+        settings.mock_pools.clone()
+    };
+    tracing::info!("Running qtrade in MOCK mode on {} synthetic pools", pools.len());
+
+    relayer_settings.mock_execution = true;
+    relayer_settings.submit_mode = SubmitMode::SimulateOnly;
+    let relayer_future = qtrade_relayer::run_relayer(Some(relayer_settings), cancellation_token.clone());
+
+    let router_future = qtrade_router::run_router(
+        Arc::new(MockPoolCache::new(&pools)),
+        vec![DexType::RaydiumCpmm],
+        settings.max_price_impact,
+        settings.probe_settings(),
+        RouterBackend::CfmmRouter,
+        cancellation_token.clone(),
+    );
+
+    try_join!(relayer_future, router_future)?;
+
+    Ok(())
+}
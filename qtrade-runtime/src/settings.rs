@@ -38,9 +38,29 @@
 //! - `TEMPORAL_API_KEY`
 //! - `QTRADE_NONCE_ACCOUNTS` (comma-separated list)
 //! - `QTRADE_NONCE_AUTHORITY_SECRET`
+//! - `QTRADE_SINGLE_WALLET_PRIVATE_KEYS` (comma-separated list)
+//! - `QTRADE_KEY_RETIREMENT_POLICY` (`always`/`on_success_only`/`never`)
+//! - `QTRADE_FEE_PAYER_KEYPAIR_PATH`
+//! - `QTRADE_RECORD_RESULTS_PATH`
+//! - `QTRADE_MAX_POOLS_PER_TX`
+//! - `QTRADE_ALLOWED_MINTS` (comma-separated list)
+//! - `QTRADE_BLOCKED_MINTS` (comma-separated list)
+//! - `QTRADE_BLOCKHASH_ONLY_RPCS` (comma-separated list)
+//! - `QTRADE_SIMULATION_RPCS` (comma-separated list)
+//! - `QTRADE_IDENTICAL_TRANSACTION` (`true`/`false`)
+//! - `QTRADE_METRICS_SERVER_ENABLED` (`true`/`false`)
+//! - `QTRADE_METRICS_SERVER_PORT`
+//! - `QTRADE_HEALTH_SERVER_ENABLED` (`true`/`false`)
+//! - `QTRADE_HEALTH_SERVER_PORT`
+//! - `QTRADE_POOL_BACKFILL_ENABLED` (`true`/`false`)
+//! - `QTRADE_MAX_CACHE_ENTRIES`
+//! - `QTRADE_MAX_PRICE_IMPACT` (fraction, e.g. `0.2` for 20%)
+//! - `QTRADE_PROBE_AMOUNTS` (comma-separated list of input token atoms)
+//! - `QTRADE_SLIPPAGE_BPS`
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::{env, fs, path::Path};
 use std::io::Read;
 
@@ -68,6 +88,10 @@ pub struct Settings {
     pub single_wallet: bool,
     pub single_wallet_private_key: Option<String>,
 
+    // Additional single wallet keys, rotated round-robin with the key above for throughput
+    #[serde(default)]
+    pub single_wallet_private_keys: Vec<String>,
+
     // Runtime configuration
     pub blockchain: crate::Blockchain,
     pub router: crate::Router,
@@ -75,11 +99,228 @@ pub struct Settings {
     // RPC providers to use for transaction submissions
     pub active_rpcs: Vec<crate::RpcProvider>,
 
+    // RPC providers that always use a recent blockhash instead of trying a durable nonce
+    #[serde(default)]
+    pub blockhash_only_rpcs: Vec<crate::RpcProvider>,
+
+    // RPC providers simulation mode simulates with, in order (empty for the default order)
+    #[serde(default)]
+    pub simulation_rpcs: Vec<crate::RpcProvider>,
+
+    // Send one identically signed transaction to every active provider instead of one each
+    #[serde(default)]
+    pub identical_transaction: bool,
+
+    // Provider names from flags or the environment that didn't match any RPC provider
+    #[serde(skip)]
+    pub unknown_rpcs: Vec<String>,
+
     // DEX platforms to use for indexing and routing
     pub active_dexes: Vec<crate::Dex>,
 
     // Transaction simulation flag
     pub simulate: bool,
+
+    // Submit, simulate, or simulate before submitting
+    #[serde(default)]
+    pub submit_mode: qtrade_relayer::settings::SubmitMode,
+
+    // Prometheus scrape endpoint
+    #[serde(default)]
+    pub metrics_server_enabled: bool,
+    #[serde(default = "default_metrics_server_port")]
+    pub metrics_server_port: u16,
+
+    // Health/readiness endpoint
+    #[serde(default)]
+    pub health_server_enabled: bool,
+    #[serde(default = "default_health_server_port")]
+    pub health_server_port: u16,
+
+    // Pools not updated within this many seconds are evicted from the pool cache
+    #[serde(default = "default_pool_cache_ttl_secs")]
+    pub pool_cache_ttl_secs: u64,
+
+    // Prime the pool cache over RPC on startup; the router waits for it before the first solve
+    #[serde(default = "default_pool_backfill_enabled")]
+    pub pool_backfill_enabled: bool,
+
+    // Maximum entries per indexer cache, least recently updated evicted first (unbounded if unset)
+    #[serde(default)]
+    pub max_cache_entries: Option<usize>,
+
+    // Stream Pyth price feeds into the indexer's price cache
+    #[serde(default)]
+    pub price_feeds_enabled: bool,
+
+    // Quotes with a larger price impact (as a fraction, 0.2 = 20%) are dropped before solving
+    #[serde(default = "default_max_price_impact")]
+    pub max_price_impact: f64,
+
+    // Input amounts (in token atoms) quoted at pools whose depth is unknown
+    #[serde(default = "default_probe_amounts")]
+    pub probe_amounts: Vec<u64>,
+
+    // Slippage tolerance of the router's quotes, in basis points
+    #[serde(default = "default_slippage_bps")]
+    pub slippage_bps: u16,
+
+    // Capacity of the relayer's arbitrage result queue
+    #[serde(default = "default_max_queue_size")]
+    pub max_queue_size: usize,
+
+    // Arbitrage executions the relayer runs at once; further opportunities wait in the queue
+    #[serde(default = "default_max_in_flight")]
+    pub max_in_flight: usize,
+
+    // Base Solana RPC URL (mainnet-beta, devnet, testnet or a private node)
+    #[serde(default = "default_solana_rpc_url")]
+    pub solana_rpc_url: String,
+
+    // Milliseconds an RPC provider request may take before it's abandoned as timed out
+    #[serde(default = "default_rpc_request_timeout_ms")]
+    pub rpc_request_timeout_ms: u64,
+
+    // Per-provider overrides of rpc_request_timeout_ms, e.g. { Jito = 1500 }
+    #[serde(default)]
+    pub rpc_request_timeouts_ms: HashMap<crate::RpcProvider, u64>,
+
+    // JSON file remembering submitted opportunities across restarts (memory only if unset)
+    #[serde(default)]
+    pub submission_store_path: Option<String>,
+
+    // JSON file keeping taxable events the database hasn't taken yet across restarts
+    #[serde(default)]
+    pub taxable_event_spool_path: Option<String>,
+
+    // Submitted opportunities are forgotten after this many seconds if they never confirm
+    #[serde(default = "default_submission_ttl_secs")]
+    pub submission_ttl_secs: u64,
+
+    // Keep executing queued results for up to drain_budget_secs after shutdown is requested
+    #[serde(default)]
+    pub drain_on_shutdown: bool,
+
+    #[serde(default = "default_drain_budget_secs")]
+    pub drain_budget_secs: u64,
+
+    // JSON file keeping results still queued at shutdown, reloaded on the next start
+    #[serde(default)]
+    pub queue_spool_path: Option<String>,
+
+    // Keypair file for a dedicated account paying transaction fees and tips (explorer keys pay if unset)
+    #[serde(default)]
+    pub fee_payer_keypair_path: Option<String>,
+
+    // Append every received arbitrage result to this file as JSON lines for later replay
+    #[serde(default)]
+    pub record_results_path: Option<String>,
+
+    // Append the decoded parameters of every swap built to this file as JSON lines for audits
+    #[serde(default)]
+    pub swap_audit_path: Option<String>,
+
+    // Most pools (swap legs) per arbitrage transaction, keeping the most profitable (no cap if unset)
+    #[serde(default)]
+    pub max_pools_per_tx: Option<usize>,
+
+    // Only trade these mints (any mint if empty)
+    #[serde(default)]
+    pub allowed_mints: Vec<String>,
+
+    // Never trade these mints, even if allowed
+    #[serde(default)]
+    pub blocked_mints: Vec<String>,
+
+    // Wrap each swap in the qtrade executor program's swap instead of calling the DEX directly
+    #[serde(default)]
+    pub route_through_executor: bool,
+
+    // Referral code passed to the executor on routed swaps (codes above 2^31 charge a fee)
+    #[serde(default)]
+    pub executor_referral_code: u32,
+
+    // When explorer keys are retired after signing: always, on_success_only or never
+    #[serde(default = "default_key_retirement_policy")]
+    pub key_retirement_policy: String,
+
+    // Run on synthetic pools with mock RPC providers instead of the network (needs the mock feature)
+    #[serde(default)]
+    pub mock: bool,
+
+    // Synthetic pools mock mode quotes (a default set if empty)
+    #[serde(default)]
+    pub mock_pools: Vec<MockPool>,
+}
+
+/// A synthetic constant product pool for mock mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MockPool {
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    /// Fee rate in basis points (30 = 0.3%)
+    #[serde(default = "default_mock_pool_fee_rate")]
+    pub fee_rate: u16,
+}
+
+fn default_mock_pool_fee_rate() -> u16 {
+    30
+}
+
+fn default_key_retirement_policy() -> String {
+    qtrade_wallets::RetirementPolicy::default().as_str().to_string()
+}
+
+fn default_metrics_server_port() -> u16 {
+    crate::metrics_server::DEFAULT_METRICS_SERVER_PORT
+}
+
+fn default_health_server_port() -> u16 {
+    crate::health_server::DEFAULT_HEALTH_SERVER_PORT
+}
+
+fn default_pool_cache_ttl_secs() -> u64 {
+    qtrade_indexer::settings::DEFAULT_POOL_CACHE_TTL_SECS
+}
+
+fn default_pool_backfill_enabled() -> bool {
+    true
+}
+
+fn default_max_price_impact() -> f64 {
+    qtrade_router::DEFAULT_MAX_PRICE_IMPACT
+}
+
+fn default_probe_amounts() -> Vec<u64> {
+    qtrade_router::probe_sizing::FALLBACK_PROBE_AMOUNTS.to_vec()
+}
+
+fn default_slippage_bps() -> u16 {
+    qtrade_router::probe_sizing::DEFAULT_SLIPPAGE_BPS
+}
+
+fn default_max_queue_size() -> usize {
+    qtrade_relayer::DEFAULT_MAX_QUEUE_SIZE
+}
+
+fn default_max_in_flight() -> usize {
+    qtrade_relayer::arbitrage::in_flight::DEFAULT_MAX_IN_FLIGHT
+}
+
+fn default_solana_rpc_url() -> String {
+    qtrade_relayer::rpc::solana::MAINNET_RPC_URL.to_string()
+}
+
+fn default_rpc_request_timeout_ms() -> u64 {
+    qtrade_relayer::rpc::DEFAULT_RPC_REQUEST_TIMEOUT.as_millis() as u64
+}
+
+fn default_submission_ttl_secs() -> u64 {
+    qtrade_relayer::arbitrage::dedup::DEFAULT_SUBMISSION_TTL.as_secs()
+}
+
+fn default_drain_budget_secs() -> u64 {
+    qtrade_relayer::arbitrage::drain::DEFAULT_DRAIN_BUDGET.as_secs()
 }
 
 /// Command-line override flags passed from qtrade-client
@@ -121,6 +362,9 @@ pub struct Flags {
 
     // Transaction simulation flag
     pub simulate: bool,
+
+    // Mock mode flag (synthetic pools, native solver, mock RPC providers)
+    pub mock: bool,
 }
 
 impl Settings {
@@ -206,6 +450,229 @@ impl Settings {
             .ok()
             .unwrap_or(settings.nonce_authority_secret);
 
+        if let Ok(keys_str) = env::var("QTRADE_SINGLE_WALLET_PRIVATE_KEYS") {
+            settings.single_wallet_private_keys = keys_str
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+
+        if let Ok(enabled) = env::var("QTRADE_METRICS_SERVER_ENABLED") {
+            settings.metrics_server_enabled = enabled.trim().eq_ignore_ascii_case("true");
+        }
+
+        if let Ok(port_str) = env::var("QTRADE_METRICS_SERVER_PORT") {
+            match port_str.trim().parse::<u16>() {
+                Ok(port) => settings.metrics_server_port = port,
+                Err(_) => tracing::warn!("Invalid QTRADE_METRICS_SERVER_PORT: {}", port_str),
+            }
+        }
+
+        if let Ok(enabled) = env::var("QTRADE_HEALTH_SERVER_ENABLED") {
+            settings.health_server_enabled = enabled.trim().eq_ignore_ascii_case("true");
+        }
+
+        if let Ok(port_str) = env::var("QTRADE_HEALTH_SERVER_PORT") {
+            match port_str.trim().parse::<u16>() {
+                Ok(port) => settings.health_server_port = port,
+                Err(_) => tracing::warn!("Invalid QTRADE_HEALTH_SERVER_PORT: {}", port_str),
+            }
+        }
+
+        if let Ok(ttl_str) = env::var("QTRADE_POOL_CACHE_TTL_SECS") {
+            match ttl_str.trim().parse::<u64>() {
+                Ok(ttl) => settings.pool_cache_ttl_secs = ttl,
+                Err(_) => tracing::warn!("Invalid QTRADE_POOL_CACHE_TTL_SECS: {}", ttl_str),
+            }
+        }
+
+        if let Ok(enabled) = env::var("QTRADE_POOL_BACKFILL_ENABLED") {
+            settings.pool_backfill_enabled = enabled.trim().eq_ignore_ascii_case("true");
+        }
+
+        if let Ok(enabled) = env::var("QTRADE_PRICE_FEEDS_ENABLED") {
+            settings.price_feeds_enabled = enabled.trim().eq_ignore_ascii_case("true");
+        }
+
+        if let Ok(max_str) = env::var("QTRADE_MAX_CACHE_ENTRIES") {
+            match max_str.trim().parse::<usize>() {
+                Ok(max) => settings.max_cache_entries = Some(max),
+                Err(_) => tracing::warn!("Invalid QTRADE_MAX_CACHE_ENTRIES: {}", max_str),
+            }
+        }
+
+        if let Ok(impact_str) = env::var("QTRADE_MAX_PRICE_IMPACT") {
+            match impact_str.trim().parse::<f64>() {
+                Ok(impact) if impact.is_finite() && impact > 0.0 => settings.max_price_impact = impact,
+                _ => tracing::warn!("Invalid QTRADE_MAX_PRICE_IMPACT: {}", impact_str),
+            }
+        }
+
+        if let Ok(amounts_str) = env::var("QTRADE_PROBE_AMOUNTS") {
+            match amounts_str
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::parse::<u64>)
+                .collect::<Result<Vec<_>, _>>()
+            {
+                Ok(amounts) => settings.probe_amounts = amounts,
+                Err(_) => tracing::warn!("Invalid QTRADE_PROBE_AMOUNTS: {}", amounts_str),
+            }
+        }
+
+        if let Ok(slippage_str) = env::var("QTRADE_SLIPPAGE_BPS") {
+            match slippage_str.trim().parse::<u16>() {
+                Ok(slippage) => settings.slippage_bps = slippage,
+                Err(_) => tracing::warn!("Invalid QTRADE_SLIPPAGE_BPS: {}", slippage_str),
+            }
+        }
+
+        if let Ok(size_str) = env::var("QTRADE_MAX_QUEUE_SIZE") {
+            match size_str.trim().parse::<usize>() {
+                Ok(size) => settings.max_queue_size = size,
+                Err(_) => tracing::warn!("Invalid QTRADE_MAX_QUEUE_SIZE: {}", size_str),
+            }
+        }
+
+        if let Ok(max_str) = env::var("QTRADE_MAX_IN_FLIGHT") {
+            match max_str.trim().parse::<usize>() {
+                Ok(max) => settings.max_in_flight = max,
+                Err(_) => tracing::warn!("Invalid QTRADE_MAX_IN_FLIGHT: {}", max_str),
+            }
+        }
+
+        if let Ok(mode_str) = env::var("QTRADE_SUBMIT_MODE") {
+            match mode_str.parse::<qtrade_relayer::settings::SubmitMode>() {
+                Ok(mode) => settings.submit_mode = mode,
+                Err(_) => tracing::warn!("Invalid QTRADE_SUBMIT_MODE: {}", mode_str),
+            }
+        }
+
+        if let Ok(url) = env::var("QTRADE_SOLANA_RPC_URL") {
+            if url.trim().is_empty() {
+                tracing::warn!("Ignoring empty QTRADE_SOLANA_RPC_URL");
+            } else {
+                settings.solana_rpc_url = url.trim().to_string();
+            }
+        }
+
+        if let Ok(timeout_str) = env::var("QTRADE_RPC_REQUEST_TIMEOUT_MS") {
+            match timeout_str.trim().parse::<u64>() {
+                Ok(timeout) if timeout > 0 => settings.rpc_request_timeout_ms = timeout,
+                _ => tracing::warn!("Invalid QTRADE_RPC_REQUEST_TIMEOUT_MS: {}", timeout_str),
+            }
+        }
+
+        if let Ok(path) = env::var("QTRADE_SUBMISSION_STORE_PATH") {
+            if path.trim().is_empty() {
+                tracing::warn!("Ignoring empty QTRADE_SUBMISSION_STORE_PATH");
+            } else {
+                settings.submission_store_path = Some(path.trim().to_string());
+            }
+        }
+
+        if let Ok(path) = env::var("QTRADE_TAXABLE_EVENT_SPOOL_PATH") {
+            if path.trim().is_empty() {
+                tracing::warn!("Ignoring empty QTRADE_TAXABLE_EVENT_SPOOL_PATH");
+            } else {
+                settings.taxable_event_spool_path = Some(path.trim().to_string());
+            }
+        }
+
+        if let Ok(ttl_str) = env::var("QTRADE_SUBMISSION_TTL_SECS") {
+            match ttl_str.trim().parse::<u64>() {
+                Ok(ttl) => settings.submission_ttl_secs = ttl,
+                Err(_) => tracing::warn!("Invalid QTRADE_SUBMISSION_TTL_SECS: {}", ttl_str),
+            }
+        }
+
+        if let Ok(enabled) = env::var("QTRADE_DRAIN_ON_SHUTDOWN") {
+            settings.drain_on_shutdown = enabled.trim().eq_ignore_ascii_case("true");
+        }
+
+        if let Ok(budget_str) = env::var("QTRADE_DRAIN_BUDGET_SECS") {
+            match budget_str.trim().parse::<u64>() {
+                Ok(budget) => settings.drain_budget_secs = budget,
+                Err(_) => tracing::warn!("Invalid QTRADE_DRAIN_BUDGET_SECS: {}", budget_str),
+            }
+        }
+
+        if let Ok(path) = env::var("QTRADE_QUEUE_SPOOL_PATH") {
+            if path.trim().is_empty() {
+                tracing::warn!("Ignoring empty QTRADE_QUEUE_SPOOL_PATH");
+            } else {
+                settings.queue_spool_path = Some(path.trim().to_string());
+            }
+        }
+
+        if let Ok(path) = env::var("QTRADE_FEE_PAYER_KEYPAIR_PATH") {
+            if path.trim().is_empty() {
+                tracing::warn!("Ignoring empty QTRADE_FEE_PAYER_KEYPAIR_PATH");
+            } else {
+                settings.fee_payer_keypair_path = Some(path.trim().to_string());
+            }
+        }
+
+        if let Ok(path) = env::var("QTRADE_RECORD_RESULTS_PATH") {
+            if path.trim().is_empty() {
+                tracing::warn!("Ignoring empty QTRADE_RECORD_RESULTS_PATH");
+            } else {
+                settings.record_results_path = Some(path.trim().to_string());
+            }
+        }
+
+        if let Ok(path) = env::var("QTRADE_SWAP_AUDIT_PATH") {
+            if path.trim().is_empty() {
+                tracing::warn!("Ignoring empty QTRADE_SWAP_AUDIT_PATH");
+            } else {
+                settings.swap_audit_path = Some(path.trim().to_string());
+            }
+        }
+
+        if let Ok(max_str) = env::var("QTRADE_MAX_POOLS_PER_TX") {
+            match max_str.trim().parse::<usize>() {
+                Ok(max) => settings.max_pools_per_tx = Some(max),
+                Err(_) => tracing::warn!("Invalid QTRADE_MAX_POOLS_PER_TX: {}", max_str),
+            }
+        }
+
+        if let Ok(mints_str) = env::var("QTRADE_ALLOWED_MINTS") {
+            settings.allowed_mints = mints_str
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+
+        if let Ok(mints_str) = env::var("QTRADE_BLOCKED_MINTS") {
+            settings.blocked_mints = mints_str
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+
+        if let Ok(enabled) = env::var("QTRADE_ROUTE_THROUGH_EXECUTOR") {
+            settings.route_through_executor = enabled.trim().eq_ignore_ascii_case("true");
+        }
+
+        if let Ok(code_str) = env::var("QTRADE_EXECUTOR_REFERRAL_CODE") {
+            match code_str.trim().parse::<u32>() {
+                Ok(code) => settings.executor_referral_code = code,
+                Err(_) => tracing::warn!("Invalid QTRADE_EXECUTOR_REFERRAL_CODE: {}", code_str),
+            }
+        }
+
+        if let Ok(policy) = env::var("QTRADE_KEY_RETIREMENT_POLICY") {
+            settings.key_retirement_policy = policy.trim().to_string();
+        }
+
+        if let Ok(enabled) = env::var("QTRADE_MOCK") {
+            settings.mock = enabled.trim().eq_ignore_ascii_case("true");
+        }
+
         // Finally override with CLI flags (highest precedence)
         if let Some(api_key) = flags.bloxroute_api_key {
             settings.bloxroute_api_key = api_key;
@@ -240,6 +707,7 @@ impl Settings {
                     parsed_rpcs.push(rpc_provider);
                 } else {
                     tracing::warn!("Unknown RPC provider: {}", rpc_str);
+                    settings.unknown_rpcs.push(rpc_str.clone());
                 }
             }
 
@@ -261,6 +729,7 @@ impl Settings {
                             parsed_rpcs.push(rpc_provider);
                         } else {
                             tracing::warn!("Unknown RPC provider in environment variable: {}", rpc_str);
+                            settings.unknown_rpcs.push(rpc_str.to_string());
                         }
                     }
 
@@ -272,6 +741,38 @@ impl Settings {
             }
         }
 
+        // Providers that skip durable nonces (comma-separated)
+        if let Ok(rpcs_str) = env::var("QTRADE_BLOCKHASH_ONLY_RPCS") {
+            let mut parsed_rpcs = Vec::new();
+            for rpc_str in rpcs_str.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+                if let Some(rpc_provider) = crate::RpcProvider::from_str(rpc_str) {
+                    parsed_rpcs.push(rpc_provider);
+                } else {
+                    tracing::warn!("Unknown RPC provider in QTRADE_BLOCKHASH_ONLY_RPCS: {}", rpc_str);
+                    settings.unknown_rpcs.push(rpc_str.to_string());
+                }
+            }
+            settings.blockhash_only_rpcs = parsed_rpcs;
+        }
+
+        // Providers to simulate with, in order (comma-separated)
+        if let Ok(rpcs_str) = env::var("QTRADE_SIMULATION_RPCS") {
+            let mut parsed_rpcs = Vec::new();
+            for rpc_str in rpcs_str.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+                if let Some(rpc_provider) = crate::RpcProvider::from_str(rpc_str) {
+                    parsed_rpcs.push(rpc_provider);
+                } else {
+                    tracing::warn!("Unknown RPC provider in QTRADE_SIMULATION_RPCS: {}", rpc_str);
+                    settings.unknown_rpcs.push(rpc_str.to_string());
+                }
+            }
+            settings.simulation_rpcs = parsed_rpcs;
+        }
+
+        if let Ok(enabled) = env::var("QTRADE_IDENTICAL_TRANSACTION") {
+            settings.identical_transaction = enabled.trim().eq_ignore_ascii_case("true");
+        }
+
         // Parse active DEXes from string array to Dex enum array
         let mut dexes_from_flags = false;
         if let Some(active_dexes_strs) = &flags.active_dexes {
@@ -333,6 +834,10 @@ impl Settings {
             settings.simulate = true;
         }
 
+        if flags.mock {
+            settings.mock = true;
+        }
+
         // Single wallet private key (flag overrides config)
         if let Some(key) = flags.single_wallet_private_key {
             settings.single_wallet_private_key = Some(key);
@@ -341,7 +846,10 @@ impl Settings {
         // Log single wallet mode status
         if settings.single_wallet {
             tracing::info!("Single wallet mode is enabled");
-            if settings.single_wallet_private_key.is_some() {
+            let key_count = settings.get_single_wallet_private_keys().len();
+            if key_count > 1 {
+                tracing::info!("Using {} provided private keys for single wallet", key_count);
+            } else if key_count == 1 {
                 tracing::info!("Using provided private key for single wallet");
             } else {
                 tracing::warn!("Single wallet mode enabled but no private key provided");
@@ -381,6 +889,15 @@ impl Settings {
 
     /// Validate that all required settings are present
     pub fn validate(&self) -> Result<()> {
+        // Wallets, RPC providers, DEX parsers and the executor are all Solana-only, so
+        // refuse to start rather than silently running the Solana pipeline for Sui
+        if !matches!(self.blockchain, crate::Blockchain::Solana) {
+            return Err(anyhow::anyhow!("Unsupported blockchain: {:?} (only Solana is implemented)", self.blockchain));
+        }
+
+        // Refuse a router backend that isn't implemented rather than solving with another
+        self.router.backend().ensure_supported()?;
+
         // Check if required API keys are set
         if self.bloxroute_api_key.is_empty() {
             tracing::warn!("BLOXROUTE_API_KEY is not set. Some functionality may be limited.");
@@ -407,11 +924,64 @@ impl Settings {
             return Err(anyhow::anyhow!("Vixon config path must be provided"));
         }
 
+        if !self.unknown_rpcs.is_empty() {
+            for name in &self.unknown_rpcs {
+                tracing::error!("Unknown RPC provider: {}", name);
+            }
+            return Err(anyhow::anyhow!("Unknown RPC providers: {}", self.unknown_rpcs.join(", ")));
+        }
+
+        self.probe_settings().validate()?;
+
+        if self.max_queue_size == 0 {
+            return Err(anyhow::anyhow!("max_queue_size must be at least 1"));
+        }
+
+        if self.max_in_flight == 0 {
+            return Err(anyhow::anyhow!("max_in_flight must be at least 1"));
+        }
+
+        if self.solana_rpc_url.trim().is_empty() {
+            return Err(anyhow::anyhow!("solana_rpc_url must not be empty"));
+        }
+
+        if self.rpc_request_timeout_ms == 0 {
+            return Err(anyhow::anyhow!("rpc_request_timeout_ms must be at least 1"));
+        }
+
+        if let Some((provider, _)) = self.rpc_request_timeouts_ms.iter().find(|(_, ms)| **ms == 0) {
+            return Err(anyhow::anyhow!("rpc_request_timeouts_ms for {} must be at least 1", provider.as_str()));
+        }
+
+        if self.submission_ttl_secs == 0 {
+            return Err(anyhow::anyhow!("submission_ttl_secs must be at least 1"));
+        }
+
+        if self.max_pools_per_tx == Some(0) {
+            return Err(anyhow::anyhow!("max_pools_per_tx must be at least 1"));
+        }
+
+        if self.mock && !cfg!(feature = "mock") {
+            return Err(anyhow::anyhow!("Mock mode needs qtrade built with the mock feature"));
+        }
+
+        if self.mock_pools.iter().any(|pool| pool.token_a_amount == 0 || pool.token_b_amount == 0) {
+            return Err(anyhow::anyhow!("mock_pools reserves must be at least 1"));
+        }
+
         // Note: We don't validate nonce account settings as they might be optional
 
         Ok(())
     }
 
+    /// How the router probes pools for quotes
+    pub fn probe_settings(&self) -> qtrade_router::probe_sizing::ProbeSettings {
+        qtrade_router::probe_sizing::ProbeSettings {
+            probe_amounts: self.probe_amounts.clone(),
+            slippage_bps: self.slippage_bps,
+        }
+    }
+
     /// Get API keys and secrets for use in other modules
     pub fn get_bloxroute_api_key(&self) -> &str {
         &self.bloxroute_api_key
@@ -441,6 +1011,18 @@ impl Settings {
         &self.nonce_authority_secret
     }
 
+    /// All single wallet private keys, the primary key first, without duplicates
+    pub fn get_single_wallet_private_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = Vec::new();
+        for key in self.single_wallet_private_key.iter().chain(self.single_wallet_private_keys.iter()) {
+            let key = key.trim();
+            if !key.is_empty() && !keys.iter().any(|k| k == key) {
+                keys.push(key.to_string());
+            }
+        }
+        keys
+    }
+
     /// Create an example configuration file at the specified path
     pub fn create_example_config<P: AsRef<Path>>(path: P) -> Result<()> {
         let path = path.as_ref();
@@ -503,8 +1085,8 @@ impl Settings {
             # API Keys\n\
             # These are used for various external services\n\
             {}\n\
-            # Available blockchain options: Solana, Sui\n\
-            # Available router options: Cvxpy, OpenQAOA, CFMMRouter\n",
+            # Available blockchain options: Solana (Sui is not supported yet)\n\
+            # Available router options: Cvxpy, CFMMRouter (OpenQAOA is not supported yet)\n",
             toml_string
         );
 
@@ -528,6 +1110,7 @@ impl Default for Settings {
             vixon_config_path: "default_vixon_config.json".to_string(),
             single_wallet: false,
             single_wallet_private_key: None,
+            single_wallet_private_keys: vec![],
             blockchain: crate::Blockchain::Solana, // Default to Solana
             router: crate::Router::Cvxpy,     // Default to Cvxpy
             active_rpcs: vec![
@@ -539,13 +1122,52 @@ impl Default for Settings {
                 crate::RpcProvider::Solana,
                 crate::RpcProvider::Temporal,
             ],                                    // By default, enable all RPCs
+            blockhash_only_rpcs: vec![],
+            simulation_rpcs: vec![],
+            identical_transaction: false,
+            unknown_rpcs: vec![],
             active_dexes: vec![
                 crate::Dex::Orca,
                 crate::Dex::Raydium,
                 crate::Dex::RaydiumCpmm,
                 crate::Dex::RaydiumClmm,
+                crate::Dex::MeteoraDlmm,
             ],                                    // By default, enable all DEXes
             simulate: false,                      // Default simulate to false
+            submit_mode: qtrade_relayer::settings::SubmitMode::SubmitOnly,
+            metrics_server_enabled: false,        // Prometheus endpoint is opt-in
+            metrics_server_port: default_metrics_server_port(),
+            health_server_enabled: false,         // Health endpoint is opt-in
+            health_server_port: default_health_server_port(),
+            pool_cache_ttl_secs: default_pool_cache_ttl_secs(),
+            pool_backfill_enabled: default_pool_backfill_enabled(),
+            max_cache_entries: None,
+            price_feeds_enabled: false,
+            max_price_impact: default_max_price_impact(),
+            probe_amounts: default_probe_amounts(),
+            slippage_bps: default_slippage_bps(),
+            max_queue_size: default_max_queue_size(),
+            max_in_flight: default_max_in_flight(),
+            solana_rpc_url: default_solana_rpc_url(),
+            rpc_request_timeout_ms: default_rpc_request_timeout_ms(),
+            rpc_request_timeouts_ms: HashMap::new(),
+            submission_store_path: None,
+            taxable_event_spool_path: None,
+            submission_ttl_secs: default_submission_ttl_secs(),
+            drain_on_shutdown: false,
+            drain_budget_secs: default_drain_budget_secs(),
+            queue_spool_path: None,
+            fee_payer_keypair_path: None,
+            record_results_path: None,
+            swap_audit_path: None,
+            max_pools_per_tx: None,
+            allowed_mints: vec![],
+            blocked_mints: vec![],
+            route_through_executor: false,
+            executor_referral_code: 0,
+            key_retirement_policy: default_key_retirement_policy(),
+            mock: false,
+            mock_pools: vec![],
         }
     }
 }
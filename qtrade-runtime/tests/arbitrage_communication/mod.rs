@@ -14,6 +14,14 @@ async fn test_arbitrage_channel_communication() {
         lambdas: vec![vec![1.0, 2.0, 3.0, 4.0]],
         a_matrices: vec![vec![vec![1.0, 0.0], vec![0.0, 1.0]]],
         status: "optimal".to_string(),
+        execution_order: vec![],
+        market_values: vec![],
+        correlation_id: None,
+        emitted_at: None,
+        enqueued_at: None,
+        pool_fees: vec![],
+        pool_states: vec![],
+        version: ArbitrageResult::VERSION,
     };
 
     // Access the ARBITRAGE_SENDER
@@ -37,6 +45,14 @@ async fn test_arbitrage_channel_communication() {
         lambdas: vec![vec![2.0, 3.0, 4.0, 5.0]],
         a_matrices: vec![vec![vec![1.0, 0.0], vec![0.0, 1.0]]],
         status: "optimal".to_string(),
+        execution_order: vec![],
+        market_values: vec![],
+        correlation_id: None,
+        emitted_at: None,
+        enqueued_at: None,
+        pool_fees: vec![],
+        pool_states: vec![],
+        version: ArbitrageResult::VERSION,
     };
 
     tx.send(mock_result2.clone()).await.expect("Failed to send second mock result");
@@ -49,22 +65,20 @@ async fn test_arbitrage_channel_communication() {
 
 // Simplified test version of the relayer's queue processing logic
 async fn test_process_queue() -> bool {
+    // Like the relayer loop, own the receiver once it has been handed over
+    let mut receiver = None;
+
     // We'll try to process up to 10 items from the queue, or until it's empty
     for _ in 0..10 {
         // Check for messages from the channel
-        {
-            let mut receiver_guard = qtrade_relayer::ARBITRAGE_RECEIVER.lock().unwrap();
-            if let Some(ref mut rx) = *receiver_guard {
-                match rx.try_recv() {
-                    Ok(arbitrage_result) => {
-                        // Successfully received a result, add it to the queue
-                        let _ = qtrade_relayer::enqueue_arbitrage_result(arbitrage_result);
-                    }
-                    Err(_) => {
-                        // No more messages or error, continue
-                    }
-                }
-            }
+        if let Some(rx) = qtrade_relayer::take_arbitrage_receiver() {
+            receiver = Some(rx);
+        }
+        if let Some(rx) = receiver.as_mut() {
+            qtrade_relayer::drain_arbitrage_channel(rx, |arbitrage_result| {
+                // Successfully received a result, add it to the queue
+                let _ = qtrade_relayer::enqueue_arbitrage_result(arbitrage_result);
+            });
         }
 
         // Process an item from the queue
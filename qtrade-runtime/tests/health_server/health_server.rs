@@ -0,0 +1,48 @@
+use qtrade_runtime::health_server::serve_health;
+use qtrade_shared_types::{HealthSnapshot, HealthStatus};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+// Dedicated status so the test does not race with the process-wide HEALTH_STATUS
+static TEST_STATUS: HealthStatus = HealthStatus::new();
+
+#[tokio::test]
+async fn test_health_reports_unavailable_until_streamer_ready() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/health", listener.local_addr().unwrap());
+    let token = CancellationToken::new();
+    let server = tokio::spawn(serve_health(listener, &TEST_STATUS, token.clone()));
+
+    TEST_STATUS.set_queue_depth(3);
+    TEST_STATUS.set_available_explorer_keys(5);
+
+    // No account update yet: not ready
+    let response = reqwest::get(&url).await.expect("Failed to query health endpoint");
+    assert_eq!(response.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+    let snapshot: HealthSnapshot = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert!(!snapshot.ready);
+    assert!(!snapshot.streamer_connected);
+    assert_eq!(snapshot.last_account_update_ms, None);
+    assert_eq!(snapshot.queue_depth, 3);
+    assert_eq!(snapshot.available_explorer_keys, 5);
+
+    // First account update marks the streamer ready
+    TEST_STATUS.record_account_update();
+    TEST_STATUS.record_router_solve();
+
+    let response = reqwest::get(&url).await.expect("Failed to query health endpoint");
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let snapshot: HealthSnapshot = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert!(snapshot.ready);
+    assert!(snapshot.streamer_connected);
+    assert!(snapshot.last_account_update_ms.is_some());
+    assert!(snapshot.last_router_solve_ms.is_some());
+
+    token.cancel();
+    let result = tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("Health server did not shut down on cancellation")
+        .unwrap();
+    assert!(result.is_ok());
+}
@@ -0,0 +1,51 @@
+use opentelemetry::metrics::MeterProvider;
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use prometheus::Registry;
+use qtrade_runtime::metrics_server::{prometheus_reader_with_registry, serve_metrics};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn test_metrics_endpoint_serves_known_counter() {
+    // Use a dedicated registry so the test does not depend on the global meter provider
+    let registry = Registry::new();
+    let reader = prometheus_reader_with_registry(&registry).expect("Failed to build reader");
+    let provider = SdkMeterProvider::builder().with_reader(reader).build();
+
+    let counter = provider
+        .meter("qtrade_runtime_test")
+        .u64_counter("qtrade.test.scrapes")
+        .with_description("Counter used to verify the Prometheus endpoint")
+        .build();
+    counter.add(3, &[]);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let token = CancellationToken::new();
+    let server = tokio::spawn(serve_metrics(listener, registry, token.clone()));
+
+    let body = reqwest::get(format!("http://{}/metrics", addr))
+        .await
+        .expect("Failed to scrape metrics endpoint")
+        .text()
+        .await
+        .unwrap();
+
+    // Dots are sanitized and counters get the `_total` suffix
+    let line = body
+        .lines()
+        .find(|line| line.starts_with("qtrade_test_scrapes_total"))
+        .unwrap_or_else(|| panic!("Counter missing from scrape output:\n{}", body));
+    assert!(line.ends_with(" 3"), "Unexpected counter sample: {}", line);
+
+    // The server must stop once the cancellation token fires
+    token.cancel();
+    let result = tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("Metrics server did not shut down on cancellation")
+        .unwrap();
+    assert!(result.is_ok());
+
+    provider.shutdown().unwrap();
+}
@@ -25,6 +25,7 @@ mod tests {
             blockchain: Some(qtrade_runtime::Blockchain::Solana),
             router: Some(qtrade_runtime::Router::Cvxpy),
             simulate: false,
+            mock: false,
             active_rpcs: Some(vec![
                 "bloxroute".to_string(),
                 "helius".to_string(),
@@ -87,6 +88,7 @@ mod tests {
             single_wallet: false,
             single_wallet_private_key: None,
             simulate: false,
+            mock: false,
             active_rpcs: Some(vec![
                 "bloxroute".to_string(),
                 "helius".to_string(),
@@ -121,4 +123,33 @@ mod tests {
         assert_eq!(settings.quicknode_api_key, "");
         assert_eq!(settings.temporal_api_key, "");
     }
+
+    #[test]
+    fn test_sui_blockchain_is_unsupported() {
+        let temp_dir = TempDir::new().unwrap();
+        let vixon_path = temp_dir.path().join("vixon_config.json").to_str().unwrap().to_string();
+
+        let flags = settings::Flags {
+            config_file_path: None,
+            vixon_config_path: Some(vixon_path),
+            blockchain: Some(qtrade_runtime::Blockchain::Sui),
+            router: Some(qtrade_runtime::Router::Cvxpy),
+            bloxroute_api_key: None,
+            helius_api_key: None,
+            nextblock_api_key: None,
+            quicknode_api_key: None,
+            temporal_api_key: None,
+            single_wallet: false,
+            single_wallet_private_key: None,
+            simulate: false,
+            mock: false,
+            active_rpcs: Some(vec!["solana".to_string()]),
+            active_dexes: Some(vec!["orca".to_string()]),
+        };
+
+        // Sui loads fine but must be refused before the Solana pipeline starts
+        let settings = settings::Settings::load(flags).unwrap();
+        let error = settings.validate().unwrap_err();
+        assert!(error.to_string().contains("Unsupported blockchain"));
+    }
 }
@@ -1,11 +1,27 @@
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use spl_pod::solana_pubkey::Pubkey;
-use std::any::Any;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use async_trait::async_trait;
+use uuid::Uuid;
+
+mod pool_state;
+pub use pool_state::{
+    BinLiquidity, ConcentratedLiquidityState, DlmmState, OrderBookLevel, OrderBookState,
+    PhoenixMarketState, PoolState, ReservesState, WeightedPoolState,
+};
 
 /// ArbitrageResult represents the result of the router's optimization process
+///
+/// Results are persisted (queue spool, replay recordings, tax records), so they carry the
+/// version of their shape. Records are upgraded to [`ArbitrageResult::VERSION`] as they
+/// are deserialized, and records from a newer version are rejected rather than misread.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct ArbitrageResult {
+    /// Version of the record's shape; records written before versioning are version 1
+    #[serde(default = "ArbitrageResult::unversioned")]
+    pub version: u32,
     /// Delta values (tender amounts) for each pool
     pub deltas: Vec<Vec<f64>>,
     /// Lambda values (receive amounts) for each pool
@@ -14,16 +30,312 @@ pub struct ArbitrageResult {
     pub a_matrices: Vec<Vec<Vec<f64>>>,
     /// Status of the optimization problem
     pub status: String,
+    /// Pool indices in the order their trades should execute so that tokens received
+    /// from earlier trades fund later ones (empty if no ordering was computed)
+    #[serde(default)]
+    pub execution_order: Vec<usize>,
+    /// Market value of each global token in a common numeraire (USD-like),
+    /// used to price the trade (empty if unknown)
+    #[serde(default)]
+    pub market_values: Vec<f64>,
+    /// Id tying together the logs, spans and records of this opportunity, from the
+    /// router's send through the relayer's execution (None until first enqueued)
+    #[serde(default)]
+    pub correlation_id: Option<Uuid>,
+    /// When the router sent the result to the relayer (None if it didn't come from the router)
+    #[serde(default)]
+    pub emitted_at: Option<SystemTime>,
+    /// When the relayer last put the result in its queue
+    #[serde(default)]
+    pub enqueued_at: Option<SystemTime>,
+    /// Trade fee the indexer parsed for each pool, by pool index (None where the pool's
+    /// fee wasn't indexed, empty if unknown)
+    #[serde(default)]
+    pub pool_fees: Vec<Option<PoolFee>>,
+    /// Indexed state of each pool, by pool index (empty if unknown), which some DEXes
+    /// need to build their swaps, e.g. a DLMM pair's active bin
+    #[serde(default)]
+    pub pool_states: Vec<PoolState>,
+}
+
+impl ArbitrageResult {
+    /// Current version of the result's shape
+    pub const VERSION: u32 = 2;
+
+    fn unversioned() -> u32 {
+        1
+    }
+
+    /// Upgrade a record of an older version to the current one
+    ///
+    /// Every field added since version 1 has a serde default, so version 1 records only
+    /// need restamping. Later shape changes add their conversion here.
+    fn migrate(mut self) -> Result<Self, String> {
+        match self.version {
+            1 => {
+                self.version = 2;
+                self.migrate()
+            },
+            Self::VERSION => Ok(self),
+            version => Err(format!(
+                "unsupported arbitrage result version {} (newest supported is {})",
+                version,
+                Self::VERSION
+            )),
+        }
+    }
+
+    /// The result's correlation id, assigning a new one if it has none yet
+    pub fn assign_correlation_id(&mut self) -> Uuid {
+        *self.correlation_id.get_or_insert_with(Uuid::new_v4)
+    }
+
+    /// Timestamp the result as sent by the router
+    pub fn mark_emitted(&mut self) {
+        self.emitted_at = Some(SystemTime::now());
+    }
+
+    /// Timestamp the result as put in the relayer's queue
+    pub fn mark_enqueued(&mut self) {
+        self.enqueued_at = Some(SystemTime::now());
+    }
+}
+
+impl Serialize for ArbitrageResult {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ArbitrageResult::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ArbitrageResult {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ArbitrageResult::deserialize(deserializer)?.migrate().map_err(de::Error::custom)
+    }
 }
 
 /// Define the PoolEntry type alias for shared use between router and indexer
-pub type PoolEntry = (Pubkey, Box<dyn Any + Send + Sync>);
+pub type PoolEntry = (Pubkey, IndexedPool);
+
+/// Denominator for fee rates quoted in hundredths of a basis point (Orca, Raydium CPMM/CLMM)
+pub const HUNDREDTHS_BPS_DENOMINATOR: u64 = 1_000_000;
+
+/// Trade fee of a pool as the fraction `numerator / denominator` of each input amount
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolFee {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+impl PoolFee {
+    pub const fn new(numerator: u64, denominator: u64) -> Self {
+        Self { numerator, denominator }
+    }
+
+    /// Fee from a rate in hundredths of a basis point, e.g. 3000 for 0.3%
+    pub const fn from_hundredths_bps(fee_rate: u64) -> Self {
+        Self::new(fee_rate, HUNDREDTHS_BPS_DENOMINATOR)
+    }
+
+    /// Fee as a fraction of the input (0.003 for 0.3%), zero if the denominator is unset
+    pub fn rate(&self) -> f64 {
+        if self.denominator == 0 {
+            return 0.0;
+        }
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    /// Fee rate rescaled to `denominator` and rounded, saturating at `u16::MAX`
+    ///
+    /// Quoters take `u16` rates in their own unit: 10_000 for basis points,
+    /// 1_000_000 for hundredths of a basis point.
+    pub fn scaled_rate(&self, denominator: u64) -> u16 {
+        (self.rate() * denominator as f64).round().min(u16::MAX as f64) as u16
+    }
+}
+
+/// Pool state handed to the router, tagged with the fee parsed from its on-chain accounts
+///
+/// The indexer builds this as the value of each [`PoolEntry`]. `fee` is `None` when the
+/// account holding the fee (e.g. a Raydium AMM config) hasn't been indexed yet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexedPool {
+    pub fee: Option<PoolFee>,
+    pub state: PoolState,
+}
+
+impl From<PoolState> for IndexedPool {
+    /// Pool state without an indexed fee, quoted with the fee rate in its state
+    fn from(state: PoolState) -> Self {
+        Self { fee: None, state }
+    }
+}
 
 /// Trait for cache implementations used by the router
 /// Allows retrieving pool entries for processing by the optimization engine
 #[async_trait::async_trait]
 pub trait PoolCache: Send + Sync {
     /// Get all entries from the cache as a vector
-    /// Returns a vector of (key, boxed state) pairs
+    /// Returns a vector of (key, indexed pool) pairs
     async fn get_all_entries_as_slice(&self) -> Vec<PoolEntry>;
+
+    /// Get only the entries updated within `max_age`
+    /// Caches that don't track update times treat every entry as fresh
+    async fn get_fresh_entries(&self, _max_age: Duration) -> Vec<PoolEntry> {
+        self.get_all_entries_as_slice().await
+    }
+
+    /// Wait until the cache holds enough state to solve over (e.g. after a startup backfill)
+    /// Caches without a warm-up phase are always ready
+    async fn wait_until_ready(&self) {}
+}
+
+/// Trait for looking up token mint decimals, used by the relayer to scale swap amounts
+#[async_trait::async_trait]
+pub trait MintDecimals: Send + Sync {
+    /// Decimals of `mint`, or `None` if the mint hasn't been indexed
+    async fn get_decimals(&self, mint: &Pubkey) -> Option<u8>;
+}
+
+/// Current time in milliseconds since the Unix epoch (0 if the clock is before the epoch)
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Shared per-subsystem status used to answer health and readiness probes
+///
+/// Each subsystem updates its own fields (the indexer on account updates, the router
+/// after each solve, the relayer with its queue depth, the wallets with explorer key
+/// availability) and the runtime reports a [`HealthSnapshot`] of the whole struct.
+#[derive(Debug)]
+pub struct HealthStatus {
+    streamer_connected: AtomicBool,
+    last_account_update_ms: AtomicU64,
+    nonce_pool_initialized: AtomicBool,
+    queue_depth: AtomicUsize,
+    available_explorer_keys: AtomicUsize,
+    last_router_solve_ms: AtomicU64,
+}
+
+impl HealthStatus {
+    pub const fn new() -> Self {
+        Self {
+            streamer_connected: AtomicBool::new(false),
+            last_account_update_ms: AtomicU64::new(0),
+            nonce_pool_initialized: AtomicBool::new(false),
+            queue_depth: AtomicUsize::new(0),
+            available_explorer_keys: AtomicUsize::new(0),
+            last_router_solve_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Record that the streamer delivered an account update; marks the streamer connected
+    pub fn record_account_update(&self) {
+        self.last_account_update_ms.store(now_millis(), Ordering::Relaxed);
+        self.streamer_connected.store(true, Ordering::Relaxed);
+    }
+
+    /// Mark the streamer connected or disconnected (e.g. when the Geyser stream drops)
+    pub fn set_streamer_connected(&self, connected: bool) {
+        self.streamer_connected.store(connected, Ordering::Relaxed);
+    }
+
+    pub fn set_nonce_pool_initialized(&self, initialized: bool) {
+        self.nonce_pool_initialized.store(initialized, Ordering::Relaxed);
+    }
+
+    pub fn set_queue_depth(&self, depth: usize) {
+        self.queue_depth.store(depth, Ordering::Relaxed);
+    }
+
+    pub fn set_available_explorer_keys(&self, count: usize) {
+        self.available_explorer_keys.store(count, Ordering::Relaxed);
+    }
+
+    /// Record that the router completed a solve
+    pub fn record_router_solve(&self) {
+        self.last_router_solve_ms.store(now_millis(), Ordering::Relaxed);
+    }
+
+    /// Ready once the streamer has received account data and is still connected
+    pub fn is_ready(&self) -> bool {
+        self.streamer_connected.load(Ordering::Relaxed)
+    }
+
+    pub fn snapshot(&self) -> HealthSnapshot {
+        let millis = |value: &AtomicU64| match value.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Some(ms),
+        };
+
+        HealthSnapshot {
+            ready: self.is_ready(),
+            streamer_connected: self.streamer_connected.load(Ordering::Relaxed),
+            last_account_update_ms: millis(&self.last_account_update_ms),
+            nonce_pool_initialized: self.nonce_pool_initialized.load(Ordering::Relaxed),
+            queue_depth: self.queue_depth.load(Ordering::Relaxed),
+            available_explorer_keys: self.available_explorer_keys.load(Ordering::Relaxed),
+            last_router_solve_ms: millis(&self.last_router_solve_ms),
+        }
+    }
+}
+
+impl Default for HealthStatus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Point-in-time view of [`HealthStatus`], serialized as the health endpoint's JSON body
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HealthSnapshot {
+    pub ready: bool,
+    pub streamer_connected: bool,
+    /// Unix time (ms) of the last account update, if any
+    pub last_account_update_ms: Option<u64>,
+    pub nonce_pool_initialized: bool,
+    pub queue_depth: usize,
+    pub available_explorer_keys: usize,
+    /// Unix time (ms) of the last completed router solve, if any
+    pub last_router_solve_ms: Option<u64>,
+}
+
+/// Process-wide health status updated by every subsystem
+pub static HEALTH_STATUS: HealthStatus = HealthStatus::new();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v1_record_deserializes_into_current_result() {
+        // As written to the queue spool and replay recordings before results were versioned
+        let v1 = r#"{
+            "deltas": [[1.0, 0.0], [0.0, 1.05]],
+            "lambdas": [[0.0, 1.1], [1.2, 0.0]],
+            "a_matrices": [],
+            "status": "optimal"
+        }"#;
+
+        let result: ArbitrageResult = serde_json::from_str(v1).unwrap();
+        assert_eq!(result.version, ArbitrageResult::VERSION);
+        assert_eq!(result.status, "optimal");
+        assert_eq!(result.lambdas[1], vec![1.2, 0.0]);
+        assert!(result.execution_order.is_empty());
+        assert!(result.correlation_id.is_none());
+
+        // The current version round trips
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.contains(r#""version":2"#));
+        assert_eq!(serde_json::from_str::<ArbitrageResult>(&json).unwrap().version, ArbitrageResult::VERSION);
+    }
+
+    #[test]
+    fn test_newer_version_is_rejected() {
+        let v3 = r#"{"version": 3, "deltas": [], "lambdas": [], "a_matrices": [], "status": "optimal"}"#;
+        let error = serde_json::from_str::<ArbitrageResult>(v3).unwrap_err();
+        assert!(error.to_string().contains("unsupported arbitrage result version 3"));
+    }
 }
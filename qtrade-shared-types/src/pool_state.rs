@@ -0,0 +1,206 @@
+//! Typed pool state handed from the indexer to the router
+//!
+//! Each variant names the DEX the state came from, so the router matches on it directly
+//! instead of downcasting. Fee rates are in the unit the DEX's quoter takes (see each
+//! field); a fee parsed by the indexer is carried by [`crate::IndexedPool`] and takes
+//! precedence over them.
+
+use serde::{Deserialize, Serialize};
+
+/// State of a concentrated-liquidity pool (Orca Whirlpool, Raydium CLMM)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConcentratedLiquidityState {
+    /// Square root of the price as a Q64.64 fixed-point number
+    pub sqrt_price: u128,
+    pub tick_current_index: i32,
+    /// Liquidity in range at the current tick
+    pub liquidity: u128,
+    /// Fee rate in hundredths of a basis point (3000 = 0.3%)
+    pub fee_rate: u16,
+    pub tick_spacing: u16,
+}
+
+/// State of a pool quoting from its two token reserves (Raydium AMM/CPMM, constant sum)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReservesState {
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    /// Fee rate in basis points (30 = 0.3%)
+    pub fee_rate: u16,
+}
+
+/// State of a weighted (Balancer-style) pool
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeightedPoolState {
+    pub balances: Vec<u64>,
+    /// Normalized weight of each balance, summing to 1
+    pub weights: Vec<f64>,
+    /// Fee rate in basis points (30 = 0.3%)
+    pub fee_rate: u16,
+}
+
+/// Token amounts held by one bin of a bin-based AMM (Meteora DLMM)
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BinLiquidity {
+    /// Bin id; the bin trades at a price of (1 + bin_step / 10_000)^bin_id
+    pub bin_id: i32,
+
+    /// Amount of token X (token A) in the bin
+    pub amount_x: u64,
+
+    /// Amount of token Y (token B) in the bin
+    pub amount_y: u64,
+}
+
+/// State of a Meteora DLMM pair
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DlmmState {
+    /// Id of the bin the pair currently trades in
+    pub active_id: i32,
+    /// Price step between bins in basis points
+    pub bin_step: u16,
+    /// Fee rate in hundredths of a basis point (3000 = 0.3%)
+    pub fee_rate: u16,
+    /// Bins holding liquidity in the pair's indexed bin arrays, by bin id
+    pub bins: Vec<BinLiquidity>,
+}
+
+/// Total size resting at one price of an order book
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OrderBookLevel {
+    /// Price in ticks
+    pub price_in_ticks: u64,
+
+    /// Size in base lots
+    pub size_in_base_lots: u64,
+}
+
+/// Order book of a market trading a base token (token A) for a quote token (token B)
+///
+/// Prices and sizes use the market's tick and lot units (as on Phoenix); the lot and
+/// tick sizes convert them to token atoms.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct OrderBookState {
+    /// Buy orders, best (highest) price first
+    pub bids: Vec<OrderBookLevel>,
+
+    /// Sell orders, best (lowest) price first
+    pub asks: Vec<OrderBookLevel>,
+
+    /// Base token atoms per base lot
+    pub base_lot_size: u64,
+
+    /// Quote token atoms per quote lot
+    pub quote_lot_size: u64,
+
+    /// Quote lots per base unit that one tick of price represents
+    pub tick_size_in_quote_lots_per_base_unit: u64,
+
+    /// Base lots in one whole base unit
+    pub base_lots_per_base_unit: u64,
+}
+
+impl OrderBookState {
+    /// Quote token atoms paid for one base lot at `price_in_ticks`
+    pub fn quote_atoms_per_base_lot(&self, price_in_ticks: u64) -> u128 {
+        if self.base_lots_per_base_unit == 0 {
+            return 0;
+        }
+        u128::from(price_in_ticks)
+            * u128::from(self.tick_size_in_quote_lots_per_base_unit)
+            * u128::from(self.quote_lot_size)
+            / u128::from(self.base_lots_per_base_unit)
+    }
+}
+
+/// State of a Phoenix market
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhoenixMarketState {
+    pub order_book: OrderBookState,
+    /// Taker fee in basis points (10 = 0.1%)
+    pub taker_fee_bps: u16,
+}
+
+/// Pool state of one pool, by DEX
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PoolState {
+    Orca(ConcentratedLiquidityState),
+    Raydium(ReservesState),
+    RaydiumCpmm(ReservesState),
+    RaydiumClmm(ConcentratedLiquidityState),
+    ConstantSum(ReservesState),
+    WeightedPool(WeightedPoolState),
+    MeteoraDlmm(DlmmState),
+    Phoenix(PhoenixMarketState),
+    /// State of a DEX the router doesn't model; never quoted
+    Unknown,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(state: &PoolState) -> PoolState {
+        let json = serde_json::to_string(state).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_every_variant_round_trips() {
+        let concentrated = ConcentratedLiquidityState {
+            // Above u64::MAX, so the full u128 range is exercised
+            sqrt_price: 1u128 << 70,
+            tick_current_index: -12_345,
+            liquidity: u128::MAX,
+            fee_rate: 3_000,
+            tick_spacing: 64,
+        };
+        let reserves = ReservesState { token_a_amount: 1_000_000_000, token_b_amount: u64::MAX, fee_rate: 25 };
+        let level = |price_in_ticks, size_in_base_lots| OrderBookLevel { price_in_ticks, size_in_base_lots };
+
+        let states = vec![
+            PoolState::Orca(concentrated.clone()),
+            PoolState::Raydium(reserves.clone()),
+            PoolState::RaydiumCpmm(reserves.clone()),
+            PoolState::RaydiumClmm(concentrated),
+            PoolState::ConstantSum(reserves),
+            PoolState::WeightedPool(WeightedPoolState {
+                balances: vec![1_000, 2_000, 3_000],
+                weights: vec![0.5, 0.25, 0.25],
+                fee_rate: 30,
+            }),
+            PoolState::MeteoraDlmm(DlmmState {
+                active_id: -3,
+                bin_step: 10,
+                fee_rate: 1_000,
+                bins: vec![BinLiquidity { bin_id: -3, amount_x: 1, amount_y: 2 }],
+            }),
+            PoolState::Phoenix(PhoenixMarketState {
+                order_book: OrderBookState {
+                    bids: vec![level(1_000, 5)],
+                    asks: vec![level(1_001, 7)],
+                    base_lot_size: 1_000,
+                    quote_lot_size: 1,
+                    tick_size_in_quote_lots_per_base_unit: 1_000,
+                    base_lots_per_base_unit: 1_000,
+                },
+                taker_fee_bps: 10,
+            }),
+            PoolState::Unknown,
+        ];
+
+        for state in &states {
+            assert_eq!(&round_trip(state), state);
+        }
+    }
+
+    #[test]
+    fn test_same_fields_different_dex_stay_distinct() {
+        // Raydium AMM and CPMM share a state struct; the variant keeps them apart
+        let reserves = ReservesState { token_a_amount: 1, token_b_amount: 2, fee_rate: 25 };
+        let cpmm = round_trip(&PoolState::RaydiumCpmm(reserves.clone()));
+
+        assert_eq!(cpmm, PoolState::RaydiumCpmm(reserves.clone()));
+        assert_ne!(cpmm, PoolState::Raydium(reserves));
+    }
+}
@@ -107,6 +107,11 @@ impl KeyInfo {
     pub fn status(&self) -> KeyStatus {
         self.status
     }
+
+    /// Get the balance the key is funded to, in lamports
+    pub fn target_balance(&self) -> u64 {
+        self.target_balance
+    }
 }
 
 /// A thread-safe pool of keys for a specific tier
@@ -174,6 +179,81 @@ impl KeyPool {
         None
     }
 
+    /// Get the first available keypair that satisfies `is_funded`
+    ///
+    /// Keys that fail the check are skipped but stay available, so the balancer
+    /// can top them up. The check runs without holding the pool locks.
+    pub fn get_keypair_where<F>(&self, is_funded: F) -> Option<(Pubkey, Keypair)>
+    where
+        F: Fn(&Pubkey) -> bool,
+    {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        // Snapshot the candidates so balance checks don't block the pool
+        let candidates: Vec<Pubkey> = match self.available_keys.lock() {
+            Ok(guard) => guard.iter().copied().collect(),
+            Err(_) => return None,
+        };
+
+        for pubkey in candidates {
+            if !is_funded(&pubkey) {
+                warn!("Skipping underfunded {:?} key {}", self.tier, pubkey);
+                continue;
+            }
+
+            let mut available_keys = match self.available_keys.lock() {
+                Ok(guard) => guard,
+                Err(_) => return None,
+            };
+
+            // The key may have been taken while we were checking balances
+            let position = match available_keys.iter().position(|k| *k == pubkey) {
+                Some(position) => position,
+                None => continue,
+            };
+
+            let mut keys = match self.keys.lock() {
+                Ok(guard) => guard,
+                Err(_) => return None,
+            };
+
+            if let Some(key_info) = keys.get_mut(&pubkey) {
+                if key_info.status == KeyStatus::Available {
+                    available_keys.remove(position);
+                    key_info.mark_in_use(now);
+                    return Some((pubkey, key_info.keypair_clone()));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Get the next keypair in round-robin order without taking it out of the pool
+    ///
+    /// The key stays available and moves to the back of the queue, so consecutive
+    /// calls cycle through every available key. Used in single wallet mode, where
+    /// keys are shared between in-flight transactions and never retired.
+    pub fn next_keypair_round_robin(&self) -> Option<(Pubkey, Keypair)> {
+        let mut available_keys = match self.available_keys.lock() {
+            Ok(guard) => guard,
+            Err(_) => return None,
+        };
+
+        let keys = match self.keys.lock() {
+            Ok(guard) => guard,
+            Err(_) => return None,
+        };
+
+        let pubkey = available_keys.pop_front()?;
+        available_keys.push_back(pubkey);
+
+        keys.get(&pubkey).map(|key_info| (pubkey, key_info.keypair_clone()))
+    }
+
     /// Return a keypair to the pool or mark it as used
     pub fn return_keypair(&self, pubkey: &Pubkey, retire: bool) -> Result<()> {
         let now = std::time::SystemTime::now()
@@ -209,6 +289,14 @@ impl KeyPool {
         !available_keys.is_empty()
     }
 
+    /// Available keys, in the order they would be handed out
+    pub fn available_pubkeys(&self) -> Vec<Pubkey> {
+        match self.available_keys.lock() {
+            Ok(guard) => guard.iter().copied().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
     /// Get all keys in the pool with their status
     pub fn get_all_keys(&self) -> Result<Vec<(Pubkey, KeyStatus)>> {
         let keys = self.keys.lock().map_err(|e| anyhow!("Failed to lock keys: {:?}", e))?;
@@ -302,6 +390,12 @@ impl KeyManager {
         }
     }
 
+    /// Use `rpc_client` for balances and transfers instead of one built from the RPC URL
+    pub fn with_rpc_client(mut self, rpc_client: Arc<RpcClient>) -> Self {
+        self.rpc_client = rpc_client;
+        self
+    }
+
     /// Get a reference to the HODL key pool
     pub fn hodl_pool(&self) -> &KeyPool {
         &self.hodl_pool
@@ -329,6 +423,37 @@ impl KeyManager {
         result
     }
 
+    /// Get an available Explorer keypair holding at least `min_lamports`
+    ///
+    /// The requirement never drops below the configured Explorer minimum balance.
+    /// Underfunded keys are skipped and left for the balancer to top up.
+    pub fn get_funded_explorer_keypair(&self, min_lamports: u64) -> Option<(Pubkey, Keypair)> {
+        let required = min_lamports.max(self.explorer_min_balance);
+
+        // One request for every candidate's balance, rather than one per key
+        let candidates = self.explorer_pool.available_pubkeys();
+        let balances = match fetch_balances(&self.rpc_client, &candidates) {
+            Ok(balances) => balances,
+            Err(e) => {
+                warn!("Failed to get balances of {} explorer keys: {}", candidates.len(), e);
+                HashMap::new()
+            }
+        };
+
+        let result = self.explorer_pool.get_keypair_where(|pubkey| {
+            balances.get(pubkey).is_some_and(|balance| *balance >= required)
+        });
+
+        if result.is_some() {
+            // Record metric for explorer key acquisition
+            crate::wallet_metrics::record_explorer_key_acquired();
+        } else {
+            warn!("No explorer key with at least {} lamports available", required);
+        }
+
+        result
+    }
+
     /// Return an Explorer keypair to the pool or retire it
     pub fn return_explorer_keypair(&self, pubkey: &Pubkey, retire: bool) -> Result<()> {
         let result = self.explorer_pool.return_keypair(pubkey, retire);
@@ -398,6 +523,54 @@ impl KeyManager {
         Ok(new_explorer_pubkeys)
     }
 
+    /// Top up available Explorer keys below the minimum balance to `lamports_per_key`
+    ///
+    /// Keys are funded from Bank keys. Returns the number of keys topped up.
+    pub async fn top_up_explorer_keys(&self, lamports_per_key: u64) -> Result<usize> {
+        let mut topped_up = 0;
+
+        let explorer_pubkeys: Vec<Pubkey> = self.explorer_pool.get_all_keys()?
+            .into_iter()
+            .filter(|(_, status)| *status == KeyStatus::Available)
+            .map(|(pubkey, _)| pubkey)
+            .collect();
+
+        for explorer_pubkey in explorer_pubkeys {
+            let balance = match self.rpc_client.get_balance(&explorer_pubkey) {
+                Ok(balance) => balance,
+                Err(e) => {
+                    warn!("Failed to check balance of explorer key {}: {}", explorer_pubkey, e);
+                    continue;
+                }
+            };
+            let Some(amount_to_transfer) = top_up_amount(balance, self.explorer_min_balance, lamports_per_key) else {
+                continue;
+            };
+
+            let (bank_pubkey, bank_keypair) = match self.bank_pool.get_keypair() {
+                Some(kp) => kp,
+                None => {
+                    warn!("No available bank keypairs to top up explorer keys");
+                    break;
+                }
+            };
+            let result = self.transfer_sol(&bank_keypair, &explorer_pubkey, amount_to_transfer).await;
+            self.bank_pool.return_keypair(&bank_pubkey, false)?;
+
+            match result {
+                Ok(_) => {
+                    info!("Topped up explorer key {} from {} to {} lamports", explorer_pubkey, balance, lamports_per_key);
+                    topped_up += 1;
+                },
+                Err(e) => {
+                    error!("Failed to top up explorer key {}: {}", explorer_pubkey, e);
+                }
+            }
+        }
+
+        Ok(topped_up)
+    }
+
     /// Fund Bank keys from HODL keys
     pub async fn fund_bank_keys(&self, lamports_per_key: u64) -> Result<usize> {
         let mut funded_count = 0;
@@ -626,7 +799,8 @@ impl KeyManager {
     /// This function performs the key maintenance tasks in our tiered key structure:
     /// 1. Clean up used Explorer keys and recover their funds to Bank keys
     /// 2. Fund Bank keys from HODL keys if their balance is low
-    /// 3. Create new Explorer keys and fund them from Bank keys if we need more
+    /// 3. Top up Explorer keys below their minimum balance from Bank keys
+    /// 4. Create new Explorer keys and fund them from Bank keys if we need more
     pub async fn balance(&self,
         min_explorer_keys: usize,
         explorer_keys_to_create: usize,
@@ -650,8 +824,15 @@ impl KeyManager {
             info!("No Bank keys needed funding at this time");
         }
 
-        // Step 3: Create new explorer keys if needed and fund them from Bank keys
-        info!("Step 3: Creating new Explorer keys if needed");
+        // Step 3: Top up explorer keys that fell below their minimum balance
+        info!("Step 3: Topping up underfunded Explorer keys");
+        let topped_up = self.top_up_explorer_keys(lamports_per_explorer).await?;
+        if topped_up > 0 {
+            info!("Topped up {} Explorer keys from Bank keys", topped_up);
+        }
+
+        // Step 4: Create new explorer keys if needed and fund them from Bank keys
+        info!("Step 4: Creating new Explorer keys if needed");
         let need_more = self.need_more_explorer_keys(min_explorer_keys);
         if need_more {
             info!("Need more Explorer keys, creating {} new ones", explorer_keys_to_create);
@@ -744,3 +925,187 @@ impl KeyManager {
         Ok(())
     }
 }
+
+/// Most accounts a single `getMultipleAccounts` request may ask for
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Lamport balances of `pubkeys`, fetched with as few `getMultipleAccounts` requests as possible
+///
+/// Accounts that don't exist have a balance of 0.
+pub fn fetch_balances(rpc_client: &RpcClient, pubkeys: &[Pubkey]) -> Result<HashMap<Pubkey, u64>> {
+    let mut balances = HashMap::with_capacity(pubkeys.len());
+    for chunk in pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = rpc_client.get_multiple_accounts(chunk)?;
+        for (pubkey, account) in chunk.iter().zip(accounts) {
+            balances.insert(*pubkey, account.map_or(0, |account| account.lamports));
+        }
+    }
+    Ok(balances)
+}
+
+/// Lamports that bring a key holding `balance` up to `target`, if it is below `min`
+pub fn top_up_amount(balance: u64, min: u64, target: u64) -> Option<u64> {
+    if balance >= min {
+        return None;
+    }
+    Some(target.saturating_sub(balance)).filter(|amount| *amount > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_keypair_where_skips_underfunded_keys() {
+        let underfunded = Keypair::new();
+        let funded = Keypair::new();
+        let underfunded_pubkey = underfunded.pubkey();
+        let funded_pubkey = funded.pubkey();
+
+        let balances: HashMap<Pubkey, u64> = [
+            (underfunded_pubkey, 1_000),
+            (funded_pubkey, 10_000_000),
+        ].into_iter().collect();
+
+        // The underfunded key is first in line
+        let pool = KeyPool::new(KeyTier::Explorer, vec![
+            (underfunded, 10_000_000),
+            (funded, 10_000_000),
+        ]);
+
+        let min_lamports = 5_000_000;
+        let (pubkey, _) = pool
+            .get_keypair_where(|pubkey| balances[pubkey] >= min_lamports)
+            .expect("A funded key should be selected");
+        assert_eq!(pubkey, funded_pubkey);
+
+        // The underfunded key stays available for the balancer to top up
+        let info = pool.get_key_info(&underfunded_pubkey).unwrap().unwrap();
+        assert_eq!(info.status(), KeyStatus::Available);
+
+        // No other funded key remains
+        assert!(pool.get_keypair_where(|pubkey| balances[pubkey] >= min_lamports).is_none());
+        assert!(pool.has_available_keys());
+    }
+
+    #[test]
+    fn test_funded_explorer_key_is_picked_from_one_balance_request() {
+        use solana_client::rpc_request::RpcRequest;
+
+        let underfunded = Keypair::new();
+        let funded = Keypair::new();
+        let funded_pubkey = funded.pubkey();
+
+        let account = |lamports: u64| serde_json::json!({
+            "lamports": lamports,
+            "data": ["", "base64"],
+            "owner": "11111111111111111111111111111111",
+            "executable": false,
+            "rentEpoch": 0,
+            "space": 0,
+        });
+        // Balances of the available keys, in pool order, answered to a single request
+        let mocks = [(
+            RpcRequest::GetMultipleAccounts,
+            serde_json::json!({ "context": { "slot": 1 }, "value": [account(1_000), account(10_000_000)] }),
+        )].into_iter().collect();
+        let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+        let key_manager = KeyManager::new(
+            vec![],
+            vec![],
+            vec![(underfunded, 10_000_000), (funded, 10_000_000)],
+            "http://127.0.0.1:1",
+            0,
+            0,
+            1_000_000,
+        )
+        .with_rpc_client(Arc::new(rpc_client));
+
+        let (pubkey, _) = key_manager
+            .get_funded_explorer_keypair(5_000_000)
+            .expect("The funded key should be selected");
+        assert_eq!(pubkey, funded_pubkey);
+    }
+
+    #[test]
+    fn test_next_keypair_round_robin_cycles_through_keys() {
+        let keypairs: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+        let pubkeys: Vec<Pubkey> = keypairs.iter().map(|keypair| keypair.pubkey()).collect();
+
+        let pool = KeyPool::new(
+            KeyTier::Explorer,
+            keypairs.into_iter().map(|keypair| (keypair, 10_000_000)).collect(),
+        );
+
+        let mut counts: HashMap<Pubkey, usize> = HashMap::new();
+        for i in 0..9 {
+            let (pubkey, keypair) = pool.next_keypair_round_robin().expect("Pool should never run dry");
+            assert_eq!(keypair.pubkey(), pubkey);
+            // Keys come back in the order they were loaded
+            assert_eq!(pubkey, pubkeys[i % 3]);
+            *counts.entry(pubkey).or_default() += 1;
+        }
+
+        // Every key was used equally and none was taken out of the pool
+        assert_eq!(counts.len(), 3);
+        assert!(counts.values().all(|count| *count == 3));
+        for pubkey in &pubkeys {
+            let info = pool.get_key_info(pubkey).unwrap().unwrap();
+            assert_eq!(info.status(), KeyStatus::Available);
+        }
+    }
+
+    #[test]
+    fn test_next_keypair_round_robin_single_key() {
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey();
+        let pool = KeyPool::new(KeyTier::Explorer, vec![(keypair, 10_000_000)]);
+
+        for _ in 0..3 {
+            let (next, _) = pool.next_keypair_round_robin().unwrap();
+            assert_eq!(next, pubkey);
+        }
+    }
+
+    #[test]
+    fn test_top_up_amount_reaches_target() {
+        assert_eq!(top_up_amount(1_000_000, 5_000_000, 12_000_000), Some(11_000_000));
+        // Keys at or above the minimum are left alone
+        assert_eq!(top_up_amount(5_000_000, 5_000_000, 12_000_000), None);
+        assert_eq!(top_up_amount(4_000_000, 5_000_000, 3_000_000), None);
+    }
+
+    #[tokio::test]
+    async fn test_explorer_keys_are_funded_to_configured_target() {
+        let funding = crate::FundingSettings {
+            explorer_target_lamports: 20_000_000,
+            explorer_fee_headroom_lamports: 5_000_000,
+            ..crate::FundingSettings::default()
+        };
+        let lamports_per_explorer = funding.explorer_funding_lamports();
+        let key_manager = KeyManager::new(
+            vec![],
+            vec![(Keypair::new(), funding.bank_target_lamports)],
+            vec![],
+            "http://127.0.0.1:1",
+            0,
+            funding.bank_min_lamports,
+            funding.explorer_required_lamports(),
+        )
+        .with_rpc_client(Arc::new(RpcClient::new_mock("succeeds".to_string())));
+
+        let created = key_manager.create_and_fund_explorer_keys(2, lamports_per_explorer).await.unwrap();
+
+        assert_eq!(created.len(), 2);
+        for pubkey in &created {
+            let info = key_manager.explorer_pool().get_key_info(pubkey).unwrap().unwrap();
+            assert_eq!(info.target_balance(), 25_000_000);
+        }
+        // The bank key went back to the pool after funding
+        assert!(key_manager.bank_pool().has_available_keys());
+
+        // The mock reports every key nearly empty, so each is topped up again
+        assert_eq!(key_manager.top_up_explorer_keys(lamports_per_explorer).await.unwrap(), 2);
+    }
+}
@@ -2,8 +2,9 @@ mod key_pool;
 pub mod metrics;
 
 use anyhow::Result;
-use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
 use std::env;
+use tokio::sync::Notify;
 use tracing::{info, warn, error};
 
 // Re-export metrics module
@@ -19,19 +20,154 @@ pub struct WalletSettings {
     /// Whether to use a single wallet instead of the multi-tiered wallet system
     pub single_wallet: bool,
 
-    /// Private key for the single wallet mode (if enabled)
-    pub single_wallet_private_key: Option<String>,
+    /// Private keys for the single wallet mode (if enabled)
+    ///
+    /// Signing rotates round-robin across every key so several transactions can be in
+    /// flight at once. A single entry behaves like one dedicated wallet; an empty list
+    /// falls back to a generated key.
+    pub single_wallet_private_keys: Vec<String>,
+
+    /// Balances the balancer funds keys to and requires of them
+    pub funding: FundingSettings,
+}
+
+/// Balances, in lamports, that keys are funded to and must keep
+///
+/// Explorer keys pay the priority fees and tips of the transactions they sign, so on
+/// top of their target and minimum they are funded with `explorer_fee_headroom_lamports`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FundingSettings {
+    /// Balance new and topped up explorer keys are funded to, before the fee headroom
+    pub explorer_target_lamports: u64,
+    /// Balance below which an explorer key is skipped and topped up, before the fee headroom
+    pub explorer_min_lamports: u64,
+    /// Extra balance every explorer key keeps for priority fees and tips
+    pub explorer_fee_headroom_lamports: u64,
+    /// Balance bank keys are funded to from HODL keys
+    pub bank_target_lamports: u64,
+    /// Balance below which a bank key isn't used to fund explorer keys
+    pub bank_min_lamports: u64,
+}
+
+impl FundingSettings {
+    pub const DEFAULT: Self = Self {
+        explorer_target_lamports: 10_000_000, // 0.01 SOL
+        explorer_min_lamports: 5_000_000,     // 0.005 SOL
+        explorer_fee_headroom_lamports: 0,
+        bank_target_lamports: 100_000_000,    // 0.1 SOL
+        bank_min_lamports: 50_000_000,        // 0.05 SOL
+    };
+
+    /// Balance explorer keys are funded to, fee headroom included
+    pub fn explorer_funding_lamports(&self) -> u64 {
+        self.explorer_target_lamports.saturating_add(self.explorer_fee_headroom_lamports)
+    }
+
+    /// Balance an explorer key needs to be handed out, fee headroom included
+    pub fn explorer_required_lamports(&self) -> u64 {
+        self.explorer_min_lamports.saturating_add(self.explorer_fee_headroom_lamports)
+    }
+
+    /// Load the balances from the environment, keeping the defaults for unset variables
+    ///
+    /// Targets never fall below their minimums.
+    pub fn from_env() -> Self {
+        fn lamports(var: &str, default: u64) -> u64 {
+            env::var(var).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+        }
+
+        let explorer_min_lamports = lamports("QTRADE_EXPLORER_MIN_LAMPORTS", Self::DEFAULT.explorer_min_lamports);
+        let bank_min_lamports = lamports("QTRADE_BANK_MIN_LAMPORTS", Self::DEFAULT.bank_min_lamports);
+        Self {
+            explorer_target_lamports: lamports("QTRADE_EXPLORER_TARGET_LAMPORTS", Self::DEFAULT.explorer_target_lamports)
+                .max(explorer_min_lamports),
+            explorer_min_lamports,
+            explorer_fee_headroom_lamports: lamports(
+                "QTRADE_EXPLORER_FEE_HEADROOM_LAMPORTS",
+                Self::DEFAULT.explorer_fee_headroom_lamports,
+            ),
+            bank_target_lamports: lamports("QTRADE_BANK_TARGET_LAMPORTS", Self::DEFAULT.bank_target_lamports)
+                .max(bank_min_lamports),
+            bank_min_lamports,
+        }
+    }
+}
+
+impl Default for FundingSettings {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Policy controlling whether an explorer key is retired after use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RetirementPolicy {
+    /// Retire every key after a single use (best for privacy)
+    #[default]
+    Always,
+    /// Retire keys only when their transaction landed; otherwise return them to the pool
+    OnSuccessOnly,
+    /// Never retire keys; always return them to the pool
+    Never,
+}
+
+impl RetirementPolicy {
+    /// Whether a key should be retired given whether its transaction landed
+    pub fn should_retire(&self, landed: bool) -> bool {
+        match self {
+            RetirementPolicy::Always => true,
+            RetirementPolicy::OnSuccessOnly => landed,
+            RetirementPolicy::Never => false,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetirementPolicy::Always => "always",
+            RetirementPolicy::OnSuccessOnly => "on_success_only",
+            RetirementPolicy::Never => "never",
+        }
+    }
+
+}
+
+impl std::str::FromStr for RetirementPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "always" => Ok(RetirementPolicy::Always),
+            "on_success_only" => Ok(RetirementPolicy::OnSuccessOnly),
+            "never" => Ok(RetirementPolicy::Never),
+            other => Err(format!(
+                "Unknown key retirement policy: {} (expected always, on_success_only or never)",
+                other
+            )),
+        }
+    }
 }
 
 // Constants for key balancing
 const MIN_EXPLORER_KEYS: usize = 5;
 const EXPLORER_KEYS_TO_CREATE: usize = 3;
-const LAMPORTS_PER_EXPLORER: u64 = 10_000_000; // 0.01 SOL
-const LAMPORTS_PER_BANK: u64 = 100_000_000;    // 0.1 SOL
+
+// Key balances, set from the wallet settings before the key manager is created
+static mut FUNDING: FundingSettings = FundingSettings::DEFAULT;
 
 // Our global key manager instance
 static mut KEY_MANAGER: Option<KeyManager> = None;
 
+// Wakes run_wallets to balance the key pools before its next scheduled run
+static BALANCE_TRIGGER: Notify = Notify::const_new();
+
+/// Request a balancer run now instead of waiting for the next scheduled one
+///
+/// Call this when no funded explorer key is available, e.g. mid-burst. Requests made
+/// while a run is in progress are coalesced into a single follow-up run.
+pub fn trigger_balance() {
+    BALANCE_TRIGGER.notify_one();
+}
+
 /// Run the wallet management service
 ///
 /// This function initializes the wallet system and then periodically manages wallet balances.
@@ -75,8 +211,11 @@ pub async fn run_wallets(settings: WalletSettings) -> Result<()> {
             error!("Error running wallet management: {:?}", e);
         }
 
-        // Wait for specified duration before running the check again
-        sleep(CHECK_INTERVAL).await;
+        // Wait for specified duration, or an on-demand trigger, before running the check again
+        tokio::select! {
+            _ = sleep(CHECK_INTERVAL) => {}
+            _ = BALANCE_TRIGGER.notified() => info!("Wallet balancer triggered on demand"),
+        }
     }
 }
 
@@ -84,24 +223,27 @@ pub async fn run_wallets(settings: WalletSettings) -> Result<()> {
 pub fn init() -> Result<()> {
     // Get RPC URL from environment
     let rpc_url = env::var("SOLANA_RPC_URL").unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string());
+    let funding = unsafe { FUNDING };
+    let lamports_per_explorer = funding.explorer_funding_lamports();
 
-    // Load HODL keys from environment (comma-separated private keys)
-    let hodl_keys_str = env::var("HODL_KEYS").unwrap_or_else(|_| "".to_string());
-    let hodl_keys = load_keypairs_from_str(&hodl_keys_str, 1_000_000_000); // 1 SOL target balance
+    // Load HODL keys from environment (comma-separated private keys and keypair file paths)
+    let hodl_keys = load_keypairs_from_env("HODL_KEYS", "HODL_KEY_PATHS", 1_000_000_000); // 1 SOL target balance
 
     // Load bank keys from environment
-    let bank_keys_str = env::var("BANK_KEYS").unwrap_or_else(|_| "".to_string());
-    let bank_keys = load_keypairs_from_str(&bank_keys_str, LAMPORTS_PER_BANK);
+    let bank_keys = load_keypairs_from_env("BANK_KEYS", "BANK_KEY_PATHS", funding.bank_target_lamports);
 
     // Load explorer keys from environment or create new ones if none provided
     let explorer_keys_str = env::var("EXPLORER_KEYS").unwrap_or_else(|_| "".to_string());
-    let explorer_keys = if explorer_keys_str.is_empty() {
+    let explorer_key_paths_str = env::var("EXPLORER_KEY_PATHS").unwrap_or_else(|_| "".to_string());
+    let explorer_keys = if explorer_keys_str.is_empty() && explorer_key_paths_str.is_empty() {
         // Create some initial explorer keys if none provided
         (0..MIN_EXPLORER_KEYS).map(|_| {
-            (Keypair::new(), LAMPORTS_PER_EXPLORER)
+            (Keypair::new(), lamports_per_explorer)
         }).collect()
     } else {
-        load_keypairs_from_str(&explorer_keys_str, LAMPORTS_PER_EXPLORER)
+        let mut keys = load_keypairs_from_str(&explorer_keys_str, lamports_per_explorer);
+        keys.extend(load_keypairs_from_paths(&explorer_key_paths_str, lamports_per_explorer));
+        keys
     };
 
     // Log key counts before creating the key manager
@@ -116,8 +258,8 @@ pub fn init() -> Result<()> {
         explorer_keys,
         &rpc_url,
         500_000_000,  // 0.5 SOL min for HODL
-        50_000_000,   // 0.05 SOL min for Bank
-        5_000_000,    // 0.005 SOL min for Explorer
+        funding.bank_min_lamports,
+        funding.explorer_required_lamports(),
     );
 
     // Store the key manager in our global static
@@ -161,6 +303,36 @@ fn load_keypairs_from_str(keys_str: &str, target_balance: u64) -> Vec<(Keypair,
         .collect()
 }
 
+/// Helper function to load keypairs from comma-separated Solana CLI keypair JSON files
+///
+/// Each file holds the standard 64-byte JSON array written by `solana-keygen`.
+/// Files that are missing or malformed are skipped with a warning.
+pub fn load_keypairs_from_paths(paths_str: &str, target_balance: u64) -> Vec<(Keypair, u64)> {
+    paths_str.split(',')
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .filter_map(|path| {
+            match read_keypair_file(path) {
+                Ok(keypair) => Some((keypair, target_balance)),
+                Err(e) => {
+                    warn!("Failed to read keypair file {}: {}", path, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Load keypairs from a base58 key variable and a keypair file path variable, merged
+fn load_keypairs_from_env(keys_var: &str, paths_var: &str, target_balance: u64) -> Vec<(Keypair, u64)> {
+    let keys_str = env::var(keys_var).unwrap_or_else(|_| "".to_string());
+    let paths_str = env::var(paths_var).unwrap_or_else(|_| "".to_string());
+
+    let mut keys = load_keypairs_from_str(&keys_str, target_balance);
+    keys.extend(load_keypairs_from_paths(&paths_str, target_balance));
+    keys
+}
+
 /// Get an instance of the key manager
 pub fn get_key_manager() -> Option<KeyManager> {
     unsafe {
@@ -184,11 +356,12 @@ pub async fn balancer() -> Result<()> {
             info!("Running key pool balancer...");
 
             // Run the balancer
+            let funding = unsafe { FUNDING };
             key_manager.balance(
                 MIN_EXPLORER_KEYS,
                 EXPLORER_KEYS_TO_CREATE,
-                LAMPORTS_PER_EXPLORER,
-                LAMPORTS_PER_BANK
+                funding.explorer_funding_lamports(),
+                funding.bank_target_lamports
             ).await?;
 
             // After balancing, update metrics about pool sizes
@@ -210,6 +383,9 @@ pub async fn balancer() -> Result<()> {
             // Record metrics to OpenTelemetry
             wallet_metrics::otel::record_otel_metrics();
 
+            // Report explorer key availability for health checks
+            qtrade_shared_types::HEALTH_STATUS.set_available_explorer_keys(explorer_available as usize);
+
             info!("Key pool balancing complete");
             Ok(())
         },
@@ -226,11 +402,11 @@ pub fn get_explorer_keypair() -> Option<(solana_sdk::pubkey::Pubkey, Keypair)> {
     if unsafe { SINGLE_WALLET_MODE } {
         if let Some(key_manager) = get_key_manager() {
             // In single wallet mode, we don't care about normal explorer key management
-            // We'll always return the same key
-            let result = key_manager.get_explorer_keypair();
-            if result.is_some() {
+            // We rotate through the configured wallets without ever retiring them
+            let result = key_manager.explorer_pool().next_keypair_round_robin();
+            if let Some((pubkey, _)) = &result {
                 wallet_metrics::record_explorer_key_acquired();
-                info!("Single wallet mode: returning the dedicated signing wallet");
+                info!("Single wallet mode: returning signing wallet {}", pubkey);
             }
             return result;
         }
@@ -253,6 +429,25 @@ pub fn get_explorer_keypair() -> Option<(solana_sdk::pubkey::Pubkey, Keypair)> {
     }
 }
 
+/// Get an explorer keypair holding at least `min_lamports` for transaction fees
+///
+/// Underfunded explorer keys are skipped rather than handed out.
+pub fn get_funded_explorer_keypair(min_lamports: u64) -> Option<(solana_sdk::pubkey::Pubkey, Keypair)> {
+    // Special handling for single wallet mode
+    if unsafe { SINGLE_WALLET_MODE } {
+        // There is only one key to choose from, so hand it out regardless of balance
+        return get_explorer_keypair();
+    }
+
+    match get_key_manager() {
+        Some(key_manager) => key_manager.get_funded_explorer_keypair(min_lamports),
+        None => {
+            error!("Key manager not initialized");
+            None
+        }
+    }
+}
+
 /// Return an explorer keypair to the pool or mark it as used
 pub fn return_explorer_keypair(pubkey: &solana_sdk::pubkey::Pubkey, retire: bool) -> Result<()> {
     // In single wallet mode, we don't actually retire keys
@@ -279,24 +474,30 @@ pub fn return_explorer_keypair(pubkey: &solana_sdk::pubkey::Pubkey, retire: bool
     }
 }
 
-/// Initialize a single wallet with a provided private key
-fn init_single_wallet(private_key: &str) -> Result<()> {
+/// Initialize single wallet mode with the provided private keys
+fn init_single_wallet(private_keys: &[String]) -> Result<()> {
     // Set the global flag for single wallet mode
     unsafe { SINGLE_WALLET_MODE = true; }
 
-    // Decode the private key from base58
-    let key_bytes = bs58::decode(private_key.trim())
-        .into_vec()
-        .map_err(|e| anyhow::anyhow!("Failed to decode base58 private key: {:?}", e))?;
+    let mut explorer_keys = Vec::with_capacity(private_keys.len());
+    for private_key in private_keys {
+        // Decode the private key from base58
+        let key_bytes = bs58::decode(private_key.trim())
+            .into_vec()
+            .map_err(|e| anyhow::anyhow!("Failed to decode base58 private key: {:?}", e))?;
 
-    // Create a keypair from the bytes
-    let keypair = Keypair::from_bytes(&key_bytes)
-        .map_err(|e| anyhow::anyhow!("Failed to create keypair from bytes: {:?}", e))?;
+        // Create a keypair from the bytes
+        let keypair = Keypair::from_bytes(&key_bytes)
+            .map_err(|e| anyhow::anyhow!("Failed to create keypair from bytes: {:?}", e))?;
 
-    // Get the public key for logging
-    let pubkey = keypair.pubkey();
+        info!("Initialized single wallet with public key: {}", keypair.pubkey());
+
+        explorer_keys.push((keypair, unsafe { FUNDING }.explorer_funding_lamports()));
+    }
 
-    info!("Initialized single wallet with public key: {}", pubkey);
+    if explorer_keys.len() > 1 {
+        info!("Single wallet mode: rotating across {} signing wallets", explorer_keys.len());
+    }
 
     // Create our single wallet key manager
     let key_manager = KeyManager::new(
@@ -304,8 +505,8 @@ fn init_single_wallet(private_key: &str) -> Result<()> {
         vec![],
         // Bank keys (empty for single wallet mode)
         vec![],
-        // Explorer keys - just our single wallet
-        vec![(keypair, LAMPORTS_PER_EXPLORER)],
+        // Explorer keys - just our single wallets
+        explorer_keys,
         // RPC URL
         &env::var("SOLANA_RPC_URL").unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string()),
         // Min balances - don't matter for single wallet mode
@@ -342,7 +543,7 @@ fn init_single_wallet_with_generated_key() -> Result<()> {
         // Bank keys (empty for single wallet mode)
         vec![],
         // Explorer keys - just our single wallet
-        vec![(keypair, LAMPORTS_PER_EXPLORER)],
+        vec![(keypair, unsafe { FUNDING }.explorer_funding_lamports())],
         // RPC URL
         &env::var("SOLANA_RPC_URL").unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string()),
         // Min balances - don't matter for single wallet mode
@@ -380,13 +581,21 @@ async fn initialize_wallet_system(settings: &WalletSettings) -> Result<()> {
     let span_name = format!("{}::initialize_wallet_system", "wallets");
 
     tracer.in_span(span_name, |_cx| async move {
+        unsafe { FUNDING = settings.funding; }
+        info!(
+            "Funding explorer keys to {} lamports ({} of fee headroom), bank keys to {} lamports",
+            settings.funding.explorer_funding_lamports(),
+            settings.funding.explorer_fee_headroom_lamports,
+            settings.funding.bank_target_lamports
+        );
+
         // Check for single wallet mode
         if settings.single_wallet {
             info!("Initializing wallet system in SINGLE WALLET MODE");
 
-            if let Some(private_key) = &settings.single_wallet_private_key {
-                // Initialize a single wallet with the provided private key
-                if let Err(e) = init_single_wallet(private_key) {
+            if !settings.single_wallet_private_keys.is_empty() {
+                // Initialize single wallet mode with the provided private keys
+                if let Err(e) = init_single_wallet(&settings.single_wallet_private_keys) {
                     error!("Failed to initialize single wallet: {:?}", e);
                     return Err(anyhow::anyhow!("Failed to initialize single wallet: {:?}", e));
                 }
@@ -412,3 +621,80 @@ async fn initialize_wallet_system(settings: &WalletSettings) -> Result<()> {
     }).await
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retirement_policy_always() {
+        let policy = RetirementPolicy::Always;
+        assert!(policy.should_retire(true));
+        assert!(policy.should_retire(false));
+    }
+
+    #[test]
+    fn test_retirement_policy_on_success_only() {
+        let policy = RetirementPolicy::OnSuccessOnly;
+        assert!(policy.should_retire(true));
+        assert!(!policy.should_retire(false));
+    }
+
+    #[test]
+    fn test_retirement_policy_never() {
+        let policy = RetirementPolicy::Never;
+        assert!(!policy.should_retire(true));
+        assert!(!policy.should_retire(false));
+    }
+
+    #[test]
+    fn test_retirement_policy_parsing() {
+        assert_eq!(RetirementPolicy::default(), RetirementPolicy::Always);
+        for policy in [RetirementPolicy::Always, RetirementPolicy::OnSuccessOnly, RetirementPolicy::Never] {
+            assert_eq!(policy.as_str().parse::<RetirementPolicy>(), Ok(policy));
+        }
+        assert!("sometimes".parse::<RetirementPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_load_keypairs_from_json_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let keypair = Keypair::new();
+
+        // The 64-byte array format written by solana-keygen
+        let key_path = dir.path().join("explorer.json");
+        let bytes: Vec<String> = keypair.to_bytes().iter().map(|byte| byte.to_string()).collect();
+        std::fs::write(&key_path, format!("[{}]", bytes.join(","))).unwrap();
+
+        let malformed_path = dir.path().join("malformed.json");
+        std::fs::write(&malformed_path, "[1, 2, 3]").unwrap();
+        let missing_path = dir.path().join("missing.json");
+
+        let paths = format!(
+            "{}, {},{}",
+            key_path.display(),
+            malformed_path.display(),
+            missing_path.display()
+        );
+        let target_balance = FundingSettings::DEFAULT.explorer_target_lamports;
+        let keys = load_keypairs_from_paths(&paths, target_balance);
+
+        // Only the well-formed file loads
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].0.pubkey(), keypair.pubkey());
+        assert_eq!(keys[0].1, target_balance);
+        assert!(load_keypairs_from_paths("", target_balance).is_empty());
+    }
+
+    #[test]
+    fn test_fee_headroom_is_added_to_explorer_balances() {
+        let funding = FundingSettings {
+            explorer_target_lamports: 20_000_000,
+            explorer_min_lamports: 8_000_000,
+            explorer_fee_headroom_lamports: 2_500_000,
+            ..FundingSettings::default()
+        };
+        assert_eq!(funding.explorer_funding_lamports(), 22_500_000);
+        assert_eq!(funding.explorer_required_lamports(), 10_500_000);
+        assert_eq!(FundingSettings::default().explorer_funding_lamports(), 10_000_000);
+    }
+}
@@ -9,6 +9,7 @@ pub mod otel;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use lazy_static::lazy_static;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
 // Create a global metric tracker for the qtrade-wallets module
 pub const QTRADE_WALLETS_METER_NAME: &str = "qtrade-wallets";
 
@@ -24,8 +25,8 @@ pub struct WalletMetrics {
     pub explorer_keys_funds_recovered: Arc<AtomicU64>,
     /// Counter for bank keys funded
     pub bank_keys_funded: Arc<AtomicU64>,
-    /// Counter for total SOL recovered from explorer keys (lamports * 10^-9)
-    pub total_sol_recovered: Arc<AtomicU64>,
+    /// Counter for total lamports recovered from explorer keys
+    pub total_lamports_recovered: Arc<AtomicU64>,
 }
 
 lazy_static! {
@@ -37,7 +38,7 @@ lazy_static! {
             explorer_keys_created: Arc::new(AtomicU64::new(0)),
             explorer_keys_funds_recovered: Arc::new(AtomicU64::new(0)),
             bank_keys_funded: Arc::new(AtomicU64::new(0)),
-            total_sol_recovered: Arc::new(AtomicU64::new(0)),
+            total_lamports_recovered: Arc::new(AtomicU64::new(0)),
         }
     };
 }
@@ -61,12 +62,8 @@ pub fn record_explorer_keys_created(count: u64) {
 pub fn record_explorer_keys_funds_recovered(count: u64, total_lamports: u64) {
     WALLET_METRICS.explorer_keys_funds_recovered.fetch_add(count, Ordering::SeqCst);
 
-    // Convert lamports to SOL with fixed precision (1 SOL = 10^9 lamports)
-    // Storing as integer with 3 decimal precision (1 SOL = 1000 units)
-    let sol = (total_lamports as f64) / 1_000_000.0; // Represents SOL with 3 decimal points
-    let sol_integer = sol as u64;
-
-    WALLET_METRICS.total_sol_recovered.fetch_add(sol_integer, Ordering::SeqCst);
+    // Keep exact lamports; converting each recovery to SOL would truncate small amounts
+    WALLET_METRICS.total_lamports_recovered.fetch_add(total_lamports, Ordering::SeqCst);
 }
 
 /// Record metrics for bank keys being funded
@@ -102,7 +99,23 @@ pub fn init() {
 
 /// Get the total number of SOL recovered from explorer keys
 pub fn get_total_sol_recovered() -> f64 {
-    let total = WALLET_METRICS.total_sol_recovered.load(Ordering::SeqCst);
-    // Convert back to SOL with 3 decimal precision
-    total as f64 / 1000.0
+    let total = WALLET_METRICS.total_lamports_recovered.load(Ordering::SeqCst);
+    total as f64 / LAMPORTS_PER_SOL as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_funds_recovered_reports_sol() {
+        let before = get_total_sol_recovered();
+        record_explorer_keys_funds_recovered(1, 1_000_000_000);
+        assert!((get_total_sol_recovered() - before - 1.0).abs() < 1e-9);
+
+        // Recoveries below 0.001 SOL are no longer truncated away
+        let before = get_total_sol_recovered();
+        record_explorer_keys_funds_recovered(1, 5_000);
+        assert!((get_total_sol_recovered() - before - 0.000_005).abs() < 1e-12);
+    }
 }
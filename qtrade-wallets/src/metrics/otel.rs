@@ -53,10 +53,10 @@ lazy_static! {
     };
 
     // SOL metrics
-    static ref SOL_RECOVERED_COUNTER: Counter<u64> = {
+    static ref SOL_RECOVERED_COUNTER: Counter<f64> = {
         QTRADE_WALLETS_METER
-            .u64_counter("qtrade.wallets.sol_recovered")
-            .with_description("Total SOL recovered from explorer keys (lamports * 10^-9)")
+            .f64_counter("qtrade.wallets.sol_recovered")
+            .with_description("Total SOL recovered from explorer keys")
             .build()
     };
 
@@ -103,8 +103,7 @@ pub fn record_otel_metrics() {
     BANK_KEYS_FUNDED_COUNTER.add(funded, &[]);
 
     // SOL metrics
-    let sol_recovered = WALLET_METRICS.total_sol_recovered.load(Ordering::SeqCst);
-    SOL_RECOVERED_COUNTER.add(sol_recovered, &[]);
+    SOL_RECOVERED_COUNTER.add(super::get_total_sol_recovered(), &[]);
 }
 
 /// Record metrics for key pool sizes in OpenTelemetry